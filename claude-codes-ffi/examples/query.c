@@ -0,0 +1,45 @@
+/*
+ * Ask one question and print every reply.
+ *
+ *   cargo build -p claude-codes-ffi
+ *   cc claude-codes-ffi/examples/query.c -Iclaude-codes-ffi/include \
+ *      -Ltarget/debug -lclaude_codes_ffi -o query
+ *   LD_LIBRARY_PATH=target/debug ./query "What is 2 + 2?"
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "claude_codes.h"
+
+int main(int argc, char **argv) {
+    const char *question = argc > 1 ? argv[1] : "What is 2 + 2?";
+
+    ClaudeClient *client = claude_client_spawn("{\"model\": \"sonnet\"}");
+    if (client == NULL) {
+        fprintf(stderr, "spawn failed: %s\n", claude_last_error());
+        return 1;
+    }
+
+    if (claude_client_query(client, question) != CLAUDE_OK) {
+        fprintf(stderr, "query failed: %s\n", claude_last_error());
+        claude_client_shutdown(client);
+        return 1;
+    }
+
+    for (;;) {
+        char *message = claude_client_receive(client);
+        if (message == NULL) {
+            fprintf(stderr, "receive failed: %s\n", claude_last_error());
+            break;
+        }
+        printf("%s\n", message);
+        int done = strstr(message, "\"type\":\"result\"") != NULL;
+        claude_string_free(message);
+        if (done) {
+            break;
+        }
+    }
+
+    return claude_client_shutdown(client) == CLAUDE_OK ? 0 : 1;
+}
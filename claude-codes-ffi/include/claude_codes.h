@@ -0,0 +1,74 @@
+/*
+ * C ABI for claude-codes.
+ *
+ * Messages cross the boundary as stream-json strings. Functions returning
+ * int32_t return CLAUDE_OK or CLAUDE_ERROR; pointer-returning functions
+ * return NULL on failure. After a failure, claude_last_error() describes it.
+ *
+ * Keep in sync with claude-codes-ffi/src/lib.rs.
+ */
+
+#ifndef CLAUDE_CODES_H
+#define CLAUDE_CODES_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define CLAUDE_OK 0
+#define CLAUDE_ERROR -1
+
+/* Opaque handle to a running Claude CLI session. */
+typedef struct ClaudeClient ClaudeClient;
+
+/*
+ * Spawn the Claude CLI. options_json is NULL or a JSON object with any of:
+ * command, model, fallback_model, permission_mode, allowed_tools,
+ * disallowed_tools, append_system_prompt, add_directories, mcp_config,
+ * settings, resume, permission_prompt_tool, env, tool_approval.
+ */
+ClaudeClient *claude_client_spawn(const char *options_json);
+
+/* Send one stream-json input message. */
+int32_t claude_client_send(ClaudeClient *client, const char *input_json);
+
+/* Send text as a user message; read replies with claude_client_receive. */
+int32_t claude_client_query(ClaudeClient *client, const char *text);
+
+/* Block for the next message. Free the result with claude_string_free. */
+char *claude_client_receive(ClaudeClient *client);
+
+/*
+ * Approve a tool permission request (the control_request JSON as received).
+ * updated_input_json is NULL or a JSON object replacing the tool input.
+ */
+int32_t claude_client_allow_tool(ClaudeClient *client,
+                                 const char *request_json,
+                                 const char *updated_input_json);
+
+/* Deny a tool permission request; interrupt also ends the turn. */
+int32_t claude_client_deny_tool(ClaudeClient *client,
+                                const char *request_json,
+                                const char *reason,
+                                bool interrupt);
+
+/* Ask the CLI to stop the current response. */
+int32_t claude_client_interrupt(ClaudeClient *client);
+
+/* Shut the CLI down and free the handle. NULL is a no-op. */
+int32_t claude_client_shutdown(ClaudeClient *client);
+
+/* Last failure on this thread, or NULL. Valid until the next call. */
+const char *claude_last_error(void);
+
+/* Free a string returned by this library. NULL is a no-op. */
+void claude_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CLAUDE_CODES_H */
@@ -0,0 +1,391 @@
+//! C ABI for [`claude_codes`].
+//!
+//! Lets C, C++, Swift, C#, or anything else with a C FFI drive a Claude Code
+//! session. Messages cross the boundary as stream-json strings, so callers
+//! parse them with whatever JSON library they already use.
+//!
+//! The header lives at `include/claude_codes.h`.
+//!
+//! # Conventions
+//!
+//! - Functions returning `int32_t` return [`CLAUDE_OK`] on success and
+//!   [`CLAUDE_ERROR`] on failure; pointer-returning functions return NULL.
+//! - After a failure, [`claude_last_error`] describes it. The message is
+//!   per-thread and lives until the next call on that thread.
+//! - Strings returned by this library must be released with
+//!   [`claude_string_free`].
+//! - A `ClaudeClient` may be moved between threads but must not be used from
+//!   two threads at once.
+//! - Panics never unwind into the caller; they are reported as errors.
+
+use claude_codes::io::ControlRequestPayload;
+use claude_codes::{BlockingClient, ClaudeInput, ClaudeOutput, ControlResponse};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use uuid::Uuid;
+
+mod options;
+
+use options::SpawnOptions;
+
+/// Status code returned on success.
+pub const CLAUDE_OK: i32 = 0;
+
+/// Status code returned on failure; see [`claude_last_error`].
+pub const CLAUDE_ERROR: i32 = -1;
+
+/// Opaque handle to a running Claude CLI session.
+pub struct ClaudeClient {
+    inner: BlockingClient,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).ok();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+/// Run `f`, converting errors and panics into `fallback` plus a last-error
+/// message.
+fn guard<T>(fallback: T, f: impl FnOnce() -> Result<T, String>) -> T {
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => value,
+        Ok(Err(message)) => {
+            set_last_error(message);
+            fallback
+        }
+        Err(_) => {
+            set_last_error("panic inside claude-codes-ffi".to_string());
+            fallback
+        }
+    }
+}
+
+fn status(f: impl FnOnce() -> Result<(), String>) -> i32 {
+    guard(CLAUDE_ERROR, || f().map(|()| CLAUDE_OK))
+}
+
+/// Borrow a required C string argument.
+///
+/// # Safety
+///
+/// `ptr` must be NULL or a valid NUL-terminated string.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("{} is NULL", name));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| format!("{} is not valid UTF-8", name))
+}
+
+/// Borrow the client behind a handle.
+///
+/// # Safety
+///
+/// `client` must be NULL or a live handle from [`claude_client_spawn`].
+unsafe fn client_arg<'a>(client: *mut ClaudeClient) -> Result<&'a mut BlockingClient, String> {
+    client
+        .as_mut()
+        .map(|c| &mut c.inner)
+        .ok_or_else(|| "client is NULL".to_string())
+}
+
+fn into_c_string(s: String) -> Result<*mut c_char, String> {
+    CString::new(s)
+        .map(CString::into_raw)
+        .map_err(|e| e.to_string())
+}
+
+/// Parse a `can_use_tool` control request as received from
+/// [`claude_client_receive`].
+fn permission_request(
+    request_json: &str,
+) -> Result<(String, claude_codes::ToolPermissionRequest), String> {
+    match ClaudeOutput::parse_json_tolerant(request_json).map_err(|e| e.to_string())? {
+        ClaudeOutput::ControlRequest(req) => match req.request {
+            ControlRequestPayload::CanUseTool(perm) => Ok((req.request_id, perm)),
+            _ => Err("control request is not a tool permission request".to_string()),
+        },
+        other => Err(format!(
+            "expected a control_request, got {}",
+            other.message_type()
+        )),
+    }
+}
+
+fn send_response(client: &mut BlockingClient, response: ControlResponse) -> Result<(), String> {
+    client
+        .send_control_response(response)
+        .map_err(|e| e.to_string())
+}
+
+/// Spawn the Claude CLI and return a client handle, or NULL on failure.
+///
+/// `options_json` is NULL or a JSON object with any of: `command`, `model`,
+/// `fallback_model`, `permission_mode`, `allowed_tools`, `disallowed_tools`,
+/// `append_system_prompt`, `add_directories`, `mcp_config`, `settings`,
+/// `resume`, `permission_prompt_tool`, `env`, and `tool_approval`.
+///
+/// # Safety
+///
+/// `options_json` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn claude_client_spawn(options_json: *const c_char) -> *mut ClaudeClient {
+    guard(ptr::null_mut(), || {
+        let options: SpawnOptions = if options_json.is_null() {
+            SpawnOptions::default()
+        } else {
+            serde_json::from_str(str_arg(options_json, "options_json")?)
+                .map_err(|e| format!("invalid options: {}", e))?
+        };
+        let tool_approval = options.tool_approval;
+        let mut inner =
+            BlockingClient::from_builder(options.into_builder()?).map_err(|e| e.to_string())?;
+        if tool_approval {
+            inner.enable_tool_approval().map_err(|e| e.to_string())?;
+        }
+        Ok(Box::into_raw(Box::new(ClaudeClient { inner })))
+    })
+}
+
+/// Send one stream-json input message, e.g. a `user` message or a
+/// `control_response`.
+///
+/// # Safety
+///
+/// `client` must be a live handle and `input_json` a valid NUL-terminated
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn claude_client_send(
+    client: *mut ClaudeClient,
+    input_json: *const c_char,
+) -> i32 {
+    status(|| {
+        let client = client_arg(client)?;
+        let input: ClaudeInput = serde_json::from_str(str_arg(input_json, "input_json")?)
+            .map_err(|e| format!("invalid input: {}", e))?;
+        client.send(&input).map_err(|e| e.to_string())
+    })
+}
+
+/// Send `text` as a user message. Read the replies with
+/// [`claude_client_receive`] until a `result` message arrives.
+///
+/// # Safety
+///
+/// `client` must be a live handle and `text` a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn claude_client_query(
+    client: *mut ClaudeClient,
+    text: *const c_char,
+) -> i32 {
+    status(|| {
+        let client = client_arg(client)?;
+        let input = ClaudeInput::user_message(str_arg(text, "text")?, Uuid::new_v4());
+        client.send(&input).map_err(|e| e.to_string())
+    })
+}
+
+/// Block until the next message arrives and return it as a JSON string, or
+/// NULL on failure. Free the result with [`claude_string_free`].
+///
+/// # Safety
+///
+/// `client` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn claude_client_receive(client: *mut ClaudeClient) -> *mut c_char {
+    guard(ptr::null_mut(), || {
+        let output = client_arg(client)?.receive().map_err(|e| e.to_string())?;
+        into_c_string(serde_json::to_string(&output).map_err(|e| e.to_string())?)
+    })
+}
+
+/// Approve a tool permission request.
+///
+/// `request_json` is the `control_request` message as returned by
+/// [`claude_client_receive`]. `updated_input_json` is NULL to run the tool
+/// with its original input, or a JSON object to run it with instead.
+///
+/// # Safety
+///
+/// `client` must be a live handle, `request_json` a valid NUL-terminated
+/// string, and `updated_input_json` NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn claude_client_allow_tool(
+    client: *mut ClaudeClient,
+    request_json: *const c_char,
+    updated_input_json: *const c_char,
+) -> i32 {
+    status(|| {
+        let client = client_arg(client)?;
+        let (request_id, perm) = permission_request(str_arg(request_json, "request_json")?)?;
+        let response = if updated_input_json.is_null() {
+            perm.allow(&request_id)
+        } else {
+            let input = serde_json::from_str(str_arg(updated_input_json, "updated_input_json")?)
+                .map_err(|e| format!("invalid updated input: {}", e))?;
+            perm.allow_with(input, &request_id)
+        };
+        send_response(client, response)
+    })
+}
+
+/// Deny a tool permission request with `reason`. If `interrupt` is true the
+/// CLI also abandons the rest of the turn.
+///
+/// # Safety
+///
+/// `client` must be a live handle; `request_json` and `reason` must be valid
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn claude_client_deny_tool(
+    client: *mut ClaudeClient,
+    request_json: *const c_char,
+    reason: *const c_char,
+    interrupt: bool,
+) -> i32 {
+    status(|| {
+        let client = client_arg(client)?;
+        let (request_id, perm) = permission_request(str_arg(request_json, "request_json")?)?;
+        let reason = str_arg(reason, "reason")?;
+        let response = if interrupt {
+            perm.deny_and_interrupt(reason, &request_id)
+        } else {
+            perm.deny(reason, &request_id)
+        };
+        send_response(client, response)
+    })
+}
+
+/// Ask the CLI to stop the current response.
+///
+/// # Safety
+///
+/// `client` must be a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn claude_client_interrupt(client: *mut ClaudeClient) -> i32 {
+    status(|| client_arg(client)?.interrupt().map_err(|e| e.to_string()))
+}
+
+/// Shut the CLI down and free the handle. The handle is invalid afterwards,
+/// even on failure. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `client` must be NULL or a live handle that is not used again.
+#[no_mangle]
+pub unsafe extern "C" fn claude_client_shutdown(client: *mut ClaudeClient) -> i32 {
+    status(|| {
+        if client.is_null() {
+            return Ok(());
+        }
+        Box::from_raw(client)
+            .inner
+            .shutdown()
+            .map_err(|e| e.to_string())
+    })
+}
+
+/// Message describing the last failure on this thread, or NULL. Owned by the
+/// library; valid until the next call on this thread.
+#[no_mangle]
+pub extern "C" fn claude_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Free a string returned by this library. Passing NULL is a no-op.
+///
+/// # Safety
+///
+/// `s` must be NULL or a string from this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn claude_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    /// A stand-in CLI that asks to run a tool, then answers with a result
+    /// once the permission response arrives.
+    fn fake_cli() -> PathBuf {
+        let path = std::env::temp_dir().join(format!("claude-codes-ffi-{}.sh", std::process::id()));
+        let session = "622ae0c3-3d50-4fa7-9ee0-69d691238c6d";
+        let script = format!(
+            r#"#!/bin/sh
+read -r query
+echo '{{"type":"control_request","request_id":"req-1","request":{{"subtype":"can_use_tool","tool_name":"Bash","input":{{"command":"ls"}},"permission_suggestions":[]}}}}'
+read -r answer
+echo '{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"done","session_id":"{session}","total_cost_usd":0.0}}'
+read -r rest
+"#
+        );
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    fn receive(client: *mut ClaudeClient) -> serde_json::Value {
+        unsafe {
+            let raw = claude_client_receive(client);
+            assert!(!raw.is_null());
+            let value = serde_json::from_str(CStr::from_ptr(raw).to_str().unwrap()).unwrap();
+            claude_string_free(raw);
+            value
+        }
+    }
+
+    #[test]
+    fn test_query_approve_and_shutdown() {
+        let cli = fake_cli();
+        let options = CString::new(format!(r#"{{"command":"{}"}}"#, cli.display())).unwrap();
+        unsafe {
+            let client = claude_client_spawn(options.as_ptr());
+            assert!(!client.is_null());
+
+            let text = CString::new("list files").unwrap();
+            assert_eq!(claude_client_query(client, text.as_ptr()), CLAUDE_OK);
+
+            let request = receive(client);
+            assert_eq!(request["request"]["tool_name"], "Bash");
+            let request = CString::new(request.to_string()).unwrap();
+            assert_eq!(
+                claude_client_allow_tool(client, request.as_ptr(), ptr::null()),
+                CLAUDE_OK
+            );
+
+            let result = receive(client);
+            assert_eq!(result["type"], "result");
+            assert_eq!(result["result"], "done");
+
+            assert_eq!(claude_client_shutdown(client), CLAUDE_OK);
+        }
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        unsafe {
+            let bad = CString::new(r#"{"modle":"sonnet"}"#).unwrap();
+            assert!(claude_client_spawn(bad.as_ptr()).is_null());
+            let message = CStr::from_ptr(claude_last_error()).to_str().unwrap();
+            assert!(message.contains("modle"), "{}", message);
+
+            assert_eq!(claude_client_interrupt(ptr::null_mut()), CLAUDE_ERROR);
+            assert_eq!(claude_client_shutdown(ptr::null_mut()), CLAUDE_OK);
+            assert!(claude_last_error().is_null());
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! JSON spawn options accepted by `claude_client_spawn`.
+
+use claude_codes::{ClaudeCliBuilder, PermissionMode};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+const PERMISSION_MODES: [PermissionMode; 6] = [
+    PermissionMode::AcceptEdits,
+    PermissionMode::BypassPermissions,
+    PermissionMode::Default,
+    PermissionMode::Delegate,
+    PermissionMode::DontAsk,
+    PermissionMode::Plan,
+];
+
+/// Subset of [`ClaudeCliBuilder`] settings, deserialized from the JSON object
+/// passed to `claude_client_spawn`. Every field is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct SpawnOptions {
+    pub command: Option<String>,
+    pub model: Option<String>,
+    pub fallback_model: Option<String>,
+    pub permission_mode: Option<String>,
+    pub allowed_tools: Vec<String>,
+    pub disallowed_tools: Vec<String>,
+    pub append_system_prompt: Option<String>,
+    pub add_directories: Vec<String>,
+    pub mcp_config: Vec<String>,
+    pub settings: Option<String>,
+    pub resume: Option<String>,
+    pub permission_prompt_tool: Option<String>,
+    pub env: BTreeMap<String, String>,
+    /// Enable the tool approval protocol right after spawning.
+    pub tool_approval: bool,
+}
+
+impl SpawnOptions {
+    pub(crate) fn into_builder(self) -> Result<ClaudeCliBuilder, String> {
+        let mut builder = ClaudeCliBuilder::new();
+        if let Some(command) = self.command {
+            builder = builder.command(command);
+        }
+        if let Some(model) = self.model {
+            builder = builder.model(model);
+        }
+        if let Some(model) = self.fallback_model {
+            builder = builder.fallback_model(model);
+        }
+        if let Some(mode) = self.permission_mode {
+            let mode = PERMISSION_MODES
+                .into_iter()
+                .find(|m| m.as_str() == mode)
+                .ok_or_else(|| format!("unknown permission mode: {}", mode))?;
+            builder = builder.permission_mode(mode);
+        }
+        if !self.allowed_tools.is_empty() {
+            builder = builder.allowed_tools(self.allowed_tools);
+        }
+        if !self.disallowed_tools.is_empty() {
+            builder = builder.disallowed_tools(self.disallowed_tools);
+        }
+        if let Some(prompt) = self.append_system_prompt {
+            builder = builder.append_system_prompt(prompt);
+        }
+        if !self.add_directories.is_empty() {
+            builder = builder.add_directories(self.add_directories);
+        }
+        if !self.mcp_config.is_empty() {
+            builder = builder.mcp_config(self.mcp_config);
+        }
+        if let Some(settings) = self.settings {
+            builder = builder.settings(settings);
+        }
+        builder = builder.resume(self.resume);
+        if let Some(tool) = self.permission_prompt_tool {
+            builder = builder.permission_prompt_tool(tool);
+        }
+        for (key, value) in self.env {
+            builder = builder.env(key, value);
+        }
+        builder.validate().map_err(|e| e.to_string())?;
+        Ok(builder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_fields_are_rejected() {
+        let err = serde_json::from_str::<SpawnOptions>(r#"{"modle":"sonnet"}"#).unwrap_err();
+        assert!(err.to_string().contains("modle"));
+    }
+
+    #[test]
+    fn test_permission_mode_is_validated() {
+        let options: SpawnOptions =
+            serde_json::from_str(r#"{"permission_mode":"sometimes"}"#).unwrap();
+        assert!(options.into_builder().is_err());
+
+        let options: SpawnOptions =
+            serde_json::from_str(r#"{"permission_mode":"plan","allowed_tools":["Read"]}"#).unwrap();
+        assert!(options.into_builder().is_ok());
+    }
+}
@@ -0,0 +1,79 @@
+from typing import Any, Iterator, Optional
+
+__version__: str
+
+class ClaudeError(Exception): ...
+class NotAuthenticatedError(ClaudeError): ...
+
+class CliBuilder:
+    def __init__(self) -> None: ...
+    def command(self, path: str) -> CliBuilder: ...
+    def model(self, model: str) -> CliBuilder: ...
+    def fallback_model(self, model: str) -> CliBuilder: ...
+    def append_system_prompt(self, prompt: str) -> CliBuilder: ...
+    def permission_mode(self, mode: str) -> CliBuilder: ...
+    def allowed_tools(self, tools: list[str]) -> CliBuilder: ...
+    def disallowed_tools(self, tools: list[str]) -> CliBuilder: ...
+    def add_directories(self, dirs: list[str]) -> CliBuilder: ...
+    def mcp_config(self, configs: list[str]) -> CliBuilder: ...
+    def settings(self, settings: str) -> CliBuilder: ...
+    def max_thinking_tokens(self, tokens: int) -> CliBuilder: ...
+    def resume(self, session_id: str) -> CliBuilder: ...
+    def continue_conversation(self, enabled: bool) -> CliBuilder: ...
+    def fork_session(self, enabled: bool) -> CliBuilder: ...
+    def dangerously_skip_permissions(self, skip: bool) -> CliBuilder: ...
+    def permission_prompt_tool(self, tool: str) -> CliBuilder: ...
+    def api_key(self, key: str) -> CliBuilder: ...
+    def oauth_token(self, token: str) -> CliBuilder: ...
+    def config_dir(self, dir: str) -> CliBuilder: ...
+    def env(self, key: str, value: str) -> CliBuilder: ...
+    def validate(self) -> None: ...
+
+class Message:
+    @staticmethod
+    def parse(line: str) -> Message: ...
+    @property
+    def message_type(self) -> str: ...
+    @property
+    def session_id(self) -> Optional[str]: ...
+    @property
+    def text(self) -> Optional[str]: ...
+    @property
+    def is_result(self) -> bool: ...
+    @property
+    def is_error(self) -> bool: ...
+    @property
+    def total_cost_usd(self) -> Optional[float]: ...
+    @property
+    def is_permission_request(self) -> bool: ...
+    @property
+    def request_id(self) -> Optional[str]: ...
+    @property
+    def tool_name(self) -> Optional[str]: ...
+    @property
+    def tool_input(self) -> Optional[dict[str, Any]]: ...
+    def to_dict(self) -> dict[str, Any]: ...
+    def to_json(self) -> str: ...
+
+class ResponseIterator(Iterator[Message]):
+    def __iter__(self) -> ResponseIterator: ...
+    def __next__(self) -> Message: ...
+
+class Client:
+    def __init__(self, builder: Optional[CliBuilder] = None) -> None: ...
+    def query(self, text: str) -> list[Message]: ...
+    def query_stream(self, text: str) -> ResponseIterator: ...
+    def send(self, json: str) -> None: ...
+    def receive(self) -> Message: ...
+    def interrupt(self) -> None: ...
+    def enable_tool_approval(self) -> None: ...
+    def allow(self, request: Message, updated_input: Optional[Any] = None) -> None: ...
+    def deny(self, request: Message, reason: str, interrupt: bool = False) -> None: ...
+    @property
+    def session_id(self) -> Optional[str]: ...
+    @property
+    def pid(self) -> Optional[int]: ...
+    def is_alive(self) -> bool: ...
+    def shutdown(self) -> None: ...
+    def __enter__(self) -> Client: ...
+    def __exit__(self, *args: Any) -> bool: ...
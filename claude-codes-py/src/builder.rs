@@ -0,0 +1,173 @@
+//! `CliBuilder`: Python view of [`ClaudeCliBuilder`].
+
+use claude_codes::{ClaudeCliBuilder, PermissionMode};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+const PERMISSION_MODES: [PermissionMode; 6] = [
+    PermissionMode::AcceptEdits,
+    PermissionMode::BypassPermissions,
+    PermissionMode::Default,
+    PermissionMode::Delegate,
+    PermissionMode::DontAsk,
+    PermissionMode::Plan,
+];
+
+/// Configuration for the spawned `claude` process.
+///
+/// Setters return the builder, so calls chain:
+/// `CliBuilder().model("sonnet").allowed_tools(["Read"])`.
+#[pyclass(name = "CliBuilder", module = "claude_codes_py")]
+#[derive(Clone)]
+pub struct PyCliBuilder {
+    pub(crate) inner: ClaudeCliBuilder,
+}
+
+impl PyCliBuilder {
+    fn update(
+        mut slf: PyRefMut<'_, Self>,
+        f: impl FnOnce(ClaudeCliBuilder) -> ClaudeCliBuilder,
+    ) -> PyRefMut<'_, Self> {
+        let inner = std::mem::replace(&mut slf.inner, ClaudeCliBuilder::new());
+        slf.inner = f(inner);
+        slf
+    }
+}
+
+#[pymethods]
+impl PyCliBuilder {
+    #[new]
+    fn new() -> Self {
+        Self {
+            inner: ClaudeCliBuilder::new(),
+        }
+    }
+
+    /// Path or name of the `claude` binary.
+    fn command(slf: PyRefMut<'_, Self>, path: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.command(path))
+    }
+
+    fn model(slf: PyRefMut<'_, Self>, model: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.model(model))
+    }
+
+    fn fallback_model(slf: PyRefMut<'_, Self>, model: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.fallback_model(model))
+    }
+
+    fn append_system_prompt(slf: PyRefMut<'_, Self>, prompt: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.append_system_prompt(prompt))
+    }
+
+    /// One of `acceptEdits`, `bypassPermissions`, `default`, `delegate`,
+    /// `dontAsk`, or `plan`.
+    fn permission_mode(slf: PyRefMut<'_, Self>, mode: String) -> PyResult<PyRefMut<'_, Self>> {
+        let mode = PERMISSION_MODES
+            .into_iter()
+            .find(|m| m.as_str() == mode.as_str())
+            .ok_or_else(|| PyValueError::new_err(format!("unknown permission mode: {}", mode)))?;
+        Ok(Self::update(slf, |b| b.permission_mode(mode)))
+    }
+
+    fn allowed_tools(slf: PyRefMut<'_, Self>, tools: Vec<String>) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.allowed_tools(tools))
+    }
+
+    fn disallowed_tools(slf: PyRefMut<'_, Self>, tools: Vec<String>) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.disallowed_tools(tools))
+    }
+
+    fn add_directories(slf: PyRefMut<'_, Self>, dirs: Vec<String>) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.add_directories(dirs))
+    }
+
+    fn mcp_config(slf: PyRefMut<'_, Self>, configs: Vec<String>) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.mcp_config(configs))
+    }
+
+    fn settings(slf: PyRefMut<'_, Self>, settings: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.settings(settings))
+    }
+
+    fn max_thinking_tokens(slf: PyRefMut<'_, Self>, tokens: u32) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.max_thinking_tokens(tokens))
+    }
+
+    /// Resume an existing session by id.
+    fn resume(slf: PyRefMut<'_, Self>, session_id: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.resume(Some(session_id)))
+    }
+
+    fn continue_conversation(slf: PyRefMut<'_, Self>, enabled: bool) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.continue_conversation(enabled))
+    }
+
+    fn fork_session(slf: PyRefMut<'_, Self>, enabled: bool) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.fork_session(enabled))
+    }
+
+    fn dangerously_skip_permissions(slf: PyRefMut<'_, Self>, skip: bool) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.dangerously_skip_permissions(skip))
+    }
+
+    fn permission_prompt_tool(slf: PyRefMut<'_, Self>, tool: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.permission_prompt_tool(tool))
+    }
+
+    fn api_key(slf: PyRefMut<'_, Self>, key: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.api_key(key))
+    }
+
+    fn oauth_token(slf: PyRefMut<'_, Self>, token: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.oauth_token(token))
+    }
+
+    fn config_dir(slf: PyRefMut<'_, Self>, dir: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.config_dir(dir))
+    }
+
+    /// Set an environment variable for the spawned CLI.
+    fn env(slf: PyRefMut<'_, Self>, key: String, value: String) -> PyRefMut<'_, Self> {
+        Self::update(slf, |b| b.env(key, value))
+    }
+
+    /// Raise `ValueError` if the options conflict.
+    fn validate(&self) -> PyResult<()> {
+        self.inner
+            .validate()
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_mode_is_validated() {
+        Python::attach(|py| {
+            let builder = Bound::new(py, PyCliBuilder::new()).unwrap();
+            assert!(builder
+                .call_method1("permission_mode", ("acceptEdits",))
+                .is_ok());
+            let err = builder
+                .call_method1("permission_mode", ("sometimes",))
+                .unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn test_validate_reports_conflicts() {
+        Python::attach(|py| {
+            let builder = Bound::new(py, PyCliBuilder::new()).unwrap();
+            builder
+                .call_method1("resume", ("550e8400-e29b-41d4-a716-446655440000",))
+                .unwrap()
+                .call_method1("continue_conversation", (true,))
+                .unwrap();
+            assert!(builder.call_method0("validate").is_err());
+        });
+    }
+}
@@ -0,0 +1,274 @@
+//! `Client`: Python view of [`BlockingClient`].
+
+use crate::builder::PyCliBuilder;
+use crate::message::{from_python, PyMessage};
+use crate::{to_py_err, ClaudeError};
+use claude_codes::{BlockingClient, ClaudeInput, ClaudeOutput};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// A Claude CLI session.
+///
+/// Every call blocks until the CLI answers but releases the GIL while it
+/// waits. From asyncio, wrap calls in `asyncio.to_thread`.
+///
+/// Calls are serialized: `interrupt()` issued from another thread during a
+/// `query_stream` iteration takes effect at the next message boundary.
+#[pyclass(name = "Client", module = "claude_codes_py", frozen)]
+pub struct PyClient {
+    inner: Mutex<Option<BlockingClient>>,
+}
+
+impl PyClient {
+    /// Run `f` on the live client with the GIL released.
+    fn with_client<T: Send>(
+        &self,
+        py: Python<'_>,
+        f: impl FnOnce(&mut BlockingClient) -> claude_codes::Result<T> + Send,
+    ) -> PyResult<T> {
+        py.detach(|| {
+            let mut guard = self
+                .inner
+                .lock()
+                .map_err(|_| PyRuntimeError::new_err("client lock poisoned"))?;
+            let client = guard
+                .as_mut()
+                .ok_or_else(|| ClaudeError::new_err("client has been shut down"))?;
+            f(client).map_err(to_py_err)
+        })
+    }
+}
+
+#[pymethods]
+impl PyClient {
+    /// Spawn the CLI, configured by `builder` if given.
+    #[new]
+    #[pyo3(signature = (builder = None))]
+    fn new(py: Python<'_>, builder: Option<PyRef<'_, PyCliBuilder>>) -> PyResult<Self> {
+        let builder = builder.map(|b| b.inner.clone()).unwrap_or_default();
+        let client = py
+            .detach(|| BlockingClient::from_builder(builder))
+            .map_err(to_py_err)?;
+        Ok(Self {
+            inner: Mutex::new(Some(client)),
+        })
+    }
+
+    /// Send a query and return every message up to and including the result.
+    fn query(&self, py: Python<'_>, text: String) -> PyResult<Vec<PyMessage>> {
+        let outputs = self.with_client(py, |c| c.query(&text))?;
+        Ok(outputs.into_iter().map(PyMessage::from).collect())
+    }
+
+    /// Send a query and return an iterator over its messages, ending after
+    /// the result.
+    fn query_stream(slf: Bound<'_, Self>, text: String) -> PyResult<PyResponseIterator> {
+        let input = ClaudeInput::user_message(text, Uuid::new_v4());
+        slf.get().with_client(slf.py(), |c| c.send(&input))?;
+        Ok(PyResponseIterator {
+            client: slf.unbind(),
+            finished: AtomicBool::new(false),
+        })
+    }
+
+    /// Send one raw stream-json input message.
+    fn send(&self, py: Python<'_>, json: &str) -> PyResult<()> {
+        let input: ClaudeInput =
+            serde_json::from_str(json).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.with_client(py, |c| c.send(&input))
+    }
+
+    /// Block until the next message arrives.
+    fn receive(&self, py: Python<'_>) -> PyResult<PyMessage> {
+        self.with_client(py, |c| c.receive()).map(PyMessage::from)
+    }
+
+    /// Ask the CLI to stop the current response.
+    fn interrupt(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_client(py, |c| c.interrupt())
+    }
+
+    /// Route tool permission prompts to this client as messages with
+    /// `is_permission_request` set; answer them with `allow` or `deny`.
+    fn enable_tool_approval(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_client(py, |c| c.enable_tool_approval())
+    }
+
+    /// Approve a permission request, optionally replacing the tool input.
+    #[pyo3(signature = (request, updated_input = None))]
+    fn allow(
+        &self,
+        py: Python<'_>,
+        request: PyRef<'_, PyMessage>,
+        updated_input: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let (request_id, perm) = request
+            .permission_request()
+            .ok_or_else(|| PyValueError::new_err("message is not a permission request"))?;
+        let response = match updated_input {
+            Some(input) => perm.allow_with(from_python(&input)?, request_id),
+            None => perm.allow(request_id),
+        };
+        self.with_client(py, |c| c.send_control_response(response))
+    }
+
+    /// Reject a permission request. With `interrupt=True` the CLI also
+    /// abandons the rest of the turn.
+    #[pyo3(signature = (request, reason, interrupt = false))]
+    fn deny(
+        &self,
+        py: Python<'_>,
+        request: PyRef<'_, PyMessage>,
+        reason: String,
+        interrupt: bool,
+    ) -> PyResult<()> {
+        let (request_id, perm) = request
+            .permission_request()
+            .ok_or_else(|| PyValueError::new_err("message is not a permission request"))?;
+        let response = if interrupt {
+            perm.deny_and_interrupt(reason, request_id)
+        } else {
+            perm.deny(reason, request_id)
+        };
+        self.with_client(py, |c| c.send_control_response(response))
+    }
+
+    /// Session id reported by the CLI, once known.
+    #[getter]
+    fn session_id(&self, py: Python<'_>) -> PyResult<Option<String>> {
+        self.with_client(py, |c| Ok(c.session_uuid().ok().map(|id| id.to_string())))
+    }
+
+    #[getter]
+    fn pid(&self, py: Python<'_>) -> PyResult<Option<u32>> {
+        self.with_client(py, |c| Ok(c.pid()))
+    }
+
+    fn is_alive(&self, py: Python<'_>) -> bool {
+        self.with_client(py, |c| Ok(c.is_alive())).unwrap_or(false)
+    }
+
+    /// Shut the CLI down. Further calls raise `ClaudeError`.
+    fn shutdown(&self, py: Python<'_>) -> PyResult<()> {
+        py.detach(|| {
+            let client = self
+                .inner
+                .lock()
+                .map_err(|_| PyRuntimeError::new_err("client lock poisoned"))?
+                .take();
+            match client {
+                Some(client) => client.shutdown().map_err(to_py_err),
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn __enter__(slf: Bound<'_, Self>) -> Bound<'_, Self> {
+        slf
+    }
+
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        _exc_type: Option<Bound<'_, PyAny>>,
+        _exc: Option<Bound<'_, PyAny>>,
+        _tb: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        self.shutdown(py)?;
+        Ok(false)
+    }
+}
+
+/// Iterator returned by `Client.query_stream`.
+#[pyclass(name = "ResponseIterator", module = "claude_codes_py", frozen)]
+pub struct PyResponseIterator {
+    client: Py<PyClient>,
+    finished: AtomicBool,
+}
+
+#[pymethods]
+impl PyResponseIterator {
+    fn __iter__(slf: Bound<'_, Self>) -> Bound<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<PyMessage>> {
+        if self.finished.load(Ordering::Acquire) {
+            return Ok(None);
+        }
+        let output = self.client.get().with_client(py, |c| c.receive());
+        match &output {
+            Ok(ClaudeOutput::Result(_)) | Err(_) => self.finished.store(true, Ordering::Release),
+            Ok(_) => {}
+        }
+        output.map(|o| Some(PyMessage::from(o)))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use claude_codes::ClaudeCliBuilder;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::PathBuf;
+
+    /// A stand-in CLI that answers every input line with one assistant
+    /// message and a result.
+    fn fake_cli() -> PathBuf {
+        let path = std::env::temp_dir().join(format!("claude-codes-py-{}.sh", std::process::id()));
+        let session = "622ae0c3-3d50-4fa7-9ee0-69d691238c6d";
+        let script = format!(
+            r#"#!/bin/sh
+while read -r line; do
+  echo '{{"type":"assistant","message":{{"id":"m","role":"assistant","model":"c","content":[{{"type":"text","text":"four"}}]}},"session_id":"{session}"}}'
+  echo '{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"four","session_id":"{session}","total_cost_usd":0.0}}'
+done
+"#
+        );
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_query_stream_and_shutdown() {
+        let cli = fake_cli();
+        Python::attach(|py| {
+            let builder = PyCliBuilder {
+                inner: ClaudeCliBuilder::new().command(&cli),
+            };
+            let client = Bound::new(
+                py,
+                PyClient::new(py, Some(builder.into_pyobject(py).unwrap().borrow())).unwrap(),
+            )
+            .unwrap();
+
+            let kinds: Vec<String> = PyClient::query_stream(client.clone(), "2 + 2?".into())
+                .unwrap()
+                .into_pyobject(py)
+                .unwrap()
+                .try_iter()
+                .unwrap()
+                .map(|m| {
+                    m.unwrap()
+                        .getattr("message_type")
+                        .unwrap()
+                        .extract()
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(kinds, vec!["assistant", "result"]);
+
+            let replies = client.get().query(py, "again".into()).unwrap();
+            assert_eq!(replies[1].inner.result_text(), Some("four"));
+
+            client.get().shutdown(py).unwrap();
+            let err = client.get().query(py, "closed".into()).unwrap_err();
+            assert!(err.is_instance_of::<ClaudeError>(py));
+        });
+        std::fs::remove_file(cli).ok();
+    }
+}
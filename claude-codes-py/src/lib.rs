@@ -0,0 +1,62 @@
+//! Python bindings for [`claude_codes`].
+//!
+//! Exposes three classes to Python:
+//!
+//! - `CliBuilder` - configures the spawned `claude` process
+//! - `Client` - a session over [`BlockingClient`](claude_codes::BlockingClient),
+//!   with queries, streaming iteration, tool approval, and interrupts
+//! - `Message` - a typed [`ClaudeOutput`](claude_codes::ClaudeOutput) with
+//!   accessors and `to_dict()`
+//!
+//! Every blocking call releases the GIL, so a `Client` can be driven from a
+//! worker thread or `asyncio.to_thread` without stalling the interpreter.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+
+mod builder;
+mod client;
+mod message;
+
+pub use builder::PyCliBuilder;
+pub use client::{PyClient, PyResponseIterator};
+pub use message::PyMessage;
+
+create_exception!(
+    claude_codes_py,
+    ClaudeError,
+    PyException,
+    "Raised when the Claude CLI session fails."
+);
+create_exception!(
+    claude_codes_py,
+    NotAuthenticatedError,
+    ClaudeError,
+    "Raised when the Claude CLI has no usable credentials."
+);
+
+/// Map a Rust SDK error onto the matching Python exception.
+pub(crate) fn to_py_err(err: claude_codes::Error) -> PyErr {
+    match err {
+        claude_codes::Error::NotAuthenticated { .. } => {
+            NotAuthenticatedError::new_err(err.to_string())
+        }
+        other => ClaudeError::new_err(other.to_string()),
+    }
+}
+
+#[pymodule]
+fn claude_codes_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyCliBuilder>()?;
+    m.add_class::<PyClient>()?;
+    m.add_class::<PyResponseIterator>()?;
+    m.add_class::<PyMessage>()?;
+    m.add("ClaudeError", m.py().get_type::<ClaudeError>())?;
+    m.add(
+        "NotAuthenticatedError",
+        m.py().get_type::<NotAuthenticatedError>(),
+    )?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
@@ -0,0 +1,204 @@
+//! `Message`: Python view of [`ClaudeOutput`].
+
+use claude_codes::io::ControlRequestPayload;
+use claude_codes::ClaudeOutput;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// Convert any serializable value to native Python objects via `json.loads`.
+pub(crate) fn to_python<T: serde::Serialize + ?Sized>(
+    py: Python<'_>,
+    value: &T,
+) -> PyResult<Py<PyAny>> {
+    let text = serde_json::to_string(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(py.import("json")?.call_method1("loads", (text,))?.unbind())
+}
+
+/// Convert a Python object to a JSON value via `json.dumps`.
+pub(crate) fn from_python(value: &Bound<'_, PyAny>) -> PyResult<serde_json::Value> {
+    let text: String = value
+        .py()
+        .import("json")?
+        .call_method1("dumps", (value,))?
+        .extract()?;
+    serde_json::from_str(&text).map_err(|e| PyValueError::new_err(e.to_string()))
+}
+
+/// A message received from the Claude CLI.
+#[pyclass(name = "Message", module = "claude_codes_py", frozen)]
+#[derive(Debug, Clone)]
+pub struct PyMessage {
+    pub(crate) inner: ClaudeOutput,
+}
+
+impl From<ClaudeOutput> for PyMessage {
+    fn from(inner: ClaudeOutput) -> Self {
+        Self { inner }
+    }
+}
+
+#[pymethods]
+impl PyMessage {
+    /// Parse one line of the CLI's stream-json output.
+    #[staticmethod]
+    fn parse(line: &str) -> PyResult<Self> {
+        ClaudeOutput::parse_json_tolerant(line)
+            .map(Self::from)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Wire type: `system`, `user`, `assistant`, `result`, `control_request`, ...
+    #[getter]
+    fn message_type(&self) -> String {
+        self.inner.message_type()
+    }
+
+    #[getter]
+    fn session_id(&self) -> Option<String> {
+        self.inner.session_id().map(|id| id.to_string())
+    }
+
+    /// Assistant text, or the final text of a result message.
+    #[getter]
+    fn text(&self) -> Option<String> {
+        self.inner
+            .assistant_text()
+            .or_else(|| self.inner.result_text().map(str::to_string))
+    }
+
+    #[getter]
+    fn is_result(&self) -> bool {
+        matches!(self.inner, ClaudeOutput::Result(_))
+    }
+
+    /// True for error results and API errors.
+    #[getter]
+    fn is_error(&self) -> bool {
+        self.inner.is_error() || matches!(self.inner, ClaudeOutput::Error(_))
+    }
+
+    #[getter]
+    fn total_cost_usd(&self) -> Option<f64> {
+        self.inner.as_result().map(|r| r.total_cost_usd)
+    }
+
+    /// True if the CLI is asking permission to run a tool; answer with
+    /// `Client.allow` or `Client.deny`.
+    #[getter]
+    fn is_permission_request(&self) -> bool {
+        self.permission_request().is_some()
+    }
+
+    /// Id of a control request, used to correlate the response.
+    #[getter]
+    fn request_id(&self) -> Option<String> {
+        match &self.inner {
+            ClaudeOutput::ControlRequest(req) => Some(req.request_id.clone()),
+            _ => None,
+        }
+    }
+
+    /// Tool named in a permission request.
+    #[getter]
+    fn tool_name(&self) -> Option<String> {
+        self.permission_request().map(|(_, p)| p.tool_name.clone())
+    }
+
+    /// Tool input of a permission request, as a dict.
+    #[getter]
+    fn tool_input(&self, py: Python<'_>) -> PyResult<Option<Py<PyAny>>> {
+        self.permission_request()
+            .map(|(_, p)| to_python(py, &p.input))
+            .transpose()
+    }
+
+    /// The full message as native Python objects.
+    fn to_dict(&self, py: Python<'_>) -> PyResult<Py<PyAny>> {
+        to_python(py, &self.inner)
+    }
+
+    /// The full message as a JSON string.
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<Message type={}>", self.inner.message_type())
+    }
+}
+
+impl PyMessage {
+    /// The request id and payload, if this is a tool permission request.
+    pub(crate) fn permission_request(
+        &self,
+    ) -> Option<(&str, &claude_codes::ToolPermissionRequest)> {
+        match &self.inner {
+            ClaudeOutput::ControlRequest(req) => match &req.request {
+                ControlRequestPayload::CanUseTool(perm) => Some((&req.request_id, perm)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERMISSION_REQUEST: &str = r#"{"type":"control_request","request_id":"req-1",
+        "request":{"subtype":"can_use_tool","tool_name":"Bash",
+        "input":{"command":"ls"},"permission_suggestions":[]}}"#;
+
+    #[test]
+    fn test_permission_request_accessors() {
+        Python::attach(|py| {
+            let msg = PyMessage::parse(PERMISSION_REQUEST).unwrap();
+            assert!(msg.is_permission_request());
+            assert_eq!(msg.request_id().as_deref(), Some("req-1"));
+            assert_eq!(msg.tool_name().as_deref(), Some("Bash"));
+
+            let input = msg.tool_input(py).unwrap().unwrap();
+            let command: String = input
+                .bind(py)
+                .get_item("command")
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(command, "ls");
+        });
+    }
+
+    #[test]
+    fn test_result_to_dict() {
+        Python::attach(|py| {
+            let msg = PyMessage::parse(
+                r#"{"type":"result","subtype":"success","is_error":false,"duration_ms":1,
+                    "duration_api_ms":1,"num_turns":1,"result":"4",
+                    "session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.5}"#,
+            )
+            .unwrap();
+            assert!(msg.is_result());
+            assert_eq!(msg.text().as_deref(), Some("4"));
+            assert_eq!(msg.total_cost_usd(), Some(0.5));
+
+            let dict = msg.to_dict(py).unwrap();
+            let subtype: String = dict
+                .bind(py)
+                .get_item("subtype")
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(subtype, "success");
+        });
+    }
+
+    #[test]
+    fn test_parse_rejects_garbage() {
+        assert!(PyMessage::parse("not json").is_err());
+    }
+}
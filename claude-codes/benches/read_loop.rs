@@ -0,0 +1,122 @@
+//! Allocation cost of the client read loop at high message rates.
+//!
+//! Compares the old per-line path (a fresh `String` for every line, parsed
+//! through an intermediate `serde_json::Value`) with the current one
+//! ([`FrameReader`] reusing its buffer, parsed straight from the text).
+//! Allocations per message are printed before the timing runs:
+//!
+//! ```text
+//! cargo bench -p claude-codes --bench read_loop
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::io::{BufRead, Cursor};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use claude_codes::framing::FrameReader;
+use claude_codes::ClaudeOutput;
+use criterion::{Criterion, Throughput};
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// A recorded session, repeated to make a long stream.
+const SESSION: &str = include_str!("../test_cases/subagent_sessions/explore_count_files.jsonl");
+const REPEAT: usize = 50;
+
+fn stream() -> (String, usize) {
+    let lines: Vec<&str> = SESSION
+        .lines()
+        .filter(|l| ClaudeOutput::parse_json(l.trim()).is_ok())
+        .collect();
+    let mut text = String::new();
+    for _ in 0..REPEAT {
+        for line in &lines {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    (text, lines.len() * REPEAT)
+}
+
+/// The read loop as it was: a new `String` per line and a `Value` round-trip.
+fn fresh_buffers(input: &str) -> usize {
+    let mut reader = Cursor::new(input.as_bytes());
+    let mut parsed = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap() == 0 {
+            return parsed;
+        }
+        let trimmed = line.trim().to_string();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let value: serde_json::Value = serde_json::from_str(&trimmed).unwrap();
+        let output: ClaudeOutput = serde_json::from_value(value.clone()).unwrap();
+        black_box(output);
+        parsed += 1;
+    }
+}
+
+/// The read loop as the clients run it now.
+fn reused_buffer(input: &str) -> usize {
+    let mut reader = Cursor::new(input.as_bytes());
+    let mut frames = FrameReader::new();
+    let mut parsed = 0;
+    while let Some(frame) = frames.read_frame(&mut reader).unwrap() {
+        black_box(ClaudeOutput::parse_json_tolerant(frame).unwrap());
+        parsed += 1;
+    }
+    parsed
+}
+
+fn allocations_per_message(read_loop: fn(&str) -> usize, input: &str) -> f64 {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let parsed = read_loop(input);
+    let after = ALLOCATIONS.load(Ordering::Relaxed);
+    (after - before) as f64 / parsed as f64
+}
+
+fn main() {
+    let (input, messages) = stream();
+
+    let fresh = allocations_per_message(fresh_buffers, &input);
+    let reused = allocations_per_message(reused_buffer, &input);
+    println!(
+        "allocations per message over {} messages: fresh buffers {:.1}, reused buffer {:.1} ({:.0}% fewer)",
+        messages,
+        fresh,
+        reused,
+        100.0 * (1.0 - reused / fresh)
+    );
+
+    let mut criterion = Criterion::default().configure_from_args();
+    let mut group = criterion.benchmark_group("read_loop");
+    group.throughput(Throughput::Elements(messages as u64));
+    group.bench_function("fresh_buffers", |b| b.iter(|| fresh_buffers(&input)));
+    group.bench_function("reused_buffer", |b| b.iter(|| reused_buffer(&input)));
+    group.finish();
+    criterion.final_summary();
+}
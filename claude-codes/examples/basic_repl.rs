@@ -170,6 +170,9 @@ fn handle_output(output: ClaudeOutput) {
                     claude_codes::io::ContentBlock::Thinking(thinking) => {
                         println!("\n[Thinking]\n{}\n", thinking.thinking);
                     }
+                    claude_codes::io::ContentBlock::RedactedThinking(_) => {
+                        println!("\n[Thinking redacted]\n");
+                    }
                     claude_codes::io::ContentBlock::ToolUse(tool) => {
                         println!("\n[Tool Request: {}]", tool.name);
                         println!("ID: {}", tool.id);
@@ -193,6 +196,15 @@ fn handle_output(output: ClaudeOutput) {
                                         serde_json::to_string_pretty(&data).unwrap_or_default()
                                     );
                                 }
+                                claude_codes::io::ToolResultContent::Spilled(large) => {
+                                    println!(
+                                        "{}... ({} bytes in {})",
+                                        large.preview,
+                                        large.len,
+                                        large.path.display()
+                                    );
+                                }
+                                other => println!("{:?}", other),
                             }
                         }
                     }
@@ -233,6 +245,7 @@ fn handle_output(output: ClaudeOutput) {
                             serde_json::to_string_pretty(value).unwrap_or_default()
                         );
                     }
+                    other => println!("\n[{}]", other.block_type()),
                 }
             }
         }
@@ -275,6 +288,9 @@ fn handle_output(output: ClaudeOutput) {
                 eprintln!("   Request ID: {}", req_id);
             }
         }
+        ClaudeOutput::Debug(msg) => {
+            debug!("CLI debug: {}", msg);
+        }
         ClaudeOutput::RateLimitEvent(evt) => {
             debug!(
                 "Rate limit event: status={}, type={:?}, resets_at={:?}",
@@ -283,5 +299,8 @@ fn handle_output(output: ClaudeOutput) {
                 evt.rate_limit_info.resets_at
             );
         }
+        other => {
+            debug!("Unhandled {} message", other.message_type());
+        }
     }
 }
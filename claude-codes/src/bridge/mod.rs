@@ -0,0 +1,213 @@
+//! Network bridges that let remote frontends attach to a session.
+//!
+//! A bridge sits between a [`ClientHandle`] and any number of connected
+//! frontends (a web UI, a phone app, a second terminal). It reads the
+//! session's messages once and passes each to every subscriber, and turns
+//! what subscribers send back — user input, answers to tool permission
+//! requests, interrupts — into calls on the handle. Each transport is behind
+//! its own feature:
+//!
+//! - [`sse`] - Server-Sent Events out, JSON `POST`s in (`sse-bridge` feature)
+//! - [`ws`] - A WebSocket carrying normalized agent events out and input,
+//!   approvals, and interrupts in (`ws-bridge` feature)
+//!
+//! Messages are numbered like [`Sequenced`](crate::Sequenced). A frontend
+//! that connects late misses earlier messages but can still see the
+//! permission requests waiting for an answer.
+//!
+//! The bridges do no authentication: bind them to localhost, or put them
+//! behind a proxy that checks who is connecting.
+
+#[cfg(feature = "sse-bridge")]
+pub mod sse;
+#[cfg(feature = "ws-bridge")]
+pub mod ws;
+
+use crate::client_handle::{ClientHandle, Sequenced};
+use crate::error::Result;
+use crate::io::{ClaudeInput, ClaudeOutput, ControlRequestPayload, ToolPermissionRequest};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+/// Text a frontend wants sent as the next user message.
+///
+/// Queued with [`ClientHandle::queue_input`], so input that arrives
+/// mid-turn waits for the turn to end.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserInput {
+    pub text: String,
+}
+
+/// A frontend's answer to a `can_use_tool` permission request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Approval {
+    pub allow: bool,
+    /// Input to run the tool with instead of the requested one (allow only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_input: Option<Value>,
+    /// Why the tool was denied, passed on to Claude (deny only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// End the turn as well as denying the tool (deny only).
+    #[serde(default)]
+    pub interrupt: bool,
+}
+
+impl Approval {
+    /// Message sent to Claude when a denial doesn't give one.
+    pub const DEFAULT_DENY_MESSAGE: &'static str = "Denied by the user";
+
+    fn response(
+        &self,
+        request_id: &str,
+        request: &ToolPermissionRequest,
+    ) -> crate::io::ControlResponse {
+        let message = self
+            .message
+            .as_deref()
+            .unwrap_or(Self::DEFAULT_DENY_MESSAGE);
+        match (self.allow, &self.updated_input) {
+            (true, Some(input)) => request.allow_with(input.clone(), request_id),
+            (true, None) => request.allow(request_id),
+            (false, _) if self.interrupt => request.deny_and_interrupt(message, request_id),
+            (false, _) => request.deny(message, request_id),
+        }
+    }
+}
+
+/// A permission request still waiting for an [`Approval`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingApproval {
+    pub request_id: String,
+    #[serde(flatten)]
+    pub request: ToolPermissionRequest,
+}
+
+/// Where the hub delivers one subscriber's messages.
+type Subscriber = mpsc::Sender<Arc<Sequenced<ClaudeOutput>>>;
+
+/// How many messages a subscriber may fall behind before it is disconnected.
+const SUBSCRIBER_BUFFER: usize = 1024;
+
+/// The transport-independent part of a bridge: one reader of the handle,
+/// many subscribers.
+pub(crate) struct Hub {
+    handle: Arc<ClientHandle>,
+    /// Session ID stamped on user messages sent through the bridge
+    session_id: Uuid,
+    /// `None` once the session's output has ended
+    subscribers: Mutex<Option<Vec<Subscriber>>>,
+    /// Unanswered `can_use_tool` requests, by request ID
+    pending: Mutex<HashMap<String, ToolPermissionRequest>>,
+}
+
+impl Hub {
+    /// Start reading `handle` on a background task. The task stops once the
+    /// session ends or the hub is dropped.
+    pub(crate) fn start(handle: Arc<ClientHandle>) -> Arc<Self> {
+        let hub = Arc::new(Self {
+            handle: Arc::clone(&handle),
+            session_id: Uuid::new_v4(),
+            subscribers: Mutex::new(Some(Vec::new())),
+            pending: Mutex::new(HashMap::new()),
+        });
+        tokio::spawn(pump(handle, Arc::downgrade(&hub)));
+        hub
+    }
+
+    /// Receive every message from now on. The receiver closes when the
+    /// session ends, or if this subscriber falls too far behind.
+    pub(crate) fn subscribe(&self) -> mpsc::Receiver<Arc<Sequenced<ClaudeOutput>>> {
+        let (tx, rx) = mpsc::channel(SUBSCRIBER_BUFFER);
+        if let Some(subscribers) = lock(&self.subscribers).as_mut() {
+            subscribers.push(tx);
+        }
+        rx
+    }
+
+    pub(crate) async fn send_input(&self, input: UserInput) -> Result<()> {
+        self.handle
+            .queue_input(ClaudeInput::user_message(input.text, self.session_id))
+            .await
+    }
+
+    pub(crate) async fn interrupt(&self) -> Result<()> {
+        self.handle.interrupt().await
+    }
+
+    /// Answer the pending request `request_id`. Returns `Ok(false)` if no
+    /// such request is waiting.
+    pub(crate) async fn approve(&self, request_id: &str, approval: &Approval) -> Result<bool> {
+        let Some(request) = lock(&self.pending).remove(request_id) else {
+            return Ok(false);
+        };
+        self.handle
+            .send_control_response(approval.response(request_id, &request))
+            .await?;
+        Ok(true)
+    }
+
+    pub(crate) fn pending(&self) -> Vec<PendingApproval> {
+        let mut pending: Vec<_> = lock(&self.pending)
+            .iter()
+            .map(|(request_id, request)| PendingApproval {
+                request_id: request_id.clone(),
+                request: request.clone(),
+            })
+            .collect();
+        pending.sort_by(|a, b| a.request_id.cmp(&b.request_id));
+        pending
+    }
+
+    fn publish(&self, output: Sequenced<ClaudeOutput>) {
+        if let ClaudeOutput::ControlRequest(ref req) = output.message {
+            if let ControlRequestPayload::CanUseTool(ref perm) = req.request {
+                lock(&self.pending).insert(req.request_id.clone(), perm.clone());
+            }
+        }
+        let output = Arc::new(output);
+        if let Some(subscribers) = lock(&self.subscribers).as_mut() {
+            subscribers.retain(|tx| match tx.try_send(Arc::clone(&output)) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    warn!("[BRIDGE] Disconnecting a subscriber that fell behind");
+                    false
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => false,
+            });
+        }
+    }
+
+    fn close(&self) {
+        lock(&self.subscribers).take();
+        lock(&self.pending).clear();
+    }
+}
+
+/// Forward `handle`'s messages to the hub until either goes away.
+async fn pump(handle: Arc<ClientHandle>, hub: Weak<Hub>) {
+    loop {
+        let received = handle.recv_sequenced().await;
+        let Some(hub) = hub.upgrade() else {
+            return;
+        };
+        match received {
+            Ok(output) => hub.publish(output),
+            Err(e) if e.ends_output() => {
+                debug!("[BRIDGE] Session ended: {}", e);
+                hub.close();
+                return;
+            }
+            Err(e) => warn!("[BRIDGE] Skipping output: {}", e),
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
@@ -0,0 +1,237 @@
+//! Serve a session over HTTP with Server-Sent Events (requires the
+//! `sse-bridge` feature).
+//!
+//! [`SseBridge`] provides an [`axum::Router`] with these routes:
+//!
+//! | Route | |
+//! |-------|-|
+//! | `GET /events` | Event stream of every message from now on: `event` is the message type, `id` its sequence number, `data` the message JSON |
+//! | `POST /input` | Send a [`UserInput`] (`{"text": "..."}`) as the next user message; `202 Accepted` |
+//! | `GET /approvals` | The [`PendingApproval`]s still waiting for an answer |
+//! | `POST /approvals/{request_id}` | Answer a permission request with an [`Approval`] (`{"allow": true}`); `204 No Content`, or `404` if it isn't pending |
+//! | `POST /interrupt` | Stop the current turn; `202 Accepted` |
+//!
+//! A request that can't reach the CLI (because the session has ended, for
+//! example) gets `503 Service Unavailable` with the reason as text.
+//!
+//! ```no_run
+//! use claude_codes::bridge::sse::SseBridge;
+//! use claude_codes::{AsyncClient, ClaudeCliBuilder};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let builder = ClaudeCliBuilder::new().permission_prompt_tool("stdio");
+//! let handle = AsyncClient::from_builder(builder).await?.into_handle();
+//!
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:8080").await?;
+//! SseBridge::new(Arc::new(handle)).serve(listener).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Mount [`router`](SseBridge::router) inside a larger application instead
+//! of calling [`serve`](SseBridge::serve) to add authentication, CORS, or
+//! static files for the frontend.
+
+use super::{Approval, Hub, PendingApproval, UserInput};
+use crate::client_handle::{ClientHandle, Sequenced};
+use crate::error::Error;
+use crate::io::ClaudeOutput;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+
+/// Serves one session to any number of HTTP clients.
+///
+/// Must be created within a tokio runtime. The bridge becomes the only
+/// reader of the handle; don't also call [`ClientHandle::recv`] on it.
+#[derive(Clone)]
+pub struct SseBridge {
+    hub: Arc<Hub>,
+}
+
+impl SseBridge {
+    /// Start reading `handle` and passing its messages to subscribers.
+    pub fn new(handle: Arc<ClientHandle>) -> Self {
+        Self {
+            hub: Hub::start(handle),
+        }
+    }
+
+    /// The bridge's routes, for serving or nesting in another router.
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/events", get(events))
+            .route("/input", post(input))
+            .route("/approvals", get(pending))
+            .route("/approvals/{request_id}", post(approve))
+            .route("/interrupt", post(interrupt))
+            .with_state(Arc::clone(&self.hub))
+    }
+
+    /// Serve [`router`](Self::router) on `listener` until the server fails.
+    pub async fn serve(self, listener: tokio::net::TcpListener) -> std::io::Result<()> {
+        axum::serve(listener, self.router()).await
+    }
+}
+
+type Rejection = (StatusCode, String);
+
+fn unavailable(e: Error) -> Rejection {
+    (StatusCode::SERVICE_UNAVAILABLE, e.to_string())
+}
+
+async fn events(
+    State(hub): State<Arc<Hub>>,
+) -> Sse<impl futures_core::Stream<Item = Result<Event, Infallible>>> {
+    Sse::new(Subscription(hub.subscribe())).keep_alive(KeepAlive::default())
+}
+
+async fn input(
+    State(hub): State<Arc<Hub>>,
+    Json(input): Json<UserInput>,
+) -> Result<StatusCode, Rejection> {
+    hub.send_input(input).await.map_err(unavailable)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn pending(State(hub): State<Arc<Hub>>) -> Json<Vec<PendingApproval>> {
+    Json(hub.pending())
+}
+
+async fn approve(
+    State(hub): State<Arc<Hub>>,
+    Path(request_id): Path<String>,
+    Json(approval): Json<Approval>,
+) -> Result<StatusCode, Rejection> {
+    if hub
+        .approve(&request_id, &approval)
+        .await
+        .map_err(unavailable)?
+    {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            format!("no pending permission request '{}'", request_id),
+        ))
+    }
+}
+
+async fn interrupt(State(hub): State<Arc<Hub>>) -> Result<StatusCode, Rejection> {
+    hub.interrupt().await.map_err(unavailable)?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// One subscriber's messages as SSE events.
+struct Subscription(mpsc::Receiver<Arc<Sequenced<ClaudeOutput>>>);
+
+impl futures_core::Stream for Subscription {
+    type Item = Result<Event, Infallible>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.0.poll_recv(cx).map(|output| {
+            output.map(|output| {
+                let data = serde_json::to_string(&output.message).unwrap_or_default();
+                Ok(Event::default()
+                    .event(output.message.message_type())
+                    .id(output.seq.to_string())
+                    .data(data))
+            })
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::cli::ClaudeCliBuilder;
+    use crate::client_async::AsyncClient;
+    use crate::test_support::fake_cli;
+    use std::net::SocketAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// Send one HTTP request and return the whole response.
+    async fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let head = format!(
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(head.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    /// Read from `stream` until `needle` has been seen.
+    async fn read_until(stream: &mut TcpStream, seen: &mut String, needle: &str) {
+        let mut buf = [0; 4096];
+        while !seen.contains(needle) {
+            let n = stream.read(&mut buf).await.unwrap();
+            assert!(n > 0, "stream ended before {needle:?}: {seen}");
+            seen.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_events_input_and_approval() {
+        // Ask for permission on each prompt; answer with a result once the
+        // permission response arrives.
+        let script = r#"#!/bin/sh
+while read -r line; do
+  case "$line" in
+    *control_response*)
+      echo '{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}' ;;
+    *)
+      echo '{"type":"control_request","request_id":"perm-1","request":{"subtype":"can_use_tool","tool_name":"Bash","input":{"command":"ls"}}}' ;;
+  esac
+done
+"#;
+        let cli = fake_cli(script);
+
+        let client = AsyncClient::from_builder(ClaudeCliBuilder::new().command(&cli))
+            .await
+            .unwrap();
+        let bridge = SseBridge::new(Arc::new(client.into_handle()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(bridge.serve(listener));
+
+        let mut events = TcpStream::connect(addr).await.unwrap();
+        events
+            .write_all(b"GET /events HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut seen = String::new();
+        read_until(&mut events, &mut seen, "text/event-stream").await;
+
+        let response = request(addr, "POST", "/input", r#"{"text":"list files"}"#).await;
+        assert!(response.starts_with("HTTP/1.1 202"), "{response}");
+        read_until(&mut events, &mut seen, "event: control_request").await;
+        assert!(seen.contains("id: 0"));
+
+        let response = request(addr, "GET", "/approvals", "").await;
+        assert!(response.contains(r#""request_id":"perm-1""#), "{response}");
+        assert!(response.contains(r#""tool_name":"Bash""#), "{response}");
+
+        let response = request(addr, "POST", "/approvals/perm-1", r#"{"allow":true}"#).await;
+        assert!(response.starts_with("HTTP/1.1 204"), "{response}");
+        read_until(&mut events, &mut seen, "event: result").await;
+        assert!(seen.contains("id: 1"));
+
+        let response = request(addr, "POST", "/approvals/perm-1", r#"{"allow":true}"#).await;
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+
+        std::fs::remove_file(cli).ok();
+    }
+}
@@ -0,0 +1,322 @@
+//! Serve a session over a WebSocket (requires the `ws-bridge` feature).
+//!
+//! [`WsBridge`] provides an [`axum::Router`] with one route, `GET /ws`,
+//! which upgrades to a WebSocket carrying JSON text messages both ways.
+//! Instead of raw protocol messages, the session is sent as the
+//! backend-neutral [`AgentEvent`] model, so a chat UI only has to render
+//! text, reasoning, tool calls and results, and turn ends.
+//!
+//! The bridge sends [`BridgeMessage`]s:
+//!
+//! ```json
+//! {"type": "event", "seq": 4, "event": {"text": "Running the tests now."}}
+//! {"type": "approval_request", "request_id": "r-1", "tool_name": "Bash", "input": {"command": "cargo test"}}
+//! {"type": "error", "message": "no pending permission request 'r-9'"}
+//! ```
+//!
+//! and accepts [`FrontendMessage`]s:
+//!
+//! ```json
+//! {"type": "input", "text": "Now fix the failing one"}
+//! {"type": "approval", "request_id": "r-1", "allow": true}
+//! {"type": "interrupt"}
+//! ```
+//!
+//! Every event converted from one CLI message carries that message's
+//! sequence number. Permission requests still waiting for an answer are
+//! sent when a frontend connects, so one may arrive twice if it was raised
+//! at that moment. When the session ends the bridge closes the socket.
+//!
+//! ```no_run
+//! use claude_codes::bridge::ws::WsBridge;
+//! use claude_codes::{AsyncClient, ClaudeCliBuilder};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let builder = ClaudeCliBuilder::new().permission_prompt_tool("stdio");
+//! let handle = AsyncClient::from_builder(builder).await?.into_handle();
+//!
+//! let listener = tokio::net::TcpListener::bind("127.0.0.1:8080").await?;
+//! WsBridge::new(Arc::new(handle)).serve(listener).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{Approval, Hub, PendingApproval, UserInput};
+use crate::client_handle::{ClientHandle, Sequenced};
+use crate::io::{ClaudeOutput, ControlRequestPayload};
+use crate::normalize::{AgentEvent, AgentEvents};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use axum::routing::get;
+use axum::Router;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A message sent by the bridge to a frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BridgeMessage {
+    /// Something the agent did.
+    Event { seq: u64, event: AgentEvent },
+    /// A tool is waiting for permission; answer with
+    /// [`FrontendMessage::Approval`].
+    ApprovalRequest(PendingApproval),
+    /// A [`FrontendMessage`] couldn't be carried out.
+    Error { message: String },
+}
+
+/// A message sent by a frontend to the bridge.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FrontendMessage {
+    /// Send the next user message.
+    Input(UserInput),
+    /// Answer a permission request.
+    Approval {
+        request_id: String,
+        #[serde(flatten)]
+        approval: Approval,
+    },
+    /// Stop the current turn.
+    Interrupt,
+}
+
+/// Serves one session to any number of WebSocket clients.
+///
+/// Must be created within a tokio runtime. The bridge becomes the only
+/// reader of the handle; don't also call [`ClientHandle::recv`] on it.
+#[derive(Clone)]
+pub struct WsBridge {
+    hub: Arc<Hub>,
+}
+
+impl WsBridge {
+    /// Start reading `handle` and passing its messages to subscribers.
+    pub fn new(handle: Arc<ClientHandle>) -> Self {
+        Self {
+            hub: Hub::start(handle),
+        }
+    }
+
+    /// The bridge's `/ws` route, for serving or nesting in another router.
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/ws", get(upgrade))
+            .with_state(Arc::clone(&self.hub))
+    }
+
+    /// Serve [`router`](Self::router) on `listener` until the server fails.
+    pub async fn serve(self, listener: tokio::net::TcpListener) -> std::io::Result<()> {
+        axum::serve(listener, self.router()).await
+    }
+}
+
+async fn upgrade(State(hub): State<Arc<Hub>>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| connection(socket, hub))
+}
+
+/// Relay between one socket and the session until either side ends.
+async fn connection(mut socket: WebSocket, hub: Arc<Hub>) {
+    let mut outputs = hub.subscribe();
+    for pending in hub.pending() {
+        if send(&mut socket, &BridgeMessage::ApprovalRequest(pending))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+    loop {
+        tokio::select! {
+            output = outputs.recv() => {
+                let Some(output) = output else {
+                    debug!("[WS] Session ended, closing socket");
+                    break;
+                };
+                for message in outgoing(&output) {
+                    if send(&mut socket, &message).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    if let Err(message) = handle(&hub, text.as_str()).await {
+                        let reply = BridgeMessage::Error { message };
+                        if send(&mut socket, &reply).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                // Pings are answered by axum; binary messages are ignored.
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+/// The messages frontends get for one CLI message.
+fn outgoing(output: &Sequenced<ClaudeOutput>) -> Vec<BridgeMessage> {
+    let mut messages: Vec<_> = AgentEvents::from(&output.message)
+        .into_iter()
+        .map(|event| BridgeMessage::Event {
+            seq: output.seq,
+            event,
+        })
+        .collect();
+    if let ClaudeOutput::ControlRequest(ref req) = output.message {
+        if let ControlRequestPayload::CanUseTool(ref perm) = req.request {
+            messages.push(BridgeMessage::ApprovalRequest(PendingApproval {
+                request_id: req.request_id.clone(),
+                request: perm.clone(),
+            }));
+        }
+    }
+    messages
+}
+
+/// Carry out one frontend message, or say why it couldn't be.
+async fn handle(hub: &Hub, text: &str) -> Result<(), String> {
+    let message: FrontendMessage =
+        serde_json::from_str(text).map_err(|e| format!("invalid message: {}", e))?;
+    let unavailable = |e: crate::Error| e.to_string();
+    match message {
+        FrontendMessage::Input(input) => hub.send_input(input).await.map_err(unavailable),
+        FrontendMessage::Interrupt => hub.interrupt().await.map_err(unavailable),
+        FrontendMessage::Approval {
+            request_id,
+            approval,
+        } => match hub.approve(&request_id, &approval).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(format!("no pending permission request '{}'", request_id)),
+            Err(e) => Err(unavailable(e)),
+        },
+    }
+}
+
+async fn send(socket: &mut WebSocket, message: &BridgeMessage) -> Result<(), axum::Error> {
+    let json = serde_json::to_string(message).map_err(axum::Error::new)?;
+    socket.send(Message::Text(json.into())).await
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::cli::ClaudeCliBuilder;
+    use crate::client_async::AsyncClient;
+    use crate::test_support::fake_cli;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+    /// The next text message from the bridge.
+    async fn next<S>(socket: &mut S) -> BridgeMessage
+    where
+        S: futures_util::Stream<Item = Result<WsMessage, tokio_tungstenite::tungstenite::Error>>
+            + Unpin,
+    {
+        loop {
+            if let WsMessage::Text(text) = socket.next().await.unwrap().unwrap() {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_wire_shapes() {
+        let message: FrontendMessage =
+            serde_json::from_str(r#"{"type":"approval","request_id":"r-1","allow":true}"#).unwrap();
+        assert_eq!(
+            message,
+            FrontendMessage::Approval {
+                request_id: "r-1".to_string(),
+                approval: Approval {
+                    allow: true,
+                    ..Approval::default()
+                },
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(BridgeMessage::Event {
+                seq: 4,
+                event: AgentEvent::Text("hi".to_string()),
+            })
+            .unwrap(),
+            serde_json::json!({"type": "event", "seq": 4, "event": {"text": "hi"}})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_over_websocket() {
+        // Ask for permission on each prompt; reply and end the turn once the
+        // permission response arrives.
+        let script = r#"#!/bin/sh
+while read -r line; do
+  case "$line" in
+    *control_response*)
+      echo '{"type":"assistant","message":{"id":"m","role":"assistant","model":"claude-3","content":[{"type":"text","text":"Done."}]},"session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d"}'
+      echo '{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"Done.","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}' ;;
+    *)
+      echo '{"type":"control_request","request_id":"perm-1","request":{"subtype":"can_use_tool","tool_name":"Bash","input":{"command":"ls"}}}' ;;
+  esac
+done
+"#;
+        let cli = fake_cli(script);
+
+        let client = AsyncClient::from_builder(ClaudeCliBuilder::new().command(&cli))
+            .await
+            .unwrap();
+        let bridge = WsBridge::new(Arc::new(client.into_handle()));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(bridge.serve(listener));
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/ws"))
+            .await
+            .unwrap();
+        socket
+            .send(WsMessage::text(r#"{"type":"input","text":"list files"}"#))
+            .await
+            .unwrap();
+        let BridgeMessage::ApprovalRequest(request) = next(&mut socket).await else {
+            panic!("expected an approval request");
+        };
+        assert_eq!(request.request_id, "perm-1");
+        assert_eq!(request.request.tool_name, "Bash");
+
+        socket
+            .send(WsMessage::text(
+                r#"{"type":"approval","request_id":"perm-9","allow":true}"#,
+            ))
+            .await
+            .unwrap();
+        assert!(matches!(
+            next(&mut socket).await,
+            BridgeMessage::Error { message } if message == "no pending permission request 'perm-9'"
+        ));
+
+        socket
+            .send(WsMessage::text(
+                r#"{"type":"approval","request_id":"perm-1","allow":true}"#,
+            ))
+            .await
+            .unwrap();
+        assert!(matches!(
+            next(&mut socket).await,
+            BridgeMessage::Event { seq: 1, event: AgentEvent::Text(text) } if text == "Done."
+        ));
+        assert!(matches!(
+            next(&mut socket).await,
+            BridgeMessage::Event {
+                seq: 2,
+                event: AgentEvent::TurnEnd { success: true, .. },
+            }
+        ));
+
+        std::fs::remove_file(cli).ok();
+    }
+}
@@ -0,0 +1,290 @@
+//! Response caching for deterministic reruns (requires the `cache` feature).
+//!
+//! A [`ResponseCache`] maps a [`CacheKey`] — a SHA-256 hash of the prompt
+//! together with the model, system prompt, and JSON schema in a
+//! [`CacheScope`] — to the messages a query produced. Install one on a client
+//! with `set_cache` and `query()` answers repeated prompts from the cache
+//! without sending anything to the CLI. Only queries whose result succeeded
+//! are stored.
+//!
+//! Two implementations are provided: [`MemoryCache`] for a single process and
+//! [`DiskCache`], which keeps one JSON Lines file per key so batch evaluation
+//! reruns can reuse earlier answers.
+//!
+//! ```
+//! use claude_codes::cache::{CacheScope, MemoryCache, ResponseCache};
+//! use claude_codes::ClaudeOutput;
+//!
+//! let scope = CacheScope::new().model("sonnet");
+//! let key = scope.key("What is 2 + 2?");
+//! assert_ne!(key, CacheScope::new().model("opus").key("What is 2 + 2?"));
+//!
+//! let cache = MemoryCache::new();
+//! let result: ClaudeOutput = serde_json::from_str(r#"{"type":"result","subtype":"success",
+//!     "is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"4",
+//!     "session_id":"68661508-f3c4-55b4-945d-ae2b4dfe5db4","total_cost_usd":0.01}"#).unwrap();
+//! cache.put(&key, &[result]).unwrap();
+//! assert_eq!(cache.get(&key).unwrap().len(), 1);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+
+use crate::io::ClaudeOutput;
+
+/// Hash identifying one prompt under one configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    /// The key as lowercase hex, safe to use as a file name.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The session settings that change what a prompt is answered with.
+///
+/// Build one to match the client's configuration, or derive it from a
+/// `ClaudeCliBuilder` with `CacheScope::from(&builder)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CacheScope {
+    pub model: Option<String>,
+    pub system_prompt: Option<String>,
+    pub json_schema: Option<String>,
+}
+
+impl CacheScope {
+    /// A scope with no model, system prompt, or schema set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the model.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Set the system prompt.
+    pub fn system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Set the JSON schema.
+    pub fn json_schema(mut self, schema: impl Into<String>) -> Self {
+        self.json_schema = Some(schema.into());
+        self
+    }
+
+    /// The cache key for `prompt` under this scope.
+    pub fn key(&self, prompt: &str) -> CacheKey {
+        let mut hasher = Sha256::new();
+        for part in [
+            self.model.as_deref(),
+            self.system_prompt.as_deref(),
+            self.json_schema.as_deref(),
+            Some(prompt),
+        ] {
+            // Length-prefix each part so ("ab", "c") and ("a", "bc") differ,
+            // and mark absent parts distinctly from empty ones.
+            match part {
+                Some(s) => {
+                    hasher.update([1]);
+                    hasher.update((s.len() as u64).to_le_bytes());
+                    hasher.update(s.as_bytes());
+                }
+                None => hasher.update([0]),
+            }
+        }
+        let digest = hasher.finalize();
+        CacheKey(digest.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+impl From<&crate::cli::ClaudeCliBuilder> for CacheScope {
+    fn from(builder: &crate::cli::ClaudeCliBuilder) -> Self {
+        builder.cache_scope()
+    }
+}
+
+/// Storage for cached query responses.
+pub trait ResponseCache: Send + Sync {
+    /// The messages stored under `key`, if any.
+    fn get(&self, key: &CacheKey) -> Option<Vec<ClaudeOutput>>;
+
+    /// Store the messages a query produced under `key`.
+    fn put(&self, key: &CacheKey, responses: &[ClaudeOutput]) -> io::Result<()>;
+}
+
+/// An in-process [`ResponseCache`].
+#[derive(Debug, Default)]
+pub struct MemoryCache {
+    entries: Mutex<HashMap<CacheKey, Vec<ClaudeOutput>>>,
+}
+
+impl MemoryCache {
+    /// An empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of cached queries.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Whether the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove every entry.
+    pub fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+impl ResponseCache for MemoryCache {
+    fn get(&self, key: &CacheKey) -> Option<Vec<ClaudeOutput>> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+    }
+
+    fn put(&self, key: &CacheKey, responses: &[ClaudeOutput]) -> io::Result<()> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.clone(), responses.to_vec());
+        Ok(())
+    }
+}
+
+/// A [`ResponseCache`] that stores each entry as `<key>.jsonl` in a
+/// directory, one message per line.
+///
+/// Entries that can't be read or parsed are treated as misses.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// Use `dir` for cache files, creating it if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// The cache directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", key))
+    }
+}
+
+impl ResponseCache for DiskCache {
+    fn get(&self, key: &CacheKey) -> Option<Vec<ClaudeOutput>> {
+        let file = fs::File::open(self.path(key)).ok()?;
+        BufReader::new(file)
+            .lines()
+            .map(|line| serde_json::from_str(&line.ok()?).ok())
+            .collect()
+    }
+
+    fn put(&self, key: &CacheKey, responses: &[ClaudeOutput]) -> io::Result<()> {
+        // Write to a temporary file and rename so readers never see a
+        // partially written entry.
+        let tmp = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        {
+            let mut file = io::BufWriter::new(fs::File::create(&tmp)?);
+            for response in responses {
+                serde_json::to_writer(&mut file, response)?;
+                file.write_all(b"\n")?;
+            }
+            file.flush()?;
+        }
+        fs::rename(tmp, self.path(key))
+    }
+}
+
+/// Whether a query's responses are worth caching: they end in a successful
+/// result.
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub(crate) fn is_cacheable(responses: &[ClaudeOutput]) -> bool {
+    matches!(responses.last(), Some(ClaudeOutput::Result(r)) if !r.is_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(text: &str) -> ClaudeOutput {
+        serde_json::from_value(serde_json::json!({
+            "type": "result", "subtype": "success", "is_error": false,
+            "duration_ms": 1, "duration_api_ms": 1, "num_turns": 1, "result": text,
+            "session_id": "68661508-f3c4-55b4-945d-ae2b4dfe5db4", "total_cost_usd": 0.01
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_key_depends_on_every_part() {
+        let base = CacheScope::new().model("sonnet");
+        let key = base.key("hi");
+        assert_eq!(key, base.clone().key("hi"));
+        assert_eq!(key.as_str().len(), 64);
+
+        assert_ne!(key, base.key("hi "));
+        assert_ne!(key, base.clone().system_prompt("terse").key("hi"));
+        assert_ne!(key, base.clone().json_schema("{}").key("hi"));
+        assert_ne!(
+            CacheScope::new().system_prompt("").key("hi"),
+            CacheScope::new().key("hi")
+        );
+    }
+
+    #[test]
+    fn test_disk_cache_round_trip() {
+        let dir = std::env::temp_dir().join(format!("claude-codes-cache-{}", std::process::id()));
+        let cache = DiskCache::new(&dir).unwrap();
+        let key = CacheScope::new().key("2 + 2?");
+
+        assert!(cache.get(&key).is_none());
+        cache.put(&key, &[result("4")]).unwrap();
+        let cached = cache.get(&key).unwrap();
+        assert_eq!(cached[0].as_result().unwrap().result.as_deref(), Some("4"));
+
+        // A second handle on the same directory sees the entry.
+        assert!(DiskCache::new(&dir).unwrap().get(&key).is_some());
+
+        fs::write(cache.path(&key), "not json\n").unwrap();
+        assert!(cache.get(&key).is_none());
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
@@ -6,17 +6,23 @@
 //! - JSON streaming input/output formats
 //! - Non-interactive print mode
 //! - Verbose output for proper streaming
-//! - OAuth token and API key environment variables for authentication
+//! - OAuth token, API key, and bearer token environment variables for authentication
 //!
+//! Credentials and other environment settings apply to the spawned process
+//! only, so clients in the same process can run with different credentials.
 
-use crate::error::{Error, Result};
+use crate::error::{BuilderError, Error, Result};
+use crate::sandbox::SandboxSettings;
+use crate::version::ClaudeVersion;
 use log::debug;
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use uuid::Uuid;
 
 /// Permission mode for Claude CLI
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum PermissionMode {
     AcceptEdits,
     BypassPermissions,
@@ -76,6 +82,82 @@ impl OutputFormat {
     }
 }
 
+/// Model family whose default model ID can be overridden
+///
+/// The CLI resolves the `opus`, `sonnet`, and `haiku` aliases (and picks its
+/// background model) through these mappings, which is how Bedrock and Vertex
+/// deployments point the aliases at provider-specific model IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelFamily {
+    Opus,
+    Sonnet,
+    Haiku,
+}
+
+impl ModelFamily {
+    /// Environment variable the CLI reads the family's model ID from
+    pub fn env_var(&self) -> &'static str {
+        match self {
+            ModelFamily::Opus => "ANTHROPIC_DEFAULT_OPUS_MODEL",
+            ModelFamily::Sonnet => "ANTHROPIC_DEFAULT_SONNET_MODEL",
+            ModelFamily::Haiku => "ANTHROPIC_DEFAULT_HAIKU_MODEL",
+        }
+    }
+}
+
+/// Bundled builder settings for a common way of running the CLI.
+///
+/// Apply with [`ClaudeCliBuilder::preset`]. Every preset keeps the streaming
+/// JSON flags the clients rely on; they differ in who, if anyone, approves
+/// tool use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// Unattended runs such as CI jobs: nothing waits on a person.
+    ///
+    /// Sets permission mode `dontAsk`, so tools that would need approval are
+    /// denied instead of prompting (pre-approve what the job needs with
+    /// [`allowed_tools`](ClaudeCliBuilder::allowed_tools)), and
+    /// `--strict-mcp-config`, so only MCP servers passed explicitly load.
+    Ci,
+    /// A person approves tool use through the SDK.
+    ///
+    /// Sets permission mode `default` with `--permission-prompt-tool stdio`,
+    /// so approval requests arrive as `can_use_tool` control requests (call
+    /// `enable_tool_approval` on the client), and turns on file
+    /// checkpointing so edits can be rewound.
+    Interactive,
+    /// An agent working on its own in a sandbox or throwaway checkout.
+    ///
+    /// Skips permission checks entirely and turns on file checkpointing and
+    /// `--strict-mcp-config`. Only use it where the agent can't do damage.
+    Autonomous,
+}
+
+impl Preset {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Preset::Ci => "ci",
+            Preset::Interactive => "interactive",
+            Preset::Autonomous => "autonomous",
+        }
+    }
+}
+
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Cloud provider hosting the model, instead of the Anthropic API
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CloudProvider {
+    Bedrock { region: String, model: String },
+    Vertex { project: String, region: String },
+}
+
 /// Comprehensive enum of all Claude CLI flags.
 ///
 /// This enum represents every flag available in the Claude CLI (`claude --help`).
@@ -383,6 +465,8 @@ pub struct ClaudeCliBuilder {
     command: PathBuf,
     prompt: Option<String>,
     debug: Option<String>,
+    /// File the CLI writes its debug log to
+    debug_file: Option<PathBuf>,
     verbose: bool,
     dangerously_skip_permissions: bool,
     allowed_tools: Vec<String>,
@@ -398,15 +482,43 @@ pub struct ClaudeCliBuilder {
     add_dir: Vec<PathBuf>,
     ide: bool,
     strict_mcp_config: bool,
+    /// MCP server names the session may load (`allowedMcpServers` setting)
+    allowed_mcp_servers: Option<Vec<String>>,
     session_id: Option<Uuid>,
     oauth_token: Option<String>,
     api_key: Option<String>,
+    /// Bearer token sent as `Authorization` (for gateways and proxies)
+    auth_token: Option<String>,
+    /// Directory the CLI reads credentials and settings from
+    config_dir: Option<PathBuf>,
+    /// Cloud provider (Bedrock / Vertex) to route requests through
+    provider: Option<CloudProvider>,
+    /// Per-family model ID overrides
+    model_mappings: Vec<(ModelFamily, String)>,
+    /// Extra environment variables for the spawned process (`None` removes the variable)
+    env: Vec<(String, Option<String>)>,
     /// Tool for handling permission prompts (e.g., "stdio" for bidirectional control)
     permission_prompt_tool: Option<String>,
     /// Allow spawning inside another Claude Code session by unsetting CLAUDECODE env var
     allow_recursion: bool,
     /// Maximum number of tokens for extended thinking
     max_thinking_tokens: Option<u32>,
+    /// Fork the resumed/continued session into a new session ID
+    fork_session: bool,
+    /// JSON Schema the final answer must satisfy
+    json_schema: Option<String>,
+    /// Spending cap for the session in US dollars
+    max_budget_usd: Option<f64>,
+    /// Back up edited files so turns can be rewound
+    file_checkpointing: bool,
+    /// Drop resumed messages after this one
+    resume_session_at: Option<Uuid>,
+    /// Bash sandbox configuration (`sandbox` setting)
+    sandbox: Option<SandboxSettings>,
+    /// Version of the CLI to be spawned, when known
+    cli_version: Option<ClaudeVersion>,
+    /// Working directory for the spawned process
+    current_dir: Option<PathBuf>,
 }
 
 impl Default for ClaudeCliBuilder {
@@ -422,6 +534,7 @@ impl ClaudeCliBuilder {
             command: PathBuf::from("claude"),
             prompt: None,
             debug: None,
+            debug_file: None,
             verbose: false,
             dangerously_skip_permissions: false,
             allowed_tools: Vec::new(),
@@ -437,15 +550,120 @@ impl ClaudeCliBuilder {
             add_dir: Vec::new(),
             ide: false,
             strict_mcp_config: false,
+            allowed_mcp_servers: None,
             session_id: None,
             oauth_token: None,
             api_key: None,
+            auth_token: None,
+            config_dir: None,
+            provider: None,
+            model_mappings: Vec::new(),
+            env: Vec::new(),
             permission_prompt_tool: None,
             allow_recursion: false,
             max_thinking_tokens: None,
+            fork_session: false,
+            json_schema: None,
+            max_budget_usd: None,
+            file_checkpointing: false,
+            resume_session_at: None,
+            sandbox: None,
+            cli_version: None,
+            current_dir: None,
         }
     }
 
+    /// Create a builder configured from `CLAUDE_CODES_*` environment variables.
+    ///
+    /// Lets a service's deployment choose the binary, model, and permission
+    /// setup without code changes. Unset or empty variables leave the
+    /// default; anything set in code afterwards overrides the environment.
+    ///
+    /// | Variable | Setting |
+    /// |----------|---------|
+    /// | `CLAUDE_CODES_BINARY` | [`command`](Self::command) |
+    /// | `CLAUDE_CODES_PRESET` | [`preset`](Self::preset) (`ci`, `interactive`, `autonomous`), applied first |
+    /// | `CLAUDE_CODES_MODEL` | [`model`](Self::model) |
+    /// | `CLAUDE_CODES_FALLBACK_MODEL` | [`fallback_model`](Self::fallback_model) |
+    /// | `CLAUDE_CODES_PERMISSION_MODE` | [`permission_mode`](Self::permission_mode), by CLI name (`acceptEdits`, `dontAsk`, ...) |
+    /// | `CLAUDE_CODES_CONFIG_DIR` | [`config_dir`](Self::config_dir), where the CLI keeps credentials and sessions |
+    /// | `CLAUDE_CODES_API_TIMEOUT_MS` | [`api_timeout`](Self::api_timeout) |
+    /// | `CLAUDE_CODES_MCP_TIMEOUT_MS` | [`mcp_timeout`](Self::mcp_timeout) |
+    ///
+    /// Returns [`BuilderError::InvalidEnvVar`] for a value that can't be
+    /// parsed, rather than silently ignoring a typo in a deployment.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use claude_codes::ClaudeCliBuilder;
+    ///
+    /// let builder = ClaudeCliBuilder::from_env()?.allowed_tools(["Read"]);
+    /// # Ok::<(), claude_codes::BuilderError>(())
+    /// ```
+    pub fn from_env() -> std::result::Result<Self, BuilderError> {
+        Self::from_env_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_env_lookup(
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> std::result::Result<Self, BuilderError> {
+        let var = |name: &str| lookup(name).filter(|value| !value.is_empty());
+        let invalid = |name: &str, value: String| BuilderError::InvalidEnvVar {
+            name: name.to_string(),
+            value,
+        };
+        let millis = |name: &str| {
+            var(name)
+                .map(|value| match value.parse() {
+                    Ok(ms) => Ok(std::time::Duration::from_millis(ms)),
+                    Err(_) => Err(invalid(name, value)),
+                })
+                .transpose()
+        };
+
+        let mut builder = Self::new();
+        if let Some(value) = var("CLAUDE_CODES_PRESET") {
+            let preset = [Preset::Ci, Preset::Interactive, Preset::Autonomous]
+                .into_iter()
+                .find(|p| p.as_str() == value)
+                .ok_or_else(|| invalid("CLAUDE_CODES_PRESET", value))?;
+            builder = builder.preset(preset);
+        }
+        if let Some(binary) = var("CLAUDE_CODES_BINARY") {
+            builder = builder.command(binary);
+        }
+        if let Some(model) = var("CLAUDE_CODES_MODEL") {
+            builder = builder.model(model);
+        }
+        if let Some(model) = var("CLAUDE_CODES_FALLBACK_MODEL") {
+            builder = builder.fallback_model(model);
+        }
+        if let Some(value) = var("CLAUDE_CODES_PERMISSION_MODE") {
+            let mode = [
+                PermissionMode::AcceptEdits,
+                PermissionMode::BypassPermissions,
+                PermissionMode::Default,
+                PermissionMode::Delegate,
+                PermissionMode::DontAsk,
+                PermissionMode::Plan,
+            ]
+            .into_iter()
+            .find(|m| m.as_str() == value)
+            .ok_or_else(|| invalid("CLAUDE_CODES_PERMISSION_MODE", value))?;
+            builder = builder.permission_mode(mode);
+        }
+        if let Some(dir) = var("CLAUDE_CODES_CONFIG_DIR") {
+            builder = builder.config_dir(dir);
+        }
+        if let Some(timeout) = millis("CLAUDE_CODES_API_TIMEOUT_MS")? {
+            builder = builder.api_timeout(timeout);
+        }
+        if let Some(timeout) = millis("CLAUDE_CODES_MCP_TIMEOUT_MS")? {
+            builder = builder.mcp_timeout(timeout);
+        }
+        Ok(builder)
+    }
+
     /// Set custom path to Claude binary
     pub fn command<P: Into<PathBuf>>(mut self, path: P) -> Self {
         self.command = path.into();
@@ -464,7 +682,19 @@ impl ClaudeCliBuilder {
         self
     }
 
+    /// Write the CLI's debug log to `path` (`--debug-file`), enabling
+    /// debug logging.
+    ///
+    /// Keeps debug output out of the process's stdout and stderr; combine
+    /// with [`debug`](Self::debug) to pick categories.
+    pub fn debug_to_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.debug_file = Some(path.into());
+        self
+    }
+
     /// Enable verbose mode
+    ///
+    /// `--verbose` is always passed, since `stream-json` output requires it.
     pub fn verbose(mut self, verbose: bool) -> Self {
         self.verbose = verbose;
         self
@@ -533,6 +763,55 @@ impl ClaudeCliBuilder {
         self
     }
 
+    /// Resume the session recorded in the transcript at `path`
+    ///
+    /// Reads the session UUID from the transcript's `sessionId` entries
+    /// (falling back to a `<uuid>.jsonl` file name) and the project
+    /// directory from its `cwd`. The CLI looks sessions up per project, so
+    /// the recorded directory becomes the [`current_dir`](Self::current_dir)
+    /// unless one was already set.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use claude_codes::ClaudeCliBuilder;
+    ///
+    /// let builder = ClaudeCliBuilder::new()
+    ///     .resume_from_transcript("archive/2f0c6e7a-5d1b-4a8e-9c3f-0b7d2e4a6c81.jsonl")?;
+    /// # Ok::<(), claude_codes::Error>(())
+    /// ```
+    pub fn resume_from_transcript<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let (session_id, cwd) = read_transcript_session(path)?;
+        let session_id = session_id
+            .or_else(|| {
+                let stem = path.file_stem()?.to_str()?;
+                Uuid::parse_str(stem).ok().map(|id| id.to_string())
+            })
+            .ok_or_else(|| BuilderError::TranscriptWithoutSession {
+                path: path.display().to_string(),
+            })?;
+        self.resume = Some(session_id);
+        if self.current_dir.is_none() {
+            self.current_dir = cwd.map(PathBuf::from);
+        }
+        Ok(self)
+    }
+
+    /// Run the CLI in `dir` instead of this process's working directory
+    pub fn current_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Fork the resumed or continued conversation into a new session
+    ///
+    /// Required to combine [`session_id`](Self::session_id) with
+    /// [`resume`](Self::resume) or [`continue_conversation`](Self::continue_conversation).
+    pub fn fork_session(mut self, fork: bool) -> Self {
+        self.fork_session = fork;
+        self
+    }
+
     /// Set the model to use
     pub fn model<S: Into<String>>(mut self, model: S) -> Self {
         self.model = Some(model.into());
@@ -551,6 +830,44 @@ impl ClaudeCliBuilder {
         self
     }
 
+    /// Stop the session once it has spent `usd` US dollars (`--max-budget-usd`).
+    ///
+    /// The CLI ends the run with an `error_max_budget_usd` result. Usage on
+    /// models without a known price isn't counted.
+    pub fn max_budget_usd(mut self, usd: f64) -> Self {
+        self.max_budget_usd = Some(usd);
+        self
+    }
+
+    /// Require the final answer to match a JSON Schema (`--json-schema`).
+    ///
+    /// The validated answer is reported as `ResultMessage::structured_output`;
+    /// read it with `query_typed` on the clients.
+    pub fn json_schema<S: Into<String>>(mut self, schema: S) -> Self {
+        self.json_schema = Some(schema.into());
+        self
+    }
+
+    /// Back up files before each turn edits them so the turn can be undone.
+    ///
+    /// Also echoes prompts back with their `uuid` (`--replay-user-messages`);
+    /// `UserMessage::checkpoint_id` reads it, and the clients' `rewind`
+    /// restores the files to that checkpoint.
+    pub fn enable_file_checkpointing(mut self, enabled: bool) -> Self {
+        self.file_checkpointing = enabled;
+        self
+    }
+
+    /// When resuming, drop every message after the user message `message_id`
+    /// (`--resume-session-at`), rolling the conversation back to that point.
+    ///
+    /// Combine with a file rewind to the same checkpoint to restore both the
+    /// conversation and the workspace.
+    pub fn resume_session_at(mut self, message_id: Uuid) -> Self {
+        self.resume_session_at = Some(message_id);
+        self
+    }
+
     /// Load settings from file or JSON
     pub fn settings<S: Into<String>>(mut self, settings: S) -> Self {
         self.settings = Some(settings.into());
@@ -574,11 +891,79 @@ impl ClaudeCliBuilder {
     }
 
     /// Use only MCP servers from config
+    ///
+    /// With `--strict-mcp-config` the CLI loads the servers passed with
+    /// [`mcp_config`](Self::mcp_config) and ignores those in user and
+    /// project configuration.
     pub fn strict_mcp_config(mut self, strict: bool) -> Self {
         self.strict_mcp_config = strict;
         self
     }
 
+    /// Only let the named MCP servers load, wherever they are configured.
+    ///
+    /// Sent as the `allowedMcpServers` setting, merged into
+    /// [`settings`](Self::settings) when those are inline JSON; settings
+    /// given as a file path fail [`validate`](Self::validate). An empty list
+    /// allows no server. An allowlist in managed (enterprise) settings takes
+    /// precedence over this one.
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::ClaudeCliBuilder;
+    ///
+    /// let builder = ClaudeCliBuilder::new()
+    ///     .mcp_config([r#"{"mcpServers":{"docs":{"command":"docs-mcp"}}}"#])
+    ///     .strict_mcp_config(true)
+    ///     .allowed_mcp_servers(["docs"]);
+    /// assert!(builder.validate().is_ok());
+    /// ```
+    pub fn allowed_mcp_servers<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_mcp_servers = Some(names.into_iter().map(|s| s.into()).collect());
+        self
+    }
+
+    /// Run Bash commands in the CLI's OS sandbox.
+    ///
+    /// Sent as the `sandbox` setting, merged into [`settings`](Self::settings)
+    /// when those are inline JSON; settings given as a file path fail
+    /// [`validate`](Self::validate). Needs Claude CLI
+    /// [`SandboxSettings::MIN_CLI_VERSION`], which `validate` checks when the
+    /// CLI version is known (see [`cli_version`](Self::cli_version)).
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::{ClaudeCliBuilder, ClaudeVersion, SandboxSettings};
+    ///
+    /// let builder = ClaudeCliBuilder::new()
+    ///     .sandbox(SandboxSettings::locked_down())
+    ///     .cli_version(ClaudeVersion::new(2, 1, 0));
+    /// assert!(builder.validate().is_ok());
+    /// ```
+    pub fn sandbox(mut self, sandbox: SandboxSettings) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// Declare the version of the CLI that will be spawned, so
+    /// [`validate`](Self::validate) can reject options it doesn't support.
+    pub fn cli_version(mut self, version: ClaudeVersion) -> Self {
+        self.cli_version = Some(version);
+        self
+    }
+
+    /// Run the configured command with `--version` and remember the result,
+    /// as [`cli_version`](Self::cli_version) does.
+    pub fn detect_cli_version(mut self) -> Result<Self> {
+        let command = self.resolve_command()?;
+        self.cli_version = Some(crate::version::version_of(&command)?);
+        Ok(self)
+    }
+
     /// Set a specific session ID (must be a UUID)
     pub fn session_id(mut self, id: Uuid) -> Self {
         self.session_id = Some(id);
@@ -605,6 +990,103 @@ impl ClaudeCliBuilder {
         self
     }
 
+    /// Set a bearer token for authentication (`ANTHROPIC_AUTH_TOKEN`)
+    ///
+    /// Sent as an `Authorization: Bearer` header instead of `x-api-key`; used
+    /// by LLM gateways and proxies in front of the Anthropic API.
+    pub fn auth_token<S: Into<String>>(mut self, token: S) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Use a separate CLI configuration directory (`CLAUDE_CONFIG_DIR`)
+    ///
+    /// The CLI keeps its login credentials and settings there, so pointing
+    /// each client at its own directory gives it an independent `/login`
+    /// profile.
+    pub fn config_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.config_dir = Some(dir.into());
+        self
+    }
+
+    /// Route requests through Amazon Bedrock
+    ///
+    /// Sets `CLAUDE_CODE_USE_BEDROCK`, `AWS_REGION`, and `ANTHROPIC_MODEL`
+    /// (the model ID or inference profile ARN) for the spawned process. AWS
+    /// credentials are picked up by the CLI from the usual AWS sources; use
+    /// [`env`](Self::env) to set e.g. `AWS_PROFILE` per client.
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::{ClaudeCliBuilder, ModelFamily};
+    ///
+    /// let builder = ClaudeCliBuilder::new()
+    ///     .use_bedrock(
+    ///         "us-east-1",
+    ///         "arn:aws:bedrock:us-east-1:123456789012:application-inference-profile/abc",
+    ///     )
+    ///     .model_mapping(ModelFamily::Haiku, "us.anthropic.claude-haiku-4-5-20251001-v1:0");
+    /// ```
+    pub fn use_bedrock<R: Into<String>, M: Into<String>>(
+        mut self,
+        region: R,
+        model_arn: M,
+    ) -> Self {
+        self.provider = Some(CloudProvider::Bedrock {
+            region: region.into(),
+            model: model_arn.into(),
+        });
+        self
+    }
+
+    /// Route requests through Google Vertex AI
+    ///
+    /// Sets `CLAUDE_CODE_USE_VERTEX`, `ANTHROPIC_VERTEX_PROJECT_ID`, and
+    /// `CLOUD_ML_REGION` for the spawned process. Google credentials are
+    /// picked up by the CLI from application default credentials.
+    pub fn use_vertex<P: Into<String>, R: Into<String>>(mut self, project: P, region: R) -> Self {
+        self.provider = Some(CloudProvider::Vertex {
+            project: project.into(),
+            region: region.into(),
+        });
+        self
+    }
+
+    /// Override the model ID a model family alias resolves to
+    pub fn model_mapping<S: Into<String>>(mut self, family: ModelFamily, model_id: S) -> Self {
+        self.model_mappings.push((family, model_id.into()));
+        self
+    }
+
+    /// Set an environment variable on the spawned process only
+    pub fn env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.env.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Remove an inherited environment variable from the spawned process
+    pub fn env_remove<K: Into<String>>(mut self, key: K) -> Self {
+        self.env.push((key.into(), None));
+        self
+    }
+
+    /// Time out each model API request after `timeout` (`API_TIMEOUT_MS`)
+    pub fn api_timeout(self, timeout: std::time::Duration) -> Self {
+        self.env("API_TIMEOUT_MS", timeout.as_millis().to_string())
+    }
+
+    /// Give MCP servers `timeout` to start before giving up on them
+    /// (`MCP_TIMEOUT`)
+    pub fn mcp_timeout(self, timeout: std::time::Duration) -> Self {
+        self.env("MCP_TIMEOUT", timeout.as_millis().to_string())
+    }
+
+    /// Retry failed model API requests up to `retries` times
+    /// (`CLAUDE_CODE_MAX_RETRIES`)
+    pub fn max_retries(self, retries: u32) -> Self {
+        self.env("CLAUDE_CODE_MAX_RETRIES", retries.to_string())
+    }
+
     /// Enable bidirectional tool permission protocol via stdio
     ///
     /// When enabled, Claude CLI will send permission requests via stdout
@@ -624,6 +1106,43 @@ impl ClaudeCliBuilder {
         self
     }
 
+    /// Apply a [`Preset`]'s permission and MCP settings.
+    ///
+    /// The preset replaces the permission mode, permission prompt tool,
+    /// `dangerously_skip_permissions`, `strict_mcp_config`, and file
+    /// checkpointing settings; apply it first and adjust afterwards. Nothing
+    /// else (model, tools, prompts, credentials) is touched.
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::{ClaudeCliBuilder, Preset};
+    ///
+    /// let builder = ClaudeCliBuilder::new()
+    ///     .preset(Preset::Ci)
+    ///     .allowed_tools(["Read", "Grep", "Bash(cargo test:*)"])
+    ///     .model("sonnet");
+    /// assert!(builder.validate().is_ok());
+    /// ```
+    pub fn preset(mut self, preset: Preset) -> Self {
+        let (mode, prompt_tool, skip_permissions, strict_mcp, checkpointing) = match preset {
+            Preset::Ci => (Some(PermissionMode::DontAsk), None, false, true, false),
+            Preset::Interactive => (
+                Some(PermissionMode::Default),
+                Some("stdio".to_string()),
+                false,
+                false,
+                true,
+            ),
+            Preset::Autonomous => (None, None, true, true, true),
+        };
+        self.permission_mode = mode;
+        self.permission_prompt_tool = prompt_tool;
+        self.dangerously_skip_permissions = skip_permissions;
+        self.strict_mcp_config = strict_mcp;
+        self.file_checkpointing = checkpointing;
+        self
+    }
+
     /// Allow spawning inside another Claude Code session by unsetting the
     /// `CLAUDECODE` environment variable in the child process.
     #[cfg(feature = "integration-tests")]
@@ -632,18 +1151,211 @@ impl ClaudeCliBuilder {
         self
     }
 
-    /// Resolve the command path, using `which` for non-absolute paths.
+    /// The settings that key cached responses for sessions spawned from
+    /// this builder.
+    #[cfg(feature = "cache")]
+    pub(crate) fn cache_scope(&self) -> crate::cache::CacheScope {
+        crate::cache::CacheScope {
+            model: self.model.clone(),
+            system_prompt: self.append_system_prompt.clone(),
+            json_schema: self.json_schema.clone(),
+        }
+    }
+
+    /// Check for flag combinations the Claude CLI would reject.
+    ///
+    /// Called automatically by the spawn methods; call it directly to report
+    /// configuration problems before attempting to start a session.
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::{BuilderError, ClaudeCliBuilder};
+    ///
+    /// let builder = ClaudeCliBuilder::new()
+    ///     .resume(Some("abc"))
+    ///     .continue_conversation(true);
+    /// assert_eq!(builder.validate(), Err(BuilderError::ResumeAndContinue));
+    /// ```
+    pub fn validate(&self) -> std::result::Result<(), BuilderError> {
+        let resuming = self.resume.is_some() || self.continue_conversation;
+
+        if self.resume.is_some() && self.continue_conversation {
+            return Err(BuilderError::ResumeAndContinue);
+        }
+
+        if self.fork_session && !resuming {
+            return Err(BuilderError::ForkWithoutResume);
+        }
+
+        if self.session_id.is_some() && resuming && !self.fork_session {
+            return Err(BuilderError::SessionIdWithoutFork);
+        }
+
+        if self.dangerously_skip_permissions {
+            if self.permission_prompt_tool.is_some() {
+                return Err(BuilderError::PermissionPromptToolWithSkipPermissions);
+            }
+            if let Some(mode) = self.permission_mode {
+                if mode != PermissionMode::BypassPermissions {
+                    return Err(BuilderError::SkipPermissionsWithPermissionMode {
+                        mode: mode.as_str().to_string(),
+                    });
+                }
+            }
+        }
+
+        if self.resume_session_at.is_some() && self.resume.is_none() {
+            return Err(BuilderError::ResumeAtWithoutResume);
+        }
+
+        if let (Some(model), Some(fallback)) = (&self.model, &self.fallback_model) {
+            if model == fallback {
+                return Err(BuilderError::FallbackSameAsModel {
+                    model: model.clone(),
+                });
+            }
+        }
+
+        if self.allowed_mcp_servers.is_some() && self.settings_object().is_none() {
+            return Err(BuilderError::McpAllowlistWithSettingsFile);
+        }
+
+        if let Some(ref sandbox) = self.sandbox {
+            if self.settings_object().is_none() {
+                return Err(BuilderError::SandboxWithSettingsFile);
+            }
+            if let Some(version) = self.cli_version {
+                if sandbox.enabled != Some(false) && version < SandboxSettings::MIN_CLI_VERSION {
+                    return Err(BuilderError::RequiresNewerCli {
+                        option: "sandbox".to_string(),
+                        required: SandboxSettings::MIN_CLI_VERSION.to_string(),
+                        found: version.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The inline settings as a JSON object: empty when no settings are
+    /// set, `None` when they are a file path or not an object.
+    fn settings_object(&self) -> Option<serde_json::Map<String, serde_json::Value>> {
+        match self.settings {
+            None => Some(serde_json::Map::new()),
+            Some(ref settings) => serde_json::from_str(settings).ok(),
+        }
+    }
+
+    /// The `--settings` value, with the MCP allowlist and sandbox merged in.
+    fn effective_settings(&self) -> Option<String> {
+        if self.allowed_mcp_servers.is_none() && self.sandbox.is_none() {
+            return self.settings.clone();
+        }
+        let Some(mut settings) = self.settings_object() else {
+            return self.settings.clone();
+        };
+        if let Some(ref names) = self.allowed_mcp_servers {
+            let allowed = names
+                .iter()
+                .map(|name| serde_json::json!({ "serverName": name }))
+                .collect();
+            settings.insert(
+                "allowedMcpServers".to_string(),
+                serde_json::Value::Array(allowed),
+            );
+        }
+        if let Some(ref sandbox) = self.sandbox {
+            settings.insert(
+                "sandbox".to_string(),
+                serde_json::to_value(sandbox).expect("SandboxSettings serializes"),
+            );
+        }
+        Some(serde_json::Value::Object(settings).to_string())
+    }
+
+    /// Environment changes for the spawned process, in application order.
+    ///
+    /// `None` values remove the variable. When any credential is configured
+    /// on the builder, the other credential variables are removed so the
+    /// child never falls back to credentials inherited from this process.
+    pub(crate) fn env_overrides(&self) -> Vec<(String, Option<String>)> {
+        let mut env = Vec::new();
+
+        if self.allow_recursion {
+            env.push(("CLAUDECODE".to_string(), None));
+        }
+
+        let credentials = [
+            ("CLAUDE_CODE_OAUTH_TOKEN", &self.oauth_token),
+            ("ANTHROPIC_API_KEY", &self.api_key),
+            ("ANTHROPIC_AUTH_TOKEN", &self.auth_token),
+        ];
+        if credentials.iter().any(|(_, value)| value.is_some()) {
+            for (key, value) in credentials {
+                env.push((key.to_string(), value.clone()));
+            }
+        }
+
+        match &self.provider {
+            Some(CloudProvider::Bedrock { region, model }) => {
+                env.push(("CLAUDE_CODE_USE_BEDROCK".to_string(), Some("1".to_string())));
+                env.push(("AWS_REGION".to_string(), Some(region.clone())));
+                env.push(("ANTHROPIC_MODEL".to_string(), Some(model.clone())));
+            }
+            Some(CloudProvider::Vertex { project, region }) => {
+                env.push(("CLAUDE_CODE_USE_VERTEX".to_string(), Some("1".to_string())));
+                env.push((
+                    "ANTHROPIC_VERTEX_PROJECT_ID".to_string(),
+                    Some(project.clone()),
+                ));
+                env.push(("CLOUD_ML_REGION".to_string(), Some(region.clone())));
+            }
+            None => {}
+        }
+
+        if self.file_checkpointing {
+            env.push((
+                "CLAUDE_CODE_ENABLE_SDK_FILE_CHECKPOINTING".to_string(),
+                Some("true".to_string()),
+            ));
+        }
+
+        for (family, model_id) in &self.model_mappings {
+            env.push((family.env_var().to_string(), Some(model_id.clone())));
+        }
+
+        if let Some(ref dir) = self.config_dir {
+            env.push((
+                "CLAUDE_CONFIG_DIR".to_string(),
+                Some(dir.to_string_lossy().to_string()),
+            ));
+        }
+
+        env.extend(self.env.iter().cloned());
+        env
+    }
+
+    /// Resolve the command path, searching `PATH` for non-absolute paths.
+    ///
+    /// A bare `claude` that isn't on `PATH` is also looked for where the
+    /// installers put it, since GUI apps and services often run with a
+    /// minimal `PATH`.
     fn resolve_command(&self) -> Result<PathBuf> {
-        if self.command.is_absolute() {
-            return Ok(self.command.clone());
+        let mut fallbacks = Vec::new();
+        if self.command == Path::new("claude") {
+            if let Some(home) = std::env::var_os("HOME") {
+                fallbacks.push(PathBuf::from(home).join(".claude/local/claude"));
+            }
+            fallbacks.extend(codes_spawn::install_locations("claude"));
         }
-        which::which(&self.command).map_err(|_| Error::BinaryNotFound {
+        codes_spawn::resolve(&self.command, &fallbacks).ok_or_else(|| Error::BinaryNotFound {
             name: self.command.display().to_string(),
         })
     }
 
     /// Build the command arguments (always includes JSON streaming flags)
-    fn build_args(&self) -> Vec<String> {
+    pub(crate) fn build_args(&self) -> Vec<String> {
         // Always add JSON streaming mode flags
         // Note: --print with stream-json requires --verbose
         let mut args = vec![
@@ -662,6 +1374,11 @@ impl ClaudeCliBuilder {
             }
         }
 
+        if let Some(ref path) = self.debug_file {
+            args.push("--debug-file".to_string());
+            args.push(path.to_string_lossy().to_string());
+        }
+
         if self.dangerously_skip_permissions {
             args.push("--dangerously-skip-permissions".to_string());
         }
@@ -700,6 +1417,11 @@ impl ClaudeCliBuilder {
             args.push(session.clone());
         }
 
+        if let Some(id) = self.resume_session_at {
+            args.push("--resume-session-at".to_string());
+            args.push(id.to_string());
+        }
+
         if let Some(ref model) = self.model {
             args.push("--model".to_string());
             args.push(model.clone());
@@ -715,9 +1437,19 @@ impl ClaudeCliBuilder {
             args.push(tokens.to_string());
         }
 
-        if let Some(ref settings) = self.settings {
+        if let Some(usd) = self.max_budget_usd {
+            args.push("--max-budget-usd".to_string());
+            args.push(usd.to_string());
+        }
+
+        if let Some(ref schema) = self.json_schema {
+            args.push("--json-schema".to_string());
+            args.push(schema.clone());
+        }
+
+        if let Some(settings) = self.effective_settings() {
             args.push("--settings".to_string());
-            args.push(settings.clone());
+            args.push(settings);
         }
 
         if !self.add_dir.is_empty() {
@@ -740,6 +1472,14 @@ impl ClaudeCliBuilder {
             args.push(tool.clone());
         }
 
+        if self.fork_session {
+            args.push("--fork-session".to_string());
+        }
+
+        if self.file_checkpointing {
+            args.push("--replay-user-messages".to_string());
+        }
+
         // Only add --session-id when NOT resuming/continuing an existing session
         // (Claude CLI error: --session-id can only be used with --continue or --resume
         // if --fork-session is also specified)
@@ -751,6 +1491,9 @@ impl ClaudeCliBuilder {
                 uuid
             });
             args.push(session_uuid.to_string());
+        } else if let (true, Some(id)) = (self.fork_session, self.session_id) {
+            args.push("--session-id".to_string());
+            args.push(id.to_string());
         }
 
         // Add prompt as the last argument if provided
@@ -761,103 +1504,105 @@ impl ClaudeCliBuilder {
         args
     }
 
-    /// Spawn the Claude process
-    #[cfg(feature = "async-client")]
-    pub async fn spawn(self) -> Result<tokio::process::Child> {
+    /// Build the fully configured `std::process::Command`: resolved binary,
+    /// arguments, piped stdio, and environment overrides. Shared by every
+    /// spawn path so the sync client never depends on tokio.
+    fn std_command(&self) -> Result<std::process::Command> {
+        self.validate()?;
         let resolved = self.resolve_command()?;
         let args = self.build_args();
 
-        debug!(
-            "[CLI] Executing command: {} {}",
-            resolved.display(),
-            args.join(" ")
-        );
-
-        let mut cmd = tokio::process::Command::new(&resolved);
+        let mut cmd = std::process::Command::new(&resolved);
         cmd.args(&args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
 
-        if self.allow_recursion {
-            cmd.env_remove("CLAUDECODE");
-        }
-
-        if let Some(ref token) = self.oauth_token {
-            cmd.env("CLAUDE_CODE_OAUTH_TOKEN", token);
+        codes_spawn::apply_env(&mut cmd, self.env_overrides());
+        if let Some(dir) = &self.current_dir {
+            cmd.current_dir(dir);
         }
 
-        if let Some(ref key) = self.api_key {
-            cmd.env("ANTHROPIC_API_KEY", key);
-        }
-
-        let child = cmd.spawn().map_err(Error::Io)?;
+        Ok(cmd)
+    }
 
-        Ok(child)
+    /// Spawn the Claude process
+    #[cfg(feature = "async-client")]
+    pub async fn spawn(self) -> Result<tokio::process::Child> {
+        let mut cmd = self.build_command()?;
+        debug!(
+            "[CLI] Executing command: {}",
+            describe_command(cmd.as_std())
+        );
+        cmd.spawn().map_err(Error::Io)
     }
 
     /// Build a Command without spawning (for testing or manual execution)
     #[cfg(feature = "async-client")]
     pub fn build_command(self) -> Result<tokio::process::Command> {
-        let resolved = self.resolve_command()?;
-        let args = self.build_args();
-        let mut cmd = tokio::process::Command::new(&resolved);
-        cmd.args(&args)
+        self.std_command().map(tokio::process::Command::from)
+    }
+
+    /// Spawn the Claude process with `async-process`, for smol-based
+    /// applications. Pair with [`AsyncClient::from_smol_child`].
+    ///
+    /// [`AsyncClient::from_smol_child`]: crate::AsyncClient::from_smol_child
+    #[cfg(feature = "smol")]
+    pub fn spawn_smol(self) -> Result<async_process::Child> {
+        let cmd = self.std_command()?;
+        debug!("[CLI] Executing smol command: {}", describe_command(&cmd));
+        // `From<std::process::Command>` does not carry stdio settings over.
+        async_process::Command::from(cmd)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        if self.allow_recursion {
-            cmd.env_remove("CLAUDECODE");
-        }
-
-        if let Some(ref token) = self.oauth_token {
-            cmd.env("CLAUDE_CODE_OAUTH_TOKEN", token);
-        }
-
-        if let Some(ref key) = self.api_key {
-            cmd.env("ANTHROPIC_API_KEY", key);
-        }
-
-        Ok(cmd)
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(Error::Io)
     }
 
     /// Spawn the Claude process using synchronous std::process
     pub fn spawn_sync(self) -> Result<std::process::Child> {
-        let resolved = self.resolve_command()?;
-        let args = self.build_args();
-
-        debug!(
-            "[CLI] Executing sync command: {} {}",
-            resolved.display(),
-            args.join(" ")
-        );
-
-        let mut cmd = std::process::Command::new(&resolved);
-        cmd.args(&args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        if self.allow_recursion {
-            cmd.env_remove("CLAUDECODE");
-        }
+        let mut cmd = self.std_command()?;
+        debug!("[CLI] Executing sync command: {}", describe_command(&cmd));
+        cmd.spawn().map_err(Error::Io)
+    }
+}
 
-        if let Some(ref token) = self.oauth_token {
-            cmd.env("CLAUDE_CODE_OAUTH_TOKEN", token);
-        }
+/// Render a command as `program arg1 arg2 ...` for debug logging.
+fn describe_command(cmd: &std::process::Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|s| s.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
-        if let Some(ref key) = self.api_key {
-            cmd.env("ANTHROPIC_API_KEY", key);
+/// Scan a JSONL transcript for the first `sessionId` and `cwd` it records.
+/// Lines that aren't JSON objects (or lack the fields, like `summary`
+/// entries) are skipped.
+fn read_transcript_session(path: &Path) -> Result<(Option<String>, Option<String>)> {
+    use std::io::BufRead;
+
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let (mut session_id, mut cwd) = (None, None);
+    for line in reader.lines() {
+        let Ok(entry) = serde_json::from_str::<serde_json::Value>(&line?) else {
+            continue;
+        };
+        let field = |name| entry.get(name).and_then(|v| v.as_str()).map(String::from);
+        session_id = session_id.or_else(|| field("sessionId"));
+        cwd = cwd.or_else(|| field("cwd"));
+        if session_id.is_some() && cwd.is_some() {
+            break;
         }
-
-        cmd.spawn().map_err(Error::Io)
     }
+    Ok((session_id, cwd))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::ffi::OsStr;
 
     #[test]
     fn test_streaming_flags_always_present() {
@@ -893,6 +1638,15 @@ mod tests {
         assert!(args.contains(&"opus".to_string()));
     }
 
+    #[test]
+    fn test_with_json_schema() {
+        let schema = r#"{"type":"object"}"#;
+        let args = ClaudeCliBuilder::new().json_schema(schema).build_args();
+
+        let pos = args.iter().position(|a| a == "--json-schema").unwrap();
+        assert_eq!(args[pos + 1], schema);
+    }
+
     #[test]
     fn test_with_debug() {
         let builder = ClaudeCliBuilder::new().debug(Some("api"));
@@ -902,6 +1656,92 @@ mod tests {
         assert!(args.contains(&"api".to_string()));
     }
 
+    #[test]
+    fn test_allowed_mcp_servers() {
+        let settings_arg = |builder: &ClaudeCliBuilder| {
+            let args = builder.build_args();
+            let pos = args.iter().position(|a| a == "--settings").unwrap();
+            serde_json::from_str::<serde_json::Value>(&args[pos + 1]).unwrap()
+        };
+
+        let builder = ClaudeCliBuilder::new().allowed_mcp_servers(["docs", "git"]);
+        assert_eq!(
+            settings_arg(&builder),
+            serde_json::json!({"allowedMcpServers": [{"serverName": "docs"}, {"serverName": "git"}]})
+        );
+
+        let builder = ClaudeCliBuilder::new()
+            .settings(r#"{"model":"sonnet"}"#)
+            .allowed_mcp_servers(Vec::<String>::new());
+        assert!(builder.validate().is_ok());
+        assert_eq!(
+            settings_arg(&builder),
+            serde_json::json!({"model": "sonnet", "allowedMcpServers": []})
+        );
+
+        let builder = ClaudeCliBuilder::new()
+            .settings("/etc/claude/settings.json")
+            .allowed_mcp_servers(["docs"]);
+        assert!(matches!(
+            builder.validate(),
+            Err(BuilderError::McpAllowlistWithSettingsFile)
+        ));
+    }
+
+    #[test]
+    fn test_sandbox_settings_and_version_check() {
+        let builder = ClaudeCliBuilder::new()
+            .settings(r#"{"model":"sonnet"}"#)
+            .allowed_mcp_servers(["docs"])
+            .sandbox(SandboxSettings::enabled());
+        let args = builder.build_args();
+        let pos = args.iter().position(|a| a == "--settings").unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&args[pos + 1]).unwrap(),
+            serde_json::json!({
+                "model": "sonnet",
+                "allowedMcpServers": [{"serverName": "docs"}],
+                "sandbox": {"enabled": true}
+            })
+        );
+
+        let old = ClaudeCliBuilder::new()
+            .sandbox(SandboxSettings::locked_down())
+            .cli_version(ClaudeVersion::new(1, 0, 89));
+        assert_eq!(
+            old.validate(),
+            Err(BuilderError::RequiresNewerCli {
+                option: "sandbox".to_string(),
+                required: "2.0.24".to_string(),
+                found: "1.0.89".to_string(),
+            })
+        );
+        // Turning the sandbox off is fine on any version.
+        let off = SandboxSettings {
+            enabled: Some(false),
+            ..SandboxSettings::default()
+        };
+        assert!(old.sandbox(off).validate().is_ok());
+
+        let builder = ClaudeCliBuilder::new()
+            .settings("/etc/claude/settings.json")
+            .sandbox(SandboxSettings::enabled());
+        assert_eq!(
+            builder.validate(),
+            Err(BuilderError::SandboxWithSettingsFile)
+        );
+    }
+
+    #[test]
+    fn test_debug_to_file() {
+        let builder = ClaudeCliBuilder::new().debug_to_file("/tmp/claude-debug.txt");
+        let args = builder.build_args();
+
+        let pos = args.iter().position(|a| a == "--debug-file").unwrap();
+        assert_eq!(args[pos + 1], "/tmp/claude-debug.txt");
+        assert!(!args.contains(&"--debug".to_string()));
+    }
+
     #[test]
     fn test_with_oauth_token() {
         let valid_token = "sk-ant-oat-123456789";
@@ -954,6 +1794,86 @@ mod tests {
         assert_eq!(builder.api_key, Some(api_key.to_string()));
     }
 
+    #[test]
+    fn test_no_env_overrides_by_default() {
+        assert!(ClaudeCliBuilder::new().env_overrides().is_empty());
+    }
+
+    #[test]
+    fn test_credential_isolates_inherited_credentials() {
+        let env = ClaudeCliBuilder::new()
+            .auth_token("bearer-123")
+            .env_overrides();
+
+        assert!(env.contains(&(
+            "ANTHROPIC_AUTH_TOKEN".to_string(),
+            Some("bearer-123".to_string())
+        )));
+        assert!(env.contains(&("ANTHROPIC_API_KEY".to_string(), None)));
+        assert!(env.contains(&("CLAUDE_CODE_OAUTH_TOKEN".to_string(), None)));
+    }
+
+    #[test]
+    fn test_config_dir_and_custom_env() {
+        let env = ClaudeCliBuilder::new()
+            .config_dir("/tmp/tenant-a")
+            .env("HTTPS_PROXY", "http://proxy:8080")
+            .env_remove("DEBUG")
+            .env_overrides();
+
+        assert_eq!(
+            env,
+            vec![
+                (
+                    "CLAUDE_CONFIG_DIR".to_string(),
+                    Some("/tmp/tenant-a".to_string())
+                ),
+                (
+                    "HTTPS_PROXY".to_string(),
+                    Some("http://proxy:8080".to_string())
+                ),
+                ("DEBUG".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_use_bedrock_env() {
+        let env = ClaudeCliBuilder::new()
+            .use_bedrock(
+                "us-west-2",
+                "arn:aws:bedrock:us-west-2:1:inference-profile/x",
+            )
+            .model_mapping(ModelFamily::Haiku, "us.anthropic.claude-haiku")
+            .env_overrides();
+
+        assert!(env.contains(&("CLAUDE_CODE_USE_BEDROCK".to_string(), Some("1".to_string()))));
+        assert!(env.contains(&("AWS_REGION".to_string(), Some("us-west-2".to_string()))));
+        assert!(env.contains(&(
+            "ANTHROPIC_MODEL".to_string(),
+            Some("arn:aws:bedrock:us-west-2:1:inference-profile/x".to_string())
+        )));
+        assert!(env.contains(&(
+            "ANTHROPIC_DEFAULT_HAIKU_MODEL".to_string(),
+            Some("us.anthropic.claude-haiku".to_string())
+        )));
+    }
+
+    #[test]
+    fn test_use_vertex_env() {
+        let env = ClaudeCliBuilder::new()
+            .use_vertex("my-project", "us-east5")
+            .env_overrides();
+
+        assert!(env.contains(&("CLAUDE_CODE_USE_VERTEX".to_string(), Some("1".to_string()))));
+        assert!(env.contains(&(
+            "ANTHROPIC_VERTEX_PROJECT_ID".to_string(),
+            Some("my-project".to_string())
+        )));
+        assert!(env.contains(&("CLOUD_ML_REGION".to_string(), Some("us-east5".to_string()))));
+        assert!(!env.iter().any(|(k, _)| k == "CLAUDE_CODE_USE_BEDROCK"));
+    }
+
     #[test]
     fn test_permission_prompt_tool() {
         let builder = ClaudeCliBuilder::new().permission_prompt_tool("stdio");
@@ -1014,4 +1934,289 @@ mod tests {
             "--session-id should NOT be present when continuing"
         );
     }
+
+    #[test]
+    fn test_from_env() {
+        let vars = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs
+                    .iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+
+        let builder = ClaudeCliBuilder::from_env_lookup(vars(&[
+            ("CLAUDE_CODES_BINARY", "/opt/claude/bin/claude"),
+            ("CLAUDE_CODES_PRESET", "ci"),
+            ("CLAUDE_CODES_MODEL", "opus"),
+            ("CLAUDE_CODES_FALLBACK_MODEL", ""),
+            ("CLAUDE_CODES_PERMISSION_MODE", "acceptEdits"),
+            ("CLAUDE_CODES_CONFIG_DIR", "/var/lib/claude"),
+            ("CLAUDE_CODES_API_TIMEOUT_MS", "90000"),
+        ]))
+        .unwrap();
+        assert_eq!(builder.command, PathBuf::from("/opt/claude/bin/claude"));
+        assert_eq!(builder.model.as_deref(), Some("opus"));
+        assert_eq!(builder.fallback_model, None);
+        // The explicit permission mode overrides the preset's.
+        assert_eq!(builder.permission_mode, Some(PermissionMode::AcceptEdits));
+        assert!(builder.strict_mcp_config);
+        let env = builder.env_overrides();
+        assert!(env.contains(&(
+            "CLAUDE_CONFIG_DIR".to_string(),
+            Some("/var/lib/claude".to_string())
+        )));
+        assert!(env.contains(&("API_TIMEOUT_MS".to_string(), Some("90000".to_string()))));
+
+        let id = Uuid::new_v4();
+        let unset = ClaudeCliBuilder::from_env_lookup(|_| None)
+            .unwrap()
+            .session_id(id);
+        assert_eq!(
+            unset.build_args(),
+            ClaudeCliBuilder::new().session_id(id).build_args()
+        );
+
+        assert_eq!(
+            ClaudeCliBuilder::from_env_lookup(vars(&[(
+                "CLAUDE_CODES_PERMISSION_MODE",
+                "accept_edits"
+            )]))
+            .unwrap_err(),
+            BuilderError::InvalidEnvVar {
+                name: "CLAUDE_CODES_PERMISSION_MODE".to_string(),
+                value: "accept_edits".to_string(),
+            }
+        );
+        assert!(matches!(
+            ClaudeCliBuilder::from_env_lookup(vars(&[("CLAUDE_CODES_MCP_TIMEOUT_MS", "5s")])),
+            Err(BuilderError::InvalidEnvVar { .. })
+        ));
+    }
+
+    #[test]
+    fn test_presets() {
+        let flag_value = |args: &[String], flag: &str| {
+            args.iter()
+                .position(|a| a == flag)
+                .map(|i| args[i + 1].clone())
+        };
+
+        let ci = ClaudeCliBuilder::new()
+            .dangerously_skip_permissions(true)
+            .preset(Preset::Ci);
+        assert!(ci.validate().is_ok());
+        let args = ci.build_args();
+        assert_eq!(
+            flag_value(&args, "--permission-mode").as_deref(),
+            Some("dontAsk")
+        );
+        assert!(args.contains(&"--strict-mcp-config".to_string()));
+        assert!(!args.contains(&"--dangerously-skip-permissions".to_string()));
+        assert!(!args.contains(&"--permission-prompt-tool".to_string()));
+
+        let interactive = ClaudeCliBuilder::new().preset(Preset::Interactive);
+        assert!(interactive.validate().is_ok());
+        let args = interactive.build_args();
+        assert_eq!(
+            flag_value(&args, "--permission-prompt-tool").as_deref(),
+            Some("stdio")
+        );
+        assert!(args.contains(&"--replay-user-messages".to_string()));
+
+        // Settings applied after the preset win.
+        let autonomous = ClaudeCliBuilder::new()
+            .preset(Preset::Autonomous)
+            .strict_mcp_config(false);
+        assert!(autonomous.validate().is_ok());
+        let args = autonomous.build_args();
+        assert!(args.contains(&"--dangerously-skip-permissions".to_string()));
+        assert!(!args.contains(&"--permission-mode".to_string()));
+        assert!(!args.contains(&"--strict-mcp-config".to_string()));
+        assert_eq!(Preset::Autonomous.to_string(), "autonomous");
+    }
+
+    #[test]
+    fn test_validate_default_builder() {
+        assert_eq!(ClaudeCliBuilder::new().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_resume_and_continue() {
+        let builder = ClaudeCliBuilder::new()
+            .resume(Some("existing-uuid"))
+            .continue_conversation(true);
+        assert_eq!(builder.validate(), Err(BuilderError::ResumeAndContinue));
+    }
+
+    #[test]
+    fn test_validate_session_id_with_resume_requires_fork() {
+        let id = Uuid::new_v4();
+        let builder = ClaudeCliBuilder::new()
+            .resume(Some("existing-uuid"))
+            .session_id(id);
+        assert_eq!(builder.validate(), Err(BuilderError::SessionIdWithoutFork));
+
+        let forked = builder.fork_session(true);
+        assert_eq!(forked.validate(), Ok(()));
+        let args = forked.build_args();
+        assert!(args.contains(&"--fork-session".to_string()));
+        assert!(args.contains(&id.to_string()));
+    }
+
+    #[test]
+    fn test_validate_fork_without_resume() {
+        let builder = ClaudeCliBuilder::new().fork_session(true);
+        assert_eq!(builder.validate(), Err(BuilderError::ForkWithoutResume));
+    }
+
+    #[test]
+    fn test_file_checkpointing_and_resume_at() {
+        let builder = ClaudeCliBuilder::new().enable_file_checkpointing(true);
+        assert!(builder
+            .build_args()
+            .contains(&"--replay-user-messages".to_string()));
+        assert!(builder.env_overrides().contains(&(
+            "CLAUDE_CODE_ENABLE_SDK_FILE_CHECKPOINTING".to_string(),
+            Some("true".to_string())
+        )));
+
+        let id = Uuid::new_v4();
+        let builder = ClaudeCliBuilder::new().resume_session_at(id);
+        assert_eq!(builder.validate(), Err(BuilderError::ResumeAtWithoutResume));
+        let args = builder.resume(Some("abc")).build_args();
+        let pos = args
+            .iter()
+            .position(|a| a == "--resume-session-at")
+            .unwrap();
+        assert_eq!(args[pos + 1], id.to_string());
+    }
+
+    #[test]
+    fn test_validate_skip_permissions_conflicts() {
+        let builder = ClaudeCliBuilder::new()
+            .dangerously_skip_permissions(true)
+            .permission_prompt_tool("stdio");
+        assert_eq!(
+            builder.validate(),
+            Err(BuilderError::PermissionPromptToolWithSkipPermissions)
+        );
+
+        let builder = ClaudeCliBuilder::new()
+            .dangerously_skip_permissions(true)
+            .permission_mode(PermissionMode::Plan);
+        assert_eq!(
+            builder.validate(),
+            Err(BuilderError::SkipPermissionsWithPermissionMode {
+                mode: "plan".to_string()
+            })
+        );
+
+        let builder = ClaudeCliBuilder::new()
+            .dangerously_skip_permissions(true)
+            .permission_mode(PermissionMode::BypassPermissions);
+        assert_eq!(builder.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_fallback_same_as_model() {
+        let builder = ClaudeCliBuilder::new().model("opus").fallback_model("opus");
+        assert_eq!(
+            builder.validate(),
+            Err(BuilderError::FallbackSameAsModel {
+                model: "opus".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_spawn_sync_rejects_invalid_builder() {
+        let err = ClaudeCliBuilder::new()
+            .command("/nonexistent/claude")
+            .resume(Some("existing-uuid"))
+            .continue_conversation(true)
+            .spawn_sync()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Builder(BuilderError::ResumeAndContinue)
+        ));
+    }
+
+    #[test]
+    fn test_std_command_carries_args_and_env() {
+        let cmd = ClaudeCliBuilder::new()
+            .command("/opt/claude/bin/claude")
+            .model("sonnet")
+            .env("DEBUG", "1")
+            .env_remove("HTTP_PROXY")
+            .std_command()
+            .unwrap();
+
+        assert_eq!(cmd.get_program(), "/opt/claude/bin/claude");
+        let args: Vec<_> = cmd.get_args().map(|a| a.to_string_lossy()).collect();
+        assert!(args.windows(2).any(|w| w == ["--model", "sonnet"]));
+
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.contains(&(OsStr::new("DEBUG"), Some(OsStr::new("1")))));
+        assert!(envs.contains(&(OsStr::new("HTTP_PROXY"), None)));
+        assert!(describe_command(&cmd).starts_with("/opt/claude/bin/claude --print"));
+    }
+
+    #[test]
+    fn test_resume_from_transcript() {
+        let dir =
+            std::env::temp_dir().join(format!("claude-codes-transcript-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("archived.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"type":"summary","summary":"Fix the build","leafUuid":"u-1"}"#,
+                "\n",
+                r#"{"type":"user","sessionId":"2f0c6e7a-5d1b-4a8e-9c3f-0b7d2e4a6c81","cwd":"/repo","message":{"role":"user","content":"hi"}}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let builder = ClaudeCliBuilder::new()
+            .resume_from_transcript(&path)
+            .unwrap();
+        let args = builder.build_args();
+        let pos = args.iter().position(|a| a == "--resume").unwrap();
+        assert_eq!(args[pos + 1], "2f0c6e7a-5d1b-4a8e-9c3f-0b7d2e4a6c81");
+        assert_eq!(builder.current_dir, Some(PathBuf::from("/repo")));
+
+        // An explicit working directory wins over the recorded one.
+        let builder = ClaudeCliBuilder::new()
+            .current_dir("/elsewhere")
+            .resume_from_transcript(&path)
+            .unwrap();
+        assert_eq!(builder.current_dir, Some(PathBuf::from("/elsewhere")));
+
+        // Without a recorded session ID the file name is used, if it's a UUID.
+        let named = dir.join("0b7d2e4a-6c81-4a8e-9c3f-2f0c6e7a5d1b.jsonl");
+        std::fs::write(&named, "{\"type\":\"summary\"}\n").unwrap();
+        let builder = ClaudeCliBuilder::new()
+            .resume_from_transcript(&named)
+            .unwrap();
+        assert_eq!(
+            builder.resume.as_deref(),
+            Some("0b7d2e4a-6c81-4a8e-9c3f-2f0c6e7a5d1b")
+        );
+        assert_eq!(builder.current_dir, None);
+
+        let unnamed = dir.join("empty.jsonl");
+        std::fs::write(&unnamed, "").unwrap();
+        assert!(matches!(
+            ClaudeCliBuilder::new().resume_from_transcript(&unnamed),
+            Err(Error::Builder(
+                BuilderError::TranscriptWithoutSession { .. }
+            ))
+        ));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
@@ -1,61 +1,166 @@
 //! Asynchronous client for Claude communication
 
 use crate::cli::ClaudeCliBuilder;
-use crate::error::{Error, Result};
+use crate::client_state::{ClientState, ClientStateTracker};
+use crate::debug_sink::{DebugSink, Direction};
+use crate::error::{CrashReport, Error, Result};
+use crate::framing::FrameReader;
+use crate::input_queue::{DispatchTrigger, InputDispatched, InputQueue};
 use crate::io::{
-    ClaudeInput, ClaudeOutput, ContentBlock, ControlRequestMessage, ControlResponse,
-    ControlResponseMessage,
+    AddDirectoryResult, AuthProblem, ClaudeInput, ClaudeOutput, ContentBlock,
+    ControlRequestMessage, ControlRequestPayload, ControlResponse, ControlResponseMessage,
+    ControlResponsePayload, LenientUuid, ParseErrorAction, ParseErrorHandler, PermissionModeName,
+    RewindFilesResult, SpillPolicy,
 };
+use crate::mcp::McpServer;
+use crate::progress::TurnProgress;
 use crate::protocol::Protocol;
+use crate::retry::{RetryPolicy, TurnRetried, TurnRetrier};
+use crate::runtime::{ChildParts, ChildProcess, PipeReader, PipeWriter};
+use crate::session_index::{SessionIndex, SessionRecorder};
+use crate::shadow::ShadowLog;
+use crate::tokens::TokenBudget;
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufReader as AsyncBufReader};
-use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout};
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::io::{
+    AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, BufReader as AsyncBufReader,
+};
+use tokio::sync::watch;
 use uuid::Uuid;
 
+/// Buffered reader over the CLI's stderr, as returned by
+/// [`AsyncClient::take_stderr`].
+pub type StderrReader = BufReader<Box<dyn tokio::io::AsyncRead + Send + Unpin>>;
+
 /// Asynchronous client for communicating with Claude
 pub struct AsyncClient {
-    child: Child,
-    stdin: ChildStdin,
-    stdout: BufReader<ChildStdout>,
-    stderr: Option<BufReader<ChildStderr>>,
+    child: Box<dyn ChildProcess>,
+    stdin: PipeWriter,
+    stdout: BufReader<PipeReader>,
+    stderr: Option<StderrReader>,
     session_uuid: Option<Uuid>,
     /// Whether tool approval protocol has been initialized
     tool_approval_enabled: bool,
+    /// Mirror of raw wire traffic, if installed
+    debug_sink: Option<DebugSink>,
+    /// Reused buffer holding the most recent stdout line
+    frames: FrameReader,
+    #[cfg(feature = "otel")]
+    tracer: crate::otel::SessionTracer,
+    /// In-process MCP servers answering `mcp_message` control requests, by name
+    mcp_servers: HashMap<String, McpServer>,
+    /// Input held back until the current turn ends
+    input_queue: InputQueue,
+    /// Where oversized tool results are moved, if anywhere
+    spill_policy: Option<SpillPolicy>,
+    /// Decides whether unparseable lines are skipped (the default) or returned
+    parse_error_handler: Option<ParseErrorHandler>,
+    /// Index that sessions seen by this client are recorded in
+    session_recorder: Option<SessionRecorder>,
+    /// Log that tool uses are denied into, in shadow mode
+    shadow: Option<ShadowLog>,
+    /// Limit on the estimated size of user messages sent
+    prompt_budget: Option<TokenBudget>,
+    /// Re-sends turns that fail on transient API errors, if a policy is set
+    retry: TurnRetrier,
+    /// Response cache consulted by `query`, with the scope that keys it
+    #[cfg(feature = "cache")]
+    cache: Option<(
+        std::sync::Arc<dyn crate::cache::ResponseCache>,
+        crate::cache::CacheScope,
+    )>,
+    /// What the session is doing, derived from the message flow
+    state: ClientStateTracker,
+    /// Publishes `state` to [`watch_state`](Self::watch_state) receivers
+    state_tx: watch::Sender<ClientState>,
+    /// Progress of the current turn, for [`watch_progress`](Self::watch_progress)
+    progress_tx: watch::Sender<TurnProgress>,
 }
 
 /// Buffer size for reading Claude's stdout (10MB).
 const STDOUT_BUFFER_SIZE: usize = 10 * 1024 * 1024;
 
+/// How long to wait for the process to exit after stdout closes before
+/// inspecting stderr for the reason.
+const EXIT_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_millis(500);
+
 impl AsyncClient {
     /// Create a new async client from a tokio Child process
-    pub fn new(mut child: Child) -> Result<Self> {
-        let stdin = child
-            .stdin
-            .take()
-            .ok_or_else(|| Error::Io(std::io::Error::other("Failed to get stdin handle")))?;
-
-        let stdout = BufReader::with_capacity(
-            STDOUT_BUFFER_SIZE,
-            child
-                .stdout
-                .take()
-                .ok_or_else(|| Error::Io(std::io::Error::other("Failed to get stdout handle")))?,
-        );
+    #[cfg(feature = "async-client")]
+    pub fn new(child: tokio::process::Child) -> Result<Self> {
+        Ok(Self::from_parts(
+            ChildParts::from_tokio(child).map_err(Error::Io)?,
+        ))
+    }
+
+    /// Create a new async client from a smol (`async-process`) Child process.
+    ///
+    /// The client does not need a tokio runtime; drive it from any executor
+    /// with `async-io`'s reactor, e.g. `smol::block_on`.
+    #[cfg(feature = "smol")]
+    pub fn from_smol_child(child: async_process::Child) -> Result<Self> {
+        Ok(Self::from_parts(
+            ChildParts::from_smol(child).map_err(Error::Io)?,
+        ))
+    }
 
-        let stderr = child.stderr.take().map(BufReader::new);
+    /// Spawn the CLI described by `builder` with `async-process` and wrap it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use claude_codes::{AsyncClient, ClaudeCliBuilder};
+    ///
+    /// # fn main() -> claude_codes::Result<()> {
+    /// async_io::block_on(async {
+    ///     let builder = ClaudeCliBuilder::new().model("sonnet");
+    ///     let mut client = AsyncClient::from_builder_smol(builder)?;
+    ///     for output in client.query("Hello!").await? {
+    ///         println!("{}", output);
+    ///     }
+    ///     Ok(())
+    /// })
+    /// # }
+    /// ```
+    #[cfg(feature = "smol")]
+    pub fn from_builder_smol(builder: ClaudeCliBuilder) -> Result<Self> {
+        let child = builder.spawn_smol()?;
+        info!("Started Claude process (smol) from custom builder");
+        Self::from_smol_child(child)
+    }
 
-        Ok(Self {
-            child,
-            stdin,
-            stdout,
-            stderr,
+    fn from_parts(parts: ChildParts) -> Self {
+        Self {
+            child: parts.child,
+            stdin: parts.stdin,
+            stdout: BufReader::with_capacity(STDOUT_BUFFER_SIZE, parts.stdout),
+            stderr: parts.stderr.map(BufReader::new),
             session_uuid: None,
             tool_approval_enabled: false,
-        })
+            debug_sink: None,
+            frames: FrameReader::new().with_history(CrashReport::STDOUT_LINES),
+            #[cfg(feature = "otel")]
+            tracer: crate::otel::SessionTracer::new(),
+            mcp_servers: HashMap::new(),
+            input_queue: InputQueue::default(),
+            spill_policy: None,
+            parse_error_handler: None,
+            session_recorder: None,
+            shadow: None,
+            prompt_budget: None,
+            retry: TurnRetrier::default(),
+            #[cfg(feature = "cache")]
+            cache: None,
+            state: ClientStateTracker::new(),
+            state_tx: watch::channel(ClientState::Starting).0,
+            progress_tx: watch::channel(TurnProgress::new()).0,
+        }
     }
 
     /// Create a client with default settings (using logic from start_claude)
+    #[cfg(feature = "async-client")]
     pub async fn with_defaults() -> Result<Self> {
         // Check Claude version (only warns once per session)
         // NOTE: The claude-codes API is in high flux. If you wish to work around
@@ -67,6 +172,7 @@ impl AsyncClient {
     }
 
     /// Create a client with a specific model
+    #[cfg(feature = "async-client")]
     pub async fn with_model(model: &str) -> Result<Self> {
         let child = ClaudeCliBuilder::new().model(model).spawn().await?;
 
@@ -75,6 +181,7 @@ impl AsyncClient {
     }
 
     /// Create a client from a custom builder
+    #[cfg(feature = "async-client")]
     pub async fn from_builder(builder: ClaudeCliBuilder) -> Result<Self> {
         let child = builder.spawn().await?;
         info!("Started Claude process from custom builder");
@@ -83,6 +190,7 @@ impl AsyncClient {
 
     /// Resume a previous session by UUID
     /// This creates a new client that resumes an existing session
+    #[cfg(feature = "async-client")]
     pub async fn resume_session(session_uuid: Uuid) -> Result<Self> {
         let child = ClaudeCliBuilder::new()
             .resume(Some(session_uuid.to_string()))
@@ -97,6 +205,7 @@ impl AsyncClient {
     }
 
     /// Resume a previous session with a specific model
+    #[cfg(feature = "async-client")]
     pub async fn resume_session_with_model(session_uuid: Uuid, model: &str) -> Result<Self> {
         let child = ClaudeCliBuilder::new()
             .model(model)
@@ -127,6 +236,19 @@ impl AsyncClient {
         text: &str,
         session_id: Uuid,
     ) -> Result<Vec<ClaudeOutput>> {
+        #[cfg(feature = "cache")]
+        let cached = self
+            .cache
+            .as_ref()
+            .map(|(cache, scope)| (cache.clone(), scope.key(text)));
+        #[cfg(feature = "cache")]
+        if let Some((cache, key)) = &cached {
+            if let Some(responses) = cache.get(key) {
+                debug!("[CACHE] Hit for {}", key);
+                return Ok(responses);
+            }
+        }
+
         // Send the query
         let input = ClaudeInput::user_message(text, session_id);
         self.send(&input).await?;
@@ -144,9 +266,33 @@ impl AsyncClient {
             }
         }
 
+        #[cfg(feature = "cache")]
+        if let Some((cache, key)) = cached {
+            if crate::cache::is_cacheable(&responses) {
+                if let Err(e) = cache.put(&key, &responses) {
+                    warn!("[CACHE] Failed to store {}: {}", key, e);
+                }
+            }
+        }
+
         Ok(responses)
     }
 
+    /// Send a query and deserialize its structured output.
+    ///
+    /// The client must have been spawned with
+    /// [`ClaudeCliBuilder::json_schema`]; see
+    /// [`ResultMessage::structured_output_as`](crate::ResultMessage::structured_output_as).
+    pub async fn query_typed<T: serde::de::DeserializeOwned>(&mut self, text: &str) -> Result<T> {
+        let responses = self.query(text).await?;
+        responses
+            .iter()
+            .rev()
+            .find_map(ClaudeOutput::as_result)
+            .ok_or_else(|| Error::MissingField("result".to_string()))?
+            .structured_output_as()
+    }
+
     /// Send a query and return an async iterator over responses
     /// Returns a stream that yields ClaudeOutput until Result message is received
     pub async fn query_stream(&mut self, text: &str) -> Result<ResponseStream<'_>> {
@@ -172,19 +318,255 @@ impl AsyncClient {
     }
 
     /// Send a ClaudeInput directly
+    ///
+    /// Fails with [`Error::PromptTooLarge`], without sending, if the input is
+    /// over the [prompt budget](Self::set_prompt_budget).
     pub async fn send(&mut self, input: &ClaudeInput) -> Result<()> {
+        if let Some(budget) = &self.prompt_budget {
+            budget.check(input)?;
+        }
         let json_line = Protocol::serialize(input)?;
         debug!("[OUTGOING] Sending JSON to Claude: {}", json_line.trim());
+        self.write_line(&json_line).await?;
+        self.input_queue.on_send(input);
+        self.retry.on_send(input);
+        if self.state.on_input(input) {
+            self.publish_state();
+        }
+        self.progress_tx
+            .send_if_modified(|progress| progress.on_input(input));
+
+        #[cfg(feature = "otel")]
+        self.tracer.record_input(input);
+        Ok(())
+    }
+
+    /// Send `input` now if no turn is in progress, otherwise hold it until
+    /// the turn's Result arrives.
+    ///
+    /// Queued input is sent, oldest first, by the [`receive`](Self::receive)
+    /// call that returns the Result, or earlier by
+    /// [`flush_queued_input`](Self::flush_queued_input). A turn is in progress
+    /// from sending a user message until its Result is received. See
+    /// [`crate::input_queue`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use claude_codes::{AsyncClient, ClaudeInput};
+    ///
+    /// # async fn example(client: &mut AsyncClient) -> claude_codes::Result<()> {
+    /// let session = client.session_uuid()?;
+    /// client.send(&ClaudeInput::user_message("Refactor main.rs", session)).await?;
+    /// // The user types again before the answer is done:
+    /// client.queue_input(ClaudeInput::user_message("Also add tests", session)).await?;
+    ///
+    /// loop {
+    ///     let output = client.receive().await?;
+    ///     for event in client.take_dispatched_input() {
+    ///         println!("sent queued input after {:?}", event.queued_for);
+    ///     }
+    ///     // A Result ends the session's work only if no queued input followed it.
+    ///     if output.as_result().is_some() && !client.is_turn_active() {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn queue_input(&mut self, input: ClaudeInput) -> Result<()> {
+        if self.input_queue.is_turn_active() {
+            debug!(
+                "[QUEUE] Turn in progress, queueing {} input(s)",
+                self.input_queue.len() + 1
+            );
+            self.input_queue.push(input);
+            Ok(())
+        } else {
+            self.send(&input).await
+        }
+    }
+
+    /// Send every queued input now, even mid-turn. Returns how many were sent.
+    pub async fn flush_queued_input(&mut self) -> Result<usize> {
+        self.dispatch_queued(DispatchTrigger::Flushed).await
+    }
+
+    /// Number of inputs waiting for the current turn to end.
+    pub fn queued_input_len(&self) -> usize {
+        self.input_queue.len()
+    }
 
+    /// Whether a user message has been sent and its Result not yet received.
+    pub fn is_turn_active(&self) -> bool {
+        self.input_queue.is_turn_active()
+    }
+
+    /// Take the events for queued input sent since the last call.
+    pub fn take_dispatched_input(&mut self) -> Vec<InputDispatched> {
+        self.input_queue.take_dispatched()
+    }
+
+    async fn dispatch_queued(&mut self, trigger: DispatchTrigger) -> Result<usize> {
+        let pending = self.input_queue.drain();
+        let count = pending.len();
+        for (input, queued_at) in pending {
+            debug!("[QUEUE] Dispatching queued input ({})", trigger);
+            self.send(&input).await?;
+            self.input_queue.record_dispatch(input, queued_at, trigger);
+        }
+        Ok(count)
+    }
+
+    /// Write a serialized line to stdin, mirroring it to the debug sink.
+    async fn write_line(&mut self, json_line: &str) -> Result<()> {
+        if let Some(sink) = &mut self.debug_sink {
+            sink.record(Direction::Outbound, json_line);
+        }
         self.stdin
             .write_all(json_line.as_bytes())
             .await
             .map_err(Error::Io)?;
-
         self.stdin.flush().await.map_err(Error::Io)?;
         Ok(())
     }
 
+    /// Answer repeated [`query`](Self::query) calls from `cache`.
+    ///
+    /// Responses are keyed by the prompt text under `scope`, which should
+    /// describe how this client was spawned (see
+    /// [`CacheScope::from`](crate::cache::CacheScope)). A hit returns the
+    /// stored messages without contacting the CLI; only queries that end in a
+    /// successful result are stored.
+    #[cfg(feature = "cache")]
+    pub fn set_cache(
+        &mut self,
+        cache: std::sync::Arc<dyn crate::cache::ResponseCache>,
+        scope: crate::cache::CacheScope,
+    ) {
+        self.cache = Some((cache, scope));
+    }
+
+    /// Record every session this client starts in `index`, tagged with
+    /// `tags`, as soon as the CLI reports the session ID.
+    ///
+    /// Failures to update the index are logged and don't interrupt the
+    /// session. See [`crate::session_index`].
+    pub fn set_session_index<I, S>(&mut self, index: SessionIndex, tags: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let tags = tags.into_iter().map(Into::into).collect();
+        self.session_recorder = Some(SessionRecorder::new(index, tags));
+    }
+
+    /// Deny every tool use, writing each one to `log` instead.
+    ///
+    /// `can_use_tool` permission requests are answered by the client with
+    /// [`SHADOW_DENIAL`](crate::shadow::SHADOW_DENIAL) and never returned.
+    /// See [`crate::shadow`].
+    pub fn set_shadow_mode(&mut self, log: ShadowLog) {
+        self.shadow = Some(log);
+    }
+
+    /// Stop shadow mode; permission requests are returned again.
+    pub fn clear_shadow_mode(&mut self) {
+        self.shadow = None;
+    }
+
+    /// Refuse to send user messages whose estimated size exceeds `budget`.
+    ///
+    /// Sending one fails with [`Error::PromptTooLarge`] and nothing is
+    /// written to the CLI. See [`crate::tokens`].
+    pub fn set_prompt_budget(&mut self, budget: TokenBudget) {
+        self.prompt_budget = Some(budget);
+    }
+
+    /// Send user messages of any size again.
+    pub fn clear_prompt_budget(&mut self) {
+        self.prompt_budget = None;
+    }
+
+    /// Send a turn's user message again when the turn ends in a transient
+    /// API error (a 5xx or overloaded response), as `policy` allows.
+    ///
+    /// The failed Result is not returned by [`receive`](Self::receive);
+    /// each retry is reported by [`take_turn_retries`](Self::take_turn_retries)
+    /// instead. See [`crate::retry`].
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry.set_policy(Some(policy));
+    }
+
+    /// Return every failed Result again, without retrying.
+    pub fn clear_retry_policy(&mut self) {
+        self.retry.set_policy(None);
+    }
+
+    /// Take the events for turns retried since the last call.
+    pub fn take_turn_retries(&mut self) -> Vec<TurnRetried> {
+        self.retry.take_retried()
+    }
+
+    /// Write tool results larger than the policy's threshold to disk as they
+    /// are received, keeping only a [`LargeContent`](crate::io::LargeContent)
+    /// handle in memory. See [`crate::io::SpillPolicy`].
+    pub fn set_spill_policy(&mut self, policy: SpillPolicy) {
+        self.spill_policy = Some(policy);
+    }
+
+    /// Refuse output lines longer than `max_bytes`.
+    ///
+    /// An oversized line is skipped without being buffered and reported as
+    /// [`Error::MessageTooLarge`]; the next receive continues with the
+    /// following message. Unlimited by default.
+    pub fn set_max_message_bytes(&mut self, max_bytes: usize) {
+        self.frames.set_max_bytes(Some(max_bytes));
+    }
+
+    /// Decide what happens to output lines that don't parse as a
+    /// [`ClaudeOutput`].
+    ///
+    /// `handler` gets the [`ParseError`](crate::io::ParseError), which holds
+    /// the raw line, and returns [`ParseErrorAction::Skip`] to drop it and
+    /// keep receiving or [`ParseErrorAction::Abort`] to return it as
+    /// [`Error::Deserialization`]. Without a handler, such lines are logged
+    /// and skipped.
+    pub fn on_parse_error(
+        &mut self,
+        handler: impl FnMut(&crate::io::ParseError) -> ParseErrorAction + Send + 'static,
+    ) {
+        self.parse_error_handler = Some(Box::new(handler));
+    }
+
+    /// Mirror every raw line sent to and received from Claude into `sink`.
+    ///
+    /// Each line is prefixed with a timestamp and a direction marker (`>>` for
+    /// outbound, `<<` for inbound); see [`crate::debug_sink`]. Replaces any
+    /// previously installed sink.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: &mut claude_codes::AsyncClient) {
+    /// client.set_debug_sink(std::io::stderr());
+    /// # }
+    /// ```
+    pub fn set_debug_sink(&mut self, sink: impl std::io::Write + Send + 'static) {
+        self.debug_sink = Some(DebugSink::new(sink));
+    }
+
+    /// Mirror raw wire traffic into the file at `path`, appending if it exists.
+    pub fn set_debug_sink_file(&mut self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        self.debug_sink = Some(DebugSink::file(path)?);
+        Ok(())
+    }
+
+    /// Stop mirroring raw wire traffic.
+    pub fn clear_debug_sink(&mut self) {
+        self.debug_sink = None;
+    }
+
     /// Send an interrupt to gracefully stop the current response.
     ///
     /// This writes `{ "subtype": "interrupt" }` to stdin, telling Claude
@@ -193,6 +575,144 @@ impl AsyncClient {
         self.send(&ClaudeInput::interrupt()).await
     }
 
+    /// Restore the files edited since `checkpoint_id` to their earlier
+    /// contents.
+    ///
+    /// `checkpoint_id` is the `uuid` of the user message that started the
+    /// turn (see [`UserMessage::checkpoint_id`](crate::UserMessage::checkpoint_id));
+    /// the session must have been spawned with
+    /// [`ClaudeCliBuilder::enable_file_checkpointing`]. A refused rewind is
+    /// returned with `can_rewind: false` and the reason in `error`.
+    ///
+    /// Only the workspace is rewound. To roll back the conversation too,
+    /// resume the session with
+    /// [`ClaudeCliBuilder::resume_session_at`] at the same id.
+    ///
+    /// Call this between turns: messages that arrive while waiting for the
+    /// CLI's answer are dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use claude_codes::{AsyncClient, ClaudeCliBuilder, ClaudeOutput};
+    ///
+    /// # #[cfg(feature = "async-client")]
+    /// # async fn example() -> claude_codes::Result<()> {
+    /// let builder = ClaudeCliBuilder::new().enable_file_checkpointing(true);
+    /// let mut client = AsyncClient::from_builder(builder).await?;
+    ///
+    /// let checkpoint = client
+    ///     .query("Refactor main.rs")
+    ///     .await?
+    ///     .iter()
+    ///     .find_map(|o| match o {
+    ///         ClaudeOutput::User(msg) => msg.checkpoint_id(),
+    ///         _ => None,
+    ///     });
+    /// if let Some(id) = checkpoint {
+    ///     let result = client.rewind(id).await?;
+    ///     println!("restored {:?}", result.files_changed);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn rewind(&mut self, checkpoint_id: Uuid) -> Result<RewindFilesResult> {
+        self.rewind_files(checkpoint_id, false).await
+    }
+
+    /// Report which files [`rewind`](Self::rewind) would restore, without
+    /// changing any.
+    pub async fn preview_rewind(&mut self, checkpoint_id: Uuid) -> Result<RewindFilesResult> {
+        self.rewind_files(checkpoint_id, true).await
+    }
+
+    /// Switch the session's permission mode, e.g. into
+    /// [`Plan`](PermissionModeName::Plan) before a planning turn.
+    ///
+    /// Applies from the next tool call on. Call this between turns: messages
+    /// that arrive while waiting for the CLI's answer are dropped.
+    pub async fn set_permission_mode(&mut self, mode: PermissionModeName) -> Result<()> {
+        let request =
+            ControlRequestMessage::set_permission_mode(format!("mode-{}", Uuid::new_v4()), mode);
+        self.control_request(request).await?;
+        Ok(())
+    }
+
+    /// Grant the session access to another directory, as `--add-dir` does at
+    /// startup, e.g. when the user opens another folder in an IDE.
+    ///
+    /// Returns the CLI's confirmation; a refused directory (one that doesn't
+    /// exist, say) is an [`Error::Protocol`]. Call this between turns:
+    /// messages that arrive while waiting for the answer are dropped.
+    pub async fn add_directory(&mut self, path: impl AsRef<Path>) -> Result<AddDirectoryResult> {
+        let request = ControlRequestMessage::add_directory(
+            format!("add-dir-{}", Uuid::new_v4()),
+            path.as_ref().to_string_lossy(),
+        );
+        let response = self.control_request(request).await?;
+        Ok(match response {
+            Some(response) => serde_json::from_value(response)?,
+            None => AddDirectoryResult::default(),
+        })
+    }
+
+    async fn rewind_files(
+        &mut self,
+        checkpoint_id: Uuid,
+        dry_run: bool,
+    ) -> Result<RewindFilesResult> {
+        let request = ControlRequestMessage::rewind_files(
+            format!("rewind-{}", Uuid::new_v4()),
+            checkpoint_id.to_string(),
+            dry_run,
+        );
+        let response = self
+            .control_request(request)
+            .await?
+            .ok_or_else(|| Error::MissingField("rewind_files response".to_string()))?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Send a control request and wait for the CLI's response to it,
+    /// dropping any other messages in between.
+    async fn control_request(
+        &mut self,
+        request: ControlRequestMessage,
+    ) -> Result<Option<serde_json::Value>> {
+        let json_line = Protocol::serialize(&request)?;
+        debug!("[CONTROL] Sending control request: {}", json_line.trim());
+        self.write_line(&json_line).await?;
+
+        loop {
+            match self.read_frame_line().await {
+                Err(Error::ConnectionClosed) => return Err(self.closed_error().await),
+                other => other?,
+            }
+            match ClaudeOutput::parse_json_tolerant(self.frames.frame())? {
+                ClaudeOutput::ControlResponse(resp) => match resp.response {
+                    ControlResponsePayload::Success {
+                        request_id,
+                        response,
+                    } if request_id == request.request_id => return Ok(response),
+                    ControlResponsePayload::Error { request_id, error }
+                        if request_id == request.request_id =>
+                    {
+                        return Err(Error::Protocol(format!(
+                            "control request failed: {}",
+                            error
+                        )))
+                    }
+                    _ => continue,
+                },
+                other => debug!(
+                    "[CONTROL] Dropping {} while waiting for {}",
+                    other.message_type(),
+                    request.request_id
+                ),
+            }
+        }
+    }
+
     /// Receive a single response from Claude.
     ///
     /// # Important: Polling Frequency
@@ -202,74 +722,233 @@ impl AsyncClient {
     /// if the pipe buffer overflows, data may be truncated.
     ///
     /// In a `tokio::select!` loop with other async operations, ensure `receive()`
-    /// is given priority or called frequently. For high-throughput scenarios,
-    /// consider spawning a dedicated task to drain stdout into an unbounded channel.
+    /// is given priority or called frequently. This method is not cancel-safe:
+    /// a message partly read when another branch wins is lost. Use
+    /// [`into_handle`](Self::into_handle) for a cancel-safe `recv(&self)` fed
+    /// by a task that drains stdout as it arrives.
     ///
     /// # Returns
     ///
     /// - `Ok(ClaudeOutput)` - A parsed message from Claude
     /// - `Err(Error::ConnectionClosed)` - Claude process has exited
-    /// - `Err(Error::Deserialization)` - Failed to parse the message
+    /// - `Err(Error::NotAuthenticated)` - The CLI reported missing or invalid credentials
+    /// - `Err(Error::Crashed)` - The CLI exited with a failure status; the
+    ///   [`CrashReport`] holds its last stdout lines and the end of stderr
+    /// - `Err(Error::Deserialization)` - Failed to parse the message, when the
+    ///   [`on_parse_error`](Self::on_parse_error) handler chose to abort (by
+    ///   default unparseable lines are skipped)
+    ///
+    /// `mcp_message` control requests for servers added with
+    /// [`register_mcp_server`](Self::register_mcp_server) are answered
+    /// internally and never returned, as are `can_use_tool` requests in
+    /// [shadow mode](Self::set_shadow_mode) and Results replaced by a
+    /// [retry](Self::set_retry_policy).
     pub async fn receive(&mut self) -> Result<ClaudeOutput> {
-        let trimmed = self.read_frame_line().await?;
-        debug!("[INCOMING] Received JSON from Claude: {}", trimmed);
+        loop {
+            if let Some(wait) = self.retry.pending_wait() {
+                self.child.sleep(wait).await;
+                self.send_pending_retry().await?;
+            }
+            if let Some(output) = self.receive_next().await? {
+                return Ok(output);
+            }
+        }
+    }
 
-        // Use the parse_json_tolerant method which handles ANSI escape codes
-        match ClaudeOutput::parse_json_tolerant(&trimmed) {
-            Ok(output) => {
-                debug!("[INCOMING] Parsed output type: {}", output.message_type());
-
-                // Capture UUID from first response if not already set
-                if self.session_uuid.is_none() {
-                    if let ClaudeOutput::Assistant(ref msg) = output {
-                        if let Some(ref uuid_str) = msg.uuid {
-                            if let Ok(uuid) = Uuid::parse_str(uuid_str) {
-                                debug!("[INCOMING] Captured session UUID: {}", uuid);
-                                self.session_uuid = Some(uuid);
-                            }
-                        }
-                    } else if let ClaudeOutput::Result(ref msg) = output {
-                        if let Some(ref uuid_str) = msg.uuid {
-                            if let Ok(uuid) = Uuid::parse_str(uuid_str) {
-                                debug!("[INCOMING] Captured session UUID: {}", uuid);
-                                self.session_uuid = Some(uuid);
-                            }
-                        }
+    /// [`receive`](Self::receive) without waiting out a retry: returns
+    /// `Ok(None)` once a failed turn is set up to be sent again, leaving the
+    /// caller to call [`send_pending_retry`](Self::send_pending_retry) when
+    /// [`pending_retry_wait`](Self::pending_retry_wait) has elapsed.
+    pub(crate) async fn receive_next(&mut self) -> Result<Option<ClaudeOutput>> {
+        loop {
+            let Some(output) = self.receive_message().await? else {
+                return Ok(None);
+            };
+            if let Some(response) = self.shadow.as_mut().and_then(|log| log.intercept(&output)) {
+                self.send_control_response(response).await?;
+                continue;
+            }
+            if let ClaudeOutput::ControlRequest(ref req) = output {
+                if let ControlRequestPayload::McpMessage(ref mcp) = req.request {
+                    if let Some(server) = self.mcp_servers.get(&mcp.server_name).cloned() {
+                        debug!(
+                            "[MCP] Routing {} to in-process server '{}'",
+                            mcp.method().unwrap_or("response"),
+                            mcp.server_name
+                        );
+                        let response = server.handle_control_request(mcp, &req.request_id).await;
+                        self.send_control_response(response).await?;
+                        continue;
+                    }
+                }
+            }
+            return Ok(Some(output));
+        }
+    }
+
+    /// Time left before a retried turn is sent again, if one is waiting.
+    #[cfg(feature = "async-client")]
+    pub(crate) fn pending_retry_wait(&self) -> Option<std::time::Duration> {
+        self.retry.pending_wait()
+    }
+
+    /// Send the input of a retried turn now, if one is waiting.
+    pub(crate) async fn send_pending_retry(&mut self) -> Result<()> {
+        match self.retry.take_pending() {
+            Some(input) => self.send(&input).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Read and parse the next message, without MCP routing. Returns
+    /// `Ok(None)` for a Result replaced by a pending retry.
+    async fn receive_message(&mut self) -> Result<Option<ClaudeOutput>> {
+        loop {
+            match self.read_frame_line().await {
+                Err(Error::ConnectionClosed) => {
+                    if self.state.on_closed() {
+                        self.publish_state();
+                    }
+                    return Err(self.closed_error().await);
+                }
+                other => other?,
+            }
+            let trimmed = self.frames.frame();
+            debug!("[INCOMING] Received JSON from Claude: {}", trimmed);
+
+            // Use the parse_json_tolerant method which handles ANSI escape codes
+            let mut output = match ClaudeOutput::parse_json_tolerant(trimmed) {
+                Ok(output) => output,
+                Err(parse_error) => {
+                    warn!("[INCOMING] Failed to deserialize message from Claude CLI. Please report this at https://github.com/meawoppl/rust-claude-codes/issues with the raw message below.");
+                    warn!("[INCOMING] Parse error: {}", parse_error.error_message);
+                    warn!("[INCOMING] Raw message: {}", trimmed);
+                    let action = self
+                        .parse_error_handler
+                        .as_mut()
+                        .map_or(ParseErrorAction::Skip, |handler| handler(&parse_error));
+                    if action == ParseErrorAction::Abort {
+                        return Err(parse_error.into());
                     }
+                    debug!("[INCOMING] Skipping unparseable message");
+                    continue;
+                }
+            };
+            debug!("[INCOMING] Parsed output type: {}", output.message_type());
+
+            if let Some(policy) = &self.spill_policy {
+                if let Err(e) = output.spill_large_tool_results(policy) {
+                    warn!("[INCOMING] Failed to spill large tool result: {}", e);
                 }
+            }
+
+            #[cfg(feature = "otel")]
+            self.tracer.record_output(&output);
+
+            if let Some(recorder) = &mut self.session_recorder {
+                recorder.observe(&output);
+            }
+
+            if self.state.on_output(&output) {
+                self.publish_state();
+            }
+            self.progress_tx
+                .send_if_modified(|progress| progress.record(&output));
+
+            if let Some((problem, message)) = output.auth_problem() {
+                warn!("[INCOMING] Claude CLI is not authenticated: {}", message);
+                return Err(Error::NotAuthenticated { problem, message });
+            }
 
-                Ok(output)
+            // Capture UUID from first response if not already set
+            if self.session_uuid.is_none() {
+                if let ClaudeOutput::Assistant(ref msg) = output {
+                    if let Some(uuid) = msg.uuid.as_ref().and_then(LenientUuid::as_uuid) {
+                        debug!("[INCOMING] Captured session UUID: {}", uuid);
+                        self.session_uuid = Some(uuid);
+                    }
+                } else if let ClaudeOutput::Result(ref msg) = output {
+                    if let Some(uuid) = msg.uuid.as_ref().and_then(LenientUuid::as_uuid) {
+                        debug!("[INCOMING] Captured session UUID: {}", uuid);
+                        self.session_uuid = Some(uuid);
+                    }
+                }
             }
-            Err(parse_error) => {
-                warn!("[INCOMING] Failed to deserialize message from Claude CLI. Please report this at https://github.com/meawoppl/rust-claude-codes/issues with the raw message below.");
-                warn!("[INCOMING] Parse error: {}", parse_error.error_message);
-                warn!("[INCOMING] Raw message: {}", trimmed);
-                Err(parse_error.into())
+
+            if let ClaudeOutput::Result(ref result) = output {
+                if let Some(delay) = self.retry.on_result(result) {
+                    warn!(
+                        "[RETRY] Turn failed on a transient API error, sending it again in {:?}",
+                        delay
+                    );
+                    return Ok(None);
+                }
+                self.input_queue.on_result();
+                self.dispatch_queued(DispatchTrigger::TurnEnded).await?;
             }
+
+            return Ok(Some(output));
         }
     }
 
-    /// Read the next non-empty line from Claude's stdout, trimmed.
+    /// Wait until stdout has data (or is at EOF) without consuming any.
+    ///
+    /// Unlike [`receive`](Self::receive), this is cancel-safe.
+    #[cfg(feature = "async-client")]
+    pub(crate) async fn wait_readable(&mut self) -> Result<()> {
+        self.stdout.fill_buf().await.map_err(Error::Io)?;
+        Ok(())
+    }
+
+    /// Read the next non-empty line from Claude's stdout into `self.frames`.
     ///
-    /// Returns `Err(Error::ConnectionClosed)` at EOF. Shared by [`receive`] and
-    /// [`receive_raw`].
+    /// The line, trimmed, is then available from `self.frames.frame()` until
+    /// the next read; the buffer is reused so steady-state reads don't
+    /// allocate. Returns `Err(Error::ConnectionClosed)` at EOF. Shared by
+    /// [`receive`] and [`receive_raw`].
     ///
     /// [`receive`]: Self::receive
     /// [`receive_raw`]: Self::receive_raw
-    async fn read_frame_line(&mut self) -> Result<String> {
-        let mut line = String::new();
-        loop {
-            line.clear();
-            let bytes_read = self.stdout.read_line(&mut line).await.map_err(Error::Io)?;
-            if bytes_read == 0 {
-                return Err(Error::ConnectionClosed);
-            }
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
+    async fn read_frame_line(&mut self) -> Result<()> {
+        let frame = self
+            .frames
+            .read_frame_async(&mut self.stdout)
+            .await?
+            .ok_or(Error::ConnectionClosed)?;
+        if let Some(sink) = &mut self.debug_sink {
+            sink.record(Direction::Inbound, frame);
+        }
+        Ok(())
+    }
+
+    /// Work out why stdout closed.
+    ///
+    /// If the process has exited and its stderr reports an authentication
+    /// failure, returns `Error::NotAuthenticated`; if it exited with a
+    /// failure status, `Error::Crashed`; otherwise `Error::ConnectionClosed`.
+    /// Stderr is only read if it was not taken.
+    async fn closed_error(&mut self) -> Error {
+        let status = match self.child.wait_timeout(EXIT_GRACE_PERIOD).await {
+            Some(Ok(status)) => status,
+            _ => return Error::ConnectionClosed,
+        };
+        let mut text = String::new();
+        if let Some(stderr) = self.stderr.as_mut() {
+            if let Err(e) = stderr.read_to_string(&mut text).await {
+                debug!("[INCOMING] Failed to read stderr after exit: {}", e);
             }
-            return Ok(trimmed.to_string());
         }
+        if let Some(problem) = AuthProblem::detect(&text) {
+            return Error::NotAuthenticated {
+                problem,
+                message: text.trim().to_string(),
+            };
+        }
+        if status.success() {
+            return Error::ConnectionClosed;
+        }
+        let lines = self.frames.recent().map(str::to_string).collect();
+        Error::Crashed(Box::new(CrashReport::new(status.code(), lines, &text)))
     }
 
     /// Receive the next frame as a raw `serde_json::Value`, before it is mapped
@@ -280,15 +959,10 @@ impl AsyncClient {
     /// captures every field the CLI emitted. Applies the same leading-prefix
     /// tolerance as [`receive`](Self::receive).
     pub async fn receive_raw(&mut self) -> Result<serde_json::Value> {
-        let trimmed = self.read_frame_line().await?;
-        match serde_json::from_str::<serde_json::Value>(&trimmed) {
-            Ok(value) => Ok(value),
-            Err(e) => match trimmed.find('{') {
-                Some(start) => Ok(serde_json::from_str::<serde_json::Value>(&trimmed[start..])
-                    .map_err(|_| Error::Json(e))?),
-                None => Err(Error::Json(e)),
-            },
-        }
+        self.read_frame_line().await?;
+        codes_codec::Flavor::JsonLines
+            .decode(self.frames.frame())
+            .map_err(Error::Json)
     }
 
     /// Check if the Claude process is still running
@@ -299,6 +973,8 @@ impl AsyncClient {
     /// Gracefully shutdown the client
     pub async fn shutdown(mut self) -> Result<()> {
         info!("Shutting down Claude process...");
+        #[cfg(feature = "otel")]
+        self.tracer.end();
         self.child.kill().await.map_err(Error::Io)?;
         Ok(())
     }
@@ -309,7 +985,7 @@ impl AsyncClient {
     }
 
     /// Take the stderr reader (can only be called once)
-    pub fn take_stderr(&mut self) -> Option<BufReader<ChildStderr>> {
+    pub fn take_stderr(&mut self) -> Option<StderrReader> {
         self.stderr.take()
     }
 
@@ -394,6 +1070,7 @@ impl AsyncClient {
     /// ```no_run
     /// use claude_codes::{AsyncClient, ClaudeCliBuilder, ClaudeOutput, ControlRequestPayload};
     ///
+    /// # #[cfg(feature = "async-client")]
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let child = ClaudeCliBuilder::new()
     ///     .model("sonnet")
@@ -420,26 +1097,12 @@ impl AsyncClient {
 
         debug!("[TOOL_APPROVAL] Sending initialization handshake");
         let json_line = Protocol::serialize(&init_request)?;
-        self.stdin
-            .write_all(json_line.as_bytes())
-            .await
-            .map_err(Error::Io)?;
-        self.stdin.flush().await.map_err(Error::Io)?;
+        self.write_line(&json_line).await?;
 
         // Wait for the initialization response
         loop {
-            let mut line = String::new();
-            let bytes_read = self.stdout.read_line(&mut line).await.map_err(Error::Io)?;
-
-            if bytes_read == 0 {
-                return Err(Error::ConnectionClosed);
-            }
-
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-
+            self.read_frame_line().await?;
+            let trimmed = self.frames.frame();
             debug!("[TOOL_APPROVAL] Received: {}", trimmed);
 
             // Try to parse as ClaudeOutput
@@ -511,15 +1174,78 @@ impl AsyncClient {
             "[TOOL_APPROVAL] Sending control response: {}",
             json_line.trim()
         );
-
-        self.stdin
-            .write_all(json_line.as_bytes())
-            .await
-            .map_err(Error::Io)?;
-        self.stdin.flush().await.map_err(Error::Io)?;
+        self.write_line(&json_line).await?;
+        if self.state.on_control_response(&message.response) {
+            self.publish_state();
+        }
         Ok(())
     }
 
+    /// What the session is doing right now (see [`crate::client_state`]).
+    pub fn state(&self) -> ClientState {
+        self.state.state()
+    }
+
+    /// Subscribe to [`state`](Self::state) changes.
+    ///
+    /// The receiver sees the current state immediately and is notified each
+    /// time it changes; changes are published as messages are sent and
+    /// received, so something must keep calling [`receive`](Self::receive)
+    /// (or own the client through a [`ClientHandle`](crate::ClientHandle)).
+    pub fn watch_state(&self) -> watch::Receiver<ClientState> {
+        self.state_tx.subscribe()
+    }
+
+    /// Subscribe to progress updates for the current turn.
+    ///
+    /// The receiver is notified when a turn starts and for each message it
+    /// produces, without taking messages from [`receive`](Self::receive); as
+    /// with [`watch_state`](Self::watch_state), something must keep reading.
+    /// [`TurnProgress::elapsed`] is measured when called, so a status bar can
+    /// redraw on a timer between updates.
+    pub fn watch_progress(&self) -> watch::Receiver<TurnProgress> {
+        self.progress_tx.subscribe()
+    }
+
+    fn publish_state(&self) {
+        self.state_tx.send_replace(self.state.state());
+    }
+
+    /// Serve `mcp_message` control requests for `server` from this client.
+    ///
+    /// The CLI must be told about the server too: pass
+    /// [`McpServer::sdk_config`] to
+    /// [`ClaudeCliBuilder::mcp_config`](crate::ClaudeCliBuilder::mcp_config).
+    /// Registering a server with an existing name replaces it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use claude_codes::mcp::McpServer;
+    /// use claude_codes::{AsyncClient, ClaudeCliBuilder};
+    /// use serde_json::json;
+    ///
+    /// # #[cfg(feature = "async-client")]
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let server = McpServer::new("clock").tool(
+    ///     "now",
+    ///     "Current system time",
+    ///     json!({"type": "object"}),
+    ///     |_| async { format!("{:?}", std::time::SystemTime::now()) },
+    /// );
+    ///
+    /// let builder = ClaudeCliBuilder::new()
+    ///     .mcp_config([server.sdk_config()])
+    ///     .allowed_tools([server.tool_id("now")]);
+    /// let mut client = AsyncClient::from_builder(builder).await?;
+    /// client.register_mcp_server(server);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_mcp_server(&mut self, server: McpServer) {
+        self.mcp_servers.insert(server.name().to_string(), server);
+    }
+
     /// Check if tool approval protocol is enabled
     pub fn is_tool_approval_enabled(&self) -> bool {
         self.tool_approval_enabled
@@ -602,13 +1328,13 @@ impl Protocol {
     pub async fn read_async<R: AsyncBufReadExt + Unpin, T: for<'de> Deserialize<'de>>(
         reader: &mut R,
     ) -> Result<T> {
-        let mut line = String::new();
-        let bytes_read = reader.read_line(&mut line).await?;
-        if bytes_read == 0 {
-            return Err(Error::ConnectionClosed);
-        }
-        debug!("[PROTOCOL] Received async: {}", line.trim());
-        Self::deserialize(&line)
+        let mut frames = FrameReader::new();
+        let line = frames
+            .read_frame_async(reader)
+            .await?
+            .ok_or(Error::ConnectionClosed)?;
+        debug!("[PROTOCOL] Received async: {}", line);
+        Self::deserialize(line)
     }
 }
 
@@ -0,0 +1,607 @@
+//! Blocking facade over the asynchronous client
+//!
+//! [`BlockingClient`] owns a single-threaded tokio runtime and drives an
+//! [`AsyncClient`] on it, so code without an async runtime gets the full async
+//! feature set — streaming responses, tool approval, interrupts, in-process MCP
+//! servers — through plain blocking calls.
+//!
+//! Do not call these methods from inside an async task: blocking on a runtime
+//! from within another runtime panics. Use [`AsyncClient`] there instead.
+
+use crate::cli::ClaudeCliBuilder;
+use crate::client_async::{AsyncClient, ResponseStream};
+use crate::error::{Error, Result};
+use crate::input_queue::InputDispatched;
+use crate::io::{
+    AddDirectoryResult, ClaudeInput, ClaudeOutput, ControlResponse, RewindFilesResult,
+};
+use crate::mcp::McpServer;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+/// A boxed future borrowing the wrapped client, as taken by
+/// [`BlockingClient::block_on`].
+pub type ClientFuture<'c, T> = Pin<Box<dyn Future<Output = T> + 'c>>;
+
+/// Synchronous wrapper around [`AsyncClient`] with its own tokio runtime.
+///
+/// # Example
+///
+/// ```no_run
+/// use claude_codes::{BlockingClient, ClaudeCliBuilder, ClaudeOutput};
+///
+/// # fn main() -> claude_codes::Result<()> {
+/// let mut client = BlockingClient::from_builder(ClaudeCliBuilder::new().model("sonnet"))?;
+///
+/// for output in client.query_stream("What is 2 + 2?")? {
+///     if let ClaudeOutput::Assistant(msg) = output? {
+///         print!("{}", msg.concat_text());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct BlockingClient {
+    // Field order matters: the client (and its child process handles) must be
+    // dropped before the runtime that owns their I/O driver.
+    client: AsyncClient,
+    runtime: Runtime,
+}
+
+impl BlockingClient {
+    /// Wrap an already-constructed [`AsyncClient`].
+    ///
+    /// The client must have been spawned on `runtime` (or one sharing its I/O
+    /// driver); use [`from_builder`](Self::from_builder) to let the blocking
+    /// client do both.
+    pub fn from_async(client: AsyncClient, runtime: Runtime) -> Self {
+        Self { client, runtime }
+    }
+
+    /// Create a client with default settings
+    pub fn with_defaults() -> Result<Self> {
+        let runtime = new_runtime()?;
+        let client = runtime.block_on(AsyncClient::with_defaults())?;
+        Ok(Self::from_async(client, runtime))
+    }
+
+    /// Create a client with a specific model
+    pub fn with_model(model: &str) -> Result<Self> {
+        let runtime = new_runtime()?;
+        let client = runtime.block_on(AsyncClient::with_model(model))?;
+        Ok(Self::from_async(client, runtime))
+    }
+
+    /// Create a client from a custom builder
+    pub fn from_builder(builder: ClaudeCliBuilder) -> Result<Self> {
+        let runtime = new_runtime()?;
+        let client = runtime.block_on(AsyncClient::from_builder(builder))?;
+        Ok(Self::from_async(client, runtime))
+    }
+
+    /// Resume a previous session by UUID
+    pub fn resume_session(session_uuid: Uuid) -> Result<Self> {
+        let runtime = new_runtime()?;
+        let client = runtime.block_on(AsyncClient::resume_session(session_uuid))?;
+        Ok(Self::from_async(client, runtime))
+    }
+
+    /// Send a query and collect all responses until the Result message
+    pub fn query(&mut self, text: &str) -> Result<Vec<ClaudeOutput>> {
+        self.runtime.block_on(self.client.query(text))
+    }
+
+    /// Send a query and deserialize its structured output (see
+    /// [`AsyncClient::query_typed`]).
+    pub fn query_typed<T: serde::de::DeserializeOwned>(&mut self, text: &str) -> Result<T> {
+        self.runtime.block_on(self.client.query_typed(text))
+    }
+
+    /// Send a query with a custom session ID and collect all responses
+    pub fn query_with_session(
+        &mut self,
+        text: &str,
+        session_id: Uuid,
+    ) -> Result<Vec<ClaudeOutput>> {
+        self.runtime
+            .block_on(self.client.query_with_session(text, session_id))
+    }
+
+    /// Send a query and return a blocking iterator over its responses, ending
+    /// after the Result message.
+    pub fn query_stream(&mut self, text: &str) -> Result<BlockingResponseStream<'_>> {
+        self.query_stream_with_session(text, Uuid::new_v4())
+    }
+
+    /// Send a query with a session ID and return a blocking iterator over
+    /// its responses.
+    pub fn query_stream_with_session(
+        &mut self,
+        text: &str,
+        session_id: Uuid,
+    ) -> Result<BlockingResponseStream<'_>> {
+        let inner = self
+            .runtime
+            .block_on(self.client.query_stream_with_session(text, session_id))?;
+        Ok(BlockingResponseStream {
+            runtime: &self.runtime,
+            inner,
+        })
+    }
+
+    /// Send a message to Claude
+    pub fn send(&mut self, input: &ClaudeInput) -> Result<()> {
+        self.runtime.block_on(self.client.send(input))
+    }
+
+    /// Send `input` now, or hold it until the current turn ends; see
+    /// [`AsyncClient::queue_input`].
+    pub fn queue_input(&mut self, input: ClaudeInput) -> Result<()> {
+        self.runtime.block_on(self.client.queue_input(input))
+    }
+
+    /// Send every queued input now. Returns how many were sent.
+    pub fn flush_queued_input(&mut self) -> Result<usize> {
+        self.runtime.block_on(self.client.flush_queued_input())
+    }
+
+    /// Number of inputs waiting for the current turn to end.
+    pub fn queued_input_len(&self) -> usize {
+        self.client.queued_input_len()
+    }
+
+    /// Whether a user message has been sent and its Result not yet received.
+    pub fn is_turn_active(&self) -> bool {
+        self.client.is_turn_active()
+    }
+
+    /// Take the events for queued input sent since the last call.
+    pub fn take_dispatched_input(&mut self) -> Vec<InputDispatched> {
+        self.client.take_dispatched_input()
+    }
+
+    /// Receive the next message, blocking until one arrives
+    pub fn receive(&mut self) -> Result<ClaudeOutput> {
+        self.runtime.block_on(self.client.receive())
+    }
+
+    /// Receive the next frame as a raw `serde_json::Value`
+    pub fn receive_raw(&mut self) -> Result<serde_json::Value> {
+        self.runtime.block_on(self.client.receive_raw())
+    }
+
+    /// Send an interrupt to gracefully stop the current response
+    pub fn interrupt(&mut self) -> Result<()> {
+        self.runtime.block_on(self.client.interrupt())
+    }
+
+    /// Restore the files edited since `checkpoint_id`; see
+    /// [`AsyncClient::rewind`].
+    pub fn rewind(&mut self, checkpoint_id: Uuid) -> Result<RewindFilesResult> {
+        self.runtime.block_on(self.client.rewind(checkpoint_id))
+    }
+
+    /// Report which files [`rewind`](Self::rewind) would restore, without
+    /// changing any.
+    pub fn preview_rewind(&mut self, checkpoint_id: Uuid) -> Result<RewindFilesResult> {
+        self.runtime
+            .block_on(self.client.preview_rewind(checkpoint_id))
+    }
+
+    /// Grant the session access to another directory; see
+    /// [`AsyncClient::add_directory`].
+    pub fn add_directory(&mut self, path: impl AsRef<Path>) -> Result<AddDirectoryResult> {
+        self.runtime.block_on(self.client.add_directory(path))
+    }
+
+    /// Enable the tool approval protocol; see
+    /// [`AsyncClient::enable_tool_approval`].
+    pub fn enable_tool_approval(&mut self) -> Result<()> {
+        self.runtime.block_on(self.client.enable_tool_approval())
+    }
+
+    /// Check if tool approval protocol is enabled
+    pub fn is_tool_approval_enabled(&self) -> bool {
+        self.client.is_tool_approval_enabled()
+    }
+
+    /// Answer a control request (e.g. a tool permission prompt)
+    pub fn send_control_response(&mut self, response: ControlResponse) -> Result<()> {
+        self.runtime
+            .block_on(self.client.send_control_response(response))
+    }
+
+    /// Register an in-process MCP server; see
+    /// [`AsyncClient::register_mcp_server`].
+    pub fn register_mcp_server(&mut self, server: McpServer) {
+        self.client.register_mcp_server(server);
+    }
+
+    /// Answer repeated [`query`](Self::query) calls from `cache` (see
+    /// [`AsyncClient::set_cache`]).
+    #[cfg(feature = "cache")]
+    pub fn set_cache(
+        &mut self,
+        cache: std::sync::Arc<dyn crate::cache::ResponseCache>,
+        scope: crate::cache::CacheScope,
+    ) {
+        self.client.set_cache(cache, scope);
+    }
+
+    /// Write oversized tool results to disk as they are received; see
+    /// [`AsyncClient::set_spill_policy`].
+    pub fn set_spill_policy(&mut self, policy: crate::io::SpillPolicy) {
+        self.client.set_spill_policy(policy);
+    }
+
+    /// Refuse output lines longer than `max_bytes`; see
+    /// [`AsyncClient::set_max_message_bytes`].
+    pub fn set_max_message_bytes(&mut self, max_bytes: usize) {
+        self.client.set_max_message_bytes(max_bytes);
+    }
+
+    /// Decide what happens to output lines that don't parse; see
+    /// [`AsyncClient::on_parse_error`].
+    pub fn on_parse_error(
+        &mut self,
+        handler: impl FnMut(&crate::io::ParseError) -> crate::io::ParseErrorAction + Send + 'static,
+    ) {
+        self.client.on_parse_error(handler);
+    }
+
+    /// Mirror every raw line sent to and received from the CLI into `sink`.
+    pub fn set_debug_sink(&mut self, sink: impl std::io::Write + Send + 'static) {
+        self.client.set_debug_sink(sink);
+    }
+
+    /// Stop mirroring raw wire traffic.
+    pub fn clear_debug_sink(&mut self) {
+        self.client.clear_debug_sink();
+    }
+
+    /// Test if the Claude connection is working
+    pub fn ping(&mut self) -> bool {
+        self.runtime.block_on(self.client.ping())
+    }
+
+    /// Check if the Claude process is still running
+    pub fn is_alive(&mut self) -> bool {
+        self.client.is_alive()
+    }
+
+    /// Get the process ID
+    pub fn pid(&self) -> Option<u32> {
+        self.client.pid()
+    }
+
+    /// Get the session UUID if available
+    pub fn session_uuid(&self) -> Result<Uuid> {
+        self.client.session_uuid()
+    }
+
+    /// Run any other async operation on the wrapped client to completion.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use claude_codes::BlockingClient;
+    /// # fn demo(client: &mut BlockingClient) -> claude_codes::Result<()> {
+    /// let reply = client.block_on(|c| Box::pin(c.query("Hi")))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn block_on<T>(
+        &mut self,
+        f: impl for<'c> FnOnce(&'c mut AsyncClient) -> ClientFuture<'c, T>,
+    ) -> T {
+        self.runtime.block_on(f(&mut self.client))
+    }
+
+    /// Gracefully shut down the client and its runtime
+    pub fn shutdown(self) -> Result<()> {
+        let Self { client, runtime } = self;
+        runtime.block_on(client.shutdown())
+    }
+}
+
+/// Blocking iterator over the responses to one query.
+///
+/// Yields each [`ClaudeOutput`] and stops after the Result message or the
+/// first error.
+pub struct BlockingResponseStream<'a> {
+    runtime: &'a Runtime,
+    inner: ResponseStream<'a>,
+}
+
+impl BlockingResponseStream<'_> {
+    /// Collect the remaining responses into a vector
+    pub fn collect_all(self) -> Result<Vec<ClaudeOutput>> {
+        self.runtime.block_on(self.inner.collect())
+    }
+}
+
+impl Iterator for BlockingResponseStream<'_> {
+    type Item = Result<ClaudeOutput>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.inner.next())
+    }
+}
+
+fn new_runtime() -> Result<Runtime> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(Error::Io)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_cli;
+    use std::path::PathBuf;
+
+    /// A stand-in CLI that answers every input line with one assistant
+    /// message and a result.
+    fn answering_cli() -> PathBuf {
+        let session = "622ae0c3-3d50-4fa7-9ee0-69d691238c6d";
+        fake_cli(&format!(
+            r#"#!/bin/sh
+while read -r line; do
+  echo '{{"type":"assistant","message":{{"id":"m","role":"assistant","model":"c","content":[{{"type":"text","text":"four"}}]}},"session_id":"{session}"}}'
+  echo '{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"four","session_id":"{session}","total_cost_usd":0.0}}'
+done
+"#
+        ))
+    }
+
+    #[test]
+    fn test_query_stream_iterates_to_result() {
+        let cli = answering_cli();
+        let mut client =
+            BlockingClient::from_builder(ClaudeCliBuilder::new().command(&cli)).unwrap();
+
+        let kinds: Vec<String> = client
+            .query_stream("2 + 2?")
+            .unwrap()
+            .map(|o| o.unwrap().message_type())
+            .collect();
+        assert_eq!(kinds, vec!["assistant", "result"]);
+
+        let replies = client.query("again").unwrap();
+        assert_eq!(replies.len(), 2);
+        assert!(client.is_alive());
+
+        client.shutdown().unwrap();
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[test]
+    fn test_queued_input_waits_for_result() {
+        use crate::input_queue::DispatchTrigger;
+
+        let cli = answering_cli();
+        let mut client =
+            BlockingClient::from_builder(ClaudeCliBuilder::new().command(&cli)).unwrap();
+        let session = Uuid::new_v4();
+
+        // Idle: sent straight away.
+        client
+            .queue_input(ClaudeInput::user_message("first", session))
+            .unwrap();
+        assert!(client.is_turn_active());
+
+        client
+            .queue_input(ClaudeInput::user_message("second", session))
+            .unwrap();
+        assert_eq!(client.queued_input_len(), 1);
+        assert!(client.take_dispatched_input().is_empty());
+
+        assert_eq!(client.receive().unwrap().message_type(), "assistant");
+        assert_eq!(client.queued_input_len(), 1);
+
+        // The first Result releases the queued message.
+        assert_eq!(client.receive().unwrap().message_type(), "result");
+        assert_eq!(client.queued_input_len(), 0);
+        assert!(client.is_turn_active());
+        let events = client.take_dispatched_input();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].trigger, DispatchTrigger::TurnEnded);
+
+        assert_eq!(client.receive().unwrap().message_type(), "assistant");
+        assert_eq!(client.receive().unwrap().message_type(), "result");
+        assert!(!client.is_turn_active());
+
+        client.shutdown().unwrap();
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[test]
+    fn test_rewind_round_trip() {
+        // Answers every control request with a dry-run style rewind result,
+        // after an unrelated message that must be skipped.
+        let script = r#"#!/bin/sh
+while read -r line; do
+  id=$(echo "$line" | sed 's/.*"request_id":"\([^"]*\)".*/\1/')
+  echo '{"type":"system","subtype":"status","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d"}'
+  echo '{"type":"control_response","response":{"subtype":"success","request_id":"'"$id"'","response":{"canRewind":true,"filesChanged":["src/main.rs"],"insertions":3,"deletions":1}}}'
+done
+"#;
+        let cli = fake_cli(script);
+
+        let mut client =
+            BlockingClient::from_builder(ClaudeCliBuilder::new().command(&cli)).unwrap();
+        let result = client.preview_rewind(Uuid::new_v4()).unwrap();
+        assert!(result.can_rewind);
+        assert_eq!(result.files_changed, vec!["src/main.rs"]);
+        assert_eq!(result.insertions, Some(3));
+
+        client.shutdown().unwrap();
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[test]
+    fn test_oversized_message_is_skipped() {
+        let script = r#"#!/bin/sh
+read -r line
+head -c 4096 /dev/zero | tr '\0' x
+echo
+echo '{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}'
+"#;
+        let cli = fake_cli(script);
+
+        let mut client =
+            BlockingClient::from_builder(ClaudeCliBuilder::new().command(&cli)).unwrap();
+        client.set_max_message_bytes(1024);
+        client
+            .send(&ClaudeInput::user_message("hi", Uuid::new_v4()))
+            .unwrap();
+
+        assert!(matches!(
+            client.receive(),
+            Err(Error::MessageTooLarge {
+                size: 4096,
+                limit: 1024
+            })
+        ));
+        assert_eq!(client.receive().unwrap().message_type(), "result");
+
+        client.shutdown().unwrap();
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[test]
+    fn test_partial_message_at_exit_is_truncated() {
+        let script = r#"#!/bin/sh
+read -r line
+printf '\r\n{"type":"assistant","message":{"id":"m","role":"assist'
+"#;
+        let cli = fake_cli(script);
+
+        let mut client =
+            BlockingClient::from_builder(ClaudeCliBuilder::new().command(&cli)).unwrap();
+        client
+            .send(&ClaudeInput::user_message("hi", Uuid::new_v4()))
+            .unwrap();
+
+        match client.receive() {
+            Err(Error::TruncatedMessage { partial }) => {
+                assert!(partial.starts_with(r#"{"type":"assistant""#))
+            }
+            other => panic!("Expected TruncatedMessage, got {:?}", other),
+        }
+        assert!(matches!(client.receive(), Err(Error::ConnectionClosed)));
+
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[test]
+    fn test_crash_reports_last_lines_and_stderr() {
+        let script = r#"#!/bin/sh
+read -r line
+echo '{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}'
+echo 'loading tools' >&2
+echo 'panic: tool registry corrupted' >&2
+exit 3
+"#;
+        let cli = fake_cli(script);
+
+        let mut client =
+            BlockingClient::from_builder(ClaudeCliBuilder::new().command(&cli)).unwrap();
+        client
+            .send(&ClaudeInput::user_message("hi", Uuid::new_v4()))
+            .unwrap();
+        assert_eq!(client.receive().unwrap().message_type(), "result");
+
+        match client.receive() {
+            Err(Error::Crashed(report)) => {
+                assert_eq!(report.exit_code, Some(3));
+                assert_eq!(report.last_stdout_lines.len(), 1);
+                assert!(report.last_stdout_lines[0].contains(r#""result":"ok""#));
+                assert_eq!(
+                    report.stderr_tail,
+                    "loading tools\npanic: tool registry corrupted"
+                );
+            }
+            other => panic!("Expected Crashed, got {:?}", other),
+        }
+
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[test]
+    fn test_parse_errors_skipped_or_aborted() {
+        use crate::io::ParseErrorAction;
+        use std::sync::{Arc, Mutex};
+
+        let script = r#"#!/bin/sh
+while read -r line; do
+  echo '{"type":"assistant","message":7}'
+  echo '{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}'
+done
+"#;
+        let cli = fake_cli(script);
+
+        let mut client =
+            BlockingClient::from_builder(ClaudeCliBuilder::new().command(&cli)).unwrap();
+        let session = Uuid::new_v4();
+
+        // Default: the bad line is skipped.
+        client
+            .send(&ClaudeInput::user_message("one", session))
+            .unwrap();
+        assert_eq!(client.receive().unwrap().message_type(), "result");
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        client.on_parse_error(move |e| {
+            log.lock().unwrap().push(e.raw_line.clone());
+            ParseErrorAction::Abort
+        });
+        client
+            .send(&ClaudeInput::user_message("two", session))
+            .unwrap();
+        assert!(matches!(client.receive(), Err(Error::Deserialization(_))));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![r#"{"type":"assistant","message":7}"#]
+        );
+        // The stream is still usable after an aborted line.
+        assert_eq!(client.receive().unwrap().message_type(), "result");
+
+        client.shutdown().unwrap();
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[cfg(feature = "cache")]
+    #[test]
+    fn test_query_served_from_cache() {
+        use crate::cache::{CacheScope, MemoryCache, ResponseCache};
+        use std::sync::Arc;
+
+        let cli = fake_cli("cache");
+        let builder = ClaudeCliBuilder::new().command(&cli).model("sonnet");
+        let scope = CacheScope::from(&builder);
+        let cache = Arc::new(MemoryCache::new());
+
+        let mut client = BlockingClient::from_builder(builder).unwrap();
+        client.set_cache(cache.clone(), scope.clone());
+        assert_eq!(client.query("2 + 2?").unwrap().len(), 2);
+        assert!(cache.get(&scope.key("2 + 2?")).is_some());
+        client.shutdown().unwrap();
+
+        // A dead CLI can still answer the cached prompt.
+        let mut client = BlockingClient::from_builder(
+            ClaudeCliBuilder::new().command("/bin/true").model("sonnet"),
+        )
+        .unwrap();
+        client.set_cache(cache, scope);
+        assert_eq!(client.query("2 + 2?").unwrap().len(), 2);
+        assert!(client.query("3 + 3?").is_err());
+
+        std::fs::remove_file(cli).ok();
+    }
+}
@@ -0,0 +1,427 @@
+//! Shared handle over an [`AsyncClient`] for use in `tokio::select!`
+//!
+//! [`AsyncClient::receive`] takes `&mut self` and is not cancel-safe: if
+//! another `select!` branch wins while a message is half read, that message
+//! is lost. [`ClientHandle`] moves the client onto a background task that
+//! drains stdout into a channel, so [`recv`](ClientHandle::recv) takes
+//! `&self`, can be dropped at any point without losing output, and can sit in
+//! a `select!` next to the branches that send input.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use claude_codes::{AsyncClient, ClaudeInput, ClaudeOutput};
+//! use tokio::sync::mpsc;
+//!
+//! # async fn example(mut prompts: mpsc::Receiver<String>) -> claude_codes::Result<()> {
+//! let handle = AsyncClient::with_defaults().await?.into_handle();
+//! let session = uuid::Uuid::new_v4();
+//!
+//! loop {
+//!     tokio::select! {
+//!         output = handle.recv() => match output? {
+//!             ClaudeOutput::Assistant(msg) => print!("{}", msg.concat_text()),
+//!             ClaudeOutput::Result(_) => println!(),
+//!             _ => {}
+//!         },
+//!         Some(prompt) = prompts.recv() => {
+//!             handle.queue_input(ClaudeInput::user_message(prompt, session)).await?;
+//!         }
+//!         _ = tokio::signal::ctrl_c() => handle.interrupt().await?,
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! # Ordering
+//!
+//! Messages are delivered in the order the CLI wrote them, and each one is
+//! stamped with a sequence number: 0 for the first, then one more per
+//! message (errors are not numbered). When several tasks call
+//! [`recv_sequenced`](ClientHandle::recv_sequenced) on one handle, each
+//! message goes to exactly one of them, but they may finish handling them
+//! out of order; the numbers let a downstream consumer restore the order or
+//! notice that a message went missing.
+
+use crate::client_async::AsyncClient;
+use crate::client_state::ClientState;
+use crate::error::{Error, Result};
+use crate::io::{ClaudeInput, ClaudeOutput, ControlResponse};
+use crate::progress::TurnProgress;
+use log::debug;
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
+use tokio::task::JoinHandle;
+
+/// Work the background task does on the client for the handle.
+enum Command {
+    Send(ClaudeInput),
+    QueueInput(ClaudeInput),
+    ControlResponse(ControlResponse),
+}
+
+/// A message together with its position in the stream.
+#[derive(Debug, Clone)]
+pub struct Sequenced<T> {
+    /// 0 for the first message, then one more per message.
+    pub seq: u64,
+    pub message: T,
+}
+
+/// `&self` access to an [`AsyncClient`] running on a background task.
+///
+/// Created with [`AsyncClient::into_handle`]. Outputs are read as soon as
+/// the CLI writes them and buffered until [`recv`](Self::recv) is called.
+/// Dropping the handle stops the task and kills the CLI process.
+pub struct ClientHandle {
+    commands: mpsc::UnboundedSender<(Command, oneshot::Sender<Result<()>>)>,
+    outputs: Mutex<mpsc::UnboundedReceiver<Result<Sequenced<ClaudeOutput>>>>,
+    state: watch::Receiver<ClientState>,
+    progress: watch::Receiver<TurnProgress>,
+    task: JoinHandle<()>,
+}
+
+impl AsyncClient {
+    /// Move the client onto a background tokio task and return a
+    /// [`ClientHandle`] whose methods take `&self`.
+    ///
+    /// Must be called from within a tokio runtime. In-process MCP servers,
+    /// queued input, and the other client settings keep working on the task.
+    pub fn into_handle(self) -> ClientHandle {
+        let (commands, command_rx) = mpsc::unbounded_channel();
+        let (output_tx, outputs) = mpsc::unbounded_channel();
+        let state = self.watch_state();
+        let progress = self.watch_progress();
+        let task = tokio::spawn(run(self, command_rx, output_tx));
+        ClientHandle {
+            commands,
+            outputs: Mutex::new(outputs),
+            state,
+            progress,
+            task,
+        }
+    }
+}
+
+impl ClientHandle {
+    /// Receive the next message from Claude.
+    ///
+    /// Cancel-safe: if the future is dropped before it completes, no message
+    /// is lost, and the next call returns it. Errors are returned as
+    /// [`AsyncClient::receive`] would; after the CLI exits, returns
+    /// `Err(Error::ConnectionClosed)` once every buffered message is read.
+    pub async fn recv(&self) -> Result<ClaudeOutput> {
+        self.recv_sequenced().await.map(|output| output.message)
+    }
+
+    /// Receive the next message with its sequence number (see the
+    /// [module docs](crate::client_handle#ordering)).
+    ///
+    /// Cancel-safe and shares the stream with [`recv`](Self::recv): a
+    /// message taken by either is not returned by the other.
+    pub async fn recv_sequenced(&self) -> Result<Sequenced<ClaudeOutput>> {
+        self.outputs
+            .lock()
+            .await
+            .recv()
+            .await
+            .unwrap_or(Err(Error::ConnectionClosed))
+    }
+
+    /// Send `input` to the CLI (see [`AsyncClient::send`]).
+    ///
+    /// Once called, the input is sent even if the returned future is
+    /// dropped; awaiting it reports whether the write succeeded.
+    pub async fn send(&self, input: ClaudeInput) -> Result<()> {
+        self.command(Command::Send(input)).await
+    }
+
+    /// Send `input` now, or when the current turn ends if one is in progress
+    /// (see [`AsyncClient::queue_input`]).
+    pub async fn queue_input(&self, input: ClaudeInput) -> Result<()> {
+        self.command(Command::QueueInput(input)).await
+    }
+
+    /// Answer a control request received from [`recv`](Self::recv) (see
+    /// [`AsyncClient::send_control_response`]).
+    pub async fn send_control_response(&self, response: ControlResponse) -> Result<()> {
+        self.command(Command::ControlResponse(response)).await
+    }
+
+    /// Ask Claude to stop the current response (see [`AsyncClient::interrupt`]).
+    pub async fn interrupt(&self) -> Result<()> {
+        self.send(ClaudeInput::interrupt()).await
+    }
+
+    /// What the session is doing right now (see [`AsyncClient::state`]).
+    ///
+    /// Updated by the background task as messages arrive, so it can be ahead
+    /// of what [`recv`](Self::recv) has returned.
+    pub fn state(&self) -> ClientState {
+        *self.state.borrow()
+    }
+
+    /// Subscribe to [`state`](Self::state) changes (see
+    /// [`AsyncClient::watch_state`]).
+    pub fn watch_state(&self) -> watch::Receiver<ClientState> {
+        self.state.clone()
+    }
+
+    /// Subscribe to progress updates for the current turn (see
+    /// [`AsyncClient::watch_progress`]).
+    pub fn watch_progress(&self) -> watch::Receiver<TurnProgress> {
+        self.progress.clone()
+    }
+
+    /// Stop the background task and kill the CLI process.
+    pub async fn shutdown(self) -> Result<()> {
+        drop(self.commands);
+        self.task
+            .await
+            .map_err(|e| Error::Protocol(format!("client task failed: {}", e)))
+    }
+
+    async fn command(&self, command: Command) -> Result<()> {
+        let (reply, done) = oneshot::channel();
+        self.commands
+            .send((command, reply))
+            .map_err(|_| Error::ConnectionClosed)?;
+        done.await.unwrap_or(Err(Error::ConnectionClosed))
+    }
+}
+
+/// Serve commands and forward outputs until the handle is dropped.
+async fn run(
+    mut client: AsyncClient,
+    mut commands: mpsc::UnboundedReceiver<(Command, oneshot::Sender<Result<()>>)>,
+    outputs: mpsc::UnboundedSender<Result<Sequenced<ClaudeOutput>>>,
+) {
+    let mut reading = true;
+    let mut seq = 0;
+    loop {
+        // A retried turn waits out its delay here, racing the commands, so an
+        // interrupt sent meanwhile is handled at once (and cancels it).
+        let retry_wait = client.pending_retry_wait();
+        tokio::select! {
+            command = commands.recv() => {
+                let Some((command, reply)) = command else {
+                    break;
+                };
+                let result = match command {
+                    Command::Send(input) => client.send(&input).await,
+                    Command::QueueInput(input) => client.queue_input(input).await,
+                    Command::ControlResponse(response) => {
+                        client.send_control_response(response).await
+                    }
+                };
+                let _ = reply.send(result);
+            }
+            _ = tokio::time::sleep(retry_wait.unwrap_or_default()), if retry_wait.is_some() => {
+                if let Err(e) = client.send_pending_retry().await {
+                    let _ = outputs.send(Err(e));
+                }
+            }
+            // Only waiting for data is raced against commands; once a line
+            // has started arriving, `receive_next` runs to completion.
+            ready = client.wait_readable(), if reading => {
+                let output = match ready {
+                    Ok(()) => match client.receive_next().await {
+                        Ok(Some(output)) => Ok(output),
+                        // The turn will be sent again; nothing to forward.
+                        Ok(None) => continue,
+                        Err(e) => Err(e),
+                    },
+                    Err(e) => Err(e),
+                };
+                reading = !output.as_ref().is_err_and(Error::ends_output);
+                let output = output.map(|message| {
+                    let stamped = Sequenced { seq, message };
+                    seq += 1;
+                    stamped
+                });
+                let _ = outputs.send(output);
+                if !reading {
+                    debug!("[HANDLE] Stdout closed, no longer reading");
+                }
+            }
+        }
+    }
+    let _ = client.shutdown().await;
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::cli::ClaudeCliBuilder;
+    use crate::retry::RetryPolicy;
+    use crate::test_support::{fake_cli, temp_path};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_recv_is_cancel_safe_in_select() {
+        // Answer each input after a pause, so some `recv` calls are cancelled
+        // while the reply is on its way.
+        let script = r#"#!/bin/sh
+while read -r line; do
+  sleep 0.1
+  echo '{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}'
+done
+"#;
+        let cli = fake_cli(script);
+
+        let client = AsyncClient::from_builder(ClaudeCliBuilder::new().command(&cli))
+            .await
+            .unwrap();
+        let handle = client.into_handle();
+        let session = uuid::Uuid::new_v4();
+
+        let mut results = 0;
+        let mut sent = 0;
+        let mut ticks = tokio::time::interval(Duration::from_millis(15));
+        while results < 3 {
+            tokio::select! {
+                output = handle.recv() => {
+                    assert!(output.unwrap().as_result().is_some());
+                    results += 1;
+                }
+                _ = ticks.tick() => {
+                    if sent < 3 {
+                        handle
+                            .send(ClaudeInput::user_message("hi", session))
+                            .await
+                            .unwrap();
+                        sent += 1;
+                    }
+                }
+            }
+        }
+
+        handle.shutdown().await.unwrap();
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[tokio::test]
+    async fn test_sequence_numbers_follow_stream_order() {
+        let script = r#"#!/bin/sh
+while read -r line; do
+  for i in 1 2 3; do
+    echo '{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}'
+  done
+done
+"#;
+        let cli = fake_cli(script);
+
+        let handle = AsyncClient::from_builder(ClaudeCliBuilder::new().command(&cli))
+            .await
+            .unwrap()
+            .into_handle();
+        let session = uuid::Uuid::new_v4();
+        handle
+            .send(ClaudeInput::user_message("hi", session))
+            .await
+            .unwrap();
+        handle
+            .send(ClaudeInput::user_message("again", session))
+            .await
+            .unwrap();
+
+        assert_eq!(handle.recv_sequenced().await.unwrap().seq, 0);
+        handle.recv().await.unwrap();
+        let (a, b) = tokio::join!(handle.recv_sequenced(), handle.recv_sequenced());
+        let mut seqs = [a.unwrap().seq, b.unwrap().seq];
+        seqs.sort();
+        assert_eq!(seqs, [2, 3]);
+        assert_eq!(handle.recv_sequenced().await.unwrap().seq, 4);
+
+        handle.shutdown().await.unwrap();
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[tokio::test]
+    async fn test_state_follows_turn() {
+        // Exit on the second message, so the session is idle in between.
+        let script = r#"#!/bin/sh
+read -r line
+sleep 0.1
+echo '{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}'
+read -r line
+"#;
+        let cli = fake_cli(script);
+
+        let handle = AsyncClient::from_builder(ClaudeCliBuilder::new().command(&cli))
+            .await
+            .unwrap()
+            .into_handle();
+        let mut states = handle.watch_state();
+        assert_eq!(*states.borrow_and_update(), ClientState::Starting);
+
+        handle
+            .send(ClaudeInput::user_message("hi", uuid::Uuid::new_v4()))
+            .await
+            .unwrap();
+        assert_eq!(handle.state(), ClientState::Processing);
+
+        states
+            .wait_for(|state| *state == ClientState::Idle)
+            .await
+            .unwrap();
+        assert!(handle.recv().await.unwrap().as_result().is_some());
+
+        handle
+            .send(ClaudeInput::user_message("bye", uuid::Uuid::new_v4()))
+            .await
+            .unwrap();
+        assert!(handle.recv().await.is_err());
+        assert_eq!(handle.state(), ClientState::Closed);
+
+        handle.shutdown().await.unwrap();
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[tokio::test]
+    async fn test_interrupt_cancels_retry_backoff() {
+        let log = temp_path("stdin.log");
+        // Fail the first turn as overloaded, then log and answer every line.
+        let script = format!(
+            r#"#!/bin/sh
+read -r line
+echo '{{"type":"result","subtype":"success","is_error":true,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"API Error: 529 Overloaded","api_error_status":529,"session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}}'
+while read -r line; do
+  printf '%s\n' "$line" >> {log}
+  echo '{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}}'
+done
+"#,
+            log = log.display()
+        );
+        let cli = fake_cli(&script);
+
+        let mut client = AsyncClient::from_builder(ClaudeCliBuilder::new().command(&cli))
+            .await
+            .unwrap();
+        client.set_retry_policy(RetryPolicy::new(3).delay(Duration::from_secs(60)));
+        let handle = client.into_handle();
+        handle
+            .send(ClaudeInput::user_message("hi", uuid::Uuid::new_v4()))
+            .await
+            .unwrap();
+        handle
+            .watch_state()
+            .wait_for(|s| *s == ClientState::Idle)
+            .await
+            .unwrap();
+
+        // The backoff must not hold up the interrupt, which cancels the retry.
+        tokio::time::timeout(Duration::from_secs(5), handle.interrupt())
+            .await
+            .expect("interrupt blocked by retry backoff")
+            .unwrap();
+        let output = handle.recv().await.unwrap();
+        assert_eq!(output.as_result().unwrap().result.as_deref(), Some("ok"));
+        let sent = std::fs::read_to_string(&log).unwrap();
+        assert_eq!(sent.lines().count(), 1);
+        assert!(sent.contains("interrupt"));
+
+        handle.shutdown().await.unwrap();
+        std::fs::remove_file(cli).ok();
+        std::fs::remove_file(log).ok();
+    }
+}
@@ -0,0 +1,247 @@
+//! What a Claude session is doing right now.
+//!
+//! [`ClientStateTracker`] follows the messages going to and coming from the
+//! CLI and keeps a [`ClientState`] suitable for a busy/idle indicator. The
+//! async client runs one internally: read it with
+//! [`AsyncClient::state`](crate::AsyncClient::state) or subscribe to changes
+//! with [`AsyncClient::watch_state`](crate::AsyncClient::watch_state).
+//!
+//! ```
+//! use claude_codes::client_state::{ClientState, ClientStateTracker};
+//! use claude_codes::{ClaudeInput, ClaudeOutput};
+//!
+//! let mut tracker = ClientStateTracker::new();
+//! assert_eq!(tracker.state(), ClientState::Starting);
+//!
+//! tracker.on_input(&ClaudeInput::user_message("hi", uuid::Uuid::new_v4()));
+//! assert_eq!(tracker.state(), ClientState::Processing);
+//!
+//! let result = ClaudeOutput::parse_json(r#"{"type":"result","subtype":"success",
+//!     "is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,
+//!     "session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}"#).unwrap();
+//! tracker.on_output(&result);
+//! assert_eq!(tracker.state(), ClientState::Idle);
+//! ```
+
+use crate::io::{
+    ClaudeInput, ClaudeOutput, ControlRequestPayload, ControlResponsePayload, SessionState,
+    StatusMessageStatus, SystemKind,
+};
+use std::collections::HashSet;
+use std::fmt;
+
+/// The state of a session as seen from the SDK side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientState {
+    /// The CLI was started and hasn't reported in yet.
+    Starting,
+    /// Waiting for a prompt.
+    Idle,
+    /// Claude is working on a turn.
+    Processing,
+    /// The CLI is waiting for an answer to a tool permission request.
+    AwaitingToolApproval,
+    /// The conversation is being compacted.
+    Compacting,
+    /// The CLI's output ended.
+    Closed,
+}
+
+impl ClientState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Starting => "starting",
+            Self::Idle => "idle",
+            Self::Processing => "processing",
+            Self::AwaitingToolApproval => "awaiting_tool_approval",
+            Self::Compacting => "compacting",
+            Self::Closed => "closed",
+        }
+    }
+
+    /// Whether Claude is busy: processing, compacting, or waiting on a
+    /// tool approval.
+    pub fn is_busy(&self) -> bool {
+        matches!(
+            self,
+            Self::Processing | Self::AwaitingToolApproval | Self::Compacting
+        )
+    }
+}
+
+impl fmt::Display for ClientState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Derives a [`ClientState`] from the message flow.
+///
+/// A user message starts a turn and a result ends it; `can_use_tool`
+/// requests wait for approval until answered; `status` messages mark
+/// compaction; `session_state_changed` messages, where the CLI sends them,
+/// are taken as authoritative.
+#[derive(Debug, Clone)]
+pub struct ClientStateTracker {
+    state: ClientState,
+    turn_active: bool,
+    compacting: bool,
+    /// IDs of `can_use_tool` requests not answered yet
+    pending_approvals: HashSet<String>,
+}
+
+impl Default for ClientStateTracker {
+    fn default() -> Self {
+        Self {
+            state: ClientState::Starting,
+            turn_active: false,
+            compacting: false,
+            pending_approvals: HashSet::new(),
+        }
+    }
+}
+
+impl ClientStateTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> ClientState {
+        self.state
+    }
+
+    /// Update from a message sent to the CLI. Returns whether the state
+    /// changed.
+    pub fn on_input(&mut self, input: &ClaudeInput) -> bool {
+        match input {
+            ClaudeInput::User(_) => self.turn_active = true,
+            ClaudeInput::ControlResponse(response) => {
+                return self.on_control_response(&response.response)
+            }
+            _ => return false,
+        }
+        self.settle()
+    }
+
+    /// Update from a control response sent to the CLI. Returns whether the
+    /// state changed.
+    pub fn on_control_response(&mut self, response: &ControlResponsePayload) -> bool {
+        let (ControlResponsePayload::Success { request_id, .. }
+        | ControlResponsePayload::Error { request_id, .. }) = response;
+        self.pending_approvals.remove(request_id);
+        self.settle()
+    }
+
+    /// Update from a message received from the CLI. Returns whether the
+    /// state changed.
+    pub fn on_output(&mut self, output: &ClaudeOutput) -> bool {
+        match output {
+            ClaudeOutput::System(sys) => match sys.kind() {
+                SystemKind::Init(_) => {}
+                SystemKind::Status(status) => {
+                    self.compacting = status.status == Some(StatusMessageStatus::Compacting);
+                }
+                SystemKind::SessionStateChanged(changed) => match changed.state {
+                    SessionState::Idle => {
+                        self.turn_active = false;
+                        self.pending_approvals.clear();
+                    }
+                    SessionState::Running => self.turn_active = true,
+                    _ => {}
+                },
+                _ => return false,
+            },
+            ClaudeOutput::ControlRequest(req) => match &req.request {
+                ControlRequestPayload::CanUseTool(_) => {
+                    self.pending_approvals.insert(req.request_id.clone());
+                }
+                _ => return false,
+            },
+            ClaudeOutput::Assistant(_) | ClaudeOutput::User(_) => self.turn_active = true,
+            ClaudeOutput::Result(_) => {
+                self.turn_active = false;
+                self.compacting = false;
+                self.pending_approvals.clear();
+            }
+            _ => return false,
+        }
+        self.settle()
+    }
+
+    /// The CLI's output ended. Returns whether the state changed.
+    pub fn on_closed(&mut self) -> bool {
+        let changed = self.state != ClientState::Closed;
+        self.state = ClientState::Closed;
+        changed
+    }
+
+    fn settle(&mut self) -> bool {
+        if self.state == ClientState::Closed {
+            return false;
+        }
+        let next = if !self.pending_approvals.is_empty() {
+            ClientState::AwaitingToolApproval
+        } else if self.compacting {
+            ClientState::Compacting
+        } else if self.turn_active {
+            ClientState::Processing
+        } else {
+            ClientState::Idle
+        };
+        let changed = next != self.state;
+        self.state = next;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::ControlResponse;
+
+    const IDS: &str = r#""session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","uuid":"0bcbb682-4075-55ad-b82b-4205729a091e""#;
+
+    fn output(json: &str) -> ClaudeOutput {
+        ClaudeOutput::parse_json(json).unwrap()
+    }
+
+    #[test]
+    fn test_turn_with_approval_and_compaction() {
+        let mut tracker = ClientStateTracker::new();
+        assert!(tracker.on_output(&output(&format!(
+            r#"{{"type":"system","subtype":"init",{IDS}}}"#
+        ))));
+        assert_eq!(tracker.state(), ClientState::Idle);
+
+        tracker.on_input(&ClaudeInput::user_message("hi", uuid::Uuid::new_v4()));
+        assert_eq!(tracker.state(), ClientState::Processing);
+
+        tracker.on_output(&output(
+            r#"{"type":"control_request","request_id":"perm-1","request":{"subtype":"can_use_tool","tool_name":"Bash","input":{"command":"ls"}}}"#,
+        ));
+        assert_eq!(tracker.state(), ClientState::AwaitingToolApproval);
+        assert!(tracker.state().is_busy());
+        tracker.on_input(&ClaudeInput::ControlResponse(
+            ControlResponse::success_empty("perm-1"),
+        ));
+        assert_eq!(tracker.state(), ClientState::Processing);
+
+        tracker.on_output(&output(&format!(
+            r#"{{"type":"system","subtype":"status","status":"compacting",{IDS}}}"#
+        )));
+        assert_eq!(tracker.state(), ClientState::Compacting);
+        tracker.on_output(&output(&format!(
+            r#"{{"type":"system","subtype":"status","status":null,{IDS}}}"#
+        )));
+        assert_eq!(tracker.state(), ClientState::Processing);
+
+        tracker.on_output(&output(&format!(
+            r#"{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"total_cost_usd":0.0,{IDS}}}"#
+        )));
+        assert_eq!(tracker.state(), ClientState::Idle);
+
+        assert!(tracker.on_closed());
+        tracker.on_input(&ClaudeInput::user_message("again", uuid::Uuid::new_v4()));
+        assert_eq!(tracker.state(), ClientState::Closed);
+    }
+}
@@ -1,16 +1,26 @@
 //! Synchronous client for Claude communication
 
 use crate::cli::ClaudeCliBuilder;
-use crate::error::{Error, Result};
+use crate::debug_sink::{DebugSink, Direction};
+use crate::error::{CrashReport, Error, Result};
+use crate::framing::FrameReader;
 use crate::io::{
-    ClaudeInput, ClaudeOutput, ContentBlock, ControlRequestMessage, ControlResponse,
-    ControlResponseMessage,
+    AddDirectoryResult, AuthProblem, ClaudeInput, ClaudeOutput, ContentBlock,
+    ControlRequestMessage, ControlResponse, ControlResponseMessage, ControlResponsePayload,
+    LenientUuid, ParseErrorAction, ParseErrorHandler, PermissionModeName, RewindFilesResult,
+    SpillPolicy,
 };
 use crate::protocol::Protocol;
+use crate::retry::{RetryPolicy, TurnRetried, TurnRetrier};
+use crate::session_index::{SessionIndex, SessionRecorder};
+use crate::shadow::ShadowLog;
+use crate::tokens::TokenBudget;
 use log::{debug, warn};
 use serde::{Deserialize, Serialize};
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
 use std::process::{Child, ChildStdin, ChildStdout};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 /// Synchronous client for communicating with Claude
@@ -21,11 +31,39 @@ pub struct SyncClient {
     session_uuid: Option<Uuid>,
     /// Whether tool approval protocol has been initialized
     tool_approval_enabled: bool,
+    /// Mirror of raw wire traffic, if installed
+    debug_sink: Option<DebugSink>,
+    /// Reused buffer holding the most recent stdout line
+    frames: FrameReader,
+    #[cfg(feature = "otel")]
+    tracer: crate::otel::SessionTracer,
+    /// Where oversized tool results are moved, if anywhere
+    spill_policy: Option<SpillPolicy>,
+    /// Decides whether unparseable lines are skipped (the default) or returned
+    parse_error_handler: Option<ParseErrorHandler>,
+    /// Index that sessions seen by this client are recorded in
+    session_recorder: Option<SessionRecorder>,
+    /// Log that tool uses are denied into, in shadow mode
+    shadow: Option<ShadowLog>,
+    /// Limit on the estimated size of user messages sent
+    prompt_budget: Option<TokenBudget>,
+    /// Re-sends turns that fail on transient API errors, if a policy is set
+    retry: TurnRetrier,
+    /// Response cache consulted by `query`, with the scope that keys it
+    #[cfg(feature = "cache")]
+    cache: Option<(
+        std::sync::Arc<dyn crate::cache::ResponseCache>,
+        crate::cache::CacheScope,
+    )>,
 }
 
 /// Buffer size for reading Claude's stdout (10MB).
 const STDOUT_BUFFER_SIZE: usize = 10 * 1024 * 1024;
 
+/// How long to wait for the process to exit after stdout closes before
+/// inspecting stderr for the reason.
+const EXIT_GRACE_PERIOD: Duration = Duration::from_millis(500);
+
 impl SyncClient {
     /// Create a new synchronous client from an existing child process
     pub fn new(mut child: Child) -> Result<Self> {
@@ -44,6 +82,18 @@ impl SyncClient {
             stdout: BufReader::with_capacity(STDOUT_BUFFER_SIZE, stdout),
             session_uuid: None,
             tool_approval_enabled: false,
+            debug_sink: None,
+            frames: FrameReader::new().with_history(CrashReport::STDOUT_LINES),
+            #[cfg(feature = "otel")]
+            tracer: crate::otel::SessionTracer::new(),
+            spill_policy: None,
+            parse_error_handler: None,
+            session_recorder: None,
+            shadow: None,
+            prompt_budget: None,
+            retry: TurnRetrier::default(),
+            #[cfg(feature = "cache")]
+            cache: None,
         })
     }
 
@@ -92,17 +142,61 @@ impl SyncClient {
 
     /// Send a query and collect all responses
     pub fn query(&mut self, input: ClaudeInput) -> Result<Vec<ClaudeOutput>> {
+        #[cfg(feature = "cache")]
+        let cached = match (&self.cache, cache_prompt(&input)) {
+            (Some((cache, scope)), Some(prompt)) => Some((cache.clone(), scope.key(&prompt))),
+            _ => None,
+        };
+        #[cfg(feature = "cache")]
+        if let Some((cache, key)) = &cached {
+            if let Some(responses) = cache.get(key) {
+                debug!("[CACHE] Hit for {}", key);
+                return Ok(responses);
+            }
+        }
+
         let mut responses = Vec::new();
         for response in self.query_stream(input)? {
             responses.push(response?);
         }
+
+        #[cfg(feature = "cache")]
+        if let Some((cache, key)) = cached {
+            if crate::cache::is_cacheable(&responses) {
+                if let Err(e) = cache.put(&key, &responses) {
+                    warn!("[CACHE] Failed to store {}: {}", key, e);
+                }
+            }
+        }
+
         Ok(responses)
     }
 
+    /// Send a query and deserialize its structured output.
+    ///
+    /// The client must have been spawned with
+    /// [`ClaudeCliBuilder::json_schema`]; see
+    /// [`ResultMessage::structured_output_as`](crate::ResultMessage::structured_output_as).
+    pub fn query_typed<T: serde::de::DeserializeOwned>(&mut self, input: ClaudeInput) -> Result<T> {
+        let responses = self.query(input)?;
+        responses
+            .iter()
+            .rev()
+            .find_map(ClaudeOutput::as_result)
+            .ok_or_else(|| Error::MissingField("result".to_string()))?
+            .structured_output_as()
+    }
+
     /// Send a query and return an iterator over responses
     pub fn query_stream(&mut self, input: ClaudeInput) -> Result<ResponseIterator<'_>> {
+        if let Some(budget) = &self.prompt_budget {
+            budget.check(&input)?;
+        }
         // Send the input
-        Protocol::write_sync(&mut self.stdin, &input)?;
+        self.write_message(&input)?;
+        self.retry.on_send(&input);
+        #[cfg(feature = "otel")]
+        self.tracer.record_input(&input);
 
         Ok(ResponseIterator {
             client: self,
@@ -110,72 +204,312 @@ impl SyncClient {
         })
     }
 
+    /// Serialize a message and write it to stdin, mirroring it to the debug sink.
+    fn write_message<T: Serialize>(&mut self, message: &T) -> Result<()> {
+        let line = Protocol::serialize(message)?;
+        debug!("[CLIENT] Sending: {}", line.trim());
+        if let Some(sink) = &mut self.debug_sink {
+            sink.record(Direction::Outbound, &line);
+        }
+        self.stdin.write_all(line.as_bytes())?;
+        self.stdin.flush()?;
+        Ok(())
+    }
+
+    /// Read the next non-empty line from stdout into `self.frames`.
+    ///
+    /// The line, trimmed, is then available from `self.frames.frame()` until
+    /// the next read. Returns `Ok(false)` at EOF.
+    fn read_frame_line(&mut self) -> Result<bool> {
+        let Some(frame) = self.frames.read_frame(&mut self.stdout)? else {
+            return Ok(false);
+        };
+        if let Some(sink) = &mut self.debug_sink {
+            sink.record(Direction::Inbound, frame);
+        }
+        Ok(true)
+    }
+
+    /// Answer repeated [`query`](Self::query) calls from `cache`.
+    ///
+    /// Responses are keyed by the prompt text under `scope`, which should
+    /// describe how this client was spawned (see
+    /// [`CacheScope::from`](crate::cache::CacheScope)). A hit returns the
+    /// stored messages without contacting the CLI; only queries that end in a
+    /// successful result are stored.
+    #[cfg(feature = "cache")]
+    pub fn set_cache(
+        &mut self,
+        cache: std::sync::Arc<dyn crate::cache::ResponseCache>,
+        scope: crate::cache::CacheScope,
+    ) {
+        self.cache = Some((cache, scope));
+    }
+
+    /// Record every session this client starts in `index`, tagged with
+    /// `tags`, as soon as the CLI reports the session ID.
+    ///
+    /// Failures to update the index are logged and don't interrupt the
+    /// session. See [`crate::session_index`].
+    pub fn set_session_index<I, S>(&mut self, index: SessionIndex, tags: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let tags = tags.into_iter().map(Into::into).collect();
+        self.session_recorder = Some(SessionRecorder::new(index, tags));
+    }
+
+    /// Deny every tool use, writing each one to `log` instead.
+    ///
+    /// `can_use_tool` permission requests are answered by the client with
+    /// [`SHADOW_DENIAL`](crate::shadow::SHADOW_DENIAL) and never returned.
+    /// See [`crate::shadow`].
+    pub fn set_shadow_mode(&mut self, log: ShadowLog) {
+        self.shadow = Some(log);
+    }
+
+    /// Stop shadow mode; permission requests are returned again.
+    pub fn clear_shadow_mode(&mut self) {
+        self.shadow = None;
+    }
+
+    /// Refuse to send user messages whose estimated size exceeds `budget`.
+    ///
+    /// Sending one fails with [`Error::PromptTooLarge`] and nothing is
+    /// written to the CLI. See [`crate::tokens`].
+    pub fn set_prompt_budget(&mut self, budget: TokenBudget) {
+        self.prompt_budget = Some(budget);
+    }
+
+    /// Send user messages of any size again.
+    pub fn clear_prompt_budget(&mut self) {
+        self.prompt_budget = None;
+    }
+
+    /// Send a turn's user message again when the turn ends in a transient
+    /// API error (a 5xx or overloaded response), as `policy` allows.
+    ///
+    /// The failed Result is not returned by the response iterator; each
+    /// retry is reported by [`take_turn_retries`](Self::take_turn_retries)
+    /// instead. See [`crate::retry`].
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry.set_policy(Some(policy));
+    }
+
+    /// Return every failed Result again, without retrying.
+    pub fn clear_retry_policy(&mut self) {
+        self.retry.set_policy(None);
+    }
+
+    /// Take the events for turns retried since the last call.
+    pub fn take_turn_retries(&mut self) -> Vec<TurnRetried> {
+        self.retry.take_retried()
+    }
+
+    /// Write tool results larger than the policy's threshold to disk as they
+    /// are received, keeping only a [`LargeContent`](crate::io::LargeContent)
+    /// handle in memory. See [`crate::io::SpillPolicy`].
+    pub fn set_spill_policy(&mut self, policy: SpillPolicy) {
+        self.spill_policy = Some(policy);
+    }
+
+    /// Refuse output lines longer than `max_bytes`.
+    ///
+    /// An oversized line is skipped without being buffered and reported as
+    /// [`Error::MessageTooLarge`]; the next receive continues with the
+    /// following message. Unlimited by default.
+    pub fn set_max_message_bytes(&mut self, max_bytes: usize) {
+        self.frames.set_max_bytes(Some(max_bytes));
+    }
+
+    /// Decide what happens to output lines that don't parse as a
+    /// [`ClaudeOutput`].
+    ///
+    /// `handler` gets the [`ParseError`](crate::io::ParseError), which holds
+    /// the raw line, and returns [`ParseErrorAction::Skip`] to drop it and
+    /// keep receiving or [`ParseErrorAction::Abort`] to return it as
+    /// [`Error::Deserialization`]. Without a handler, such lines are logged
+    /// and skipped.
+    pub fn on_parse_error(
+        &mut self,
+        handler: impl FnMut(&crate::io::ParseError) -> ParseErrorAction + Send + 'static,
+    ) {
+        self.parse_error_handler = Some(Box::new(handler));
+    }
+
+    /// Mirror every raw line sent to and received from Claude into `sink`.
+    ///
+    /// Each line is prefixed with a timestamp and a direction marker (`>>` for
+    /// outbound, `<<` for inbound); see [`crate::debug_sink`]. Replaces any
+    /// previously installed sink.
+    pub fn set_debug_sink(&mut self, sink: impl Write + Send + 'static) {
+        self.debug_sink = Some(DebugSink::new(sink));
+    }
+
+    /// Mirror raw wire traffic into the file at `path`, appending if it exists.
+    pub fn set_debug_sink_file(&mut self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        self.debug_sink = Some(DebugSink::file(path)?);
+        Ok(())
+    }
+
+    /// Stop mirroring raw wire traffic.
+    pub fn clear_debug_sink(&mut self) {
+        self.debug_sink = None;
+    }
+
     /// Read the next response from Claude
     fn read_next(&mut self) -> Result<Option<ClaudeOutput>> {
-        let mut line = String::new();
-        match self.stdout.read_line(&mut line) {
-            Ok(0) => {
-                debug!("[CLIENT] Stream closed");
-                Ok(None)
-            }
-            Ok(_) => {
-                let trimmed = line.trim();
-                if trimmed.is_empty() {
-                    debug!("[CLIENT] Skipping empty line");
-                    return self.read_next();
+        loop {
+            return match self.read_frame_line() {
+                Ok(false) => {
+                    debug!("[CLIENT] Stream closed");
+                    match self.failure_on_exit() {
+                        Some(err) => Err(err),
+                        None => Ok(None),
+                    }
                 }
+                Ok(true) => {
+                    let trimmed = self.frames.frame();
+
+                    debug!("[CLIENT] Received: {}", trimmed);
+                    match ClaudeOutput::parse_json_tolerant(trimmed) {
+                        Ok(mut output) => {
+                            if let Some(policy) = &self.spill_policy {
+                                if let Err(e) = output.spill_large_tool_results(policy) {
+                                    warn!("[CLIENT] Failed to spill large tool result: {}", e);
+                                }
+                            }
+
+                            #[cfg(feature = "otel")]
+                            self.tracer.record_output(&output);
+
+                            if let Some(recorder) = &mut self.session_recorder {
+                                recorder.observe(&output);
+                            }
+
+                            if let Some((problem, message)) = output.auth_problem() {
+                                warn!("[CLIENT] Claude CLI is not authenticated: {}", message);
+                                return Err(Error::NotAuthenticated { problem, message });
+                            }
 
-                debug!("[CLIENT] Received: {}", trimmed);
-                match ClaudeOutput::parse_json_tolerant(trimmed) {
-                    Ok(output) => {
-                        // Capture UUID from first response if not already set
-                        if self.session_uuid.is_none() {
-                            if let ClaudeOutput::Assistant(ref msg) = output {
-                                if let Some(ref uuid_str) = msg.uuid {
-                                    if let Ok(uuid) = Uuid::parse_str(uuid_str) {
+                            if let Some(response) =
+                                self.shadow.as_mut().and_then(|log| log.intercept(&output))
+                            {
+                                self.send_control_response(response)?;
+                                continue;
+                            }
+
+                            // Capture UUID from first response if not already set
+                            if self.session_uuid.is_none() {
+                                if let ClaudeOutput::Assistant(ref msg) = output {
+                                    if let Some(uuid) =
+                                        msg.uuid.as_ref().and_then(LenientUuid::as_uuid)
+                                    {
                                         debug!("[CLIENT] Captured session UUID: {}", uuid);
                                         self.session_uuid = Some(uuid);
                                     }
-                                }
-                            } else if let ClaudeOutput::Result(ref msg) = output {
-                                if let Some(ref uuid_str) = msg.uuid {
-                                    if let Ok(uuid) = Uuid::parse_str(uuid_str) {
+                                } else if let ClaudeOutput::Result(ref msg) = output {
+                                    if let Some(uuid) =
+                                        msg.uuid.as_ref().and_then(LenientUuid::as_uuid)
+                                    {
                                         debug!("[CLIENT] Captured session UUID: {}", uuid);
                                         self.session_uuid = Some(uuid);
                                     }
                                 }
                             }
-                        }
 
-                        // Check if this is a result message
-                        if matches!(output, ClaudeOutput::Result(_)) {
-                            debug!("[CLIENT] Received result message, stream complete");
-                            Ok(Some(output))
-                        } else {
-                            Ok(Some(output))
+                            if let ClaudeOutput::Result(ref result) = output {
+                                if let Some(delay) = self.retry.on_result(result) {
+                                    warn!(
+                                        "[CLIENT] Turn failed on a transient API error, sending it again in {:?}",
+                                        delay
+                                    );
+                                    std::thread::sleep(delay);
+                                    if let Some(input) = self.retry.take_pending() {
+                                        self.write_message(&input)?;
+                                        self.retry.on_send(&input);
+                                        #[cfg(feature = "otel")]
+                                        self.tracer.record_input(&input);
+                                    }
+                                    continue;
+                                }
+                            }
+
+                            // Check if this is a result message
+                            if matches!(output, ClaudeOutput::Result(_)) {
+                                debug!("[CLIENT] Received result message, stream complete");
+                                Ok(Some(output))
+                            } else {
+                                Ok(Some(output))
+                            }
+                        }
+                        Err(parse_error) => {
+                            warn!("[CLIENT] Failed to deserialize message from Claude CLI. Please report this at https://github.com/meawoppl/rust-claude-codes/issues with the raw message below.");
+                            warn!("[CLIENT] Parse error: {}", parse_error.error_message);
+                            warn!("[CLIENT] Raw message: {}", trimmed);
+                            let action = self
+                                .parse_error_handler
+                                .as_mut()
+                                .map_or(ParseErrorAction::Skip, |handler| handler(&parse_error));
+                            if action == ParseErrorAction::Skip {
+                                debug!("[CLIENT] Skipping unparseable message");
+                                continue;
+                            }
+                            Err(parse_error.into())
                         }
-                    }
-                    Err(parse_error) => {
-                        warn!("[CLIENT] Failed to deserialize message from Claude CLI. Please report this at https://github.com/meawoppl/rust-claude-codes/issues with the raw message below.");
-                        warn!("[CLIENT] Parse error: {}", parse_error.error_message);
-                        warn!("[CLIENT] Raw message: {}", trimmed);
-                        Err(parse_error.into())
                     }
                 }
+                Err(e) => {
+                    debug!("[CLIENT] Error reading from stdout: {}", e);
+                    Err(e)
+                }
+            };
+        }
+    }
+
+    /// After stdout closes, check whether the process exited because it is
+    /// not authenticated or crashed, reading the reason from its stderr.
+    fn failure_on_exit(&mut self) -> Option<Error> {
+        let deadline = Instant::now() + EXIT_GRACE_PERIOD;
+        let status = loop {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                break status;
             }
-            Err(e) => {
-                debug!("[CLIENT] Error reading from stdout: {}", e);
-                Err(Error::Io(e))
+            if Instant::now() >= deadline {
+                return None;
             }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let mut text = String::new();
+        if let Some(stderr) = self.child.stderr.as_mut() {
+            if let Err(e) = stderr.read_to_string(&mut text) {
+                debug!("[CLIENT] Failed to read stderr after exit: {}", e);
+            }
+        }
+        if let Some(problem) = AuthProblem::detect(&text) {
+            return Some(Error::NotAuthenticated {
+                problem,
+                message: text.trim().to_string(),
+            });
         }
+        if status.success() {
+            return None;
+        }
+        let lines = self.frames.recent().map(str::to_string).collect();
+        Some(Error::Crashed(Box::new(CrashReport::new(
+            status.code(),
+            lines,
+            &text,
+        ))))
     }
 
     /// Shutdown the client and wait for the process to exit
     pub fn shutdown(&mut self) -> Result<()> {
         debug!("[CLIENT] Shutting down client");
-        self.child.kill().map_err(Error::Io)?;
-        self.child.wait().map_err(Error::Io)?;
-        Ok(())
+        #[cfg(feature = "otel")]
+        self.tracer.end();
+        codes_spawn::kill_and_reap(&mut self.child).map_err(Error::Io)
     }
 
     /// Get the session UUID if available
@@ -259,22 +593,14 @@ impl SyncClient {
         let init_request = ControlRequestMessage::initialize(&request_id);
 
         debug!("[TOOL_APPROVAL] Sending initialization handshake");
-        Protocol::write_sync(&mut self.stdin, &init_request)?;
+        self.write_message(&init_request)?;
 
         // Wait for the initialization response
         loop {
-            let mut line = String::new();
-            let bytes_read = self.stdout.read_line(&mut line).map_err(Error::Io)?;
-
-            if bytes_read == 0 {
+            if !self.read_frame_line()? {
                 return Err(Error::ConnectionClosed);
             }
-
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-
+            let trimmed = self.frames.frame();
             debug!("[TOOL_APPROVAL] Received: {}", trimmed);
 
             // Try to parse as ClaudeOutput
@@ -342,8 +668,7 @@ impl SyncClient {
             "[TOOL_APPROVAL] Sending control response: {:?}",
             serde_json::to_string(&message)
         );
-        Protocol::write_sync(&mut self.stdin, &message)?;
-        Ok(())
+        self.write_message(&message)
     }
 
     /// Send an interrupt to gracefully stop the current response.
@@ -351,11 +676,98 @@ impl SyncClient {
     /// This writes `{ "subtype": "interrupt" }` to stdin, telling Claude
     /// to stop without killing the session.
     pub fn interrupt(&mut self) -> Result<()> {
-        let input = ClaudeInput::interrupt();
-        Protocol::write_sync(&mut self.stdin, &input)?;
+        self.write_message(&ClaudeInput::interrupt())
+    }
+
+    /// Restore the files edited since `checkpoint_id` to their earlier
+    /// contents; see [`AsyncClient::rewind`](crate::AsyncClient::rewind).
+    ///
+    /// The session must have been spawned with
+    /// [`ClaudeCliBuilder::enable_file_checkpointing`]. Call this between
+    /// turns: messages that arrive while waiting for the CLI's answer are
+    /// dropped.
+    pub fn rewind(&mut self, checkpoint_id: Uuid) -> Result<RewindFilesResult> {
+        self.rewind_files(checkpoint_id, false)
+    }
+
+    /// Report which files [`rewind`](Self::rewind) would restore, without
+    /// changing any.
+    pub fn preview_rewind(&mut self, checkpoint_id: Uuid) -> Result<RewindFilesResult> {
+        self.rewind_files(checkpoint_id, true)
+    }
+
+    /// Switch the session's permission mode; see
+    /// [`AsyncClient::set_permission_mode`](crate::AsyncClient::set_permission_mode).
+    pub fn set_permission_mode(&mut self, mode: PermissionModeName) -> Result<()> {
+        let request =
+            ControlRequestMessage::set_permission_mode(format!("mode-{}", Uuid::new_v4()), mode);
+        self.control_request(request)?;
         Ok(())
     }
 
+    /// Grant the session access to another directory; see
+    /// [`AsyncClient::add_directory`](crate::AsyncClient::add_directory).
+    pub fn add_directory(&mut self, path: impl AsRef<Path>) -> Result<AddDirectoryResult> {
+        let request = ControlRequestMessage::add_directory(
+            format!("add-dir-{}", Uuid::new_v4()),
+            path.as_ref().to_string_lossy(),
+        );
+        let response = self.control_request(request)?;
+        Ok(match response {
+            Some(response) => serde_json::from_value(response)?,
+            None => AddDirectoryResult::default(),
+        })
+    }
+
+    fn rewind_files(&mut self, checkpoint_id: Uuid, dry_run: bool) -> Result<RewindFilesResult> {
+        let request = ControlRequestMessage::rewind_files(
+            format!("rewind-{}", Uuid::new_v4()),
+            checkpoint_id.to_string(),
+            dry_run,
+        );
+        let response = self
+            .control_request(request)?
+            .ok_or_else(|| Error::MissingField("rewind_files response".to_string()))?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Send a control request and wait for the CLI's response to it,
+    /// dropping any other messages in between.
+    fn control_request(
+        &mut self,
+        request: ControlRequestMessage,
+    ) -> Result<Option<serde_json::Value>> {
+        self.write_message(&request)?;
+
+        loop {
+            if !self.read_frame_line()? {
+                return Err(Error::ConnectionClosed);
+            }
+            match ClaudeOutput::parse_json_tolerant(self.frames.frame())? {
+                ClaudeOutput::ControlResponse(resp) => match resp.response {
+                    ControlResponsePayload::Success {
+                        request_id,
+                        response,
+                    } if request_id == request.request_id => return Ok(response),
+                    ControlResponsePayload::Error { request_id, error }
+                        if request_id == request.request_id =>
+                    {
+                        return Err(Error::Protocol(format!(
+                            "control request failed: {}",
+                            error
+                        )))
+                    }
+                    _ => continue,
+                },
+                other => debug!(
+                    "[CONTROL] Dropping {} while waiting for {}",
+                    other.message_type(),
+                    request.request_id
+                ),
+            }
+        }
+    }
+
     /// Check if tool approval protocol is enabled
     pub fn is_tool_approval_enabled(&self) -> bool {
         self.tool_approval_enabled
@@ -375,13 +787,10 @@ impl Protocol {
 
     /// Read a message from a synchronous reader
     pub fn read_sync<R: BufRead, T: for<'de> Deserialize<'de>>(reader: &mut R) -> Result<T> {
-        let mut line = String::new();
-        let bytes_read = reader.read_line(&mut line)?;
-        if bytes_read == 0 {
-            return Err(Error::ConnectionClosed);
-        }
-        debug!("[PROTOCOL] Received: {}", line.trim());
-        Self::deserialize(&line)
+        let mut frames = FrameReader::new();
+        let line = frames.read_frame(reader)?.ok_or(Error::ConnectionClosed)?;
+        debug!("[PROTOCOL] Received: {}", line);
+        Self::deserialize(line)
     }
 }
 
@@ -461,3 +870,17 @@ impl<R: std::io::Read> StreamProcessor<R> {
         Ok(())
     }
 }
+
+/// The text a user message is cached under: its text when it is a single
+/// text block, otherwise its content blocks as JSON. Other inputs aren't
+/// cached.
+#[cfg(feature = "cache")]
+fn cache_prompt(input: &ClaudeInput) -> Option<String> {
+    let ClaudeInput::User(message) = input else {
+        return None;
+    };
+    match message.message.content.as_slice() {
+        [ContentBlock::Text(text)] => Some(text.text.clone()),
+        blocks => serde_json::to_string(blocks).ok(),
+    }
+}
@@ -0,0 +1,320 @@
+//! Declarative client configuration loaded from a file.
+//!
+//! [`ClientConfig`] mirrors the [`ClaudeCliBuilder`] options that describe
+//! *how* sessions run — binary, model, permissions, tools, MCP servers,
+//! timeouts, retries, and spending limits — so a fleet can ship them as a
+//! JSON or TOML file instead of code. Per-session settings (prompt, resume
+//! target, session ID) and credentials are left to the code that spawns the
+//! session.
+//!
+//! ```toml
+//! binary = "/opt/claude/bin/claude"
+//! preset = "ci"
+//! model = "sonnet"
+//! allowed_tools = ["Read", "Grep", "Bash(cargo test:*)"]
+//! api_timeout_ms = 120000
+//! max_retries = 5
+//! max_budget_usd = 2.5
+//!
+//! [env]
+//! DISABLE_TELEMETRY = "1"
+//! ```
+//!
+//! TOML files need the `toml` feature; JSON is always supported.
+
+use crate::cli::{ClaudeCliBuilder, ModelFamily, PermissionMode, Preset};
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Builder and client options read from a configuration file.
+///
+/// Every field is optional; unset fields leave the builder's default.
+/// Unknown keys are rejected so a misspelled option fails loudly.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClientConfig {
+    /// Path to the `claude` binary
+    pub binary: Option<PathBuf>,
+    /// Preset applied before the other options
+    pub preset: Option<Preset>,
+    pub model: Option<String>,
+    pub fallback_model: Option<String>,
+    pub permission_mode: Option<PermissionMode>,
+    pub permission_prompt_tool: Option<String>,
+    pub dangerously_skip_permissions: Option<bool>,
+    pub allowed_tools: Vec<String>,
+    pub disallowed_tools: Vec<String>,
+    /// MCP server configs: file paths or JSON strings
+    pub mcp_config: Vec<String>,
+    pub strict_mcp_config: Option<bool>,
+    /// Names of the only MCP servers the session may load
+    pub allowed_mcp_servers: Option<Vec<String>>,
+    pub append_system_prompt: Option<String>,
+    /// Settings file path or JSON string
+    pub settings: Option<String>,
+    pub add_dirs: Vec<PathBuf>,
+    pub max_thinking_tokens: Option<u32>,
+    /// JSON Schema for the final answer, written inline
+    pub json_schema: Option<Value>,
+    pub enable_file_checkpointing: Option<bool>,
+    /// CLI configuration directory (`CLAUDE_CONFIG_DIR`)
+    pub config_dir: Option<PathBuf>,
+    pub bedrock: Option<BedrockConfig>,
+    pub vertex: Option<VertexConfig>,
+    /// Model ID overrides per family (`opus`, `sonnet`, `haiku`)
+    pub model_mappings: BTreeMap<ModelFamily, String>,
+    /// Extra environment variables for the spawned process
+    pub env: BTreeMap<String, String>,
+    /// Per-request model API timeout in milliseconds
+    pub api_timeout_ms: Option<u64>,
+    /// MCP server startup timeout in milliseconds
+    pub mcp_timeout_ms: Option<u64>,
+    /// Retries for failed model API requests
+    pub max_retries: Option<u32>,
+    /// Spending cap per session in US dollars
+    pub max_budget_usd: Option<f64>,
+}
+
+/// Amazon Bedrock settings (see [`ClaudeCliBuilder::use_bedrock`])
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BedrockConfig {
+    pub region: String,
+    /// Model ID or inference profile ARN
+    pub model: String,
+}
+
+/// Google Vertex AI settings (see [`ClaudeCliBuilder::use_vertex`])
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VertexConfig {
+    pub project: String,
+    pub region: String,
+}
+
+impl ClientConfig {
+    /// Read a config file, parsed as TOML if the extension is `.toml` and as
+    /// JSON otherwise.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let is_toml = path.extension().is_some_and(|ext| ext == "toml");
+        let parsed = if is_toml {
+            Self::parse_toml(&text)
+        } else {
+            serde_json::from_str(&text).map_err(|e| e.to_string())
+        };
+        parsed.map_err(|message| Error::Config {
+            path: path.to_path_buf(),
+            message,
+        })
+    }
+
+    #[cfg(feature = "toml")]
+    fn parse_toml(text: &str) -> std::result::Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(feature = "toml"))]
+    fn parse_toml(_text: &str) -> std::result::Result<Self, String> {
+        Err("TOML config files need the `toml` feature".to_string())
+    }
+
+    /// Apply these options on top of `builder`.
+    pub fn apply(&self, mut builder: ClaudeCliBuilder) -> ClaudeCliBuilder {
+        if let Some(preset) = self.preset {
+            builder = builder.preset(preset);
+        }
+        if let Some(ref binary) = self.binary {
+            builder = builder.command(binary);
+        }
+        if let Some(ref model) = self.model {
+            builder = builder.model(model);
+        }
+        if let Some(ref model) = self.fallback_model {
+            builder = builder.fallback_model(model);
+        }
+        if let Some(mode) = self.permission_mode {
+            builder = builder.permission_mode(mode);
+        }
+        if let Some(ref tool) = self.permission_prompt_tool {
+            builder = builder.permission_prompt_tool(tool);
+        }
+        if let Some(skip) = self.dangerously_skip_permissions {
+            builder = builder.dangerously_skip_permissions(skip);
+        }
+        if !self.allowed_tools.is_empty() {
+            builder = builder.allowed_tools(&self.allowed_tools);
+        }
+        if !self.disallowed_tools.is_empty() {
+            builder = builder.disallowed_tools(&self.disallowed_tools);
+        }
+        if !self.mcp_config.is_empty() {
+            builder = builder.mcp_config(&self.mcp_config);
+        }
+        if let Some(strict) = self.strict_mcp_config {
+            builder = builder.strict_mcp_config(strict);
+        }
+        if let Some(ref names) = self.allowed_mcp_servers {
+            builder = builder.allowed_mcp_servers(names);
+        }
+        if let Some(ref prompt) = self.append_system_prompt {
+            builder = builder.append_system_prompt(prompt);
+        }
+        if let Some(ref settings) = self.settings {
+            builder = builder.settings(settings);
+        }
+        if !self.add_dirs.is_empty() {
+            builder = builder.add_directories(&self.add_dirs);
+        }
+        if let Some(tokens) = self.max_thinking_tokens {
+            builder = builder.max_thinking_tokens(tokens);
+        }
+        if let Some(ref schema) = self.json_schema {
+            builder = builder.json_schema(schema.to_string());
+        }
+        if let Some(enabled) = self.enable_file_checkpointing {
+            builder = builder.enable_file_checkpointing(enabled);
+        }
+        if let Some(ref dir) = self.config_dir {
+            builder = builder.config_dir(dir);
+        }
+        if let Some(ref bedrock) = self.bedrock {
+            builder = builder.use_bedrock(&bedrock.region, &bedrock.model);
+        }
+        if let Some(ref vertex) = self.vertex {
+            builder = builder.use_vertex(&vertex.project, &vertex.region);
+        }
+        for (family, model_id) in &self.model_mappings {
+            builder = builder.model_mapping(*family, model_id);
+        }
+        for (key, value) in &self.env {
+            builder = builder.env(key, value);
+        }
+        if let Some(ms) = self.api_timeout_ms {
+            builder = builder.api_timeout(Duration::from_millis(ms));
+        }
+        if let Some(ms) = self.mcp_timeout_ms {
+            builder = builder.mcp_timeout(Duration::from_millis(ms));
+        }
+        if let Some(retries) = self.max_retries {
+            builder = builder.max_retries(retries);
+        }
+        if let Some(usd) = self.max_budget_usd {
+            builder = builder.max_budget_usd(usd);
+        }
+        builder
+    }
+}
+
+impl ClaudeCliBuilder {
+    /// Create a builder from a [`ClientConfig`] file (TOML or JSON).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use claude_codes::ClaudeCliBuilder;
+    ///
+    /// let builder = ClaudeCliBuilder::from_config_file("/etc/agent/claude.json")?
+    ///     .prompt("Summarize the open issues");
+    /// # Ok::<(), claude_codes::Error>(())
+    /// ```
+    pub fn from_config_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(ClientConfig::from_file(path)?.apply(Self::new()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_temp(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_json_config_applies_to_builder() {
+        let path = write_temp(
+            "claude-config.json",
+            r#"{
+                "preset": "ci",
+                "model": "sonnet",
+                "permission_mode": "acceptEdits",
+                "allowed_tools": ["Read", "Grep"],
+                "json_schema": {"type": "object"},
+                "model_mappings": {"haiku": "claude-haiku-4-5"},
+                "env": {"DISABLE_TELEMETRY": "1"},
+                "api_timeout_ms": 60000,
+                "max_retries": 4,
+                "max_budget_usd": 1.5
+            }"#,
+        );
+        let config = ClientConfig::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(config.permission_mode, Some(PermissionMode::AcceptEdits));
+
+        let builder = config.apply(ClaudeCliBuilder::new());
+        let args = builder.build_args();
+        let flag_value = |flag: &str| {
+            args.iter()
+                .position(|a| a == flag)
+                .map(|i| args[i + 1].as_str())
+        };
+        assert_eq!(flag_value("--model"), Some("sonnet"));
+        assert_eq!(flag_value("--permission-mode"), Some("acceptEdits"));
+        assert_eq!(flag_value("--json-schema"), Some(r#"{"type":"object"}"#));
+        assert_eq!(flag_value("--max-budget-usd"), Some("1.5"));
+        assert!(args.contains(&"--strict-mcp-config".to_string()));
+
+        let env = builder.env_overrides();
+        for (key, value) in [
+            ("ANTHROPIC_DEFAULT_HAIKU_MODEL", "claude-haiku-4-5"),
+            ("DISABLE_TELEMETRY", "1"),
+            ("API_TIMEOUT_MS", "60000"),
+            ("CLAUDE_CODE_MAX_RETRIES", "4"),
+        ] {
+            assert!(env.contains(&(key.to_string(), Some(value.to_string()))));
+        }
+    }
+
+    #[test]
+    fn test_invalid_config_reports_path() {
+        let path = write_temp("claude-config-bad.json", r#"{"modle": "sonnet"}"#);
+        let err = ClientConfig::from_file(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        match err {
+            Error::Config { path: p, message } => {
+                assert_eq!(p, path);
+                assert!(message.contains("modle"), "{message}");
+            }
+            other => panic!("Expected Config error, got {other:?}"),
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_config() {
+        let path = write_temp(
+            "claude-config.toml",
+            r#"
+            binary = "/opt/claude/bin/claude"
+            model = "opus"
+            mcp_timeout_ms = 30000
+
+            [bedrock]
+            region = "us-west-2"
+            model = "us.anthropic.claude-opus-4"
+            "#,
+        );
+        let builder = ClaudeCliBuilder::from_config_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let env = builder.env_overrides();
+        assert!(env.contains(&("AWS_REGION".to_string(), Some("us-west-2".to_string()))));
+        assert!(env.contains(&("MCP_TIMEOUT".to_string(), Some("30000".to_string()))));
+    }
+}
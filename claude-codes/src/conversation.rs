@@ -0,0 +1,595 @@
+//! Plan-then-execute turns over one Claude session.
+//!
+//! A [`Conversation`] keeps an [`AsyncClient`] and a session ID together and
+//! wraps the plan mode mechanics: [`plan`](Conversation::plan) switches the
+//! session into plan mode, sends the prompt, and stops when Claude proposes a
+//! plan with `ExitPlanMode`; [`execute_plan`](Conversation::execute_plan)
+//! approves it, switches to the chosen permission mode, and runs the rest of
+//! the turn. [`revise_plan`](Conversation::revise_plan) sends feedback
+//! instead and waits for the next proposal.
+//!
+//! The CLI only offers `ExitPlanMode` to SDK sessions that answer permission
+//! prompts, so spawn it with
+//! [`permission_prompt_tool("stdio")`](crate::ClaudeCliBuilder::permission_prompt_tool).
+//!
+//! Every turn a conversation runs is recorded, and
+//! [`export`](Conversation::export) returns the record as a serializable
+//! [`ConversationHistory`]. [`import`](Conversation::import) continues it in a
+//! newly spawned client, on another machine or after a CLI upgrade: by
+//! resuming or forking the CLI's session when its transcript is available
+//! there, or by replaying the turns as a single prompt when it isn't (see
+//! [`ImportMode`]).
+//!
+//! ```no_run
+//! use claude_codes::conversation::Conversation;
+//! use claude_codes::{AsyncClient, ClaudeCliBuilder, PermissionModeName};
+//!
+//! # async fn example() -> claude_codes::Result<()> {
+//! let builder = ClaudeCliBuilder::new().permission_prompt_tool("stdio");
+//! let mut conversation = Conversation::new(AsyncClient::from_builder(builder).await?);
+//!
+//! let mut plan = conversation.plan("Add a --verbose flag to the CLI").await?;
+//! if !plan.text.contains("test") {
+//!     plan = conversation.revise_plan("Include a step that adds tests").await?;
+//! }
+//! println!("{}", plan.text);
+//! conversation.execute_plan(PermissionModeName::AcceptEdits).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::cli::ClaudeCliBuilder;
+use crate::client_async::AsyncClient;
+use crate::error::{Error, Result};
+use crate::io::{
+    ClaudeInput, ClaudeOutput, ControlRequestPayload, ControlResponse, PermissionModeName,
+    PermissionResult, ToolPermissionRequest,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use uuid::Uuid;
+
+type PermissionHandler = Box<dyn FnMut(&ToolPermissionRequest) -> PermissionResult + Send>;
+
+/// A plan Claude proposed in plan mode, waiting for approval.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// The plan, as Markdown.
+    pub text: String,
+    /// The file the CLI saved the plan to, if it reported one.
+    pub file_path: Option<String>,
+    /// Every message received while Claude worked on the plan.
+    pub messages: Vec<ClaudeOutput>,
+}
+
+/// One prompt sent in a conversation and everything received in answer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryTurn {
+    /// The user's prompt, or their feedback on a plan.
+    pub prompt: String,
+    pub messages: Vec<ClaudeOutput>,
+}
+
+impl HistoryTurn {
+    /// Claude's text replies in the turn, separated by blank lines, or the
+    /// turn's result text if it had none.
+    pub fn response_text(&self) -> String {
+        let replies: Vec<String> = self
+            .messages
+            .iter()
+            .filter_map(ClaudeOutput::assistant_text)
+            .collect();
+        if replies.is_empty() {
+            return self
+                .messages
+                .iter()
+                .find_map(ClaudeOutput::result_text)
+                .unwrap_or_default()
+                .to_string();
+        }
+        replies.join("\n\n")
+    }
+}
+
+/// A portable record of a [`Conversation`], from
+/// [`export`](Conversation::export).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationHistory {
+    /// The ID the conversation sent its prompts under.
+    pub session_id: Uuid,
+    /// The CLI's ID for the session, from the last result received; needed
+    /// to resume or fork it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cli_session_id: Option<Uuid>,
+    pub turns: Vec<HistoryTurn>,
+}
+
+impl ConversationHistory {
+    /// The prompt [`ImportMode::Replay`] sends: every turn's prompt and
+    /// Claude's replies, as a transcript for Claude to continue from.
+    pub fn replay_prompt(&self) -> String {
+        let mut prompt = String::from(
+            "The following is an earlier conversation between us, carried over from \
+             another session. Treat it as our conversation so far and continue from where \
+             it left off. Reply to this message with just \"OK\".\n\n<conversation>\n",
+        );
+        for turn in &self.turns {
+            let _ = write!(
+                prompt,
+                "\nUser: {}\n\nAssistant: {}\n",
+                turn.prompt.trim(),
+                turn.response_text().trim()
+            );
+        }
+        prompt.push_str("</conversation>");
+        prompt
+    }
+}
+
+/// How [`Conversation::import`] carries a history into a new client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Resume the CLI's session, continuing under the same session ID. The
+    /// CLI must have the session's transcript, e.g. after a CLI upgrade on
+    /// the same machine or with the transcript copied across.
+    Resume,
+    /// Resume the CLI's session as a new session, leaving the original
+    /// untouched. Needs the transcript like [`Resume`](Self::Resume).
+    Fork,
+    /// Start a new session and send the history as one prompt
+    /// ([`ConversationHistory::replay_prompt`]). Works anywhere, but Claude
+    /// only sees the text of earlier turns, not their tool calls.
+    Replay,
+}
+
+/// The `ExitPlanMode` request a [`Plan`] came from, still unanswered.
+struct PendingPlan {
+    request_id: String,
+    request: ToolPermissionRequest,
+}
+
+/// An [`AsyncClient`] bound to one session, with plan mode turns.
+pub struct Conversation {
+    client: AsyncClient,
+    session_id: Uuid,
+    on_permission: PermissionHandler,
+    pending: Option<PendingPlan>,
+    history: Vec<HistoryTurn>,
+    /// The CLI's session ID from the last result received.
+    cli_session_id: Option<Uuid>,
+}
+
+impl Conversation {
+    /// Start a conversation with a new session ID.
+    pub fn new(client: AsyncClient) -> Self {
+        Self::with_session(client, Uuid::new_v4())
+    }
+
+    /// Start a conversation that sends its prompts under `session_id`.
+    pub fn with_session(client: AsyncClient, session_id: Uuid) -> Self {
+        Self {
+            client,
+            session_id,
+            on_permission: Box::new(|req| {
+                PermissionResult::deny(format!(
+                    "{} needs approval and this conversation has no permission handler",
+                    req.tool_name
+                ))
+            }),
+            pending: None,
+            history: Vec::new(),
+            cli_session_id: None,
+        }
+    }
+
+    /// Spawn the CLI described by `builder` and continue `history` in it.
+    ///
+    /// The returned conversation's own history starts with `history`'s
+    /// turns, so exporting it again carries them along, under the CLI
+    /// session ID of the new session once it is known (after the replay
+    /// turn, or the first turn of a fork). Fails with
+    /// [`Error::InvalidState`] if `mode` resumes but `history` has no CLI
+    /// session ID, and with [`Error::Protocol`] if the replay turn fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use claude_codes::conversation::{Conversation, ConversationHistory, ImportMode};
+    /// use claude_codes::ClaudeCliBuilder;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let history: ConversationHistory =
+    ///     serde_json::from_str(&std::fs::read_to_string("conversation.json")?)?;
+    /// let mut conversation =
+    ///     Conversation::import(ClaudeCliBuilder::new(), &history, ImportMode::Replay).await?;
+    /// conversation.query("Where were we?").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn import(
+        builder: ClaudeCliBuilder,
+        history: &ConversationHistory,
+        mode: ImportMode,
+    ) -> Result<Self> {
+        let builder = match mode {
+            ImportMode::Resume | ImportMode::Fork => {
+                let id = history.cli_session_id.ok_or_else(|| {
+                    Error::InvalidState("history has no CLI session ID to resume".to_string())
+                })?;
+                builder
+                    .resume(Some(id.to_string()))
+                    .fork_session(mode == ImportMode::Fork)
+            }
+            ImportMode::Replay => builder,
+        };
+        let client = AsyncClient::from_builder(builder).await?;
+        let mut conversation = Self::with_session(client, history.session_id);
+        if mode == ImportMode::Replay && !history.turns.is_empty() {
+            conversation
+                .client
+                .send(&ClaudeInput::user_message(
+                    history.replay_prompt(),
+                    history.session_id,
+                ))
+                .await?;
+            let messages = conversation.finish_turn().await?;
+            if let Some(result) = messages.iter().find_map(ClaudeOutput::as_result) {
+                if result.is_error {
+                    return Err(Error::Protocol(format!(
+                        "replaying the conversation failed: {}",
+                        result.result.as_deref().unwrap_or("unknown error")
+                    )));
+                }
+            }
+        }
+        conversation.history = history.turns.clone();
+        conversation.cli_session_id = conversation.cli_session_id.or(history.cli_session_id);
+        Ok(conversation)
+    }
+
+    /// Decide the permission prompts for tools other than `ExitPlanMode`.
+    ///
+    /// Without a handler every such prompt is denied; tools already allowed
+    /// by the permission mode or `allowed_tools` never prompt.
+    pub fn on_permission(
+        mut self,
+        handler: impl FnMut(&ToolPermissionRequest) -> PermissionResult + Send + 'static,
+    ) -> Self {
+        self.on_permission = Box::new(handler);
+        self
+    }
+
+    pub fn session_id(&self) -> Uuid {
+        self.session_id
+    }
+
+    /// The underlying client, for sending and receiving outside plan turns.
+    pub fn client(&mut self) -> &mut AsyncClient {
+        &mut self.client
+    }
+
+    pub fn into_client(self) -> AsyncClient {
+        self.client
+    }
+
+    /// The turns run so far, oldest first.
+    pub fn history(&self) -> &[HistoryTurn] {
+        &self.history
+    }
+
+    /// A portable copy of the conversation so far, for
+    /// [`import`](Self::import).
+    pub fn export(&self) -> ConversationHistory {
+        ConversationHistory {
+            session_id: self.session_id,
+            cli_session_id: self.cli_session_id,
+            turns: self.history.clone(),
+        }
+    }
+
+    /// Send `prompt` as an ordinary turn and collect its messages up to and
+    /// including the result. Fails with [`Error::InvalidState`] while a plan
+    /// is pending.
+    pub async fn query(&mut self, prompt: &str) -> Result<Vec<ClaudeOutput>> {
+        if self.pending.is_some() {
+            return Err(Error::InvalidState(
+                "a plan is waiting for approval".to_string(),
+            ));
+        }
+        self.client
+            .send(&ClaudeInput::user_message(prompt, self.session_id))
+            .await?;
+        self.start_turn(prompt);
+        self.finish_turn().await
+    }
+
+    /// Whether a plan is waiting for [`execute_plan`](Self::execute_plan),
+    /// [`revise_plan`](Self::revise_plan), or [`reject_plan`](Self::reject_plan).
+    pub fn has_pending_plan(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Run a turn in plan mode and return the plan Claude proposes.
+    ///
+    /// Returns once Claude calls `ExitPlanMode`; the turn stays open until
+    /// the plan is approved or rejected. Fails with [`Error::Protocol`] if
+    /// the turn ends without a plan, e.g. because Claude asked a question
+    /// instead, and with [`Error::InvalidState`] if an earlier plan is still
+    /// pending.
+    pub async fn plan(&mut self, prompt: &str) -> Result<Plan> {
+        if self.pending.is_some() {
+            return Err(Error::InvalidState(
+                "a plan is already waiting for approval".to_string(),
+            ));
+        }
+        self.client.enable_tool_approval().await?;
+        self.client
+            .set_permission_mode(PermissionModeName::Plan)
+            .await?;
+        self.client
+            .send(&ClaudeInput::user_message(prompt, self.session_id))
+            .await?;
+        self.start_turn(prompt);
+        self.await_plan().await
+    }
+
+    /// Send `feedback` on the pending plan and return Claude's next one.
+    pub async fn revise_plan(&mut self, feedback: &str) -> Result<Plan> {
+        let pending = self.take_pending()?;
+        let response = pending.request.deny(feedback, &pending.request_id);
+        self.client.send_control_response(response).await?;
+        self.start_turn(feedback);
+        self.await_plan().await
+    }
+
+    /// Approve the pending plan, switch the session to `mode`, and collect
+    /// the rest of the turn up to and including its result.
+    pub async fn execute_plan(&mut self, mode: PermissionModeName) -> Result<Vec<ClaudeOutput>> {
+        let pending = self.take_pending()?;
+        let response = pending.request.approve_plan(mode, &pending.request_id);
+        self.client.send_control_response(response).await?;
+        self.finish_turn().await
+    }
+
+    /// Reject the pending plan and end the turn.
+    pub async fn reject_plan(&mut self, message: &str) -> Result<Vec<ClaudeOutput>> {
+        let pending = self.take_pending()?;
+        let response = pending
+            .request
+            .deny_and_interrupt(message, &pending.request_id);
+        self.client.send_control_response(response).await?;
+        self.finish_turn().await
+    }
+
+    fn start_turn(&mut self, prompt: &str) {
+        self.history.push(HistoryTurn {
+            prompt: prompt.to_string(),
+            messages: Vec::new(),
+        });
+    }
+
+    /// Add `output` to the current turn's history.
+    fn record(&mut self, output: &ClaudeOutput) {
+        if let Some(id) = output
+            .as_result()
+            .and_then(|result| result.session_id.as_uuid())
+        {
+            self.cli_session_id = Some(id);
+        }
+        if let Some(turn) = self.history.last_mut() {
+            turn.messages.push(output.clone());
+        }
+    }
+
+    fn take_pending(&mut self) -> Result<PendingPlan> {
+        self.pending
+            .take()
+            .ok_or_else(|| Error::InvalidState("no plan is waiting for approval".to_string()))
+    }
+
+    async fn await_plan(&mut self) -> Result<Plan> {
+        let mut messages = Vec::new();
+        loop {
+            let output = self.client.receive().await?;
+            self.record(&output);
+            if let ClaudeOutput::ControlRequest(req) = &output {
+                if let ControlRequestPayload::CanUseTool(perm) = &req.request {
+                    if let Some(input) = perm.plan() {
+                        self.pending = Some(PendingPlan {
+                            request_id: req.request_id.clone(),
+                            request: perm.clone(),
+                        });
+                        return Ok(Plan {
+                            text: input.plan.unwrap_or_default(),
+                            file_path: input.plan_file_path,
+                            messages,
+                        });
+                    }
+                }
+            }
+            let is_result = matches!(output, ClaudeOutput::Result(_));
+            self.answer_permission(&output).await?;
+            messages.push(output);
+            if is_result {
+                return Err(Error::Protocol(
+                    "turn ended without Claude proposing a plan".to_string(),
+                ));
+            }
+        }
+    }
+
+    async fn finish_turn(&mut self) -> Result<Vec<ClaudeOutput>> {
+        let mut messages = Vec::new();
+        loop {
+            let output = self.client.receive().await?;
+            self.record(&output);
+            let is_result = matches!(output, ClaudeOutput::Result(_));
+            self.answer_permission(&output).await?;
+            messages.push(output);
+            if is_result {
+                return Ok(messages);
+            }
+        }
+    }
+
+    async fn answer_permission(&mut self, output: &ClaudeOutput) -> Result<()> {
+        if let ClaudeOutput::ControlRequest(req) = output {
+            if let ControlRequestPayload::CanUseTool(perm) = &req.request {
+                let result = (self.on_permission)(perm);
+                self.client
+                    .send_control_response(ControlResponse::from_result(&req.request_id, result))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ClaudeCliBuilder;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_plan_then_execute() {
+        use crate::test_support::{fake_cli, temp_path};
+
+        let log = temp_path("requests.log");
+        // Acknowledge control requests, propose a plan for the prompt, and
+        // finish the turn once the plan is answered.
+        let script = format!(
+            r#"#!/bin/sh
+while read -r line; do
+  echo "$line" >> {log}
+  case "$line" in
+    *'"type":"control_request"'*)
+      id=$(printf '%s' "$line" | sed 's/.*"request_id":"\([^"]*\)".*/\1/')
+      echo "{{\"type\":\"control_response\",\"response\":{{\"subtype\":\"success\",\"request_id\":\"$id\"}}}}" ;;
+    *'"type":"user"'*)
+      echo '{{"type":"control_request","request_id":"perm-1","request":{{"subtype":"can_use_tool","tool_name":"ExitPlanMode","input":{{"plan":"1. Add the flag","planFilePath":"/tmp/plan.md"}},"permission_suggestions":[]}}}}' ;;
+    *'"type":"control_response"'*)
+      echo '{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"done","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}}' ;;
+  esac
+done
+"#,
+            log = log.display()
+        );
+        let cli = fake_cli(&script);
+
+        let builder = ClaudeCliBuilder::new()
+            .command(&cli)
+            .permission_prompt_tool("stdio");
+        let mut conversation = Conversation::new(AsyncClient::from_builder(builder).await.unwrap());
+
+        let plan = conversation.plan("Add a --verbose flag").await.unwrap();
+        assert_eq!(plan.text, "1. Add the flag");
+        assert_eq!(plan.file_path.as_deref(), Some("/tmp/plan.md"));
+        assert!(conversation.has_pending_plan());
+        assert!(matches!(
+            conversation.plan("again").await,
+            Err(Error::InvalidState(_))
+        ));
+
+        let messages = conversation
+            .execute_plan(PermissionModeName::AcceptEdits)
+            .await
+            .unwrap();
+        assert!(messages.last().unwrap().as_result().is_some());
+        assert!(!conversation.has_pending_plan());
+
+        let sent = std::fs::read_to_string(&log).unwrap();
+        assert!(sent.contains(r#""subtype":"set_permission_mode","mode":"plan""#));
+        let approval = sent.lines().last().unwrap();
+        assert!(approval.contains(r#""behavior":"allow""#), "{approval}");
+        assert!(approval.contains(r#""mode":"acceptEdits""#), "{approval}");
+
+        conversation.into_client().shutdown().await.unwrap();
+        std::fs::remove_file(cli).ok();
+        std::fs::remove_file(log).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_export_and_import() {
+        use crate::test_support::{fake_cli, temp_path};
+
+        let log = temp_path("input.log");
+        let args = temp_path("args.log");
+        // Log the arguments and every line received; answer each prompt.
+        // Each process reports its own CLI session, numbered by spawn.
+        let script = format!(
+            r#"#!/bin/sh
+echo "$@" >> {args}
+n=$(wc -l < {args} | tr -d ' ')
+while read -r line; do
+  printf '%s\n' "$line" >> {log}
+  echo '{{"type":"assistant","message":{{"id":"m","role":"assistant","model":"claude-3","content":[{{"type":"text","text":"Blue."}}]}},"session_id":"00000000-0000-4000-8000-00000000000'$n'"}}'
+  echo '{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"Blue.","session_id":"00000000-0000-4000-8000-00000000000'$n'","total_cost_usd":0.0}}'
+done
+"#,
+            args = args.display(),
+            log = log.display()
+        );
+        let cli = fake_cli(&script);
+        let builder = || ClaudeCliBuilder::new().command(&cli);
+        let cli_session =
+            |n: u8| Uuid::parse_str(&format!("00000000-0000-4000-8000-00000000000{n}")).unwrap();
+
+        let mut conversation =
+            Conversation::new(AsyncClient::from_builder(builder()).await.unwrap());
+        let messages = conversation.query("Name a color").await.unwrap();
+        assert_eq!(messages.len(), 2);
+        let history = conversation.export();
+        conversation.into_client().shutdown().await.unwrap();
+        assert_eq!(history.turns.len(), 1);
+        assert_eq!(history.turns[0].response_text(), "Blue.");
+        assert_eq!(history.cli_session_id, Some(cli_session(1)));
+
+        let json = serde_json::to_string(&history).unwrap();
+        let history: ConversationHistory = serde_json::from_str(&json).unwrap();
+
+        let imported = Conversation::import(builder(), &history, ImportMode::Replay)
+            .await
+            .unwrap();
+        assert_eq!(imported.history().len(), 1);
+        assert_eq!(imported.session_id(), history.session_id);
+        let reexported = imported.export();
+        assert_eq!(reexported.turns.len(), 1);
+        assert_eq!(reexported.cli_session_id, Some(cli_session(2)));
+        imported.into_client().shutdown().await.unwrap();
+        let sent = std::fs::read_to_string(&log).unwrap();
+        let replay = sent.lines().last().unwrap();
+        assert!(replay.contains("User: Name a color"), "{replay}");
+        assert!(replay.contains("Assistant: Blue."), "{replay}");
+
+        let mut forked = Conversation::import(builder(), &history, ImportMode::Fork)
+            .await
+            .unwrap();
+        assert_eq!(forked.export().cli_session_id, Some(cli_session(1)));
+        forked.query("Another one").await.unwrap();
+        assert_eq!(forked.history().len(), 2);
+        assert_eq!(forked.export().cli_session_id, Some(cli_session(3)));
+        forked.into_client().shutdown().await.unwrap();
+        let spawned = std::fs::read_to_string(&args).unwrap();
+        let fork_args = spawned.lines().last().unwrap();
+        assert!(
+            fork_args.contains(&format!("--resume {}", cli_session(1))),
+            "{fork_args}"
+        );
+        assert!(fork_args.contains("--fork-session"), "{fork_args}");
+
+        let unresumable = ConversationHistory {
+            cli_session_id: None,
+            ..history
+        };
+        assert!(matches!(
+            Conversation::import(builder(), &unresumable, ImportMode::Resume).await,
+            Err(Error::InvalidState(_))
+        ));
+
+        for path in [cli, log, args] {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Raw wire mirroring for debugging client sessions.
+//!
+//! A [`DebugSink`] receives a copy of every line the client writes to or
+//! reads from the Claude CLI, prefixed with an RFC 3339 timestamp and a
+//! direction marker:
+//!
+//! ```text
+//! 2026-01-01T12:00:00.000Z >> {"type":"user","message":{...}}
+//! 2026-01-01T12:00:01.250Z << {"type":"assistant","message":{...}}
+//! ```
+//!
+//! `>>` marks lines sent to Claude and `<<` marks lines received from it.
+//! Install one with `set_debug_sink` / `set_debug_sink_file` on
+//! [`AsyncClient`](crate::AsyncClient) or [`SyncClient`](crate::SyncClient).
+
+use chrono::{SecondsFormat, Utc};
+use log::warn;
+use std::fmt;
+use std::io::Write;
+
+/// Which way a mirrored line travelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Written to the CLI's stdin.
+    Outbound,
+    /// Read from the CLI's stdout.
+    Inbound,
+}
+
+impl Direction {
+    /// The marker written before each line (`>>` or `<<`).
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Direction::Outbound => ">>",
+            Direction::Inbound => "<<",
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.marker())
+    }
+}
+
+/// A writer that mirrors raw protocol lines with timestamps and direction markers.
+pub struct DebugSink {
+    writer: Box<dyn Write + Send>,
+}
+
+impl DebugSink {
+    /// Wrap any writer (a file, `std::io::stderr()`, a `Vec<u8>`, ...).
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+        }
+    }
+
+    /// Open `path` for appending, creating it if needed.
+    pub fn file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self::new(file))
+    }
+
+    /// Mirror one line. A trailing newline on `line` is ignored.
+    ///
+    /// Write failures are logged and otherwise ignored so a broken debug sink
+    /// never interrupts the session it is observing.
+    pub fn record(&mut self, direction: Direction, line: &str) {
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if let Err(e) = writeln!(self.writer, "{} {} {}", timestamp, direction, line)
+            .and_then(|_| self.writer.flush())
+        {
+            warn!("[DEBUG_SINK] Failed to write debug line: {}", e);
+        }
+    }
+}
+
+impl fmt::Debug for DebugSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugSink").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_record_writes_timestamp_marker_and_line() {
+        let buf = SharedBuf::default();
+        let mut sink = DebugSink::new(buf.clone());
+        sink.record(Direction::Outbound, "{\"type\":\"user\"}\n");
+        sink.record(Direction::Inbound, "{\"type\":\"result\"}");
+
+        let out = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let (ts, rest) = lines[0].split_once(' ').unwrap();
+        assert!(chrono::DateTime::parse_from_rfc3339(ts).is_ok());
+        assert_eq!(rest, ">> {\"type\":\"user\"}");
+        assert!(lines[1].ends_with("<< {\"type\":\"result\"}"));
+    }
+
+    #[test]
+    fn test_file_sink_appends() {
+        let path = std::env::temp_dir().join(format!("claude-debug-{}.log", uuid::Uuid::new_v4()));
+        DebugSink::file(&path)
+            .unwrap()
+            .record(Direction::Outbound, "one");
+        DebugSink::file(&path)
+            .unwrap()
+            .record(Direction::Inbound, "two");
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(contents.lines().count(), 2);
+        assert!(contents.contains(">> one"));
+        assert!(contents.contains("<< two"));
+    }
+}
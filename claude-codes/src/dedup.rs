@@ -0,0 +1,225 @@
+//! Dropping messages delivered twice.
+//!
+//! Resuming a session or replaying a transcript can deliver a message that
+//! was already seen. [`DedupFilter`] remembers the `uuid` of recent messages
+//! in a bounded LRU set and rejects repeats; [`DedupExt::dedup_by_uuid`]
+//! applies it to any iterator of messages, such as the response iterators
+//! of the blocking and sync clients.
+//!
+//! ```
+//! use claude_codes::dedup::DedupExt;
+//! use claude_codes::ClaudeOutput;
+//!
+//! let line = r#"{"type":"user","message":{"role":"user","content":[]},
+//!     "session_id":"550e8400-e29b-41d4-a716-446655440000",
+//!     "uuid":"0bcbb682-4075-55ad-b82b-4205729a091e"}"#;
+//! let replayed = vec![
+//!     ClaudeOutput::parse_json(line).unwrap(),
+//!     ClaudeOutput::parse_json(line).unwrap(),
+//! ];
+//! assert_eq!(replayed.into_iter().dedup_by_uuid(1024).count(), 1);
+//! ```
+
+use crate::io::ClaudeOutput;
+use std::collections::{HashMap, VecDeque};
+use uuid::Uuid;
+
+/// A message, or a fallible read of one, that may carry a `uuid`.
+pub trait MessageUuid {
+    /// The `uuid` to deduplicate on; items without one are never dropped.
+    fn message_uuid(&self) -> Option<Uuid>;
+}
+
+impl MessageUuid for ClaudeOutput {
+    fn message_uuid(&self) -> Option<Uuid> {
+        self.uuid()
+    }
+}
+
+impl<T: MessageUuid, E> MessageUuid for Result<T, E> {
+    fn message_uuid(&self) -> Option<Uuid> {
+        self.as_ref().ok().and_then(MessageUuid::message_uuid)
+    }
+}
+
+/// Remembers the most recently seen message `uuid`s, up to a fixed count.
+///
+/// Seeing a `uuid` again makes it the most recent; once the set is full,
+/// the least recently seen one is forgotten.
+#[derive(Debug, Clone)]
+pub struct DedupFilter {
+    capacity: usize,
+    /// Each remembered uuid and the tick it was last seen at
+    seen: HashMap<Uuid, u64>,
+    /// Uuids in the order they were seen; entries whose tick no longer
+    /// matches `seen` are stale and skipped
+    order: VecDeque<(Uuid, u64)>,
+    tick: u64,
+}
+
+impl DedupFilter {
+    /// A filter remembering up to `capacity` uuids (at least one).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            seen: HashMap::new(),
+            order: VecDeque::new(),
+            tick: 0,
+        }
+    }
+
+    /// Whether `item` has not been seen recently, remembering it either way.
+    ///
+    /// Items without a `uuid` are always new.
+    pub fn is_new<T: MessageUuid + ?Sized>(&mut self, item: &T) -> bool {
+        item.message_uuid().is_none_or(|uuid| self.insert(uuid))
+    }
+
+    /// Remember `uuid`, returning `true` if it was not already remembered.
+    pub fn insert(&mut self, uuid: Uuid) -> bool {
+        self.tick += 1;
+        let new = self.seen.insert(uuid, self.tick).is_none();
+        self.order.push_back((uuid, self.tick));
+        if new && self.seen.len() > self.capacity {
+            self.evict_oldest();
+        }
+        if self.order.len() > self.capacity * 2 {
+            let seen = &self.seen;
+            self.order
+                .retain(|(uuid, tick)| seen.get(uuid) == Some(tick));
+        }
+        new
+    }
+
+    /// Number of uuids remembered.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+
+    /// Forget every uuid.
+    pub fn clear(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+    }
+
+    fn evict_oldest(&mut self) {
+        while let Some((uuid, tick)) = self.order.pop_front() {
+            if self.seen.get(&uuid) == Some(&tick) {
+                self.seen.remove(&uuid);
+                return;
+            }
+        }
+    }
+}
+
+/// Iterator adaptor returned by [`DedupExt::dedup_by_uuid`].
+#[derive(Debug, Clone)]
+pub struct Deduped<I> {
+    inner: I,
+    filter: DedupFilter,
+}
+
+impl<I> Deduped<I> {
+    /// The filter, e.g. to check how many uuids it holds.
+    pub fn filter(&self) -> &DedupFilter {
+        &self.filter
+    }
+
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+}
+
+impl<I> Iterator for Deduped<I>
+where
+    I: Iterator,
+    I::Item: MessageUuid,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let filter = &mut self.filter;
+        self.inner.find(|item| filter.is_new(item))
+    }
+}
+
+/// Adds [`dedup_by_uuid`](Self::dedup_by_uuid) to iterators of messages.
+pub trait DedupExt: Iterator + Sized
+where
+    Self::Item: MessageUuid,
+{
+    /// Skip messages whose `uuid` was among the last `capacity` seen.
+    ///
+    /// Errors and messages without a `uuid` pass through.
+    fn dedup_by_uuid(self, capacity: usize) -> Deduped<Self> {
+        Deduped {
+            inner: self,
+            filter: DedupFilter::new(capacity),
+        }
+    }
+}
+
+impl<I> DedupExt for I
+where
+    I: Iterator,
+    I::Item: MessageUuid,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(n: u128) -> Uuid {
+        Uuid::from_u128(n)
+    }
+
+    #[test]
+    fn test_filter_evicts_least_recently_seen() {
+        let mut filter = DedupFilter::new(2);
+        assert!(filter.insert(id(1)));
+        assert!(filter.insert(id(2)));
+        assert!(!filter.insert(id(1)));
+        // 2 is now the least recently seen, so 3 pushes it out.
+        assert!(filter.insert(id(3)));
+        assert_eq!(filter.len(), 2);
+        assert!(!filter.insert(id(1)));
+        assert!(filter.insert(id(2)));
+
+        for _ in 0..10 {
+            filter.insert(id(2));
+        }
+        assert!(filter.order.len() <= 4);
+    }
+
+    #[test]
+    fn test_dedup_passes_errors_and_messages_without_uuid() {
+        let with_uuid = |uuid: &str| {
+            ClaudeOutput::parse_json(&format!(
+                r#"{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0,"uuid":"{uuid}"}}"#
+            ))
+            .unwrap()
+        };
+        let no_uuid = ClaudeOutput::parse_json(
+            r#"{"type":"control_response","response":{"subtype":"success","request_id":"r"}}"#,
+        )
+        .unwrap();
+        let a = "4aede724-089c-5140-86e0-2e9a29f7462f";
+        let b = "a6cbbc68-962a-54a9-8937-51f25d60e97c";
+        let items: Vec<Result<ClaudeOutput, String>> = vec![
+            Ok(with_uuid(a)),
+            Ok(no_uuid.clone()),
+            Ok(with_uuid(a)),
+            Err("read failed".to_string()),
+            Ok(no_uuid),
+            Ok(with_uuid(b)),
+        ];
+        let kept: Vec<_> = items.into_iter().dedup_by_uuid(8).collect();
+        assert_eq!(kept.len(), 5);
+        assert!(kept[2].is_err());
+    }
+}
@@ -1,6 +1,7 @@
 //! Error types for the Claude Code protocol
 
-use crate::io::ParseError;
+use crate::io::{AuthProblem, ParseError};
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -29,6 +30,22 @@ pub enum Error {
     #[error("Connection closed")]
     ConnectionClosed,
 
+    /// A line of CLI output was longer than the configured maximum. The line
+    /// was skipped; the stream can still be read.
+    #[error("Message of {size} bytes exceeds the {limit}-byte limit")]
+    MessageTooLarge { size: usize, limit: usize },
+
+    /// A prompt's estimated size exceeds the
+    /// [`TokenBudget`](crate::tokens::TokenBudget) it was checked against.
+    /// Nothing was sent.
+    #[error("Prompt of about {estimated} tokens exceeds the {limit}-token budget")]
+    PromptTooLarge { estimated: usize, limit: usize },
+
+    /// The output ended partway through a message, usually because the CLI
+    /// exited mid-write. `partial` is what was received.
+    #[error("Output ended mid-message after {} bytes", .partial.len())]
+    TruncatedMessage { partial: String },
+
     #[error("Deserialization error: {0}")]
     Deserialization(#[from] ParseError),
 
@@ -38,8 +55,178 @@ pub enum Error {
     #[error("Binary not found: '{name}' is not on PATH. Is it installed?")]
     BinaryNotFound { name: String },
 
+    #[error("Not authenticated ({problem}): {message}. {}", .problem.remediation())]
+    NotAuthenticated {
+        problem: AuthProblem,
+        message: String,
+    },
+
+    /// A configuration file couldn't be parsed.
+    #[error("Invalid config file {}: {message}", .path.display())]
+    Config {
+        path: std::path::PathBuf,
+        message: String,
+    },
+
+    #[error("Invalid CLI configuration: {0}")]
+    Builder(#[from] BuilderError),
+
+    /// The CLI exited with a failure status while its output was being read.
+    #[error("Claude CLI exited unexpectedly ({0})")]
+    Crashed(Box<CrashReport>),
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
 
+impl Error {
+    /// Whether receiving stopped for good with this error: the CLI is gone
+    /// or its output can't be read any further.
+    #[cfg(feature = "async-client")]
+    pub(crate) fn ends_output(&self) -> bool {
+        matches!(
+            self,
+            Error::ConnectionClosed
+                | Error::NotAuthenticated { .. }
+                | Error::TruncatedMessage { .. }
+                | Error::Crashed(_)
+                | Error::Io(_)
+        )
+    }
+}
+
+/// What the CLI left behind when it exited unexpectedly.
+///
+/// Carried by [`Error::Crashed`]. Collected whether or not debug logging was
+/// on, so the error itself is enough for a postmortem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashReport {
+    /// Exit code, or `None` if the process was killed by a signal.
+    pub exit_code: Option<i32>,
+    /// The last lines read from stdout, oldest first, each cut to 2 KiB.
+    pub last_stdout_lines: Vec<String>,
+    /// The end of stderr, at most [`STDERR_TAIL_BYTES`](Self::STDERR_TAIL_BYTES).
+    /// Empty if stderr was taken with `take_stderr`.
+    pub stderr_tail: String,
+}
+
+impl CrashReport {
+    /// Stdout lines the clients keep for a report.
+    pub const STDOUT_LINES: usize = 20;
+    /// Bytes of stderr kept for a report.
+    pub const STDERR_TAIL_BYTES: usize = 8 * 1024;
+
+    /// Build a report, keeping only the end of `stderr`.
+    pub fn new(exit_code: Option<i32>, last_stdout_lines: Vec<String>, stderr: &str) -> Self {
+        let stderr = stderr.trim_end();
+        let mut start = stderr.len().saturating_sub(Self::STDERR_TAIL_BYTES);
+        while !stderr.is_char_boundary(start) {
+            start += 1;
+        }
+        Self {
+            exit_code,
+            last_stdout_lines,
+            stderr_tail: stderr[start..].to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.exit_code {
+            Some(code) => write!(f, "exit code {code}")?,
+            None => write!(f, "killed by a signal")?,
+        }
+        if let Some(last) = self
+            .stderr_tail
+            .lines()
+            .rfind(|line| !line.trim().is_empty())
+        {
+            write!(f, ": {}", last.trim())?;
+        }
+        Ok(())
+    }
+}
+
+/// An incompatible combination of `ClaudeCliBuilder` options.
+///
+/// Returned by `ClaudeCliBuilder::validate` and by the spawn methods before
+/// the CLI process is started, so misconfigurations surface as typed errors
+/// instead of a CLI that exits immediately with a usage message.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BuilderError {
+    #[error("--resume and --continue cannot be used together")]
+    ResumeAndContinue,
+
+    #[error(
+        "--session-id can only be used with --resume or --continue if --fork-session is also set"
+    )]
+    SessionIdWithoutFork,
+
+    #[error("--fork-session requires --resume or --continue")]
+    ForkWithoutResume,
+
+    #[error("--resume-session-at requires --resume")]
+    ResumeAtWithoutResume,
+
+    #[error("--permission-prompt-tool has no effect with --dangerously-skip-permissions")]
+    PermissionPromptToolWithSkipPermissions,
+
+    #[error("--dangerously-skip-permissions conflicts with --permission-mode {mode}")]
+    SkipPermissionsWithPermissionMode { mode: String },
+
+    #[error("--fallback-model cannot be the same as --model ('{model}')")]
+    FallbackSameAsModel { model: String },
+
+    #[error("allowed MCP servers need --settings to be a JSON object, not a file path")]
+    McpAllowlistWithSettingsFile,
+
+    #[error("sandbox settings need --settings to be a JSON object, not a file path")]
+    SandboxWithSettingsFile,
+
+    /// An option needs a newer CLI than the one the builder was told about.
+    #[error("{option} needs Claude CLI {required} or newer, found {found}")]
+    RequiresNewerCli {
+        option: String,
+        required: String,
+        found: String,
+    },
+
+    #[error("no session ID found in transcript {path}")]
+    TranscriptWithoutSession { path: String },
+
+    #[error("invalid value for {name}: '{value}'")]
+    InvalidEnvVar { name: String, value: String },
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+impl From<codes_codec::FrameError> for Error {
+    fn from(err: codes_codec::FrameError) -> Self {
+        match err {
+            codes_codec::FrameError::Io(e) => Error::Io(e),
+            codes_codec::FrameError::TooLarge { size, limit } => {
+                Error::MessageTooLarge { size, limit }
+            }
+            codes_codec::FrameError::Truncated { partial } => Error::TruncatedMessage { partial },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_report_keeps_stderr_tail() {
+        let stderr = format!("{}\nfatal: out of memory\n\n", "é".repeat(5000));
+        let report = CrashReport::new(Some(134), vec!["{}".to_string()], &stderr);
+        assert!(report.stderr_tail.len() <= CrashReport::STDERR_TAIL_BYTES);
+        assert!(report.stderr_tail.ends_with("fatal: out of memory"));
+        assert_eq!(
+            Error::Crashed(Box::new(report)).to_string(),
+            "Claude CLI exited unexpectedly (exit code 134: fatal: out of memory)"
+        );
+    }
+}
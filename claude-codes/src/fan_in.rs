@@ -0,0 +1,174 @@
+//! Merge the output of several sessions into one stream.
+//!
+//! A dashboard supervising many agents wants one place to read from, not a
+//! `select!` over every client. [`select_all`] takes [`ClientHandle`]s and
+//! returns a [`SelectAll`] stream of `(ClientId, ClaudeOutput)` pairs, where
+//! the [`ClientId`] is the handle's position in the input. The handles are
+//! shared, so input can still be sent to each session while the merged
+//! stream is read.
+//!
+//! ```no_run
+//! use claude_codes::{select_all, AsyncClient, ClaudeInput, ClaudeOutput};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> claude_codes::Result<()> {
+//! let mut handles = Vec::new();
+//! for _ in 0..3 {
+//!     handles.push(Arc::new(AsyncClient::with_defaults().await?.into_handle()));
+//! }
+//! for handle in &handles {
+//!     let prompt = ClaudeInput::user_message("Run the tests", uuid::Uuid::new_v4());
+//!     handle.send(prompt).await?;
+//! }
+//!
+//! let mut merged = select_all(handles.iter().cloned());
+//! while let Some((id, output)) = merged.recv().await {
+//!     if let ClaudeOutput::Result(result) = output {
+//!         println!("agent {id} finished: {:?}", result.result);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A session's messages arrive in order; messages from different sessions
+//! interleave in the order they were read. Output that a client reports as
+//! an error is logged and skipped, and a session drops out of the stream
+//! once its CLI exits. The stream ends when every session has.
+
+use crate::client_handle::ClientHandle;
+use crate::io::ClaudeOutput;
+use log::{debug, warn};
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Which of the [`select_all`] inputs a message came from: its position,
+/// starting at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClientId(pub usize);
+
+impl fmt::Display for ClientId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Merged output of several sessions, returned by [`select_all`].
+///
+/// Implements [`futures_core::Stream`]; [`recv`](Self::recv) reads it
+/// without a futures combinator crate. Dropping it stops reading from the
+/// handles but leaves the sessions running.
+pub struct SelectAll {
+    outputs: mpsc::UnboundedReceiver<(ClientId, ClaudeOutput)>,
+    readers: Vec<JoinHandle<()>>,
+}
+
+/// Merge the messages of every handle in `clients` into one stream, each
+/// tagged with the [`ClientId`] of the handle it came from.
+///
+/// Must be called from within a tokio runtime. While the stream exists it
+/// is the only reader of the handles: don't also call
+/// [`ClientHandle::recv`] on them, or messages are split between the two.
+pub fn select_all<I>(clients: I) -> SelectAll
+where
+    I: IntoIterator<Item = Arc<ClientHandle>>,
+{
+    let (tx, outputs) = mpsc::unbounded_channel();
+    let readers = clients
+        .into_iter()
+        .enumerate()
+        .map(|(i, handle)| tokio::spawn(forward(ClientId(i), handle, tx.clone())))
+        .collect();
+    SelectAll { outputs, readers }
+}
+
+impl SelectAll {
+    /// The next message from any session, or `None` once all have ended.
+    pub async fn recv(&mut self) -> Option<(ClientId, ClaudeOutput)> {
+        self.outputs.recv().await
+    }
+}
+
+impl futures_core::Stream for SelectAll {
+    type Item = (ClientId, ClaudeOutput);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.outputs.poll_recv(cx)
+    }
+}
+
+impl Drop for SelectAll {
+    fn drop(&mut self) {
+        for reader in &self.readers {
+            reader.abort();
+        }
+    }
+}
+
+/// Read `handle` until its CLI exits or the stream is dropped.
+async fn forward(
+    id: ClientId,
+    handle: Arc<ClientHandle>,
+    outputs: mpsc::UnboundedSender<(ClientId, ClaudeOutput)>,
+) {
+    loop {
+        match handle.recv().await {
+            Ok(output) => {
+                if outputs.send((id, output)).is_err() {
+                    return;
+                }
+            }
+            Err(e) if e.ends_output() => {
+                debug!("[FAN-IN] Client {} ended: {}", id, e);
+                return;
+            }
+            Err(e) => warn!("[FAN-IN] Skipping output from client {}: {}", id, e),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::cli::ClaudeCliBuilder;
+    use crate::client_async::AsyncClient;
+    use crate::io::ClaudeInput;
+    use crate::test_support::fake_cli;
+
+    #[tokio::test]
+    async fn test_select_all_tags_each_session() {
+        // Answer one input, then exit.
+        let script = r#"#!/bin/sh
+read -r line
+echo '{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}'
+"#;
+        let cli = fake_cli(script);
+
+        let mut handles = Vec::new();
+        for _ in 0..3 {
+            let client = AsyncClient::from_builder(ClaudeCliBuilder::new().command(&cli))
+                .await
+                .unwrap();
+            handles.push(Arc::new(client.into_handle()));
+        }
+        let mut merged = select_all(handles.iter().cloned());
+        for handle in &handles {
+            let input = ClaudeInput::user_message("hi", uuid::Uuid::new_v4());
+            handle.send(input).await.unwrap();
+        }
+
+        let mut ids = Vec::new();
+        while let Some((id, output)) = merged.recv().await {
+            assert!(output.as_result().is_some());
+            ids.push(id);
+        }
+        ids.sort();
+        assert_eq!(ids, [ClientId(0), ClientId(1), ClientId(2)]);
+
+        std::fs::remove_file(cli).ok();
+    }
+}
@@ -0,0 +1,226 @@
+//! Canned protocol messages for tests.
+//!
+//! Representative CLI output for each message type, as raw JSON lines in
+//! [`json`] and as parsed [`ClaudeOutput`] values from the functions here, so
+//! parsers, renderers, and UIs can be tested without a live CLI. The samples
+//! are trimmed from real sessions and share one session id, [`SESSION_ID`].
+//! [`session`] strings several of them into a complete tool-using turn.
+//!
+//! ```
+//! use claude_codes::fixtures;
+//!
+//! let turn = fixtures::session();
+//! assert_eq!(turn.first().unwrap().message_type(), "system");
+//! assert!(turn.last().unwrap().as_result().is_some());
+//!
+//! for (name, line) in fixtures::json::ALL {
+//!     assert!(claude_codes::ClaudeOutput::parse_json(line).is_ok(), "{}", name);
+//! }
+//! ```
+
+use crate::io::ClaudeOutput;
+
+/// Session id used by every fixture.
+pub const SESSION_ID: &str = "4e3453f9-129a-4da9-bc25-a287453d58d9";
+
+/// Tool use id linking [`json::ASSISTANT_TOOL_USE`],
+/// [`json::CONTROL_REQUEST_CAN_USE_TOOL`], and [`json::USER_TOOL_RESULT`].
+pub const TOOL_USE_ID: &str = "toolu_01RmLUJdhjTMn56TnF9cMamW";
+
+/// Raw JSON lines as the CLI writes them.
+pub mod json {
+    /// `system` / `init`: the first message of every session.
+    pub const SYSTEM_INIT: &str = r#"{"type":"system","subtype":"init","cwd":"/tmp/project","session_id":"4e3453f9-129a-4da9-bc25-a287453d58d9","tools":["Bash","Edit","Glob","Grep","Read","Write"],"mcp_servers":[],"model":"claude-sonnet-4-6","permissionMode":"default","slash_commands":["compact","review"],"apiKeySource":"none","claude_code_version":"2.1.178","output_style":"default","agents":["general-purpose","Explore"],"uuid":"a04fe3f9-22d0-41e7-963f-5744eb143b3e"}"#;
+
+    /// `assistant` message with a single text block.
+    pub const ASSISTANT_TEXT: &str = r#"{"type":"assistant","message":{"model":"claude-sonnet-4-6","id":"msg_01QoWnPzFoQtmAvhRBUjxU4k","type":"message","role":"assistant","content":[{"type":"text","text":"There are 21 `.rs` files in `src`."}],"stop_reason":"end_turn","usage":{"input_tokens":12,"output_tokens":16,"cache_read_input_tokens":7281}},"session_id":"4e3453f9-129a-4da9-bc25-a287453d58d9","uuid":"5c1a9e0b-6f4e-4d8e-9a51-7f1f0e2d3c4b"}"#;
+
+    /// `assistant` message with thinking followed by a `Bash` tool use.
+    pub const ASSISTANT_TOOL_USE: &str = r#"{"type":"assistant","message":{"model":"claude-sonnet-4-6","id":"msg_01QoWnPzFoQtmAvhRBUjxU4j","type":"message","role":"assistant","content":[{"type":"thinking","thinking":"I should count the files with find.","signature":"Eq4MCmUIDxgCKkAn"},{"type":"tool_use","id":"toolu_01RmLUJdhjTMn56TnF9cMamW","name":"Bash","input":{"command":"find src -name '*.rs' | wc -l","description":"Count Rust files"}}],"stop_reason":"tool_use","usage":{"input_tokens":4,"output_tokens":38,"cache_read_input_tokens":23673}},"session_id":"4e3453f9-129a-4da9-bc25-a287453d58d9","uuid":"8396f1ec-bc1f-4352-b38b-554a8907b6d7"}"#;
+
+    /// `user` message carrying the result of [`ASSISTANT_TOOL_USE`].
+    pub const USER_TOOL_RESULT: &str = r#"{"type":"user","message":{"role":"user","content":[{"tool_use_id":"toolu_01RmLUJdhjTMn56TnF9cMamW","type":"tool_result","content":"21","is_error":false}]},"session_id":"4e3453f9-129a-4da9-bc25-a287453d58d9","uuid":"23f41a80-91ae-4ba0-ad12-5f33ad8ce879","timestamp":"2026-06-25T00:23:54.795Z"}"#;
+
+    /// `user` message carrying a failed tool result.
+    pub const USER_TOOL_RESULT_ERROR: &str = r#"{"type":"user","message":{"role":"user","content":[{"tool_use_id":"toolu_01XbP9kQ2mZ7vN4cR8tL1sYw","type":"tool_result","content":"Exit code 1\nfind: 'missing': No such file or directory","is_error":true}]},"session_id":"4e3453f9-129a-4da9-bc25-a287453d58d9","uuid":"9a2af237-e8be-4b9a-b54a-313516cf4d3b","timestamp":"2026-06-25T00:23:56.120Z"}"#;
+
+    /// `control_request` / `can_use_tool`: the CLI asking permission to run
+    /// [`ASSISTANT_TOOL_USE`].
+    pub const CONTROL_REQUEST_CAN_USE_TOOL: &str = r#"{"type":"control_request","request_id":"f3cf357c-17d6-4eca-b498-dd17c7ac43dd","request":{"subtype":"can_use_tool","tool_name":"Bash","input":{"command":"find src -name '*.rs' | wc -l","description":"Count Rust files"},"permission_suggestions":[{"type":"setMode","mode":"acceptEdits","destination":"session"}],"tool_use_id":"toolu_01RmLUJdhjTMn56TnF9cMamW"}}"#;
+
+    /// `control_response` answering an SDK control request.
+    pub const CONTROL_RESPONSE_SUCCESS: &str = r#"{"type":"control_response","response":{"subtype":"success","request_id":"init-1","response":{}}}"#;
+
+    /// `rate_limit_event` reporting the current rate-limit window.
+    pub const RATE_LIMIT_EVENT: &str = r#"{"type":"rate_limit_event","rate_limit_info":{"status":"allowed","resetsAt":1782348600,"rateLimitType":"five_hour","overageStatus":"rejected","isUsingOverage":false},"uuid":"8fc11221-3980-4766-8fce-99be9ce6edb4","session_id":"4e3453f9-129a-4da9-bc25-a287453d58d9"}"#;
+
+    /// `result` / `success`: the end of a completed turn.
+    pub const RESULT_SUCCESS: &str = r#"{"type":"result","subtype":"success","is_error":false,"duration_ms":19333,"duration_api_ms":16030,"num_turns":2,"result":"There are 21 `.rs` files in `src`.","stop_reason":"end_turn","session_id":"4e3453f9-129a-4da9-bc25-a287453d58d9","total_cost_usd":0.0763163,"usage":{"input_tokens":16,"cache_creation_input_tokens":7281,"cache_read_input_tokens":30954,"output_tokens":54,"service_tier":"standard"},"permission_denials":[],"uuid":"fbdf4f61-1cac-469f-8034-e7e210fa2719"}"#;
+
+    /// `result` / `error_max_turns`: a turn cut off by `--max-turns`.
+    pub const RESULT_ERROR_MAX_TURNS: &str = r#"{"type":"result","subtype":"error_max_turns","is_error":true,"duration_ms":48211,"duration_api_ms":45007,"num_turns":5,"session_id":"4e3453f9-129a-4da9-bc25-a287453d58d9","total_cost_usd":0.2114,"permission_denials":[],"uuid":"c69e98bc-b639-46a2-8958-1ad342001b6b"}"#;
+
+    /// `error`: an Anthropic API error passed through by the CLI.
+    pub const API_ERROR: &str = r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"},"request_id":"req_011CXPC6BqUogB959LWEf52X"}"#;
+
+    /// Every fixture above, by name.
+    pub const ALL: &[(&str, &str)] = &[
+        ("system_init", SYSTEM_INIT),
+        ("assistant_text", ASSISTANT_TEXT),
+        ("assistant_tool_use", ASSISTANT_TOOL_USE),
+        ("user_tool_result", USER_TOOL_RESULT),
+        ("user_tool_result_error", USER_TOOL_RESULT_ERROR),
+        ("control_request_can_use_tool", CONTROL_REQUEST_CAN_USE_TOOL),
+        ("control_response_success", CONTROL_RESPONSE_SUCCESS),
+        ("rate_limit_event", RATE_LIMIT_EVENT),
+        ("result_success", RESULT_SUCCESS),
+        ("result_error_max_turns", RESULT_ERROR_MAX_TURNS),
+        ("api_error", API_ERROR),
+    ];
+}
+
+fn parse(line: &str) -> ClaudeOutput {
+    ClaudeOutput::parse_json(line).expect("fixture JSON matches the message types")
+}
+
+/// [`json::SYSTEM_INIT`], parsed.
+pub fn system_init() -> ClaudeOutput {
+    parse(json::SYSTEM_INIT)
+}
+
+/// [`json::ASSISTANT_TEXT`], parsed.
+pub fn assistant_text() -> ClaudeOutput {
+    parse(json::ASSISTANT_TEXT)
+}
+
+/// [`json::ASSISTANT_TOOL_USE`], parsed.
+pub fn assistant_tool_use() -> ClaudeOutput {
+    parse(json::ASSISTANT_TOOL_USE)
+}
+
+/// [`json::USER_TOOL_RESULT`], parsed.
+pub fn user_tool_result() -> ClaudeOutput {
+    parse(json::USER_TOOL_RESULT)
+}
+
+/// [`json::USER_TOOL_RESULT_ERROR`], parsed.
+pub fn user_tool_result_error() -> ClaudeOutput {
+    parse(json::USER_TOOL_RESULT_ERROR)
+}
+
+/// [`json::CONTROL_REQUEST_CAN_USE_TOOL`], parsed.
+pub fn control_request_can_use_tool() -> ClaudeOutput {
+    parse(json::CONTROL_REQUEST_CAN_USE_TOOL)
+}
+
+/// [`json::CONTROL_RESPONSE_SUCCESS`], parsed.
+pub fn control_response_success() -> ClaudeOutput {
+    parse(json::CONTROL_RESPONSE_SUCCESS)
+}
+
+/// [`json::RATE_LIMIT_EVENT`], parsed.
+pub fn rate_limit_event() -> ClaudeOutput {
+    parse(json::RATE_LIMIT_EVENT)
+}
+
+/// [`json::RESULT_SUCCESS`], parsed.
+pub fn result_success() -> ClaudeOutput {
+    parse(json::RESULT_SUCCESS)
+}
+
+/// [`json::RESULT_ERROR_MAX_TURNS`], parsed.
+pub fn result_error_max_turns() -> ClaudeOutput {
+    parse(json::RESULT_ERROR_MAX_TURNS)
+}
+
+/// [`json::API_ERROR`], parsed.
+pub fn api_error() -> ClaudeOutput {
+    parse(json::API_ERROR)
+}
+
+/// One complete turn in order: init, a tool use, its permission request and
+/// result, the final answer, and the success result.
+pub fn session() -> Vec<ClaudeOutput> {
+    session_lines().iter().map(|line| parse(line)).collect()
+}
+
+/// [`session`] as JSON Lines, one message per line, as read from the CLI's
+/// stdout.
+pub fn session_jsonl() -> String {
+    session_lines()
+        .iter()
+        .flat_map(|line| [*line, "\n"])
+        .collect()
+}
+
+fn session_lines() -> [&'static str; 6] {
+    [
+        json::SYSTEM_INIT,
+        json::ASSISTANT_TOOL_USE,
+        json::CONTROL_REQUEST_CAN_USE_TOOL,
+        json::USER_TOOL_RESULT,
+        json::ASSISTANT_TEXT,
+        json::RESULT_SUCCESS,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::ContentBlock;
+
+    #[test]
+    fn test_fixtures_parse_to_expected_types() {
+        let expected = [
+            "system",
+            "assistant",
+            "assistant",
+            "user",
+            "user",
+            "control_request",
+            "control_response",
+            "rate_limit_event",
+            "result",
+            "result",
+            "error",
+        ];
+        for ((name, line), kind) in json::ALL.iter().zip(expected) {
+            let output = ClaudeOutput::parse_json(line).unwrap_or_else(|e| panic!("{name}: {e}"));
+            assert_eq!(output.message_type(), kind, "{}", name);
+            assert_eq!(
+                output.session_id().map(|id| id.to_string()),
+                match kind {
+                    "user" | "control_request" | "control_response" | "error" => None,
+                    _ => Some(SESSION_ID.to_string()),
+                },
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_session_links_tool_use_and_result() {
+        let ClaudeOutput::Assistant(msg) = assistant_tool_use() else {
+            panic!("Expected Assistant");
+        };
+        let tool_use = msg
+            .message
+            .content
+            .iter()
+            .find_map(|b| match b {
+                ContentBlock::ToolUse(t) => Some(t),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(tool_use.id, TOOL_USE_ID);
+
+        let result = user_tool_result();
+        assert_eq!(
+            result.tool_results().next().unwrap().tool_use_id,
+            TOOL_USE_ID
+        );
+
+        assert_eq!(session_jsonl().lines().count(), session().len());
+    }
+}
@@ -0,0 +1,193 @@
+//! Line framing for the CLI's JSON Lines output.
+//!
+//! A thin layer over the workspace's shared `codes_codec::FrameReader` that
+//! reports failures as this crate's [`Error`](crate::Error).
+//!
+//! The CLI writes one JSON message per line. [`FrameReader`] reads those lines
+//! into a single buffer that it keeps between calls, so a session that
+//! streams thousands of messages does not allocate a fresh `String` for each
+//! one. The returned frame borrows that buffer; parse it (for example with
+//! [`ClaudeOutput::parse_json`](crate::ClaudeOutput::parse_json)) before
+//! reading the next.
+//!
+//! If the CLI dies while writing a message, the partial last line is
+//! reported as [`Error::TruncatedMessage`](crate::Error::TruncatedMessage)
+//! instead of being handed to the JSON parser.
+//!
+//! A reader can also keep copies of the last few frames
+//! ([`with_history`](FrameReader::with_history)); the clients use them to
+//! build a [`CrashReport`](crate::CrashReport) when the CLI dies.
+//!
+//! ```
+//! use claude_codes::framing::FrameReader;
+//! use std::io::Cursor;
+//!
+//! let mut input = Cursor::new("{\"a\":1}\r\n\r\n  {\"b\":2}  \n");
+//! let mut frames = FrameReader::new();
+//! assert_eq!(frames.read_frame(&mut input).unwrap(), Some("{\"a\":1}"));
+//! assert_eq!(frames.read_frame(&mut input).unwrap(), Some("{\"b\":2}"));
+//! assert_eq!(frames.read_frame(&mut input).unwrap(), None);
+//! ```
+
+use std::io::BufRead;
+
+use crate::error::Result;
+
+/// Reads newline-delimited frames into a reused buffer.
+///
+/// Blank lines are skipped and surrounding whitespace, including the `\r` of
+/// CRLF endings, is trimmed. A last line without a newline is returned if it
+/// holds a complete JSON value; otherwise the writer stopped mid-message and
+/// the read fails with [`Error::TruncatedMessage`](crate::Error::TruncatedMessage).
+#[derive(Debug, Default)]
+pub struct FrameReader(codes_codec::FrameReader);
+
+impl FrameReader {
+    /// A reader with an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refuse lines longer than `max_bytes` (see [`set_max_bytes`](Self::set_max_bytes)).
+    pub fn with_max_bytes(self, max_bytes: usize) -> Self {
+        Self(self.0.with_max_bytes(max_bytes))
+    }
+
+    /// Keep copies of the last `lines` frames, readable with
+    /// [`recent`](Self::recent). Each copy is cut to 2 KiB.
+    pub fn with_history(self, lines: usize) -> Self {
+        Self(self.0.with_history(lines))
+    }
+
+    /// The last frames read, oldest first (empty unless
+    /// [`with_history`](Self::with_history) was set).
+    ///
+    /// ```
+    /// use claude_codes::framing::FrameReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut input = Cursor::new("{\"n\":1}\n{\"n\":2}\n{\"n\":3}\n");
+    /// let mut frames = FrameReader::new().with_history(2);
+    /// while frames.read_frame(&mut input).unwrap().is_some() {}
+    /// assert_eq!(frames.recent().collect::<Vec<_>>(), ["{\"n\":2}", "{\"n\":3}"]);
+    /// ```
+    pub fn recent(&self) -> impl Iterator<Item = &str> {
+        self.0.recent()
+    }
+
+    /// Cap the length of a line, or lift the cap with `None`.
+    ///
+    /// A line longer than the cap is read past and dropped without being
+    /// buffered, and the read returns [`Error::MessageTooLarge`](crate::Error::MessageTooLarge); the next
+    /// read continues with the following line.
+    ///
+    /// ```
+    /// use claude_codes::framing::FrameReader;
+    /// use claude_codes::Error;
+    /// use std::io::Cursor;
+    ///
+    /// let mut input = Cursor::new(format!("{}\n{{\"ok\":true}}\n", "x".repeat(100)));
+    /// let mut frames = FrameReader::new().with_max_bytes(32);
+    /// assert!(matches!(
+    ///     frames.read_frame(&mut input),
+    ///     Err(Error::MessageTooLarge { size: 100, limit: 32 })
+    /// ));
+    /// assert_eq!(frames.read_frame(&mut input).unwrap(), Some("{\"ok\":true}"));
+    /// ```
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.0.set_max_bytes(max_bytes);
+    }
+
+    /// The line length cap, if any.
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.0.max_bytes()
+    }
+
+    /// The most recently read frame (empty before the first read).
+    pub fn frame(&self) -> &str {
+        self.0.frame()
+    }
+
+    /// Current buffer capacity in bytes.
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Read the next non-blank line from `reader`. Returns `None` at EOF.
+    pub fn read_frame<R: BufRead>(&mut self, reader: &mut R) -> Result<Option<&str>> {
+        Ok(self.0.read_frame(reader)?)
+    }
+
+    /// Async version of [`read_frame`](Self::read_frame).
+    #[cfg(feature = "async-core")]
+    pub async fn read_frame_async<R>(&mut self, reader: &mut R) -> Result<Option<&str>>
+    where
+        R: tokio::io::AsyncBufRead + Unpin,
+    {
+        Ok(self.0.read_frame_async(reader).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::io::ClaudeOutput;
+    use std::io::{self, BufReader, Cursor, Read};
+
+    struct Broken;
+
+    impl Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "pipe closed"))
+        }
+    }
+
+    #[test]
+    fn test_codec_errors_map_to_crate_errors() {
+        let mut frames = FrameReader::new().with_max_bytes(8);
+        let mut reader = Cursor::new("{\"long\":true}\n{\"n\":1}\n{\"n\":");
+        assert!(matches!(
+            frames.read_frame(&mut reader),
+            Err(Error::MessageTooLarge { size: 13, limit: 8 })
+        ));
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), Some("{\"n\":1}"));
+        assert!(matches!(
+            frames.read_frame(&mut reader),
+            Err(Error::TruncatedMessage { partial }) if partial == "{\"n\":"
+        ));
+
+        match frames.read_frame(&mut BufReader::new(Broken)) {
+            Err(Error::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::BrokenPipe),
+            other => panic!("Expected Io, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "async-client")]
+    #[tokio::test]
+    async fn test_async_read_maps_errors() {
+        let mut frames = FrameReader::new();
+        let mut reader = tokio::io::BufReader::new(&b"{\"n\":1}\n{\"type\":\"assist"[..]);
+        assert_eq!(
+            frames.read_frame_async(&mut reader).await.unwrap(),
+            Some("{\"n\":1}")
+        );
+        assert!(matches!(
+            frames.read_frame_async(&mut reader).await,
+            Err(Error::TruncatedMessage { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unterminated_cli_line_with_ansi_prefix_is_a_message() {
+        // The CLI can leave a color reset in front of its last line and exit
+        // without a newline; that line is still a whole message.
+        let line = "\u{1b}[0m{\"type\":\"result\",\"subtype\":\"success\",\"is_error\":false,\
+            \"duration_ms\":1,\"duration_api_ms\":1,\"num_turns\":1,\
+            \"session_id\":\"abc\",\"total_cost_usd\":0.0}";
+        let mut frames = FrameReader::new();
+        let frame = frames.read_frame(&mut Cursor::new(line)).unwrap().unwrap();
+        let output = ClaudeOutput::parse_json_tolerant(frame).unwrap();
+        assert!(output.as_result().is_some());
+    }
+}
@@ -0,0 +1,108 @@
+//! Holding user input back until the current turn ends.
+//!
+//! Writing a new user message while Claude is still answering the previous
+//! one interleaves the two turns. [`AsyncClient::queue_input`] instead
+//! buffers the message while a turn is in progress and sends it once the
+//! turn's Result arrives, or when [`AsyncClient::flush_queued_input`] is
+//! called. Each send is reported as an [`InputDispatched`] event, collected
+//! with [`AsyncClient::take_dispatched_input`].
+//!
+//! [`AsyncClient::queue_input`]: crate::AsyncClient::queue_input
+//! [`AsyncClient::flush_queued_input`]: crate::AsyncClient::flush_queued_input
+//! [`AsyncClient::take_dispatched_input`]: crate::AsyncClient::take_dispatched_input
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::io::ClaudeInput;
+
+/// Why queued input was sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DispatchTrigger {
+    /// The turn in progress ended with a Result message.
+    TurnEnded,
+    /// The caller flushed the queue explicitly.
+    Flushed,
+}
+
+impl DispatchTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TurnEnded => "turn_ended",
+            Self::Flushed => "flushed",
+        }
+    }
+}
+
+impl fmt::Display for DispatchTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Queued input that has now been written to the CLI.
+#[derive(Debug, Clone)]
+pub struct InputDispatched {
+    pub input: ClaudeInput,
+    /// How long the input waited in the queue.
+    pub queued_for: Duration,
+    pub trigger: DispatchTrigger,
+}
+
+/// Pending input plus the dispatch events not yet collected.
+#[derive(Debug, Default)]
+pub(crate) struct InputQueue {
+    pending: VecDeque<(ClaudeInput, Instant)>,
+    dispatched: Vec<InputDispatched>,
+    turn_active: bool,
+}
+
+impl InputQueue {
+    pub(crate) fn is_turn_active(&self) -> bool {
+        self.turn_active
+    }
+
+    /// Record that `input` was written; user messages start a turn.
+    pub(crate) fn on_send(&mut self, input: &ClaudeInput) {
+        if matches!(input, ClaudeInput::User(_)) {
+            self.turn_active = true;
+        }
+    }
+
+    /// Record that a Result arrived, ending the turn.
+    pub(crate) fn on_result(&mut self) {
+        self.turn_active = false;
+    }
+
+    pub(crate) fn push(&mut self, input: ClaudeInput) {
+        self.pending.push_back((input, Instant::now()));
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Remove every pending input, oldest first.
+    pub(crate) fn drain(&mut self) -> Vec<(ClaudeInput, Instant)> {
+        self.pending.drain(..).collect()
+    }
+
+    /// Record that a queued input, enqueued at `queued_at`, was sent.
+    pub(crate) fn record_dispatch(
+        &mut self,
+        input: ClaudeInput,
+        queued_at: Instant,
+        trigger: DispatchTrigger,
+    ) {
+        self.dispatched.push(InputDispatched {
+            input,
+            queued_for: queued_at.elapsed(),
+            trigger,
+        });
+    }
+
+    pub(crate) fn take_dispatched(&mut self) -> Vec<InputDispatched> {
+        std::mem::take(&mut self.dispatched)
+    }
+}
@@ -0,0 +1,196 @@
+//! Detection of authentication failures reported by the Claude CLI.
+//!
+//! When the CLI has no usable credentials it does not fail to start; it emits
+//! a synthetic assistant message and an error result (e.g. `Invalid API key ·
+//! Please run /login`), or prints a notice to stderr and exits. These helpers
+//! recognise those reports so clients can surface
+//! [`Error::NotAuthenticated`](crate::Error::NotAuthenticated) instead of a
+//! confusing parse or EOF error.
+
+use super::claude_output::ClaudeOutput;
+use std::fmt;
+
+/// Model name the CLI puts on assistant messages it synthesizes locally
+/// (errors, notices) rather than receiving from the API.
+const SYNTHETIC_MODEL: &str = "<synthetic>";
+
+/// Why the CLI could not authenticate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthProblem {
+    /// No credentials are configured; the CLI asks the user to log in.
+    LoginRequired,
+    /// An API key was supplied but the API rejected it.
+    InvalidApiKey,
+    /// An OAuth token was supplied but has expired or been revoked.
+    TokenExpired,
+}
+
+impl AuthProblem {
+    /// Classify a CLI message, returning `None` if it is not an auth failure.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use claude_codes::AuthProblem;
+    ///
+    /// assert_eq!(
+    ///     AuthProblem::detect("Invalid API key · Please run /login"),
+    ///     Some(AuthProblem::InvalidApiKey)
+    /// );
+    /// assert_eq!(AuthProblem::detect("All done!"), None);
+    /// ```
+    pub fn detect(text: &str) -> Option<Self> {
+        let lower = text.to_lowercase();
+        if lower.contains("invalid api key") || lower.contains("invalid x-api-key") {
+            Some(Self::InvalidApiKey)
+        } else if lower.contains("oauth token has expired")
+            || lower.contains("oauth token has been revoked")
+            || lower.contains("token expired")
+        {
+            Some(Self::TokenExpired)
+        } else if lower.contains("please run /login")
+            || lower.contains("not logged in")
+            || lower.contains("run `claude login`")
+        {
+            Some(Self::LoginRequired)
+        } else {
+            None
+        }
+    }
+
+    /// A short hint telling the end user how to fix the problem.
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            Self::LoginRequired => {
+                "Run `claude /login` in a terminal, or pass credentials to the spawned CLI \
+                 (ANTHROPIC_API_KEY or CLAUDE_CODE_OAUTH_TOKEN)"
+            }
+            Self::InvalidApiKey => {
+                "Check the ANTHROPIC_API_KEY passed to the CLI, or run `claude /login`"
+            }
+            Self::TokenExpired => {
+                "Run `claude /login` again, or supply a fresh CLAUDE_CODE_OAUTH_TOKEN"
+            }
+        }
+    }
+}
+
+impl fmt::Display for AuthProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::LoginRequired => "login required",
+            Self::InvalidApiKey => "invalid API key",
+            Self::TokenExpired => "OAuth token expired",
+        })
+    }
+}
+
+impl ClaudeOutput {
+    /// If this message reports an authentication failure, classify it and
+    /// return the CLI's message text.
+    ///
+    /// Only CLI-generated reports are considered: synthetic assistant
+    /// messages, error results, and `authentication_error` API errors. Model
+    /// output that merely mentions API keys is never flagged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use claude_codes::{AuthProblem, ClaudeOutput};
+    ///
+    /// let json = r#"{"type":"result","subtype":"success","is_error":true,
+    ///     "duration_ms":5,"duration_api_ms":0,"num_turns":1,
+    ///     "result":"Invalid API key · Please run /login",
+    ///     "session_id":"abc","total_cost_usd":0.0}"#;
+    /// let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+    ///
+    /// let (problem, message) = output.auth_problem().unwrap();
+    /// assert_eq!(problem, AuthProblem::InvalidApiKey);
+    /// assert_eq!(message, "Invalid API key · Please run /login");
+    /// ```
+    pub fn auth_problem(&self) -> Option<(AuthProblem, String)> {
+        match self {
+            ClaudeOutput::Assistant(msg) if msg.message.model == SYNTHETIC_MODEL => {
+                let text = msg.concat_text();
+                AuthProblem::detect(&text).map(|p| (p, text))
+            }
+            ClaudeOutput::Result(res) if res.is_error => {
+                let text = res.result.as_deref()?;
+                AuthProblem::detect(text).map(|p| (p, text.to_string()))
+            }
+            ClaudeOutput::Error(err) if err.is_authentication_error() => {
+                let message = err.error.message.clone();
+                let problem = AuthProblem::detect(&message).unwrap_or(AuthProblem::InvalidApiKey);
+                Some((problem, message))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> ClaudeOutput {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_detect_patterns() {
+        assert_eq!(
+            AuthProblem::detect("Not logged in · Please run /login"),
+            Some(AuthProblem::LoginRequired)
+        );
+        assert_eq!(
+            AuthProblem::detect("OAuth token has expired. Please obtain a new token"),
+            Some(AuthProblem::TokenExpired)
+        );
+        assert_eq!(
+            AuthProblem::detect("Invalid API key · Fix external API key"),
+            Some(AuthProblem::InvalidApiKey)
+        );
+        assert_eq!(AuthProblem::detect("Credit balance is too low"), None);
+    }
+
+    #[test]
+    fn test_synthetic_assistant_message_is_flagged() {
+        let output = parse(
+            r#"{"type":"assistant","message":{"id":"m","role":"assistant",
+                "model":"<synthetic>","content":[{"type":"text",
+                "text":"Invalid API key · Please run /login"}]},"session_id":"s"}"#,
+        );
+        assert_eq!(
+            output.auth_problem().map(|(p, _)| p),
+            Some(AuthProblem::InvalidApiKey)
+        );
+    }
+
+    #[test]
+    fn test_model_output_is_not_flagged() {
+        let output = parse(
+            r#"{"type":"assistant","message":{"id":"m","role":"assistant",
+                "model":"claude-sonnet-4","content":[{"type":"text",
+                "text":"An invalid API key returns HTTP 401."}]},"session_id":"s"}"#,
+        );
+        assert!(output.auth_problem().is_none());
+
+        let output = parse(
+            r#"{"type":"result","subtype":"success","is_error":false,"duration_ms":1,
+                "duration_api_ms":1,"num_turns":1,"result":"Please run /login first",
+                "session_id":"s","total_cost_usd":0.0}"#,
+        );
+        assert!(output.auth_problem().is_none());
+    }
+
+    #[test]
+    fn test_api_authentication_error_is_flagged() {
+        let output = parse(
+            r#"{"type":"error","error":{"type":"authentication_error",
+                "message":"invalid x-api-key"},"request_id":"req_1"}"#,
+        );
+        let (problem, message) = output.auth_problem().unwrap();
+        assert_eq!(problem, AuthProblem::InvalidApiKey);
+        assert_eq!(message, "invalid x-api-key");
+    }
+}
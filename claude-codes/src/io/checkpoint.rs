@@ -0,0 +1,107 @@
+//! File checkpoint types.
+//!
+//! With file checkpointing enabled (`ClaudeCliBuilder::enable_file_checkpointing`)
+//! the CLI backs up every file it edits, keyed by the user message that
+//! started the turn. That message's `uuid` is the checkpoint id: pass it to
+//! the clients' `rewind` to restore the workspace as it was before the turn.
+//! Session transcripts record each checkpoint as a [`FileHistorySnapshot`].
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use super::timestamp::Timestamp;
+
+/// `rewind_files` control request (SDK -> CLI): restore tracked files to
+/// their state before the user message `user_message_id`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RewindFilesRequest {
+    pub user_message_id: String,
+    /// Report what would change without touching any file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+}
+
+/// The CLI's answer to a [`RewindFilesRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RewindFilesResult {
+    /// Whether the rewind was (or, for a dry run, could be) applied.
+    pub can_rewind: bool,
+    /// Why the rewind was refused, when `can_rewind` is false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Paths whose contents differ from the checkpoint.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files_changed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insertions: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deletions: Option<u64>,
+    /// Files left alone because a symlink was found at or above the tracked
+    /// path. Only set by a real (non-dry-run) rewind.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_links: Option<u64>,
+}
+
+/// A `file-history-snapshot` transcript entry: the file backups taken for
+/// one checkpoint.
+///
+/// # Example
+/// ```
+/// use claude_codes::FileHistorySnapshot;
+///
+/// let json = r#"{"type":"file-history-snapshot","messageId":"550e8400-e29b-41d4-a716-446655440000",
+///     "snapshot":{"messageId":"550e8400-e29b-41d4-a716-446655440000","trackedFileBackups":{
+///         "src/main.rs":{"backupFileName":"a1b2c3@v1","version":1,"backupTime":"2025-06-01T12:00:00.000Z"}},
+///     "timestamp":"2025-06-01T12:00:00.000Z"},"isSnapshotUpdate":false}"#;
+/// let snapshot: FileHistorySnapshot = serde_json::from_str(json).unwrap();
+/// assert_eq!(snapshot.tracked_files().collect::<Vec<_>>(), ["src/main.rs"]);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename = "file-history-snapshot",
+    rename_all = "camelCase"
+)]
+pub struct FileHistorySnapshot {
+    /// The user message this checkpoint belongs to.
+    pub message_id: Uuid,
+    pub snapshot: FileCheckpoint,
+    /// Whether this entry adds backups to an existing checkpoint rather than
+    /// starting a new one.
+    #[serde(default)]
+    pub is_snapshot_update: bool,
+}
+
+impl FileHistorySnapshot {
+    /// Paths with a backup in this checkpoint.
+    pub fn tracked_files(&self) -> impl Iterator<Item = &str> {
+        self.snapshot
+            .tracked_file_backups
+            .keys()
+            .map(String::as_str)
+    }
+}
+
+/// The file backups making up one checkpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileCheckpoint {
+    pub message_id: Uuid,
+    /// Backups keyed by file path.
+    #[serde(default)]
+    pub tracked_file_backups: HashMap<String, FileBackup>,
+    pub timestamp: Timestamp,
+}
+
+/// One file's backup within a [`FileCheckpoint`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileBackup {
+    /// Backup file name under the CLI's file-history directory; `None` when
+    /// the file did not exist at the checkpoint (rewinding deletes it).
+    pub backup_file_name: Option<String>,
+    pub version: u32,
+    pub backup_time: Timestamp,
+}
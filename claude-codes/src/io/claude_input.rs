@@ -35,7 +35,7 @@ impl ClaudeInput {
                     citations: Vec::new(),
                 })],
             },
-            session_id: Some(session_id),
+            session_id: Some(session_id.into()),
             parent_tool_use_id: None,
             uuid: None,
             timestamp: None,
@@ -52,7 +52,7 @@ impl ClaudeInput {
                 role: super::MessageRole::User,
                 content: blocks,
             },
-            session_id: Some(session_id),
+            session_id: Some(session_id.into()),
             parent_tool_use_id: None,
             uuid: None,
             timestamp: None,
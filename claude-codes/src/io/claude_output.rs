@@ -1,16 +1,24 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use uuid::Uuid;
 
-use super::content_blocks::{ContentBlock, ToolUseBlock};
+use super::content_blocks::{ToolResultBlock, ToolUseBlock};
 use super::control::{ControlRequest, ControlResponse};
+use super::debug_log::DebugMessage;
 use super::errors::{AnthropicError, ParseError};
+use super::lenient_uuid::LenientUuid;
 use super::message_types::{AssistantMessage, SystemMessage, UserMessage};
 use super::rate_limit::RateLimitEvent;
-use super::result::ResultMessage;
+use super::result::{PermissionDenial, ResultMessage};
 
 /// Top-level enum for all possible Claude output messages
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Messages with a `type` this version of the crate doesn't know parse as
+/// [`Unknown`](Self::Unknown); a known `type` whose fields don't match is
+/// still an error.
+#[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
 pub enum ClaudeOutput {
     /// System initialization message
     System(SystemMessage),
@@ -35,6 +43,53 @@ pub enum ClaudeOutput {
 
     /// Rate limit status event
     RateLimitEvent(RateLimitEvent),
+
+    /// A CLI debug log line that reached stdout (see [`DebugMessage`])
+    Debug(DebugMessage),
+
+    /// A message type not yet known to this version of the crate.
+    /// Contains the raw JSON value for caller inspection.
+    #[serde(untagged)]
+    Unknown(Value),
+}
+
+impl<'de> Deserialize<'de> for ClaudeOutput {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = Value::deserialize(deserializer)?;
+        let tag = take_tag(&mut value, "type")?;
+        let parsed = match tag.as_str() {
+            "system" => serde_json::from_value(value).map(ClaudeOutput::System),
+            "user" => serde_json::from_value(value).map(ClaudeOutput::User),
+            "assistant" => serde_json::from_value(value).map(ClaudeOutput::Assistant),
+            "result" => serde_json::from_value(value).map(ClaudeOutput::Result),
+            "control_request" => serde_json::from_value(value).map(ClaudeOutput::ControlRequest),
+            "control_response" => serde_json::from_value(value).map(ClaudeOutput::ControlResponse),
+            "error" => serde_json::from_value(value).map(ClaudeOutput::Error),
+            "rate_limit_event" => serde_json::from_value(value).map(ClaudeOutput::RateLimitEvent),
+            "debug" => serde_json::from_value(value).map(ClaudeOutput::Debug),
+            _ => {
+                value["type"] = Value::String(tag);
+                Ok(ClaudeOutput::Unknown(value))
+            }
+        };
+        parsed.map_err(serde::de::Error::custom)
+    }
+}
+
+/// Remove the tag of an internally tagged enum from `value`, as the derived
+/// deserializers do, so a flattened field of the variant doesn't capture it.
+pub(super) fn take_tag<E: serde::de::Error>(
+    value: &mut Value,
+    key: &'static str,
+) -> Result<String, E> {
+    match value.as_object_mut().and_then(|obj| obj.remove(key)) {
+        Some(Value::String(tag)) => Ok(tag),
+        Some(other) => Err(E::invalid_type(
+            serde::de::Unexpected::Other(&other.to_string()),
+            &"a string tag",
+        )),
+        None => Err(E::missing_field(key)),
+    }
 }
 
 impl ClaudeOutput {
@@ -49,6 +104,12 @@ impl ClaudeOutput {
             ClaudeOutput::ControlResponse(_) => "control_response".to_string(),
             ClaudeOutput::Error(_) => "error".to_string(),
             ClaudeOutput::RateLimitEvent(_) => "rate_limit_event".to_string(),
+            ClaudeOutput::Debug(_) => "debug".to_string(),
+            ClaudeOutput::Unknown(v) => v
+                .get("type")
+                .and_then(|t| t.as_str())
+                .unwrap_or("unknown")
+                .to_string(),
         }
     }
 
@@ -110,6 +171,22 @@ impl ClaudeOutput {
         }
     }
 
+    /// Get the debug log line if this is one
+    pub fn as_debug(&self) -> Option<&DebugMessage> {
+        match self {
+            ClaudeOutput::Debug(msg) => Some(msg),
+            _ => None,
+        }
+    }
+
+    /// Get the raw JSON if this is a message type the crate doesn't know
+    pub fn as_unknown(&self) -> Option<&Value> {
+        match self {
+            ClaudeOutput::Unknown(value) => Some(value),
+            _ => None,
+        }
+    }
+
     /// Check if this is a result with error
     pub fn is_error(&self) -> bool {
         matches!(self, ClaudeOutput::Result(r) if r.is_error)
@@ -141,8 +218,9 @@ impl ClaudeOutput {
 
     /// Get the session ID from any message type that has one.
     ///
-    /// Returns the session ID from System, Assistant, or Result messages.
-    /// Returns `None` for User, ControlRequest, and ControlResponse messages.
+    /// Returns the session ID from System, Assistant, Result, or rate limit
+    /// messages, and from unknown message types that carry one. Returns `None` for User, ControlRequest, and ControlResponse
+    /// messages.
     ///
     /// # Example
     /// ```
@@ -152,18 +230,57 @@ impl ClaudeOutput {
     ///     "duration_ms":100,"duration_api_ms":200,"num_turns":1,
     ///     "session_id":"my-session","total_cost_usd":0.01}"#;
     /// let output: ClaudeOutput = serde_json::from_str(json).unwrap();
-    /// assert_eq!(output.session_id(), Some("my-session"));
+    /// assert_eq!(
+    ///     output.session_id().map(|id| id.to_string()).as_deref(),
+    ///     Some("my-session")
+    /// );
     /// ```
-    pub fn session_id(&self) -> Option<&str> {
+    pub fn session_id(&self) -> Option<LenientUuid> {
         match self {
-            ClaudeOutput::System(sys) => sys.data.get("session_id").and_then(|v| v.as_str()),
-            ClaudeOutput::Assistant(ass) => Some(&ass.session_id),
-            ClaudeOutput::Result(res) => Some(&res.session_id),
+            ClaudeOutput::System(sys) => sys
+                .data
+                .get("session_id")
+                .and_then(|v| v.as_str())
+                .map(LenientUuid::from),
+            ClaudeOutput::Assistant(ass) => Some(ass.session_id.clone()),
+            ClaudeOutput::Result(res) => Some(res.session_id.clone()),
             ClaudeOutput::User(_) => None,
             ClaudeOutput::ControlRequest(_) => None,
             ClaudeOutput::ControlResponse(_) => None,
             ClaudeOutput::Error(_) => None,
-            ClaudeOutput::RateLimitEvent(evt) => Some(&evt.session_id),
+            ClaudeOutput::RateLimitEvent(evt) => Some(evt.session_id.clone()),
+            ClaudeOutput::Debug(_) => None,
+            ClaudeOutput::Unknown(v) => v
+                .get("session_id")
+                .and_then(|v| v.as_str())
+                .map(LenientUuid::from),
+        }
+    }
+
+    /// Get the message's own `uuid`, which the CLI assigns to each
+    /// transcript entry.
+    ///
+    /// Returns `None` for control messages, API errors, debug lines, and
+    /// messages sent without one.
+    pub fn uuid(&self) -> Option<Uuid> {
+        match self {
+            ClaudeOutput::System(sys) => sys
+                .data
+                .get("uuid")
+                .and_then(|v| v.as_str())
+                .and_then(|s| Uuid::parse_str(s).ok()),
+            ClaudeOutput::User(msg) => msg.uuid.as_ref().and_then(LenientUuid::as_uuid),
+            ClaudeOutput::Assistant(msg) => msg.uuid.as_ref().and_then(LenientUuid::as_uuid),
+            ClaudeOutput::Result(res) => res.uuid.as_ref().and_then(LenientUuid::as_uuid),
+            ClaudeOutput::RateLimitEvent(evt) => evt.uuid.as_ref().and_then(LenientUuid::as_uuid),
+            ClaudeOutput::Unknown(v) => v
+                .get("uuid")
+                .and_then(|v| v.as_str())
+                .and_then(|s| Uuid::parse_str(s).ok()),
+            ClaudeOutput::ControlRequest(_)
+            | ClaudeOutput::ControlResponse(_)
+            | ClaudeOutput::Error(_)
+            | ClaudeOutput::Debug(_) => None,
         }
     }
 
@@ -186,15 +303,7 @@ impl ClaudeOutput {
     /// }
     /// ```
     pub fn as_tool_use(&self, tool_name: &str) -> Option<&ToolUseBlock> {
-        match self {
-            ClaudeOutput::Assistant(ass) => {
-                ass.message.content.iter().find_map(|block| match block {
-                    ContentBlock::ToolUse(tu) if tu.name == tool_name => Some(tu),
-                    _ => None,
-                })
-            }
-            _ => None,
-        }
+        self.as_assistant()?.tool_use(tool_name)
     }
 
     /// Get all tool uses from an assistant message.
@@ -217,18 +326,32 @@ impl ClaudeOutput {
     /// assert_eq!(tools.len(), 2);
     /// ```
     pub fn tool_uses(&self) -> impl Iterator<Item = &ToolUseBlock> {
-        let content = match self {
-            ClaudeOutput::Assistant(ass) => Some(&ass.message.content),
-            _ => None,
-        };
+        self.as_assistant()
+            .into_iter()
+            .flat_map(AssistantMessage::tool_uses)
+    }
 
-        content
+    /// Get all tool results from a user message.
+    ///
+    /// Returns an iterator over the `ToolResultBlock`s the CLI echoes back in
+    /// user messages, or an empty iterator for any other message type.
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::ClaudeOutput;
+    ///
+    /// let json = r#"{"type":"user","message":{"role":"user","content":[
+    ///         {"type":"tool_result","tool_use_id":"tu_1","content":"file.txt"}
+    ///     ]},"session_id":"550e8400-e29b-41d4-a716-446655440000"}"#;
+    /// let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+    ///
+    /// let results: Vec<_> = output.tool_results().collect();
+    /// assert_eq!(results[0].tool_use_id, "tu_1");
+    /// ```
+    pub fn tool_results(&self) -> impl Iterator<Item = &ToolResultBlock> {
+        self.as_user()
             .into_iter()
-            .flat_map(|c| c.iter())
-            .filter_map(|block| match block {
-                ContentBlock::ToolUse(tu) => Some(tu),
-                _ => None,
-            })
+            .flat_map(UserMessage::tool_results)
     }
 
     /// Get text content from an assistant message.
@@ -247,24 +370,58 @@ impl ClaudeOutput {
     /// assert_eq!(output.text_content(), Some("Hello, world!".to_string()));
     /// ```
     pub fn text_content(&self) -> Option<String> {
+        self.assistant_text()
+    }
+
+    /// Get the text of an assistant message.
+    ///
+    /// Joins every text block in the message, skipping tool uses and
+    /// thinking. Returns `None` if this is not an assistant message or it has
+    /// no text blocks.
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::ClaudeOutput;
+    ///
+    /// let json = r#"{"type":"assistant","message":{"id":"msg_1","role":"assistant",
+    ///     "model":"claude-3","content":[{"type":"text","text":"Done."}]},
+    ///     "session_id":"abc"}"#;
+    /// let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+    /// assert_eq!(output.assistant_text().as_deref(), Some("Done."));
+    /// ```
+    pub fn assistant_text(&self) -> Option<String> {
+        let assistant = self.as_assistant()?;
+        assistant.text_blocks().next()?;
+        Some(assistant.concat_text())
+    }
+
+    /// Get the thinking text of an assistant message, joined with newlines.
+    ///
+    /// Returns `None` if this is not an assistant message or it has no
+    /// thinking blocks.
+    pub fn thinking_text(&self) -> Option<String> {
+        self.as_assistant()?.thinking_text()
+    }
+
+    /// Get the final result text if this is a result message.
+    pub fn result_text(&self) -> Option<&str> {
+        self.as_result()?.result.as_deref()
+    }
+
+    /// Tool calls blocked by permission denials, if this is a result message.
+    ///
+    /// Empty for every other message type.
+    pub fn permission_denials(&self) -> &[PermissionDenial] {
         match self {
-            ClaudeOutput::Assistant(ass) => {
-                let texts: Vec<&str> = ass
-                    .message
-                    .content
-                    .iter()
-                    .filter_map(|block| match block {
-                        ContentBlock::Text(t) => Some(t.text.as_str()),
-                        _ => None,
-                    })
-                    .collect();
+            ClaudeOutput::Result(res) => &res.permission_denials,
+            _ => &[],
+        }
+    }
 
-                if texts.is_empty() {
-                    None
-                } else {
-                    Some(texts.join(""))
-                }
-            }
+    /// Get the user message if this is one.
+    pub fn as_user(&self) -> Option<&UserMessage> {
+        match self {
+            ClaudeOutput::User(user) => Some(user),
             _ => None,
         }
     }
@@ -347,13 +504,28 @@ impl ClaudeOutput {
     }
 
     /// Parse a JSON string, returning ParseError with raw JSON if it doesn't match our types
+    ///
+    /// A line that is not JSON but reads as a CLI debug log line is returned
+    /// as [`ClaudeOutput::Debug`].
     pub fn parse_json(s: &str) -> Result<ClaudeOutput, ParseError> {
-        // First try to parse as a Value
-        let value: Value = serde_json::from_str(s).map_err(|e| ParseError {
-            raw_line: s.to_string(),
-            raw_json: None,
-            error_message: format!("Invalid JSON: {}", e),
-        })?;
+        // Fast path: most lines parse, so the Value kept for the error
+        // report is only built below, when a line failed.
+        if let Ok(output) = serde_json::from_str::<ClaudeOutput>(s) {
+            return Ok(output);
+        }
+
+        let value: Value = match serde_json::from_str(s) {
+            Ok(value) => value,
+            Err(e) => {
+                return DebugMessage::parse_line(s)
+                    .map(ClaudeOutput::Debug)
+                    .ok_or_else(|| ParseError {
+                        raw_line: s.to_string(),
+                        raw_json: None,
+                        error_message: format!("Invalid JSON: {}", e),
+                    })
+            }
+        };
 
         // Then try to parse that Value as ClaudeOutput
         serde_json::from_value::<ClaudeOutput>(value.clone()).map_err(|e| ParseError {
@@ -367,6 +539,56 @@ impl ClaudeOutput {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::io::ControlRequestPayload;
+
+    #[test]
+    fn test_debug_line_parses_as_debug_output() {
+        let line = "2026-05-12T23:12:04.121Z [DEBUG] Hooks: found 0 total hooks";
+        let output = ClaudeOutput::parse_json_tolerant(line).unwrap();
+        let msg = output.as_debug().unwrap();
+        assert_eq!(msg.level, crate::io::DebugLevel::Debug);
+        assert_eq!(msg.message, "Hooks: found 0 total hooks");
+        assert_eq!(output.message_type(), "debug");
+
+        let json = serde_json::to_string(&output).unwrap();
+        let reparsed: ClaudeOutput = serde_json::from_str(&json).unwrap();
+        assert!(reparsed.as_debug().is_some());
+
+        assert!(ClaudeOutput::parse_json("Loading...").is_err());
+    }
+
+    #[test]
+    fn test_unknown_types_keep_raw_value() {
+        let line = r#"{"type":"progress_report","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","percent":40}"#;
+        let output = ClaudeOutput::parse_json(line).unwrap();
+        assert_eq!(output.message_type(), "progress_report");
+        assert_eq!(output.as_unknown().unwrap()["percent"], 40);
+        assert!(output.session_id().is_some());
+        let reparsed: Value = serde_json::to_value(&output).unwrap();
+        assert_eq!(reparsed, serde_json::from_str::<Value>(line).unwrap());
+
+        // A known type with the wrong shape is still an error.
+        assert!(ClaudeOutput::parse_json(r#"{"type":"assistant","message":7}"#).is_err());
+
+        let request = ClaudeOutput::parse_json(
+            r#"{"type":"control_request","request_id":"r1","request":{"subtype":"elicit","prompt":"?"}}"#,
+        )
+        .unwrap();
+        let req = request.as_control_request().unwrap();
+        assert_eq!(req.request.subtype(), "elicit");
+        assert!(matches!(req.request, ControlRequestPayload::Unknown(_)));
+        assert_eq!(request.to_string(), "[control_request:elicit] id=r1");
+
+        // System messages don't pick up the tag in their flattened data.
+        let system = ClaudeOutput::parse_json(
+            r#"{"type":"system","subtype":"status","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d"}"#,
+        )
+        .unwrap();
+        let ClaudeOutput::System(sys) = &system else {
+            panic!("expected a system message");
+        };
+        assert!(sys.data.get("type").is_none());
+    }
 
     #[test]
     fn test_deserialize_assistant_message() {
@@ -418,7 +640,10 @@ mod tests {
             "total_cost_usd": 0.01
         }"#;
         let output: ClaudeOutput = serde_json::from_str(result_json).unwrap();
-        assert_eq!(output.session_id(), Some("result-session"));
+        assert_eq!(
+            output.session_id(),
+            Some(LenientUuid::from("result-session"))
+        );
 
         // Assistant message
         let assistant_json = r#"{
@@ -432,7 +657,10 @@ mod tests {
             "session_id": "assistant-session"
         }"#;
         let output: ClaudeOutput = serde_json::from_str(assistant_json).unwrap();
-        assert_eq!(output.session_id(), Some("assistant-session"));
+        assert_eq!(
+            output.session_id(),
+            Some(LenientUuid::from("assistant-session"))
+        );
 
         // System message
         let system_json = r#"{
@@ -441,7 +669,10 @@ mod tests {
             "session_id": "system-session"
         }"#;
         let output: ClaudeOutput = serde_json::from_str(system_json).unwrap();
-        assert_eq!(output.session_id(), Some("system-session"));
+        assert_eq!(
+            output.session_id(),
+            Some(LenientUuid::from("system-session"))
+        );
     }
 
     #[test]
@@ -674,4 +905,101 @@ mod tests {
         let result: ClaudeOutput = serde_json::from_str(result_json).unwrap();
         assert!(result.as_system().is_none());
     }
+
+    #[test]
+    fn test_assistant_text_accessors() {
+        let json = r#"{
+            "type": "assistant",
+            "message": {
+                "id": "msg_1",
+                "role": "assistant",
+                "model": "claude-3",
+                "content": [
+                    {"type": "thinking", "thinking": "plan", "signature": "sig"},
+                    {"type": "text", "text": "Part one. "},
+                    {"type": "tool_use", "id": "tu_1", "name": "Read", "input": {"file_path": "/a"}},
+                    {"type": "text", "text": "Part two."}
+                ]
+            },
+            "session_id": "abc"
+        }"#;
+        let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            output.assistant_text().as_deref(),
+            Some("Part one. Part two.")
+        );
+        assert_eq!(output.thinking_text().as_deref(), Some("plan"));
+        assert_eq!(output.tool_uses().count(), 1);
+        assert!(output.tool_results().next().is_none());
+
+        let assistant = output.as_assistant().unwrap();
+        assert!(assistant.has_tool_use());
+        assert_eq!(assistant.tool_use("Read").unwrap().id, "tu_1");
+        assert!(assistant.tool_use("Write").is_none());
+        assert_eq!(
+            assistant.text_blocks().collect::<Vec<_>>(),
+            vec!["Part one. ", "Part two."]
+        );
+    }
+
+    #[test]
+    fn test_assistant_text_none_without_text_blocks() {
+        let json = r#"{
+            "type": "assistant",
+            "message": {
+                "id": "msg_1",
+                "role": "assistant",
+                "model": "claude-3",
+                "content": [{"type": "tool_use", "id": "tu_1", "name": "Bash", "input": {}}]
+            },
+            "session_id": "abc"
+        }"#;
+        let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+        assert!(output.assistant_text().is_none());
+        assert!(output.thinking_text().is_none());
+        assert_eq!(output.as_assistant().unwrap().concat_text(), "");
+    }
+
+    #[test]
+    fn test_user_tool_result_accessors() {
+        let json = r#"{
+            "type": "user",
+            "message": {
+                "role": "user",
+                "content": [
+                    {"type": "text", "text": "see "},
+                    {"type": "tool_result", "tool_use_id": "tu_1", "content": "ok"},
+                    {"type": "tool_result", "tool_use_id": "tu_2", "content": "fail", "is_error": true}
+                ]
+            },
+            "session_id": "550e8400-e29b-41d4-a716-446655440000"
+        }"#;
+        let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+
+        assert_eq!(output.tool_results().count(), 2);
+        assert!(output.assistant_text().is_none());
+        let user = output.as_user().unwrap();
+        assert_eq!(user.concat_text(), "see ");
+        assert!(user.tool_result("tu_2").unwrap().is_error());
+        assert!(user.tool_result("tu_3").is_none());
+    }
+
+    #[test]
+    fn test_result_text() {
+        let json = r#"{
+            "type": "result",
+            "subtype": "success",
+            "is_error": false,
+            "duration_ms": 100,
+            "duration_api_ms": 200,
+            "num_turns": 1,
+            "result": "All done",
+            "session_id": "abc",
+            "total_cost_usd": 0.01
+        }"#;
+        let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+        assert_eq!(output.result_text(), Some("All done"));
+        assert!(output.as_user().is_none());
+    }
 }
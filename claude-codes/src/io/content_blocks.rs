@@ -1,5 +1,7 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
+
+use super::spill::LargeContent;
 use std::fmt;
 
 /// Deserialize content blocks that can be either a string or array
@@ -27,10 +29,14 @@ where
 /// Includes typed variants for known block types and an `Unknown` fallback
 /// for forward compatibility with new block types added by the CLI.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum ContentBlock {
     Text(TextBlock),
     Image(ImageBlock),
     Thinking(ThinkingBlock),
+    /// Thinking the API returned encrypted because it was flagged by safety
+    /// systems.
+    RedactedThinking(RedactedThinkingBlock),
     ToolUse(ToolUseBlock),
     ToolResult(ToolResultBlock),
     /// Server-side tool use (e.g., web search, code execution).
@@ -60,6 +66,7 @@ impl ContentBlock {
             Self::Text(_) => "text",
             Self::Image(_) => "image",
             Self::Thinking(_) => "thinking",
+            Self::RedactedThinking(_) => "redacted_thinking",
             Self::ToolUse(_) => "tool_use",
             Self::ToolResult(_) => "tool_result",
             Self::ServerToolUse(_) => "server_tool_use",
@@ -73,6 +80,14 @@ impl ContentBlock {
         }
     }
 
+    /// Returns `true` for thinking blocks, redacted or not.
+    ///
+    /// When assistant content is replayed to the API, these blocks must be
+    /// passed back unmodified, signature and all.
+    pub fn is_thinking(&self) -> bool {
+        matches!(self, Self::Thinking(_) | Self::RedactedThinking(_))
+    }
+
     /// Returns `true` if this is an unknown/unrecognized content block type.
     pub fn is_unknown(&self) -> bool {
         matches!(self, Self::Unknown(_))
@@ -85,6 +100,7 @@ impl Serialize for ContentBlock {
             Self::Text(v) => serialize_tagged("text", v, serializer),
             Self::Image(v) => serialize_tagged("image", v, serializer),
             Self::Thinking(v) => serialize_tagged("thinking", v, serializer),
+            Self::RedactedThinking(v) => serialize_tagged("redacted_thinking", v, serializer),
             Self::ToolUse(v) => serialize_tagged("tool_use", v, serializer),
             Self::ToolResult(v) => serialize_tagged("tool_result", v, serializer),
             Self::ServerToolUse(v) => serialize_tagged("server_tool_use", v, serializer),
@@ -121,6 +137,9 @@ impl<'de> Deserialize<'de> for ContentBlock {
             "thinking" => serde_json::from_value(value)
                 .map(ContentBlock::Thinking)
                 .map_err(serde::de::Error::custom),
+            "redacted_thinking" => serde_json::from_value(value)
+                .map(ContentBlock::RedactedThinking)
+                .map_err(serde::de::Error::custom),
             "tool_use" => serde_json::from_value(value)
                 .map(ContentBlock::ToolUse)
                 .map_err(serde::de::Error::custom),
@@ -218,6 +237,7 @@ pub struct ImageBlock {
 
 /// Encoding type for image source data.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum ImageSourceType {
     /// Base64-encoded image data.
     Base64,
@@ -264,6 +284,7 @@ impl<'de> Deserialize<'de> for ImageSourceType {
 
 /// MIME type for image content.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum MediaType {
     /// JPEG image.
     Jpeg,
@@ -333,9 +354,20 @@ pub struct ImageSource {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThinkingBlock {
     pub thinking: String,
+    /// Opaque signature the API uses to verify the thinking is unmodified
+    /// when it is sent back in a later request.
     pub signature: String,
 }
 
+/// Redacted thinking content block
+///
+/// The thinking is encrypted in `data`. It can't be read, but it must be
+/// kept and sent back as-is for the model to continue from it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactedThinkingBlock {
+    pub data: String,
+}
+
 /// Tool use content block
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolUseBlock {
@@ -408,9 +440,13 @@ pub struct ToolResultBlock {
 /// Tool result content type
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
+#[non_exhaustive]
 pub enum ToolResultContent {
     Text(String),
     Structured(Vec<Value>),
+    /// Content moved to disk by a [`SpillPolicy`](crate::io::SpillPolicy);
+    /// never sent by the CLI.
+    Spilled(LargeContent),
 }
 
 /// Server-side tool use content block (e.g., web search, code execution).
@@ -677,6 +713,29 @@ mod tests {
         assert_eq!(text_json, reserialized);
     }
 
+    #[test]
+    fn test_thinking_blocks_roundtrip_unchanged() {
+        let blocks = json!([
+            {"type": "thinking", "thinking": "check the tests", "signature": "EqQBCgIYAhIM1gbcDa9GJwZA"},
+            {"type": "redacted_thinking", "data": "EmwKAhgBEgy3va3pzix/LafPsn4aDFIT2Xlxh0L5L8rLVyIwxtE3rAFBa8cr3qpP"},
+            {"type": "text", "text": "Done."}
+        ]);
+        let parsed: Vec<ContentBlock> = serde_json::from_value(blocks.clone()).unwrap();
+        assert!(
+            matches!(&parsed[0], ContentBlock::Thinking(t) if t.signature == "EqQBCgIYAhIM1gbcDa9GJwZA")
+        );
+        assert!(matches!(&parsed[1], ContentBlock::RedactedThinking(_)));
+        assert_eq!(parsed[1].block_type(), "redacted_thinking");
+        assert_eq!(
+            parsed
+                .iter()
+                .map(ContentBlock::is_thinking)
+                .collect::<Vec<_>>(),
+            vec![true, true, false]
+        );
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), blocks);
+    }
+
     #[test]
     fn test_assistant_message_with_server_tool_use() {
         let json = r#"{
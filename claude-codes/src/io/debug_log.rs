@@ -0,0 +1,157 @@
+//! Debug log lines from the CLI.
+//!
+//! With debug logging on, the CLI writes lines like
+//! `2026-05-12T23:12:04.121Z [DEBUG] Loaded 3 MCP servers` to its debug
+//! log. When one of those reaches stdout instead of a log file it would
+//! fail JSON parsing; [`ClaudeOutput::parse_json`](super::ClaudeOutput::parse_json)
+//! returns it as [`ClaudeOutput::Debug`](super::ClaudeOutput::Debug) instead.
+//! Use [`ClaudeCliBuilder::debug_to_file`](crate::ClaudeCliBuilder::debug_to_file)
+//! to keep the log off the stream entirely.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use super::timestamp::Timestamp;
+
+/// One line of the CLI's debug log.
+///
+/// # Example
+/// ```
+/// use claude_codes::{DebugLevel, DebugMessage};
+///
+/// let line = "2026-05-12T23:12:04.121Z [WARN] MCP server \"docs\" failed to start";
+/// let msg = DebugMessage::parse_line(line).unwrap();
+/// assert_eq!(msg.level, DebugLevel::Warn);
+/// assert_eq!(msg.message, "MCP server \"docs\" failed to start");
+///
+/// assert!(DebugMessage::parse_line("not a log line").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DebugMessage {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<Timestamp>,
+    pub level: DebugLevel,
+    pub message: String,
+}
+
+impl DebugMessage {
+    /// Parse a debug log line: `[LEVEL] message`, optionally preceded by an
+    /// RFC 3339 timestamp. Returns `None` for anything else.
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let (timestamp, rest) = if line.starts_with('[') {
+            (None, line)
+        } else {
+            let (ts, rest) = line.split_once(' ')?;
+            let ts = Timestamp::from(ts);
+            if !ts.is_parsed() {
+                return None;
+            }
+            (Some(ts), rest)
+        };
+        let (level, message) = rest.strip_prefix('[')?.split_once(']')?;
+        if level.is_empty() || !level.bytes().all(|b| b.is_ascii_uppercase()) {
+            return None;
+        }
+        Some(Self {
+            timestamp,
+            level: DebugLevel::from(level.to_ascii_lowercase().as_str()),
+            message: message.trim_start().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for DebugMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{}] {}",
+            self.level.as_str().to_ascii_uppercase(),
+            self.message
+        )
+    }
+}
+
+/// Severity of a CLI debug log line.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DebugLevel {
+    /// Most detailed output.
+    Verbose,
+    /// Regular debug output.
+    Debug,
+    /// Informational messages.
+    Info,
+    /// Warnings.
+    Warn,
+    /// Errors.
+    Error,
+    /// A level this version doesn't know about.
+    Unknown(String),
+}
+
+impl DebugLevel {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Verbose => "verbose",
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for DebugLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for DebugLevel {
+    fn from(s: &str) -> Self {
+        match s {
+            "verbose" => Self::Verbose,
+            "debug" => Self::Debug,
+            "info" => Self::Info,
+            "warn" => Self::Warn,
+            "error" => Self::Error,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for DebugLevel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for DebugLevel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line_variants() {
+        let msg = DebugMessage::parse_line("[DEBUG] Writing to temp file").unwrap();
+        assert_eq!(msg.timestamp, None);
+        assert_eq!(msg.level, DebugLevel::Debug);
+        assert_eq!(msg.message, "Writing to temp file");
+
+        let msg = DebugMessage::parse_line("2026-05-12T23:12:04.121Z [TRACE] x").unwrap();
+        assert!(msg.timestamp.unwrap().is_parsed());
+        assert_eq!(msg.level, DebugLevel::Unknown("trace".to_string()));
+
+        assert!(DebugMessage::parse_line("[1] first item").is_none());
+        assert!(DebugMessage::parse_line("yesterday [DEBUG] x").is_none());
+        assert!(DebugMessage::parse_line(r#"{"type":"debug"}"#).is_none());
+    }
+}
@@ -0,0 +1,468 @@
+//! Human-readable [`Display`](fmt::Display) implementations for protocol messages.
+//!
+//! These render plain, uncolored summaries suited to logs and simple CLIs —
+//! one header line per message, followed by one line per content block.
+//! Long text is left intact; tool inputs and results are condensed to a
+//! single line. For colorized, truncated terminal output see
+//! `crate::render` (requires the `render` feature).
+//!
+//! ```
+//! use claude_codes::ClaudeOutput;
+//!
+//! let json = r#"{"type":"assistant","message":{"id":"msg_1","role":"assistant",
+//!     "model":"claude-3","content":[
+//!         {"type":"text","text":"Listing files"},
+//!         {"type":"tool_use","id":"tu_1","name":"Bash","input":{"command":"ls -la"}}
+//!     ]},"session_id":"abc"}"#;
+//! let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+//! assert_eq!(output.to_string(), "[assistant]\nListing files\n→ Bash(ls -la)");
+//! ```
+
+use std::fmt;
+
+use serde_json::Value;
+
+use super::claude_output::ClaudeOutput;
+use super::content_blocks::{ContentBlock, ToolResultBlock, ToolResultContent, ToolUseBlock};
+use super::control::{
+    ControlRequest, ControlRequestPayload, ControlResponse, ControlResponsePayload,
+};
+use super::errors::AnthropicError;
+use super::message_types::{AssistantMessage, SystemMessage, UserMessage};
+use super::rate_limit::RateLimitEvent;
+use super::result::{PermissionDenial, ResultMessage};
+
+/// Maximum characters shown for a condensed tool argument or result.
+const SUMMARY_CHARS: usize = 80;
+
+/// Input keys that best describe a tool call, in priority order.
+const SUMMARY_KEYS: &[&str] = &[
+    "command",
+    "file_path",
+    "notebook_path",
+    "path",
+    "pattern",
+    "url",
+    "query",
+    "description",
+    "skill",
+    "prompt",
+];
+
+/// Collapse `s` to its first non-empty line and cap it at `max` characters,
+/// appending `…` when anything was cut.
+pub(crate) fn one_line(s: &str, max: usize) -> String {
+    let mut lines = s.lines().map(str::trim).filter(|l| !l.is_empty());
+    let first = lines.next().unwrap_or("");
+    let more_lines = lines.next().is_some();
+    let mut out: String = first.chars().take(max).collect();
+    if more_lines || first.chars().count() > max {
+        out.push('…');
+    }
+    out
+}
+
+/// A one-line description of a tool's input, picking the most telling field.
+fn input_summary(input: &Value) -> String {
+    if let Some(obj) = input.as_object() {
+        for key in SUMMARY_KEYS {
+            if let Some(Value::String(s)) = obj.get(*key) {
+                return one_line(s, SUMMARY_CHARS);
+            }
+        }
+        if obj.is_empty() {
+            return String::new();
+        }
+    }
+    one_line(&input.to_string(), SUMMARY_CHARS)
+}
+
+impl ToolUseBlock {
+    /// A compact one-line summary of the call, e.g. `Bash(ls -la)` or
+    /// `Read(/src/main.rs)`.
+    ///
+    /// The argument is taken from the most descriptive input field
+    /// (`command`, `file_path`, `pattern`, `url`, ...), falling back to the
+    /// JSON input truncated to 80 characters.
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::ToolUseBlock;
+    /// use serde_json::json;
+    ///
+    /// let block = ToolUseBlock {
+    ///     id: "tu_1".to_string(),
+    ///     name: "Read".to_string(),
+    ///     input: json!({"file_path": "/src/main.rs"}),
+    ///     caller: None,
+    /// };
+    /// assert_eq!(block.summary(), "Read(/src/main.rs)");
+    /// ```
+    pub fn summary(&self) -> String {
+        format!("{}({})", self.name, input_summary(&self.input))
+    }
+}
+
+impl PermissionDenial {
+    /// A compact one-line summary of the blocked call, e.g. `Bash(rm -rf /)`.
+    pub fn summary(&self) -> String {
+        format!("{}({})", self.tool_name, input_summary(&self.tool_input))
+    }
+}
+
+impl fmt::Display for PermissionDenial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "denied: {}", self.summary())
+    }
+}
+
+impl ToolResultContent {
+    /// The textual content of the result: the string itself, or the `text`
+    /// fields of structured content joined with newlines. For spilled content
+    /// this is only the in-memory preview.
+    pub fn text(&self) -> String {
+        match self {
+            ToolResultContent::Text(s) => s.clone(),
+            ToolResultContent::Spilled(large) => large.preview.clone(),
+            ToolResultContent::Structured(items) => items
+                .iter()
+                .filter_map(|item| item.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+impl ToolResultBlock {
+    /// Whether the tool reported an error.
+    pub fn is_error(&self) -> bool {
+        self.is_error.unwrap_or(false)
+    }
+}
+
+impl fmt::Display for ToolUseBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "→ {}", self.summary())
+    }
+}
+
+impl fmt::Display for ToolResultBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let marker = if self.is_error() { "✗" } else { "←" };
+        let text = self.content.as_ref().map(|c| c.text()).unwrap_or_default();
+        write!(f, "{} {}", marker, self.tool_use_id)?;
+        if !text.trim().is_empty() {
+            write!(f, ": {}", one_line(&text, SUMMARY_CHARS))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ContentBlock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContentBlock::Text(t) => f.write_str(&t.text),
+            ContentBlock::Image(_) => f.write_str("[image]"),
+            ContentBlock::Thinking(t) => {
+                write!(f, "(thinking) {}", one_line(&t.thinking, SUMMARY_CHARS))
+            }
+            ContentBlock::RedactedThinking(_) => f.write_str("(thinking) [redacted]"),
+            ContentBlock::ToolUse(tu) => write!(f, "{}", tu),
+            ContentBlock::ToolResult(tr) => write!(f, "{}", tr),
+            ContentBlock::ServerToolUse(s) => {
+                write!(f, "→ {}({}) [server]", s.name, input_summary(&s.input))
+            }
+            ContentBlock::McpToolUse(m) => match &m.server_name {
+                Some(server) => write!(
+                    f,
+                    "→ {}({}) [mcp:{}]",
+                    m.name,
+                    input_summary(&m.input),
+                    server
+                ),
+                None => write!(f, "→ {}({}) [mcp]", m.name, input_summary(&m.input)),
+            },
+            ContentBlock::WebSearchToolResult(r) => {
+                write!(f, "← {}: [web search results]", r.tool_use_id)
+            }
+            ContentBlock::Fallback(fb) => {
+                write!(f, "(fallback {} → {})", fb.from.model, fb.to.model)
+            }
+            other => write!(f, "[{}]", other.block_type()),
+        }
+    }
+}
+
+/// Write a header followed by each block on its own line.
+fn write_blocks(f: &mut fmt::Formatter<'_>, header: &str, blocks: &[ContentBlock]) -> fmt::Result {
+    f.write_str(header)?;
+    for block in blocks {
+        write!(f, "\n{}", block)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for SystemMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[system:{}]", self.subtype)?;
+        if let Some(init) = self.as_init() {
+            if let Some(model) = &init.model {
+                write!(f, " model={}", model)?;
+            }
+            write!(f, " session={}", init.session_id)?;
+            if !init.tools.is_empty() {
+                write!(f, " tools={}", init.tools.len())?;
+            }
+        } else if let Some(status) = self.data.get("status").and_then(Value::as_str) {
+            write!(f, " {}", status)?;
+        } else if let Some(text) = ["description", "content", "text"]
+            .iter()
+            .find_map(|key| self.data.get(*key).and_then(Value::as_str))
+        {
+            write!(f, " {}", one_line(text, SUMMARY_CHARS))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for UserMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_blocks(f, "[user]", &self.message.content)
+    }
+}
+
+impl fmt::Display for AssistantMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = match &self.subagent_type {
+            Some(agent) => format!("[assistant:{}]", agent),
+            None => "[assistant]".to_string(),
+        };
+        write_blocks(f, &header, &self.message.content)
+    }
+}
+
+impl fmt::Display for ResultMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[result:{}] {} turn{}, {:.1}s, ${:.4}",
+            self.subtype,
+            self.num_turns,
+            if self.num_turns == 1 { "" } else { "s" },
+            self.duration_ms as f64 / 1000.0,
+            self.total_cost_usd
+        )?;
+        if let Some(usage) = &self.usage {
+            write!(
+                f,
+                ", {} in / {} out tokens",
+                usage.input_tokens, usage.output_tokens
+            )?;
+        }
+        if self.is_error {
+            for error in &self.errors {
+                write!(f, "\nerror: {}", error)?;
+            }
+        }
+        for denial in &self.permission_denials {
+            write!(f, "\n{}", denial)?;
+        }
+        if let Some(result) = &self.result {
+            if !result.is_empty() {
+                write!(f, "\n{}", result)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ControlRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.request {
+            ControlRequestPayload::CanUseTool(req) => write!(
+                f,
+                "[control_request:can_use_tool] {}({}) id={}",
+                req.tool_name,
+                input_summary(&req.input),
+                self.request_id
+            ),
+            ControlRequestPayload::HookCallback(req) => write!(
+                f,
+                "[control_request:hook_callback] {} id={}",
+                req.callback_id, self.request_id
+            ),
+            ControlRequestPayload::McpMessage(req) => write!(
+                f,
+                "[control_request:mcp_message] {} id={}",
+                req.server_name, self.request_id
+            ),
+            ControlRequestPayload::Initialize(_) => {
+                write!(f, "[control_request:initialize] id={}", self.request_id)
+            }
+            ControlRequestPayload::RewindFiles(req) => write!(
+                f,
+                "[control_request:rewind_files] {} id={}",
+                req.user_message_id, self.request_id
+            ),
+            ControlRequestPayload::SetPermissionMode(req) => write!(
+                f,
+                "[control_request:set_permission_mode] {} id={}",
+                req.mode, self.request_id
+            ),
+            ControlRequestPayload::AddDirectory(req) => write!(
+                f,
+                "[control_request:add_directory] {} id={}",
+                req.path, self.request_id
+            ),
+            other => write!(
+                f,
+                "[control_request:{}] id={}",
+                other.subtype(),
+                self.request_id
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ControlResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.response {
+            ControlResponsePayload::Success { request_id, .. } => {
+                write!(f, "[control_response:success] id={}", request_id)
+            }
+            ControlResponsePayload::Error { request_id, error } => {
+                write!(f, "[control_response:error] id={} {}", request_id, error)
+            }
+        }
+    }
+}
+
+impl fmt::Display for AnthropicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[error:{}] {}",
+            self.error.error_type, self.error.message
+        )
+    }
+}
+
+impl fmt::Display for RateLimitEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let info = &self.rate_limit_info;
+        write!(f, "[rate_limit] {}", info.status)?;
+        if let Some(window) = &info.rate_limit_type {
+            write!(f, " window={}", window)?;
+        }
+        if let Some(utilization) = info.utilization {
+            write!(f, " utilization={:.0}%", utilization * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ClaudeOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClaudeOutput::System(m) => m.fmt(f),
+            ClaudeOutput::User(m) => m.fmt(f),
+            ClaudeOutput::Assistant(m) => m.fmt(f),
+            ClaudeOutput::Result(m) => m.fmt(f),
+            ClaudeOutput::ControlRequest(m) => m.fmt(f),
+            ClaudeOutput::ControlResponse(m) => m.fmt(f),
+            ClaudeOutput::Error(m) => m.fmt(f),
+            ClaudeOutput::RateLimitEvent(m) => m.fmt(f),
+            ClaudeOutput::Debug(m) => m.fmt(f),
+            ClaudeOutput::Unknown(_) => write!(f, "[{}]", self.message_type()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> ClaudeOutput {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_one_line_truncates() {
+        assert_eq!(one_line("short", 10), "short");
+        assert_eq!(one_line("abcdefghij", 4), "abcd…");
+        assert_eq!(one_line("\n  first\nsecond", 20), "first…");
+        assert_eq!(one_line("", 5), "");
+    }
+
+    #[test]
+    fn test_display_system_init() {
+        let output = parse(
+            r#"{"type":"system","subtype":"init","session_id":"s-1",
+                "model":"claude-sonnet-4","tools":["Bash","Read"]}"#,
+        );
+        assert_eq!(
+            output.to_string(),
+            "[system:init] model=claude-sonnet-4 session=s-1 tools=2"
+        );
+    }
+
+    #[test]
+    fn test_display_user_tool_result() {
+        let output = parse(
+            r#"{"type":"user","message":{"role":"user","content":[
+                {"type":"tool_result","tool_use_id":"tu_1","content":"line one\nline two"},
+                {"type":"tool_result","tool_use_id":"tu_2","content":"boom","is_error":true}
+            ]},"session_id":"550e8400-e29b-41d4-a716-446655440000"}"#,
+        );
+        assert_eq!(
+            output.to_string(),
+            "[user]\n← tu_1: line one…\n✗ tu_2: boom"
+        );
+    }
+
+    #[test]
+    fn test_display_result() {
+        let output = parse(
+            r#"{"type":"result","subtype":"success","is_error":false,
+                "duration_ms":1500,"duration_api_ms":1200,"num_turns":1,
+                "result":"All done","session_id":"s","total_cost_usd":0.0123,
+                "usage":{"input_tokens":10,"output_tokens":5}}"#,
+        );
+        assert_eq!(
+            output.to_string(),
+            "[result:success] 1 turn, 1.5s, $0.0123, 10 in / 5 out tokens\nAll done"
+        );
+    }
+
+    #[test]
+    fn test_display_control_request() {
+        let output = parse(
+            r#"{"type":"control_request","request_id":"req-1","request":{
+                "subtype":"can_use_tool","tool_name":"Write",
+                "input":{"file_path":"/tmp/x","content":"hi"},
+                "permission_suggestions":[]}}"#,
+        );
+        assert_eq!(
+            output.to_string(),
+            "[control_request:can_use_tool] Write(/tmp/x) id=req-1"
+        );
+    }
+
+    #[test]
+    fn test_display_anthropic_error() {
+        let output =
+            parse(r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#);
+        assert_eq!(output.to_string(), "[error:overloaded_error] Overloaded");
+    }
+
+    #[test]
+    fn test_tool_summary_falls_back_to_json() {
+        let block = ToolUseBlock {
+            id: "tu".to_string(),
+            name: "mcp__db__query".to_string(),
+            input: serde_json::json!({"limit": 5}),
+            caller: None,
+        };
+        assert_eq!(block.summary(), r#"mcp__db__query({"limit":5})"#);
+    }
+}
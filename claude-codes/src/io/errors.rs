@@ -30,10 +30,25 @@ impl fmt::Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
+/// What a client does with a line it could not parse, as decided by the
+/// handler installed with `on_parse_error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ParseErrorAction {
+    /// Drop the line and read the next message.
+    #[default]
+    Skip,
+    /// Return the failure from `receive` as `Error::Deserialization`.
+    Abort,
+}
+
+/// Callback given each [`ParseError`] a client hits while receiving.
+pub type ParseErrorHandler = Box<dyn FnMut(&ParseError) -> ParseErrorAction + Send>;
+
 /// Known Anthropic API error types.
 ///
 /// Maps to the `type` field inside an error response from the Anthropic API.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum ApiErrorType {
     /// Internal server error (HTTP 500)
     ApiError,
@@ -0,0 +1,647 @@
+//! Hook event payloads and responses.
+//!
+//! The CLI runs hooks at fixed points of a session: before and after tool
+//! calls, when a prompt is submitted, when Claude or a subagent is about to
+//! stop, and on notifications. Each hook receives a [`HookInput`] — as JSON
+//! on stdin for hook commands configured in settings, or as the `input` of a
+//! [`HookCallbackRequest`](super::HookCallbackRequest) for hooks registered by
+//! an SDK client — and may answer with a [`HookOutput`]: printed to stdout by
+//! a command, or sent back with [`ControlResponse::hook_output`](super::ControlResponse::hook_output).
+//!
+//! A hook command written in Rust:
+//!
+//! ```no_run
+//! use claude_codes::{HookInput, HookOutput};
+//!
+//! let input = HookInput::from_reader(std::io::stdin()).unwrap();
+//! let output = match input {
+//!     HookInput::PreToolUse(call) if call.tool_input["command"] == "rm -rf /" => {
+//!         HookOutput::deny_tool("not on this machine")
+//!     }
+//!     _ => HookOutput::default(),
+//! };
+//! println!("{}", serde_json::to_string(&output).unwrap());
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+use std::io::Read;
+
+use super::control::PermissionModeName;
+
+/// Fields the CLI sends with every hook event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HookInputBase {
+    pub session_id: String,
+    /// Path of the session's JSONL transcript.
+    pub transcript_path: String,
+    pub cwd: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionModeName>,
+    /// Correlates every event that follows one user prompt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_id: Option<String>,
+    /// Set when the hook fires inside a subagent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// Agent type name, e.g. `general-purpose`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_type: Option<String>,
+}
+
+/// `PreToolUse` payload: a tool call about to run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreToolUseHookInput {
+    #[serde(flatten)]
+    pub base: HookInputBase,
+    pub tool_name: String,
+    pub tool_input: Value,
+    pub tool_use_id: String,
+}
+
+impl PreToolUseHookInput {
+    /// Parse the call's input as a typed [`ToolInput`](crate::tool_inputs::ToolInput).
+    pub fn typed_input(&self) -> Option<crate::tool_inputs::ToolInput> {
+        serde_json::from_value(self.tool_input.clone()).ok()
+    }
+}
+
+/// `PostToolUse` payload: a tool call that finished successfully.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostToolUseHookInput {
+    #[serde(flatten)]
+    pub base: HookInputBase,
+    pub tool_name: String,
+    pub tool_input: Value,
+    /// What the tool returned, in the tool's own result shape.
+    pub tool_response: Value,
+    pub tool_use_id: String,
+    /// Execution time, excluding permission prompts and hooks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+impl PostToolUseHookInput {
+    /// Parse the call's input as a typed [`ToolInput`](crate::tool_inputs::ToolInput).
+    pub fn typed_input(&self) -> Option<crate::tool_inputs::ToolInput> {
+        serde_json::from_value(self.tool_input.clone()).ok()
+    }
+}
+
+/// `UserPromptSubmit` payload: a prompt Claude has not seen yet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserPromptSubmitHookInput {
+    #[serde(flatten)]
+    pub base: HookInputBase,
+    pub prompt: String,
+}
+
+/// `Stop` payload: the main agent is about to end its turn.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StopHookInput {
+    #[serde(flatten)]
+    pub base: HookInputBase,
+    /// True when Claude is already continuing because of a stop hook; check
+    /// it to avoid blocking forever.
+    #[serde(default)]
+    pub stop_hook_active: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_assistant_message: Option<String>,
+}
+
+/// `SubagentStop` payload: a subagent is about to return. Its ID and type
+/// are in [`HookInputBase::agent_id`] and [`HookInputBase::agent_type`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubagentStopHookInput {
+    #[serde(flatten)]
+    pub base: HookInputBase,
+    #[serde(default)]
+    pub stop_hook_active: bool,
+    /// Path of the subagent's own transcript.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_transcript_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_assistant_message: Option<String>,
+}
+
+/// `Notification` payload: a message the CLI would show the user, such as
+/// a permission prompt or an idle reminder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NotificationHookInput {
+    #[serde(flatten)]
+    pub base: HookInputBase,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notification_type: Option<String>,
+}
+
+/// A hook event payload, tagged by `hook_event_name`.
+///
+/// Events without a typed variant are kept whole in [`HookInput::Unknown`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "hook_event_name")]
+#[non_exhaustive]
+pub enum HookInput {
+    PreToolUse(PreToolUseHookInput),
+    PostToolUse(PostToolUseHookInput),
+    UserPromptSubmit(UserPromptSubmitHookInput),
+    Stop(StopHookInput),
+    SubagentStop(SubagentStopHookInput),
+    Notification(NotificationHookInput),
+    /// Any other event, with its `hook_event_name` still in the object.
+    #[serde(untagged)]
+    Unknown(Value),
+}
+
+impl HookInput {
+    /// Read one payload, as a hook command receives it on stdin.
+    pub fn from_reader<R: Read>(reader: R) -> serde_json::Result<Self> {
+        serde_json::from_reader(reader)
+    }
+
+    /// The event this payload belongs to.
+    pub fn event(&self) -> HookEvent {
+        match self {
+            Self::PreToolUse(_) => HookEvent::PreToolUse,
+            Self::PostToolUse(_) => HookEvent::PostToolUse,
+            Self::UserPromptSubmit(_) => HookEvent::UserPromptSubmit,
+            Self::Stop(_) => HookEvent::Stop,
+            Self::SubagentStop(_) => HookEvent::SubagentStop,
+            Self::Notification(_) => HookEvent::Notification,
+            Self::Unknown(value) => HookEvent::from(
+                value
+                    .get("hook_event_name")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+
+    /// The fields common to every event; `None` for [`HookInput::Unknown`].
+    pub fn base(&self) -> Option<&HookInputBase> {
+        match self {
+            Self::PreToolUse(input) => Some(&input.base),
+            Self::PostToolUse(input) => Some(&input.base),
+            Self::UserPromptSubmit(input) => Some(&input.base),
+            Self::Stop(input) => Some(&input.base),
+            Self::SubagentStop(input) => Some(&input.base),
+            Self::Notification(input) => Some(&input.base),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+/// What a hook tells the CLI to do. Every field is optional; the default
+/// output lets the session carry on unchanged.
+///
+/// # Example
+/// ```
+/// use claude_codes::{HookEvent, HookOutput};
+///
+/// let output = HookOutput::deny_tool("writes outside the workspace")
+///     .with_system_message("Blocked a write to /etc");
+/// let json = serde_json::to_value(&output).unwrap();
+/// assert_eq!(json["hookSpecificOutput"]["permissionDecision"], "deny");
+///
+/// let context = HookOutput::add_context(HookEvent::UserPromptSubmit, "Today is Friday");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookOutput {
+    /// `Some(false)` ends the session after this hook; see `stop_reason`.
+    #[serde(rename = "continue", default, skip_serializing_if = "Option::is_none")]
+    pub should_continue: Option<bool>,
+    /// Hide the hook's stdout from the transcript.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suppress_output: Option<bool>,
+    /// Shown to the user when `should_continue` is false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decision: Option<HookDecision>,
+    /// Warning shown to the user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_message: Option<String>,
+    /// Explanation for `decision`, shown to Claude.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook_specific_output: Option<HookSpecificOutput>,
+}
+
+impl HookOutput {
+    /// `PreToolUse`: run the tool without a permission prompt.
+    pub fn allow_tool(reason: impl Into<String>) -> Self {
+        Self::permission(HookPermissionDecision::Allow, reason.into())
+    }
+
+    /// `PreToolUse`: refuse the tool call, telling Claude why.
+    pub fn deny_tool(reason: impl Into<String>) -> Self {
+        Self::permission(HookPermissionDecision::Deny, reason.into())
+    }
+
+    /// `PreToolUse`: ask the user, showing them `reason`.
+    pub fn ask_user(reason: impl Into<String>) -> Self {
+        Self::permission(HookPermissionDecision::Ask, reason.into())
+    }
+
+    /// Block the event: reject a submitted prompt, keep Claude going
+    /// instead of stopping, or flag a tool result to Claude. `reason` says
+    /// why.
+    pub fn block(reason: impl Into<String>) -> Self {
+        Self {
+            decision: Some(HookDecision::Block),
+            reason: Some(reason.into()),
+            ..Self::default()
+        }
+    }
+
+    /// End the session, showing `reason` to the user.
+    pub fn stop(reason: impl Into<String>) -> Self {
+        Self {
+            should_continue: Some(false),
+            stop_reason: Some(reason.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Give Claude extra context for `event`.
+    pub fn add_context(event: HookEvent, context: impl Into<String>) -> Self {
+        Self {
+            hook_specific_output: Some(HookSpecificOutput::with_context(event, context.into())),
+            ..Self::default()
+        }
+    }
+
+    /// Show `message` to the user as a warning.
+    pub fn with_system_message(mut self, message: impl Into<String>) -> Self {
+        self.system_message = Some(message.into());
+        self
+    }
+
+    /// Hide the hook's stdout from the transcript.
+    pub fn suppress_output(mut self) -> Self {
+        self.suppress_output = Some(true);
+        self
+    }
+
+    fn permission(decision: HookPermissionDecision, reason: String) -> Self {
+        Self {
+            hook_specific_output: Some(HookSpecificOutput::PreToolUse {
+                permission_decision: Some(decision),
+                permission_decision_reason: Some(reason),
+                updated_input: None,
+                additional_context: None,
+            }),
+            ..Self::default()
+        }
+    }
+}
+
+/// Event-specific part of a [`HookOutput`], tagged by `hookEventName`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "hookEventName", rename_all_fields = "camelCase")]
+#[non_exhaustive]
+pub enum HookSpecificOutput {
+    PreToolUse {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        permission_decision: Option<HookPermissionDecision>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        permission_decision_reason: Option<String>,
+        /// Replacement tool input.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        updated_input: Option<Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        additional_context: Option<String>,
+    },
+    PostToolUse {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        additional_context: Option<String>,
+        /// Replaces the tool output before Claude sees it.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        updated_tool_output: Option<Value>,
+    },
+    UserPromptSubmit {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        additional_context: Option<String>,
+    },
+    Stop {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        additional_context: Option<String>,
+    },
+    SubagentStop {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        additional_context: Option<String>,
+    },
+    Notification {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        additional_context: Option<String>,
+    },
+    /// Output for any other event, with `hookEventName` in the object.
+    #[serde(untagged)]
+    Unknown(Value),
+}
+
+impl HookSpecificOutput {
+    /// `additionalContext` for `event`.
+    pub fn with_context(event: HookEvent, context: String) -> Self {
+        let additional_context = Some(context);
+        match event {
+            HookEvent::PreToolUse => Self::PreToolUse {
+                permission_decision: None,
+                permission_decision_reason: None,
+                updated_input: None,
+                additional_context,
+            },
+            HookEvent::PostToolUse => Self::PostToolUse {
+                additional_context,
+                updated_tool_output: None,
+            },
+            HookEvent::UserPromptSubmit => Self::UserPromptSubmit { additional_context },
+            HookEvent::Stop => Self::Stop { additional_context },
+            HookEvent::SubagentStop => Self::SubagentStop { additional_context },
+            HookEvent::Notification => Self::Notification { additional_context },
+            other => Self::Unknown(serde_json::json!({
+                "hookEventName": other.as_str(),
+                "additionalContext": additional_context,
+            })),
+        }
+    }
+}
+
+/// Hook event names, as used for `hook_event_name` and as keys of the `hooks` settings.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HookEvent {
+    /// Before a tool runs; can allow, deny, or rewrite the call.
+    PreToolUse,
+    /// After a tool succeeds.
+    PostToolUse,
+    /// After a tool fails.
+    PostToolUseFailure,
+    /// When a permission prompt would be shown.
+    PermissionRequest,
+    /// When a prompt is submitted, before Claude sees it.
+    UserPromptSubmit,
+    /// When the CLI sends a notification.
+    Notification,
+    /// When the main agent is about to stop.
+    Stop,
+    /// When a subagent starts.
+    SubagentStart,
+    /// When a subagent is about to stop.
+    SubagentStop,
+    /// When a session starts or resumes.
+    SessionStart,
+    /// When a session ends.
+    SessionEnd,
+    /// Before the conversation is compacted.
+    PreCompact,
+    /// An event this version doesn't know about.
+    Unknown(String),
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::PreToolUse => "PreToolUse",
+            Self::PostToolUse => "PostToolUse",
+            Self::PostToolUseFailure => "PostToolUseFailure",
+            Self::PermissionRequest => "PermissionRequest",
+            Self::UserPromptSubmit => "UserPromptSubmit",
+            Self::Notification => "Notification",
+            Self::Stop => "Stop",
+            Self::SubagentStart => "SubagentStart",
+            Self::SubagentStop => "SubagentStop",
+            Self::SessionStart => "SessionStart",
+            Self::SessionEnd => "SessionEnd",
+            Self::PreCompact => "PreCompact",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for HookEvent {
+    fn from(s: &str) -> Self {
+        match s {
+            "PreToolUse" => Self::PreToolUse,
+            "PostToolUse" => Self::PostToolUse,
+            "PostToolUseFailure" => Self::PostToolUseFailure,
+            "PermissionRequest" => Self::PermissionRequest,
+            "UserPromptSubmit" => Self::UserPromptSubmit,
+            "Notification" => Self::Notification,
+            "Stop" => Self::Stop,
+            "SubagentStart" => Self::SubagentStart,
+            "SubagentStop" => Self::SubagentStop,
+            "SessionStart" => Self::SessionStart,
+            "SessionEnd" => Self::SessionEnd,
+            "PreCompact" => Self::PreCompact,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for HookEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for HookEvent {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// Top-level `decision` of a hook's output.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HookDecision {
+    /// Let the action go ahead.
+    Approve,
+    /// Block the prompt, stop, or tool result; `reason` tells Claude why.
+    Block,
+    /// A decision this version doesn't know about.
+    Unknown(String),
+}
+
+impl HookDecision {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Approve => "approve",
+            Self::Block => "block",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for HookDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for HookDecision {
+    fn from(s: &str) -> Self {
+        match s {
+            "approve" => Self::Approve,
+            "block" => Self::Block,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for HookDecision {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for HookDecision {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// A `PreToolUse` hook's verdict on the tool call.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HookPermissionDecision {
+    /// Run the tool without asking.
+    Allow,
+    /// Refuse the tool call; the reason is shown to Claude.
+    Deny,
+    /// Ask the user, as if no hook had run.
+    Ask,
+    /// Leave the decision to the other hooks and permission rules.
+    Defer,
+    /// A decision this version doesn't know about.
+    Unknown(String),
+}
+
+impl HookPermissionDecision {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+            Self::Ask => "ask",
+            Self::Defer => "defer",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for HookPermissionDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for HookPermissionDecision {
+    fn from(s: &str) -> Self {
+        match s {
+            "allow" => Self::Allow,
+            "deny" => Self::Deny,
+            "ask" => Self::Ask,
+            "defer" => Self::Defer,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for HookPermissionDecision {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for HookPermissionDecision {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hook_inputs() {
+        let json = r#"{"session_id":"abc","transcript_path":"/tmp/t.jsonl","cwd":"/repo","permission_mode":"default",
+            "hook_event_name":"PreToolUse","tool_name":"Bash","tool_input":{"command":"ls"},"tool_use_id":"toolu_1"}"#;
+        let input: HookInput = serde_json::from_str(json).unwrap();
+        assert_eq!(input.event(), HookEvent::PreToolUse);
+        let base = input.base().unwrap();
+        assert_eq!(base.cwd, "/repo");
+        assert_eq!(base.permission_mode, Some(PermissionModeName::Default));
+        match &input {
+            HookInput::PreToolUse(call) => assert!(matches!(
+                call.typed_input(),
+                Some(crate::tool_inputs::ToolInput::Bash(_))
+            )),
+            other => panic!("Expected PreToolUse, got {other:?}"),
+        }
+        let reserialized = serde_json::to_value(&input).unwrap();
+        assert_eq!(reserialized["hook_event_name"], "PreToolUse");
+
+        let json = r#"{"session_id":"abc","transcript_path":"/tmp/t.jsonl","cwd":"/repo",
+            "hook_event_name":"SubagentStop","stop_hook_active":true,"agent_id":"a1","agent_type":"Explore",
+            "agent_transcript_path":"/tmp/a1.jsonl"}"#;
+        match HookInput::from_reader(json.as_bytes()).unwrap() {
+            HookInput::SubagentStop(stop) => {
+                assert!(stop.stop_hook_active);
+                assert_eq!(stop.base.agent_type.as_deref(), Some("Explore"));
+            }
+            other => panic!("Expected SubagentStop, got {other:?}"),
+        }
+
+        let json = r#"{"session_id":"abc","transcript_path":"/tmp/t.jsonl","cwd":"/repo",
+            "hook_event_name":"PreCompact","trigger":"auto","custom_instructions":null}"#;
+        let input: HookInput = serde_json::from_str(json).unwrap();
+        assert!(matches!(input, HookInput::Unknown(_)));
+        assert_eq!(input.event(), HookEvent::PreCompact);
+    }
+
+    #[test]
+    fn test_hook_output_wire_shape() {
+        let output = HookOutput::deny_tool("no").suppress_output();
+        assert_eq!(
+            serde_json::to_value(&output).unwrap(),
+            serde_json::json!({
+                "suppressOutput": true,
+                "hookSpecificOutput": {
+                    "hookEventName": "PreToolUse",
+                    "permissionDecision": "deny",
+                    "permissionDecisionReason": "no"
+                }
+            })
+        );
+
+        let output = HookOutput::stop("budget spent");
+        assert_eq!(
+            serde_json::to_value(&output).unwrap(),
+            serde_json::json!({"continue": false, "stopReason": "budget spent"})
+        );
+
+        let output = HookOutput::add_context(HookEvent::SessionStart, "on main");
+        let json = serde_json::to_value(&output).unwrap();
+        assert_eq!(
+            json["hookSpecificOutput"],
+            serde_json::json!({"hookEventName": "SessionStart", "additionalContext": "on main"})
+        );
+        let parsed: HookOutput = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, output);
+    }
+}
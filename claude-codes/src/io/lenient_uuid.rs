@@ -0,0 +1,170 @@
+//! UUID-shaped identifiers carried by CLI messages.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use uuid::Uuid;
+
+/// A `session_id` or `uuid` value from the CLI.
+///
+/// Keeps the text exactly as received, and also parses it into a [`Uuid`]
+/// when possible (any form [`Uuid::parse_str`] understands, surrounding
+/// whitespace ignored). Values that do not parse are kept too, so an
+/// unexpected ID never fails the whole message, and serializing writes the
+/// original text back unchanged.
+///
+/// Two values are equal if they parse to the same UUID, or, when neither
+/// parses, if their text is the same.
+///
+/// # Example
+///
+/// ```
+/// use claude_codes::LenientUuid;
+///
+/// let id: LenientUuid = serde_json::from_str(r#""622AE0C3-3D50-4FA7-9EE0-69D691238C6D""#).unwrap();
+/// assert!(id.as_uuid().is_some());
+/// assert_eq!(serde_json::to_string(&id).unwrap(), r#""622AE0C3-3D50-4FA7-9EE0-69D691238C6D""#);
+///
+/// let odd: LenientUuid = serde_json::from_str(r#""test-session""#).unwrap();
+/// assert!(!odd.is_uuid());
+/// assert_eq!(odd.as_str(), "test-session");
+/// ```
+#[derive(Debug, Clone)]
+pub struct LenientUuid {
+    text: String,
+    uuid: Option<Uuid>,
+}
+
+impl LenientUuid {
+    /// The parsed UUID, or `None` for a non-UUID value.
+    pub fn as_uuid(&self) -> Option<Uuid> {
+        self.uuid
+    }
+
+    /// Whether the value parsed as a UUID.
+    pub fn is_uuid(&self) -> bool {
+        self.uuid.is_some()
+    }
+
+    /// The value as received.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl From<Uuid> for LenientUuid {
+    fn from(id: Uuid) -> Self {
+        Self {
+            text: id.to_string(),
+            uuid: Some(id),
+        }
+    }
+}
+
+impl From<String> for LenientUuid {
+    fn from(text: String) -> Self {
+        let uuid = Uuid::parse_str(text.trim()).ok();
+        Self { text, uuid }
+    }
+}
+
+impl From<&str> for LenientUuid {
+    fn from(s: &str) -> Self {
+        Self::from(s.to_string())
+    }
+}
+
+impl PartialEq for LenientUuid {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.uuid, other.uuid) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => self.text == other.text,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for LenientUuid {}
+
+impl Hash for LenientUuid {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.uuid {
+            Some(id) => id.hash(state),
+            None => self.text.hash(state),
+        }
+    }
+}
+
+impl PartialEq<Uuid> for LenientUuid {
+    fn eq(&self, other: &Uuid) -> bool {
+        self.uuid == Some(*other)
+    }
+}
+
+impl fmt::Display for LenientUuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Serialize for LenientUuid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.text)
+    }
+}
+
+impl<'de> Deserialize<'de> for LenientUuid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::from(String::deserialize(deserializer)?))
+    }
+}
+
+/// Deserialize an optional [`LenientUuid`], treating `null` or an empty
+/// string as `None`.
+pub(crate) fn deserialize_optional_lenient_uuid<'de, D>(
+    deserializer: D,
+) -> Result<Option<LenientUuid>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let opt_str: Option<String> = Option::deserialize(deserializer)?;
+    Ok(match opt_str.as_deref() {
+        None => None,
+        Some(s) if s.trim().is_empty() => None,
+        Some(s) => Some(LenientUuid::from(s)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_uuid_forms_and_keeps_raw_text() {
+        let id = Uuid::parse_str("622ae0c3-3d50-4fa7-9ee0-69d691238c6d").unwrap();
+        assert_eq!(LenientUuid::from(" 622ae0c33d504fa79ee069d691238c6d "), id);
+        let raw: LenientUuid = serde_json::from_str(r#""123""#).unwrap();
+        assert!(!raw.is_uuid());
+        assert_eq!(raw, LenientUuid::from("123"));
+        assert_eq!(serde_json::to_string(&raw).unwrap(), r#""123""#);
+    }
+
+    #[test]
+    fn test_round_trips_the_original_text() {
+        for text in [
+            "622AE0C3-3D50-4FA7-9EE0-69D691238C6D",
+            "{622ae0c3-3d50-4fa7-9ee0-69d691238c6d}",
+            "urn:uuid:622ae0c3-3d50-4fa7-9ee0-69d691238c6d",
+            "622ae0c33d504fa79ee069d691238c6d",
+        ] {
+            let json = serde_json::to_string(text).unwrap();
+            let id: LenientUuid = serde_json::from_str(&json).unwrap();
+            assert!(id.is_uuid(), "{text}");
+            assert_eq!(serde_json::to_string(&id).unwrap(), json);
+            assert_eq!(
+                id,
+                LenientUuid::from(Uuid::parse_str("622ae0c3-3d50-4fa7-9ee0-69d691238c6d").unwrap())
+            );
+        }
+    }
+}
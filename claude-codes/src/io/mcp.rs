@@ -7,6 +7,9 @@ pub enum McpServerConfig {
     Stdio(McpStdioServerConfig),
     Sse(McpSseServerConfig),
     Http(McpHttpServerConfig),
+    /// A server running inside the SDK process, reached through
+    /// `mcp_message` control requests.
+    Sdk(McpSdkServerConfig),
 }
 
 /// MCP stdio server configuration
@@ -34,3 +37,9 @@ pub struct McpHttpServerConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub headers: Option<std::collections::HashMap<String, String>>,
 }
+
+/// MCP SDK server configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpSdkServerConfig {
+    pub name: String,
+}
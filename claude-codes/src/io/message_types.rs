@@ -3,7 +3,12 @@ use serde_json::Value;
 use std::fmt;
 use uuid::Uuid;
 
-use super::content_blocks::{deserialize_content_blocks, ContentBlock};
+use super::content_blocks::{
+    deserialize_content_blocks, ContentBlock, ToolResultBlock, ToolUseBlock,
+};
+use super::control::PermissionModeName;
+use super::lenient_uuid::{deserialize_optional_lenient_uuid, LenientUuid};
+use super::timestamp::Timestamp;
 
 /// Known system message subtypes.
 ///
@@ -11,6 +16,7 @@ use super::content_blocks::{deserialize_content_blocks, ContentBlock};
 /// kind of system event occurred. This enum captures the known subtypes while
 /// preserving unknown values via the `Unknown` variant for forward compatibility.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum SystemSubtype {
     Init,
     Status,
@@ -20,6 +26,14 @@ pub enum SystemSubtype {
     TaskProgress,
     TaskUpdated,
     TaskNotification,
+    ApiRetry,
+    Informational,
+    LocalCommandOutput,
+    Notification,
+    SessionStateChanged,
+    ModelFallback,
+    FilesPersisted,
+    BackgroundTasksChanged,
     /// A subtype not yet known to this version of the crate.
     Unknown(String),
 }
@@ -35,6 +49,14 @@ impl SystemSubtype {
             Self::TaskProgress => "task_progress",
             Self::TaskUpdated => "task_updated",
             Self::TaskNotification => "task_notification",
+            Self::ApiRetry => "api_retry",
+            Self::Informational => "informational",
+            Self::LocalCommandOutput => "local_command_output",
+            Self::Notification => "notification",
+            Self::SessionStateChanged => "session_state_changed",
+            Self::ModelFallback => "model_fallback",
+            Self::FilesPersisted => "files_persisted",
+            Self::BackgroundTasksChanged => "background_tasks_changed",
             Self::Unknown(s) => s.as_str(),
         }
     }
@@ -57,6 +79,14 @@ impl From<&str> for SystemSubtype {
             "task_progress" => Self::TaskProgress,
             "task_updated" => Self::TaskUpdated,
             "task_notification" => Self::TaskNotification,
+            "api_retry" => Self::ApiRetry,
+            "informational" => Self::Informational,
+            "local_command_output" => Self::LocalCommandOutput,
+            "notification" => Self::Notification,
+            "session_state_changed" => Self::SessionStateChanged,
+            "model_fallback" => Self::ModelFallback,
+            "files_persisted" => Self::FilesPersisted,
+            "background_tasks_changed" => Self::BackgroundTasksChanged,
             other => Self::Unknown(other.to_string()),
         }
     }
@@ -80,6 +110,7 @@ impl<'de> Deserialize<'de> for SystemSubtype {
 /// Used in `MessageContent` and `AssistantMessageContent` to indicate the
 /// speaker of a message.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum MessageRole {
     User,
     Assistant,
@@ -128,6 +159,7 @@ impl<'de> Deserialize<'de> for MessageRole {
 
 /// What triggered a context compaction.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum CompactionTrigger {
     /// Automatic compaction triggered by token limit.
     Auto,
@@ -178,6 +210,7 @@ impl<'de> Deserialize<'de> for CompactionTrigger {
 
 /// Reason why the assistant stopped generating.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum StopReason {
     /// The assistant reached a natural end of its turn.
     EndTurn,
@@ -232,6 +265,7 @@ impl<'de> Deserialize<'de> for StopReason {
 
 /// How the API key was sourced for the session.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum ApiKeySource {
     /// No API key provided.
     None,
@@ -278,6 +312,7 @@ impl<'de> Deserialize<'de> for ApiKeySource {
 
 /// Output formatting style for the session.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum OutputStyle {
     /// Default output style.
     Default,
@@ -324,6 +359,7 @@ impl<'de> Deserialize<'de> for OutputStyle {
 
 /// Permission mode reported in init messages.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum InitPermissionMode {
     /// Default permission mode.
     Default,
@@ -370,6 +406,7 @@ impl<'de> Deserialize<'de> for InitPermissionMode {
 
 /// Status of an ongoing operation (e.g., context compaction).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum StatusMessageStatus {
     /// Context compaction is in progress.
     Compacting,
@@ -414,31 +451,20 @@ impl<'de> Deserialize<'de> for StatusMessageStatus {
     }
 }
 
-/// Serialize an optional UUID as a string
-pub(crate) fn serialize_optional_uuid<S>(
-    uuid: &Option<Uuid>,
-    serializer: S,
-) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    match uuid {
-        Some(id) => serializer.serialize_str(&id.to_string()),
-        None => serializer.serialize_none(),
-    }
-}
-
-/// Deserialize an optional UUID from a string
+/// Deserialize an optional UUID from a string.
+///
+/// Permissive: accepts any form [`Uuid::parse_str`] understands (hyphenated,
+/// simple, braced, URN) and treats `null` or an empty string as `None`.
 pub(crate) fn deserialize_optional_uuid<'de, D>(deserializer: D) -> Result<Option<Uuid>, D::Error>
 where
     D: Deserializer<'de>,
 {
     let opt_str: Option<String> = Option::deserialize(deserializer)?;
-    match opt_str {
-        Some(s) => Uuid::parse_str(&s)
+    match opt_str.as_deref().map(str::trim) {
+        Some("") | None => Ok(None),
+        Some(s) => Uuid::parse_str(s)
             .map(Some)
             .map_err(serde::de::Error::custom),
-        None => Ok(None),
     }
 }
 
@@ -446,21 +472,26 @@ where
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserMessage {
     pub message: MessageContent,
-    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(
-        serialize_with = "serialize_optional_uuid",
-        deserialize_with = "deserialize_optional_uuid"
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_lenient_uuid"
     )]
-    pub session_id: Option<Uuid>,
-    /// Parent tool use ID for nested agent messages
+    pub session_id: Option<LenientUuid>,
+    /// Parent tool use ID for nested agent messages. A tool-use id
+    /// (`toolu_...`), not a UUID.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_tool_use_id: Option<String>,
     /// Message-level unique identifier
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub uuid: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_lenient_uuid"
+    )]
+    pub uuid: Option<LenientUuid>,
     /// CLI-emitted ISO-8601 timestamp for the message (present on echoed tool results).
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<Timestamp>,
     /// Structured tool result data echoed by the CLI alongside the `tool_result`
     /// content block. The shape depends on which tool produced it (e.g. for
     /// `AskUserQuestion` it is `{ questions, answers }`; for `Bash` it is
@@ -479,6 +510,44 @@ pub struct UserMessage {
 }
 
 impl UserMessage {
+    /// All text blocks joined into one string (empty if there are none).
+    pub fn concat_text(&self) -> String {
+        self.message
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text(t) => Some(t.text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Iterate over the tool results carried by this message.
+    pub fn tool_results(&self) -> impl Iterator<Item = &ToolResultBlock> {
+        self.message.content.iter().filter_map(|block| match block {
+            ContentBlock::ToolResult(tr) => Some(tr),
+            _ => None,
+        })
+    }
+
+    /// The result for a specific tool invocation, if this message carries it.
+    pub fn tool_result(&self, tool_use_id: &str) -> Option<&ToolResultBlock> {
+        self.tool_results().find(|tr| tr.tool_use_id == tool_use_id)
+    }
+
+    /// The checkpoint id for the turn this message started: its `uuid`,
+    /// when it is a prompt rather than tool results.
+    ///
+    /// Prompts are echoed back with a `uuid` when the session was spawned
+    /// with `ClaudeCliBuilder::enable_file_checkpointing`; pass the id to the
+    /// clients' `rewind` to undo the file edits made since.
+    pub fn checkpoint_id(&self) -> Option<Uuid> {
+        if self.parent_tool_use_id.is_some() || self.tool_results().next().is_some() {
+            return None;
+        }
+        self.uuid.as_ref().and_then(LenientUuid::as_uuid)
+    }
+
     /// Parse the `tool_use_result` field into a caller-specified type.
     ///
     /// Returns `None` if `tool_use_result` is absent, otherwise returns the
@@ -729,9 +798,124 @@ impl SystemMessage {
             SystemSubtype::TaskProgress => reserialize(self.as_task_progress()),
             SystemSubtype::TaskUpdated => reserialize(self.as_task_updated()),
             SystemSubtype::TaskNotification => reserialize(self.as_task_notification()),
+            SystemSubtype::ApiRetry
+            | SystemSubtype::Informational
+            | SystemSubtype::LocalCommandOutput
+            | SystemSubtype::Notification
+            | SystemSubtype::SessionStateChanged
+            | SystemSubtype::ModelFallback
+            | SystemSubtype::FilesPersisted
+            | SystemSubtype::BackgroundTasksChanged => match self.kind() {
+                SystemKind::ApiRetry(m) => reserialize(Some(m)),
+                SystemKind::Informational(m) => reserialize(Some(m)),
+                SystemKind::LocalCommandOutput(m) => reserialize(Some(m)),
+                SystemKind::Notification(m) => reserialize(Some(m)),
+                SystemKind::SessionStateChanged(m) => reserialize(Some(m)),
+                SystemKind::ModelFallback(m) => reserialize(Some(m)),
+                SystemKind::FilesPersisted(m) => reserialize(Some(m)),
+                SystemKind::BackgroundTasksChanged(m) => reserialize(Some(m)),
+                _ => None,
+            },
             SystemSubtype::Unknown(_) => None,
         }
     }
+
+    /// Parse this message into the typed struct for its subtype.
+    ///
+    /// Subtypes this crate doesn't model, and payloads that don't match
+    /// their subtype's struct, come back as [`SystemKind::OtherSystem`].
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::{ClaudeOutput, SessionState, SystemKind};
+    ///
+    /// let json = r#"{"type":"system","subtype":"session_state_changed","state":"idle",
+    ///     "uuid":"8ef6e997-a849-4d15-bed3-2837c3d3f4cd",
+    ///     "session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d"}"#;
+    /// let ClaudeOutput::System(sys) = ClaudeOutput::parse_json(json).unwrap() else {
+    ///     unreachable!()
+    /// };
+    /// match sys.kind() {
+    ///     SystemKind::SessionStateChanged(m) => assert_eq!(m.state, SessionState::Idle),
+    ///     other => panic!("unexpected {other:?}"),
+    /// }
+    /// ```
+    pub fn kind(&self) -> SystemKind {
+        let typed = match self.subtype {
+            SystemSubtype::Init => self.as_init().map(|m| SystemKind::Init(Box::new(m))),
+            SystemSubtype::Status => self.as_status().map(SystemKind::Status),
+            SystemSubtype::CompactBoundary => {
+                self.as_compact_boundary().map(SystemKind::CompactBoundary)
+            }
+            SystemSubtype::ThinkingTokens => {
+                self.as_thinking_tokens().map(SystemKind::ThinkingTokens)
+            }
+            SystemSubtype::TaskStarted => self.as_task_started().map(SystemKind::TaskStarted),
+            SystemSubtype::TaskProgress => self.as_task_progress().map(SystemKind::TaskProgress),
+            SystemSubtype::TaskUpdated => self.as_task_updated().map(SystemKind::TaskUpdated),
+            SystemSubtype::TaskNotification => self
+                .as_task_notification()
+                .map(SystemKind::TaskNotification),
+            SystemSubtype::ApiRetry => self.parse_data().map(SystemKind::ApiRetry),
+            SystemSubtype::Informational => self.parse_data().map(SystemKind::Informational),
+            SystemSubtype::LocalCommandOutput => {
+                self.parse_data().map(SystemKind::LocalCommandOutput)
+            }
+            SystemSubtype::Notification => self.parse_data().map(SystemKind::Notification),
+            SystemSubtype::SessionStateChanged => {
+                self.parse_data().map(SystemKind::SessionStateChanged)
+            }
+            SystemSubtype::ModelFallback => self.parse_data().map(SystemKind::ModelFallback),
+            SystemSubtype::FilesPersisted => self.parse_data().map(SystemKind::FilesPersisted),
+            SystemSubtype::BackgroundTasksChanged => {
+                self.parse_data().map(SystemKind::BackgroundTasksChanged)
+            }
+            SystemSubtype::Unknown(_) => None,
+        };
+        typed.unwrap_or_else(|| {
+            SystemKind::OtherSystem(OtherSystem {
+                subtype: self.subtype.clone(),
+                data: self.data.clone(),
+            })
+        })
+    }
+
+    fn parse_data<T: serde::de::DeserializeOwned>(&self) -> Option<T> {
+        serde_json::from_value(self.data.clone()).ok()
+    }
+}
+
+/// A [`SystemMessage`] parsed by subtype (see [`SystemMessage::kind`]).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum SystemKind {
+    /// Boxed: the init payload is much larger than the rest.
+    Init(Box<InitMessage>),
+    Status(StatusMessage),
+    CompactBoundary(CompactBoundaryMessage),
+    ThinkingTokens(ThinkingTokensMessage),
+    TaskStarted(TaskStartedMessage),
+    TaskProgress(TaskProgressMessage),
+    TaskUpdated(TaskUpdatedMessage),
+    TaskNotification(TaskNotificationMessage),
+    ApiRetry(ApiRetryMessage),
+    Informational(InformationalMessage),
+    LocalCommandOutput(LocalCommandOutputMessage),
+    Notification(NotificationMessage),
+    SessionStateChanged(SessionStateChangedMessage),
+    ModelFallback(ModelFallbackMessage),
+    FilesPersisted(FilesPersistedMessage),
+    BackgroundTasksChanged(BackgroundTasksChangedMessage),
+    /// A subtype without a typed struct, or a payload that didn't parse.
+    OtherSystem(OtherSystem),
+}
+
+/// A system message [`SystemMessage::kind`] couldn't type.
+#[derive(Debug, Clone)]
+pub struct OtherSystem {
+    pub subtype: SystemSubtype,
+    /// Every field besides `type` and `subtype`.
+    pub data: Value,
 }
 
 /// Plugin info from the init message
@@ -750,7 +934,7 @@ pub struct PluginInfo {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InitMessage {
     /// Session identifier
-    pub session_id: String,
+    pub session_id: LenientUuid,
     /// Current working directory
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cwd: Option<String>,
@@ -789,8 +973,12 @@ pub struct InitMessage {
     pub permission_mode: Option<InitPermissionMode>,
 
     /// Message-level unique identifier
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub uuid: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_lenient_uuid"
+    )]
+    pub uuid: Option<LenientUuid>,
 
     /// Memory storage paths (e.g., {"auto": "/path/to/memory/"})
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -813,19 +1001,36 @@ pub struct InitMessage {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatusMessage {
     /// Session identifier
-    pub session_id: String,
+    pub session_id: LenientUuid,
     /// Current status (e.g., compacting) or null when complete
     pub status: Option<StatusMessageStatus>,
+    /// The session's permission mode, sent when it changes
+    #[serde(
+        rename = "permissionMode",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub permission_mode: Option<PermissionModeName>,
+    /// Outcome of a finished compaction (`success` or `failed`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compact_result: Option<String>,
+    /// Why compaction failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compact_error: Option<String>,
     /// Unique identifier for this message
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub uuid: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_lenient_uuid"
+    )]
+    pub uuid: Option<LenientUuid>,
 }
 
 /// Compact boundary message - marks where context compaction occurred
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompactBoundaryMessage {
     /// Session identifier
-    pub session_id: String,
+    pub session_id: LenientUuid,
     /// Metadata about the compaction
     pub compact_metadata: CompactMetadata,
     /// Human-readable summary of what was compacted, when the CLI emits one.
@@ -851,8 +1056,12 @@ pub struct CompactBoundaryMessage {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub duration_ms: Option<u64>,
     /// Unique identifier for this message
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub uuid: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_lenient_uuid"
+    )]
+    pub uuid: Option<LenientUuid>,
 }
 
 /// Metadata about context compaction
@@ -880,27 +1089,111 @@ pub struct TaskUsage {
 }
 
 /// The kind of background task.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum TaskType {
     /// A sub-agent task (e.g., Explore, Plan).
     LocalAgent,
     /// A background bash command.
     LocalBash,
+    /// A task type not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl TaskType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::LocalAgent => "local_agent",
+            Self::LocalBash => "local_bash",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for TaskType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for TaskType {
+    fn from(s: &str) -> Self {
+        match s {
+            "local_agent" => Self::LocalAgent,
+            "local_bash" => Self::LocalBash,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for TaskType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
 }
 
 /// Completion status of a background task.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum TaskStatus {
+    /// The task finished.
     Completed,
+    /// The task failed.
     Failed,
+    /// A status not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for TaskStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "completed" => Self::Completed,
+            "failed" => Self::Failed,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for TaskStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
 }
 
 /// `task_started` system message — emitted once when a background task begins.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskStartedMessage {
-    pub session_id: String,
+    pub session_id: LenientUuid,
     pub task_id: String,
     pub task_type: TaskType,
     pub tool_use_id: String,
@@ -912,7 +1205,7 @@ pub struct TaskStartedMessage {
     /// The prompt handed to the subagent. Present for `local_agent` tasks.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub prompt: Option<String>,
-    pub uuid: String,
+    pub uuid: LenientUuid,
 }
 
 /// `task_updated` system message — emitted when a background task's state
@@ -920,10 +1213,10 @@ pub struct TaskStartedMessage {
 /// fields that changed rather than the full task record.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskUpdatedMessage {
-    pub session_id: String,
+    pub session_id: LenientUuid,
     pub task_id: String,
     pub patch: TaskPatch,
-    pub uuid: String,
+    pub uuid: LenientUuid,
 }
 
 /// The partial update carried by a [`TaskUpdatedMessage`]. Every field is
@@ -932,29 +1225,34 @@ pub struct TaskUpdatedMessage {
 pub struct TaskPatch {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub status: Option<TaskStatus>,
-    /// Wall-clock epoch milliseconds when the task finished, when the patch
-    /// reports completion.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub end_time: Option<u64>,
+    /// When the task finished, when the patch reports completion. Sent as
+    /// epoch milliseconds on the wire; other values are kept in
+    /// [`Timestamp::Raw`].
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "super::timestamp::epoch_millis_option"
+    )]
+    pub end_time: Option<Timestamp>,
 }
 
 /// `thinking_tokens` system message — emitted as the model streams extended
 /// thinking, reporting the running estimate of thinking tokens consumed.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThinkingTokensMessage {
-    pub session_id: String,
+    pub session_id: LenientUuid,
     /// Running estimate of total thinking tokens for the current turn.
     pub estimated_tokens: u64,
     /// Increase in the estimate since the previous `thinking_tokens` event.
     pub estimated_tokens_delta: u64,
-    pub uuid: String,
+    pub uuid: LenientUuid,
 }
 
 /// `task_progress` system message — emitted periodically as a background
 /// agent task executes tools. Not emitted for `local_bash` tasks.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskProgressMessage {
-    pub session_id: String,
+    pub session_id: LenientUuid,
     pub task_id: String,
     pub tool_use_id: String,
     pub description: String,
@@ -963,14 +1261,14 @@ pub struct TaskProgressMessage {
     /// Subagent type for `local_agent` tasks (e.g. `Explore`).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub subagent_type: Option<String>,
-    pub uuid: String,
+    pub uuid: LenientUuid,
 }
 
 /// `task_notification` system message — emitted once when a background
 /// task completes or fails.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskNotificationMessage {
-    pub session_id: String,
+    pub session_id: LenientUuid,
     pub task_id: String,
     pub status: TaskStatus,
     pub summary: String,
@@ -979,17 +1277,344 @@ pub struct TaskNotificationMessage {
     pub tool_use_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub usage: Option<TaskUsage>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub uuid: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_lenient_uuid"
+    )]
+    pub uuid: Option<LenientUuid>,
+}
+
+// ---------------------------------------------------------------------------
+// Notice system message types (api_retry, notification, session_state_changed, ...)
+// ---------------------------------------------------------------------------
+
+/// How prominently an [`InformationalMessage`] should be shown.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum NoticeLevel {
+    /// Only shown in transcript mode.
+    Info,
+    /// Shown dimmed.
+    Notice,
+    /// Shown prominently.
+    Suggestion,
+    /// Shown prominently as a warning.
+    Warning,
+    /// A level not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl NoticeLevel {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Info => "info",
+            Self::Notice => "notice",
+            Self::Suggestion => "suggestion",
+            Self::Warning => "warning",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for NoticeLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for NoticeLevel {
+    fn from(s: &str) -> Self {
+        match s {
+            "info" => Self::Info,
+            "notice" => Self::Notice,
+            "suggestion" => Self::Suggestion,
+            "warning" => Self::Warning,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for NoticeLevel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for NoticeLevel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// Priority of a [`NotificationMessage`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum NotificationPriority {
+    /// Low priority.
+    Low,
+    /// Medium priority.
+    Medium,
+    /// High priority.
+    High,
+    /// Shown right away, ahead of queued notifications.
+    Immediate,
+    /// A priority not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl NotificationPriority {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Immediate => "immediate",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for NotificationPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for NotificationPriority {
+    fn from(s: &str) -> Self {
+        match s {
+            "low" => Self::Low,
+            "medium" => Self::Medium,
+            "high" => Self::High,
+            "immediate" => Self::Immediate,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for NotificationPriority {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for NotificationPriority {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// Run state reported by [`SessionStateChangedMessage`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SessionState {
+    /// The turn and its background work are finished.
+    Idle,
+    /// Claude is working.
+    Running,
+    /// Waiting on the host, e.g. for a permission answer.
+    RequiresAction,
+    /// A state not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl SessionState {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Idle => "idle",
+            Self::Running => "running",
+            Self::RequiresAction => "requires_action",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for SessionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for SessionState {
+    fn from(s: &str) -> Self {
+        match s {
+            "idle" => Self::Idle,
+            "running" => Self::Running,
+            "requires_action" => Self::RequiresAction,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for SessionState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for SessionState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// `api_retry` system message — a model API request failed with a retryable
+/// error and will be retried after `retry_delay_ms`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiRetryMessage {
+    pub session_id: LenientUuid,
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub retry_delay_ms: u64,
+    /// HTTP status of the failed attempt; `None` for connection errors.
+    pub error_status: Option<u16>,
+    /// Error category, e.g. `rate_limit`, `overloaded`, or `server_error`.
+    pub error: String,
+    /// Set when the API sent no response headers in time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_response: Option<ApiRetryNoResponse>,
+    pub uuid: LenientUuid,
+}
+
+/// How long an [`ApiRetryMessage`] attempt waited for a first byte.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiRetryNoResponse {
+    pub waited_ms: u64,
+    pub retry_wait_ms: u64,
+}
+
+/// `informational` system message — a plain-text banner such as hook
+/// feedback or slash-command output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InformationalMessage {
+    pub session_id: LenientUuid,
+    pub content: String,
+    pub level: NoticeLevel,
+    /// Groups repeated progress banners for the same tool call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<String>,
+    /// Whether the turn stops after this message (e.g. a Stop hook blocked it).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prevent_continuation: Option<bool>,
+    pub uuid: LenientUuid,
+}
+
+/// `local_command_output` system message — output of a local slash command
+/// such as `/usage`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalCommandOutputMessage {
+    pub session_id: LenientUuid,
+    pub content: String,
+    pub uuid: LenientUuid,
+}
+
+/// `notification` system message — a text notification from the agent loop,
+/// such as a login or usage-limit notice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationMessage {
+    pub session_id: LenientUuid,
+    /// Identifies the notification; a newer one with the same key replaces it.
+    pub key: String,
+    pub text: String,
+    pub priority: NotificationPriority,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// How long to show the notification, in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    pub uuid: LenientUuid,
+}
+
+/// `session_state_changed` system message — the session went idle, started
+/// running, or needs the host to act. `idle` is the authoritative end of a
+/// turn, including its background work.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStateChangedMessage {
+    pub session_id: LenientUuid,
+    pub state: SessionState,
+    pub uuid: LenientUuid,
+}
+
+/// `model_fallback` system message — the turn switched to the fallback model
+/// because the primary model failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelFallbackMessage {
+    pub session_id: LenientUuid,
+    /// Why the primary model was dropped, e.g. `overloaded` or
+    /// `model_not_found`.
+    pub trigger: String,
+    pub original_model: String,
+    pub fallback_model: String,
+    /// Banner text describing the switch.
+    pub content: String,
+    pub uuid: LenientUuid,
+}
+
+/// `files_persisted` system message — output files uploaded at the end of a
+/// turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilesPersistedMessage {
+    pub session_id: LenientUuid,
+    pub files: Vec<PersistedFile>,
+    pub failed: Vec<FailedPersistedFile>,
+    pub processed_at: String,
+    pub uuid: LenientUuid,
+}
+
+/// A file listed in [`FilesPersistedMessage::files`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedFile {
+    pub filename: String,
+    pub file_id: String,
+}
+
+/// A file listed in [`FilesPersistedMessage::failed`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedPersistedFile {
+    pub filename: String,
+    pub error: String,
+}
+
+/// `background_tasks_changed` system message — the full set of live
+/// background tasks, sent whenever it changes. Replace any earlier set with
+/// this one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundTasksChangedMessage {
+    pub session_id: LenientUuid,
+    pub tasks: Vec<BackgroundTask>,
+    pub uuid: LenientUuid,
+}
+
+/// An entry in [`BackgroundTasksChangedMessage::tasks`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundTask {
+    pub task_id: String,
+    /// Task kind, e.g. `local_agent` or `local_bash`.
+    pub task_type: String,
+    pub description: String,
+    /// Whether the task is a watcher rather than activity; hosts should
+    /// leave these out of activity indicators.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ambient: Option<bool>,
 }
 
 /// Assistant message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssistantMessage {
     pub message: AssistantMessageContent,
-    pub session_id: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub uuid: Option<String>,
+    pub session_id: LenientUuid,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_lenient_uuid"
+    )]
+    pub uuid: Option<LenientUuid>,
+    /// Tool-use id (`toolu_...`) of the Task call that spawned this message,
+    /// when it came from a subagent. Not a UUID.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parent_tool_use_id: Option<String>,
     /// Anthropic API request id that produced this message (e.g. `req_...`).
@@ -1004,6 +1629,71 @@ pub struct AssistantMessage {
     pub task_description: Option<String>,
 }
 
+impl AssistantMessage {
+    /// Iterate over the text of each `text` block, in order.
+    pub fn text_blocks(&self) -> impl Iterator<Item = &str> {
+        self.message.content.iter().filter_map(|block| match block {
+            ContentBlock::Text(t) => Some(t.text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// All text blocks joined into one string (empty if there are none).
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::ClaudeOutput;
+    ///
+    /// let json = r#"{"type":"assistant","message":{"id":"msg_1","role":"assistant",
+    ///     "model":"claude-3","content":[
+    ///         {"type":"text","text":"Hello, "},
+    ///         {"type":"tool_use","id":"tu_1","name":"Bash","input":{"command":"ls"}},
+    ///         {"type":"text","text":"world!"}
+    ///     ]},"session_id":"abc"}"#;
+    /// let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+    /// assert_eq!(output.as_assistant().unwrap().concat_text(), "Hello, world!");
+    /// ```
+    pub fn concat_text(&self) -> String {
+        self.text_blocks().collect()
+    }
+
+    /// Iterate over the tool invocations in this message.
+    pub fn tool_uses(&self) -> impl Iterator<Item = &ToolUseBlock> {
+        self.message.content.iter().filter_map(|block| match block {
+            ContentBlock::ToolUse(tu) => Some(tu),
+            _ => None,
+        })
+    }
+
+    /// The first tool invocation with the given name, if any.
+    pub fn tool_use(&self, name: &str) -> Option<&ToolUseBlock> {
+        self.tool_uses().find(|tu| tu.name == name)
+    }
+
+    /// Whether this message invokes any tools.
+    pub fn has_tool_use(&self) -> bool {
+        self.tool_uses().next().is_some()
+    }
+
+    /// All thinking blocks joined with newlines, or `None` if there are none.
+    pub fn thinking_text(&self) -> Option<String> {
+        let thoughts: Vec<&str> = self
+            .message
+            .content
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::Thinking(t) => Some(t.thinking.as_str()),
+                _ => None,
+            })
+            .collect();
+        if thoughts.is_empty() {
+            None
+        } else {
+            Some(thoughts.join("\n"))
+        }
+    }
+}
+
 /// Nested message content for assistant messages
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssistantMessageContent {
@@ -1074,7 +1764,7 @@ pub struct CacheCreationDetails {
 
 #[cfg(test)]
 mod tests {
-    use crate::io::ClaudeOutput;
+    use crate::io::{ClaudeOutput, LenientUuid, Timestamp};
 
     #[test]
     fn test_system_message_init() {
@@ -1103,7 +1793,7 @@ mod tests {
             assert!(!sys.is_compact_boundary());
 
             let init = sys.as_init().expect("Should parse as init");
-            assert_eq!(init.session_id, "test-session-123");
+            assert_eq!(init.session_id, LenientUuid::from("test-session-123"));
             assert_eq!(init.cwd, Some("/home/user/project".to_string()));
             assert_eq!(init.model, Some("claude-sonnet-4".to_string()));
             assert_eq!(init.tools, vec!["Bash", "Read", "Write"]);
@@ -1159,11 +1849,14 @@ mod tests {
             assert!(!sys.is_init());
 
             let status = sys.as_status().expect("Should parse as status");
-            assert_eq!(status.session_id, "879c1a88-3756-4092-aa95-0020c4ed9692");
+            assert_eq!(
+                status.session_id,
+                uuid::uuid!("879c1a88-3756-4092-aa95-0020c4ed9692")
+            );
             assert_eq!(status.status, Some(super::StatusMessageStatus::Compacting));
             assert_eq!(
                 status.uuid,
-                Some("32eb9f9d-5ef7-47ff-8fce-bbe22fe7ed93".to_string())
+                Some(uuid::uuid!("32eb9f9d-5ef7-47ff-8fce-bbe22fe7ed93").into())
             );
         } else {
             panic!("Expected System message");
@@ -1209,7 +1902,10 @@ mod tests {
             assert!(!sys.is_task_notification());
 
             let task = sys.as_task_started().expect("Should parse as task_started");
-            assert_eq!(task.session_id, "9abbc466-dad0-4b8e-b6b0-cad5eb7a16b9");
+            assert_eq!(
+                task.session_id,
+                uuid::uuid!("9abbc466-dad0-4b8e-b6b0-cad5eb7a16b9")
+            );
             assert_eq!(task.task_id, "b6daf3f");
             assert_eq!(task.task_type, super::TaskType::LocalBash);
             assert_eq!(task.tool_use_id, "toolu_011rfSTFumpJZdCCfzeD7jaS");
@@ -1431,7 +2127,10 @@ mod tests {
             let compact = sys
                 .as_compact_boundary()
                 .expect("Should parse as compact_boundary");
-            assert_eq!(compact.session_id, "879c1a88-3756-4092-aa95-0020c4ed9692");
+            assert_eq!(
+                compact.session_id,
+                uuid::uuid!("879c1a88-3756-4092-aa95-0020c4ed9692")
+            );
             assert_eq!(compact.compact_metadata.pre_tokens, 155285);
             assert_eq!(
                 compact.compact_metadata.trigger,
@@ -1513,8 +2212,8 @@ mod tests {
         if let ClaudeOutput::System(sys) = output {
             let init = sys.as_init().expect("Should parse as init");
             assert_eq!(
-                init.uuid.as_deref(),
-                Some("44841a0d-182d-493a-86b5-79800d3d9665")
+                init.uuid,
+                Some(uuid::uuid!("44841a0d-182d-493a-86b5-79800d3d9665").into())
             );
             assert!(init.memory_paths.is_some());
             assert_eq!(init.fast_mode_state.as_deref(), Some("off"));
@@ -1578,7 +2277,7 @@ mod tests {
         let output: ClaudeOutput = serde_json::from_str(json).unwrap();
         if let ClaudeOutput::User(user) = output {
             assert_eq!(user.parent_tool_use_id.as_deref(), Some("toolu_123"));
-            assert_eq!(user.uuid.as_deref(), Some("user-msg-456"));
+            assert_eq!(user.uuid, Some(LenientUuid::from("user-msg-456")));
         } else {
             panic!("Expected User message");
         }
@@ -1607,7 +2306,11 @@ mod tests {
             other => panic!("Expected User message, got {:?}", other.message_type()),
         };
 
-        assert_eq!(user.timestamp.as_deref(), Some("2026-05-12T23:12:04.121Z"));
+        assert_eq!(
+            user.timestamp.as_ref().map(|t| t.to_string()).as_deref(),
+            Some("2026-05-12T23:12:04.121Z")
+        );
+        assert!(user.timestamp.as_ref().unwrap().is_parsed());
         let raw = user
             .tool_use_result
             .as_ref()
@@ -1661,6 +2364,68 @@ mod tests {
         assert!(reser.get("timestamp").is_none());
     }
 
+    #[test]
+    fn test_task_updated_end_time_is_typed() {
+        let json = r#"{"type":"system","subtype":"task_updated",
+            "session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","task_id":"b6daf3f",
+            "patch":{"status":"completed","end_time":1778627524121},
+            "uuid":"8ef6e997-a849-4d15-bed3-2837c3d3f4cd"}"#;
+        let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+        let ClaudeOutput::System(sys) = output else {
+            panic!("Expected System message");
+        };
+        let task = sys.as_task_updated().expect("Should parse as task_updated");
+        let end = task.patch.end_time.as_ref().expect("end_time present");
+        assert_eq!(end.as_datetime().unwrap().timestamp_millis(), 1778627524121);
+        let reser = serde_json::to_value(&task.patch).unwrap();
+        assert_eq!(reser["end_time"], 1778627524121u64);
+
+        let odd = r#"{"type":"system","subtype":"task_updated",
+            "session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","task_id":"b6daf3f",
+            "patch":{"status":"completed","end_time":"later"},
+            "uuid":"8ef6e997-a849-4d15-bed3-2837c3d3f4cd"}"#;
+        let ClaudeOutput::System(sys) = serde_json::from_str(odd).unwrap() else {
+            panic!("Expected System message");
+        };
+        let task = sys.as_task_updated().expect("Should parse as task_updated");
+        assert_eq!(
+            task.patch.end_time,
+            Some(Timestamp::Raw("later".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_uuid_fields_parse_permissively() {
+        let json = r#"{"type":"assistant","message":{"id":"m","role":"assistant",
+            "model":"c","content":[]},
+            "session_id":"622AE0C33D504FA79EE069D691238C6D","uuid":""}"#;
+        let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+        let ClaudeOutput::Assistant(msg) = output else {
+            panic!("Expected Assistant message");
+        };
+        assert_eq!(
+            msg.session_id,
+            uuid::uuid!("622ae0c3-3d50-4fa7-9ee0-69d691238c6d")
+        );
+        assert!(msg.uuid.is_none());
+
+        let reser = serde_json::to_value(&msg).unwrap();
+        assert_eq!(reser["session_id"], "622AE0C33D504FA79EE069D691238C6D");
+        assert!(reser.get("uuid").is_none());
+
+        let odd = r#"{"type":"assistant","message":{"id":"m","role":"assistant",
+            "model":"c","content":[]},"session_id":"not-a-uuid","uuid":"u1"}"#;
+        let ClaudeOutput::Assistant(msg) = serde_json::from_str(odd).unwrap() else {
+            panic!("Expected Assistant message");
+        };
+        assert_eq!(msg.session_id, LenientUuid::from("not-a-uuid"));
+        assert!(!msg.session_id.is_uuid());
+        assert_eq!(msg.uuid, Some(LenientUuid::from("u1")));
+        let reser = serde_json::to_value(&msg).unwrap();
+        assert_eq!(reser["session_id"], "not-a-uuid");
+        assert_eq!(reser["uuid"], "u1");
+    }
+
     /// A `Task` tool result must expose subagent token / timing / tool-use
     /// accounting through the typed [`UserMessage::subagent_result`] accessor,
     /// including the nested per-model `usage` breakdown and `toolStats`.
@@ -1731,4 +2496,91 @@ mod tests {
         assert_eq!(result.total_tokens, None);
         assert_eq!(result.agent_type, None);
     }
+
+    #[test]
+    fn test_system_kind_notices() {
+        use super::{NotificationPriority, SessionState, SystemKind};
+
+        let ids = r#""uuid":"8ef6e997-a849-4d15-bed3-2837c3d3f4cd","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d""#;
+        let frames = [
+            format!(
+                r#"{{"type":"system","subtype":"api_retry","attempt":2,"max_retries":10,
+                "retry_delay_ms":1200,"error_status":null,"error":"server_error",{ids}}}"#
+            ),
+            format!(
+                r#"{{"type":"system","subtype":"notification","key":"login-expired",
+                "text":"Your session expired; run /login","priority":"high",{ids}}}"#
+            ),
+            format!(
+                r#"{{"type":"system","subtype":"session_state_changed","state":"requires_action",{ids}}}"#
+            ),
+            format!(
+                r#"{{"type":"system","subtype":"model_fallback","trigger":"overloaded",
+                "original_model":"claude-opus-4","fallback_model":"claude-sonnet-4",
+                "content":"Switched to claude-sonnet-4",{ids}}}"#
+            ),
+            format!(
+                r#"{{"type":"system","subtype":"status","status":null,"permissionMode":"plan",{ids}}}"#
+            ),
+        ];
+        let kinds: Vec<SystemKind> = frames
+            .iter()
+            .map(|frame| {
+                let raw: serde_json::Value = serde_json::from_str(frame).unwrap();
+                crate::io::assert_fully_wrapped(&raw);
+                match ClaudeOutput::parse_json(frame).unwrap() {
+                    ClaudeOutput::System(sys) => sys.kind(),
+                    other => panic!("Expected System, got {}", other.message_type()),
+                }
+            })
+            .collect();
+
+        let SystemKind::ApiRetry(retry) = &kinds[0] else {
+            panic!("Expected ApiRetry, got {:?}", kinds[0]);
+        };
+        assert_eq!((retry.attempt, retry.error_status), (2, None));
+        let SystemKind::Notification(note) = &kinds[1] else {
+            panic!("Expected Notification, got {:?}", kinds[1]);
+        };
+        assert_eq!(note.priority, NotificationPriority::High);
+        let SystemKind::SessionStateChanged(state) = &kinds[2] else {
+            panic!("Expected SessionStateChanged, got {:?}", kinds[2]);
+        };
+        assert_eq!(state.state, SessionState::RequiresAction);
+        let SystemKind::ModelFallback(fallback) = &kinds[3] else {
+            panic!("Expected ModelFallback, got {:?}", kinds[3]);
+        };
+        assert_eq!(fallback.fallback_model, "claude-sonnet-4");
+        let SystemKind::Status(status) = &kinds[4] else {
+            panic!("Expected Status, got {:?}", kinds[4]);
+        };
+        assert_eq!(
+            status.permission_mode,
+            Some(crate::io::PermissionModeName::Plan)
+        );
+    }
+
+    #[test]
+    fn test_system_kind_falls_back_to_other() {
+        use super::SystemKind;
+
+        let json = r#"{"type":"system","subtype":"ui_toast","plugin":"p","text":"hi","timeout_ms":4000,
+            "uuid":"8ef6e997-a849-4d15-bed3-2837c3d3f4cd","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d"}"#;
+        let ClaudeOutput::System(sys) = ClaudeOutput::parse_json(json).unwrap() else {
+            panic!("Expected System message");
+        };
+        let SystemKind::OtherSystem(other) = sys.kind() else {
+            panic!("Expected OtherSystem");
+        };
+        assert_eq!(other.subtype.as_str(), "ui_toast");
+        assert_eq!(other.data["text"], "hi");
+        assert_eq!(sys.to_string(), "[system:ui_toast] hi");
+
+        // A known subtype whose payload doesn't fit its struct also lands here.
+        let json = r#"{"type":"system","subtype":"notification","text":"no key"}"#;
+        let ClaudeOutput::System(sys) = ClaudeOutput::parse_json(json).unwrap() else {
+            panic!("Expected System message");
+        };
+        assert!(matches!(sys.kind(), SystemKind::OtherSystem(_)));
+    }
 }
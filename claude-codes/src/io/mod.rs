@@ -29,24 +29,47 @@
 //! }
 //! ```
 
+mod auth;
+mod checkpoint;
 mod claude_input;
 mod claude_output;
 mod content_blocks;
 mod control;
+mod debug_log;
+mod display;
 mod errors;
+mod hooks;
+mod lenient_uuid;
 mod mcp;
 mod message_types;
 mod rate_limit;
 mod result;
+mod sanitize;
+mod spill;
+mod timestamp;
+mod tool_results;
+mod transcript;
+mod visitor;
 mod wrap_audit;
 
+pub use auth::*;
+pub use checkpoint::*;
 pub use claude_input::*;
 pub use claude_output::*;
 pub use content_blocks::*;
 pub use control::*;
+pub use debug_log::*;
 pub use errors::*;
+pub use hooks::*;
+pub use lenient_uuid::*;
 pub use mcp::*;
 pub use message_types::*;
 pub use rate_limit::*;
 pub use result::*;
+pub use sanitize::*;
+pub use spill::*;
+pub use timestamp::*;
+pub use tool_results::*;
+pub use transcript::*;
+pub use visitor::*;
 pub use wrap_audit::*;
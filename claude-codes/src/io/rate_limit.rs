@@ -1,8 +1,12 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 
+use super::lenient_uuid::{deserialize_optional_lenient_uuid, LenientUuid};
+use super::timestamp::Timestamp;
+
 /// Current rate limit disposition.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum RateLimitStatus {
     /// Request is within limits.
     Allowed,
@@ -57,6 +61,7 @@ impl<'de> Deserialize<'de> for RateLimitStatus {
 
 /// The time window a rate limit applies to.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum RateLimitWindow {
     /// Five-hour rolling window.
     FiveHour,
@@ -111,6 +116,7 @@ impl<'de> Deserialize<'de> for RateLimitWindow {
 
 /// Whether overage billing was accepted or rejected.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum OverageStatus {
     /// Overage was accepted.
     Allowed,
@@ -161,6 +167,7 @@ impl<'de> Deserialize<'de> for OverageStatus {
 
 /// Why overage billing is disabled.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum OverageDisabledReason {
     /// Overage is disabled at the organization level.
     OrgLevelDisabled,
@@ -242,7 +249,7 @@ impl<'de> Deserialize<'de> for OverageDisabledReason {
 ///
 /// if let Some(evt) = output.as_rate_limit_event() {
 ///     println!("Rate limit status: {}", evt.rate_limit_info.status);
-///     if let Some(resets_at) = evt.rate_limit_info.resets_at {
+///     if let Some(resets_at) = &evt.rate_limit_info.resets_at {
 ///         println!("Resets at: {}", resets_at);
 ///     }
 /// }
@@ -252,10 +259,14 @@ pub struct RateLimitEvent {
     /// Rate limit status details
     pub rate_limit_info: RateLimitInfo,
     /// Session identifier
-    pub session_id: String,
+    pub session_id: LenientUuid,
     /// Unique identifier for this message
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub uuid: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_lenient_uuid"
+    )]
+    pub uuid: Option<LenientUuid>,
 }
 
 /// Rate limit status information.
@@ -263,9 +274,15 @@ pub struct RateLimitEvent {
 pub struct RateLimitInfo {
     /// Current rate limit status
     pub status: RateLimitStatus,
-    /// Unix timestamp when the rate limit resets
-    #[serde(rename = "resetsAt", skip_serializing_if = "Option::is_none")]
-    pub resets_at: Option<u64>,
+    /// When the rate limit resets. Sent as Unix seconds on the wire; other
+    /// values are kept in [`Timestamp::Raw`].
+    #[serde(
+        rename = "resetsAt",
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "super::timestamp::epoch_seconds_option"
+    )]
+    pub resets_at: Option<Timestamp>,
     /// Type of rate limit window
     #[serde(rename = "rateLimitType", skip_serializing_if = "Option::is_none")]
     pub rate_limit_type: Option<RateLimitWindow>,
@@ -286,7 +303,7 @@ pub struct RateLimitInfo {
 #[cfg(test)]
 mod tests {
     use super::{OverageDisabledReason, OverageStatus, RateLimitStatus, RateLimitWindow};
-    use crate::io::ClaudeOutput;
+    use crate::io::{ClaudeOutput, Timestamp};
 
     #[test]
     fn test_deserialize_rate_limit_event() {
@@ -297,12 +314,19 @@ mod tests {
         assert_eq!(output.message_type(), "rate_limit_event");
         assert_eq!(
             output.session_id(),
-            Some("1ae0af5b-89fa-4075-8156-d5d3702f6505")
+            Some(uuid::uuid!("1ae0af5b-89fa-4075-8156-d5d3702f6505").into())
         );
 
         let evt = output.as_rate_limit_event().unwrap();
         assert_eq!(evt.rate_limit_info.status, RateLimitStatus::Allowed);
-        assert_eq!(evt.rate_limit_info.resets_at, Some(1771390800));
+        assert_eq!(
+            evt.rate_limit_info
+                .resets_at
+                .as_ref()
+                .and_then(Timestamp::as_datetime)
+                .map(|t| t.timestamp()),
+            Some(1771390800)
+        );
         assert_eq!(
             evt.rate_limit_info.rate_limit_type,
             Some(RateLimitWindow::FiveHour)
@@ -319,7 +343,7 @@ mod tests {
         assert!(!evt.rate_limit_info.is_using_overage);
         assert_eq!(
             evt.uuid,
-            Some("76258cfb-0dc8-4d4b-8682-77082b59c03f".to_string())
+            Some(uuid::uuid!("76258cfb-0dc8-4d4b-8682-77082b59c03f").into())
         );
     }
 
@@ -332,6 +356,8 @@ mod tests {
         assert_eq!(evt.rate_limit_info.overage_disabled_reason, None);
         assert!(evt.rate_limit_info.is_using_overage);
         assert!(evt.uuid.is_none());
+        assert_eq!(evt.session_id.as_str(), "abc");
+        assert!(!evt.session_id.is_uuid());
     }
 
     #[test]
@@ -355,6 +381,14 @@ mod tests {
         let evt = output.as_rate_limit_event().unwrap();
         assert_eq!(evt.rate_limit_info.status, RateLimitStatus::Allowed);
         assert_eq!(evt.rate_limit_info.resets_at, None);
+
+        let json = r#"{"type":"rate_limit_event","rate_limit_info":{"status":"allowed","resetsAt":1771390800.25,"isUsingOverage":false},"session_id":"abc"}"#;
+        let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+        let evt = output.as_rate_limit_event().unwrap();
+        assert_eq!(
+            evt.rate_limit_info.resets_at,
+            Some(Timestamp::Raw("1771390800.25".to_string()))
+        );
         assert_eq!(evt.rate_limit_info.rate_limit_type, None);
         assert!(!evt.rate_limit_info.is_using_overage);
     }
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use super::lenient_uuid::{deserialize_optional_lenient_uuid, LenientUuid};
+
 /// Result message for completed queries
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResultMessage {
@@ -26,7 +28,7 @@ pub struct ResultMessage {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<String>,
 
-    pub session_id: String,
+    pub session_id: LenientUuid,
     pub total_cost_usd: f64,
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -43,8 +45,12 @@ pub struct ResultMessage {
     #[serde(default)]
     pub errors: Vec<String>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub uuid: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_lenient_uuid"
+    )]
+    pub uuid: Option<LenientUuid>,
 
     /// HTTP status code when the result is an API error (e.g., 429, 500, 529)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -65,6 +71,11 @@ pub struct ResultMessage {
     /// Per-model cost breakdown, keyed by model name (e.g. `"claude-opus-4-8"`).
     #[serde(skip_serializing_if = "Option::is_none", rename = "modelUsage")]
     pub model_usage: Option<std::collections::BTreeMap<String, ModelUsageEntry>>,
+
+    /// The final answer as JSON, validated against the session's
+    /// `--json-schema`. Only present when a schema was configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_output: Option<Value>,
 }
 
 /// Usage and cost for a single model within a session, as found in
@@ -107,13 +118,233 @@ pub struct PermissionDenial {
     pub tool_use_id: String,
 }
 
+impl PermissionDenial {
+    /// Parse the blocked input as a typed [`ToolInput`](crate::tool_inputs::ToolInput).
+    pub fn typed_input(&self) -> Option<crate::tool_inputs::ToolInput> {
+        serde_json::from_value(self.tool_input.clone()).ok()
+    }
+}
+
+impl ResultMessage {
+    /// Whether any tool call was blocked by a permission denial during the query.
+    pub fn has_permission_denials(&self) -> bool {
+        !self.permission_denials.is_empty()
+    }
+
+    /// Names of the tools that were denied, deduplicated, in first-denied order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use claude_codes::ClaudeOutput;
+    ///
+    /// let json = r#"{"type":"result","subtype":"success","is_error":false,
+    ///     "duration_ms":10,"duration_api_ms":8,"num_turns":2,
+    ///     "session_id":"abc","total_cost_usd":0.01,
+    ///     "permission_denials":[
+    ///         {"tool_name":"Bash","tool_input":{"command":"rm -rf build"},"tool_use_id":"tu_1"},
+    ///         {"tool_name":"Write","tool_input":{"file_path":"/etc/hosts","content":""},"tool_use_id":"tu_2"},
+    ///         {"tool_name":"Bash","tool_input":{"command":"sudo ls"},"tool_use_id":"tu_3"}
+    ///     ]}"#;
+    /// let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+    /// let result = output.as_result().unwrap();
+    ///
+    /// assert_eq!(result.denied_tools(), vec!["Bash", "Write"]);
+    /// for denial in output.permission_denials() {
+    ///     println!("the agent wanted to run {} but was denied", denial.summary());
+    /// }
+    /// ```
+    pub fn denied_tools(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for denial in &self.permission_denials {
+            if !names.contains(&denial.tool_name.as_str()) {
+                names.push(&denial.tool_name);
+            }
+        }
+        names
+    }
+
+    /// Whether a call to `tool_name` was denied during the query.
+    pub fn was_denied(&self, tool_name: &str) -> bool {
+        self.permission_denials
+            .iter()
+            .any(|denial| denial.tool_name == tool_name)
+    }
+
+    /// Whether the query stopped because it hit a configured limit (turns or
+    /// budget) rather than finishing or failing.
+    pub fn is_truncated(&self) -> bool {
+        matches!(
+            self.subtype,
+            ResultSubtype::ErrorMaxTurns | ResultSubtype::ErrorMaxBudgetUsd
+        )
+    }
+
+    /// Whether the query failed because the API was unavailable (a 5xx
+    /// status, including 529 overloaded) rather than because of the request.
+    ///
+    /// Such a turn may well succeed if sent again; see [`crate::retry`].
+    pub fn is_transient_api_error(&self) -> bool {
+        if !self.is_error {
+            return false;
+        }
+        if let Some(status) = self.api_error_status {
+            return (500..600).contains(&status);
+        }
+        self.result.as_deref().is_some_and(|text| {
+            text.starts_with("API Error: 5")
+                || text.contains("overloaded_error")
+                || text.contains("Overloaded")
+        })
+    }
+
+    /// Why the query failed, or `None` if it succeeded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use claude_codes::{ClaudeOutput, ResultErrorKind};
+    ///
+    /// let json = r#"{"type":"result","subtype":"error_max_turns","is_error":true,
+    ///     "duration_ms":10,"duration_api_ms":8,"num_turns":5,
+    ///     "session_id":"abc","total_cost_usd":0.02}"#;
+    /// let ClaudeOutput::Result(result) = serde_json::from_str(json).unwrap() else {
+    ///     unreachable!()
+    /// };
+    ///
+    /// assert!(result.is_truncated());
+    /// assert_eq!(result.error_kind(), Some(ResultErrorKind::MaxTurns));
+    /// ```
+    pub fn error_kind(&self) -> Option<ResultErrorKind> {
+        match &self.subtype {
+            ResultSubtype::Success if self.is_error => Some(ResultErrorKind::Reported),
+            ResultSubtype::Success => None,
+            ResultSubtype::ErrorMaxTurns => Some(ResultErrorKind::MaxTurns),
+            ResultSubtype::ErrorMaxBudgetUsd => Some(ResultErrorKind::MaxBudget),
+            ResultSubtype::ErrorMaxStructuredOutputRetries => {
+                Some(ResultErrorKind::MaxStructuredOutputRetries)
+            }
+            ResultSubtype::ErrorDuringExecution => Some(ResultErrorKind::DuringExecution),
+            ResultSubtype::Other(s) => Some(ResultErrorKind::Other(s.clone())),
+        }
+    }
+
+    /// Deserialize [`structured_output`](Self::structured_output) into `T`.
+    ///
+    /// Fails if the query failed, if no structured output was reported (the
+    /// session was started without a JSON schema), or if it doesn't match `T`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use claude_codes::ClaudeOutput;
+    ///
+    /// let json = r#"{"type":"result","subtype":"success","is_error":false,
+    ///     "duration_ms":10,"duration_api_ms":8,"num_turns":1,"result":"{\"n\":4}",
+    ///     "session_id":"abc","total_cost_usd":0.01,
+    ///     "structured_output":{"n":4}}"#;
+    /// let result = serde_json::from_str::<ClaudeOutput>(json).unwrap();
+    ///
+    /// let value: serde_json::Value = result.as_result().unwrap().structured_output_as().unwrap();
+    /// assert_eq!(value["n"], 4);
+    /// ```
+    pub fn structured_output_as<T: serde::de::DeserializeOwned>(&self) -> crate::Result<T> {
+        if self.is_error {
+            return Err(crate::Error::Protocol(format!(
+                "query failed ({}): {}",
+                self.subtype,
+                self.errors.join("; ")
+            )));
+        }
+        let value = self
+            .structured_output
+            .as_ref()
+            .ok_or_else(|| crate::Error::MissingField("structured_output".to_string()))?;
+        Ok(T::deserialize(value)?)
+    }
+}
+
 /// Result subtypes
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum ResultSubtype {
+    /// The query ran to completion (check `is_error` for API-level failures).
     Success,
+    /// The query stopped after `--max-turns` turns.
     ErrorMaxTurns,
+    /// The query stopped after exceeding its USD budget.
+    ErrorMaxBudgetUsd,
+    /// Structured output failed validation too many times.
+    ErrorMaxStructuredOutputRetries,
+    /// The query failed while executing.
     ErrorDuringExecution,
+    /// A subtype not yet known to this version of the crate.
+    Other(String),
+}
+
+impl ResultSubtype {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Success => "success",
+            Self::ErrorMaxTurns => "error_max_turns",
+            Self::ErrorMaxBudgetUsd => "error_max_budget_usd",
+            Self::ErrorMaxStructuredOutputRetries => "error_max_structured_output_retries",
+            Self::ErrorDuringExecution => "error_during_execution",
+            Self::Other(s) => s.as_str(),
+        }
+    }
+}
+
+impl std::fmt::Display for ResultSubtype {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for ResultSubtype {
+    fn from(s: &str) -> Self {
+        match s {
+            "success" => Self::Success,
+            "error_max_turns" => Self::ErrorMaxTurns,
+            "error_max_budget_usd" => Self::ErrorMaxBudgetUsd,
+            "error_max_structured_output_retries" => Self::ErrorMaxStructuredOutputRetries,
+            "error_during_execution" => Self::ErrorDuringExecution,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for ResultSubtype {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ResultSubtype {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// Why a query failed, as reported by [`ResultMessage::error_kind`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ResultErrorKind {
+    /// Hit the `--max-turns` limit.
+    MaxTurns,
+    /// Hit the USD budget limit.
+    MaxBudget,
+    /// Structured output failed validation too many times.
+    MaxStructuredOutputRetries,
+    /// Failed during execution; see [`ResultMessage::errors`].
+    DuringExecution,
+    /// The subtype is `success` but `is_error` is set: the CLI finished the
+    /// query but reported an error (API error, authentication failure, ...)
+    /// in [`ResultMessage::result`].
+    Reported,
+    /// An error subtype not yet known to this version of the crate.
+    Other(String),
 }
 
 /// Usage information for the request
@@ -181,6 +412,10 @@ mod tests {
 
         let output: ClaudeOutput = serde_json::from_str(json).unwrap();
         assert!(!output.is_error());
+        assert_eq!(
+            output.as_result().unwrap().session_id,
+            LenientUuid::from("123")
+        );
     }
 
     #[test]
@@ -305,6 +540,7 @@ mod tests {
 
         assert!(reserialized.contains("Error 1"));
         assert!(reserialized.contains("Error 2"));
+        assert!(reserialized.contains(r#""session_id":"test-session""#));
     }
 
     #[test]
@@ -397,4 +633,83 @@ mod tests {
             panic!("Expected Result");
         }
     }
+
+    fn parse_result(subtype: &str, is_error: bool) -> ResultMessage {
+        let json = format!(
+            r#"{{"type":"result","subtype":"{}","is_error":{},"duration_ms":1,
+                "duration_api_ms":1,"num_turns":1,"session_id":"s","total_cost_usd":0.0}}"#,
+            subtype, is_error
+        );
+        match serde_json::from_str(&json).unwrap() {
+            ClaudeOutput::Result(result) => result,
+            other => panic!("expected Result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_result_subtype_known_and_unknown() {
+        assert_eq!(
+            parse_result("error_max_budget_usd", true).subtype,
+            ResultSubtype::ErrorMaxBudgetUsd
+        );
+
+        let result = parse_result("error_rate_limited", true);
+        assert_eq!(
+            result.subtype,
+            ResultSubtype::Other("error_rate_limited".to_string())
+        );
+        assert_eq!(
+            result.error_kind(),
+            Some(ResultErrorKind::Other("error_rate_limited".to_string()))
+        );
+        let reserialized = serde_json::to_value(&result).unwrap();
+        assert_eq!(reserialized["subtype"], "error_rate_limited");
+    }
+
+    #[test]
+    fn test_result_error_kind_and_truncation() {
+        let success = parse_result("success", false);
+        assert_eq!(success.error_kind(), None);
+        assert!(!success.is_truncated());
+
+        let reported = parse_result("success", true);
+        assert_eq!(reported.error_kind(), Some(ResultErrorKind::Reported));
+
+        assert!(parse_result("error_max_turns", true).is_truncated());
+        assert!(parse_result("error_max_budget_usd", true).is_truncated());
+        let failed = parse_result("error_during_execution", true);
+        assert!(!failed.is_truncated());
+        assert_eq!(failed.error_kind(), Some(ResultErrorKind::DuringExecution));
+    }
+
+    #[test]
+    fn test_permission_denial_helpers() {
+        let json = r#"{
+            "type": "result",
+            "subtype": "success",
+            "is_error": false,
+            "duration_ms": 1,
+            "duration_api_ms": 1,
+            "num_turns": 1,
+            "session_id": "s",
+            "total_cost_usd": 0.0,
+            "permission_denials": [
+                {"tool_name": "Bash", "tool_input": {"command": "rm -rf /"}, "tool_use_id": "tu_1"}
+            ]
+        }"#;
+        let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+        let result = output.as_result().unwrap();
+
+        assert!(result.has_permission_denials());
+        assert!(result.was_denied("Bash"));
+        assert!(!result.was_denied("Write"));
+        assert_eq!(output.permission_denials().len(), 1);
+
+        let denial = &result.permission_denials[0];
+        match denial.typed_input() {
+            Some(crate::tool_inputs::ToolInput::Bash(bash)) => assert_eq!(bash.command, "rm -rf /"),
+            other => panic!("expected Bash input, got {:?}", other),
+        }
+        assert_eq!(denial.summary(), "Bash(rm -rf /)");
+    }
 }
@@ -0,0 +1,229 @@
+//! Cleaning terminal output captured in tool results.
+//!
+//! Command output often carries ANSI color codes, cursor movement, and
+//! carriage-return progress bars meant for a terminal. [`clean_terminal_text`]
+//! strips the escape sequences and replays `\r` and backspace the way a
+//! terminal would, so only the text a user would finally see remains.
+//! Nothing is cleaned unless asked: use [`ToolResultBlock::clean_text`] for a
+//! cleaned copy, or [`ClaudeOutput::sanitize_tool_results`] to clean a
+//! message in place before it reaches a UI or log.
+
+use serde_json::Value;
+
+use super::claude_output::ClaudeOutput;
+use super::content_blocks::{ContentBlock, ToolResultBlock, ToolResultContent};
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+/// Strip ANSI escape sequences and control characters from `text`, applying
+/// carriage returns and backspaces as overwrites.
+///
+/// Newlines and tabs are kept; `\r\n` is treated as a newline.
+///
+/// # Example
+/// ```
+/// use claude_codes::io::clean_terminal_text;
+///
+/// let raw = "\x1b[32mok\x1b[0m\nDownloading  10%\rDownloading 100%\n";
+/// assert_eq!(clean_terminal_text(raw), "ok\nDownloading 100%\n");
+/// ```
+pub fn clean_terminal_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    // The current line being built, and the column writes go to.
+    let mut line: Vec<char> = Vec::new();
+    let mut col: usize = 0;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ESC => skip_escape(&mut chars),
+            // 8-bit CSI
+            '\u{9b}' => skip_csi(&mut chars),
+            '\n' => {
+                out.extend(line.drain(..));
+                out.push('\n');
+                col = 0;
+            }
+            '\r' => {
+                if chars.peek() != Some(&'\n') {
+                    col = 0;
+                }
+            }
+            '\x08' => col = col.saturating_sub(1),
+            '\t' => put(&mut line, &mut col, '\t'),
+            c if c.is_control() => {}
+            c => put(&mut line, &mut col, c),
+        }
+    }
+    out.extend(line);
+    out
+}
+
+/// Write `c` at `col`, overwriting what is there.
+fn put(line: &mut Vec<char>, col: &mut usize, c: char) {
+    if *col < line.len() {
+        line[*col] = c;
+    } else {
+        line.resize(*col, ' ');
+        line.push(c);
+    }
+    *col += 1;
+}
+
+/// Skip the rest of an escape sequence whose `ESC` was just consumed.
+fn skip_escape(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) {
+    match chars.next() {
+        Some('[') => skip_csi(chars),
+        // OSC, DCS, SOS, PM, APC: string terminated by BEL or ESC \
+        Some(']' | 'P' | 'X' | '^' | '_') => {
+            while let Some(c) = chars.next() {
+                if c == BEL {
+                    break;
+                }
+                if c == ESC {
+                    if chars.peek() == Some(&'\\') {
+                        chars.next();
+                    }
+                    break;
+                }
+            }
+        }
+        // Character set selection and similar take one more character
+        Some('(' | ')' | '*' | '+' | '-' | '.' | '/' | '#' | '%') => {
+            chars.next();
+        }
+        // Any other two-character sequence (ESC 7, ESC M, ...)
+        _ => {}
+    }
+}
+
+/// Skip CSI parameters and intermediates up to and including the final byte.
+fn skip_csi(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) {
+    for c in chars.by_ref() {
+        if ('\x40'..='\x7e').contains(&c) {
+            break;
+        }
+    }
+}
+
+impl ToolResultContent {
+    /// [`text`](Self::text) with terminal escapes and overwrites cleaned up
+    /// by [`clean_terminal_text`].
+    pub fn clean_text(&self) -> String {
+        clean_terminal_text(&self.text())
+    }
+
+    /// Clean the text in place: the string itself, or the `text` field of
+    /// each structured item. Only the preview of spilled content is cleaned.
+    pub fn sanitize(&mut self) {
+        match self {
+            ToolResultContent::Text(s) => *s = clean_terminal_text(s),
+            ToolResultContent::Spilled(large) => {
+                large.preview = clean_terminal_text(&large.preview)
+            }
+            ToolResultContent::Structured(items) => {
+                for item in items {
+                    if let Some(Value::String(s)) = item.get_mut("text") {
+                        *s = clean_terminal_text(s);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl ToolResultBlock {
+    /// The result's text with terminal escapes and overwrites cleaned up
+    /// (empty if there is no content).
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::{ToolResultBlock, ToolResultContent};
+    ///
+    /// let block = ToolResultBlock {
+    ///     tool_use_id: "toolu_1".to_string(),
+    ///     content: Some(ToolResultContent::Text("\x1b[1;31merror\x1b[0m: failed".to_string())),
+    ///     is_error: Some(true),
+    /// };
+    /// assert_eq!(block.clean_text(), "error: failed");
+    /// ```
+    pub fn clean_text(&self) -> String {
+        self.content
+            .as_ref()
+            .map(ToolResultContent::clean_text)
+            .unwrap_or_default()
+    }
+}
+
+impl ClaudeOutput {
+    /// Clean the text of every tool result in this message in place (see
+    /// [`clean_terminal_text`]). Other messages are left untouched.
+    pub fn sanitize_tool_results(&mut self) {
+        let ClaudeOutput::User(msg) = self else {
+            return;
+        };
+        for block in &mut msg.message.content {
+            if let ContentBlock::ToolResult(ToolResultBlock {
+                content: Some(content),
+                ..
+            }) = block
+            {
+                content.sanitize();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_escape_sequences() {
+        assert_eq!(
+            clean_terminal_text("\x1b[1;32mPASS\x1b[0m src/a.rs\x1b[K"),
+            "PASS src/a.rs"
+        );
+        assert_eq!(
+            clean_terminal_text("\x1b]0;title\x07a\x1b]8;;http://x\x1b\\link\x1b]8;;\x1b\\"),
+            "alink"
+        );
+        assert_eq!(clean_terminal_text("\x1b(Bplain\u{9b}2Jtext"), "plaintext");
+        assert_eq!(clean_terminal_text("a\x00b\x07c\td"), "abc\td");
+    }
+
+    #[test]
+    fn test_applies_carriage_returns_and_backspaces() {
+        assert_eq!(
+            clean_terminal_text("[#   ] 25%\r[####] 100%"),
+            "[####] 100%"
+        );
+        assert_eq!(clean_terminal_text("abcdef\rXY"), "XYcdef");
+        assert_eq!(
+            clean_terminal_text("line one\r\nline two\r\n"),
+            "line one\nline two\n"
+        );
+        assert_eq!(clean_terminal_text("abc\x08\x08X"), "aXc");
+        assert_eq!(clean_terminal_text("ünï\rc"), "cnï");
+    }
+
+    #[test]
+    fn test_sanitize_tool_results_in_place() {
+        let json = r#"{"type":"user","message":{"role":"user","content":[
+            {"type":"tool_result","tool_use_id":"toolu_1","content":"\u001b[31mred\u001b[0m"},
+            {"type":"tool_result","tool_use_id":"toolu_2","content":[{"type":"text","text":"50%\r100%"}]}
+        ]}}"#;
+        let mut output: ClaudeOutput = serde_json::from_str(json).unwrap();
+        output.sanitize_tool_results();
+
+        let ClaudeOutput::User(msg) = output else {
+            panic!("Expected User");
+        };
+        let texts: Vec<String> = msg
+            .tool_results()
+            .map(|r| r.content.as_ref().unwrap().text())
+            .collect();
+        assert_eq!(texts, vec!["red", "100%"]);
+    }
+}
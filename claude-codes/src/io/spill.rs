@@ -0,0 +1,226 @@
+//! Moving oversized tool results out of memory.
+//!
+//! A long session can accumulate many multi-megabyte command outputs. With a
+//! [`SpillPolicy`] installed (`set_spill_policy` on the clients, or
+//! [`ClaudeOutput::spill_large_tool_results`] by hand), any tool result whose
+//! content exceeds the threshold is written to a file and replaced in memory
+//! by [`ToolResultContent::Spilled`], a [`LargeContent`] handle holding the
+//! path, the size, and a short preview. [`LargeContent::load`] reads the
+//! original content back.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::claude_output::ClaudeOutput;
+use super::content_blocks::{ContentBlock, ToolResultBlock, ToolResultContent};
+
+/// Default number of characters kept in [`LargeContent::preview`].
+pub const DEFAULT_PREVIEW_CHARS: usize = 512;
+
+/// When and where to spill tool results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpillPolicy {
+    threshold: usize,
+    dir: PathBuf,
+    preview_chars: usize,
+}
+
+impl SpillPolicy {
+    /// Spill results larger than `threshold` bytes into a `claude-codes-spill`
+    /// directory under the system temp directory.
+    pub fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            dir: std::env::temp_dir().join("claude-codes-spill"),
+            preview_chars: DEFAULT_PREVIEW_CHARS,
+        }
+    }
+
+    /// Write spilled results into `dir` instead (created on first use).
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    /// Keep this many characters of each spilled result in memory.
+    pub fn preview_chars(mut self, chars: usize) -> Self {
+        self.preview_chars = chars;
+        self
+    }
+
+    /// The size in bytes above which results are spilled.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// The directory spilled results are written to.
+    pub fn spill_dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Handle to a tool result that was written to disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LargeContent {
+    /// File holding the full content.
+    pub path: PathBuf,
+    /// Size of the full content in bytes.
+    pub len: u64,
+    /// The first characters of the content's text.
+    pub preview: String,
+    /// Whether the content was structured; the file then holds its JSON
+    /// array rather than plain text.
+    #[serde(default)]
+    pub structured: bool,
+}
+
+impl LargeContent {
+    /// Read the full content back from disk.
+    pub fn load(&self) -> io::Result<ToolResultContent> {
+        let data = fs::read_to_string(&self.path)?;
+        if self.structured {
+            Ok(ToolResultContent::Structured(serde_json::from_str(&data)?))
+        } else {
+            Ok(ToolResultContent::Text(data))
+        }
+    }
+}
+
+impl ToolResultBlock {
+    /// Write this result's content to disk if it is larger than the policy's
+    /// threshold. Returns whether it was spilled.
+    pub fn spill_if_large(&mut self, policy: &SpillPolicy) -> io::Result<bool> {
+        let (data, structured) = match &self.content {
+            Some(ToolResultContent::Text(s)) if s.len() > policy.threshold => (s.clone(), false),
+            Some(ToolResultContent::Structured(items)) => {
+                let json = serde_json::to_string(items)?;
+                if json.len() <= policy.threshold {
+                    return Ok(false);
+                }
+                (json, true)
+            }
+            _ => return Ok(false),
+        };
+        let preview = self
+            .content
+            .as_ref()
+            .map(|c| c.text().chars().take(policy.preview_chars).collect())
+            .unwrap_or_default();
+
+        fs::create_dir_all(&policy.dir)?;
+        let name: String = self
+            .tool_use_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let path = policy.dir.join(format!(
+            "{}.{}",
+            name,
+            if structured { "json" } else { "txt" }
+        ));
+        fs::write(&path, &data)?;
+
+        self.content = Some(ToolResultContent::Spilled(LargeContent {
+            path,
+            len: data.len() as u64,
+            preview,
+            structured,
+        }));
+        Ok(true)
+    }
+}
+
+impl ClaudeOutput {
+    /// Spill every tool result in this message larger than the policy's
+    /// threshold to disk. Returns how many were spilled.
+    ///
+    /// # Example
+    /// ```
+    /// use claude_codes::io::{ClaudeOutput, SpillPolicy, ToolResultContent};
+    ///
+    /// let big = "x".repeat(10_000);
+    /// let json = format!(r#"{{"type":"user","message":{{"role":"user","content":[
+    ///     {{"type":"tool_result","tool_use_id":"toolu_doc","content":"{}"}}]}}}}"#, big);
+    /// let mut output: ClaudeOutput = serde_json::from_str(&json).unwrap();
+    ///
+    /// let policy = SpillPolicy::new(4096).preview_chars(10);
+    /// assert_eq!(output.spill_large_tool_results(&policy).unwrap(), 1);
+    ///
+    /// let result = output.tool_results().next().unwrap();
+    /// let Some(ToolResultContent::Spilled(handle)) = &result.content else { panic!() };
+    /// assert_eq!(handle.len, 10_000);
+    /// assert_eq!(handle.preview, "xxxxxxxxxx");
+    /// # std::fs::remove_file(&handle.path).ok();
+    /// ```
+    pub fn spill_large_tool_results(&mut self, policy: &SpillPolicy) -> io::Result<usize> {
+        let ClaudeOutput::User(msg) = self else {
+            return Ok(0);
+        };
+        let mut spilled = 0;
+        for block in &mut msg.message.content {
+            if let ContentBlock::ToolResult(result) = block {
+                if result.spill_if_large(policy)? {
+                    spilled += 1;
+                }
+            }
+        }
+        Ok(spilled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_spill_and_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("claude-codes-spill-{}", std::process::id()));
+        let policy = SpillPolicy::new(16).dir(&dir).preview_chars(4);
+
+        let mut small = ToolResultBlock {
+            tool_use_id: "toolu_small".to_string(),
+            content: Some(ToolResultContent::Text("short".to_string())),
+            is_error: None,
+        };
+        assert!(!small.spill_if_large(&policy).unwrap());
+
+        let text = "a long line of command output";
+        let mut block = ToolResultBlock {
+            tool_use_id: "toolu_text".to_string(),
+            content: Some(ToolResultContent::Text(text.to_string())),
+            is_error: None,
+        };
+        assert!(block.spill_if_large(&policy).unwrap());
+        let Some(ToolResultContent::Spilled(handle)) = &block.content else {
+            panic!("Expected Spilled");
+        };
+        assert_eq!(handle.preview, "a lo");
+        assert_eq!(handle.len, text.len() as u64);
+        assert_eq!(block.content.as_ref().unwrap().text(), "a lo");
+        assert!(matches!(handle.load().unwrap(), ToolResultContent::Text(t) if t == text));
+
+        let items = vec![json!({"type": "text", "text": "structured output here"})];
+        let mut block = ToolResultBlock {
+            tool_use_id: "toolu/../structured".to_string(),
+            content: Some(ToolResultContent::Structured(items.clone())),
+            is_error: None,
+        };
+        assert!(block.spill_if_large(&policy).unwrap());
+        let Some(ToolResultContent::Spilled(handle)) = &block.content else {
+            panic!("Expected Spilled");
+        };
+        assert_eq!(handle.path.parent(), Some(dir.as_path()));
+        assert!(matches!(handle.load().unwrap(), ToolResultContent::Structured(v) if v == items));
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
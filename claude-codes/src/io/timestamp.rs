@@ -0,0 +1,239 @@
+//! Typed wall-clock timestamps carried by CLI messages.
+
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// An ISO-8601 / RFC 3339 timestamp string from the CLI.
+///
+/// Parsed into a [`DateTime<FixedOffset>`] when possible, keeping the offset
+/// it was written with so it serializes back the same way. Values that do not
+/// parse are kept verbatim in [`Timestamp::Raw`] so a surprising format never
+/// fails the whole message.
+///
+/// # Example
+///
+/// ```
+/// use claude_codes::Timestamp;
+///
+/// let ts: Timestamp = serde_json::from_str(r#""2026-05-12T23:12:04.121Z""#).unwrap();
+/// assert_eq!(ts.as_datetime().unwrap().timestamp_millis(), 1778627524121);
+///
+/// let odd: Timestamp = serde_json::from_str(r#""yesterday""#).unwrap();
+/// assert_eq!(odd, Timestamp::Raw("yesterday".to_string()));
+/// assert!(odd.as_datetime().is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Timestamp {
+    /// A timestamp that parsed as RFC 3339, in its original offset.
+    Parsed(DateTime<FixedOffset>),
+    /// The original text of a timestamp that did not parse.
+    Raw(String),
+}
+
+impl Timestamp {
+    /// The parsed instant, or `None` for an unparseable value.
+    pub fn as_datetime(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Parsed(dt) => Some(dt.to_utc()),
+            Self::Raw(_) => None,
+        }
+    }
+
+    /// Whether the value parsed as RFC 3339.
+    pub fn is_parsed(&self) -> bool {
+        matches!(self, Self::Parsed(_))
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(dt: DateTime<Utc>) -> Self {
+        Self::Parsed(dt.fixed_offset())
+    }
+}
+
+impl From<DateTime<FixedOffset>> for Timestamp {
+    fn from(dt: DateTime<FixedOffset>) -> Self {
+        Self::Parsed(dt)
+    }
+}
+
+impl From<&str> for Timestamp {
+    fn from(s: &str) -> Self {
+        match DateTime::parse_from_rfc3339(s) {
+            Ok(dt) => Self::Parsed(dt),
+            Err(_) => Self::Raw(s.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parsed(dt) => {
+                let utc = dt.offset().local_minus_utc() == 0;
+                f.write_str(&dt.to_rfc3339_opts(SecondsFormat::AutoSi, utc))
+            }
+            Self::Raw(s) => f.write_str(s),
+        }
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// Parse an epoch number with `to_datetime`, keeping anything else (floats,
+/// out-of-range values, non-RFC 3339 strings) as [`Timestamp::Raw`].
+fn from_epoch(value: Value, to_datetime: fn(i64) -> Option<DateTime<Utc>>) -> Timestamp {
+    match value {
+        Value::Number(n) => match n.as_i64().and_then(to_datetime) {
+            Some(dt) => Timestamp::from(dt),
+            None => Timestamp::Raw(n.to_string()),
+        },
+        Value::String(s) => Timestamp::from(s.as_str()),
+        other => Timestamp::Raw(other.to_string()),
+    }
+}
+
+/// Write a parsed instant as an epoch number via `to_epoch`. Raw values are
+/// written back as numbers when they were numbers, and as strings otherwise.
+fn serialize_epoch<S: Serializer>(
+    ts: &Option<Timestamp>,
+    serializer: S,
+    to_epoch: fn(&DateTime<Utc>) -> i64,
+) -> Result<S::Ok, S::Error> {
+    match ts {
+        None => serializer.serialize_none(),
+        Some(Timestamp::Parsed(dt)) => serializer.serialize_i64(to_epoch(&dt.to_utc())),
+        Some(Timestamp::Raw(s)) => match s.parse::<serde_json::Number>() {
+            Ok(n) => n.serialize(serializer),
+            Err(_) => serializer.serialize_str(s),
+        },
+    }
+}
+
+/// `#[serde(with = "...")]` for an optional [`Timestamp`] sent as Unix seconds.
+pub(crate) mod epoch_seconds_option {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        ts: &Option<Timestamp>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serialize_epoch(ts, serializer, DateTime::timestamp)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Timestamp>, D::Error> {
+        let value = Option::<Value>::deserialize(deserializer)?;
+        Ok(value.map(|v| from_epoch(v, |secs| DateTime::from_timestamp(secs, 0))))
+    }
+}
+
+/// `#[serde(with = "...")]` for an optional [`Timestamp`] sent as epoch
+/// milliseconds.
+pub(crate) mod epoch_millis_option {
+    use super::*;
+
+    pub(crate) fn serialize<S: Serializer>(
+        ts: &Option<Timestamp>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serialize_epoch(ts, serializer, DateTime::timestamp_millis)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Timestamp>, D::Error> {
+        let value = Option::<Value>::deserialize(deserializer)?;
+        Ok(value.map(|v| from_epoch(v, DateTime::from_timestamp_millis)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trips_cli_format() {
+        let json = r#""2026-05-12T23:12:04.121Z""#;
+        let ts: Timestamp = serde_json::from_str(json).unwrap();
+        assert!(ts.is_parsed());
+        assert_eq!(serde_json::to_string(&ts).unwrap(), json);
+    }
+
+    #[test]
+    fn test_offset_is_kept() {
+        let json = r#""2026-05-12T16:12:04.121-07:00""#;
+        let ts: Timestamp = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&ts).unwrap(), json);
+        assert_eq!(
+            ts.as_datetime()
+                .unwrap()
+                .to_rfc3339_opts(SecondsFormat::Millis, true),
+            "2026-05-12T23:12:04.121Z"
+        );
+        assert_eq!(ts, Timestamp::from("2026-05-12T23:12:04.121Z"));
+    }
+
+    #[test]
+    fn test_unparseable_value_round_trips_verbatim() {
+        let ts: Timestamp = serde_json::from_str(r#""12 May 2026""#).unwrap();
+        assert_eq!(ts, Timestamp::Raw("12 May 2026".to_string()));
+        assert_eq!(serde_json::to_string(&ts).unwrap(), r#""12 May 2026""#);
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Epochs {
+        #[serde(default, with = "epoch_seconds_option")]
+        secs: Option<Timestamp>,
+        #[serde(default, with = "epoch_millis_option")]
+        millis: Option<Timestamp>,
+    }
+
+    #[test]
+    fn test_epoch_numbers_parse_and_round_trip() {
+        let json = r#"{"secs":1771390800,"millis":1778627524121}"#;
+        let epochs: Epochs = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            epochs.secs.as_ref().unwrap().to_string(),
+            "2026-02-18T05:00:00Z"
+        );
+        assert_eq!(
+            epochs.millis.as_ref().unwrap().to_string(),
+            "2026-05-12T23:12:04.121Z"
+        );
+        assert_eq!(serde_json::to_string(&epochs).unwrap(), json);
+    }
+
+    #[test]
+    fn test_unparseable_epochs_are_kept_raw() {
+        let json = r#"{"secs":1771390800.5,"millis":"soon"}"#;
+        let epochs: Epochs = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            epochs.secs,
+            Some(Timestamp::Raw("1771390800.5".to_string()))
+        );
+        assert_eq!(epochs.millis, Some(Timestamp::Raw("soon".to_string())));
+        assert_eq!(serde_json::to_string(&epochs).unwrap(), json);
+
+        let huge: Epochs = serde_json::from_str(r#"{"secs":9223372036854775807}"#).unwrap();
+        assert_eq!(
+            huge.secs,
+            Some(Timestamp::Raw("9223372036854775807".to_string()))
+        );
+        assert!(huge.millis.is_none());
+    }
+}
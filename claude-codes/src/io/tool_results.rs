@@ -0,0 +1,465 @@
+//! Typed `tool_use_result` payloads for built-in tools.
+//!
+//! When the CLI echoes a tool's result back as a user message, it attaches
+//! the tool's structured output as `tool_use_result` next to the text the
+//! model sees. These types parse that structured output, so consumers can
+//! read e.g. search hits and fetched URLs without scraping the result text.
+//! Use the matching [`UserMessage`] accessor, or
+//! [`UserMessage::tool_use_result_as`] with the type directly.
+
+use serde::{Deserialize, Serialize};
+
+use super::message_types::UserMessage;
+use crate::tool_inputs::{GrepOutputMode, NotebookCellType, NotebookEditMode};
+
+/// Output of the `WebSearch` tool.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebSearchResult {
+    /// The query that was searched.
+    pub query: String,
+    /// Search hits interleaved with the model's commentary on them.
+    pub results: Vec<WebSearchResultEntry>,
+    /// Time the search took, in seconds.
+    #[serde(rename = "durationSeconds")]
+    pub duration_seconds: f64,
+    /// Number of searches performed to answer the query.
+    #[serde(
+        rename = "searchCount",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub search_count: Option<u32>,
+}
+
+/// One entry of [`WebSearchResult::results`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WebSearchResultEntry {
+    /// Hits returned by one search.
+    Hits(WebSearchHits),
+    /// Text the model wrote between searches.
+    Commentary(String),
+}
+
+/// The hits returned by one server-side search.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebSearchHits {
+    /// ID of the server tool use that ran the search.
+    pub tool_use_id: String,
+    pub content: Vec<WebSearchHit>,
+}
+
+/// A single search hit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebSearchHit {
+    pub title: String,
+    pub url: String,
+}
+
+impl WebSearchResult {
+    /// Every hit across all searches, in order — the citable sources.
+    pub fn hits(&self) -> impl Iterator<Item = &WebSearchHit> {
+        self.results.iter().flat_map(|entry| match entry {
+            WebSearchResultEntry::Hits(hits) => hits.content.as_slice(),
+            WebSearchResultEntry::Commentary(_) => &[],
+        })
+    }
+
+    /// The model's commentary entries, in order.
+    pub fn commentary(&self) -> impl Iterator<Item = &str> {
+        self.results.iter().filter_map(|entry| match entry {
+            WebSearchResultEntry::Commentary(text) => Some(text.as_str()),
+            WebSearchResultEntry::Hits(_) => None,
+        })
+    }
+}
+
+/// Output of the `WebFetch` tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebFetchResult {
+    /// The URL that was fetched (after redirects).
+    pub url: String,
+    /// HTTP status code.
+    pub code: u16,
+    /// HTTP status text (e.g. `"OK"`).
+    pub code_text: String,
+    /// Size of the fetched content in bytes.
+    pub bytes: u64,
+    /// The answer to the tool's prompt, produced from the fetched content.
+    pub result: String,
+    /// Time taken to fetch and process the content, in milliseconds.
+    pub duration_ms: u64,
+}
+
+impl WebFetchResult {
+    /// Whether the fetch got a 2xx response.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.code)
+    }
+}
+
+/// Output of the `Grep` tool.
+///
+/// Which fields are filled depends on [`mode`](Self::mode): `filenames` for
+/// `files_with_matches`, `content` for `content` and `count`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrepResult {
+    pub mode: GrepOutputMode,
+    /// Files listed (`files_with_matches`) or counted (`count`).
+    pub num_files: u64,
+    /// Matching file paths, newest first (`files_with_matches` mode).
+    pub filenames: Vec<String>,
+    /// Raw ripgrep-style output lines (`content` and `count` modes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Lines in `content` after the head limit was applied.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_lines: Option<u64>,
+    /// Total matches across files (`count` mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_matches: Option<u64>,
+    /// Matching files before the head limit (`files_with_matches` mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_files: Option<u64>,
+    /// Output lines before the head limit (`content` mode).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_lines: Option<u64>,
+    /// The head limit, when it cut the output short.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applied_limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub applied_offset: Option<u64>,
+}
+
+/// One line of `content`-mode [`GrepResult`] output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepMatch {
+    /// File the line is from; `None` when a single file was searched.
+    pub path: Option<String>,
+    /// 1-based line number, if line numbers were shown.
+    pub line: Option<u32>,
+    /// The line's text (or just the matched part with `-o`).
+    pub text: String,
+    /// Whether this is a context line around a match rather than a match.
+    pub is_context: bool,
+}
+
+impl GrepResult {
+    /// Parse `content`-mode output into one entry per line, skipping the
+    /// `--` separators between context groups.
+    ///
+    /// Lines are split as ripgrep prints them: `path:line:text` for matches
+    /// and `path-line-text` for context. When every line starts with a line
+    /// number, the search was of a single file and `path` is `None`. Paths
+    /// that themselves contain `:<digits>:` can be split in the wrong place.
+    /// Returns nothing for other modes.
+    pub fn matches(&self) -> Vec<GrepMatch> {
+        let content = match (&self.mode, &self.content) {
+            (GrepOutputMode::Content, Some(content)) => content,
+            _ => return Vec::new(),
+        };
+        let lines: Vec<&str> = content.lines().filter(|l| *l != "--").collect();
+        let single_file =
+            !lines.is_empty() && lines.iter().all(|l| split_numbered(l, true).is_some());
+        lines
+            .into_iter()
+            .map(|line| parse_grep_line(line, single_file))
+            .collect()
+    }
+
+    /// Parse `count`-mode output into `(path, matches)` pairs.
+    ///
+    /// Returns nothing for other modes.
+    pub fn counts(&self) -> Vec<(String, u64)> {
+        let content = match (&self.mode, &self.content) {
+            (GrepOutputMode::Count, Some(content)) => content,
+            _ => return Vec::new(),
+        };
+        content
+            .lines()
+            .filter_map(|line| {
+                let (path, count) = line.rsplit_once(':')?;
+                Some((path.to_string(), count.parse().ok()?))
+            })
+            .collect()
+    }
+}
+
+/// Find `<digits><sep>` at the start of `line` (if `leading`) or else the
+/// first `<sep><digits><sep>`, trying `:` before `-`. Returns where the path
+/// prefix ends, the number, the separator, and where the text starts.
+fn split_numbered(line: &str, leading: bool) -> Option<(usize, u32, char, usize)> {
+    const SEPS: [char; 2] = [':', '-'];
+    let bytes = line.as_bytes();
+    let number_at = |start: usize| {
+        let digits = bytes[start..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        let sep = *bytes.get(start + digits)? as char;
+        if digits == 0 || !SEPS.contains(&sep) {
+            return None;
+        }
+        let number = line[start..start + digits].parse().ok()?;
+        Some((number, sep, start + digits + 1))
+    };
+    if leading {
+        return number_at(0).map(|(number, sep, text)| (0, number, sep, text));
+    }
+    for sep in SEPS {
+        for (at, _) in line.match_indices(sep) {
+            if let Some((number, s, text)) = number_at(at + 1) {
+                if s == sep {
+                    return Some((at, number, sep, text));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn parse_grep_line(line: &str, single_file: bool) -> GrepMatch {
+    if let Some((at, number, sep, text_start)) = split_numbered(line, single_file) {
+        return GrepMatch {
+            path: (at > 0).then(|| line[..at].to_string()),
+            line: Some(number),
+            text: line[text_start..].to_string(),
+            is_context: sep == '-',
+        };
+    }
+    match line.split_once(':').filter(|_| !single_file) {
+        Some((path, text)) => GrepMatch {
+            path: Some(path.to_string()),
+            line: None,
+            text: text.to_string(),
+            is_context: false,
+        },
+        None => GrepMatch {
+            path: None,
+            line: None,
+            text: line.to_string(),
+            is_context: false,
+        },
+    }
+}
+
+/// Output of the `Glob` tool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobResult {
+    /// Matching file paths, after truncation.
+    pub filenames: Vec<String>,
+    /// Number of paths in `filenames`.
+    pub num_files: u64,
+    /// Whether more files matched than were returned.
+    pub truncated: bool,
+    /// Time the search took, in milliseconds.
+    pub duration_ms: u64,
+    /// Matching files before truncation; a lower bound unless
+    /// `count_is_complete` is `true`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_matches: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count_is_complete: Option<bool>,
+}
+
+/// Output of the `NotebookEdit` tool.
+///
+/// `old_source` and `new_source` hold just the edited cell, so a cell-level
+/// diff needs no re-read of the notebook; `original_file` and `updated_file`
+/// hold the whole `.ipynb` JSON before and after the edit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotebookEditResult {
+    pub notebook_path: String,
+    /// The cell that was edited; for inserts, the new cell's ID.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell_id: Option<String>,
+    pub cell_type: NotebookCellType,
+    pub edit_mode: NotebookEditMode,
+    /// The cell's source before the edit (`replace` and `delete` only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_source: Option<String>,
+    /// The cell's source after the edit.
+    pub new_source: String,
+    /// Notebook language, e.g. `"python"`.
+    pub language: String,
+    /// Set when the edit failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub original_file: String,
+    #[serde(default)]
+    pub updated_file: String,
+}
+
+impl UserMessage {
+    /// Parse the `tool_use_result` as a `WebSearch` result.
+    ///
+    /// Returns `None` when the field is absent or has a different shape.
+    pub fn web_search_result(&self) -> Option<WebSearchResult> {
+        self.tool_use_result_as().and_then(Result::ok)
+    }
+
+    /// Parse the `tool_use_result` as a `WebFetch` result.
+    ///
+    /// Returns `None` when the field is absent or has a different shape.
+    pub fn web_fetch_result(&self) -> Option<WebFetchResult> {
+        self.tool_use_result_as().and_then(Result::ok)
+    }
+
+    /// Parse the `tool_use_result` as a `Grep` result.
+    ///
+    /// Returns `None` when the field is absent or has a different shape.
+    pub fn grep_result(&self) -> Option<GrepResult> {
+        self.tool_use_result_as().and_then(Result::ok)
+    }
+
+    /// Parse the `tool_use_result` as a `Glob` result.
+    ///
+    /// Returns `None` when the field is absent or has a different shape.
+    pub fn glob_result(&self) -> Option<GlobResult> {
+        self.tool_use_result_as().and_then(Result::ok)
+    }
+
+    /// Parse the `tool_use_result` as a `NotebookEdit` result.
+    ///
+    /// Returns `None` when the field is absent or has a different shape.
+    pub fn notebook_edit_result(&self) -> Option<NotebookEditResult> {
+        self.tool_use_result_as().and_then(Result::ok)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::ClaudeOutput;
+
+    fn user_with_result(tool_use_result: &str) -> UserMessage {
+        let json = format!(
+            r#"{{"type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"toolu_1","content":"..."}}]}},"session_id":"4e3453f9-129a-4da9-bc25-a287453d58d9","tool_use_result":{}}}"#,
+            tool_use_result
+        );
+        match ClaudeOutput::parse_json(&json).unwrap() {
+            ClaudeOutput::User(msg) => msg,
+            other => panic!("Expected User, got {}", other.message_type()),
+        }
+    }
+
+    #[test]
+    fn test_web_search_result() {
+        let msg = user_with_result(
+            r#"{"query":"rust serde untagged","results":[
+                "Searching for serde docs.",
+                {"tool_use_id":"srvtoolu_01","content":[
+                    {"title":"Enum representations · Serde","url":"https://serde.rs/enum-representations.html"},
+                    {"title":"serde - Rust","url":"https://docs.rs/serde"}
+                ]},
+                {"tool_use_id":"srvtoolu_02","content":[]}
+            ],"durationSeconds":2.41,"searchCount":2}"#,
+        );
+        let search = msg.web_search_result().unwrap();
+        assert_eq!(search.query, "rust serde untagged");
+        assert_eq!(search.search_count, Some(2));
+        let urls: Vec<&str> = search.hits().map(|h| h.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://serde.rs/enum-representations.html",
+                "https://docs.rs/serde"
+            ]
+        );
+        assert_eq!(
+            search.commentary().collect::<Vec<_>>(),
+            vec!["Searching for serde docs."]
+        );
+        assert!(msg.web_fetch_result().is_none());
+    }
+
+    #[test]
+    fn test_web_fetch_result() {
+        let msg = user_with_result(
+            r#"{"bytes":48213,"code":200,"codeText":"OK","result":"The page documents four enum representations.","durationMs":3120,"url":"https://serde.rs/enum-representations.html"}"#,
+        );
+        let fetch = msg.web_fetch_result().unwrap();
+        assert!(fetch.is_success());
+        assert_eq!(fetch.bytes, 48213);
+        assert_eq!(fetch.url, "https://serde.rs/enum-representations.html");
+        assert!(msg.web_search_result().is_none());
+    }
+
+    #[test]
+    fn test_grep_content_matches() {
+        let msg = user_with_result(
+            r#"{"mode":"content","numFiles":0,"filenames":[],"content":"src/lib.rs:12:pub mod cli;\nsrc/lib.rs-13-pub mod config;\n--\n2024-notes.md:3:cli: done\nsrc/main.rs:fn main() {}","numLines":5,"totalLines":5}"#,
+        );
+        let grep = msg.grep_result().unwrap();
+        assert!(msg.glob_result().is_none());
+        let m = grep.matches();
+        assert_eq!(m.len(), 4);
+        assert_eq!(
+            m[0],
+            GrepMatch {
+                path: Some("src/lib.rs".to_string()),
+                line: Some(12),
+                text: "pub mod cli;".to_string(),
+                is_context: false,
+            }
+        );
+        assert!(m[1].is_context);
+        assert_eq!(m[1].line, Some(13));
+        assert_eq!(m[2].path.as_deref(), Some("2024-notes.md"));
+        assert_eq!(m[2].text, "cli: done");
+        assert_eq!(
+            (m[3].path.as_deref(), m[3].line),
+            (Some("src/main.rs"), None)
+        );
+
+        // Searching one file prints no path.
+        let single = GrepResult {
+            content: Some("7:let x = 1;\n8-let y = 2;".to_string()),
+            ..grep
+        };
+        let m = single.matches();
+        assert_eq!((m[0].path.as_deref(), m[0].line), (None, Some(7)));
+        assert_eq!(m[0].text, "let x = 1;");
+        assert!(m[1].is_context);
+        assert!(single.counts().is_empty());
+    }
+
+    #[test]
+    fn test_grep_counts_and_glob() {
+        let msg = user_with_result(
+            r#"{"mode":"count","numFiles":2,"filenames":[],"content":"src/a.rs:3\nsrc/b.rs:1","numMatches":4}"#,
+        );
+        let grep = msg.grep_result().unwrap();
+        assert_eq!(
+            grep.counts(),
+            vec![("src/a.rs".to_string(), 3), ("src/b.rs".to_string(), 1)]
+        );
+        assert!(grep.matches().is_empty());
+
+        let msg = user_with_result(
+            r#"{"durationMs":14,"numFiles":2,"filenames":["src/a.rs","src/b.rs"],"truncated":false}"#,
+        );
+        let glob = msg.glob_result().unwrap();
+        assert_eq!(glob.filenames, vec!["src/a.rs", "src/b.rs"]);
+        assert!(!glob.truncated);
+        assert!(msg.grep_result().is_none());
+    }
+
+    #[test]
+    fn test_notebook_edit_result() {
+        let msg = user_with_result(
+            r#"{"new_source":"df.describe()","old_source":"df.head()","cell_id":"c3","cell_type":"code","language":"python","edit_mode":"replace","notebook_path":"/work/analysis.ipynb","original_file":"{}","updated_file":"{}"}"#,
+        );
+        let nb = msg.notebook_edit_result().unwrap();
+        assert_eq!(nb.edit_mode, NotebookEditMode::Replace);
+        assert_eq!(nb.cell_type, NotebookCellType::Code);
+        assert_eq!(nb.old_source.as_deref(), Some("df.head()"));
+        assert_eq!(nb.cell_id.as_deref(), Some("c3"));
+        assert!(nb.error.is_none());
+        assert!(msg.glob_result().is_none());
+    }
+}
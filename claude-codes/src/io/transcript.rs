@@ -0,0 +1,518 @@
+//! Session transcript entries.
+//!
+//! The CLI records every session as a JSON Lines transcript under
+//! `~/.claude/projects/`. Besides the user and assistant messages of the
+//! live stream, a transcript holds records the stream never emits:
+//! `summary` entries naming a conversation by its last message, and
+//! sidechain messages from the subagents the `Task` tool started. Each
+//! message record carries its own `uuid` and its parent's `parentUuid`, so
+//! the session forms a tree.
+//!
+//! [`TranscriptEntry`] types one line; [`Transcript`] holds a whole file and
+//! answers tree questions: the [`children`](Transcript::children) of a
+//! record, the [`thread`](Transcript::thread) leading to it, and for a
+//! sidechain the [`origin`](Transcript::sidechain_origin) in the main
+//! conversation that started it.
+//!
+//! ```
+//! use claude_codes::{Transcript, TranscriptEntry};
+//!
+//! let jsonl = r#"{"type":"summary","summary":"List the files","leafUuid":"9b2f7c1e-3d4a-4e5b-8c6d-7e8f9a0b1c2d"}
+//! {"type":"user","uuid":"1a2b3c4d-5e6f-4a8b-9c0d-1e2f3a4b5c6d","parentUuid":null,"isSidechain":false,"sessionId":"2f0c6e7a-5d1b-4a8e-9c3f-0b7d2e4a6c81","message":{"role":"user","content":"What's in this repo?"}}
+//! {"type":"assistant","uuid":"9b2f7c1e-3d4a-4e5b-8c6d-7e8f9a0b1c2d","parentUuid":"1a2b3c4d-5e6f-4a8b-9c0d-1e2f3a4b5c6d","isSidechain":false,"sessionId":"2f0c6e7a-5d1b-4a8e-9c3f-0b7d2e4a6c81","message":{"id":"msg_1","role":"assistant","model":"claude-sonnet-4","content":[{"type":"text","text":"A Rust workspace."}]}}"#;
+//! let transcript = Transcript::parse(jsonl);
+//!
+//! let root = transcript.roots().next().unwrap();
+//! let reply = transcript.children(root.uuid().unwrap()).next().unwrap();
+//! assert!(matches!(reply, TranscriptEntry::Assistant(_)));
+//! assert_eq!(transcript.summary_for(reply.uuid().unwrap()), Some("List the files"));
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+use std::path::Path;
+use uuid::Uuid;
+
+use super::checkpoint::FileHistorySnapshot;
+use super::claude_output::take_tag;
+use super::message_types::{deserialize_optional_uuid, AssistantMessageContent, MessageContent};
+use super::timestamp::Timestamp;
+
+/// One line of a session transcript.
+#[derive(Debug, Clone)]
+pub enum TranscriptEntry {
+    /// A conversation title, attached to the message it summarizes up to.
+    Summary(TranscriptSummary),
+    /// A user prompt or tool result.
+    User(Box<TranscriptRecord<MessageContent>>),
+    /// An assistant response.
+    Assistant(Box<TranscriptRecord<AssistantMessageContent>>),
+    /// The file backups taken for one checkpoint.
+    FileHistorySnapshot(FileHistorySnapshot),
+    /// Any other entry, such as `system` records, as raw JSON.
+    Unknown(Value),
+}
+
+impl<'de> Deserialize<'de> for TranscriptEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = Value::deserialize(deserializer)?;
+        if value.get("type").and_then(Value::as_str) == Some("file-history-snapshot") {
+            return serde_json::from_value(value)
+                .map(TranscriptEntry::FileHistorySnapshot)
+                .map_err(serde::de::Error::custom);
+        }
+        let tag = take_tag(&mut value, "type")?;
+        let parsed = match tag.as_str() {
+            "summary" => serde_json::from_value(value).map(TranscriptEntry::Summary),
+            "user" => serde_json::from_value(value).map(TranscriptEntry::User),
+            "assistant" => serde_json::from_value(value).map(TranscriptEntry::Assistant),
+            _ => {
+                value["type"] = Value::String(tag);
+                Ok(TranscriptEntry::Unknown(value))
+            }
+        };
+        parsed.map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for TranscriptEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (tag, value) = match self {
+            TranscriptEntry::Summary(summary) => ("summary", serde_json::to_value(summary)),
+            TranscriptEntry::User(record) => ("user", serde_json::to_value(record)),
+            TranscriptEntry::Assistant(record) => ("assistant", serde_json::to_value(record)),
+            TranscriptEntry::FileHistorySnapshot(snapshot) => {
+                return snapshot.serialize(serializer)
+            }
+            TranscriptEntry::Unknown(value) => return value.serialize(serializer),
+        };
+        let mut value = value.map_err(serde::ser::Error::custom)?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.insert("type".to_string(), Value::String(tag.to_string()));
+        }
+        value.serialize(serializer)
+    }
+}
+
+impl TranscriptEntry {
+    /// The entry's own ID. Summaries and snapshots have none.
+    pub fn uuid(&self) -> Option<Uuid> {
+        match self {
+            TranscriptEntry::User(record) => Some(record.uuid),
+            TranscriptEntry::Assistant(record) => Some(record.uuid),
+            TranscriptEntry::Unknown(value) => raw_uuid(value, "uuid"),
+            TranscriptEntry::Summary(_) | TranscriptEntry::FileHistorySnapshot(_) => None,
+        }
+    }
+
+    /// The ID of the entry this one follows, `None` at the start of a chain.
+    pub fn parent_uuid(&self) -> Option<Uuid> {
+        match self {
+            TranscriptEntry::User(record) => record.parent_uuid,
+            TranscriptEntry::Assistant(record) => record.parent_uuid,
+            TranscriptEntry::Unknown(value) => raw_uuid(value, "parentUuid"),
+            TranscriptEntry::Summary(_) | TranscriptEntry::FileHistorySnapshot(_) => None,
+        }
+    }
+
+    /// Whether the entry belongs to a subagent's conversation rather than
+    /// the main one.
+    pub fn is_sidechain(&self) -> bool {
+        match self {
+            TranscriptEntry::User(record) => record.is_sidechain,
+            TranscriptEntry::Assistant(record) => record.is_sidechain,
+            TranscriptEntry::Unknown(value) => value
+                .get("isSidechain")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            TranscriptEntry::Summary(_) | TranscriptEntry::FileHistorySnapshot(_) => false,
+        }
+    }
+
+    /// The subagent a sidechain entry came from, when the CLI recorded it.
+    pub fn agent_id(&self) -> Option<&str> {
+        match self {
+            TranscriptEntry::User(record) => record.agent_id.as_deref(),
+            TranscriptEntry::Assistant(record) => record.agent_id.as_deref(),
+            TranscriptEntry::Unknown(value) => value.get("agentId").and_then(Value::as_str),
+            TranscriptEntry::Summary(_) | TranscriptEntry::FileHistorySnapshot(_) => None,
+        }
+    }
+
+    pub fn as_summary(&self) -> Option<&TranscriptSummary> {
+        match self {
+            TranscriptEntry::Summary(summary) => Some(summary),
+            _ => None,
+        }
+    }
+}
+
+fn raw_uuid(value: &Value, key: &str) -> Option<Uuid> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .and_then(|s| Uuid::parse_str(s).ok())
+}
+
+/// A `summary` entry: the title the CLI gave the conversation ending at
+/// `leaf_uuid`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptSummary {
+    pub summary: String,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_uuid"
+    )]
+    pub leaf_uuid: Option<Uuid>,
+}
+
+/// A message as the transcript records it, with its place in the tree.
+///
+/// `M` is [`MessageContent`] for user entries and
+/// [`AssistantMessageContent`] for assistant entries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptRecord<M> {
+    pub uuid: Uuid,
+    /// The entry this one follows; `None` for the first message of the
+    /// session or of a sidechain.
+    #[serde(default, deserialize_with = "deserialize_optional_uuid")]
+    pub parent_uuid: Option<Uuid>,
+    /// The message this one logically follows when `parent_uuid` was reset,
+    /// e.g. after the conversation was compacted.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_uuid"
+    )]
+    pub logical_parent_uuid: Option<Uuid>,
+    /// Whether the message belongs to a subagent's conversation.
+    #[serde(default)]
+    pub is_sidechain: bool,
+    /// The subagent that produced a sidechain message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_uuid"
+    )]
+    pub session_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<Timestamp>,
+    /// Working directory of the session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+    /// CLI version that wrote the entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Anthropic API request ID, on assistant entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Structured tool result data, on user entries carrying a tool result.
+    /// For a `Task` result it holds the subagent's `agentId`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_use_result: Option<Value>,
+    pub message: M,
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+/// A parsed session transcript, indexed by entry ID.
+///
+/// Subagent conversations may be written to their own files
+/// (`agent-<id>.jsonl` next to the session's transcript); [`merge`]
+/// them in to see the sidechains alongside the main conversation.
+///
+/// [`merge`]: Transcript::merge
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    entries: Vec<TranscriptEntry>,
+    by_uuid: HashMap<Uuid, usize>,
+}
+
+impl Transcript {
+    /// Build a transcript from entries in file order.
+    pub fn from_entries(entries: impl IntoIterator<Item = TranscriptEntry>) -> Self {
+        let mut transcript = Self::default();
+        transcript.extend(entries);
+        transcript
+    }
+
+    /// Parse JSON Lines text. Blank lines and lines that don't parse as an
+    /// entry are skipped.
+    pub fn parse(jsonl: &str) -> Self {
+        Self::from_entries(
+            jsonl
+                .lines()
+                .filter_map(|line| serde_json::from_str(line.trim()).ok()),
+        )
+    }
+
+    /// Read the transcript at `path`, skipping lines that don't parse.
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Self> {
+        let reader = io::BufReader::new(std::fs::File::open(path)?);
+        let mut entries = Vec::new();
+        for line in reader.lines() {
+            if let Ok(entry) = serde_json::from_str(line?.trim()) {
+                entries.push(entry);
+            }
+        }
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Add the entries of `other`, e.g. a subagent's transcript file.
+    pub fn merge(&mut self, other: Transcript) {
+        self.extend(other.entries);
+    }
+
+    /// Every entry, in file order.
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+
+    /// The entry with ID `uuid`.
+    pub fn get(&self, uuid: Uuid) -> Option<&TranscriptEntry> {
+        self.by_uuid.get(&uuid).map(|&i| &self.entries[i])
+    }
+
+    /// The entry `uuid` follows.
+    pub fn parent(&self, uuid: Uuid) -> Option<&TranscriptEntry> {
+        self.get(uuid)?.parent_uuid().and_then(|p| self.get(p))
+    }
+
+    /// The entries that follow `uuid`, in file order. More than one means
+    /// the conversation branched there, e.g. because a prompt was edited.
+    pub fn children(&self, uuid: Uuid) -> impl Iterator<Item = &TranscriptEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.uuid().is_some() && entry.parent_uuid() == Some(uuid))
+    }
+
+    /// Entries starting the main conversation: those without a parent (or
+    /// whose parent isn't in the transcript) that aren't sidechains.
+    pub fn roots(&self) -> impl Iterator<Item = &TranscriptEntry> {
+        self.starts().filter(|entry| !entry.is_sidechain())
+    }
+
+    /// Entries starting a subagent conversation.
+    pub fn sidechain_roots(&self) -> impl Iterator<Item = &TranscriptEntry> {
+        self.starts().filter(|entry| entry.is_sidechain())
+    }
+
+    fn starts(&self) -> impl Iterator<Item = &TranscriptEntry> {
+        self.entries.iter().filter(|entry| {
+            entry.uuid().is_some()
+                && entry
+                    .parent_uuid()
+                    .is_none_or(|parent| !self.by_uuid.contains_key(&parent))
+        })
+    }
+
+    /// The entries from the start of `uuid`'s chain down to `uuid` itself.
+    /// Empty if `uuid` isn't in the transcript.
+    pub fn thread(&self, uuid: Uuid) -> Vec<&TranscriptEntry> {
+        let mut thread = Vec::new();
+        let mut next = self.get(uuid);
+        while let Some(entry) = next {
+            // Guard against a malformed transcript whose parents loop.
+            if thread.len() > self.entries.len() {
+                break;
+            }
+            thread.push(entry);
+            next = entry.parent_uuid().and_then(|p| self.get(p));
+        }
+        thread.reverse();
+        thread
+    }
+
+    /// Every `summary` entry, in file order.
+    pub fn summaries(&self) -> impl Iterator<Item = &TranscriptSummary> {
+        self.entries.iter().filter_map(TranscriptEntry::as_summary)
+    }
+
+    /// The most recent summary whose leaf is `uuid`.
+    pub fn summary_for(&self, uuid: Uuid) -> Option<&str> {
+        self.summaries()
+            .filter(|summary| summary.leaf_uuid == Some(uuid))
+            .last()
+            .map(|summary| summary.summary.as_str())
+    }
+
+    /// The main-conversation entry that started the subagent `sidechain`
+    /// belongs to: the `Task` tool result recording the same `agentId`.
+    ///
+    /// `None` if `sidechain` isn't a sidechain entry, carries no agent ID,
+    /// or the result isn't in the transcript.
+    pub fn sidechain_origin(&self, sidechain: &TranscriptEntry) -> Option<&TranscriptEntry> {
+        if !sidechain.is_sidechain() {
+            return None;
+        }
+        let agent_id = sidechain.agent_id()?;
+        self.entries.iter().find(|entry| match entry {
+            TranscriptEntry::User(record) if !record.is_sidechain => {
+                record
+                    .tool_use_result
+                    .as_ref()
+                    .and_then(|result| result.get("agentId"))
+                    .and_then(Value::as_str)
+                    == Some(agent_id)
+            }
+            _ => false,
+        })
+    }
+}
+
+impl Extend<TranscriptEntry> for Transcript {
+    fn extend<I: IntoIterator<Item = TranscriptEntry>>(&mut self, entries: I) {
+        for entry in entries {
+            if let Some(uuid) = entry.uuid() {
+                self.by_uuid.insert(uuid, self.entries.len());
+            }
+            self.entries.push(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::ContentBlock;
+
+    const SESSION: &str = "2f0c6e7a-5d1b-4a8e-9c3f-0b7d2e4a6c81";
+
+    fn user(uuid: &str, parent: Option<&str>, extra: Value) -> String {
+        let mut entry = serde_json::json!({
+            "type": "user", "uuid": uuid, "parentUuid": parent, "isSidechain": false,
+            "sessionId": SESSION, "message": {"role": "user", "content": "prompt"}
+        });
+        entry
+            .as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        entry.to_string()
+    }
+
+    fn assistant(uuid: &str, parent: &str, sidechain: bool) -> String {
+        serde_json::json!({
+            "type": "assistant", "uuid": uuid, "parentUuid": parent, "isSidechain": sidechain,
+            "agentId": if sidechain { Some("a1b2") } else { None },
+            "sessionId": SESSION, "requestId": "req_1",
+            "message": {"id": "msg_1", "role": "assistant", "model": "claude-sonnet-4",
+                "content": [{"type": "text", "text": "reply"}]}
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_tree_with_sidechain() {
+        let prompt = "00000000-0000-4000-8000-000000000001";
+        let task_call = "00000000-0000-4000-8000-000000000002";
+        let task_result = "00000000-0000-4000-8000-000000000003";
+        let sub_prompt = "00000000-0000-4000-8000-000000000004";
+        let sub_reply = "00000000-0000-4000-8000-000000000005";
+        let main = [
+            r#"{"type":"summary","summary":"Explore the repo","leafUuid":"00000000-0000-4000-8000-000000000003"}"#.to_string(),
+            user(prompt, None, serde_json::json!({"cwd": "/repo", "gitBranch": "main"})),
+            assistant(task_call, prompt, false),
+            r#"{"type":"system","subtype":"informational","uuid":"00000000-0000-4000-8000-000000000009","parentUuid":"00000000-0000-4000-8000-000000000002","content":"note"}"#.to_string(),
+            user(
+                task_result,
+                Some(task_call),
+                serde_json::json!({"toolUseResult": {"agentId": "a1b2", "status": "completed"}}),
+            ),
+            "not json".to_string(),
+        ]
+        .join("\n");
+        let side = [
+            user(
+                sub_prompt,
+                None,
+                serde_json::json!({"isSidechain": true, "agentId": "a1b2"}),
+            ),
+            assistant(sub_reply, sub_prompt, true),
+        ]
+        .join("\n");
+
+        let mut transcript = Transcript::parse(&main);
+        assert_eq!(transcript.entries().len(), 5);
+        transcript.merge(Transcript::parse(&side));
+
+        let id = |s: &str| Uuid::parse_str(s).unwrap();
+        let roots: Vec<_> = transcript
+            .roots()
+            .filter_map(TranscriptEntry::uuid)
+            .collect();
+        assert_eq!(roots, [id(prompt)]);
+        let TranscriptEntry::User(first) = transcript.get(id(prompt)).unwrap() else {
+            panic!("expected a user entry");
+        };
+        assert_eq!(first.cwd.as_deref(), Some("/repo"));
+        assert_eq!(first.git_branch.as_deref(), Some("main"));
+        assert!(matches!(first.message.content[0], ContentBlock::Text(_)));
+
+        let children: Vec<_> = transcript
+            .children(id(task_call))
+            .filter_map(TranscriptEntry::uuid)
+            .collect();
+        assert_eq!(children.len(), 2);
+        assert!(children.contains(&id(task_result)));
+        assert_eq!(
+            transcript.thread(id(task_result)).len(),
+            3,
+            "prompt, task call, task result"
+        );
+        assert_eq!(
+            transcript.summary_for(id(task_result)),
+            Some("Explore the repo")
+        );
+
+        let sidechain: Vec<_> = transcript.sidechain_roots().collect();
+        assert_eq!(sidechain.len(), 1);
+        assert_eq!(sidechain[0].agent_id(), Some("a1b2"));
+        let origin = transcript.sidechain_origin(sidechain[0]).unwrap();
+        assert_eq!(origin.uuid(), Some(id(task_result)));
+        let reply = transcript.get(id(sub_reply)).unwrap();
+        assert!(reply.is_sidechain());
+        assert_eq!(
+            transcript.parent(id(sub_reply)).unwrap().uuid(),
+            Some(id(sub_prompt))
+        );
+        assert!(transcript.sidechain_origin(origin).is_none());
+    }
+
+    #[test]
+    fn test_entries_round_trip() {
+        let line = assistant(
+            "00000000-0000-4000-8000-000000000005",
+            "00000000-0000-4000-8000-000000000004",
+            true,
+        );
+        let entry: TranscriptEntry = serde_json::from_str(&line).unwrap();
+        let TranscriptEntry::Assistant(ref record) = entry else {
+            panic!("expected an assistant entry");
+        };
+        assert_eq!(record.request_id.as_deref(), Some("req_1"));
+        assert_eq!(record.message.model, "claude-sonnet-4");
+
+        let value = serde_json::to_value(&entry).unwrap();
+        assert_eq!(value["type"], "assistant");
+        assert_eq!(value["agentId"], "a1b2");
+        assert_eq!(value["isSidechain"], true);
+
+        let summary: TranscriptEntry =
+            serde_json::from_str(r#"{"type":"summary","summary":"Title","leafUuid":"00000000-0000-4000-8000-000000000005"}"#)
+                .unwrap();
+        assert_eq!(
+            serde_json::to_value(&summary).unwrap(),
+            serde_json::json!({"type": "summary", "summary": "Title", "leafUuid": "00000000-0000-4000-8000-000000000005"})
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Visitor-style traversal of [`ClaudeOutput`] messages.
+//!
+//! Implement [`MessageVisitor`] and override only the callbacks you care
+//! about, then hand each message to [`ClaudeOutput::accept`]. Every method has
+//! a default: container methods (`visit_assistant`, `visit_user`) walk their
+//! content blocks and dispatch to the per-block methods, while leaf methods do
+//! nothing.
+
+use super::claude_output::ClaudeOutput;
+use super::content_blocks::{
+    ContentBlock, RedactedThinkingBlock, ThinkingBlock, ToolResultBlock, ToolUseBlock,
+};
+use super::control::{ControlRequest, ControlResponse};
+use super::debug_log::DebugMessage;
+use super::errors::AnthropicError;
+use super::message_types::{AssistantMessage, SystemMessage, UserMessage};
+use super::rate_limit::RateLimitEvent;
+use super::result::ResultMessage;
+use serde_json::Value;
+
+/// Callbacks for each kind of message and content block.
+///
+/// Override only the callbacks you care about, then hand each message to
+/// [`ClaudeOutput::accept`]. Container methods (`visit_assistant`,
+/// `visit_user`) default to walking their content blocks; leaf methods default
+/// to doing nothing. Overriding a container method replaces its block walk —
+/// call [`walk_assistant`] / [`walk_user`] from the override to keep it.
+///
+/// # Example
+///
+/// ```
+/// use claude_codes::{ClaudeOutput, MessageVisitor, ResultMessage, ToolUseBlock};
+///
+/// #[derive(Default)]
+/// struct Collector {
+///     text: String,
+///     tools: Vec<String>,
+///     cost: f64,
+/// }
+///
+/// impl MessageVisitor for Collector {
+///     fn visit_assistant_text(&mut self, text: &str) {
+///         self.text.push_str(text);
+///     }
+///     fn visit_tool_use(&mut self, tool_use: &ToolUseBlock) {
+///         self.tools.push(tool_use.name.clone());
+///     }
+///     fn visit_result(&mut self, result: &ResultMessage) {
+///         self.cost += result.total_cost_usd;
+///     }
+/// }
+///
+/// let json = r#"{"type":"assistant","message":{"id":"msg_1","role":"assistant",
+///     "model":"claude-3","content":[
+///         {"type":"text","text":"Checking"},
+///         {"type":"tool_use","id":"tu_1","name":"Bash","input":{"command":"ls"}}
+///     ]},"session_id":"abc"}"#;
+/// let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+///
+/// let mut collector = Collector::default();
+/// output.accept(&mut collector);
+/// assert_eq!(collector.text, "Checking");
+/// assert_eq!(collector.tools, vec!["Bash"]);
+/// ```
+#[allow(unused_variables)]
+pub trait MessageVisitor {
+    /// A `system` message (init, status, task progress, ...).
+    fn visit_system(&mut self, message: &SystemMessage) {}
+
+    /// A `user` message. By default walks its content blocks.
+    fn visit_user(&mut self, message: &UserMessage) {
+        walk_user(self, message);
+    }
+
+    /// An `assistant` message. By default walks its content blocks.
+    fn visit_assistant(&mut self, message: &AssistantMessage) {
+        walk_assistant(self, message);
+    }
+
+    /// A `result` message, marking the end of a query.
+    fn visit_result(&mut self, result: &ResultMessage) {}
+
+    /// A control request from the CLI (tool permission, hook callback, ...).
+    fn visit_control_request(&mut self, request: &ControlRequest) {}
+
+    /// A control response from the CLI.
+    fn visit_control_response(&mut self, response: &ControlResponse) {}
+
+    /// An API error reported by the CLI.
+    fn visit_api_error(&mut self, error: &AnthropicError) {}
+
+    /// A rate limit status event.
+    fn visit_rate_limit(&mut self, event: &RateLimitEvent) {}
+
+    /// A CLI debug log line read from stdout.
+    fn visit_debug(&mut self, message: &DebugMessage) {}
+
+    /// A message whose `type` this version of the crate doesn't know.
+    fn visit_unknown(&mut self, message: &Value) {}
+
+    /// A text block in an assistant message.
+    fn visit_assistant_text(&mut self, text: &str) {}
+
+    /// A text block in a user message.
+    fn visit_user_text(&mut self, text: &str) {}
+
+    /// A thinking block in an assistant message.
+    fn visit_thinking(&mut self, thinking: &ThinkingBlock) {}
+
+    /// A redacted (encrypted) thinking block in an assistant message.
+    fn visit_redacted_thinking(&mut self, thinking: &RedactedThinkingBlock) {}
+
+    /// A tool invocation in an assistant message.
+    fn visit_tool_use(&mut self, tool_use: &ToolUseBlock) {}
+
+    /// A tool result in a user message.
+    fn visit_tool_result(&mut self, tool_result: &ToolResultBlock) {}
+
+    /// Any other content block (images, server tool blocks, unknown types, ...).
+    fn visit_other_block(&mut self, block: &ContentBlock) {}
+}
+
+/// Dispatch each block of an assistant message to the matching visitor method.
+pub fn walk_assistant<V: MessageVisitor + ?Sized>(visitor: &mut V, message: &AssistantMessage) {
+    for block in &message.message.content {
+        match block {
+            ContentBlock::Text(t) => visitor.visit_assistant_text(&t.text),
+            ContentBlock::Thinking(t) => visitor.visit_thinking(t),
+            ContentBlock::RedactedThinking(t) => visitor.visit_redacted_thinking(t),
+            ContentBlock::ToolUse(tu) => visitor.visit_tool_use(tu),
+            ContentBlock::ToolResult(tr) => visitor.visit_tool_result(tr),
+            other => visitor.visit_other_block(other),
+        }
+    }
+}
+
+/// Dispatch each block of a user message to the matching visitor method.
+pub fn walk_user<V: MessageVisitor + ?Sized>(visitor: &mut V, message: &UserMessage) {
+    for block in &message.message.content {
+        match block {
+            ContentBlock::Text(t) => visitor.visit_user_text(&t.text),
+            ContentBlock::ToolResult(tr) => visitor.visit_tool_result(tr),
+            other => visitor.visit_other_block(other),
+        }
+    }
+}
+
+impl ClaudeOutput {
+    /// Dispatch this message to the matching [`MessageVisitor`] method.
+    pub fn accept<V: MessageVisitor + ?Sized>(&self, visitor: &mut V) {
+        match self {
+            ClaudeOutput::System(m) => visitor.visit_system(m),
+            ClaudeOutput::User(m) => visitor.visit_user(m),
+            ClaudeOutput::Assistant(m) => visitor.visit_assistant(m),
+            ClaudeOutput::Result(m) => visitor.visit_result(m),
+            ClaudeOutput::ControlRequest(m) => visitor.visit_control_request(m),
+            ClaudeOutput::ControlResponse(m) => visitor.visit_control_response(m),
+            ClaudeOutput::Error(m) => visitor.visit_api_error(m),
+            ClaudeOutput::RateLimitEvent(m) => visitor.visit_rate_limit(m),
+            ClaudeOutput::Debug(m) => visitor.visit_debug(m),
+            ClaudeOutput::Unknown(v) => visitor.visit_unknown(v),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl MessageVisitor for Recorder {
+        fn visit_system(&mut self, message: &SystemMessage) {
+            self.0.push(format!("system:{}", message.subtype));
+        }
+        fn visit_result(&mut self, result: &ResultMessage) {
+            self.0.push(format!("result:{}", result.subtype));
+        }
+        fn visit_assistant_text(&mut self, text: &str) {
+            self.0.push(format!("text:{}", text));
+        }
+        fn visit_user_text(&mut self, text: &str) {
+            self.0.push(format!("user:{}", text));
+        }
+        fn visit_thinking(&mut self, thinking: &ThinkingBlock) {
+            self.0.push(format!("thinking:{}", thinking.thinking));
+        }
+        fn visit_tool_use(&mut self, tool_use: &ToolUseBlock) {
+            self.0.push(format!("tool_use:{}", tool_use.name));
+        }
+        fn visit_tool_result(&mut self, tool_result: &ToolResultBlock) {
+            self.0
+                .push(format!("tool_result:{}", tool_result.tool_use_id));
+        }
+        fn visit_other_block(&mut self, block: &ContentBlock) {
+            self.0.push(format!("other:{}", block.block_type()));
+        }
+    }
+
+    fn visit(json: &str) -> Vec<String> {
+        let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+        let mut recorder = Recorder::default();
+        output.accept(&mut recorder);
+        recorder.0
+    }
+
+    #[test]
+    fn test_assistant_blocks_dispatch_in_order() {
+        let seen = visit(
+            r#"{"type":"assistant","message":{"id":"m","role":"assistant","model":"c",
+                "content":[
+                    {"type":"thinking","thinking":"hmm","signature":"s"},
+                    {"type":"text","text":"hi"},
+                    {"type":"tool_use","id":"tu_1","name":"Read","input":{}},
+                    {"type":"image","source":{"type":"base64","media_type":"image/png","data":"AA=="}}
+                ]},"session_id":"s"}"#,
+        );
+        assert_eq!(
+            seen,
+            vec!["thinking:hmm", "text:hi", "tool_use:Read", "other:image"]
+        );
+    }
+
+    #[test]
+    fn test_user_blocks_dispatch() {
+        let seen = visit(
+            r#"{"type":"user","message":{"role":"user","content":[
+                    {"type":"text","text":"go"},
+                    {"type":"tool_result","tool_use_id":"tu_1","content":"ok"}
+                ]},"session_id":"550e8400-e29b-41d4-a716-446655440000"}"#,
+        );
+        assert_eq!(seen, vec!["user:go", "tool_result:tu_1"]);
+    }
+
+    #[test]
+    fn test_system_and_result_dispatch() {
+        assert_eq!(
+            visit(r#"{"type":"system","subtype":"init","session_id":"s"}"#),
+            vec!["system:init"]
+        );
+        assert_eq!(
+            visit(
+                r#"{"type":"result","subtype":"success","is_error":false,"duration_ms":1,
+                    "duration_api_ms":1,"num_turns":1,"session_id":"s","total_cost_usd":0.0}"#
+            ),
+            vec!["result:success"]
+        );
+    }
+
+    #[test]
+    fn test_overriding_container_skips_walk() {
+        struct Counter(usize);
+        impl MessageVisitor for Counter {
+            fn visit_assistant(&mut self, _message: &AssistantMessage) {
+                self.0 += 1;
+            }
+            fn visit_assistant_text(&mut self, _text: &str) {
+                panic!("walk should be skipped");
+            }
+        }
+
+        let output: ClaudeOutput = serde_json::from_str(
+            r#"{"type":"assistant","message":{"id":"m","role":"assistant","model":"c",
+                "content":[{"type":"text","text":"hi"}]},"session_id":"s"}"#,
+        )
+        .unwrap();
+        let mut counter = Counter(0);
+        output.accept(&mut counter);
+        assert_eq!(counter.0, 1);
+    }
+}
@@ -70,8 +70,32 @@
 //! - [`io`] - Top-level message types (`ClaudeInput`, `ClaudeOutput`)
 //! - [`messages`] - Detailed message structures for requests and responses
 //! - [`cli`] - Builder for configuring Claude CLI invocation
+//! - [`config`] - Builder and client options loaded from a TOML or JSON file
 //! - [`error`] - Error types and result aliases
 //! - [`version`] - Version compatibility checking
+//! - [`sandbox`] - Typed settings for the CLI's Bash sandbox
+//! - [`framing`] - Reading JSON Lines frames into a reused buffer
+//! - [`client_state`] - Starting / idle / processing / awaiting-approval state for busy indicators
+//! - [`progress`] - Elapsed time, running tool and subagent count of the current turn
+//! - [`client_handle`] - Cancel-safe `&self` receiving for use in `tokio::select!`
+//! - [`fan_in`] - Merging the output of several sessions into one stream
+//! - [`input_queue`] - Holding user input back until the current turn ends
+//! - [`conversation`] - Plan mode turns, and exporting a conversation's history to continue elsewhere
+//! - [`pipeline`] - Chain agent turns, templating each stage's output into the next prompt
+//! - [`stats`] - Live message, token, and tool-call counters for progress displays
+//! - [`dedup`] - Dropping re-delivered messages by `uuid` in resumed or replayed sessions
+//! - [`session_index`] - Tagging sessions with names and finding them again
+//! - [`shadow`] - Denying every tool use while recording what it would have done
+//! - [`permissions`] - Remembered permission rules and the session's permission mode
+//! - [`tokens`] - Estimating prompt tokens and keeping prompts within a budget
+//! - [`retry`] - Re-sending turns that end on a transient API error
+//! - `render` - Colorized terminal rendering of messages (requires the `render` feature)
+//! - `normalize` - Backend-neutral `AgentEvent` model for Claude and Codex messages (requires the `normalize` feature)
+//! - `orchestrator` - Run one prompt on several Claude / Codex backends in parallel (requires the `orchestrator` feature)
+//! - `cache` - Response cache keyed by prompt and configuration hash (requires the `cache` feature)
+//! - `fixtures` - Canned protocol messages for tests (requires the `fixtures` feature)
+//! - `bridge` - Serve a session to remote frontends over Server-Sent Events or a WebSocket (requires the `sse-bridge` or `ws-bridge` feature)
+//! - `otel` - OpenTelemetry session and turn spans (requires the `otel` feature)
 //!
 //! # Version Compatibility
 //!
@@ -99,77 +123,191 @@
 //! - `basic_repl.rs` - Interactive REPL implementation
 
 // Core modules always available
+pub mod client_state;
+pub mod dedup;
 pub mod error;
 pub mod io;
 pub mod messages;
+pub mod permissions;
+pub mod progress;
 pub mod protocol;
+pub mod stats;
+pub mod tokens;
 pub mod tool_inputs;
 pub mod types;
 
 // Client modules
-#[cfg(feature = "async-client")]
+#[cfg(feature = "async-core")]
 pub mod client_async;
+#[cfg(feature = "async-client")]
+pub mod client_blocking;
+#[cfg(feature = "async-client")]
+pub mod client_handle;
 #[cfg(feature = "sync-client")]
 pub mod client_sync;
+#[cfg(feature = "async-client")]
+pub mod conversation;
+#[cfg(feature = "async-client")]
+pub mod fan_in;
+#[cfg(feature = "async-core")]
+pub mod input_queue;
+#[cfg(feature = "async-client")]
+pub mod pipeline;
+#[cfg(all(test, unix, feature = "async-client"))]
+mod test_support;
 
 // Client-related modules
-#[cfg(any(feature = "sync-client", feature = "async-client"))]
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
 pub mod cli;
-#[cfg(any(feature = "sync-client", feature = "async-client"))]
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub mod config;
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub mod debug_sink;
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub mod framing;
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub mod retry;
+#[cfg(feature = "async-core")]
+mod runtime;
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub mod sandbox;
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub mod session_index;
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub mod shadow;
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
 pub mod version;
 
+// In-process MCP server
+#[cfg(feature = "async-core")]
+pub mod mcp;
+
+// Observability
+#[cfg(feature = "otel")]
+pub mod otel;
+
+// Network bridges for remote frontends
+#[cfg(any(feature = "sse-bridge", feature = "ws-bridge"))]
+pub mod bridge;
+
+// Terminal rendering
+#[cfg(feature = "render")]
+pub mod render;
+
+// Cross-SDK event normalization
+#[cfg(feature = "normalize")]
+pub mod normalize;
+
+// Multi-backend runs
+#[cfg(feature = "orchestrator")]
+pub mod orchestrator;
+
+// Response caching
+#[cfg(feature = "cache")]
+pub mod cache;
+
+// Canned protocol messages for downstream tests
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+
 // Core exports always available
-pub use error::{Error, Result};
+pub use client_state::{ClientState, ClientStateTracker};
+pub use dedup::{DedupExt, DedupFilter};
+pub use error::{BuilderError, CrashReport, Error, Result};
 pub use io::{
-    AnthropicError, AnthropicErrorDetails, ApiErrorType, AssistantMessageContent, ClaudeInput,
-    ClaudeOutput, ParseError,
+    AnthropicError, AnthropicErrorDetails, ApiErrorType, AssistantMessageContent, AuthProblem,
+    ClaudeInput, ClaudeOutput, ParseError, ParseErrorAction,
 };
 pub use messages::*;
+pub use permissions::{FilePermissionStore, PermissionState, PermissionStore};
+pub use progress::TurnProgress;
 pub use protocol::{MessageEnvelope, Protocol};
+pub use stats::StreamStats;
 pub use types::*;
 
 // Content block types for message parsing
 pub use io::{
     CodeExecutionToolResultBlock, ContainerUploadBlock, ContentBlock, FallbackBlock, FallbackModel,
     ImageBlock, ImageSource, ImageSourceType, McpToolResultBlock, McpToolUseBlock, MediaType,
-    ServerToolUseBlock, TextBlock, ThinkingBlock, ToolResultBlock, ToolResultContent,
-    WebSearchToolResultBlock,
+    RedactedThinkingBlock, ServerToolUseBlock, TextBlock, ThinkingBlock, ToolResultBlock,
+    ToolResultContent, WebSearchToolResultBlock,
 };
 
 // Control protocol types for tool permission handling
 pub use io::{
-    AskUserQuestionResponseError, ControlRequest, ControlRequestMessage, ControlRequestPayload,
-    ControlResponse, ControlResponseMessage, ControlResponsePayload, HookCallbackRequest,
-    InitializeRequest, McpMessageRequest, Permission, PermissionBehavior, PermissionDenial,
-    PermissionDestination, PermissionModeName, PermissionResult, PermissionRule,
-    PermissionSuggestion, PermissionType, SDKControlInterruptRequest, ToolCaller,
-    ToolPermissionRequest, ToolUseBlock,
+    AddDirectoryRequest, AddDirectoryResult, AskUserQuestionResponseError, ControlRequest,
+    ControlRequestMessage, ControlRequestPayload, ControlResponse, ControlResponseMessage,
+    ControlResponsePayload, HookCallbackRequest, InitializeRequest, McpMessageRequest, Permission,
+    PermissionBehavior, PermissionDenial, PermissionDestination, PermissionModeName,
+    PermissionResult, PermissionRule, PermissionSuggestion, PermissionType, PlanDecision,
+    SDKControlInterruptRequest, SetPermissionModeRequest, ToolCaller, ToolPermissionRequest,
+    ToolUseBlock,
 };
 
 // System message and assistant message types
 pub use io::{
-    ApiKeySource, CompactBoundaryMessage, CompactMetadata, CompactionTrigger, InitMessage,
-    InitPermissionMode, MessageRole, OutputStyle, PluginInfo, StatusMessage, StatusMessageStatus,
-    StopReason, SystemMessage, SystemSubtype, TaskNotificationMessage, TaskPatch,
+    ApiKeySource, ApiRetryMessage, ApiRetryNoResponse, BackgroundTask,
+    BackgroundTasksChangedMessage, CompactBoundaryMessage, CompactMetadata, CompactionTrigger,
+    FailedPersistedFile, FilesPersistedMessage, InformationalMessage, InitMessage,
+    InitPermissionMode, LocalCommandOutputMessage, MessageRole, ModelFallbackMessage, NoticeLevel,
+    NotificationMessage, NotificationPriority, OtherSystem, OutputStyle, PersistedFile, PluginInfo,
+    SessionState, SessionStateChangedMessage, StatusMessage, StatusMessageStatus, StopReason,
+    SystemKind, SystemMessage, SystemSubtype, TaskNotificationMessage, TaskPatch,
     TaskProgressMessage, TaskStartedMessage, TaskStatus, TaskType, TaskUpdatedMessage, TaskUsage,
     ThinkingTokensMessage,
 };
 
+// CLI debug log lines
+pub use io::{DebugLevel, DebugMessage};
+
+// Hook event payloads and responses
+pub use io::{
+    HookDecision, HookEvent, HookInput, HookInputBase, HookOutput, HookPermissionDecision,
+    HookSpecificOutput, NotificationHookInput, PostToolUseHookInput, PreToolUseHookInput,
+    StopHookInput, SubagentStopHookInput, UserPromptSubmitHookInput,
+};
+
 // Wire-fidelity audit for verifying frames are fully typed
 pub use io::{assert_fully_wrapped, audit_frame, FrameAudit};
 
+// Cleaning terminal escapes out of tool output
+pub use io::clean_terminal_text;
+
+// Visitor-style message traversal
+pub use io::{walk_assistant, walk_user, MessageVisitor};
+
+// File checkpoint types
+pub use io::{
+    FileBackup, FileCheckpoint, FileHistorySnapshot, RewindFilesRequest, RewindFilesResult,
+};
+
+// Session transcript types
+pub use io::{Transcript, TranscriptEntry, TranscriptRecord, TranscriptSummary};
+
 // Rate limit types
 pub use io::{
     OverageDisabledReason, OverageStatus, RateLimitEvent, RateLimitInfo, RateLimitStatus,
     RateLimitWindow,
 };
 
+// Message and result types
+pub use io::{
+    AssistantMessage, LenientUuid, ModelUsageEntry, ResultErrorKind, ResultMessage, ResultSubtype,
+    Timestamp, UserMessage,
+};
+
 // Usage types
 pub use io::{
     AssistantUsage, CacheCreationDetails, ServerToolUse, SubagentResult, SubagentToolStats,
     UsageInfo,
 };
 
+// Typed tool result types
+pub use io::{
+    GlobResult, GrepMatch, GrepResult, NotebookEditResult, WebFetchResult, WebSearchHit,
+    WebSearchHits, WebSearchResult, WebSearchResultEntry,
+};
+
 // Typed tool input types
 pub use tool_inputs::{
     AllowedPrompt, AskUserQuestionInput, BashInput, EditInput, EnterPlanModeInput,
@@ -181,14 +319,30 @@ pub use tool_inputs::{
 };
 
 // Client exports
+#[cfg(feature = "async-core")]
+pub use client_async::{AsyncClient, AsyncStreamProcessor, StderrReader};
+#[cfg(feature = "async-client")]
+pub use client_blocking::{BlockingClient, BlockingResponseStream};
 #[cfg(feature = "async-client")]
-pub use client_async::{AsyncClient, AsyncStreamProcessor};
+pub use client_handle::{ClientHandle, Sequenced};
 #[cfg(feature = "sync-client")]
 pub use client_sync::{StreamProcessor, SyncClient};
+#[cfg(feature = "async-client")]
+pub use fan_in::{select_all, ClientId, SelectAll};
 
 // Client-related exports
-#[cfg(any(feature = "sync-client", feature = "async-client"))]
-pub use cli::{ClaudeCliBuilder, CliFlag, InputFormat, OutputFormat, PermissionMode};
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub use cli::{
+    ClaudeCliBuilder, CliFlag, InputFormat, ModelFamily, OutputFormat, PermissionMode, Preset,
+};
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub use config::{BedrockConfig, ClientConfig, VertexConfig};
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub use debug_sink::{DebugSink, Direction};
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub use sandbox::{SandboxNetwork, SandboxSettings};
+#[cfg(any(feature = "sync-client", feature = "async-core"))]
+pub use version::ClaudeVersion;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,640 @@
+//! In-process MCP server for exposing Rust functions as Claude tools.
+//!
+//! An [`McpServer`] holds a set of tools — a name, a description, a JSON
+//! schema for the input, and an async handler — and speaks the server side of
+//! the [Model Context Protocol](https://modelcontextprotocol.io) over
+//! JSON-RPC: `initialize`, `tools/list`, `tools/call`, and `ping`.
+//!
+//! The server is transport-agnostic. [`McpServer::handle_message`] answers a
+//! single JSON-RPC message, and [`McpServer::serve`] /
+//! [`McpServer::serve_stdio`] run it as a newline-delimited stdio server, the
+//! transport the CLI uses for `"type": "stdio"` servers in `--mcp-config`;
+//! [`McpServer::stdio_config`] builds that config entry.
+//!
+//! With the `schemars` feature, [`McpServer::typed_tool`] derives a tool's
+//! input schema from a Rust type and deserializes each call into it.
+//!
+//! To run the server inside the SDK process instead, pass
+//! [`McpServer::sdk_config`] to
+//! [`ClaudeCliBuilder::mcp_config`](crate::ClaudeCliBuilder::mcp_config) and
+//! register the server with
+//! [`AsyncClient::register_mcp_server`](crate::AsyncClient::register_mcp_server);
+//! the client then answers the CLI's `mcp_message` control requests itself.
+//!
+//! Claude sees each tool as `mcp__<server>__<tool>`; use
+//! [`McpServer::tool_id`] to build those names for
+//! [`ClaudeCliBuilder::allowed_tools`](crate::ClaudeCliBuilder::allowed_tools).
+//!
+//! # Example
+//!
+//! ```
+//! use claude_codes::mcp::{McpServer, ToolOutput};
+//! use serde_json::json;
+//!
+//! let server = McpServer::new("calc").tool(
+//!     "add",
+//!     "Add two integers",
+//!     json!({
+//!         "type": "object",
+//!         "properties": { "a": { "type": "integer" }, "b": { "type": "integer" } },
+//!         "required": ["a", "b"]
+//!     }),
+//!     |input| async move {
+//!         let a = input["a"].as_i64().unwrap_or(0);
+//!         let b = input["b"].as_i64().unwrap_or(0);
+//!         ToolOutput::text((a + b).to_string())
+//!     },
+//! );
+//!
+//! assert_eq!(server.tool_id("add"), "mcp__calc__add");
+//! ```
+
+use crate::io::{
+    ControlResponse, McpMessageRequest, McpSdkServerConfig, McpServerConfig, McpStdioServerConfig,
+};
+use log::{debug, warn};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+/// MCP protocol version advertised when the client does not request one.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// JSON-RPC error code for an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for invalid parameters (including unknown tools).
+const INVALID_PARAMS: i64 = -32602;
+
+/// Boxed future returned by a tool handler.
+pub type ToolFuture = Pin<Box<dyn Future<Output = ToolOutput> + Send>>;
+
+type Handler = Arc<dyn Fn(Value) -> ToolFuture + Send + Sync>;
+
+/// The result of a tool call, sent back to Claude as MCP `CallToolResult`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolOutput {
+    /// MCP content items (`{"type": "text", "text": ...}`, ...).
+    pub content: Vec<Value>,
+    /// Whether the call failed. Claude sees the content as an error message.
+    pub is_error: bool,
+}
+
+impl ToolOutput {
+    /// A successful text result.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            content: vec![json!({"type": "text", "text": text.into()})],
+            is_error: false,
+        }
+    }
+
+    /// A successful result containing pretty-printed JSON.
+    pub fn json(value: &Value) -> Self {
+        Self::text(serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string()))
+    }
+
+    /// A failed call with an error message Claude can react to.
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            content: vec![json!({"type": "text", "text": message.into()})],
+            is_error: true,
+        }
+    }
+
+    /// Serialize as an MCP `CallToolResult`.
+    pub fn to_value(&self) -> Value {
+        json!({ "content": self.content, "isError": self.is_error })
+    }
+}
+
+impl From<String> for ToolOutput {
+    fn from(text: String) -> Self {
+        Self::text(text)
+    }
+}
+
+impl From<&str> for ToolOutput {
+    fn from(text: &str) -> Self {
+        Self::text(text)
+    }
+}
+
+impl<T: Into<ToolOutput>, E: fmt::Display> From<std::result::Result<T, E>> for ToolOutput {
+    fn from(result: std::result::Result<T, E>) -> Self {
+        match result {
+            Ok(output) => output.into(),
+            Err(e) => Self::error(e.to_string()),
+        }
+    }
+}
+
+/// The JSON schema for `T`, in the form MCP expects for a tool's `inputSchema`.
+///
+/// The `$schema` and `title` keys schemars adds at the root are dropped.
+#[cfg(feature = "schemars")]
+pub fn input_schema<T: schemars::JsonSchema>() -> Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(T)).unwrap_or_else(|_| json!({}));
+    if let Some(obj) = schema.as_object_mut() {
+        obj.remove("$schema");
+        obj.remove("title");
+    }
+    schema
+}
+
+/// A tool registered on an [`McpServer`].
+#[derive(Clone)]
+struct Tool {
+    description: String,
+    input_schema: Value,
+    handler: Handler,
+}
+
+/// An MCP server whose tools are async Rust closures.
+///
+/// Cheap to clone; clones share the same tool registry.
+#[derive(Clone)]
+pub struct McpServer {
+    name: String,
+    version: String,
+    tools: Arc<BTreeMap<String, Tool>>,
+}
+
+impl McpServer {
+    /// Create a server with no tools. `name` is the key Claude knows it by.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            tools: Arc::new(BTreeMap::new()),
+        }
+    }
+
+    /// Set the version reported in `serverInfo` (defaults to this crate's version).
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// Register a tool. Registering an existing name replaces it.
+    ///
+    /// `input_schema` is the JSON schema advertised to Claude; the handler
+    /// receives the call's `arguments` object and may return anything
+    /// convertible to [`ToolOutput`] (`String`, `&str`, `Result<_, impl Display>`).
+    pub fn tool<F, Fut, O>(
+        mut self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        input_schema: Value,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = O> + Send + 'static,
+        O: Into<ToolOutput>,
+    {
+        let handler: Handler = Arc::new(move |input| {
+            let fut = handler(input);
+            Box::pin(async move { fut.await.into() })
+        });
+        Arc::make_mut(&mut self.tools).insert(
+            name.into(),
+            Tool {
+                description: description.into(),
+                input_schema,
+                handler,
+            },
+        );
+        self
+    }
+
+    /// Register a tool whose input schema is derived from `T`.
+    ///
+    /// The JSON schema advertised to Claude comes from `T`'s
+    /// [`JsonSchema`](schemars::JsonSchema) impl, and each call's `arguments`
+    /// are deserialized into `T` before the handler runs. Arguments that do not
+    /// match are reported back to Claude as an error result without calling
+    /// the handler.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use claude_codes::mcp::McpServer;
+    /// use schemars::JsonSchema;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, JsonSchema)]
+    /// struct Weather {
+    ///     /// City name, e.g. "Paris"
+    ///     city: String,
+    /// }
+    ///
+    /// let server = McpServer::new("weather").typed_tool(
+    ///     "forecast",
+    ///     "Get tomorrow's forecast",
+    ///     |input: Weather| async move { format!("Sunny in {}", input.city) },
+    /// );
+    /// ```
+    #[cfg(feature = "schemars")]
+    pub fn typed_tool<T, F, Fut, O>(
+        self,
+        name: impl Into<String>,
+        description: impl Into<String>,
+        handler: F,
+    ) -> Self
+    where
+        T: schemars::JsonSchema + serde::de::DeserializeOwned + Send + 'static,
+        F: Fn(T) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = O> + Send + 'static,
+        O: Into<ToolOutput>,
+    {
+        let name = name.into();
+        let tool_name = name.clone();
+        let handler = Arc::new(handler);
+        self.tool(name, description, input_schema::<T>(), move |input| {
+            let handler = Arc::clone(&handler);
+            let tool_name = tool_name.clone();
+            async move {
+                match serde_json::from_value::<T>(input) {
+                    Ok(input) => handler(input).await.into(),
+                    Err(e) => ToolOutput::error(format!("Invalid input for {}: {}", tool_name, e)),
+                }
+            }
+        })
+    }
+
+    /// The server name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Names of the registered tools, sorted.
+    pub fn tool_names(&self) -> impl Iterator<Item = &str> {
+        self.tools.keys().map(String::as_str)
+    }
+
+    /// The fully-qualified name Claude uses for `tool` (`mcp__<server>__<tool>`).
+    pub fn tool_id(&self, tool: &str) -> String {
+        format!("mcp__{}__{}", self.name, tool)
+    }
+
+    /// `--mcp-config` JSON telling the CLI to launch `command args...` as this
+    /// server over stdio (typically the current binary with a subcommand that
+    /// calls [`serve_stdio`](Self::serve_stdio)).
+    pub fn stdio_config<I, S>(&self, command: impl Into<String>, args: I) -> String
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let config = McpServerConfig::Stdio(McpStdioServerConfig {
+            command: command.into(),
+            args: Some(args.into_iter().map(Into::into).collect()),
+            env: None,
+        });
+        json!({ "mcpServers": { &self.name: config } }).to_string()
+    }
+
+    /// `--mcp-config` JSON declaring this server as an in-process SDK server.
+    ///
+    /// The CLI then sends the server's traffic as `mcp_message` control
+    /// requests; register the server with
+    /// [`AsyncClient::register_mcp_server`](crate::AsyncClient::register_mcp_server)
+    /// to answer them automatically.
+    pub fn sdk_config(&self) -> String {
+        let config = McpServerConfig::Sdk(McpSdkServerConfig {
+            name: self.name.clone(),
+        });
+        json!({ "mcpServers": { &self.name: config } }).to_string()
+    }
+
+    /// Answer an `mcp_message` control request addressed to this server.
+    ///
+    /// Notifications get an empty JSON-RPC result, since every control
+    /// request needs a response.
+    pub async fn handle_control_request(
+        &self,
+        request: &McpMessageRequest,
+        request_id: &str,
+    ) -> ControlResponse {
+        let reply = self
+            .handle_message(request.message.clone())
+            .await
+            .unwrap_or_else(|| json!({"jsonrpc": "2.0", "result": {}}));
+        ControlResponse::mcp_response(request_id, reply)
+    }
+
+    /// Call a registered tool directly, bypassing JSON-RPC.
+    ///
+    /// Returns `None` if no tool with that name is registered.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Option<ToolOutput> {
+        let handler = self.tools.get(name)?.handler.clone();
+        Some(handler(arguments).await)
+    }
+
+    /// Answer one JSON-RPC message.
+    ///
+    /// Returns the response to send back, or `None` for notifications (messages
+    /// without an `id`), which get no reply.
+    pub async fn handle_message(&self, message: Value) -> Option<Value> {
+        let id = message.get("id").cloned()?;
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        debug!("[MCP] {} request: {}", self.name, method);
+
+        let result = match method {
+            "initialize" => Ok(self.initialize_result(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(self.list_tools()),
+            "tools/call" => self.handle_call(&params).await,
+            other => Err((METHOD_NOT_FOUND, format!("Method not found: {}", other))),
+        };
+
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": code, "message": message},
+            }),
+        })
+    }
+
+    /// Serve newline-delimited JSON-RPC from `reader`, writing replies to `writer`.
+    ///
+    /// Returns when `reader` reaches EOF. Lines that are not valid JSON are
+    /// logged and skipped.
+    pub async fn serve<R, W>(&self, reader: R, mut writer: W) -> std::io::Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next_line().await? {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let message: Value = match serde_json::from_str(line) {
+                Ok(message) => message,
+                Err(e) => {
+                    warn!("[MCP] Ignoring malformed message: {}", e);
+                    continue;
+                }
+            };
+            if let Some(response) = self.handle_message(message).await {
+                let mut out = response.to_string();
+                out.push('\n');
+                writer.write_all(out.as_bytes()).await?;
+                writer.flush().await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serve over this process's stdin and stdout (requires the tokio runtime).
+    #[cfg(feature = "async-client")]
+    pub async fn serve_stdio(&self) -> std::io::Result<()> {
+        let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+        self.serve(stdin, tokio::io::stdout()).await
+    }
+
+    fn initialize_result(&self, params: &Value) -> Value {
+        let version = params
+            .get("protocolVersion")
+            .and_then(Value::as_str)
+            .unwrap_or(PROTOCOL_VERSION);
+        json!({
+            "protocolVersion": version,
+            "capabilities": {"tools": {}},
+            "serverInfo": {"name": self.name, "version": self.version},
+        })
+    }
+
+    fn list_tools(&self) -> Value {
+        let tools: Vec<Value> = self
+            .tools
+            .iter()
+            .map(|(name, tool)| {
+                json!({
+                    "name": name,
+                    "description": tool.description,
+                    "inputSchema": tool.input_schema,
+                })
+            })
+            .collect();
+        json!({ "tools": tools })
+    }
+
+    async fn handle_call(&self, params: &Value) -> std::result::Result<Value, (i64, String)> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or((INVALID_PARAMS, "Missing tool name".to_string()))?;
+        let arguments = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        match self.call_tool(name, arguments).await {
+            Some(output) => Ok(output.to_value()),
+            None => Err((INVALID_PARAMS, format!("Unknown tool: {}", name))),
+        }
+    }
+}
+
+impl fmt::Debug for McpServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("McpServer")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("tools", &self.tools.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> McpServer {
+        McpServer::new("test")
+            .version("1.0.0")
+            .tool(
+                "echo",
+                "Echo the input text",
+                json!({"type": "object", "properties": {"text": {"type": "string"}}}),
+                |input| async move { input["text"].as_str().unwrap_or("").to_string() },
+            )
+            .tool(
+                "fail",
+                "Always fails",
+                json!({"type": "object"}),
+                |_| async { Err::<String, _>("boom") },
+            )
+    }
+
+    #[tokio::test]
+    async fn test_initialize() {
+        let response = server()
+            .handle_message(json!({
+                "jsonrpc": "2.0", "id": 1, "method": "initialize",
+                "params": {"protocolVersion": "2025-06-18", "capabilities": {}}
+            }))
+            .await
+            .unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2025-06-18");
+        assert_eq!(response["result"]["serverInfo"]["name"], "test");
+        assert!(response["result"]["capabilities"]["tools"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_notifications_get_no_reply() {
+        let response = server()
+            .handle_message(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .await;
+        assert!(response.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tools_list() {
+        let response = server()
+            .handle_message(json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}))
+            .await
+            .unwrap();
+        let tools = response["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools[0]["name"], "echo");
+        assert_eq!(tools[0]["inputSchema"]["type"], "object");
+    }
+
+    #[tokio::test]
+    async fn test_tools_call() {
+        let server = server();
+        let response = server
+            .handle_message(json!({
+                "jsonrpc": "2.0", "id": 3, "method": "tools/call",
+                "params": {"name": "echo", "arguments": {"text": "hi"}}
+            }))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["content"][0]["text"], "hi");
+        assert_eq!(response["result"]["isError"], false);
+
+        let response = server
+            .handle_message(json!({
+                "jsonrpc": "2.0", "id": 4, "method": "tools/call",
+                "params": {"name": "fail", "arguments": {}}
+            }))
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["content"][0]["text"], "boom");
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_tool_and_method() {
+        let server = server();
+        let response = server
+            .handle_message(json!({
+                "jsonrpc": "2.0", "id": 5, "method": "tools/call",
+                "params": {"name": "nope"}
+            }))
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let response = server
+            .handle_message(json!({"jsonrpc": "2.0", "id": 6, "method": "resources/list"}))
+            .await
+            .unwrap();
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_stdio_config() {
+        let config: Value =
+            serde_json::from_str(&server().stdio_config("/usr/bin/app", ["mcp-serve"])).unwrap();
+        assert_eq!(config["mcpServers"]["test"]["type"], "stdio");
+        assert_eq!(config["mcpServers"]["test"]["command"], "/usr/bin/app");
+        assert_eq!(config["mcpServers"]["test"]["args"][0], "mcp-serve");
+    }
+
+    #[cfg(feature = "schemars")]
+    #[tokio::test]
+    async fn test_typed_tool() {
+        #[derive(serde::Deserialize, schemars::JsonSchema)]
+        struct AddInput {
+            a: i64,
+            b: i64,
+        }
+
+        let server =
+            McpServer::new("calc").typed_tool("add", "Add", |input: AddInput| async move {
+                (input.a + input.b).to_string()
+            });
+
+        let list = server
+            .handle_message(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"}))
+            .await
+            .unwrap();
+        let schema = &list["result"]["tools"][0]["inputSchema"];
+        assert_eq!(schema["type"], "object");
+        assert!(schema["properties"]["a"].is_object());
+        assert!(schema.get("$schema").is_none());
+
+        let ok = server
+            .call_tool("add", json!({"a": 2, "b": 3}))
+            .await
+            .unwrap();
+        assert_eq!(ok, ToolOutput::text("5"));
+
+        let bad = server.call_tool("add", json!({"a": "two"})).await.unwrap();
+        assert!(bad.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_sdk_config_and_control_request() {
+        let server = server();
+        let config: Value = serde_json::from_str(&server.sdk_config()).unwrap();
+        assert_eq!(config["mcpServers"]["test"]["type"], "sdk");
+        assert_eq!(config["mcpServers"]["test"]["name"], "test");
+
+        let request = McpMessageRequest {
+            server_name: "test".to_string(),
+            message: json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+        };
+        let response = server.handle_control_request(&request, "r-1").await;
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(
+            value["response"]["response"]["mcp_response"]["result"],
+            json!({})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_serve_over_stream() {
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "\n",
+            "not json\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"echo","arguments":{"text":"x"}}}"#,
+            "\n",
+        );
+        let mut output = Vec::new();
+        server().serve(input.as_bytes(), &mut output).await.unwrap();
+
+        let replies: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0]["id"], 1);
+        assert_eq!(replies[1]["result"]["content"][0]["text"], "x");
+    }
+}
@@ -0,0 +1,524 @@
+//! Backend-neutral agent events (requires the `normalize` feature).
+//!
+//! [`AgentEvent`] is a small common model of what a coding agent does during a
+//! turn — text, reasoning, tool calls, tool results, turn end, and errors —
+//! so a UI can render Claude and Codex sessions with one code path. Convert a
+//! message from either SDK into [`AgentEvents`] with `From`:
+//!
+//! - [`ClaudeOutput`] — one message may hold several content blocks, so it
+//!   can yield several events.
+//! - [`codex_codes::Notification`] and [`codex_codes::ServerMessage`] —
+//!   Codex streams text and reasoning as deltas, so `Text` and `Reasoning`
+//!   events carry chunks; the completed `agentMessage` / `reasoning` items are
+//!   skipped to avoid rendering the same text twice.
+//!
+//! Messages with no counterpart in the model (system messages, rate limit
+//! events, control traffic, approval requests, ...) yield no events.
+//!
+//! ```
+//! use claude_codes::normalize::{AgentEvent, AgentEvents};
+//! use claude_codes::ClaudeOutput;
+//!
+//! let json = r#"{"type":"assistant","message":{"id":"m","role":"assistant",
+//!     "model":"claude-3","content":[{"type":"text","text":"Hello"}]},
+//!     "session_id":"3c88babd-0bf8-55a8-9a73-98a084ec3a25"}"#;
+//! let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+//!
+//! let events = AgentEvents::from(&output);
+//! assert_eq!(events[..], [AgentEvent::Text("Hello".to_string())]);
+//! ```
+
+use std::ops::Deref;
+
+use codex_codes::{
+    CommandExecutionStatus, DynamicToolCallOutputContentItem, DynamicToolCallStatus,
+    McpToolCallStatus, Notification, PatchApplyStatus, ServerMessage, ThreadItem, TurnStatus,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::io::{ClaudeOutput, ContentBlock, ResultMessage, ToolResultContent};
+
+/// One thing an agent did, independent of which backend produced it.
+///
+/// Serializes externally tagged by the variant name in `snake_case`, e.g.
+/// `{"text": "Hello"}` or `{"turn_end": {"success": true, ...}}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentEvent {
+    /// Assistant text. A whole block from Claude, a streamed chunk from Codex.
+    Text(String),
+    /// Model reasoning or thinking text.
+    Reasoning(String),
+    /// The agent invoked a tool.
+    ToolCall {
+        /// Backend-assigned id, matched by the later [`AgentEvent::ToolResult`].
+        id: String,
+        /// Tool name, e.g. `"Bash"` for Claude or `"commandExecution"` for Codex.
+        name: String,
+        /// Tool arguments as JSON.
+        input: Value,
+    },
+    /// A tool call finished.
+    ToolResult {
+        id: String,
+        /// Textual output; empty when the tool produced none.
+        output: String,
+        is_error: bool,
+    },
+    /// The turn is over.
+    TurnEnd {
+        /// `false` if the turn failed or was interrupted.
+        success: bool,
+        duration_ms: Option<u64>,
+        /// Only reported by Claude.
+        total_cost_usd: Option<f64>,
+    },
+    /// An API or agent error.
+    Error(String),
+}
+
+/// The events produced by converting one backend message.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AgentEvents(pub Vec<AgentEvent>);
+
+impl AgentEvents {
+    /// Consume the wrapper and return the events.
+    pub fn into_vec(self) -> Vec<AgentEvent> {
+        self.0
+    }
+}
+
+impl Deref for AgentEvents {
+    type Target = [AgentEvent];
+
+    fn deref(&self) -> &[AgentEvent] {
+        &self.0
+    }
+}
+
+impl IntoIterator for AgentEvents {
+    type Item = AgentEvent;
+    type IntoIter = std::vec::IntoIter<AgentEvent>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a AgentEvents {
+    type Item = &'a AgentEvent;
+    type IntoIter = std::slice::Iter<'a, AgentEvent>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl From<AgentEvent> for AgentEvents {
+    fn from(event: AgentEvent) -> Self {
+        Self(vec![event])
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Claude
+// ---------------------------------------------------------------------------
+
+impl From<&ClaudeOutput> for AgentEvents {
+    fn from(output: &ClaudeOutput) -> Self {
+        match output {
+            ClaudeOutput::Assistant(msg) => {
+                Self(msg.message.content.iter().filter_map(block_event).collect())
+            }
+            ClaudeOutput::User(msg) => {
+                Self(msg.message.content.iter().filter_map(block_event).collect())
+            }
+            ClaudeOutput::Result(result) => result_events(result),
+            ClaudeOutput::Error(err) => AgentEvent::Error(err.error.message.clone()).into(),
+            _ => Self::default(),
+        }
+    }
+}
+
+fn block_event(block: &ContentBlock) -> Option<AgentEvent> {
+    Some(match block {
+        ContentBlock::Text(t) => AgentEvent::Text(t.text.clone()),
+        ContentBlock::Thinking(t) => AgentEvent::Reasoning(t.thinking.clone()),
+        ContentBlock::ToolUse(t) => AgentEvent::ToolCall {
+            id: t.id.clone(),
+            name: t.name.clone(),
+            input: t.input.clone(),
+        },
+        ContentBlock::ServerToolUse(t) => AgentEvent::ToolCall {
+            id: t.id.clone(),
+            name: t.name.clone(),
+            input: t.input.clone(),
+        },
+        ContentBlock::McpToolUse(t) => AgentEvent::ToolCall {
+            id: t.id.clone(),
+            name: t.name.clone(),
+            input: t.input.clone(),
+        },
+        ContentBlock::ToolResult(t) => AgentEvent::ToolResult {
+            id: t.tool_use_id.clone(),
+            output: match &t.content {
+                Some(ToolResultContent::Text(s)) => s.clone(),
+                Some(ToolResultContent::Structured(items)) => join_text(items),
+                Some(ToolResultContent::Spilled(large)) => large.preview.clone(),
+                None => String::new(),
+            },
+            is_error: t.is_error.unwrap_or(false),
+        },
+        ContentBlock::McpToolResult(t) => AgentEvent::ToolResult {
+            id: t.tool_use_id.clone(),
+            output: value_text(&t.content),
+            is_error: t.is_error.unwrap_or(false),
+        },
+        ContentBlock::WebSearchToolResult(t) => AgentEvent::ToolResult {
+            id: t.tool_use_id.clone(),
+            output: value_text(&t.content),
+            is_error: false,
+        },
+        _ => return None,
+    })
+}
+
+fn result_events(result: &ResultMessage) -> AgentEvents {
+    let mut events: Vec<AgentEvent> = result
+        .errors
+        .iter()
+        .map(|e| AgentEvent::Error(e.clone()))
+        .collect();
+    events.push(AgentEvent::TurnEnd {
+        success: !result.is_error,
+        duration_ms: Some(result.duration_ms),
+        total_cost_usd: Some(result.total_cost_usd),
+    });
+    AgentEvents(events)
+}
+
+/// Concatenate the `text` fields of `{"type":"text","text":...}` items.
+fn join_text(items: &[Value]) -> String {
+    items
+        .iter()
+        .filter_map(|item| item.get("text").and_then(Value::as_str))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => join_text(items),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Codex
+// ---------------------------------------------------------------------------
+
+impl From<&Notification> for AgentEvents {
+    fn from(notification: &Notification) -> Self {
+        match notification {
+            Notification::AgentMessageDelta(n) => AgentEvent::Text(n.delta.clone()).into(),
+            Notification::ReasoningTextDelta(n) => AgentEvent::Reasoning(n.delta.clone()).into(),
+            Notification::ReasoningDelta(n) => AgentEvent::Reasoning(n.delta.clone()).into(),
+            Notification::ItemStarted(n) => Self(item_started(&n.item).into_iter().collect()),
+            Notification::ItemCompleted(n) => Self(item_completed(&n.item).into_iter().collect()),
+            Notification::TurnCompleted(n) => AgentEvent::TurnEnd {
+                success: n.turn.status == TurnStatus::Completed,
+                duration_ms: n.turn.duration_ms.map(|ms| ms.max(0) as u64),
+                total_cost_usd: None,
+            }
+            .into(),
+            Notification::Error(n) => AgentEvent::Error(n.error.message.clone()).into(),
+            _ => Self::default(),
+        }
+    }
+}
+
+impl From<&ServerMessage> for AgentEvents {
+    fn from(message: &ServerMessage) -> Self {
+        match message {
+            ServerMessage::Notification(n) => n.into(),
+            ServerMessage::Request { .. } => Self::default(),
+        }
+    }
+}
+
+fn item_started(item: &ThreadItem) -> Option<AgentEvent> {
+    let (id, name, input) = match item {
+        ThreadItem::CommandExecution {
+            id, command, cwd, ..
+        } => (
+            id,
+            "commandExecution".to_string(),
+            json!({ "command": command, "cwd": cwd }),
+        ),
+        ThreadItem::FileChange { id, changes, .. } => (
+            id,
+            "fileChange".to_string(),
+            json!({ "paths": changes.iter().map(|c| &c.path).collect::<Vec<_>>() }),
+        ),
+        ThreadItem::McpToolCall {
+            id,
+            tool,
+            arguments,
+            ..
+        }
+        | ThreadItem::DynamicToolCall {
+            id,
+            tool,
+            arguments,
+            ..
+        } => (id, tool.clone(), arguments.clone()),
+        ThreadItem::WebSearch { id, query, .. } => {
+            (id, "webSearch".to_string(), json!({ "query": query }))
+        }
+        _ => return None,
+    };
+    Some(AgentEvent::ToolCall {
+        id: id.clone(),
+        name,
+        input,
+    })
+}
+
+fn item_completed(item: &ThreadItem) -> Option<AgentEvent> {
+    let (id, output, is_error) = match item {
+        ThreadItem::CommandExecution {
+            id,
+            aggregated_output,
+            exit_code,
+            status,
+            ..
+        } => (
+            id,
+            aggregated_output.clone().unwrap_or_default(),
+            matches!(
+                status,
+                CommandExecutionStatus::Failed | CommandExecutionStatus::Declined
+            ) || exit_code.is_some_and(|code| code != 0),
+        ),
+        ThreadItem::FileChange {
+            id,
+            changes,
+            status,
+        } => (
+            id,
+            changes
+                .iter()
+                .map(|c| c.diff.as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            matches!(
+                status,
+                PatchApplyStatus::Failed | PatchApplyStatus::Declined
+            ),
+        ),
+        ThreadItem::McpToolCall {
+            id,
+            result,
+            error,
+            status,
+            ..
+        } => match error {
+            Some(err) => (id, err.message.clone(), true),
+            None => (
+                id,
+                result
+                    .as_ref()
+                    .map(|r| join_text(&r.content))
+                    .unwrap_or_default(),
+                *status == McpToolCallStatus::Failed,
+            ),
+        },
+        ThreadItem::DynamicToolCall {
+            id,
+            content_items,
+            status,
+            success,
+            ..
+        } => (
+            id,
+            content_items
+                .iter()
+                .flatten()
+                .filter_map(|item| match item {
+                    DynamicToolCallOutputContentItem::InputText { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            *status == DynamicToolCallStatus::Failed || *success == Some(false),
+        ),
+        ThreadItem::WebSearch { id, .. } => (id, String::new(), false),
+        _ => return None,
+    };
+    Some(AgentEvent::ToolResult {
+        id: id.clone(),
+        output,
+        is_error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claude(value: Value) -> Vec<AgentEvent> {
+        let output: ClaudeOutput = serde_json::from_value(value).unwrap();
+        AgentEvents::from(&output).into_vec()
+    }
+
+    fn codex(method: &str, params: Value) -> Vec<AgentEvent> {
+        let notification = Notification::from_envelope(method, Some(params)).unwrap();
+        AgentEvents::from(&notification).into_vec()
+    }
+
+    #[test]
+    fn test_claude_assistant_and_tool_result() {
+        let events = claude(json!({
+            "type": "assistant",
+            "message": {
+                "id": "m", "role": "assistant", "model": "claude-3",
+                "content": [
+                    {"type": "thinking", "thinking": "list files", "signature": "s"},
+                    {"type": "text", "text": "Listing."},
+                    {"type": "tool_use", "id": "tu_1", "name": "Bash", "input": {"command": "ls"}}
+                ]
+            },
+            "session_id": "3c88babd-0bf8-55a8-9a73-98a084ec3a25"
+        }));
+        assert_eq!(
+            events,
+            vec![
+                AgentEvent::Reasoning("list files".into()),
+                AgentEvent::Text("Listing.".into()),
+                AgentEvent::ToolCall {
+                    id: "tu_1".into(),
+                    name: "Bash".into(),
+                    input: json!({"command": "ls"}),
+                },
+            ]
+        );
+
+        let events = claude(json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "tu_1",
+                 "content": [{"type": "text", "text": "Cargo.toml"}], "is_error": false}
+            ]}
+        }));
+        assert_eq!(
+            events,
+            vec![AgentEvent::ToolResult {
+                id: "tu_1".into(),
+                output: "Cargo.toml".into(),
+                is_error: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_claude_result_and_error() {
+        let events = claude(json!({
+            "type": "result", "subtype": "error_during_execution", "is_error": true,
+            "duration_ms": 1200, "duration_api_ms": 900, "num_turns": 1,
+            "session_id": "3c88babd-0bf8-55a8-9a73-98a084ec3a25", "total_cost_usd": 0.01,
+            "errors": ["boom"]
+        }));
+        assert_eq!(
+            events,
+            vec![
+                AgentEvent::Error("boom".into()),
+                AgentEvent::TurnEnd {
+                    success: false,
+                    duration_ms: Some(1200),
+                    total_cost_usd: Some(0.01),
+                },
+            ]
+        );
+
+        let events = claude(json!({
+            "type": "error",
+            "error": {"type": "overloaded_error", "message": "Overloaded"}
+        }));
+        assert_eq!(events, vec![AgentEvent::Error("Overloaded".into())]);
+    }
+
+    #[test]
+    fn test_codex_deltas_and_turn_end() {
+        let ids = json!({"itemId": "i", "threadId": "t", "turnId": "u"});
+        let mut delta = ids.clone();
+        delta["delta"] = json!("Hel");
+        assert_eq!(
+            codex("item/agentMessage/delta", delta.clone()),
+            vec![AgentEvent::Text("Hel".into())]
+        );
+        assert_eq!(
+            codex("item/reasoning/textDelta", delta),
+            vec![AgentEvent::Reasoning("Hel".into())]
+        );
+
+        // Completed messages repeat the deltas and are skipped.
+        let completed = json!({
+            "item": {"type": "agentMessage", "id": "i", "text": "Hello"},
+            "threadId": "t", "turnId": "u"
+        });
+        assert!(codex("item/completed", completed).is_empty());
+
+        let events = codex(
+            "turn/completed",
+            json!({"threadId": "t", "turn": {"id": "u", "status": "interrupted", "durationMs": 50}}),
+        );
+        assert_eq!(
+            events,
+            vec![AgentEvent::TurnEnd {
+                success: false,
+                duration_ms: Some(50),
+                total_cost_usd: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_codex_command_execution() {
+        let item = json!({
+            "type": "commandExecution", "id": "cmd_1", "command": "ls", "cwd": "/tmp",
+            "commandActions": [], "status": "inProgress"
+        });
+        let events = codex(
+            "item/started",
+            json!({"item": item, "threadId": "t", "turnId": "u"}),
+        );
+        assert_eq!(
+            events,
+            vec![AgentEvent::ToolCall {
+                id: "cmd_1".into(),
+                name: "commandExecution".into(),
+                input: json!({"command": "ls", "cwd": "/tmp"}),
+            }]
+        );
+
+        let mut item = item;
+        item["status"] = json!("completed");
+        item["exitCode"] = json!(2);
+        item["aggregatedOutput"] = json!("ls: missing");
+        let events = codex(
+            "item/completed",
+            json!({"item": item, "threadId": "t", "turnId": "u"}),
+        );
+        assert_eq!(
+            events,
+            vec![AgentEvent::ToolResult {
+                id: "cmd_1".into(),
+                output: "ls: missing".into(),
+                is_error: true,
+            }]
+        );
+    }
+}
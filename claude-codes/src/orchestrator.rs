@@ -0,0 +1,462 @@
+//! Run one task on several agent backends in parallel (requires the
+//! `orchestrator` feature).
+//!
+//! An [`Orchestrator`] holds a list of labelled [`Backend`]s — Claude CLI
+//! builders and Codex app-server builders — and [`run`](Orchestrator::run)
+//! sends the same prompt to all of them concurrently. Each backend's messages
+//! are normalized into [`AgentEvent`]s, and the [`OrchestratorReport`] records
+//! per-backend output, cost, and latency: the raw material for A/B
+//! evaluation of coding agents.
+//!
+//! ```ignore
+//! use claude_codes::orchestrator::Orchestrator;
+//! use claude_codes::ClaudeCliBuilder;
+//! use codex_codes::AppServerBuilder;
+//!
+//! let report = Orchestrator::new()
+//!     .claude("sonnet", ClaudeCliBuilder::new().model("sonnet"))
+//!     .codex("codex", AppServerBuilder::new())
+//!     .timeout(std::time::Duration::from_secs(600))
+//!     .run("Add a --verbose flag to src/main.rs")
+//!     .await;
+//!
+//! for run in &report.runs {
+//!     println!("{}: {:?} in {:?}", run.label, run.total_cost_usd, run.latency);
+//! }
+//! ```
+//!
+//! Runs are headless: Codex approval requests are declined, so configure the
+//! approval policy you want to evaluate on the builder (for example
+//! `config_override("approval_policy", "\"never\"")`), and give each backend
+//! its own working directory if they edit files.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use codex_codes::{
+    AppServerBuilder, CommandExecutionApprovalDecision, CommandExecutionRequestApprovalResponse,
+    FileChangeApprovalDecision, FileChangeRequestApprovalResponse, Notification, RequestId,
+    ServerMessage, ServerRequest, ThreadItem,
+};
+use serde_json::json;
+use tokio::task::JoinSet;
+
+use crate::cli::ClaudeCliBuilder;
+use crate::client_async::AsyncClient;
+use crate::io::ClaudeOutput;
+use crate::normalize::{AgentEvent, AgentEvents};
+
+/// An agent backend the orchestrator can drive.
+#[derive(Debug, Clone)]
+pub enum Backend {
+    /// The Claude CLI, spawned from this builder (boxed; it is several
+    /// times larger than the Codex builder).
+    Claude(Box<ClaudeCliBuilder>),
+    /// A Codex app-server, spawned from this builder.
+    Codex(AppServerBuilder),
+}
+
+impl Backend {
+    /// Which SDK drives this backend.
+    pub fn kind(&self) -> BackendKind {
+        match self {
+            Self::Claude(_) => BackendKind::Claude,
+            Self::Codex(_) => BackendKind::Codex,
+        }
+    }
+}
+
+/// The SDK behind a [`BackendRun`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BackendKind {
+    Claude,
+    Codex,
+}
+
+impl BackendKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Claude => "claude",
+            Self::Codex => "codex",
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Runs a prompt on several backends concurrently.
+#[derive(Debug, Clone, Default)]
+pub struct Orchestrator {
+    backends: Vec<(String, Backend)>,
+    timeout: Option<Duration>,
+}
+
+impl Orchestrator {
+    /// An orchestrator with no backends and no timeout.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a backend under `label`. Labels identify runs in the report and
+    /// should be unique.
+    pub fn backend(mut self, label: impl Into<String>, backend: Backend) -> Self {
+        self.backends.push((label.into(), backend));
+        self
+    }
+
+    /// Add a Claude CLI backend.
+    pub fn claude(self, label: impl Into<String>, builder: ClaudeCliBuilder) -> Self {
+        self.backend(label, Backend::Claude(Box::new(builder)))
+    }
+
+    /// Add a Codex app-server backend.
+    pub fn codex(self, label: impl Into<String>, builder: AppServerBuilder) -> Self {
+        self.backend(label, Backend::Codex(builder))
+    }
+
+    /// Stop waiting for a backend after `timeout`. Its run is reported with
+    /// the events received so far and an error.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send `prompt` to every backend in parallel and wait for all turns to
+    /// end.
+    ///
+    /// Backend failures don't abort the others; they are recorded in
+    /// [`BackendRun::error`]. Runs are reported in the order the backends
+    /// were added.
+    pub async fn run(&self, prompt: &str) -> OrchestratorReport {
+        let mut tasks = JoinSet::new();
+        for (index, (label, backend)) in self.backends.iter().enumerate() {
+            let label = label.clone();
+            let backend = backend.clone();
+            let prompt = prompt.to_string();
+            let timeout = self.timeout;
+            tasks.spawn(async move { (index, run_backend(label, backend, prompt, timeout).await) });
+        }
+
+        let mut runs: Vec<Option<BackendRun>> = vec![None; self.backends.len()];
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((index, run)) => runs[index] = Some(run),
+                Err(e) => log::error!("Orchestrator task failed: {}", e),
+            }
+        }
+
+        let runs = runs
+            .into_iter()
+            .zip(&self.backends)
+            .map(|(run, (label, backend))| {
+                run.unwrap_or_else(|| {
+                    let mut run = BackendRun::new(label.clone(), backend.kind());
+                    run.error = Some("backend task panicked".to_string());
+                    run
+                })
+            })
+            .collect();
+        OrchestratorReport { runs }
+    }
+}
+
+/// The outcome of one backend's turn.
+#[derive(Debug, Clone)]
+pub struct BackendRun {
+    pub label: String,
+    pub kind: BackendKind,
+    /// Every normalized event the backend produced, in order.
+    pub events: Vec<AgentEvent>,
+    /// The final answer: Claude's result text, or Codex's last agent message.
+    pub output: Option<String>,
+    /// Only reported by Claude.
+    pub total_cost_usd: Option<f64>,
+    /// Wall-clock time from spawning the backend to the end of the turn.
+    pub latency: Duration,
+    /// Wall-clock time from spawning the backend to its first event.
+    pub time_to_first_event: Option<Duration>,
+    /// Why the run stopped early: spawn failure, protocol error, or timeout.
+    pub error: Option<String>,
+}
+
+impl BackendRun {
+    fn new(label: String, kind: BackendKind) -> Self {
+        Self {
+            label,
+            kind,
+            events: Vec::new(),
+            output: None,
+            total_cost_usd: None,
+            latency: Duration::ZERO,
+            time_to_first_event: None,
+            error: None,
+        }
+    }
+
+    /// `true` if the run finished without error and the turn succeeded.
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+            && self
+                .events
+                .iter()
+                .any(|e| matches!(e, AgentEvent::TurnEnd { success: true, .. }))
+    }
+
+    /// Number of tool calls the backend made.
+    pub fn tool_calls(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|e| matches!(e, AgentEvent::ToolCall { .. }))
+            .count()
+    }
+
+    fn record(&mut self, events: AgentEvents, started: Instant) {
+        if self.time_to_first_event.is_none() && !events.is_empty() {
+            self.time_to_first_event = Some(started.elapsed());
+        }
+        self.events.extend(events);
+    }
+}
+
+/// The result of [`Orchestrator::run`].
+#[derive(Debug, Clone)]
+pub struct OrchestratorReport {
+    /// One entry per backend, in the order they were added.
+    pub runs: Vec<BackendRun>,
+}
+
+impl OrchestratorReport {
+    /// The run with this label.
+    pub fn get(&self, label: &str) -> Option<&BackendRun> {
+        self.runs.iter().find(|r| r.label == label)
+    }
+
+    /// The successful run with the lowest latency.
+    pub fn fastest(&self) -> Option<&BackendRun> {
+        self.runs
+            .iter()
+            .filter(|r| r.is_success())
+            .min_by_key(|r| r.latency)
+    }
+
+    /// The successful run with the lowest reported cost.
+    pub fn cheapest(&self) -> Option<&BackendRun> {
+        self.runs
+            .iter()
+            .filter(|r| r.is_success())
+            .filter_map(|r| r.total_cost_usd.map(|cost| (r, cost)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(r, _)| r)
+    }
+}
+
+async fn run_backend(
+    label: String,
+    backend: Backend,
+    prompt: String,
+    timeout: Option<Duration>,
+) -> BackendRun {
+    let mut run = BackendRun::new(label, backend.kind());
+    let started = Instant::now();
+    let turn = async {
+        match backend {
+            Backend::Claude(builder) => run_claude(*builder, &prompt, &mut run, started)
+                .await
+                .map_err(|e| e.to_string()),
+            Backend::Codex(builder) => run_codex(builder, &prompt, &mut run, started)
+                .await
+                .map_err(|e| e.to_string()),
+        }
+    };
+    let result = match timeout {
+        Some(limit) => tokio::time::timeout(limit, turn)
+            .await
+            .unwrap_or_else(|_| Err(format!("timed out after {:?}", limit))),
+        None => turn.await,
+    };
+    run.latency = started.elapsed();
+    run.error = result.err();
+    run
+}
+
+async fn run_claude(
+    builder: ClaudeCliBuilder,
+    prompt: &str,
+    run: &mut BackendRun,
+    started: Instant,
+) -> crate::Result<()> {
+    let mut client = AsyncClient::from_builder(builder).await?;
+    let mut stream = client.query_stream(prompt).await?;
+    while let Some(output) = stream.next().await {
+        let output = output?;
+        if let ClaudeOutput::Result(result) = &output {
+            run.output = result.result.clone();
+            run.total_cost_usd = Some(result.total_cost_usd);
+        }
+        run.record(AgentEvents::from(&output), started);
+    }
+    client.shutdown().await
+}
+
+async fn run_codex(
+    builder: AppServerBuilder,
+    prompt: &str,
+    run: &mut BackendRun,
+    started: Instant,
+) -> codex_codes::Result<()> {
+    let mut client = codex_codes::AsyncClient::start_with(builder).await?;
+    let thread = client
+        .thread_start(&serde_json::from_value(json!({}))?)
+        .await?;
+    client
+        .turn_start(&serde_json::from_value(json!({
+            "threadId": thread.thread.id,
+            "input": [{"type": "text", "text": prompt}],
+        }))?)
+        .await?;
+
+    while let Some(message) = client.next_message().await? {
+        if let ServerMessage::Request { id, request } = &message {
+            decline(&mut client, id.clone(), request).await?;
+            continue;
+        }
+        let ServerMessage::Notification(notification) = &message else {
+            continue;
+        };
+        if let Notification::ItemCompleted(n) = notification {
+            if let ThreadItem::AgentMessage { text, .. } = &n.item {
+                run.output = Some(text.clone());
+            }
+        }
+        run.record(AgentEvents::from(notification), started);
+        if matches!(notification, Notification::TurnCompleted(_)) {
+            break;
+        }
+    }
+    client.shutdown().await
+}
+
+async fn decline(
+    client: &mut codex_codes::AsyncClient,
+    id: RequestId,
+    request: &ServerRequest,
+) -> codex_codes::Result<()> {
+    match request {
+        ServerRequest::CmdExecApproval(_) => {
+            client
+                .respond(
+                    id,
+                    &CommandExecutionRequestApprovalResponse {
+                        decision: CommandExecutionApprovalDecision::Decline,
+                    },
+                )
+                .await
+        }
+        ServerRequest::FileChangeApproval(_) => {
+            client
+                .respond(
+                    id,
+                    &FileChangeRequestApprovalResponse {
+                        decision: FileChangeApprovalDecision::Decline,
+                    },
+                )
+                .await
+        }
+        other => {
+            client
+                .respond_error(
+                    id,
+                    -32601,
+                    &format!("{} is not supported by the orchestrator", other.method()),
+                )
+                .await
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_cli;
+    use std::path::PathBuf;
+
+    /// A stand-in Claude CLI that answers each input with a tool call, its
+    /// result, and a final result message.
+    fn fake_claude() -> PathBuf {
+        let session = "622ae0c3-3d50-4fa7-9ee0-69d691238c6d";
+        fake_cli(&format!(
+            r#"#!/bin/sh
+while read -r line; do
+  echo '{{"type":"assistant","message":{{"id":"m","role":"assistant","model":"c","content":[{{"type":"tool_use","id":"tu_1","name":"Bash","input":{{"command":"ls"}}}}]}},"session_id":"{session}"}}'
+  echo '{{"type":"user","message":{{"role":"user","content":[{{"type":"tool_result","tool_use_id":"tu_1","content":"a.rs"}}]}},"session_id":"{session}"}}'
+  echo '{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"one file","session_id":"{session}","total_cost_usd":0.02}}'
+done
+"#
+        ))
+    }
+
+    /// A stand-in Codex app-server that answers the handshake, one thread,
+    /// and one turn that streams a single agent message.
+    fn fake_codex() -> PathBuf {
+        fake_cli(
+            r#"#!/bin/sh
+while read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      echo "{\"id\":$id,\"result\":{\"codexHome\":\"/tmp\",\"platformFamily\":\"unix\",\"platformOs\":\"linux\",\"userAgent\":\"fake\"}}" ;;
+    *'"method":"thread/start"'*)
+      echo "{\"id\":$id,\"result\":{\"approvalPolicy\":\"never\",\"cwd\":\"/tmp\",\"thread\":{\"id\":\"th_1\"}}}" ;;
+    *'"method":"turn/start"'*)
+      echo "{\"id\":$id,\"result\":{\"turn\":{\"id\":\"tu_1\",\"status\":\"inProgress\"}}}"
+      echo '{"method":"item/agentMessage/delta","params":{"delta":"one file","itemId":"i","threadId":"th_1","turnId":"tu_1"}}'
+      echo '{"method":"item/completed","params":{"item":{"type":"agentMessage","id":"i","text":"one file"},"threadId":"th_1","turnId":"tu_1"}}'
+      echo '{"method":"turn/completed","params":{"threadId":"th_1","turn":{"id":"tu_1","status":"completed"}}}' ;;
+  esac
+done
+"#,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_run_collects_every_backend() {
+        let claude = fake_claude();
+        let codex = fake_codex();
+        let report = Orchestrator::new()
+            .claude("claude", ClaudeCliBuilder::new().command(&claude))
+            .codex("codex", AppServerBuilder::new().command(&codex))
+            .claude(
+                "missing",
+                ClaudeCliBuilder::new().command("/nonexistent/claude"),
+            )
+            .timeout(Duration::from_secs(30))
+            .run("How many files?")
+            .await;
+
+        let labels: Vec<&str> = report.runs.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, vec!["claude", "codex", "missing"]);
+
+        let claude_run = report.get("claude").unwrap();
+        assert!(claude_run.is_success(), "{:?}", claude_run.error);
+        assert_eq!(claude_run.tool_calls(), 1);
+        assert_eq!(claude_run.output.as_deref(), Some("one file"));
+        assert_eq!(claude_run.total_cost_usd, Some(0.02));
+
+        let codex_run = report.get("codex").unwrap();
+        assert!(codex_run.is_success(), "{:?}", codex_run.error);
+        assert_eq!(codex_run.kind, BackendKind::Codex);
+        assert_eq!(codex_run.output.as_deref(), Some("one file"));
+        assert_eq!(codex_run.events[0], AgentEvent::Text("one file".into()));
+
+        assert!(report.get("missing").unwrap().error.is_some());
+        assert_eq!(report.cheapest().unwrap().label, "claude");
+        assert!(report.fastest().is_some());
+
+        std::fs::remove_file(claude).ok();
+        std::fs::remove_file(codex).ok();
+    }
+}
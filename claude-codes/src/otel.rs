@@ -0,0 +1,467 @@
+//! OpenTelemetry tracing for Claude sessions.
+//!
+//! Enabled with the `otel` feature. Each [`AsyncClient`](crate::AsyncClient) and
+//! [`SyncClient`](crate::SyncClient) owns a [`SessionTracer`] that emits:
+//!
+//! - a `claude.session` span covering the lifetime of the client, and
+//! - a `claude.turn` child span for each user message, closed when the
+//!   matching `result` message arrives.
+//!
+//! Spans are created with the globally registered tracer provider
+//! (`opentelemetry::global::set_tracer_provider`), so applications that already
+//! export traces get Claude sessions in their collector without extra wiring.
+//!
+//! # Attributes
+//!
+//! | Attribute | Span | Source |
+//! |-----------|------|--------|
+//! | `gen_ai.system` | both | always `"anthropic"` |
+//! | `gen_ai.request.model` | both | init message / assistant messages |
+//! | `claude.session_id` | both | first message carrying a session ID |
+//! | `gen_ai.usage.input_tokens` | both | [`ResultMessage::usage`] |
+//! | `gen_ai.usage.output_tokens` | both | [`ResultMessage::usage`] |
+//! | `claude.usage.cache_read_input_tokens` | both | [`ResultMessage::usage`] |
+//! | `claude.usage.cache_creation_input_tokens` | both | [`ResultMessage::usage`] |
+//! | `claude.cost_usd` | both | [`ResultMessage::total_cost_usd`] (the CLI's running total; a turn gets its increase) |
+//! | `claude.tool_calls` | both | `tool_use` blocks in assistant messages |
+//! | `claude.num_turns` | turn | [`ResultMessage::num_turns`] |
+//! | `claude.turns` | session | number of completed turns |
+//! | `claude.result.subtype` | turn | [`ResultMessage::subtype`] |
+//! | `claude.is_error` | turn | [`ResultMessage::is_error`] |
+//!
+//! The tracer can also be driven by hand when reading messages without one of
+//! the built-in clients:
+//!
+//! ```
+//! use claude_codes::otel::SessionTracer;
+//! use claude_codes::{ClaudeInput, ClaudeOutput};
+//!
+//! let mut tracer = SessionTracer::new();
+//! tracer.record_input(&ClaudeInput::user_message("hi", uuid::Uuid::new_v4()));
+//! let output: ClaudeOutput = serde_json::from_str(r#"{
+//!     "type": "result", "subtype": "success", "is_error": false,
+//!     "duration_ms": 10, "duration_api_ms": 8, "num_turns": 1,
+//!     "session_id": "abc", "total_cost_usd": 0.01
+//! }"#).unwrap();
+//! tracer.record_output(&output);
+//! tracer.end();
+//! ```
+//!
+//! [`ResultMessage::usage`]: crate::ResultMessage::usage
+//! [`ResultMessage::total_cost_usd`]: crate::ResultMessage::total_cost_usd
+//! [`ResultMessage::num_turns`]: crate::ResultMessage::num_turns
+//! [`ResultMessage::subtype`]: crate::ResultMessage::subtype
+//! [`ResultMessage::is_error`]: crate::ResultMessage::is_error
+
+use crate::io::{ClaudeInput, ClaudeOutput, ResultMessage};
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::trace::{Status, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+
+/// Instrumentation scope name used for all spans emitted by this crate.
+pub const TRACER_NAME: &str = "claude-codes";
+
+/// Span name for the session-level span.
+pub const SESSION_SPAN_NAME: &str = "claude.session";
+
+/// Span name for per-turn spans.
+pub const TURN_SPAN_NAME: &str = "claude.turn";
+
+/// Running totals attached to a span when it ends.
+#[derive(Debug, Default, Clone)]
+struct Totals {
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_read_input_tokens: u64,
+    cache_creation_input_tokens: u64,
+    cost_usd: f64,
+    tool_calls: u64,
+}
+
+impl Totals {
+    /// Add a result's usage and `cost_usd`, the turn's share of the
+    /// session's running cost.
+    fn add_result(&mut self, result: &ResultMessage, cost_usd: f64) {
+        if let Some(usage) = &result.usage {
+            self.input_tokens += u64::from(usage.input_tokens);
+            self.output_tokens += u64::from(usage.output_tokens);
+            self.cache_read_input_tokens += u64::from(usage.cache_read_input_tokens);
+            self.cache_creation_input_tokens += u64::from(usage.cache_creation_input_tokens);
+        }
+        self.cost_usd += cost_usd;
+    }
+
+    fn attributes(&self) -> Vec<KeyValue> {
+        vec![
+            KeyValue::new("gen_ai.usage.input_tokens", self.input_tokens as i64),
+            KeyValue::new("gen_ai.usage.output_tokens", self.output_tokens as i64),
+            KeyValue::new(
+                "claude.usage.cache_read_input_tokens",
+                self.cache_read_input_tokens as i64,
+            ),
+            KeyValue::new(
+                "claude.usage.cache_creation_input_tokens",
+                self.cache_creation_input_tokens as i64,
+            ),
+            KeyValue::new("claude.cost_usd", self.cost_usd),
+            KeyValue::new("claude.tool_calls", self.tool_calls as i64),
+        ]
+    }
+}
+
+/// An in-flight `claude.turn` span.
+struct Turn {
+    cx: Context,
+    totals: Totals,
+}
+
+/// Emits OpenTelemetry spans for a Claude session from the messages flowing
+/// through it.
+///
+/// Feed every outgoing [`ClaudeInput`] to [`record_input`](Self::record_input)
+/// and every incoming [`ClaudeOutput`] to [`record_output`](Self::record_output).
+/// The built-in clients do this automatically when the `otel` feature is on.
+/// The session span is ended by [`end`](Self::end) or when the tracer is dropped.
+pub struct SessionTracer {
+    tracer: BoxedTracer,
+    session: Option<Context>,
+    turn: Option<Turn>,
+    totals: Totals,
+    turns: u64,
+    session_id: Option<String>,
+    model: Option<String>,
+}
+
+impl SessionTracer {
+    /// Create a tracer backed by the global tracer provider.
+    pub fn new() -> Self {
+        Self::with_tracer(global::tracer(TRACER_NAME))
+    }
+
+    /// Create a tracer backed by an explicit tracer instead of the global one.
+    pub fn with_tracer(tracer: BoxedTracer) -> Self {
+        let span = tracer.start(SESSION_SPAN_NAME);
+        let session = Context::new().with_span(span);
+        session
+            .span()
+            .set_attribute(KeyValue::new("gen_ai.system", "anthropic"));
+        Self {
+            tracer,
+            session: Some(session),
+            turn: None,
+            totals: Totals::default(),
+            turns: 0,
+            session_id: None,
+            model: None,
+        }
+    }
+
+    /// Record a message sent to Claude. User messages open a new turn span,
+    /// closing any turn that never received a result.
+    pub fn record_input(&mut self, input: &ClaudeInput) {
+        if !matches!(input, ClaudeInput::User(_)) {
+            return;
+        }
+        self.finish_turn(None);
+        let Some(session) = &self.session else {
+            return;
+        };
+
+        let span = self.tracer.start_with_context(TURN_SPAN_NAME, session);
+        let cx = session.with_span(span);
+        cx.span()
+            .set_attribute(KeyValue::new("gen_ai.system", "anthropic"));
+        if let Some(id) = &self.session_id {
+            cx.span()
+                .set_attribute(KeyValue::new("claude.session_id", id.clone()));
+        }
+        if let Some(model) = &self.model {
+            cx.span()
+                .set_attribute(KeyValue::new("gen_ai.request.model", model.clone()));
+        }
+        self.turn = Some(Turn {
+            cx,
+            totals: Totals::default(),
+        });
+    }
+
+    /// Record a message received from Claude.
+    pub fn record_output(&mut self, output: &ClaudeOutput) {
+        if self.session.is_none() {
+            return;
+        }
+
+        if self.session_id.is_none() {
+            if let Some(id) = output.session_id() {
+                self.session_id = Some(id.to_string());
+                self.set_attribute(KeyValue::new("claude.session_id", id.to_string()));
+            }
+        }
+
+        match output {
+            ClaudeOutput::System(sys) => {
+                if let Some(model) = sys.as_init().and_then(|init| init.model) {
+                    self.set_model(&model);
+                }
+            }
+            ClaudeOutput::Assistant(msg) => {
+                self.set_model(&msg.message.model);
+                let calls = output.tool_uses().count() as u64;
+                self.totals.tool_calls += calls;
+                if let Some(turn) = &mut self.turn {
+                    turn.totals.tool_calls += calls;
+                }
+            }
+            ClaudeOutput::Result(result) => {
+                let cost = (result.total_cost_usd - self.totals.cost_usd).max(0.0);
+                self.totals.add_result(result, cost);
+                self.turns += 1;
+                self.finish_turn(Some((result, cost)));
+            }
+            _ => {}
+        }
+    }
+
+    /// End the open turn (if any) and the session span.
+    ///
+    /// Calling this more than once is a no-op.
+    pub fn end(&mut self) {
+        self.finish_turn(None);
+        if let Some(session) = self.session.take() {
+            let span = session.span();
+            for kv in self.totals.attributes() {
+                span.set_attribute(kv);
+            }
+            span.set_attribute(KeyValue::new("claude.turns", self.turns as i64));
+            span.end();
+        }
+    }
+
+    fn set_model(&mut self, model: &str) {
+        if model.is_empty() || self.model.as_deref() == Some(model) {
+            return;
+        }
+        self.model = Some(model.to_string());
+        self.set_attribute(KeyValue::new("gen_ai.request.model", model.to_string()));
+    }
+
+    /// Set an attribute on the session span and the open turn span.
+    fn set_attribute(&self, kv: KeyValue) {
+        if let Some(turn) = &self.turn {
+            turn.cx.span().set_attribute(kv.clone());
+        }
+        if let Some(session) = &self.session {
+            session.span().set_attribute(kv);
+        }
+    }
+
+    fn finish_turn(&mut self, result: Option<(&ResultMessage, f64)>) {
+        let Some(mut turn) = self.turn.take() else {
+            return;
+        };
+        let span = turn.cx.span();
+        if let Some((result, cost)) = result {
+            turn.totals.add_result(result, cost);
+            span.set_attribute(KeyValue::new("claude.num_turns", result.num_turns as i64));
+            span.set_attribute(KeyValue::new(
+                "claude.result.subtype",
+                result.subtype.to_string(),
+            ));
+            span.set_attribute(KeyValue::new("claude.is_error", result.is_error));
+            if result.is_error {
+                let description = result
+                    .errors
+                    .first()
+                    .cloned()
+                    .or_else(|| result.result.clone())
+                    .unwrap_or_else(|| "Claude turn failed".to_string());
+                span.set_status(Status::error(description));
+            }
+        }
+        for kv in turn.totals.attributes() {
+            span.set_attribute(kv);
+        }
+        span.end();
+    }
+}
+
+impl Default for SessionTracer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SessionTracer {
+    fn drop(&mut self) {
+        self.end();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::Value;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider, SpanData};
+    use uuid::Uuid;
+
+    fn tracer() -> (SessionTracer, InMemorySpanExporter, SdkTracerProvider) {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let tracer = BoxedTracer::new(Box::new(provider.tracer(TRACER_NAME)));
+        (SessionTracer::with_tracer(tracer), exporter, provider)
+    }
+
+    fn attr<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| &kv.value)
+    }
+
+    fn parse(json: &str) -> ClaudeOutput {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn assistant_with_tool() -> ClaudeOutput {
+        parse(
+            r#"{
+                "type": "assistant",
+                "message": {
+                    "id": "msg_1", "role": "assistant", "model": "claude-sonnet-4",
+                    "content": [
+                        {"type": "text", "text": "Listing files"},
+                        {"type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {"command": "ls"}}
+                    ]
+                },
+                "session_id": "sess-1"
+            }"#,
+        )
+    }
+
+    fn result(is_error: bool) -> ClaudeOutput {
+        result_costing(is_error, 0.25)
+    }
+
+    fn result_costing(is_error: bool, total_cost_usd: f64) -> ClaudeOutput {
+        parse(&format!(
+            r#"{{
+                "type": "result", "subtype": "success", "is_error": {is_error},
+                "duration_ms": 10, "duration_api_ms": 8, "num_turns": 2,
+                "result": "done", "session_id": "sess-1", "total_cost_usd": {total_cost_usd},
+                "usage": {{"input_tokens": 100, "output_tokens": 40,
+                           "cache_read_input_tokens": 7, "cache_creation_input_tokens": 3}}
+            }}"#
+        ))
+    }
+
+    #[test]
+    fn test_turn_span_carries_usage_cost_model_and_tool_calls() {
+        let (mut tracer, exporter, _provider) = tracer();
+        tracer.record_input(&ClaudeInput::user_message("hi", Uuid::new_v4()));
+        tracer.record_output(&assistant_with_tool());
+        tracer.record_output(&result(false));
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 1);
+        let turn = &spans[0];
+        assert_eq!(turn.name, TURN_SPAN_NAME);
+        assert_eq!(
+            attr(turn, "gen_ai.request.model"),
+            Some(&Value::from("claude-sonnet-4"))
+        );
+        assert_eq!(
+            attr(turn, "gen_ai.usage.input_tokens"),
+            Some(&Value::I64(100))
+        );
+        assert_eq!(
+            attr(turn, "gen_ai.usage.output_tokens"),
+            Some(&Value::I64(40))
+        );
+        assert_eq!(attr(turn, "claude.cost_usd"), Some(&Value::F64(0.25)));
+        assert_eq!(attr(turn, "claude.tool_calls"), Some(&Value::I64(1)));
+        assert_eq!(attr(turn, "claude.num_turns"), Some(&Value::I64(2)));
+        assert_eq!(
+            attr(turn, "claude.session_id"),
+            Some(&Value::from("sess-1"))
+        );
+        assert_eq!(turn.status, Status::Unset);
+    }
+
+    #[test]
+    fn test_session_span_aggregates_turns() {
+        let (mut tracer, exporter, _provider) = tracer();
+        for _ in 0..2 {
+            tracer.record_input(&ClaudeInput::user_message("hi", Uuid::new_v4()));
+            tracer.record_output(&assistant_with_tool());
+            tracer.record_output(&result(false));
+        }
+        tracer.end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 3);
+        let session = spans.iter().find(|s| s.name == SESSION_SPAN_NAME).unwrap();
+        assert_eq!(attr(session, "claude.turns"), Some(&Value::I64(2)));
+        assert_eq!(attr(session, "claude.tool_calls"), Some(&Value::I64(2)));
+        assert_eq!(
+            attr(session, "gen_ai.usage.input_tokens"),
+            Some(&Value::I64(200))
+        );
+
+        let session_span_id = session.span_context.span_id();
+        assert!(spans
+            .iter()
+            .filter(|s| s.name == TURN_SPAN_NAME)
+            .all(|s| s.parent_span_id == session_span_id));
+    }
+
+    #[test]
+    fn test_session_cost_is_latest_running_total() {
+        let (mut tracer, exporter, _provider) = tracer();
+        for total in [0.25, 0.75] {
+            tracer.record_input(&ClaudeInput::user_message("hi", Uuid::new_v4()));
+            tracer.record_output(&result_costing(false, total));
+        }
+        tracer.end();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let costs: Vec<_> = spans.iter().map(|s| attr(s, "claude.cost_usd")).collect();
+        assert_eq!(
+            costs,
+            [
+                Some(&Value::F64(0.25)),
+                Some(&Value::F64(0.5)),
+                Some(&Value::F64(0.75))
+            ]
+        );
+        assert_eq!(spans[2].name, SESSION_SPAN_NAME);
+    }
+
+    #[test]
+    fn test_error_result_sets_error_status() {
+        let (mut tracer, exporter, _provider) = tracer();
+        tracer.record_input(&ClaudeInput::user_message("hi", Uuid::new_v4()));
+        tracer.record_output(&result(true));
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert!(matches!(spans[0].status, Status::Error { .. }));
+        assert_eq!(attr(&spans[0], "claude.is_error"), Some(&Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_drop_ends_open_spans() {
+        let (mut tracer, exporter, _provider) = tracer();
+        tracer.record_input(&ClaudeInput::user_message("hi", Uuid::new_v4()));
+        drop(tracer);
+
+        let names: Vec<_> = exporter
+            .get_finished_spans()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, vec![TURN_SPAN_NAME, SESSION_SPAN_NAME]);
+    }
+}
@@ -0,0 +1,322 @@
+//! Chain agent turns into a pipeline.
+//!
+//! A [`Pipeline`] runs [`Stage`]s in order, each in a fresh Claude session
+//! with its own model, permission mode, and system prompt. A stage's prompt is
+//! a template: `{{name}}` is replaced with the output of the earlier stage
+//! called `name` (or a variable set with [`Pipeline::var`]), and
+//! `{{name.field.0}}` selects into JSON output. Stages with an
+//! [`output_schema`](Stage::output_schema) answer with schema-validated JSON
+//! (the same structured output [`AsyncClient::query_typed`] reads), so later
+//! stages can pick out fields; stages without one produce their result text.
+//!
+//! ```ignore
+//! use claude_codes::pipeline::{Pipeline, Stage};
+//! use claude_codes::PermissionMode;
+//! use serde_json::json;
+//!
+//! let output = Pipeline::new()
+//!     .var("task", "Add a --verbose flag to the CLI")
+//!     .stage(
+//!         Stage::new("plan", "Plan this change as a list of steps: {{task}}")
+//!             .permission_mode(PermissionMode::Plan)
+//!             .output_schema(json!({
+//!                 "type": "object",
+//!                 "properties": {"steps": {"type": "array", "items": {"type": "string"}}},
+//!                 "required": ["steps"]
+//!             })),
+//!     )
+//!     .stage(
+//!         Stage::new("implement", "Implement these steps:\n{{plan.steps}}")
+//!             .permission_mode(PermissionMode::AcceptEdits),
+//!     )
+//!     .stage(Stage::new("review", "Review the change you just made: {{implement}}").model("opus"))
+//!     .run()
+//!     .await?;
+//!
+//! println!("{}", output.last().unwrap().output);
+//! ```
+
+use serde_json::{Map, Value};
+
+use crate::cli::{ClaudeCliBuilder, PermissionMode};
+use crate::client_async::AsyncClient;
+use crate::error::{Error, Result};
+use crate::io::{ClaudeOutput, ResultMessage};
+
+/// One agent turn in a [`Pipeline`].
+#[derive(Debug, Clone)]
+pub struct Stage {
+    name: String,
+    prompt: String,
+    builder: ClaudeCliBuilder,
+    schema: Option<Value>,
+}
+
+impl Stage {
+    /// A stage called `name` whose prompt is rendered from `prompt_template`.
+    pub fn new(name: impl Into<String>, prompt_template: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            prompt: prompt_template.into(),
+            builder: ClaudeCliBuilder::new(),
+            schema: None,
+        }
+    }
+
+    /// Spawn this stage's session from `builder` instead of the default one.
+    pub fn builder(mut self, builder: ClaudeCliBuilder) -> Self {
+        self.builder = builder;
+        self
+    }
+
+    /// Set the model for this stage.
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.builder = self.builder.model(model);
+        self
+    }
+
+    /// Set the permission mode for this stage.
+    pub fn permission_mode(mut self, mode: PermissionMode) -> Self {
+        self.builder = self.builder.permission_mode(mode);
+        self
+    }
+
+    /// Append to the system prompt for this stage.
+    pub fn append_system_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.builder = self.builder.append_system_prompt(prompt);
+        self
+    }
+
+    /// Require a JSON answer matching `schema`. The stage's output is then
+    /// the structured JSON rather than the result text.
+    pub fn output_schema(mut self, schema: Value) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// The stage name, used to reference its output in later templates.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A sequence of [`Stage`]s whose outputs feed later prompts.
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    stages: Vec<Stage>,
+    vars: Map<String, Value>,
+}
+
+impl Pipeline {
+    /// An empty pipeline.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a stage.
+    pub fn stage(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Set a template variable available to every stage.
+    pub fn var(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.vars.insert(name.into(), value.into());
+        self
+    }
+
+    /// Run every stage in order, stopping at the first failure.
+    pub async fn run(&self) -> Result<PipelineOutput> {
+        let mut vars = self.vars.clone();
+        let mut stages = Vec::with_capacity(self.stages.len());
+
+        for stage in &self.stages {
+            let prompt = render_template(&stage.prompt, &vars)?;
+            let mut builder = stage.builder.clone();
+            if let Some(ref schema) = stage.schema {
+                builder = builder.json_schema(schema.to_string());
+            }
+
+            let mut client = AsyncClient::from_builder(builder).await?;
+            let responses = client.query(&prompt).await;
+            client.shutdown().await?;
+            let result = responses?
+                .into_iter()
+                .rev()
+                .find_map(|output| match output {
+                    ClaudeOutput::Result(result) => Some(result),
+                    _ => None,
+                })
+                .ok_or_else(|| Error::MissingField("result".to_string()))?;
+
+            let output = match stage.schema {
+                Some(_) => result.structured_output_as::<Value>()?,
+                None if result.is_error => {
+                    return Err(Error::Protocol(format!(
+                        "stage '{}' failed ({}): {}",
+                        stage.name,
+                        result.subtype,
+                        result.errors.join("; ")
+                    )))
+                }
+                None => Value::String(result.result.clone().unwrap_or_default()),
+            };
+
+            vars.insert(stage.name.clone(), output.clone());
+            stages.push(StageOutput {
+                name: stage.name.clone(),
+                prompt,
+                output,
+                result,
+            });
+        }
+
+        Ok(PipelineOutput { stages })
+    }
+}
+
+/// What one stage produced.
+#[derive(Debug, Clone)]
+pub struct StageOutput {
+    pub name: String,
+    /// The rendered prompt that was sent.
+    pub prompt: String,
+    /// Structured output for stages with a schema, result text otherwise.
+    pub output: Value,
+    /// The stage's final result message, for cost and usage.
+    pub result: ResultMessage,
+}
+
+/// The result of [`Pipeline::run`].
+#[derive(Debug, Clone)]
+pub struct PipelineOutput {
+    /// One entry per stage, in order.
+    pub stages: Vec<StageOutput>,
+}
+
+impl PipelineOutput {
+    /// The output of the stage called `name`.
+    pub fn get(&self, name: &str) -> Option<&StageOutput> {
+        self.stages.iter().find(|s| s.name == name)
+    }
+
+    /// The output of the final stage.
+    pub fn last(&self) -> Option<&StageOutput> {
+        self.stages.last()
+    }
+
+    /// Combined cost of every stage in USD.
+    pub fn total_cost_usd(&self) -> f64 {
+        self.stages.iter().map(|s| s.result.total_cost_usd).sum()
+    }
+}
+
+/// Replace each `{{path}}` in `template` with the value it names in `vars`.
+///
+/// Strings are inserted as-is; other JSON values are pretty-printed.
+fn render_template(template: &str, vars: &Map<String, Value>) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let path = rest[start + 2..start + 2 + len].trim();
+        let value = lookup(vars, path)
+            .ok_or_else(|| Error::MissingField(format!("pipeline variable '{}'", path)))?;
+        match value {
+            Value::String(s) => out.push_str(s),
+            other => out.push_str(&serde_json::to_string_pretty(other)?),
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn lookup<'a>(vars: &'a Map<String, Value>, path: &str) -> Option<&'a Value> {
+    let mut parts = path.split('.');
+    let mut value = vars.get(parts.next()?)?;
+    for part in parts {
+        value = match value {
+            Value::Array(items) => items.get(part.parse::<usize>().ok()?)?,
+            other => other.get(part)?,
+        };
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_template() {
+        let vars = json!({
+            "task": "add a flag",
+            "plan": {"steps": ["parse it", "use it"], "risk": 2}
+        });
+        let vars = vars.as_object().unwrap();
+
+        assert_eq!(
+            render_template("Do {{ task }}: {{plan.steps.1}} (risk {{plan.risk}})", vars).unwrap(),
+            "Do add a flag: use it (risk 2)"
+        );
+        assert_eq!(
+            render_template("{{plan.steps}}", vars).unwrap(),
+            "[\n  \"parse it\",\n  \"use it\"\n]"
+        );
+        assert_eq!(render_template("no {{ end", vars).unwrap(), "no {{ end");
+        assert!(matches!(
+            render_template("{{plan.missing}}", vars),
+            Err(Error::MissingField(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_feeds_structured_output_forward() {
+        use crate::test_support::fake_cli;
+
+        // Answers with structured output when started with --json-schema,
+        // and with plain text otherwise.
+        let session = "622ae0c3-3d50-4fa7-9ee0-69d691238c6d";
+        let script = format!(
+            r#"#!/bin/sh
+extra=''
+case "$*" in *--json-schema*) extra=',"structured_output":{{"steps":["edit main.rs"]}}' ;; esac
+while read -r line; do
+  echo '{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"done","session_id":"{session}","total_cost_usd":0.5'"$extra"'}}'
+done
+"#
+        );
+        let cli = fake_cli(&script);
+
+        let builder = ClaudeCliBuilder::new().command(&cli);
+        let output = Pipeline::new()
+            .var("task", "add a flag")
+            .stage(
+                Stage::new("plan", "Plan: {{task}}")
+                    .builder(builder.clone())
+                    .output_schema(json!({"type": "object"})),
+            )
+            .stage(Stage::new("implement", "Do {{plan.steps.0}}").builder(builder))
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(output.get("plan").unwrap().prompt, "Plan: add a flag");
+        assert_eq!(
+            output.get("plan").unwrap().output["steps"][0],
+            "edit main.rs"
+        );
+        let last = output.last().unwrap();
+        assert_eq!(last.prompt, "Do edit main.rs");
+        assert_eq!(last.output, "done");
+        assert_eq!(output.total_cost_usd(), 1.0);
+
+        std::fs::remove_file(cli).ok();
+    }
+}
@@ -0,0 +1,239 @@
+//! Progress of the turn in flight, for status bars.
+//!
+//! [`TurnProgress`] is a small snapshot — how long the turn has run, how
+//! many messages it has produced, which tool is running and how many
+//! subagents are out — updated from the message flow. The async client
+//! keeps one on a `tokio::sync::watch` channel, so a status bar can follow
+//! it from [`AsyncClient::watch_progress`](crate::AsyncClient::watch_progress)
+//! without touching the main message stream.
+//!
+//! ```
+//! use claude_codes::{ClaudeInput, ClaudeOutput, TurnProgress};
+//!
+//! let mut progress = TurnProgress::new();
+//! progress.on_input(&ClaudeInput::user_message("list files", uuid::Uuid::new_v4()));
+//!
+//! let json = r#"{"type":"assistant","session_id":"9a4c1e6d-4c1f-4f69-9d6e-4c1a6a4e9f10",
+//!     "message":{"id":"msg_1","role":"assistant","model":"claude-sonnet-4","content":[
+//!         {"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"ls"}}]}}"#;
+//! progress.record(&ClaudeOutput::parse_json(json).unwrap());
+//!
+//! assert_eq!(progress.current_tool(), Some("Bash"));
+//! assert_eq!(progress.messages(), 1);
+//! println!("{progress}");
+//! ```
+
+use crate::io::{ClaudeInput, ClaudeOutput};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Tool names that run a subagent.
+const SUBAGENT_TOOLS: &[&str] = &["Task", "Agent"];
+
+/// Where the current (or last) turn has got to.
+///
+/// A turn starts when a user message is sent, or with the first message
+/// from Claude if none was seen, and ends with its result; the counters
+/// keep their final values until the next turn starts.
+///
+/// Displays as a one-line summary such as `Bash · 6 msgs · 1 subagent · 4.2s`.
+#[derive(Debug, Clone, Default)]
+pub struct TurnProgress {
+    started_at: Option<Instant>,
+    finished_at: Option<Instant>,
+    messages: u64,
+    /// ID and name of each tool called this turn that has no result yet,
+    /// oldest first
+    running: Vec<(String, String)>,
+}
+
+impl TurnProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update from a message sent to the CLI. Returns whether anything
+    /// changed.
+    pub fn on_input(&mut self, input: &ClaudeInput) -> bool {
+        self.on_input_at(input, Instant::now())
+    }
+
+    /// Update from a message sent to the CLI at `at`.
+    pub fn on_input_at(&mut self, input: &ClaudeInput, at: Instant) -> bool {
+        match input {
+            ClaudeInput::User(_) if !self.is_running() => {
+                self.start(at);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Update from a message received now. Returns whether anything
+    /// changed.
+    pub fn record(&mut self, output: &ClaudeOutput) -> bool {
+        self.record_at(output, Instant::now())
+    }
+
+    /// Update from a message received at `at`.
+    pub fn record_at(&mut self, output: &ClaudeOutput, at: Instant) -> bool {
+        match output {
+            ClaudeOutput::Assistant(_) | ClaudeOutput::User(_) => {
+                if !self.is_running() {
+                    self.start(at);
+                }
+                self.messages += 1;
+                for tool in output.tool_uses() {
+                    self.running.push((tool.id.clone(), tool.name.clone()));
+                }
+                for result in output.tool_results() {
+                    self.running.retain(|(id, _)| *id != result.tool_use_id);
+                }
+            }
+            ClaudeOutput::Result(_) if self.is_running() => {
+                self.messages += 1;
+                self.finished_at = Some(at);
+                self.running.clear();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Whether a turn is in progress.
+    pub fn is_running(&self) -> bool {
+        self.started_at.is_some() && self.finished_at.is_none()
+    }
+
+    /// How long the turn has been running, or how long the last one took;
+    /// `None` before the first turn.
+    pub fn elapsed(&self) -> Option<Duration> {
+        let started = self.started_at?;
+        let end = self.finished_at.unwrap_or_else(Instant::now);
+        Some(end.saturating_duration_since(started))
+    }
+
+    /// Messages received this turn, including its result.
+    pub fn messages(&self) -> u64 {
+        self.messages
+    }
+
+    /// Name of the most recently called tool that is still running.
+    pub fn current_tool(&self) -> Option<&str> {
+        self.running.last().map(|(_, name)| name.as_str())
+    }
+
+    /// Tools called this turn that are still running.
+    pub fn running_tools(&self) -> impl Iterator<Item = &str> {
+        self.running.iter().map(|(_, name)| name.as_str())
+    }
+
+    /// Subagents launched this turn that haven't finished.
+    pub fn subagents(&self) -> usize {
+        self.running
+            .iter()
+            .filter(|(_, name)| SUBAGENT_TOOLS.contains(&name.as_str()))
+            .count()
+    }
+
+    fn start(&mut self, at: Instant) {
+        *self = Self {
+            started_at: Some(at),
+            ..Self::default()
+        };
+    }
+}
+
+impl fmt::Display for TurnProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(tool) = self.current_tool() {
+            write!(f, "{} · ", tool)?;
+        }
+        write!(f, "{} msgs", self.messages)?;
+        match self.subagents() {
+            0 => {}
+            1 => write!(f, " · 1 subagent")?,
+            n => write!(f, " · {} subagents", n)?,
+        }
+        if let Some(elapsed) = self.elapsed() {
+            write!(f, " · {:.1}s", elapsed.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = "622ae0c3-3d50-4fa7-9ee0-69d691238c6d";
+
+    fn tool_use(id: &str, name: &str) -> ClaudeOutput {
+        serde_json::from_value(serde_json::json!({
+            "type": "assistant",
+            "session_id": SESSION,
+            "message": {
+                "id": "msg_1", "role": "assistant", "model": "claude-sonnet-4",
+                "content": [{"type": "tool_use", "id": id, "name": name, "input": {}}]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn tool_result(id: &str) -> ClaudeOutput {
+        serde_json::from_value(serde_json::json!({
+            "type": "user",
+            "session_id": SESSION,
+            "message": {
+                "role": "user",
+                "content": [{"type": "tool_result", "tool_use_id": id, "content": "done"}]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tracks_tools_and_subagents_through_a_turn() {
+        let result: ClaudeOutput = serde_json::from_str(&format!(
+            r#"{{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"{SESSION}","total_cost_usd":0.0}}"#
+        ))
+        .unwrap();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        let mut progress = TurnProgress::new();
+        assert!(progress.elapsed().is_none());
+        assert!(progress.on_input_at(
+            &ClaudeInput::user_message("go", uuid::Uuid::new_v4()),
+            at(0)
+        ));
+        assert!(progress.is_running());
+
+        progress.record_at(&tool_use("toolu_1", "Task"), at(100));
+        progress.record_at(&tool_use("toolu_2", "Task"), at(200));
+        progress.record_at(&tool_use("toolu_3", "Bash"), at(300));
+        assert_eq!(progress.current_tool(), Some("Bash"));
+        assert_eq!(progress.subagents(), 2);
+
+        progress.record_at(&tool_result("toolu_3"), at(400));
+        progress.record_at(&tool_result("toolu_1"), at(500));
+        assert_eq!(progress.current_tool(), Some("Task"));
+        assert_eq!(progress.subagents(), 1);
+        assert_eq!(progress.to_string().split(" · ").count(), 4);
+
+        assert!(progress.record_at(&result, at(1500)));
+        assert!(!progress.is_running());
+        assert_eq!(progress.messages(), 6);
+        assert_eq!(progress.subagents(), 0);
+        assert_eq!(progress.elapsed(), Some(Duration::from_millis(1500)));
+        assert_eq!(progress.to_string(), "6 msgs · 1.5s");
+
+        // A late duplicate result doesn't count against the finished turn.
+        assert!(!progress.record_at(&result, at(1600)));
+        progress.on_input_at(
+            &ClaudeInput::user_message("next", uuid::Uuid::new_v4()),
+            at(2000),
+        );
+        assert_eq!(progress.messages(), 0);
+    }
+}
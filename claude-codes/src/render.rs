@@ -0,0 +1,447 @@
+//! Colorized terminal rendering of Claude output (requires the `render` feature).
+//!
+//! [`Terminal`] turns [`ClaudeOutput`] messages into ANSI-colored, truncated
+//! text for interactive tools: assistant text as-is, tool calls as one-line
+//! summaries, file and notebook cell edits as `-`/`+` diffs, and tool results
+//! clipped to a few lines. For uncolored one-line summaries use the
+//! [`Display`](std::fmt::Display) impls on the message types instead.
+//!
+//! ```
+//! use claude_codes::render::Terminal;
+//! use claude_codes::ClaudeOutput;
+//!
+//! let json = r#"{"type":"assistant","message":{"id":"m","role":"assistant",
+//!     "model":"claude-3","content":[{"type":"tool_use","id":"tu_1","name":"Edit",
+//!     "input":{"file_path":"src/lib.rs","old_string":"a = 1","new_string":"a = 2"}}]},
+//!     "session_id":"s"}"#;
+//! let output: ClaudeOutput = serde_json::from_str(json).unwrap();
+//!
+//! let rendered = Terminal::plain().render(&output);
+//! assert_eq!(rendered, "→ Edit(src/lib.rs)\n  - a = 1\n  + a = 2\n");
+//! ```
+
+use std::io::{self, Write};
+
+use crate::io::{
+    AssistantMessage, ClaudeOutput, ContentBlock, ResultMessage, SystemMessage, ToolResultBlock,
+    ToolUseBlock, UserMessage,
+};
+use crate::tool_inputs::{NotebookEditMode, ToolInput};
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const ITALIC: &str = "\x1b[3m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const CYAN: &str = "\x1b[36m";
+
+/// Renders [`ClaudeOutput`] messages for a terminal.
+///
+/// Configure with the chainable setters, then call [`render`](Self::render)
+/// or [`write`](Self::write) for each message.
+#[derive(Debug, Clone)]
+pub struct Terminal {
+    color: bool,
+    max_lines: usize,
+    max_line_chars: usize,
+    show_thinking: bool,
+    show_system: bool,
+}
+
+impl Default for Terminal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Terminal {
+    /// A colorized renderer, unless the `NO_COLOR` environment variable is set.
+    ///
+    /// Defaults: tool results and diffs are clipped to 10 lines of 160
+    /// characters, thinking is shown, and system messages other than `init`
+    /// are hidden.
+    pub fn new() -> Self {
+        Self {
+            color: std::env::var_os("NO_COLOR").is_none(),
+            max_lines: 10,
+            max_line_chars: 160,
+            show_thinking: true,
+            show_system: false,
+        }
+    }
+
+    /// A renderer with colors disabled.
+    pub fn plain() -> Self {
+        Self::new().color(false)
+    }
+
+    /// Enable or disable ANSI colors.
+    pub fn color(mut self, enabled: bool) -> Self {
+        self.color = enabled;
+        self
+    }
+
+    /// Maximum lines shown for tool results, diffs, and thinking.
+    pub fn max_lines(mut self, lines: usize) -> Self {
+        self.max_lines = lines;
+        self
+    }
+
+    /// Maximum characters per line before it is cut with `…`.
+    pub fn max_line_chars(mut self, chars: usize) -> Self {
+        self.max_line_chars = chars;
+        self
+    }
+
+    /// Show or hide thinking blocks.
+    pub fn show_thinking(mut self, show: bool) -> Self {
+        self.show_thinking = show;
+        self
+    }
+
+    /// Show system messages other than `init` (status, task progress, ...)
+    /// CLI debug log lines, and message types the crate doesn't know.
+    pub fn show_system(mut self, show: bool) -> Self {
+        self.show_system = show;
+        self
+    }
+
+    /// Render a message to a string. Returns an empty string for messages
+    /// that are hidden by the current settings.
+    pub fn render(&self, output: &ClaudeOutput) -> String {
+        let mut out = String::new();
+        match output {
+            ClaudeOutput::System(sys) => self.system(&mut out, sys),
+            ClaudeOutput::User(user) => self.user(&mut out, user),
+            ClaudeOutput::Assistant(msg) => self.assistant(&mut out, msg),
+            ClaudeOutput::Result(res) => self.result(&mut out, res),
+            ClaudeOutput::ControlRequest(req) => {
+                self.line(&mut out, &[BOLD, YELLOW], &req.to_string())
+            }
+            ClaudeOutput::ControlResponse(_) => {}
+            ClaudeOutput::Error(err) => self.line(&mut out, &[BOLD, RED], &err.to_string()),
+            ClaudeOutput::RateLimitEvent(ev) => self.line(&mut out, &[YELLOW], &ev.to_string()),
+            ClaudeOutput::Debug(msg) => {
+                if self.show_system {
+                    self.line(&mut out, &[DIM], &msg.to_string())
+                }
+            }
+            ClaudeOutput::Unknown(_) => {
+                if self.show_system {
+                    self.line(&mut out, &[DIM], &output.to_string())
+                }
+            }
+        }
+        out
+    }
+
+    /// Render a message and write it to `w`.
+    pub fn write<W: Write>(&self, w: &mut W, output: &ClaudeOutput) -> io::Result<()> {
+        w.write_all(self.render(output).as_bytes())
+    }
+
+    /// Render a single content block.
+    pub fn render_block(&self, block: &ContentBlock) -> String {
+        let mut out = String::new();
+        self.block(&mut out, block);
+        out
+    }
+
+    /// Render a line-level diff between `old` and `new`, indented by two spaces.
+    ///
+    /// Lines shared at the start and end are omitted; the remaining old lines
+    /// are shown with `-` and the new lines with `+`, each side clipped to
+    /// [`max_lines`](Self::max_lines).
+    pub fn render_diff(&self, old: &str, new: &str) -> String {
+        let mut out = String::new();
+        self.diff(&mut out, old, new);
+        out
+    }
+
+    fn paint(&self, styles: &[&str], text: &str) -> String {
+        if !self.color || styles.is_empty() {
+            return text.to_string();
+        }
+        format!("{}{}{}", styles.concat(), text, RESET)
+    }
+
+    fn line(&self, out: &mut String, styles: &[&str], text: &str) {
+        out.push_str(&self.paint(styles, text));
+        out.push('\n');
+    }
+
+    fn clip(&self, line: &str) -> String {
+        if line.chars().count() <= self.max_line_chars {
+            return line.to_string();
+        }
+        let mut clipped: String = line.chars().take(self.max_line_chars).collect();
+        clipped.push('…');
+        clipped
+    }
+
+    /// Write up to `max_lines` lines of `text`, each prefixed and styled.
+    fn clipped_lines(&self, out: &mut String, styles: &[&str], prefix: &str, text: &str) {
+        let lines: Vec<&str> = text.lines().collect();
+        for line in lines.iter().take(self.max_lines) {
+            self.line(out, styles, &format!("{}{}", prefix, self.clip(line)));
+        }
+        if lines.len() > self.max_lines {
+            let more = format!("  … {} more lines", lines.len() - self.max_lines);
+            self.line(out, &[DIM], &more);
+        }
+    }
+
+    fn diff(&self, out: &mut String, old: &str, new: &str) {
+        let old_lines: Vec<&str> = old.lines().collect();
+        let new_lines: Vec<&str> = new.lines().collect();
+        let prefix = old_lines
+            .iter()
+            .zip(&new_lines)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = old_lines[prefix..]
+            .iter()
+            .rev()
+            .zip(new_lines[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let removed = old_lines[prefix..old_lines.len() - suffix].join("\n");
+        let added = new_lines[prefix..new_lines.len() - suffix].join("\n");
+        self.clipped_lines(out, &[RED], "  - ", &removed);
+        self.clipped_lines(out, &[GREEN], "  + ", &added);
+    }
+
+    fn system(&self, out: &mut String, sys: &SystemMessage) {
+        if sys.is_init() || self.show_system {
+            self.line(out, &[DIM], &sys.to_string());
+        }
+    }
+
+    fn user(&self, out: &mut String, user: &UserMessage) {
+        // The result text repeats the whole cell; the structured result
+        // carries the old source, so show what actually changed instead.
+        if let Some(nb) = user.notebook_edit_result() {
+            if nb.error.is_none() {
+                self.line(
+                    out,
+                    &[DIM],
+                    &cell_header(nb.cell_id.as_deref(), &nb.edit_mode),
+                );
+                match (nb.edit_mode, nb.old_source) {
+                    (NotebookEditMode::Delete, Some(old)) => {
+                        self.clipped_lines(out, &[RED], "  - ", &old)
+                    }
+                    (_, Some(old)) => self.diff(out, &old, &nb.new_source),
+                    (_, None) => self.clipped_lines(out, &[GREEN], "  + ", &nb.new_source),
+                }
+                return;
+            }
+        }
+        for block in &user.message.content {
+            match block {
+                // Echoed prompts are already on screen; only show tool output.
+                ContentBlock::Text(_) => {}
+                other => self.block(out, other),
+            }
+        }
+    }
+
+    fn assistant(&self, out: &mut String, msg: &AssistantMessage) {
+        for block in &msg.message.content {
+            self.block(out, block);
+        }
+    }
+
+    fn result(&self, out: &mut String, res: &ResultMessage) {
+        let header = res.to_string();
+        let summary = header.lines().next().unwrap_or_default();
+        let styles: &[&str] = if res.is_error { &[BOLD, RED] } else { &[GREEN] };
+        self.line(out, styles, summary);
+        if res.is_error {
+            for error in &res.errors {
+                self.line(out, &[RED], error);
+            }
+        }
+    }
+
+    fn block(&self, out: &mut String, block: &ContentBlock) {
+        match block {
+            ContentBlock::Text(t) => {
+                out.push_str(&t.text);
+                if !t.text.ends_with('\n') {
+                    out.push('\n');
+                }
+            }
+            ContentBlock::Thinking(t) if self.show_thinking => {
+                self.clipped_lines(out, &[DIM, ITALIC], "✻ ", &t.thinking);
+            }
+            ContentBlock::RedactedThinking(_) if self.show_thinking => {
+                self.line(out, &[DIM, ITALIC], "✻ [redacted]");
+            }
+            ContentBlock::Thinking(_) | ContentBlock::RedactedThinking(_) => {}
+            ContentBlock::ToolUse(tu) => self.tool_use(out, tu),
+            ContentBlock::ToolResult(tr) => self.tool_result(out, tr),
+            other => self.line(out, &[CYAN], &other.to_string()),
+        }
+    }
+
+    fn tool_use(&self, out: &mut String, tu: &ToolUseBlock) {
+        self.line(out, &[BOLD, CYAN], &tu.to_string());
+        match tu.typed_input() {
+            Some(ToolInput::Edit(edit)) => self.diff(out, &edit.old_string, &edit.new_string),
+            Some(ToolInput::MultiEdit(multi)) => {
+                for edit in &multi.edits {
+                    self.diff(out, &edit.old_string, &edit.new_string);
+                }
+            }
+            Some(ToolInput::Write(write)) => {
+                self.clipped_lines(out, &[GREEN], "  + ", &write.content)
+            }
+            Some(ToolInput::NotebookEdit(nb)) => {
+                let mode = nb.mode();
+                self.line(out, &[DIM], &cell_header(nb.cell_id.as_deref(), &mode));
+                if mode != NotebookEditMode::Delete {
+                    self.clipped_lines(out, &[GREEN], "  + ", &nb.new_source);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn tool_result(&self, out: &mut String, tr: &ToolResultBlock) {
+        let text = tr.content.as_ref().map(|c| c.text()).unwrap_or_default();
+        if tr.is_error() {
+            self.line(out, &[RED], &format!("✗ {}", tr.tool_use_id));
+            self.clipped_lines(out, &[RED], "  ", &text);
+        } else {
+            self.clipped_lines(out, &[DIM], "  ", &text);
+        }
+    }
+}
+
+/// `cell <id> (<mode>)` line shown above a notebook cell's changes.
+fn cell_header(cell_id: Option<&str>, mode: &NotebookEditMode) -> String {
+    match cell_id {
+        Some(id) => format!("  cell {} ({})", id, mode),
+        None => format!("  first cell ({})", mode),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> ClaudeOutput {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn assistant(content: &str) -> ClaudeOutput {
+        parse(&format!(
+            r#"{{"type":"assistant","message":{{"id":"m","role":"assistant",
+                "model":"claude-3","content":{}}},"session_id":"s"}}"#,
+            content
+        ))
+    }
+
+    #[test]
+    fn test_plain_text_and_tool_call() {
+        let output = assistant(
+            r#"[{"type":"text","text":"Running it"},
+                {"type":"tool_use","id":"tu","name":"Bash","input":{"command":"cargo test"}}]"#,
+        );
+        assert_eq!(
+            Terminal::plain().render(&output),
+            "Running it\n→ Bash(cargo test)\n"
+        );
+    }
+
+    #[test]
+    fn test_color_wraps_tool_call() {
+        let output =
+            assistant(r#"[{"type":"tool_use","id":"tu","name":"Bash","input":{"command":"ls"}}]"#);
+        let rendered = Terminal::new().color(true).render(&output);
+        assert_eq!(rendered, "\x1b[1m\x1b[36m→ Bash(ls)\x1b[0m\n");
+    }
+
+    #[test]
+    fn test_diff_skips_shared_lines() {
+        let term = Terminal::plain();
+        assert_eq!(term.render_diff("a\nb\nc", "a\nB\nc"), "  - b\n  + B\n");
+    }
+
+    #[test]
+    fn test_tool_result_is_truncated() {
+        let body: Vec<String> = (1..=5).map(|i| format!("line {}", i)).collect();
+        let output = parse(&format!(
+            r#"{{"type":"user","message":{{"role":"user","content":[
+                {{"type":"tool_result","tool_use_id":"tu","content":{}}}]}},
+                "session_id":"550e8400-e29b-41d4-a716-446655440000"}}"#,
+            serde_json::to_string(&body.join("\n")).unwrap()
+        ));
+        let rendered = Terminal::plain().max_lines(2).render(&output);
+        assert_eq!(rendered, "  line 1\n  line 2\n  … 3 more lines\n");
+    }
+
+    #[test]
+    fn test_long_lines_are_clipped() {
+        let term = Terminal::plain().max_line_chars(4);
+        assert_eq!(term.render_diff("", "abcdefgh"), "  + abcd…\n");
+    }
+
+    #[test]
+    fn test_thinking_can_be_hidden() {
+        let output = assistant(r#"[{"type":"thinking","thinking":"hmm","signature":"sig"}]"#);
+        assert_eq!(Terminal::plain().render(&output), "✻ hmm\n");
+        assert_eq!(Terminal::plain().show_thinking(false).render(&output), "");
+    }
+
+    #[test]
+    fn test_system_status_hidden_by_default() {
+        let output = parse(r#"{"type":"system","subtype":"status","status":"compacting"}"#);
+        assert_eq!(Terminal::plain().render(&output), "");
+        assert_eq!(
+            Terminal::plain().show_system(true).render(&output),
+            "[system:status] compacting\n"
+        );
+    }
+
+    #[test]
+    fn test_error_result() {
+        let output = parse(
+            r#"{"type":"result","subtype":"error_during_execution","is_error":true,
+                "duration_ms":0,"duration_api_ms":0,"num_turns":0,
+                "session_id":"s","total_cost_usd":0.0,"errors":["boom"]}"#,
+        );
+        assert_eq!(
+            Terminal::plain().render(&output),
+            "[result:error_during_execution] 0 turns, 0.0s, $0.0000\nboom\n"
+        );
+    }
+
+    #[test]
+    fn test_notebook_edit_shows_cell_diff() {
+        let call = assistant(
+            r#"[{"type":"tool_use","id":"tu","name":"NotebookEdit","input":{
+                "notebook_path":"/work/a.ipynb","cell_id":"c3","new_source":"df.describe()"}}]"#,
+        );
+        assert_eq!(
+            Terminal::plain().render(&call),
+            "→ NotebookEdit(/work/a.ipynb)\n  cell c3 (replace)\n  + df.describe()\n"
+        );
+
+        let result = parse(
+            r#"{"type":"user","message":{"role":"user","content":[
+                {"type":"tool_result","tool_use_id":"tu","content":"Updated cell c3 with df.describe()"}]},
+                "session_id":"550e8400-e29b-41d4-a716-446655440000",
+                "tool_use_result":{"new_source":"df.describe()","old_source":"df.head()",
+                "cell_id":"c3","cell_type":"code","language":"python","edit_mode":"replace",
+                "notebook_path":"/work/a.ipynb","original_file":"{}","updated_file":"{}"}}"#,
+        );
+        assert_eq!(
+            Terminal::plain().render(&result),
+            "  cell c3 (replace)\n  - df.head()\n  + df.describe()\n"
+        );
+    }
+}
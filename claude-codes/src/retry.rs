@@ -0,0 +1,270 @@
+//! Re-running turns that end on a transient API error.
+//!
+//! The CLI retries failed API calls on its own, but an outage that outlasts
+//! those retries ends the turn with an error Result, and a batch job loses
+//! that prompt's work. With a [`RetryPolicy`] installed via
+//! `set_retry_policy` on [`AsyncClient`](crate::AsyncClient) or
+//! [`SyncClient`](crate::SyncClient), a Result that
+//! [`is_transient_api_error`](crate::ResultMessage::is_transient_api_error)
+//! is not returned: the client waits, sends the turn's user message again on
+//! the same session, and keeps reading. Each retry is reported as a
+//! [`TurnRetried`] event, collected with `take_turn_retries`. Once the
+//! policy's retries are used up, the failing Result is returned as usual.
+//!
+//! Sending an interrupt while a retry is waiting cancels it. A
+//! [`ClientHandle`](crate::ClientHandle) keeps serving its other commands
+//! during the wait.
+//!
+//! Errors caused by the request itself (invalid requests, authentication,
+//! limits) are never retried.
+//!
+//! ```no_run
+//! # #[cfg(feature = "sync-client")]
+//! # fn main() -> claude_codes::Result<()> {
+//! use claude_codes::retry::RetryPolicy;
+//! use claude_codes::{ClaudeInput, SyncClient};
+//! use std::time::Duration;
+//! use uuid::Uuid;
+//!
+//! let mut client = SyncClient::with_defaults()?;
+//! client.set_retry_policy(RetryPolicy::new(3).delay(Duration::from_secs(10)));
+//! client.query(ClaudeInput::user_message("Summarize CHANGELOG.md", Uuid::new_v4()))?;
+//! for retry in client.take_turn_retries() {
+//!     eprintln!("retried after {:?}: {:?}", retry.delay, retry.result.result);
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "sync-client"))]
+//! # fn main() {}
+//! ```
+
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+use crate::io::{ClaudeInput, ResultMessage};
+
+/// How often, and how patiently, to re-run a turn that failed on a
+/// transient API error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Retry a turn up to `max_retries` times, waiting 5 seconds before the
+    /// first retry and twice as long before each one after it.
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            delay: Duration::from_secs(5),
+        }
+    }
+
+    /// Wait `delay` before the first retry (doubling for each later one).
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// How many times one turn is sent again before its error Result is
+    /// returned.
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// How long to wait before retry number `attempt` (starting at 1).
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        self.delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+/// A turn that failed on a transient API error and was sent again.
+#[derive(Debug, Clone)]
+pub struct TurnRetried {
+    /// Which retry this was, starting at 1.
+    pub attempt: u32,
+    /// The policy's limit on retries for one turn.
+    pub max_retries: u32,
+    /// How long the client waited before sending the input again.
+    pub delay: Duration,
+    /// The user message that was sent again.
+    pub input: ClaudeInput,
+    /// The error Result the retry replaced.
+    pub result: ResultMessage,
+}
+
+/// The installed policy, the turn it would retry, and the retry events not
+/// yet collected.
+#[derive(Debug, Default)]
+pub(crate) struct TurnRetrier {
+    policy: Option<RetryPolicy>,
+    /// The user message that started the current turn.
+    input: Option<ClaudeInput>,
+    /// Retries spent on the current turn.
+    attempts: u32,
+    /// A retry waiting out its delay: the input to send and when it is due.
+    pending: Option<(ClaudeInput, Instant)>,
+    /// Set while the pending retry is being sent, so the resend isn't
+    /// mistaken for a new turn.
+    resending: bool,
+    retried: Vec<TurnRetried>,
+}
+
+impl TurnRetrier {
+    pub(crate) fn set_policy(&mut self, policy: Option<RetryPolicy>) {
+        self.policy = policy;
+        if policy.is_none() {
+            self.input = None;
+            self.pending = None;
+        }
+    }
+
+    /// Record that `input` was written; user messages start a new turn, and
+    /// an interrupt or a new turn cancels a pending retry.
+    pub(crate) fn on_send(&mut self, input: &ClaudeInput) {
+        if std::mem::take(&mut self.resending) {
+            return;
+        }
+        let new_turn = matches!(input, ClaudeInput::User(_));
+        if (new_turn || is_interrupt(input)) && self.pending.take().is_some() {
+            debug!("[RETRY] Pending retry cancelled");
+            self.input = None;
+        }
+        if self.policy.is_some() && new_turn {
+            self.input = Some(input.clone());
+            self.attempts = 0;
+        }
+    }
+
+    /// Decide whether the turn that ended with `result` should run again.
+    ///
+    /// If so, records the [`TurnRetried`] event, makes the turn's input
+    /// [pending](Self::take_pending), and returns how long to wait before
+    /// sending it.
+    pub(crate) fn on_result(&mut self, result: &ResultMessage) -> Option<Duration> {
+        let policy = self.policy?;
+        if !result.is_transient_api_error() || self.attempts >= policy.max_retries {
+            self.input = None;
+            return None;
+        }
+        let input = self.input.clone()?;
+        self.attempts += 1;
+        let delay = policy.delay_for(self.attempts);
+        self.retried.push(TurnRetried {
+            attempt: self.attempts,
+            max_retries: policy.max_retries,
+            delay,
+            input: input.clone(),
+            result: result.clone(),
+        });
+        self.pending = Some((input, Instant::now() + delay));
+        Some(delay)
+    }
+
+    /// Time left before the pending retry is due, if one is waiting.
+    #[cfg(any(test, feature = "async-core"))]
+    pub(crate) fn pending_wait(&self) -> Option<Duration> {
+        self.pending
+            .as_ref()
+            .map(|(_, due)| due.saturating_duration_since(Instant::now()))
+    }
+
+    /// Take the pending retry's input to send it now.
+    pub(crate) fn take_pending(&mut self) -> Option<ClaudeInput> {
+        let (input, _) = self.pending.take()?;
+        self.resending = true;
+        Some(input)
+    }
+
+    pub(crate) fn take_retried(&mut self) -> Vec<TurnRetried> {
+        std::mem::take(&mut self.retried)
+    }
+}
+
+/// Whether `input` is the interrupt built by [`ClaudeInput::interrupt`].
+fn is_interrupt(input: &ClaudeInput) -> bool {
+    matches!(input, ClaudeInput::Raw(value)
+        if value.get("subtype").and_then(|s| s.as_str()) == Some("interrupt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::ClaudeOutput;
+    use uuid::Uuid;
+
+    fn result(is_error: bool, status: Option<u16>, text: &str) -> ResultMessage {
+        let json = serde_json::json!({
+            "type": "result", "subtype": "success", "is_error": is_error,
+            "duration_ms": 10, "duration_api_ms": 8, "num_turns": 1, "result": text,
+            "session_id": Uuid::nil(), "total_cost_usd": 0.0, "api_error_status": status
+        });
+        let ClaudeOutput::Result(result) = serde_json::from_value(json).unwrap() else {
+            unreachable!()
+        };
+        result
+    }
+
+    #[test]
+    fn test_retries_transient_errors_up_to_limit() {
+        let mut retrier = TurnRetrier::default();
+        retrier.set_policy(Some(RetryPolicy::new(2).delay(Duration::from_millis(100))));
+        let overloaded = result(true, Some(529), "API Error: 529 Overloaded");
+        let input = ClaudeInput::user_message("Run the tests", Uuid::nil());
+
+        retrier.on_send(&input);
+        assert_eq!(
+            retrier.on_result(&overloaded),
+            Some(Duration::from_millis(100))
+        );
+        assert!(retrier.pending_wait().unwrap() <= Duration::from_millis(100));
+        let resend = retrier.take_pending().unwrap();
+        assert!(matches!(resend, ClaudeInput::User(_)));
+        retrier.on_send(&resend);
+        assert_eq!(
+            retrier.on_result(&overloaded),
+            Some(Duration::from_millis(200))
+        );
+        let resend = retrier.take_pending().unwrap();
+        retrier.on_send(&resend);
+        assert!(retrier.on_result(&overloaded).is_none(), "retries used up");
+        assert!(retrier.pending_wait().is_none());
+
+        let events = retrier.take_retried();
+        assert_eq!(events.iter().map(|e| e.attempt).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(events[0].result.api_error_status, Some(529));
+        assert!(retrier.take_retried().is_empty());
+
+        // A new turn gets a fresh allowance; user errors are never retried.
+        retrier.on_send(&input);
+        assert!(retrier
+            .on_result(&result(
+                true,
+                Some(400),
+                "API Error: 400 invalid_request_error"
+            ))
+            .is_none());
+        retrier.on_send(&input);
+        assert!(retrier.on_result(&result(false, None, "done")).is_none());
+        retrier.on_send(&input);
+        assert!(retrier.on_result(&overloaded).is_some());
+    }
+
+    #[test]
+    fn test_interrupt_cancels_pending_retry() {
+        let mut retrier = TurnRetrier::default();
+        retrier.set_policy(Some(RetryPolicy::new(2)));
+        retrier.on_send(&ClaudeInput::user_message("Run the tests", Uuid::nil()));
+        assert!(retrier
+            .on_result(&result(true, Some(529), "API Error: 529 Overloaded"))
+            .is_some());
+
+        retrier.on_send(&ClaudeInput::interrupt());
+        assert!(retrier.pending_wait().is_none());
+        assert!(retrier.take_pending().is_none());
+        assert_eq!(retrier.take_retried().len(), 1);
+    }
+}
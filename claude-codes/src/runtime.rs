@@ -0,0 +1,230 @@
+//! Async runtime abstraction for [`AsyncClient`](crate::AsyncClient).
+//!
+//! The client only needs a handful of things from its runtime: a spawned
+//! child process it can wait on, kill, and poll, a timer, plus byte pipes
+//! to the child's stdio. [`ChildProcess`] covers the process half; pipes are boxed
+//! behind tokio's runtime-independent `AsyncRead` / `AsyncWrite` traits.
+//!
+//! Backends:
+//! - `async-client`: `tokio::process::Child`
+//! - `smol`: `async_process::Child`, with pipes adapted through
+//!   `tokio_util::compat`
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::process::ExitStatus;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+pub(crate) type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Read half of a child stdio pipe.
+pub(crate) type PipeReader = Box<dyn AsyncRead + Send + Unpin>;
+
+/// Write half of a child stdio pipe.
+pub(crate) type PipeWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// A spawned CLI process, independent of the runtime that spawned it.
+pub(crate) trait ChildProcess: Send {
+    /// OS process id, if the process has not been reaped.
+    fn id(&self) -> Option<u32>;
+
+    /// Non-blocking exit check.
+    fn try_wait(&mut self) -> io::Result<Option<ExitStatus>>;
+
+    /// Wait for exit, giving up after `timeout`. Returns `None` on timeout.
+    fn wait_timeout(&mut self, timeout: Duration) -> BoxFuture<'_, Option<io::Result<ExitStatus>>>;
+
+    /// Kill the process and wait for it to exit.
+    fn kill(&mut self) -> BoxFuture<'_, io::Result<()>>;
+
+    /// Send a kill signal without waiting (usable from `Drop`).
+    fn start_kill(&mut self) -> io::Result<()>;
+
+    /// Wait for `duration` on the runtime that spawned the process.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// A child process with its stdio pipes split off.
+pub(crate) struct ChildParts {
+    pub child: Box<dyn ChildProcess>,
+    pub stdin: PipeWriter,
+    pub stdout: PipeReader,
+    pub stderr: Option<PipeReader>,
+}
+
+fn missing_pipe(name: &str) -> io::Error {
+    io::Error::other(format!("Failed to get {} handle", name))
+}
+
+#[cfg(feature = "async-client")]
+mod tokio_backend {
+    use super::*;
+
+    impl ChildProcess for tokio::process::Child {
+        fn id(&self) -> Option<u32> {
+            tokio::process::Child::id(self)
+        }
+
+        fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+            tokio::process::Child::try_wait(self)
+        }
+
+        fn wait_timeout(
+            &mut self,
+            timeout: Duration,
+        ) -> BoxFuture<'_, Option<io::Result<ExitStatus>>> {
+            Box::pin(async move { tokio::time::timeout(timeout, self.wait()).await.ok() })
+        }
+
+        fn kill(&mut self) -> BoxFuture<'_, io::Result<()>> {
+            Box::pin(tokio::process::Child::kill(self))
+        }
+
+        fn start_kill(&mut self) -> io::Result<()> {
+            tokio::process::Child::start_kill(self)
+        }
+
+        fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+            Box::pin(tokio::time::sleep(duration))
+        }
+    }
+
+    impl ChildParts {
+        pub(crate) fn from_tokio(mut child: tokio::process::Child) -> io::Result<Self> {
+            let stdin = child.stdin.take().ok_or_else(|| missing_pipe("stdin"))?;
+            let stdout = child.stdout.take().ok_or_else(|| missing_pipe("stdout"))?;
+            let stderr = child.stderr.take();
+            Ok(Self {
+                child: Box::new(child),
+                stdin: Box::new(stdin),
+                stdout: Box::new(stdout),
+                stderr: stderr.map(|s| Box::new(s) as PipeReader),
+            })
+        }
+    }
+}
+
+#[cfg(feature = "smol")]
+mod smol_backend {
+    use super::*;
+    use tokio_util::compat::{FuturesAsyncReadCompatExt, FuturesAsyncWriteCompatExt};
+
+    impl ChildProcess for async_process::Child {
+        fn id(&self) -> Option<u32> {
+            Some(async_process::Child::id(self))
+        }
+
+        fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+            self.try_status()
+        }
+
+        fn wait_timeout(
+            &mut self,
+            timeout: Duration,
+        ) -> BoxFuture<'_, Option<io::Result<ExitStatus>>> {
+            Box::pin(futures_lite::future::or(
+                async move { Some(self.status().await) },
+                async move {
+                    async_io::Timer::after(timeout).await;
+                    None
+                },
+            ))
+        }
+
+        fn kill(&mut self) -> BoxFuture<'_, io::Result<()>> {
+            Box::pin(async move {
+                async_process::Child::kill(self)?;
+                self.status().await.map(|_| ())
+            })
+        }
+
+        fn start_kill(&mut self) -> io::Result<()> {
+            async_process::Child::kill(self)
+        }
+
+        fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+            Box::pin(async move {
+                async_io::Timer::after(duration).await;
+            })
+        }
+    }
+
+    impl ChildParts {
+        pub(crate) fn from_smol(mut child: async_process::Child) -> io::Result<Self> {
+            let stdin = child.stdin.take().ok_or_else(|| missing_pipe("stdin"))?;
+            let stdout = child.stdout.take().ok_or_else(|| missing_pipe("stdout"))?;
+            let stderr = child.stderr.take();
+            Ok(Self {
+                child: Box::new(child),
+                stdin: Box::new(stdin.compat_write()),
+                stdout: Box::new(stdout.compat()),
+                stderr: stderr.map(|s| Box::new(s.compat()) as PipeReader),
+            })
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    /// Echo stdin back on stdout, then linger so timeouts can be observed.
+    const ECHO_THEN_SLEEP: &str = "read line; echo \"$line\"; sleep 5";
+
+    fn shell() -> std::process::Command {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.args(["-c", ECHO_THEN_SLEEP])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+
+    async fn exercise(parts: ChildParts) {
+        let ChildParts {
+            mut child,
+            mut stdin,
+            stdout,
+            stderr,
+        } = parts;
+        assert!(stderr.is_some());
+        assert!(child.id().is_some());
+
+        stdin.write_all(b"ping\n").await.unwrap();
+        stdin.flush().await.unwrap();
+        let mut line = String::new();
+        BufReader::new(stdout).read_line(&mut line).await.unwrap();
+        assert_eq!(line, "ping\n");
+
+        assert!(child.try_wait().unwrap().is_none());
+        assert!(child
+            .wait_timeout(Duration::from_millis(50))
+            .await
+            .is_none());
+        child.kill().await.unwrap();
+        assert!(child.try_wait().unwrap().is_some());
+    }
+
+    #[cfg(feature = "async-client")]
+    #[tokio::test]
+    async fn test_tokio_backend() {
+        let child = tokio::process::Command::from(shell()).spawn().unwrap();
+        exercise(ChildParts::from_tokio(child).unwrap()).await;
+    }
+
+    #[cfg(feature = "smol")]
+    #[test]
+    fn test_smol_backend() {
+        let child = async_process::Command::from(shell())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        async_io::block_on(exercise(ChildParts::from_smol(child).unwrap()));
+    }
+}
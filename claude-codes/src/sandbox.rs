@@ -0,0 +1,167 @@
+//! Typed `sandbox` settings for the CLI's Bash tool.
+//!
+//! Claude Code can run Bash commands inside an OS sandbox (Seatbelt on
+//! macOS, bubblewrap on Linux) that confines writes to the working
+//! directory and sends network traffic through a filtering proxy. The
+//! sandbox is configured through the `sandbox` key of the CLI settings;
+//! [`ClaudeCliBuilder::sandbox`](crate::ClaudeCliBuilder::sandbox) merges a
+//! [`SandboxSettings`] into the `--settings` it passes.
+//!
+//! ```
+//! use claude_codes::{ClaudeCliBuilder, SandboxSettings};
+//!
+//! let builder = ClaudeCliBuilder::new().sandbox(
+//!     SandboxSettings::locked_down()
+//!         .exclude_command("docker")
+//!         .allow_unix_socket("/var/run/docker.sock"),
+//! );
+//! assert!(builder.validate().is_ok());
+//! ```
+//!
+//! The sandbox needs Claude CLI [`SandboxSettings::MIN_CLI_VERSION`] or
+//! newer. Older versions ignore the settings, so the builder refuses them
+//! once it knows the CLI is too old; see
+//! [`ClaudeCliBuilder::detect_cli_version`](crate::ClaudeCliBuilder::detect_cli_version).
+
+use crate::version::ClaudeVersion;
+use serde::{Deserialize, Serialize};
+
+/// The `sandbox` settings object. Unset fields keep the CLI's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Run sandboxed Bash commands without asking for permission.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_allow_bash_if_sandboxed: Option<bool>,
+    /// Commands that always run outside the sandbox.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_commands: Vec<String>,
+    /// Whether a command that fails in the sandbox may be retried outside
+    /// it (with the usual permission prompt). `false` closes that escape
+    /// hatch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_unsandboxed_commands: Option<bool>,
+    /// Allow a sandbox that works inside unprivileged containers at the
+    /// cost of weaker isolation (Linux only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_weaker_nested_sandbox: Option<bool>,
+    #[serde(default, skip_serializing_if = "SandboxNetwork::is_empty")]
+    pub network: SandboxNetwork,
+}
+
+/// The `sandbox.network` settings object.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxNetwork {
+    /// Unix socket paths sandboxed commands may connect to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_unix_sockets: Vec<String>,
+    /// Let sandboxed commands bind to localhost ports (macOS only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_local_binding: Option<bool>,
+    /// Use your own HTTP proxy instead of the CLI's.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_proxy_port: Option<u16>,
+    /// Use your own SOCKS proxy instead of the CLI's.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socks_proxy_port: Option<u16>,
+}
+
+impl SandboxNetwork {
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl SandboxSettings {
+    /// The first Claude CLI release with the Bash sandbox.
+    pub const MIN_CLI_VERSION: ClaudeVersion = ClaudeVersion::new(2, 0, 24);
+
+    /// Sandbox on, everything else at the CLI's defaults.
+    pub fn enabled() -> Self {
+        Self {
+            enabled: Some(true),
+            ..Self::default()
+        }
+    }
+
+    /// Settings for unattended runs: sandboxed commands run without a
+    /// prompt, and nothing falls back to running outside the sandbox.
+    pub fn locked_down() -> Self {
+        Self {
+            auto_allow_bash_if_sandboxed: Some(true),
+            allow_unsandboxed_commands: Some(false),
+            ..Self::enabled()
+        }
+    }
+
+    /// Set whether sandboxed commands run without a permission prompt.
+    pub fn auto_allow_bash(mut self, allow: bool) -> Self {
+        self.auto_allow_bash_if_sandboxed = Some(allow);
+        self
+    }
+
+    /// Set whether commands may be retried outside the sandbox.
+    pub fn allow_unsandboxed_commands(mut self, allow: bool) -> Self {
+        self.allow_unsandboxed_commands = Some(allow);
+        self
+    }
+
+    /// Always run `command` (e.g. `docker`) outside the sandbox.
+    pub fn exclude_command(mut self, command: impl Into<String>) -> Self {
+        self.excluded_commands.push(command.into());
+        self
+    }
+
+    /// Let sandboxed commands connect to the Unix socket at `path`.
+    pub fn allow_unix_socket(mut self, path: impl Into<String>) -> Self {
+        self.network.allow_unix_sockets.push(path.into());
+        self
+    }
+
+    /// Set whether sandboxed commands may bind to localhost ports.
+    pub fn allow_local_binding(mut self, allow: bool) -> Self {
+        self.network.allow_local_binding = Some(allow);
+        self
+    }
+
+    /// Route sandboxed traffic through your own proxies.
+    pub fn proxy_ports(mut self, http: Option<u16>, socks: Option<u16>) -> Self {
+        self.network.http_proxy_port = http;
+        self.network.socks_proxy_port = socks;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_settings_wire_shape() {
+        let settings = SandboxSettings::locked_down()
+            .exclude_command("docker")
+            .allow_unix_socket("/var/run/docker.sock")
+            .proxy_ports(Some(8080), None);
+        assert_eq!(
+            serde_json::to_value(&settings).unwrap(),
+            json!({
+                "enabled": true,
+                "autoAllowBashIfSandboxed": true,
+                "allowUnsandboxedCommands": false,
+                "excludedCommands": ["docker"],
+                "network": {
+                    "allowUnixSockets": ["/var/run/docker.sock"],
+                    "httpProxyPort": 8080
+                }
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(SandboxSettings::enabled()).unwrap(),
+            json!({"enabled": true})
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Tags for finding sessions again later.
+//!
+//! A [`SessionIndex`] is a small JSON file (`sessions.json` in a directory
+//! you choose) mapping session UUIDs to user-defined tags, so an application
+//! can name a conversation (`"pr-1234-review"`) and resume it by that name
+//! later. Install one on a client with `set_session_index` and every session
+//! the client starts is recorded, with any tags given, as soon as the CLI
+//! reports its ID.
+//!
+//! ```no_run
+//! use claude_codes::session_index::SessionIndex;
+//! use claude_codes::ClaudeCliBuilder;
+//!
+//! let index = SessionIndex::open("/var/lib/review-bot")?;
+//! if let Some(session) = index.find_by_tag("pr-1234-review")?.last() {
+//!     let builder = ClaudeCliBuilder::new().resume(Some(session.to_string()));
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! The file is re-read before every lookup and update, so several processes
+//! can share an index; concurrent updates are not locked against each other,
+//! and the last writer wins.
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::io::ClaudeOutput;
+
+/// One session in a [`SessionIndex`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_id: Uuid,
+    /// When the session was first recorded.
+    pub recorded_at: DateTime<Utc>,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+}
+
+/// A tag index stored as `sessions.json` in a directory.
+///
+/// Missing or unreadable index files read as empty.
+#[derive(Debug, Clone)]
+pub struct SessionIndex {
+    path: PathBuf,
+}
+
+impl SessionIndex {
+    /// File name of the index inside its directory.
+    pub const FILE_NAME: &'static str = "sessions.json";
+
+    /// Use the index in `dir`, creating the directory if needed.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            path: dir.join(Self::FILE_NAME),
+        })
+    }
+
+    /// Path of the index file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Every recorded session, oldest first.
+    pub fn sessions(&self) -> io::Result<Vec<SessionRecord>> {
+        self.load()
+    }
+
+    /// The record for `session_id`, if it has been recorded.
+    pub fn get(&self, session_id: Uuid) -> io::Result<Option<SessionRecord>> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .find(|record| record.session_id == session_id))
+    }
+
+    /// Sessions carrying `tag`, oldest first.
+    pub fn find_by_tag(&self, tag: &str) -> io::Result<Vec<Uuid>> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .filter(|record| record.tags.contains(tag))
+            .map(|record| record.session_id)
+            .collect())
+    }
+
+    /// Record `session_id` if it isn't in the index yet. Returns whether it
+    /// was added.
+    pub fn record_session(&self, session_id: Uuid) -> io::Result<bool> {
+        self.update(session_id, |_| false)
+    }
+
+    /// Add `tag` to `session_id`, recording the session if needed.
+    pub fn tag_session(&self, session_id: Uuid, tag: impl Into<String>) -> io::Result<()> {
+        let tag = tag.into();
+        self.update(session_id, |record| record.tags.insert(tag))
+            .map(drop)
+    }
+
+    /// Remove `tag` from `session_id`. Returns whether it was there.
+    pub fn untag_session(&self, session_id: Uuid, tag: &str) -> io::Result<bool> {
+        let mut records = self.load()?;
+        let removed = records
+            .iter_mut()
+            .find(|record| record.session_id == session_id)
+            .is_some_and(|record| record.tags.remove(tag));
+        if removed {
+            self.store(&records)?;
+        }
+        Ok(removed)
+    }
+
+    /// Apply `change` to the record for `session_id`, creating it first if
+    /// needed, and save if anything changed.
+    fn update(
+        &self,
+        session_id: Uuid,
+        change: impl FnOnce(&mut SessionRecord) -> bool,
+    ) -> io::Result<bool> {
+        let mut records = self.load()?;
+        let (record, created) = match records.iter().position(|r| r.session_id == session_id) {
+            Some(i) => (&mut records[i], false),
+            None => {
+                records.push(SessionRecord {
+                    session_id,
+                    recorded_at: Utc::now(),
+                    tags: BTreeSet::new(),
+                });
+                (records.last_mut().unwrap(), true)
+            }
+        };
+        if change(record) || created {
+            self.store(&records)?;
+        }
+        Ok(created)
+    }
+
+    fn load(&self) -> io::Result<Vec<SessionRecord>> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable session index {:?}: {}", self.path, e);
+                Vec::new()
+            })),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn store(&self, records: &[SessionRecord]) -> io::Result<()> {
+        // Write to a temporary file and rename so readers never see a
+        // partially written index.
+        let tmp = self
+            .path
+            .with_extension(format!("{}.tmp", std::process::id()));
+        fs::write(&tmp, serde_json::to_vec_pretty(records)?)?;
+        fs::rename(tmp, &self.path)
+    }
+}
+
+/// Records the sessions a client sees into a [`SessionIndex`].
+#[derive(Debug)]
+pub(crate) struct SessionRecorder {
+    index: SessionIndex,
+    tags: Vec<String>,
+    last: Option<Uuid>,
+}
+
+impl SessionRecorder {
+    pub(crate) fn new(index: SessionIndex, tags: Vec<String>) -> Self {
+        Self {
+            index,
+            tags,
+            last: None,
+        }
+    }
+
+    /// Record the session `output` belongs to, once per session. Failures
+    /// are logged rather than interrupting the client.
+    pub(crate) fn observe(&mut self, output: &ClaudeOutput) {
+        let Some(session_id) = output.session_id().and_then(|id| id.as_uuid()) else {
+            return;
+        };
+        if self.last == Some(session_id) {
+            return;
+        }
+        self.last = Some(session_id);
+        let result = self.index.record_session(session_id).and_then(|_| {
+            self.tags
+                .iter()
+                .try_for_each(|tag| self.index.tag_session(session_id, tag.as_str()))
+        });
+        if let Err(e) = result {
+            warn!("Failed to record session {} in index: {}", session_id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_persist_across_opens() {
+        let dir =
+            std::env::temp_dir().join(format!("claude-codes-session-index-{}", std::process::id()));
+        let first = Uuid::parse_str("2f0c6e7a-5d1b-4a8e-9c3f-0b7d2e4a6c81").unwrap();
+        let second = Uuid::parse_str("0b7d2e4a-6c81-4a8e-9c3f-2f0c6e7a5d1b").unwrap();
+
+        let index = SessionIndex::open(&dir).unwrap();
+        assert!(index.find_by_tag("pr-1234-review").unwrap().is_empty());
+        assert!(index.record_session(first).unwrap());
+        assert!(!index.record_session(first).unwrap());
+        index.tag_session(first, "pr-1234-review").unwrap();
+        index.tag_session(second, "pr-1234-review").unwrap();
+        index.tag_session(second, "nightly").unwrap();
+
+        let reopened = SessionIndex::open(&dir).unwrap();
+        assert_eq!(
+            reopened.find_by_tag("pr-1234-review").unwrap(),
+            [first, second]
+        );
+        assert_eq!(reopened.find_by_tag("nightly").unwrap(), [second]);
+        assert!(reopened.untag_session(second, "nightly").unwrap());
+        assert!(!reopened.untag_session(second, "nightly").unwrap());
+        assert!(index.find_by_tag("nightly").unwrap().is_empty());
+        assert_eq!(index.sessions().unwrap().len(), 2);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_recorder_records_each_session_once() {
+        let dir = std::env::temp_dir().join(format!(
+            "claude-codes-session-recorder-{}",
+            std::process::id()
+        ));
+        let index = SessionIndex::open(&dir).unwrap();
+        let mut recorder = SessionRecorder::new(index.clone(), vec!["nightly".to_string()]);
+        let init: ClaudeOutput = serde_json::from_str(
+            r#"{"type":"system","subtype":"init","session_id":"2f0c6e7a-5d1b-4a8e-9c3f-0b7d2e4a6c81"}"#,
+        )
+        .unwrap();
+        recorder.observe(&init);
+        recorder.observe(&init);
+
+        let sessions = index.sessions().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(
+            sessions[0].session_id.to_string(),
+            "2f0c6e7a-5d1b-4a8e-9c3f-0b7d2e4a6c81"
+        );
+        assert!(sessions[0].tags.contains("nightly"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
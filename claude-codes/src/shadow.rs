@@ -0,0 +1,261 @@
+//! Shadow mode: deny every tool use, but record what it would have been.
+//!
+//! With a [`ShadowLog`] installed via `set_shadow_mode` on
+//! [`AsyncClient`](crate::AsyncClient) or [`SyncClient`](crate::SyncClient),
+//! the client answers every `can_use_tool` permission request itself: the
+//! tool is denied with [`SHADOW_DENIAL`] and the request is written to the
+//! log as one [`ShadowRecord`] per line. Claude sees the denial and carries
+//! on, so a whole prompt can be tried out before it is trusted with real
+//! permissions. [`report`] turns the log into a "what would the agent have
+//! done" summary.
+//!
+//! The CLI only asks about tools that aren't already allowed, so spawn it
+//! with [`permission_prompt_tool("stdio")`](crate::ClaudeCliBuilder::permission_prompt_tool),
+//! no `allowed_tools`, and the default permission mode, then call
+//! `enable_tool_approval`.
+//!
+//! ```no_run
+//! # #[cfg(feature = "sync-client")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use claude_codes::shadow::{self, ShadowLog};
+//! use claude_codes::{ClaudeCliBuilder, ClaudeInput, SyncClient};
+//! use uuid::Uuid;
+//!
+//! let child = ClaudeCliBuilder::new()
+//!     .permission_prompt_tool("stdio")
+//!     .spawn_sync()?;
+//! let mut client = SyncClient::new(child)?;
+//! client.enable_tool_approval()?;
+//! client.set_shadow_mode(ShadowLog::file("shadow.jsonl")?);
+//! client.query(ClaudeInput::user_message("Clean up the build scripts", Uuid::new_v4()))?;
+//!
+//! print!("{}", shadow::report(&shadow::read_log("shadow.jsonl")?));
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "sync-client"))]
+//! # fn main() {}
+//! ```
+
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use crate::io::{ClaudeOutput, ControlRequestPayload, ControlResponse, ToolPermissionRequest};
+
+/// The reason given to Claude for every tool use denied in shadow mode.
+pub const SHADOW_DENIAL: &str =
+    "Shadow mode: this tool use was recorded for review but not executed";
+
+/// One tool use denied in shadow mode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShadowRecord {
+    pub recorded_at: DateTime<Utc>,
+    /// ID of the permission request the CLI sent
+    pub request_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_use_id: Option<String>,
+    pub tool_name: String,
+    pub input: Value,
+    /// The tool use as a terminal would show it, with file edits as `-`/`+`
+    /// diffs (requires the `render` feature)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rendered: Option<String>,
+}
+
+impl ShadowRecord {
+    /// Record `req`, received as permission request `request_id`, now.
+    pub fn new(req: &ToolPermissionRequest, request_id: &str) -> Self {
+        Self {
+            recorded_at: Utc::now(),
+            request_id: request_id.to_string(),
+            tool_use_id: req.tool_use_id.clone(),
+            tool_name: req.tool_name.clone(),
+            input: req.input.clone(),
+            rendered: render(req),
+        }
+    }
+}
+
+#[cfg(feature = "render")]
+fn render(req: &ToolPermissionRequest) -> Option<String> {
+    let block = crate::io::ContentBlock::ToolUse(crate::io::ToolUseBlock {
+        id: req.tool_use_id.clone().unwrap_or_default(),
+        name: req.tool_name.clone(),
+        input: req.input.clone(),
+        caller: None,
+    });
+    let terminal = crate::render::Terminal::plain()
+        .max_lines(usize::MAX)
+        .max_line_chars(usize::MAX);
+    Some(terminal.render_block(&block))
+}
+
+#[cfg(not(feature = "render"))]
+fn render(_req: &ToolPermissionRequest) -> Option<String> {
+    None
+}
+
+/// Where shadow mode writes the tool uses it denies, one JSON
+/// [`ShadowRecord`] per line.
+pub struct ShadowLog {
+    writer: Box<dyn Write + Send>,
+}
+
+impl ShadowLog {
+    /// Wrap any writer (a file, `std::io::stderr()`, a `Vec<u8>`, ...).
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+        }
+    }
+
+    /// Open `path` for appending, creating it if needed.
+    pub fn file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self::new(file))
+    }
+
+    /// Record `record` as one line.
+    pub fn record(&mut self, record: &ShadowRecord) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+
+    /// If `output` is a `can_use_tool` request, record it and return the
+    /// denial to send back.
+    ///
+    /// Write failures are logged and the tool is still denied, so nothing
+    /// runs even when the log is broken.
+    pub(crate) fn intercept(&mut self, output: &ClaudeOutput) -> Option<ControlResponse> {
+        let ClaudeOutput::ControlRequest(req) = output else {
+            return None;
+        };
+        let ControlRequestPayload::CanUseTool(perm) = &req.request else {
+            return None;
+        };
+        debug!("[SHADOW] Denying {} ({})", perm.tool_name, req.request_id);
+        if let Err(e) = self.record(&ShadowRecord::new(perm, &req.request_id)) {
+            warn!("[SHADOW] Failed to record {}: {}", perm.tool_name, e);
+        }
+        Some(perm.deny(SHADOW_DENIAL, &req.request_id))
+    }
+}
+
+/// Read the records a [`ShadowLog`] wrote to `path`, skipping lines that
+/// don't parse.
+pub fn read_log(path: impl AsRef<Path>) -> io::Result<Vec<ShadowRecord>> {
+    let file = io::BufReader::new(fs::File::open(path)?);
+    let mut records = Vec::new();
+    for line in file.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(record) => records.push(record),
+            Err(e) => warn!("[SHADOW] Skipping unreadable record: {}", e),
+        }
+    }
+    Ok(records)
+}
+
+/// A Markdown report of what the agent would have done: a count per tool,
+/// then each tool use in order, rendered when available and as JSON input
+/// otherwise.
+pub fn report(records: &[ShadowRecord]) -> String {
+    let mut out = format!("# Shadow run\n\nDenied tool uses: {}\n\n", records.len());
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for record in records {
+        match counts
+            .iter_mut()
+            .find(|(name, _)| *name == record.tool_name)
+        {
+            Some((_, count)) => *count += 1,
+            None => counts.push((&record.tool_name, 1)),
+        }
+    }
+    for (name, count) in &counts {
+        let _ = writeln!(out, "- {}: {}", name, count);
+    }
+    for (i, record) in records.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "\n## {}. {} ({})\n",
+            i + 1,
+            record.tool_name,
+            record.recorded_at.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+        let body = match &record.rendered {
+            Some(rendered) => rendered.trim_end().to_string(),
+            None => serde_json::to_string_pretty(&record.input).unwrap_or_default(),
+        };
+        let _ = writeln!(out, "```\n{}\n```", body);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// A writer whose contents stay readable after it is moved into a log.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_intercept_denies_and_records() {
+        let buffer = Shared::default();
+        let mut log = ShadowLog::new(buffer.clone());
+        let request = ClaudeOutput::parse_json(
+            r#"{"type":"control_request","request_id":"perm-1","request":{"subtype":"can_use_tool","tool_name":"Edit","input":{"file_path":"src/lib.rs","old_string":"a = 1","new_string":"a = 2"},"tool_use_id":"toolu_1"}}"#,
+        )
+        .unwrap();
+
+        let response = log.intercept(&request).unwrap();
+        let response = serde_json::to_value(&response).unwrap();
+        assert_eq!(response["response"]["request_id"], "perm-1");
+        assert_eq!(response["response"]["response"]["behavior"], "deny");
+        assert_eq!(response["response"]["response"]["message"], SHADOW_DENIAL);
+
+        let assistant = ClaudeOutput::parse_json(
+            r#"{"type":"assistant","message":{"id":"m","role":"assistant","model":"claude-3","content":[]},"session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d"}"#,
+        )
+        .unwrap();
+        assert!(log.intercept(&assistant).is_none());
+
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let record: ShadowRecord = serde_json::from_str(written.trim_end()).unwrap();
+        assert_eq!(record.request_id, "perm-1");
+        assert_eq!(record.tool_use_id.as_deref(), Some("toolu_1"));
+        assert_eq!(record.tool_name, "Edit");
+        #[cfg(feature = "render")]
+        assert_eq!(
+            record.rendered.as_deref(),
+            Some("→ Edit(src/lib.rs)\n  - a = 1\n  + a = 2\n")
+        );
+
+        let report = report(&[record]);
+        assert!(report.starts_with("# Shadow run\n\nDenied tool uses: 1\n\n- Edit: 1\n"));
+        assert!(report.contains("## 1. Edit ("));
+    }
+}
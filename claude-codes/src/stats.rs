@@ -0,0 +1,241 @@
+//! Live throughput and progress counters for a message stream.
+//!
+//! Feed every [`ClaudeOutput`] to a [`StreamStats`] as it arrives and read
+//! the counters back to draw a progress footer: messages and output tokens
+//! per second, how long the current turn has been running, and how many
+//! tools Claude has called.
+//!
+//! ```
+//! use claude_codes::{ClaudeOutput, StreamStats};
+//!
+//! let mut stats = StreamStats::new();
+//! let json = r#"{"type":"assistant","session_id":"9a4c1e6d-4c1f-4f69-9d6e-4c1a6a4e9f10",
+//!     "message":{"id":"msg_1","role":"assistant","model":"claude-sonnet-4","content":[
+//!         {"type":"tool_use","id":"toolu_1","name":"Read","input":{"file_path":"src/lib.rs"}}],
+//!     "usage":{"input_tokens":10,"output_tokens":42}}}"#;
+//! stats.record(&ClaudeOutput::parse_json(json).unwrap());
+//!
+//! assert_eq!(stats.output_tokens(), 42);
+//! assert_eq!(stats.tool_calls(), 1);
+//! assert!(stats.turn_elapsed().is_some());
+//! println!("{stats}");
+//! ```
+
+use crate::io::{ClaudeOutput, ContentBlock};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Counters updated from the messages of one session.
+///
+/// Output tokens come from the `usage` of assistant messages. The CLI
+/// repeats an API message's usage on every content block it emits for it,
+/// so each message ID is counted once, at its highest value.
+///
+/// Displays as a one-line summary such as
+/// `12 msgs · 340 tokens (45.2/s) · 3 tool calls · 7.5s`.
+#[derive(Debug, Clone, Default)]
+pub struct StreamStats {
+    first_at: Option<Instant>,
+    last_at: Option<Instant>,
+    turn_started_at: Option<Instant>,
+    last_turn: Option<Duration>,
+    messages: u64,
+    turns: u64,
+    /// Output tokens of API messages that are complete
+    finished_tokens: u64,
+    /// ID and output tokens of the API message being streamed
+    current: Option<(String, u64)>,
+    tool_calls: BTreeMap<String, u64>,
+}
+
+impl StreamStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the counters with a message received now.
+    pub fn record(&mut self, output: &ClaudeOutput) {
+        self.record_at(output, Instant::now());
+    }
+
+    /// Update the counters with a message received at `at`.
+    pub fn record_at(&mut self, output: &ClaudeOutput, at: Instant) {
+        self.first_at.get_or_insert(at);
+        self.last_at = Some(at);
+        self.messages += 1;
+
+        match output {
+            ClaudeOutput::Assistant(msg) => {
+                self.turn_started_at.get_or_insert(at);
+                let content = &msg.message;
+                let tokens = content.usage.as_ref().map_or(0, |u| u.output_tokens as u64);
+                match &mut self.current {
+                    Some((id, current)) if *id == content.id => {
+                        *current = (*current).max(tokens);
+                    }
+                    _ => {
+                        if let Some((_, previous)) = self.current.take() {
+                            self.finished_tokens += previous;
+                        }
+                        self.current = Some((content.id.clone(), tokens));
+                    }
+                }
+                for block in &content.content {
+                    if let ContentBlock::ToolUse(tool) = block {
+                        *self.tool_calls.entry(tool.name.clone()).or_default() += 1;
+                    }
+                }
+            }
+            ClaudeOutput::User(_) => {
+                self.turn_started_at.get_or_insert(at);
+            }
+            ClaudeOutput::Result(_) => {
+                if let Some(started) = self.turn_started_at.take() {
+                    self.last_turn = Some(at.saturating_duration_since(started));
+                }
+                self.turns += 1;
+            }
+            _ => {}
+        }
+    }
+
+    /// Messages recorded so far.
+    pub fn messages(&self) -> u64 {
+        self.messages
+    }
+
+    /// Output tokens Claude has generated so far.
+    pub fn output_tokens(&self) -> u64 {
+        self.finished_tokens + self.current.as_ref().map_or(0, |(_, tokens)| *tokens)
+    }
+
+    /// Tool calls Claude has made so far.
+    pub fn tool_calls(&self) -> u64 {
+        self.tool_calls.values().sum()
+    }
+
+    /// Tool calls so far, by tool name.
+    pub fn tool_call_counts(&self) -> &BTreeMap<String, u64> {
+        &self.tool_calls
+    }
+
+    /// Turns that have ended with a result.
+    pub fn turns(&self) -> u64 {
+        self.turns
+    }
+
+    /// Time between the first and the latest message.
+    pub fn elapsed(&self) -> Duration {
+        match (self.first_at, self.last_at) {
+            (Some(first), Some(last)) => last.saturating_duration_since(first),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// How long the turn in progress has been running, or `None` between
+    /// turns.
+    pub fn turn_elapsed(&self) -> Option<Duration> {
+        self.turn_started_at.map(|started| started.elapsed())
+    }
+
+    /// How long the last finished turn took.
+    pub fn last_turn_duration(&self) -> Option<Duration> {
+        self.last_turn
+    }
+
+    /// Messages per second over [`elapsed`](Self::elapsed); zero until two
+    /// messages have arrived at different times.
+    pub fn messages_per_sec(&self) -> f64 {
+        self.rate(self.messages)
+    }
+
+    /// Output tokens per second over [`elapsed`](Self::elapsed).
+    pub fn tokens_per_sec(&self) -> f64 {
+        self.rate(self.output_tokens())
+    }
+
+    fn rate(&self, count: u64) -> f64 {
+        let secs = self.elapsed().as_secs_f64();
+        if secs > 0.0 {
+            count as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for StreamStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} msgs · {} tokens ({:.1}/s) · {} tool calls",
+            self.messages,
+            self.output_tokens(),
+            self.tokens_per_sec(),
+            self.tool_calls()
+        )?;
+        let turn = self.turn_elapsed().or(self.last_turn);
+        if let Some(turn) = turn {
+            write!(f, " · {:.1}s", turn.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assistant(id: &str, output_tokens: u32, tool: Option<&str>) -> ClaudeOutput {
+        let content = match tool {
+            Some(name) => {
+                serde_json::json!([{"type": "tool_use", "id": "toolu_1", "name": name, "input": {}}])
+            }
+            None => serde_json::json!([{"type": "text", "text": "..."}]),
+        };
+        serde_json::from_value(serde_json::json!({
+            "type": "assistant",
+            "session_id": "622ae0c3-3d50-4fa7-9ee0-69d691238c6d",
+            "message": {
+                "id": id, "role": "assistant", "model": "claude-sonnet-4",
+                "content": content,
+                "usage": {"input_tokens": 5, "output_tokens": output_tokens}
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_counts_tokens_once_per_message_and_times_turns() {
+        let result: ClaudeOutput = serde_json::from_str(
+            r#"{"type":"result","subtype":"success","is_error":false,"duration_ms":1,"duration_api_ms":1,"num_turns":1,"result":"ok","session_id":"622ae0c3-3d50-4fa7-9ee0-69d691238c6d","total_cost_usd":0.0}"#,
+        )
+        .unwrap();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        let mut stats = StreamStats::new();
+        stats.record_at(&assistant("msg_1", 10, None), at(0));
+        stats.record_at(&assistant("msg_1", 30, Some("Bash")), at(500));
+        stats.record_at(&assistant("msg_2", 20, Some("Bash")), at(1500));
+        stats.record_at(&assistant("msg_2", 20, Some("Read")), at(1800));
+        assert_eq!(stats.output_tokens(), 50);
+        assert!(stats.turn_elapsed().is_some());
+
+        stats.record_at(&result, at(2000));
+        assert_eq!(stats.messages(), 5);
+        assert_eq!(stats.turns(), 1);
+        assert_eq!(stats.tool_calls(), 3);
+        assert_eq!(stats.tool_call_counts()["Bash"], 2);
+        assert_eq!(stats.elapsed(), Duration::from_secs(2));
+        assert_eq!(stats.last_turn_duration(), Some(Duration::from_secs(2)));
+        assert!(stats.turn_elapsed().is_none());
+        assert_eq!(stats.tokens_per_sec(), 25.0);
+        assert_eq!(stats.messages_per_sec(), 2.5);
+        assert_eq!(
+            stats.to_string(),
+            "5 msgs · 50 tokens (25.0/s) · 3 tool calls · 2.0s"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Scaffolding for unit tests that drive a client against a scripted
+//! stand-in for the CLI.
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A path under the temp dir that no other call, test, or test process
+/// returns, ending in `name`.
+pub(crate) fn temp_path(name: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "claude-codes-test-{}-{}-{}",
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed),
+        name
+    ))
+}
+
+/// Write `script` (usually a `#!/bin/sh` loop over stdin) to a fresh
+/// executable file, to pass to `ClaudeCliBuilder::command`.
+pub(crate) fn fake_cli(script: &str) -> PathBuf {
+    let path = temp_path("claude.sh");
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
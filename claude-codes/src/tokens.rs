@@ -0,0 +1,232 @@
+//! Estimating how many tokens a prompt will use before sending it.
+//!
+//! Claude's tokenizer isn't published, so these are estimates. By default
+//! they come from a heuristic of about 3.5 characters per token, which
+//! leans towards overcounting for English prose and code. With the
+//! `tokenizer` feature, text is counted with the `cl100k_base` BPE encoding
+//! instead, which tracks Claude's counts more closely but is still an
+//! approximation.
+//!
+//! A [`TokenBudget`] turns the estimates into a limit: batch schedulers can
+//! [`check`](TokenBudget::check) a prompt and reject it, or
+//! [`split_text`](TokenBudget::split_text) it into pieces that fit, before
+//! spending a turn on it. Install one with `set_prompt_budget` on
+//! `AsyncClient` or `SyncClient` to have oversized user messages refused
+//! with [`Error::PromptTooLarge`] instead of being sent.
+//!
+//! ```
+//! use claude_codes::tokens::{estimate_tokens, TokenBudget};
+//! use claude_codes::ClaudeInput;
+//! use uuid::Uuid;
+//!
+//! let prompt = "Summarize this file:\n\n".to_string() + &"fn main() {}\n".repeat(2000);
+//! let input = ClaudeInput::user_message(prompt.as_str(), Uuid::new_v4());
+//! assert!(estimate_tokens(&input) > 4000);
+//!
+//! let budget = TokenBudget::new(4000);
+//! assert!(budget.check(&input).is_err());
+//! let pieces = budget.split_text(&prompt);
+//! assert!(pieces.iter().all(|piece| budget.fits_text(piece)));
+//! assert_eq!(pieces.concat(), prompt);
+//! ```
+
+use crate::error::{Error, Result};
+use crate::io::{ClaudeInput, ContentBlock};
+
+/// Estimated tokens for one image.
+///
+/// An image costs about `width * height / 750` tokens, and the API scales
+/// images down to roughly 1.15 megapixels, so this is the cost of a
+/// full-size image.
+pub const IMAGE_TOKENS: usize = 1600;
+
+/// Estimate the tokens in `text`.
+pub fn estimate_tokens_text(text: &str) -> usize {
+    #[cfg(feature = "tokenizer")]
+    {
+        tiktoken_rs::cl100k_base_singleton()
+            .encode_ordinary(text)
+            .len()
+    }
+    #[cfg(not(feature = "tokenizer"))]
+    {
+        // About 3.5 characters per token, rounded up.
+        (text.chars().count() * 2).div_ceil(7)
+    }
+}
+
+/// Estimate the prompt tokens `input` will add when sent.
+///
+/// User messages count their text, tool results and images; other blocks,
+/// and raw JSON user messages, are counted as their JSON. Control messages
+/// don't reach the model and count as zero.
+pub fn estimate_tokens(input: &ClaudeInput) -> usize {
+    match input {
+        ClaudeInput::User(user) => user.message.content.iter().map(estimate_block).sum(),
+        ClaudeInput::Raw(value) if value.get("type").and_then(|t| t.as_str()) == Some("user") => {
+            estimate_tokens_text(&value.to_string())
+        }
+        _ => 0,
+    }
+}
+
+fn estimate_block(block: &ContentBlock) -> usize {
+    match block {
+        ContentBlock::Text(text) => estimate_tokens_text(&text.text),
+        ContentBlock::Image(_) => IMAGE_TOKENS,
+        ContentBlock::ToolResult(result) => result
+            .content
+            .as_ref()
+            .map_or(0, |content| estimate_tokens_text(&content.text())),
+        other => serde_json::to_string(other).map_or(0, |json| estimate_tokens_text(&json)),
+    }
+}
+
+/// A limit on the estimated size of one prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBudget {
+    max_tokens: usize,
+}
+
+impl TokenBudget {
+    /// Allow prompts of up to `max_tokens` estimated tokens.
+    pub fn new(max_tokens: usize) -> Self {
+        Self { max_tokens }
+    }
+
+    pub fn max_tokens(&self) -> usize {
+        self.max_tokens
+    }
+
+    /// Whether `text` fits in the budget.
+    pub fn fits_text(&self, text: &str) -> bool {
+        estimate_tokens_text(text) <= self.max_tokens
+    }
+
+    /// The estimated tokens in `input`, or [`Error::PromptTooLarge`] if
+    /// they exceed the budget.
+    pub fn check(&self, input: &ClaudeInput) -> Result<usize> {
+        let estimated = estimate_tokens(input);
+        if estimated > self.max_tokens {
+            return Err(Error::PromptTooLarge {
+                estimated,
+                limit: self.max_tokens,
+            });
+        }
+        Ok(estimated)
+    }
+
+    /// Split `text` into pieces that each fit the budget.
+    ///
+    /// Splits fall between paragraphs where possible, then between lines,
+    /// then between words, and only cut inside a word as a last resort.
+    /// The pieces keep their separators, so concatenated they give back
+    /// `text`.
+    pub fn split_text(&self, text: &str) -> Vec<String> {
+        let mut pieces = Vec::new();
+        self.split_into(text, &["\n\n", "\n", " "], &mut pieces);
+        pieces
+    }
+
+    fn split_into(&self, text: &str, separators: &[&str], pieces: &mut Vec<String>) {
+        if text.is_empty() {
+            return;
+        }
+        if self.fits_text(text) {
+            pieces.push(text.to_string());
+            return;
+        }
+        let Some((separator, finer)) = separators.split_first() else {
+            self.split_chars(text, pieces);
+            return;
+        };
+        let mut current = String::new();
+        for part in text.split_inclusive(separator) {
+            let candidate = format!("{}{}", current, part);
+            if self.fits_text(&candidate) {
+                current = candidate;
+                continue;
+            }
+            if !current.is_empty() {
+                pieces.push(std::mem::take(&mut current));
+            }
+            if self.fits_text(part) {
+                current = part.to_string();
+            } else {
+                self.split_into(part, finer, pieces);
+            }
+        }
+        if !current.is_empty() {
+            pieces.push(current);
+        }
+    }
+
+    /// Cut `text` into runs of characters that fit, halving the run length
+    /// until every run does.
+    fn split_chars(&self, text: &str, pieces: &mut Vec<String>) {
+        let chars: Vec<char> = text.chars().collect();
+        let mut size = self.max_tokens.max(1);
+        loop {
+            let runs: Vec<String> = chars.chunks(size).map(|c| c.iter().collect()).collect();
+            if size == 1 || runs.iter().all(|run| self.fits_text(run)) {
+                pieces.extend(runs);
+                return;
+            }
+            size /= 2;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_estimates_and_budget() {
+        assert_eq!(estimate_tokens_text(""), 0);
+        let text = "The quick brown fox jumps over the lazy dog. ".repeat(100);
+        let tokens = estimate_tokens_text(&text);
+        assert!((800..=1400).contains(&tokens), "{} tokens", tokens);
+
+        let input: ClaudeInput = serde_json::from_value(json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "text", "text": text},
+                {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "iVBORw0KGgo="}}
+            ]},
+            "session_id": Uuid::nil()
+        }))
+        .unwrap();
+        assert_eq!(estimate_tokens(&input), tokens + IMAGE_TOKENS);
+        assert_eq!(estimate_tokens(&ClaudeInput::interrupt()), 0);
+
+        assert!(TokenBudget::new(tokens + IMAGE_TOKENS)
+            .check(&input)
+            .is_ok());
+        assert!(matches!(
+            TokenBudget::new(tokens).check(&input),
+            Err(Error::PromptTooLarge { estimated, limit })
+                if estimated == tokens + IMAGE_TOKENS && limit == tokens
+        ));
+    }
+
+    #[test]
+    fn test_split_text_keeps_text_and_prefers_paragraphs() {
+        let budget = TokenBudget::new(20);
+        let paragraph = "word ".repeat(10);
+        let text = format!("{p}\n\n{p}\n\n{p}", p = paragraph.trim_end());
+        let pieces = budget.split_text(&text);
+        assert!(pieces.len() > 1);
+        assert_eq!(pieces.concat(), text);
+        assert!(pieces.iter().all(|piece| budget.fits_text(piece)));
+        assert!(pieces[0].ends_with("\n\n"));
+
+        let unbroken = "x".repeat(500);
+        let pieces = budget.split_text(&unbroken);
+        assert_eq!(pieces.concat(), unbroken);
+        assert!(pieces.iter().all(|piece| budget.fits_text(piece)));
+        assert!(budget.split_text("").is_empty());
+    }
+}
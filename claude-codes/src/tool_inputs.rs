@@ -145,9 +145,17 @@ pub struct GrepInput {
     pub before_context: Option<u32>,
 
     /// Number of lines to show before and after each match
-    #[serde(rename = "-C", skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "-C",
+        alias = "context",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub context: Option<u32>,
 
+    /// Print only the matched parts of each line
+    #[serde(rename = "-o", skip_serializing_if = "Option::is_none")]
+    pub only_matching: Option<bool>,
+
     /// Output mode: content, files_with_matches, or count
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_mode: Option<GrepOutputMode>,
@@ -228,6 +236,7 @@ pub struct TodoWriteInput {
 
 /// Status of a todo item.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum TodoStatus {
     Pending,
     InProgress,
@@ -279,6 +288,7 @@ impl<'de> Deserialize<'de> for TodoStatus {
 
 /// Output mode for the Grep tool.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum GrepOutputMode {
     /// Show matching lines with context.
     Content,
@@ -333,6 +343,7 @@ impl<'de> Deserialize<'de> for GrepOutputMode {
 
 /// Type of specialized subagent for the Task tool.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum SubagentType {
     /// Command execution specialist.
     Bash,
@@ -391,6 +402,7 @@ impl<'de> Deserialize<'de> for SubagentType {
 
 /// Type of Jupyter notebook cell.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum NotebookCellType {
     /// Code cell.
     Code,
@@ -441,6 +453,7 @@ impl<'de> Deserialize<'de> for NotebookCellType {
 
 /// Type of edit to perform on a notebook cell.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum NotebookEditMode {
     /// Replace the cell's content.
     Replace,
@@ -580,6 +593,14 @@ pub struct NotebookEditInput {
     pub edit_mode: Option<NotebookEditMode>,
 }
 
+impl NotebookEditInput {
+    /// The edit mode, defaulting to [`NotebookEditMode::Replace`] as the CLI
+    /// does when `edit_mode` is omitted.
+    pub fn mode(&self) -> NotebookEditMode {
+        self.edit_mode.clone().unwrap_or(NotebookEditMode::Replace)
+    }
+}
+
 /// Input for the TaskOutput tool - retrieves output from background tasks.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TaskOutputInput {
@@ -660,6 +681,10 @@ pub struct ExitPlanModeInput {
     /// The plan content from plan mode
     #[serde(skip_serializing_if = "Option::is_none")]
     pub plan: Option<String>,
+
+    /// The file the plan was written to
+    #[serde(rename = "planFilePath", skip_serializing_if = "Option::is_none")]
+    pub plan_file_path: Option<String>,
 }
 
 /// An allowed prompt permission for plan mode.
@@ -767,6 +792,7 @@ pub struct ToolSearchInput {
 /// specific to ensure correct deserialization with `#[serde(untagged)]`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
+#[non_exhaustive]
 pub enum ToolInput {
     /// Edit tool - has unique field combination (file_path, old_string, new_string)
     Edit(EditInput),
@@ -877,6 +903,21 @@ impl ToolInput {
         }
     }
 
+    /// The file this tool call modifies, for the tools that edit files
+    /// (`Edit`, `MultiEdit`, `Write`, and `NotebookEdit`).
+    ///
+    /// Returns `None` for every other tool, so collecting this over a
+    /// session's tool calls gives the set of files the agent changed.
+    pub fn edited_path(&self) -> Option<&str> {
+        match self {
+            ToolInput::Edit(input) => Some(&input.file_path),
+            ToolInput::MultiEdit(input) => Some(&input.file_path),
+            ToolInput::Write(input) => Some(&input.file_path),
+            ToolInput::NotebookEdit(input) => Some(&input.notebook_path),
+            _ => None,
+        }
+    }
+
     /// Parse a tool-use `input` payload using the authoritative tool *name*
     /// from the surrounding `ToolUse` block, instead of guessing the variant
     /// from field shape.
@@ -1252,6 +1293,12 @@ mod tests {
         assert_eq!(input.file_type, Some("rust".to_string()));
         assert_eq!(input.case_insensitive, Some(true));
         assert_eq!(input.context, Some(3));
+
+        let input: GrepInput =
+            serde_json::from_value(serde_json::json!({"pattern": "x", "context": 2, "-o": true}))
+                .unwrap();
+        assert_eq!(input.context, Some(2));
+        assert_eq!(input.only_matching, Some(true));
     }
 
     #[test]
@@ -1423,6 +1470,38 @@ mod tests {
         assert_eq!(input.cell_id, Some("abc123".to_string()));
     }
 
+    #[test]
+    fn test_edited_path() {
+        let notebook = ToolInput::from_named_input(
+            "NotebookEdit",
+            serde_json::json!({
+                "notebook_path": "/home/user/analysis.ipynb",
+                "new_source": "df.describe()"
+            }),
+        );
+        let ToolInput::NotebookEdit(ref input) = notebook else {
+            panic!("Expected NotebookEdit variant");
+        };
+        assert_eq!(input.mode(), NotebookEditMode::Replace);
+        assert_eq!(notebook.edited_path(), Some("/home/user/analysis.ipynb"));
+
+        let edit = ToolInput::from_named_input(
+            "Edit",
+            serde_json::json!({
+                "file_path": "/home/user/main.rs",
+                "old_string": "a",
+                "new_string": "b"
+            }),
+        );
+        assert_eq!(edit.edited_path(), Some("/home/user/main.rs"));
+
+        let read = ToolInput::from_named_input(
+            "Read",
+            serde_json::json!({ "file_path": "/home/user/main.rs" }),
+        );
+        assert_eq!(read.edited_path(), None);
+    }
+
     #[test]
     fn test_task_output_input_parsing() {
         let json = serde_json::json!({
@@ -1,7 +1,14 @@
 //! Version checking utilities for Claude CLI compatibility
+//!
+//! Besides the once-per-process warning about untested CLI versions, this
+//! module parses CLI versions ([`ClaudeVersion`]) so options that need a
+//! newer CLI can be checked before spawning; see
+//! [`ClaudeCliBuilder::detect_cli_version`](crate::ClaudeCliBuilder::detect_cli_version).
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use log::{debug, warn};
+use std::fmt;
+use std::path::Path;
 use std::process::Command;
 use std::sync::Once;
 
@@ -11,6 +18,86 @@ const TESTED_VERSION: &str = "2.1.178";
 /// Ensures version warning is only shown once per session
 static VERSION_CHECK: Once = Once::new();
 
+/// A Claude CLI version, as printed by `claude --version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClaudeVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ClaudeVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse `2.1.178`, `2.1.178 (Claude Code)` or a pre-release such as
+    /// `2.2.0-beta.1`, whose suffix is ignored. A missing patch reads as 0.
+    ///
+    /// ```
+    /// use claude_codes::ClaudeVersion;
+    ///
+    /// let version = ClaudeVersion::parse("2.1.178 (Claude Code)").unwrap();
+    /// assert_eq!(version, ClaudeVersion::new(2, 1, 178));
+    /// assert!(version > ClaudeVersion::parse("2.0.24").unwrap());
+    /// ```
+    pub fn parse(text: &str) -> Option<Self> {
+        let word = text.split_whitespace().next()?;
+        let core = word.split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|part| part.parse::<u32>().ok());
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self::new(major, minor, patch))
+    }
+
+    /// The newest version this crate has been tested against.
+    pub fn tested() -> Self {
+        Self::parse(TESTED_VERSION).expect("TESTED_VERSION is a valid version")
+    }
+}
+
+impl fmt::Display for ClaudeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Run `claude --version` and parse its output.
+pub fn installed_version() -> Result<ClaudeVersion> {
+    version_of(Path::new("claude"))
+}
+
+/// Run `<command> --version` and parse its output.
+pub(crate) fn version_of(command: &Path) -> Result<ClaudeVersion> {
+    let output = Command::new(command)
+        .arg("--version")
+        .output()
+        .map_err(Error::Io)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next().unwrap_or("");
+    if !output.status.success() {
+        return Err(Error::Protocol(format!(
+            "{} --version exited with {}",
+            command.display(),
+            output.status
+        )));
+    }
+    ClaudeVersion::parse(line).ok_or_else(|| {
+        Error::Protocol(format!(
+            "Could not parse Claude CLI version from output: '{}'",
+            line
+        ))
+    })
+}
+
 /// Check the Claude CLI version and warn if newer than tested
 /// This will only issue a warning once per program execution
 pub fn check_claude_version() -> Result<()> {
@@ -166,4 +253,22 @@ mod tests {
         assert!(is_version_newer("2.0.0", "1.99.99"));
         assert!(!is_version_newer("0.9.99", "1.0.0"));
     }
+
+    #[test]
+    fn test_parse_version_forms() {
+        assert_eq!(
+            ClaudeVersion::parse("2.1.178 (Claude Code)"),
+            Some(ClaudeVersion::new(2, 1, 178))
+        );
+        assert_eq!(
+            ClaudeVersion::parse("2.2.0-beta.1"),
+            Some(ClaudeVersion::new(2, 2, 0))
+        );
+        assert_eq!(
+            ClaudeVersion::parse("2.1"),
+            Some(ClaudeVersion::new(2, 1, 0))
+        );
+        assert_eq!(ClaudeVersion::parse("Claude Code"), None);
+        assert_eq!(ClaudeVersion::tested().to_string(), TESTED_VERSION);
+    }
 }
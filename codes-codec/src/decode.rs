@@ -0,0 +1,90 @@
+//! Turning frames into values, and values into frames.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// The wire dialect a frame is decoded under.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Flavor {
+    /// Newline-delimited JSON as CLIs print it. A frame that doesn't parse
+    /// is retried from its first `{`, skipping ANSI codes or log prefixes
+    /// written in front of the value.
+    #[default]
+    JsonLines,
+    /// JSON-RPC over stdio. Every frame must be a single JSON object; no
+    /// recovery is attempted.
+    JsonRpc,
+}
+
+impl Flavor {
+    /// Decode one frame as read by [`FrameReader`](crate::FrameReader).
+    ///
+    /// ```
+    /// use codes_codec::Flavor;
+    /// use serde_json::Value;
+    ///
+    /// let frame = "\u{1b}[0m{\"type\":\"result\"}";
+    /// assert!(Flavor::JsonLines.decode::<Value>(frame).is_ok());
+    /// assert!(Flavor::JsonRpc.decode::<Value>(frame).is_err());
+    /// assert!(Flavor::JsonRpc.decode::<Value>("[1, 2]").is_err());
+    /// ```
+    pub fn decode<T: DeserializeOwned>(self, frame: &str) -> serde_json::Result<T> {
+        match self {
+            Flavor::JsonLines => parse_lenient(frame),
+            Flavor::JsonRpc if !frame.trim_start().starts_with('{') => Err(
+                serde::de::Error::custom("JSON-RPC message is not a JSON object"),
+            ),
+            Flavor::JsonRpc => serde_json::from_str(frame),
+        }
+    }
+}
+
+/// Parse `text`, retrying from its first `{` if it doesn't parse as is.
+/// On failure the error of the first attempt is returned.
+pub fn parse_lenient<T: DeserializeOwned>(text: &str) -> serde_json::Result<T> {
+    serde_json::from_str(text).or_else(|first| match text.find('{') {
+        Some(start) if start > 0 => serde_json::from_str(&text[start..]).map_err(|_| first),
+        _ => Err(first),
+    })
+}
+
+/// Serialize `value` as one frame, newline included.
+pub fn encode_frame<T: Serialize + ?Sized>(value: &T) -> serde_json::Result<String> {
+    let mut frame = serde_json::to_string(value)?;
+    frame.push('\n');
+    Ok(frame)
+}
+
+/// Whether an unterminated last line still holds a whole JSON value, after
+/// any leading noise [`parse_lenient`] would skip.
+pub(crate) fn is_complete_json(text: &str) -> bool {
+    parse_lenient::<serde::de::IgnoredAny>(text).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_lenient_keeps_first_error() {
+        assert_eq!(
+            parse_lenient::<Value>("[debug] {\"n\":1}").unwrap(),
+            json!({"n": 1})
+        );
+        let err = parse_lenient::<Value>("## {broken").unwrap_err();
+        assert_eq!(err.to_string(), "expected value at line 1 column 1");
+        assert!(!is_complete_json("{\"n\":"));
+        assert!(is_complete_json("prefix {\"n\":1}"));
+    }
+
+    #[test]
+    fn test_encode_frame_is_one_line() {
+        let frame = encode_frame(&json!({"text": "a\nb"})).unwrap();
+        assert_eq!(frame, "{\"text\":\"a\\nb\"}\n");
+        assert_eq!(
+            Flavor::JsonRpc.decode::<Value>(frame.trim()).unwrap()["text"],
+            "a\nb"
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Line framing: one JSON value per line, read into a reused buffer.
+//!
+//! [`FrameReader`] keeps a single buffer between calls, so a session that
+//! streams thousands of messages does not allocate a fresh `String` for each
+//! one. The returned frame borrows that buffer; decode it before reading the
+//! next.
+//!
+//! If the writer dies while writing a message, the partial last line is
+//! reported as [`FrameError::Truncated`] instead of being handed to the JSON
+//! parser. Lines longer than an optional cap are skipped without being
+//! buffered and reported as [`FrameError::TooLarge`].
+//!
+//! ```
+//! use codes_codec::FrameReader;
+//! use std::io::Cursor;
+//!
+//! let mut input = Cursor::new("{\"a\":1}\r\n\r\n  {\"b\":2}  \n");
+//! let mut frames = FrameReader::new();
+//! assert_eq!(frames.read_frame(&mut input).unwrap(), Some("{\"a\":1}"));
+//! assert_eq!(frames.read_frame(&mut input).unwrap(), Some("{\"b\":2}"));
+//! assert_eq!(frames.read_frame(&mut input).unwrap(), None);
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Read};
+
+use crate::decode::is_complete_json;
+
+/// Why a frame could not be read.
+#[derive(Debug, thiserror::Error)]
+pub enum FrameError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    /// A line was longer than the configured maximum. The line was skipped;
+    /// the stream can still be read.
+    #[error("Message of {size} bytes exceeds the {limit}-byte limit")]
+    TooLarge { size: usize, limit: usize },
+
+    /// The input ended partway through a message, usually because the
+    /// writer exited mid-write. `partial` is what was received.
+    #[error("Output ended mid-message after {} bytes", .partial.len())]
+    Truncated { partial: String },
+}
+
+type Result<T> = std::result::Result<T, FrameError>;
+
+/// Buffer capacity kept after reading an unusually large frame. Larger
+/// buffers are shrunk back to this before the next read so a single huge
+/// message doesn't pin its memory for the rest of the session.
+pub const RETAINED_CAPACITY: usize = 64 * 1024;
+
+/// Longest copy of a frame kept in the history; longer frames are cut.
+pub const HISTORY_LINE_BYTES: usize = 2048;
+
+/// Reads newline-delimited frames into a reused buffer.
+///
+/// Blank lines are skipped and surrounding whitespace, including the `\r` of
+/// CRLF endings, is trimmed. A last line without a newline is returned if it
+/// holds a complete JSON value; otherwise the writer stopped mid-message and
+/// the read fails with [`FrameError::Truncated`].
+#[derive(Debug, Default)]
+pub struct FrameReader {
+    buf: String,
+    start: usize,
+    end: usize,
+    max_bytes: Option<usize>,
+    history: VecDeque<String>,
+    history_len: usize,
+}
+
+impl FrameReader {
+    /// A reader with an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refuse lines longer than `max_bytes` (see [`set_max_bytes`](Self::set_max_bytes)).
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Keep copies of the last `lines` frames, readable with
+    /// [`recent`](Self::recent). Each copy is cut to 2 KiB.
+    pub fn with_history(mut self, lines: usize) -> Self {
+        self.history_len = lines;
+        self
+    }
+
+    /// The last frames read, oldest first (empty unless
+    /// [`with_history`](Self::with_history) was set).
+    ///
+    /// ```
+    /// use codes_codec::FrameReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut input = Cursor::new("{\"n\":1}\n{\"n\":2}\n{\"n\":3}\n");
+    /// let mut frames = FrameReader::new().with_history(2);
+    /// while frames.read_frame(&mut input).unwrap().is_some() {}
+    /// assert_eq!(frames.recent().collect::<Vec<_>>(), ["{\"n\":2}", "{\"n\":3}"]);
+    /// ```
+    pub fn recent(&self) -> impl Iterator<Item = &str> {
+        self.history.iter().map(String::as_str)
+    }
+
+    /// Cap the length of a line, or lift the cap with `None`.
+    ///
+    /// A line longer than the cap is read past and dropped without being
+    /// buffered, and the read returns [`FrameError::TooLarge`]; the next
+    /// read continues with the following line.
+    ///
+    /// ```
+    /// use codes_codec::FrameReader;
+    /// use codes_codec::FrameError;
+    /// use std::io::Cursor;
+    ///
+    /// let mut input = Cursor::new(format!("{}\n{{\"ok\":true}}\n", "x".repeat(100)));
+    /// let mut frames = FrameReader::new().with_max_bytes(32);
+    /// assert!(matches!(
+    ///     frames.read_frame(&mut input),
+    ///     Err(FrameError::TooLarge { size: 100, limit: 32 })
+    /// ));
+    /// assert_eq!(frames.read_frame(&mut input).unwrap(), Some("{\"ok\":true}"));
+    /// ```
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.max_bytes = max_bytes;
+    }
+
+    /// The line length cap, if any.
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
+
+    /// The most recently read frame (empty before the first read).
+    pub fn frame(&self) -> &str {
+        &self.buf[self.start..self.end]
+    }
+
+    /// Current buffer capacity in bytes.
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Read the next non-blank line from `reader`. Returns `None` at EOF.
+    pub fn read_frame<R: BufRead>(&mut self, reader: &mut R) -> Result<Option<&str>> {
+        loop {
+            let mut line = self.take_buffer();
+            let read = reader.take(self.read_limit()).read_until(b'\n', &mut line);
+            match self.store(line, read)? {
+                Line::Eof => return Ok(None),
+                Line::Complete => {
+                    if self.locate() {
+                        self.remember();
+                        return Ok(Some(self.frame()));
+                    }
+                }
+                Line::TooLong(mut size) => loop {
+                    let mut chunk = self.take_buffer();
+                    let read = reader
+                        .take(RETAINED_CAPACITY as u64)
+                        .read_until(b'\n', &mut chunk);
+                    let (len, ended) = self.skip(chunk, read)?;
+                    size += len;
+                    if ended {
+                        return Err(self.too_large(size));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Async version of [`read_frame`](Self::read_frame).
+    #[cfg(feature = "tokio")]
+    pub async fn read_frame_async<R>(&mut self, reader: &mut R) -> Result<Option<&str>>
+    where
+        R: tokio::io::AsyncBufRead + Unpin,
+    {
+        use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+
+        loop {
+            let mut line = self.take_buffer();
+            let read = reader
+                .take(self.read_limit())
+                .read_until(b'\n', &mut line)
+                .await;
+            match self.store(line, read)? {
+                Line::Eof => return Ok(None),
+                Line::Complete => {
+                    if self.locate() {
+                        self.remember();
+                        return Ok(Some(self.frame()));
+                    }
+                }
+                Line::TooLong(mut size) => loop {
+                    let mut chunk = self.take_buffer();
+                    let read = reader
+                        .take(RETAINED_CAPACITY as u64)
+                        .read_until(b'\n', &mut chunk)
+                        .await;
+                    let (len, ended) = self.skip(chunk, read)?;
+                    size += len;
+                    if ended {
+                        return Err(self.too_large(size));
+                    }
+                },
+            }
+        }
+    }
+
+    /// Empty the buffer and hand it out as bytes for the next read.
+    fn take_buffer(&mut self) -> Vec<u8> {
+        self.start = 0;
+        self.end = 0;
+        let mut bytes = std::mem::take(&mut self.buf).into_bytes();
+        bytes.clear();
+        if bytes.capacity() > RETAINED_CAPACITY {
+            bytes.shrink_to(RETAINED_CAPACITY);
+        }
+        bytes
+    }
+
+    /// Give the allocation back to the buffer, empty.
+    fn restore(&mut self, mut bytes: Vec<u8>) {
+        bytes.clear();
+        self.buf = String::from_utf8(bytes).unwrap_or_default();
+    }
+
+    /// How many bytes one read may take: one past the cap, so an oversized
+    /// line is detected without buffering the rest of it.
+    fn read_limit(&self) -> u64 {
+        self.max_bytes
+            .map_or(u64::MAX, |max| (max as u64).saturating_add(1))
+    }
+
+    /// Keep a line just read as the buffer, checking it is UTF-8.
+    fn store(&mut self, line: Vec<u8>, read: io::Result<usize>) -> Result<Line> {
+        let read = match read {
+            Ok(read) => read,
+            Err(e) => {
+                self.restore(line);
+                return Err(FrameError::Io(e));
+            }
+        };
+        if read == 0 {
+            self.restore(line);
+            return Ok(Line::Eof);
+        }
+        if line.last() != Some(&b'\n') && self.max_bytes.is_some_and(|max| line.len() > max) {
+            let size = line.len();
+            self.restore(line);
+            return Ok(Line::TooLong(size));
+        }
+        let terminated = line.last() == Some(&b'\n');
+        match String::from_utf8(line) {
+            Ok(line) => {
+                self.buf = line;
+                let text = self.buf.trim();
+                if terminated || text.is_empty() || is_complete_json(text) {
+                    return Ok(Line::Complete);
+                }
+                let partial = text.to_string();
+                self.buf.clear();
+                Err(FrameError::Truncated { partial })
+            }
+            // Cut off in the middle of a multi-byte character.
+            Err(e) if !terminated => {
+                let partial = String::from_utf8_lossy(e.as_bytes()).trim().to_string();
+                self.restore(e.into_bytes());
+                Err(FrameError::Truncated { partial })
+            }
+            Err(e) => {
+                self.restore(e.into_bytes());
+                Err(FrameError::Io(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stream did not contain valid UTF-8",
+                )))
+            }
+        }
+    }
+
+    /// Drop a chunk of an oversized line. Returns its length and whether the
+    /// line has ended.
+    fn skip(&mut self, chunk: Vec<u8>, read: io::Result<usize>) -> Result<(usize, bool)> {
+        let newline = chunk.last() == Some(&b'\n');
+        let len = chunk.len() - usize::from(newline);
+        self.restore(chunk);
+        let read = read.map_err(FrameError::Io)?;
+        Ok((len, newline || read == 0))
+    }
+
+    fn too_large(&self, size: usize) -> FrameError {
+        FrameError::TooLarge {
+            size,
+            limit: self.max_bytes.unwrap_or_default(),
+        }
+    }
+
+    /// Copy the current frame into the history.
+    fn remember(&mut self) {
+        if self.history_len == 0 {
+            return;
+        }
+        if self.history.len() == self.history_len {
+            self.history.pop_front();
+        }
+        let frame = self.frame();
+        let mut cut = frame.len().min(HISTORY_LINE_BYTES);
+        while !frame.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        self.history.push_back(frame[..cut].to_string());
+    }
+
+    /// Point the frame at the trimmed line; false if it is blank.
+    fn locate(&mut self) -> bool {
+        let trimmed_end = self.buf.trim_end().len();
+        let trimmed_start = trimmed_end - self.buf[..trimmed_end].trim_start().len();
+        self.start = trimmed_start;
+        self.end = trimmed_end;
+        trimmed_start < trimmed_end
+    }
+}
+
+/// What a single read produced.
+enum Line {
+    Eof,
+    /// A whole line is in the buffer.
+    Complete,
+    /// The line passed the cap after this many bytes; the rest is unread.
+    TooLong(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_reuses_and_shrinks_buffer() {
+        let big = "x".repeat(RETAINED_CAPACITY * 4);
+        let input = format!("{{\"n\":1}}\n{}\n{{\"n\":2}}\n", big);
+        let mut reader = Cursor::new(input);
+        let mut frames = FrameReader::new();
+
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), Some("{\"n\":1}"));
+        let small_capacity = frames.capacity();
+        assert_eq!(
+            frames.read_frame(&mut reader).unwrap().unwrap().len(),
+            big.len()
+        );
+        assert!(frames.capacity() > RETAINED_CAPACITY);
+
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), Some("{\"n\":2}"));
+        assert!(frames.capacity() <= RETAINED_CAPACITY.max(small_capacity));
+        assert_eq!(frames.frame(), "{\"n\":2}");
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_oversized_lines_are_skipped() {
+        let huge = "y".repeat(RETAINED_CAPACITY * 3);
+        let input = format!("{}\n{{\"n\":1}}\n{}", huge, "z".repeat(20));
+        let mut reader = Cursor::new(input);
+        let mut frames = FrameReader::new().with_max_bytes(16);
+
+        match frames.read_frame(&mut reader) {
+            Err(FrameError::TooLarge { size, limit: 16 }) => assert_eq!(size, huge.len()),
+            other => panic!("Expected MessageTooLarge, got {:?}", other),
+        }
+        assert!(frames.capacity() <= RETAINED_CAPACITY);
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), Some("{\"n\":1}"));
+        // An oversized line cut off by EOF is still reported.
+        assert!(matches!(
+            frames.read_frame(&mut reader),
+            Err(FrameError::TooLarge { size: 20, .. })
+        ));
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn test_partial_last_line() {
+        let mut reader = Cursor::new("{\"n\":1}\r\n\r\n\n{\"n\":2}");
+        let mut frames = FrameReader::new();
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), Some("{\"n\":1}"));
+        // Complete JSON without a trailing newline is still a message.
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), Some("{\"n\":2}"));
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), None);
+
+        let mut reader = Cursor::new("{\"n\":1}\n{\"type\":\"assistant\",\"mess");
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), Some("{\"n\":1}"));
+        match frames.read_frame(&mut reader) {
+            Err(FrameError::Truncated { partial }) => {
+                assert_eq!(partial, "{\"type\":\"assistant\",\"mess")
+            }
+            other => panic!("Expected TruncatedMessage, got {:?}", other),
+        }
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), None);
+
+        let mut reader = Cursor::new(b"{\"text\":\"\xc3".to_vec());
+        assert!(matches!(
+            frames.read_frame(&mut reader),
+            Err(FrameError::Truncated { .. })
+        ));
+        let mut reader = Cursor::new("  \r\n  ");
+        assert_eq!(frames.read_frame(&mut reader).unwrap(), None);
+    }
+}
@@ -0,0 +1,36 @@
+//! The line codec shared by the `*-codes` SDK crates.
+//!
+//! Every CLI these SDKs drive talks one JSON value per line over stdio,
+//! either as plain newline-delimited JSON (Claude Code, Gemini) or as
+//! JSON-RPC (the Codex app-server, OpenCode's ACP agent). This crate holds
+//! the hardened reading side once:
+//!
+//! - [`FrameReader`] splits a stream into trimmed lines in a reused buffer,
+//!   skips blank lines, caps line length ([`FrameError::TooLarge`]) and
+//!   tells a writer that died mid-message ([`FrameError::Truncated`]) apart
+//!   from a complete last line. With the `tokio` feature it reads from
+//!   `AsyncBufRead` too.
+//! - [`Flavor`] decodes a frame: [`Flavor::JsonLines`] tolerates noise in
+//!   front of the value ([`parse_lenient`]), [`Flavor::JsonRpc`] insists on
+//!   a single object.
+//! - [`encode_frame`] writes a value as one line.
+//!
+//! ```
+//! use codes_codec::{Flavor, FrameReader};
+//! use serde_json::Value;
+//! use std::io::Cursor;
+//!
+//! let mut input = Cursor::new("{\"id\":1,\"result\":{}}\n\n{\"method\":\"ping\"}\n");
+//! let mut frames = FrameReader::new().with_max_bytes(1 << 20);
+//! while let Some(frame) = frames.read_frame(&mut input)? {
+//!     let message: Value = Flavor::JsonRpc.decode(frame)?;
+//!     assert!(message.is_object());
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod decode;
+mod frame;
+
+pub use decode::{encode_frame, parse_lenient, Flavor};
+pub use frame::{FrameError, FrameReader, HISTORY_LINE_BYTES, RETAINED_CAPACITY};
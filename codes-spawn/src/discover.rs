@@ -0,0 +1,64 @@
+//! Finding the CLI binary.
+
+use std::path::{Path, PathBuf};
+
+/// Resolve `command` to a path to execute.
+///
+/// Absolute paths are returned as given. Anything else is looked up on
+/// `PATH`, then in `fallbacks`, the first existing file winning.
+pub fn resolve(command: &Path, fallbacks: &[PathBuf]) -> Option<PathBuf> {
+    if command.is_absolute() {
+        return Some(command.to_path_buf());
+    }
+    which::which(command)
+        .ok()
+        .or_else(|| fallbacks.iter().find(|path| path.is_file()).cloned())
+}
+
+/// Where npm, cargo and Homebrew style installers put `binary`, most
+/// specific first.
+pub fn install_locations(binary: &str) -> Vec<PathBuf> {
+    let mut locations = Vec::new();
+    if let Some(home) = std::env::var_os("HOME") {
+        let home = PathBuf::from(home);
+        for dir in [".local/bin", ".npm-global/bin", ".cargo/bin"] {
+            locations.push(home.join(dir).join(binary));
+        }
+    }
+    locations.push(Path::new("/opt/homebrew/bin").join(binary));
+    locations.push(Path::new("/usr/local/bin").join(binary));
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_prefers_path_then_fallbacks() {
+        assert_eq!(
+            resolve(Path::new("/no/such/tool"), &[]),
+            Some(PathBuf::from("/no/such/tool"))
+        );
+        assert!(resolve(Path::new("sh"), &[]).is_some());
+
+        let dir = std::env::temp_dir().join(format!("codes-spawn-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let installed = dir.join("agent-cli");
+        std::fs::write(&installed, "").unwrap();
+        let fallbacks = [dir.join("missing"), installed.clone()];
+        assert_eq!(
+            resolve(Path::new("codes-spawn-not-on-path"), &fallbacks),
+            Some(installed)
+        );
+        assert_eq!(resolve(Path::new("codes-spawn-not-on-path"), &[]), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_install_locations_end_with_binary() {
+        let locations = install_locations("codex");
+        assert!(locations.iter().all(|path| path.ends_with("codex")));
+        assert_eq!(locations.last().unwrap(), Path::new("/usr/local/bin/codex"));
+    }
+}
@@ -0,0 +1,94 @@
+//! Background readers for a child's stderr.
+//!
+//! Lines are handed to the callback without their line ending. Bytes that
+//! aren't UTF-8 are replaced rather than ending the drain, since a reader
+//! that stops early leaves the child to block on a full pipe.
+
+use std::io::{self, BufRead, BufReader, Read};
+use std::thread::JoinHandle;
+
+/// Read `reader` line by line on a thread called `name` until EOF or a
+/// read error, calling `on_line` for each line.
+pub fn drain_lines<R, F>(name: &str, reader: R, mut on_line: F) -> io::Result<JoinHandle<()>>
+where
+    R: Read + Send + 'static,
+    F: FnMut(&str) + Send + 'static,
+{
+    std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                match reader.read_until(b'\n', &mut line) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => on_line(&decode(&line)),
+                }
+            }
+        })
+}
+
+/// Tokio form of [`drain_lines`], reading on a spawned task.
+#[cfg(feature = "tokio")]
+pub fn drain_lines_async<R, F>(reader: R, mut on_line: F) -> tokio::task::JoinHandle<()>
+where
+    R: tokio::io::AsyncRead + Send + Unpin + 'static,
+    F: FnMut(&str) + Send + 'static,
+{
+    use tokio::io::AsyncBufReadExt;
+
+    tokio::spawn(async move {
+        let mut reader = tokio::io::BufReader::new(reader);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => on_line(&decode(&line)),
+            }
+        }
+    })
+}
+
+fn decode(line: &[u8]) -> std::borrow::Cow<'_, str> {
+    let end = line
+        .iter()
+        .rposition(|&b| b != b'\n' && b != b'\r')
+        .map_or(0, |i| i + 1);
+    String::from_utf8_lossy(&line[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_drain_survives_invalid_utf8() {
+        let input: &[u8] = b"first\r\n\xffsecond\nlast";
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&lines);
+        drain_lines("test-drain", input, move |line| {
+            sink.lock().unwrap().push(line.to_string())
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+        assert_eq!(*lines.lock().unwrap(), ["first", "\u{fffd}second", "last"]);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_drain_async_reads_to_eof() {
+        let input: &[u8] = b"one\ntwo\n";
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&lines);
+        drain_lines_async(input, move |line| {
+            sink.lock().unwrap().push(line.to_string())
+        })
+        .await
+        .unwrap();
+        assert_eq!(*lines.lock().unwrap(), ["one", "two"]);
+    }
+}
@@ -0,0 +1,43 @@
+//! Environment overrides for the child.
+
+use std::ffi::OsStr;
+use std::process::Command;
+
+/// Apply `changes` to `cmd` in order: `Some` sets the variable, `None`
+/// removes it, so later entries win.
+///
+/// For a `tokio::process::Command`, pass `cmd.as_std_mut()`.
+pub fn apply_env<I, K, V>(cmd: &mut Command, changes: I)
+where
+    I: IntoIterator<Item = (K, Option<V>)>,
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    for (key, value) in changes {
+        match value {
+            Some(value) => cmd.env(key, value),
+            None => cmd.env_remove(key),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_later_changes_win() {
+        let mut cmd = Command::new("sh");
+        apply_env(
+            &mut cmd,
+            [
+                ("CODES_SPAWN_A", Some("1")),
+                ("CODES_SPAWN_B", Some("2")),
+                ("CODES_SPAWN_A", None),
+            ],
+        );
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.contains(&(OsStr::new("CODES_SPAWN_A"), None)));
+        assert!(envs.contains(&(OsStr::new("CODES_SPAWN_B"), Some(OsStr::new("2")))));
+    }
+}
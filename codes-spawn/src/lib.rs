@@ -0,0 +1,51 @@
+//! Child-process plumbing shared by the `*-codes` SDK crates.
+//!
+//! Every SDK in this workspace drives an agent CLI as a child process and
+//! needs the same handful of things around it:
+//!
+//! - **Discovery** — [`resolve`] finds the binary on `PATH`, falling back to
+//!   the places installers put it ([`install_locations`]) for GUI apps and
+//!   services that run with a minimal `PATH`.
+//! - **Environment** — [`apply_env`] applies set/remove overrides in order,
+//!   so credentials and nesting markers inherited from this process can be
+//!   scrubbed before the child sees them.
+//! - **stderr** — [`drain_lines`] (and `drain_lines_async` with the `tokio`
+//!   feature) read a pipe line by line in the background. A CLI whose
+//!   stderr is piped but unread blocks once the ~64 KB pipe buffer fills.
+//! - **Shutdown** — [`kill_and_reap`] and [`wait_or_kill`] (and
+//!   `wait_or_kill_async`) always wait on the child after killing it, so
+//!   it doesn't linger as a zombie.
+//!
+//! ```no_run
+//! use std::process::{Command, Stdio};
+//! use std::time::Duration;
+//!
+//! let binary = codes_spawn::resolve("claude".as_ref(), &codes_spawn::install_locations("claude"))
+//!     .expect("claude is installed");
+//! let mut cmd = Command::new(binary);
+//! cmd.arg("--version").stderr(Stdio::piped());
+//! codes_spawn::apply_env(&mut cmd, [("CLAUDECODE", None::<&str>)]);
+//!
+//! let mut child = cmd.spawn()?;
+//! let stderr = child.stderr.take().unwrap();
+//! codes_spawn::drain_lines("claude-stderr", stderr, |line| eprintln!("{line}"))?;
+//! codes_spawn::wait_or_kill(&mut child, Duration::from_secs(5))?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! The crate has no opinion about logging or error types; callers map
+//! [`std::io::Error`] into their own.
+
+mod discover;
+mod drain;
+mod env;
+mod reap;
+
+pub use discover::{install_locations, resolve};
+pub use drain::drain_lines;
+#[cfg(feature = "tokio")]
+pub use drain::drain_lines_async;
+pub use env::apply_env;
+#[cfg(feature = "tokio")]
+pub use reap::wait_or_kill_async;
+pub use reap::{kill_and_reap, wait_or_kill, wait_timeout};
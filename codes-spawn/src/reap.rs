@@ -0,0 +1,111 @@
+//! Stopping the child without leaving a zombie behind.
+//!
+//! A killed process stays in the process table until its parent waits on
+//! it, so every path here that kills also waits.
+
+use std::io;
+use std::process::{Child, ExitStatus};
+use std::time::{Duration, Instant};
+
+/// How often [`wait_timeout`] polls the child.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Kill `child` if it is still running, then wait for it.
+///
+/// Safe to call on a child that has already exited or been reaped.
+pub fn kill_and_reap(child: &mut Child) -> io::Result<()> {
+    if child.try_wait()?.is_some() {
+        return Ok(());
+    }
+    match child.kill() {
+        Ok(()) => {}
+        // It exited between the check and the kill.
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput => {}
+        Err(e) => return Err(e),
+    }
+    child.wait().map(|_| ())
+}
+
+/// Wait up to `timeout` for `child` to exit. `None` if it is still running.
+pub fn wait_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        std::thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+/// Give `child` up to `timeout` to exit on its own (typically after its
+/// stdin was closed), then kill and reap it.
+///
+/// Returns the exit status if it exited in time, `None` if it was killed.
+pub fn wait_or_kill(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    match wait_timeout(child, timeout)? {
+        Some(status) => Ok(Some(status)),
+        None => kill_and_reap(child).map(|()| None),
+    }
+}
+
+/// Tokio form of [`wait_or_kill`].
+#[cfg(feature = "tokio")]
+pub async fn wait_or_kill_async(
+    child: &mut tokio::process::Child,
+    timeout: Duration,
+) -> io::Result<Option<ExitStatus>> {
+    match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(status) => status.map(Some),
+        // `kill` waits for the process after signalling it.
+        Err(_) => child.kill().await.map(|()| None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::{Command, Stdio};
+
+    fn sleeper() -> Child {
+        Command::new("sleep")
+            .arg("30")
+            .stdin(Stdio::null())
+            .spawn()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_wait_or_kill_kills_and_reaps() {
+        let mut child = sleeper();
+        let status = wait_or_kill(&mut child, Duration::from_millis(50)).unwrap();
+        assert!(status.is_none());
+        assert!(child.try_wait().unwrap().is_some());
+        // Already reaped: a second attempt is a no-op.
+        kill_and_reap(&mut child).unwrap();
+    }
+
+    #[test]
+    fn test_wait_or_kill_returns_exit_status() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let status = wait_or_kill(&mut child, Duration::from_secs(10)).unwrap();
+        assert!(status.unwrap().success());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_wait_or_kill_async_kills() {
+        let mut child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let status = wait_or_kill_async(&mut child, Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert!(status.is_none());
+        assert!(child.try_wait().unwrap().is_some());
+    }
+}
@@ -2,9 +2,12 @@
 //!
 //! The [`AppServerBuilder`] configures and spawns `codex app-server --listen stdio://`,
 //! a long-lived process that speaks JSON-RPC over newline-delimited stdio.
+//! It finds the `codex` binary, and sets the working directory, environment,
+//! model, profile and sandbox the server starts with.
 
+use crate::protocol::SandboxMode;
 use log::debug;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
 /// Builder for launching a Codex app-server process.
@@ -25,8 +28,14 @@ pub struct AppServerBuilder {
     /// Raw additional args appended *after* the `--listen stdio://` so they
     /// land as subcommand args to `app-server`.
     extra_args: Vec<String>,
+    /// Environment changes for the spawned process; `None` removes the
+    /// variable.
+    env: Vec<(String, Option<String>)>,
 }
 
+/// Name the builder is known by alongside `ClaudeCliBuilder` in claude-codes.
+pub type CodexCliBuilder = AppServerBuilder;
+
 impl Default for AppServerBuilder {
     fn default() -> Self {
         Self::new()
@@ -41,9 +50,56 @@ impl AppServerBuilder {
             working_directory: None,
             config_overrides: Vec::new(),
             extra_args: Vec::new(),
+            env: Vec::new(),
         }
     }
 
+    /// Create a builder configured from `CODEX_CODES_*` environment
+    /// variables, so deployments can change settings without a rebuild.
+    ///
+    /// | Variable | Sets |
+    /// |---|---|
+    /// | `CODEX_CODES_BINARY` | [`command`](Self::command) |
+    /// | `CODEX_CODES_MODEL` | [`model`](Self::model) |
+    /// | `CODEX_CODES_PROFILE` | [`profile`](Self::profile) |
+    /// | `CODEX_CODES_SANDBOX` | [`sandbox`](Self::sandbox): `read-only`, `workspace-write` or `danger-full-access` |
+    ///
+    /// Unset or empty variables leave the default in place.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::InvalidEnvVar`](crate::Error::InvalidEnvVar) if a variable
+    /// holds a value the builder doesn't accept.
+    pub fn from_env() -> crate::error::Result<Self> {
+        Self::from_env_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_env_lookup(lookup: impl Fn(&str) -> Option<String>) -> crate::error::Result<Self> {
+        let var = |name: &str| lookup(name).filter(|value| !value.is_empty());
+
+        let mut builder = Self::new();
+        if let Some(binary) = var("CODEX_CODES_BINARY") {
+            builder = builder.command(binary);
+        }
+        if let Some(model) = var("CODEX_CODES_MODEL") {
+            builder = builder.model(model);
+        }
+        if let Some(profile) = var("CODEX_CODES_PROFILE") {
+            builder = builder.profile(profile);
+        }
+        if let Some(value) = var("CODEX_CODES_SANDBOX") {
+            let mode =
+                serde_json::from_value(serde_json::Value::String(value.clone())).map_err(|_| {
+                    crate::error::Error::InvalidEnvVar {
+                        name: "CODEX_CODES_SANDBOX".to_string(),
+                        value,
+                    }
+                })?;
+            builder = builder.sandbox(mode);
+        }
+        Ok(builder)
+    }
+
     /// Set custom path to the codex binary.
     pub fn command<P: Into<PathBuf>>(mut self, path: P) -> Self {
         self.command = path.into();
@@ -56,6 +112,56 @@ impl AppServerBuilder {
         self
     }
 
+    /// The directory the app-server will run in: the one set with
+    /// [`working_directory`](Self::working_directory), or the current one.
+    pub(crate) fn resolved_working_directory(&self) -> PathBuf {
+        self.working_directory
+            .clone()
+            .or_else(|| std::env::current_dir().ok())
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// Use `model` for new threads (`-c model=...`).
+    pub fn model<S: Into<String>>(self, model: S) -> Self {
+        self.config_override("model", toml_string(&model.into()))
+    }
+
+    /// Apply the named profile from `config.toml` (`-c profile=...`).
+    pub fn profile<S: Into<String>>(self, profile: S) -> Self {
+        self.config_override("profile", toml_string(&profile.into()))
+    }
+
+    /// Run commands under the given sandbox (`-c sandbox_mode=...`).
+    pub fn sandbox(self, mode: SandboxMode) -> Self {
+        let mode = match mode {
+            SandboxMode::Read_only => "read-only",
+            SandboxMode::Workspace_write => "workspace-write",
+            SandboxMode::Danger_full_access => "danger-full-access",
+        };
+        self.config_override("sandbox_mode", mode)
+    }
+
+    /// Allow or deny network access to commands run under the
+    /// `workspace-write` sandbox.
+    pub fn sandbox_network_access(self, allowed: bool) -> Self {
+        self.config_override(
+            "sandbox_workspace_write.network_access",
+            allowed.to_string(),
+        )
+    }
+
+    /// Set an environment variable on the spawned process only.
+    pub fn env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.env.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Remove an inherited environment variable from the spawned process.
+    pub fn env_remove<K: Into<String>>(mut self, key: K) -> Self {
+        self.env.push((key.into(), None));
+        self
+    }
+
     /// Append a `-c key=value` global config override.
     ///
     /// Repeatable. Each call appends one override; order is preserved on the
@@ -113,12 +219,20 @@ impl AppServerBuilder {
     }
 
     /// Resolve the command path, using `which` for non-absolute paths.
+    ///
+    /// A bare `codex` that isn't on `PATH` is also looked for where the
+    /// installers put it, since GUI apps and services often run with a
+    /// minimal `PATH`.
     fn resolve_command(&self) -> crate::error::Result<PathBuf> {
-        if self.command.is_absolute() {
-            return Ok(self.command.clone());
-        }
-        which::which(&self.command).map_err(|_| crate::error::Error::BinaryNotFound {
-            name: self.command.display().to_string(),
+        let fallbacks = if self.command == Path::new("codex") {
+            codes_spawn::install_locations("codex")
+        } else {
+            Vec::new()
+        };
+        codes_spawn::resolve(&self.command, &fallbacks).ok_or_else(|| {
+            crate::error::Error::BinaryNotFound {
+                name: self.command.display().to_string(),
+            }
         })
     }
 
@@ -160,6 +274,10 @@ impl AppServerBuilder {
         if let Some(ref dir) = self.working_directory {
             cmd.current_dir(dir);
         }
+        codes_spawn::apply_env(
+            cmd.as_std_mut(),
+            self.env.iter().map(|(k, v)| (k, v.as_ref())),
+        );
 
         cmd.spawn().map_err(crate::error::Error::Io)
     }
@@ -184,11 +302,28 @@ impl AppServerBuilder {
         if let Some(ref dir) = self.working_directory {
             cmd.current_dir(dir);
         }
+        codes_spawn::apply_env(&mut cmd, self.env.iter().map(|(k, v)| (k, v.as_ref())));
 
         cmd.spawn().map_err(crate::error::Error::Io)
     }
 }
 
+/// Quote `value` as a TOML basic string.
+fn toml_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -292,4 +427,82 @@ mod tests {
             .build_args();
         assert_eq!(args[1], r#"sandbox_permissions=["disk-full-read-access"]"#);
     }
+
+    #[test]
+    fn test_model_profile_and_sandbox_overrides() {
+        let args = AppServerBuilder::new()
+            .model("gpt-5-codex")
+            .profile("fast")
+            .sandbox(SandboxMode::Workspace_write)
+            .sandbox_network_access(true)
+            .build_args();
+        assert_eq!(
+            &args[..8],
+            [
+                "-c",
+                r#"model="gpt-5-codex""#,
+                "-c",
+                r#"profile="fast""#,
+                "-c",
+                "sandbox_mode=workspace-write",
+                "-c",
+                "sandbox_workspace_write.network_access=true",
+            ]
+        );
+        assert_eq!(toml_string(r#"a"b\c"#), r#""a\"b\\c""#);
+    }
+
+    #[test]
+    fn test_from_env_lookup() {
+        let vars = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs
+                    .iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        let builder = AppServerBuilder::from_env_lookup(vars(&[
+            ("CODEX_CODES_BINARY", "/opt/codex/bin/codex"),
+            ("CODEX_CODES_MODEL", ""),
+            ("CODEX_CODES_SANDBOX", "read-only"),
+        ]))
+        .unwrap();
+        assert_eq!(builder.command, PathBuf::from("/opt/codex/bin/codex"));
+        assert_eq!(builder.build_args()[..2], ["-c", "sandbox_mode=read-only"]);
+
+        let err = AppServerBuilder::from_env_lookup(vars(&[("CODEX_CODES_SANDBOX", "yolo")]))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::Error::InvalidEnvVar { ref name, .. } if name == "CODEX_CODES_SANDBOX"
+        ));
+    }
+
+    #[test]
+    fn test_env_applied_to_spawned_process() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("codex-codes-env-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let server = dir.join("app-server.sh");
+        std::fs::write(
+            &server,
+            "#!/bin/sh\necho \"$CODEX_CODES_TEST_VAR:${HOME-unset}\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&server, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut child = AppServerBuilder::new()
+            .command(&server)
+            .env("CODEX_CODES_TEST_VAR", "set")
+            .env_remove("HOME")
+            .spawn_sync()
+            .unwrap();
+        let mut output = String::new();
+        std::io::Read::read_to_string(child.stdout.as_mut().unwrap(), &mut output).unwrap();
+        child.wait().unwrap();
+        assert_eq!(output.trim(), "set:unset");
+        std::fs::remove_dir_all(dir).ok();
+    }
 }
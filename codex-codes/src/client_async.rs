@@ -44,27 +44,48 @@
 
 use crate::cli::AppServerBuilder;
 use crate::error::{Error, ParseError, Result};
+use crate::file_search::FileMatch;
 use crate::jsonrpc::{
-    JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, RequestId,
+    FrameDirection, JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest,
+    JsonRpcResponse, RawTap, RequestId,
 };
+use crate::mcp::McpTool;
 use crate::messages::{Notification, ServerMessage, ServerRequest};
 use crate::protocol::{
-    ClientInfo, InitializeParams, InitializeResponse, ThreadArchiveParams, ThreadArchiveResponse,
-    ThreadDeleteParams, ThreadDeleteResponse, ThreadForkParams, ThreadForkResponse,
-    ThreadResumeParams, ThreadResumeResponse, ThreadStartParams, ThreadStartResponse,
-    TurnInterruptParams, TurnInterruptResponse, TurnStartParams, TurnStartResponse,
+    ClientInfo, ConfigBatchWriteParams, ConfigEdit, ConfigReadParams, ConfigReadResponse,
+    ConfigValueWriteParams, ConfigWriteResponse, ContextCompactedNotification,
+    FuzzyFileSearchParams, FuzzyFileSearchResponse, InitializeParams, InitializeResponse,
+    ListMcpServerStatusParams, ListMcpServerStatusResponse, McpServerStatus, ReviewStartParams,
+    ReviewStartResponse, ThreadArchiveParams, ThreadArchiveResponse, ThreadCompactStartParams,
+    ThreadCompactStartResponse, ThreadDeleteParams, ThreadDeleteResponse, ThreadForkParams,
+    ThreadForkResponse, ThreadResumeParams, ThreadResumeResponse, ThreadRollbackParams,
+    ThreadRollbackResponse, ThreadStartParams, ThreadStartResponse, TurnInterruptParams,
+    TurnInterruptResponse, TurnStartParams, TurnStartResponse,
 };
+use crate::review::{ReviewEvent, ReviewResult, ReviewSession};
+use crate::version::Capabilities;
+use codes_codec::{Flavor, FrameReader};
 use log::{debug, error, warn};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicI64, Ordering};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use std::time::Duration;
+use tokio::io::{AsyncWriteExt, BufReader, BufWriter};
 use tokio::process::Child;
 
 /// Buffer size for reading stdout (10MB).
 const STDOUT_BUFFER_SIZE: usize = 10 * 1024 * 1024;
 
+/// How long [`AsyncClient::shutdown`] waits for the app-server to exit before
+/// killing it.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// JSON-RPC error code sent when declining a server request the client
+/// doesn't handle.
+const REQUEST_DECLINED: i64 = -32601;
+
 /// Asynchronous multi-turn client for the Codex app-server.
 ///
 /// Communicates with a long-lived `codex app-server` process via
@@ -75,8 +96,11 @@ const STDOUT_BUFFER_SIZE: usize = 10 * 1024 * 1024;
 /// The client automatically kills the app-server process when dropped.
 pub struct AsyncClient {
     child: Child,
-    writer: BufWriter<tokio::process::ChildStdin>,
+    /// `None` once stdin has been closed by [`shutdown`](Self::shutdown)
+    writer: Option<BufWriter<tokio::process::ChildStdin>>,
     reader: BufReader<tokio::process::ChildStdout>,
+    /// Line framing for `reader`, reusing one buffer across messages
+    frames: FrameReader,
     /// Handle to the background task draining the child's stderr pipe.
     /// Kept alive for the lifetime of the client; the task exits on EOF
     /// when the child is killed.
@@ -85,6 +109,14 @@ pub struct AsyncClient {
     /// Buffered incoming messages (notifications/server requests) that arrived
     /// while waiting for a response to a client request.
     buffered: VecDeque<ServerMessage>,
+    /// Directory the app-server runs in; the root for [`file_search`](Self::file_search)
+    working_directory: PathBuf,
+    /// Turns in progress, as thread id to turn id
+    active_turns: HashMap<String, String>,
+    /// Sees every frame sent and received; see [`set_raw_tap`](Self::set_raw_tap)
+    raw_tap: Option<RawTap>,
+    /// What the server is known to support; see [`supports`](Self::supports)
+    capabilities: Capabilities,
 }
 
 impl AsyncClient {
@@ -135,6 +167,7 @@ impl AsyncClient {
     pub async fn spawn(builder: AppServerBuilder) -> Result<Self> {
         crate::version::check_codex_version_async().await?;
 
+        let working_directory = builder.resolved_working_directory();
         let mut child = builder.spawn().await?;
 
         let stdin = child
@@ -158,11 +191,16 @@ impl AsyncClient {
 
         Ok(Self {
             child,
-            writer: BufWriter::new(stdin),
+            writer: Some(BufWriter::new(stdin)),
             reader: BufReader::with_capacity(STDOUT_BUFFER_SIZE, stdout),
+            frames: FrameReader::new(),
             _stderr_drain: stderr_drain,
             next_id: AtomicI64::new(1),
             buffered: VecDeque::new(),
+            working_directory,
+            active_turns: HashMap::new(),
+            raw_tap: None,
+            capabilities: Capabilities::new(),
         })
     }
 
@@ -200,6 +238,8 @@ impl AsyncClient {
                     return Ok(result);
                 }
                 JsonRpcMessage::Error(err) if err.id == id => {
+                    self.capabilities
+                        .observe_error(method, err.error.code, &err.error.message);
                     return Err(Error::JsonRpc {
                         code: err.error.code,
                         message: err.error.message,
@@ -209,6 +249,7 @@ impl AsyncClient {
                 JsonRpcMessage::Notification(notif) => {
                     let typed = Notification::from_envelope(&notif.method, notif.params)
                         .map_err(Error::Json)?;
+                    self.track_turn(&typed);
                     self.buffered.push_back(ServerMessage::Notification(typed));
                 }
                 JsonRpcMessage::Request(req) => {
@@ -270,8 +311,12 @@ impl AsyncClient {
     /// Sends user input to the agent. After calling this, use [`AsyncClient::next_message`]
     /// to stream notifications until `turn/completed` arrives.
     pub async fn turn_start(&mut self, params: &TurnStartParams) -> Result<TurnStartResponse> {
-        self.request(crate::protocol::methods::TURN_START, params)
-            .await
+        let response: TurnStartResponse = self
+            .request(crate::protocol::methods::TURN_START, params)
+            .await?;
+        self.active_turns
+            .insert(params.thread_id.clone(), response.turn.id.clone());
+        Ok(response)
     }
 
     /// Interrupt an active turn.
@@ -283,6 +328,242 @@ impl AsyncClient {
             .await
     }
 
+    /// Turns started by this client, or announced by `turn/started`, that
+    /// haven't completed yet, as `(thread_id, turn_id)`.
+    pub fn active_turns(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.active_turns
+            .iter()
+            .map(|(thread, turn)| (thread.as_str(), turn.as_str()))
+    }
+
+    /// Interrupt every turn in [`active_turns`](AsyncClient::active_turns).
+    ///
+    /// Tries every turn even if one fails, and returns the first error.
+    pub async fn interrupt_all(&mut self) -> Result<()> {
+        let turns: Vec<_> = self
+            .active_turns
+            .iter()
+            .map(|(thread_id, turn_id)| TurnInterruptParams {
+                thread_id: thread_id.clone(),
+                turn_id: turn_id.clone(),
+            })
+            .collect();
+        let mut first_error = None;
+        for params in turns {
+            debug!(
+                "[CLIENT] Interrupting turn {} on {}",
+                params.turn_id, params.thread_id
+            );
+            if let Err(e) = self.turn_interrupt(&params).await {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Start a code review turn (`review/start`).
+    ///
+    /// Follow it with a [`ReviewSession`] built from the response, or use
+    /// [`AsyncClient::review`] to start it and wait for the result.
+    pub async fn review_start(
+        &mut self,
+        params: &ReviewStartParams,
+    ) -> Result<ReviewStartResponse> {
+        let response: ReviewStartResponse = self
+            .request(crate::protocol::methods::REVIEW_START, params)
+            .await?;
+        self.active_turns
+            .insert(response.review_thread_id.clone(), response.turn.id.clone());
+        Ok(response)
+    }
+
+    /// Read messages until the next one that concerns `session`'s review.
+    ///
+    /// Returns items as the review turn completes them, server requests to
+    /// answer with [`AsyncClient::respond`], and finally
+    /// [`ReviewEvent::Finished`]. Returns `Ok(None)` at EOF.
+    pub async fn next_review_event(
+        &mut self,
+        session: &mut ReviewSession,
+    ) -> Result<Option<ReviewEvent>> {
+        while let Some(message) = self.next_message().await? {
+            if let Some(event) = session.observe(message) {
+                return Ok(Some(event));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Run a code review and wait for its result.
+    ///
+    /// Server requests that arrive during the review, such as approvals,
+    /// are declined with a JSON-RPC error; use [`AsyncClient::review_start`] and
+    /// [`AsyncClient::next_review_event`] to answer them instead.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ServerClosed`] if the app-server exits before the review
+    /// turn completes.
+    pub async fn review(&mut self, params: &ReviewStartParams) -> Result<ReviewResult> {
+        let response = self.review_start(params).await?;
+        let mut session = ReviewSession::new(&response);
+        loop {
+            match self.next_review_event(&mut session).await? {
+                Some(ReviewEvent::Finished(result)) => return Ok(*result),
+                Some(ReviewEvent::Request { id, request }) => {
+                    debug!("[CLIENT] Declining {} during review", request.method());
+                    self.respond_error(id, REQUEST_DECLINED, "not handled during review")
+                        .await?;
+                }
+                Some(ReviewEvent::Item(_)) => {}
+                None => return Err(Error::ServerClosed),
+            }
+        }
+    }
+
+    /// Fuzzy-search file paths under the app-server's working directory.
+    ///
+    /// Returns the matches ranked best first, with the matched characters
+    /// as byte ranges into each [`FileMatch::path`]. To search other roots,
+    /// or to pass a cancellation token, use [`AsyncClient::fuzzy_file_search`].
+    pub async fn file_search(&mut self, query: &str) -> Result<Vec<FileMatch>> {
+        let params = FuzzyFileSearchParams {
+            cancellation_token: None,
+            query: query.to_string(),
+            roots: vec![self.working_directory.to_string_lossy().into_owned()],
+        };
+        let mut matches: Vec<FileMatch> = self
+            .fuzzy_file_search(&params)
+            .await?
+            .files
+            .into_iter()
+            .map(FileMatch::from)
+            .collect();
+        matches.sort_by_key(|found| std::cmp::Reverse(found.score));
+        Ok(matches)
+    }
+
+    /// Send a raw `fuzzyFileSearch` request.
+    pub async fn fuzzy_file_search(
+        &mut self,
+        params: &FuzzyFileSearchParams,
+    ) -> Result<FuzzyFileSearchResponse> {
+        self.request(crate::protocol::methods::FUZZYFILESEARCH, params)
+            .await
+    }
+
+    /// Read the effective configuration, and optionally the layers it was
+    /// merged from (see [`ConfigReadParams::with_layers`]).
+    pub async fn config_read(&mut self, params: &ConfigReadParams) -> Result<ConfigReadResponse> {
+        self.request(crate::protocol::methods::CONFIG_READ, params)
+            .await
+    }
+
+    /// Write one value to the user's `config.toml`.
+    pub async fn config_value_write(
+        &mut self,
+        params: &ConfigValueWriteParams,
+    ) -> Result<ConfigWriteResponse> {
+        self.request(crate::protocol::methods::CONFIG_VALUE_WRITE, params)
+            .await
+    }
+
+    /// Apply several edits to the user's `config.toml` in one write.
+    pub async fn config_batch_write(
+        &mut self,
+        params: &ConfigBatchWriteParams,
+    ) -> Result<ConfigWriteResponse> {
+        self.request(crate::protocol::methods::CONFIG_BATCHWRITE, params)
+            .await
+    }
+
+    /// Apply one [`ConfigEdit`] unconditionally, e.g.
+    /// `ConfigEdit::upsert("model", "gpt-5-codex")`.
+    pub async fn config_set(&mut self, edit: ConfigEdit) -> Result<ConfigWriteResponse> {
+        self.config_value_write(&edit.into()).await
+    }
+
+    /// Send a raw `mcpServerStatus/list` request for one page of servers.
+    pub async fn mcp_server_status_list(
+        &mut self,
+        params: &ListMcpServerStatusParams,
+    ) -> Result<ListMcpServerStatusResponse> {
+        self.request(crate::protocol::methods::MCPSERVERSTATUS_LIST, params)
+            .await
+    }
+
+    /// List every configured MCP server, following `nextCursor` through all
+    /// pages.
+    pub async fn mcp_servers(
+        &mut self,
+        params: &ListMcpServerStatusParams,
+    ) -> Result<Vec<McpServerStatus>> {
+        let mut params = params.clone();
+        let mut servers = Vec::new();
+        loop {
+            let page = self.mcp_server_status_list(&params).await?;
+            servers.extend(page.data);
+            match page.next_cursor {
+                Some(cursor) => params.cursor = Some(cursor),
+                None => return Ok(servers),
+            }
+        }
+    }
+
+    /// Every tool offered by the configured MCP servers.
+    pub async fn mcp_tools(&mut self) -> Result<Vec<McpTool>> {
+        let servers = self
+            .mcp_servers(&ListMcpServerStatusParams::tools_only())
+            .await?;
+        Ok(McpTool::from_servers(&servers))
+    }
+
+    /// Start compacting a thread's history (`thread/compact/start`).
+    ///
+    /// The server reports completion with
+    /// [`Notification::ContextCompacted`].
+    pub async fn thread_compact_start(
+        &mut self,
+        params: &ThreadCompactStartParams,
+    ) -> Result<ThreadCompactStartResponse> {
+        self.request(crate::protocol::methods::THREAD_COMPACT_START, params)
+            .await
+    }
+
+    /// Compact a thread's history and wait until it has been compacted.
+    ///
+    /// Messages that arrive meanwhile are read and dropped; use
+    /// [`AsyncClient::thread_compact_start`] to handle them yourself.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ServerClosed`] if the app-server exits first.
+    pub async fn thread_compact(
+        &mut self,
+        thread_id: &str,
+    ) -> Result<ContextCompactedNotification> {
+        self.thread_compact_start(&ThreadCompactStartParams::new(thread_id))
+            .await?;
+        while let Some(message) = self.next_message().await? {
+            if let Some(compacted) = crate::history::compacted(message, thread_id) {
+                return Ok(compacted);
+            }
+        }
+        Err(Error::ServerClosed)
+    }
+
+    /// Drop the last turns of a thread (`thread/rollback`).
+    ///
+    /// [`ThreadRollbackResponse::thread`] gives the thread as it stands
+    /// afterwards.
+    pub async fn thread_rollback(
+        &mut self,
+        params: &ThreadRollbackParams,
+    ) -> Result<ThreadRollbackResponse> {
+        self.request(crate::protocol::methods::THREAD_ROLLBACK, params)
+            .await
+    }
+
     /// Archive a thread.
     pub async fn thread_archive(
         &mut self,
@@ -310,6 +591,7 @@ impl AsyncClient {
         let resp: InitializeResponse = self
             .request(crate::protocol::methods::INITIALIZE, params)
             .await?;
+        self.capabilities = Capabilities::from_initialize(&resp);
         self.send_notification(crate::protocol::methods::INITIALIZED)
             .await?;
         Ok(resp)
@@ -379,6 +661,7 @@ impl AsyncClient {
                         Notification::from_envelope(&method, params.clone()).map_err(|e| {
                             Error::Deserialization(ParseError::from_envelope(method, params, e))
                         })?;
+                    self.track_turn(&typed);
                     return Ok(Some(ServerMessage::Notification(typed)));
                 }
                 JsonRpcMessage::Request(req) => {
@@ -415,6 +698,43 @@ impl AsyncClient {
         EventStream { client: self }
     }
 
+    /// What the connected app-server is known to support.
+    ///
+    /// Filled in by [`AsyncClient::initialize`] and updated whenever the server
+    /// rejects a request as an unknown method.
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Whether requests for `method` (one of [`crate::protocol::methods`])
+    /// can be expected to work, so callers can skip features an older
+    /// app-server lacks.
+    ///
+    /// Optimistic: `true` until a request for `method` has come back as an
+    /// unknown method.
+    pub fn supports(&self, method: &str) -> bool {
+        self.capabilities.supports(method)
+    }
+
+    /// Call `tap` with every raw JSON-RPC frame sent or received, replacing
+    /// any previous tap.
+    ///
+    /// Frames are passed as single lines of JSON, inbound ones before they
+    /// are parsed. The tap runs inline on the client's I/O path, so keep it
+    /// cheap.
+    ///
+    /// ```ignore
+    /// client.set_raw_tap(|direction, frame| eprintln!("{direction}: {frame}"));
+    /// ```
+    pub fn set_raw_tap(&mut self, tap: impl FnMut(FrameDirection, &str) + Send + 'static) {
+        self.raw_tap = Some(Box::new(tap));
+    }
+
+    /// Remove the tap set with [`AsyncClient::set_raw_tap`].
+    pub fn clear_raw_tap(&mut self) {
+        self.raw_tap = None;
+    }
+
     /// Get the process ID.
     pub fn pid(&self) -> Option<u32> {
         self.child.id()
@@ -425,18 +745,65 @@ impl AsyncClient {
         self.child.try_wait().ok().flatten().is_none()
     }
 
-    /// Shut down the app-server process.
+    /// Shut down the app-server gracefully, waiting up to
+    /// [`SHUTDOWN_TIMEOUT`] for it to exit.
+    ///
+    /// See [`AsyncClient::shutdown_timeout`].
+    pub async fn shutdown(self) -> Result<()> {
+        self.shutdown_timeout(SHUTDOWN_TIMEOUT).await
+    }
+
+    /// Shut down the app-server gracefully.
     ///
-    /// Consumes the client. If you don't call this explicitly, the
-    /// [`Drop`] implementation will kill the process automatically.
-    pub async fn shutdown(mut self) -> Result<()> {
+    /// Interrupts any [active turns](AsyncClient::active_turns), closes the
+    /// app-server's stdin so it exits on its own, and kills it if it is
+    /// still running once `timeout` has passed. Consumes the client. If you
+    /// don't call this explicitly, the [`Drop`] implementation will kill
+    /// the process automatically.
+    pub async fn shutdown_timeout(mut self, timeout: Duration) -> Result<()> {
         debug!("[CLIENT] Shutting down");
-        self.child.kill().await.map_err(Error::Io)?;
+        let deadline = tokio::time::Instant::now() + timeout;
+        if self.is_alive() && !self.active_turns.is_empty() {
+            match tokio::time::timeout_at(deadline, self.interrupt_all()).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => debug!("[CLIENT] Failed to interrupt active turns: {}", e),
+                Err(_) => debug!("[CLIENT] Timed out interrupting active turns"),
+            }
+        }
+
+        // The app-server exits when its stdin closes.
+        self.writer = None;
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if codes_spawn::wait_or_kill_async(&mut self.child, remaining)
+            .await
+            .map_err(Error::Io)?
+            .is_none()
+        {
+            debug!(
+                "[CLIENT] App-server still running after {:?}, killed it",
+                timeout
+            );
+        }
         Ok(())
     }
 
     // -- internal --
 
+    fn track_turn(&mut self, notification: &Notification) {
+        match notification {
+            Notification::TurnStarted(started) => {
+                self.active_turns
+                    .insert(started.thread_id.clone(), started.turn.id.clone());
+            }
+            Notification::TurnCompleted(completed)
+                if self.active_turns.get(&completed.thread_id) == Some(&completed.turn.id) =>
+            {
+                self.active_turns.remove(&completed.thread_id);
+            }
+            _ => {}
+        }
+    }
+
     async fn send_notification(&mut self, method: &str) -> Result<()> {
         let notif = JsonRpcNotification {
             method: method.to_string(),
@@ -448,12 +815,13 @@ impl AsyncClient {
     async fn send_raw<T: Serialize>(&mut self, msg: &T) -> Result<()> {
         let json = serde_json::to_string(msg).map_err(Error::Json)?;
         debug!("[CLIENT] Sending: {}", json);
-        self.writer
-            .write_all(json.as_bytes())
-            .await
-            .map_err(Error::Io)?;
-        self.writer.write_all(b"\n").await.map_err(Error::Io)?;
-        self.writer.flush().await.map_err(Error::Io)?;
+        if let Some(tap) = self.raw_tap.as_mut() {
+            tap(FrameDirection::Outbound, &json);
+        }
+        let writer = self.writer.as_mut().ok_or(Error::ServerClosed)?;
+        writer.write_all(json.as_bytes()).await.map_err(Error::Io)?;
+        writer.write_all(b"\n").await.map_err(Error::Io)?;
+        writer.flush().await.map_err(Error::Io)?;
         Ok(())
     }
 
@@ -462,37 +830,28 @@ impl AsyncClient {
     }
 
     async fn read_message_opt(&mut self) -> Result<Option<JsonRpcMessage>> {
-        let mut line = String::new();
-
-        loop {
-            line.clear();
-            let bytes_read = self.reader.read_line(&mut line).await.map_err(Error::Io)?;
-
-            if bytes_read == 0 {
-                debug!("[CLIENT] Stream closed (EOF)");
-                return Ok(None);
-            }
-
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                continue;
-            }
-
-            debug!("[CLIENT] Received: {}", trimmed);
+        let Some(frame) = self.frames.read_frame_async(&mut self.reader).await? else {
+            debug!("[CLIENT] Stream closed (EOF)");
+            return Ok(None);
+        };
 
-            match serde_json::from_str::<JsonRpcMessage>(trimmed) {
-                Ok(msg) => return Ok(Some(msg)),
-                Err(e) => {
-                    warn!(
-                        "[CLIENT] Failed to deserialize message. \
-                         Please report this at https://github.com/meawoppl/rust-code-agent-sdks/issues"
-                    );
-                    warn!("[CLIENT] Parse error: {}", e);
-                    warn!("[CLIENT] Raw: {}", trimmed);
-                    return Err(Error::Deserialization(ParseError::from_line(trimmed, e)));
-                }
-            }
+        debug!("[CLIENT] Received: {}", frame);
+        if let Some(tap) = self.raw_tap.as_mut() {
+            tap(FrameDirection::Inbound, frame);
         }
+
+        Flavor::JsonRpc
+            .decode::<JsonRpcMessage>(frame)
+            .map(Some)
+            .map_err(|e| {
+                warn!(
+                    "[CLIENT] Failed to deserialize message. \
+                 Please report this at https://github.com/meawoppl/rust-code-agent-sdks/issues"
+                );
+                warn!("[CLIENT] Parse error: {}", e);
+                warn!("[CLIENT] Raw: {}", frame);
+                Error::Deserialization(ParseError::from_line(frame, e))
+            })
     }
 }
 
@@ -534,9 +893,102 @@ impl EventStream<'_> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::os::unix::fs::PermissionsExt;
 
     #[test]
     fn test_buffer_size() {
         assert_eq!(STDOUT_BUFFER_SIZE, 10 * 1024 * 1024);
     }
+
+    #[tokio::test]
+    async fn test_shutdown_interrupts_active_turn_and_waits_for_exit() {
+        let dir = std::env::temp_dir().join(format!("codex-codes-shutdown-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let server = dir.join("app-server.sh");
+        let log = dir.join("interrupts.log");
+        // Answer turn/start and turn/interrupt, then exit when stdin closes.
+        let script = format!(
+            r#"#!/bin/sh
+while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/^{{"id":\([0-9]*\),.*/\1/p')
+  case "$line" in
+    *'"turn/start"'*) echo "{{\"id\":$id,\"result\":{{\"turn\":{{\"id\":\"turn_1\",\"items\":[],\"status\":\"inProgress\"}}}}}}" ;;
+    *'"turn/interrupt"'*) printf '%s\n' "$line" >> '{}'; echo "{{\"id\":$id,\"result\":{{}}}}" ;;
+  esac
+done
+"#,
+            log.display()
+        );
+        std::fs::write(&server, script).unwrap();
+        std::fs::set_permissions(&server, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut client = AsyncClient::spawn(AppServerBuilder::new().command(&server))
+            .await
+            .unwrap();
+        let params: TurnStartParams =
+            serde_json::from_value(serde_json::json!({"threadId": "th_1", "input": []})).unwrap();
+        client.turn_start(&params).await.unwrap();
+        assert_eq!(
+            client.active_turns().collect::<Vec<_>>(),
+            [("th_1", "turn_1")]
+        );
+
+        client
+            .shutdown_timeout(Duration::from_secs(10))
+            .await
+            .unwrap();
+        let interrupts = std::fs::read_to_string(&log).unwrap();
+        assert!(interrupts.contains(r#""threadId":"th_1","turnId":"turn_1""#));
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_raw_tap_sees_both_directions() {
+        let dir = std::env::temp_dir().join(format!("codex-codes-tap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let server = dir.join("app-server.sh");
+        // Announce something, then answer every request with an empty result.
+        let script = r#"#!/bin/sh
+echo '{"method":"thread/compacted","params":{"threadId":"th_1","turnId":"turn_1"}}'
+while read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/^{"id":\([0-9]*\),.*/\1/p')
+  echo "{\"id\":$id,\"result\":{}}"
+done
+"#;
+        std::fs::write(&server, script).unwrap();
+        std::fs::set_permissions(&server, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let frames = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut client = AsyncClient::spawn(AppServerBuilder::new().command(&server))
+            .await
+            .unwrap();
+        let seen = frames.clone();
+        client.set_raw_tap(move |direction, frame| {
+            seen.lock().unwrap().push((direction, frame.to_string()));
+        });
+        client
+            .turn_interrupt(&TurnInterruptParams {
+                thread_id: "th_1".to_string(),
+                turn_id: "turn_1".to_string(),
+            })
+            .await
+            .unwrap();
+        client.clear_raw_tap();
+        client.shutdown().await.unwrap();
+
+        let frames = frames.lock().unwrap();
+        let directions: Vec<_> = frames.iter().map(|(direction, _)| *direction).collect();
+        assert_eq!(
+            directions,
+            [
+                FrameDirection::Outbound,
+                FrameDirection::Inbound,
+                FrameDirection::Inbound
+            ]
+        );
+        assert!(frames[0].1.contains(r#""method":"turn/interrupt""#));
+        assert!(frames[1].1.contains("thread/compacted"));
+        assert_eq!(frames[2].1, r#"{"id":1,"result":{}}"#);
+        std::fs::remove_dir_all(dir).ok();
+    }
 }
@@ -44,26 +44,47 @@
 
 use crate::cli::AppServerBuilder;
 use crate::error::{Error, ParseError, Result};
+use crate::file_search::FileMatch;
 use crate::jsonrpc::{
-    JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, RequestId,
+    FrameDirection, JsonRpcError, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest,
+    JsonRpcResponse, RawTap, RequestId,
 };
+use crate::mcp::McpTool;
 use crate::messages::{Notification, ServerMessage, ServerRequest};
 use crate::protocol::{
-    ClientInfo, InitializeParams, InitializeResponse, ThreadArchiveParams, ThreadArchiveResponse,
-    ThreadDeleteParams, ThreadDeleteResponse, ThreadForkParams, ThreadForkResponse,
-    ThreadResumeParams, ThreadResumeResponse, ThreadStartParams, ThreadStartResponse,
-    TurnInterruptParams, TurnInterruptResponse, TurnStartParams, TurnStartResponse,
+    ClientInfo, ConfigBatchWriteParams, ConfigEdit, ConfigReadParams, ConfigReadResponse,
+    ConfigValueWriteParams, ConfigWriteResponse, ContextCompactedNotification,
+    FuzzyFileSearchParams, FuzzyFileSearchResponse, InitializeParams, InitializeResponse,
+    ListMcpServerStatusParams, ListMcpServerStatusResponse, McpServerStatus, ReviewStartParams,
+    ReviewStartResponse, ThreadArchiveParams, ThreadArchiveResponse, ThreadCompactStartParams,
+    ThreadCompactStartResponse, ThreadDeleteParams, ThreadDeleteResponse, ThreadForkParams,
+    ThreadForkResponse, ThreadResumeParams, ThreadResumeResponse, ThreadRollbackParams,
+    ThreadRollbackResponse, ThreadStartParams, ThreadStartResponse, TurnInterruptParams,
+    TurnInterruptResponse, TurnStartParams, TurnStartResponse,
 };
+use crate::review::{ReviewEvent, ReviewResult, ReviewSession};
+use crate::version::Capabilities;
+use codes_codec::{Flavor, FrameReader};
 use log::{debug, warn};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
-use std::collections::VecDeque;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::PathBuf;
 use std::process::Child;
+use std::time::Duration;
 
 /// Buffer size for reading stdout (10MB).
 const STDOUT_BUFFER_SIZE: usize = 10 * 1024 * 1024;
 
+/// How long [`SyncClient::shutdown`] waits for the app-server to exit before
+/// killing it.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// JSON-RPC error code sent when declining a server request the client
+/// doesn't handle.
+const REQUEST_DECLINED: i64 = -32601;
+
 /// Synchronous multi-turn client for the Codex app-server.
 ///
 /// Communicates with a long-lived `codex app-server` process via
@@ -74,14 +95,25 @@ const STDOUT_BUFFER_SIZE: usize = 10 * 1024 * 1024;
 /// The client automatically kills the app-server process when dropped.
 pub struct SyncClient {
     child: Child,
-    writer: BufWriter<std::process::ChildStdin>,
+    /// `None` once stdin has been closed by [`shutdown`](Self::shutdown)
+    writer: Option<BufWriter<std::process::ChildStdin>>,
     reader: BufReader<std::process::ChildStdout>,
+    /// Line framing for `reader`, reusing one buffer across messages
+    frames: FrameReader,
     /// Handle to the background thread draining the child's stderr pipe.
     /// Kept alive for the lifetime of the client; the thread exits on EOF
     /// when the child is killed.
     _stderr_drain: std::thread::JoinHandle<()>,
     next_id: i64,
     buffered: VecDeque<ServerMessage>,
+    /// Directory the app-server runs in; the root for [`file_search`](Self::file_search)
+    working_directory: PathBuf,
+    /// Turns in progress, as thread id to turn id
+    active_turns: HashMap<String, String>,
+    /// Sees every frame sent and received; see [`set_raw_tap`](Self::set_raw_tap)
+    raw_tap: Option<RawTap>,
+    /// What the server is known to support; see [`supports`](Self::supports)
+    capabilities: Capabilities,
 }
 
 impl SyncClient {
@@ -130,6 +162,7 @@ impl SyncClient {
     pub fn spawn(builder: AppServerBuilder) -> Result<Self> {
         crate::version::check_codex_version()?;
 
+        let working_directory = builder.resolved_working_directory();
         let mut child = builder.spawn_sync()?;
 
         let stdin = child
@@ -153,11 +186,16 @@ impl SyncClient {
 
         Ok(Self {
             child,
-            writer: BufWriter::new(stdin),
+            writer: Some(BufWriter::new(stdin)),
             reader: BufReader::with_capacity(STDOUT_BUFFER_SIZE, stdout),
+            frames: FrameReader::new(),
             _stderr_drain: stderr_drain,
             next_id: 1,
             buffered: VecDeque::new(),
+            working_directory,
+            active_turns: HashMap::new(),
+            raw_tap: None,
+            capabilities: Capabilities::new(),
         })
     }
 
@@ -195,6 +233,8 @@ impl SyncClient {
                     return Ok(result);
                 }
                 JsonRpcMessage::Error(err) if err.id == id => {
+                    self.capabilities
+                        .observe_error(method, err.error.code, &err.error.message);
                     return Err(Error::JsonRpc {
                         code: err.error.code,
                         message: err.error.message,
@@ -203,6 +243,7 @@ impl SyncClient {
                 JsonRpcMessage::Notification(notif) => {
                     let typed = Notification::from_envelope(&notif.method, notif.params)
                         .map_err(Error::Json)?;
+                    self.track_turn(&typed);
                     self.buffered.push_back(ServerMessage::Notification(typed));
                 }
                 JsonRpcMessage::Request(req) => {
@@ -254,7 +295,11 @@ impl SyncClient {
     /// Sends user input to the agent. After calling this, use [`SyncClient::events`]
     /// or [`SyncClient::next_message`] to consume notifications until `turn/completed`.
     pub fn turn_start(&mut self, params: &TurnStartParams) -> Result<TurnStartResponse> {
-        self.request(crate::protocol::methods::TURN_START, params)
+        let response: TurnStartResponse =
+            self.request(crate::protocol::methods::TURN_START, params)?;
+        self.active_turns
+            .insert(params.thread_id.clone(), response.turn.id.clone());
+        Ok(response)
     }
 
     /// Interrupt an active turn.
@@ -265,6 +310,223 @@ impl SyncClient {
         self.request(crate::protocol::methods::TURN_INTERRUPT, params)
     }
 
+    /// Turns started by this client, or announced by `turn/started`, that
+    /// haven't completed yet, as `(thread_id, turn_id)`.
+    pub fn active_turns(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.active_turns
+            .iter()
+            .map(|(thread, turn)| (thread.as_str(), turn.as_str()))
+    }
+
+    /// Interrupt every turn in [`active_turns`](SyncClient::active_turns).
+    ///
+    /// Tries every turn even if one fails, and returns the first error.
+    pub fn interrupt_all(&mut self) -> Result<()> {
+        let turns: Vec<_> = self
+            .active_turns
+            .iter()
+            .map(|(thread_id, turn_id)| TurnInterruptParams {
+                thread_id: thread_id.clone(),
+                turn_id: turn_id.clone(),
+            })
+            .collect();
+        let mut first_error = None;
+        for params in turns {
+            debug!(
+                "[CLIENT] Interrupting turn {} on {}",
+                params.turn_id, params.thread_id
+            );
+            if let Err(e) = self.turn_interrupt(&params) {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Start a code review turn (`review/start`).
+    ///
+    /// Follow it with a [`ReviewSession`] built from the response, or use
+    /// [`SyncClient::review`] to start it and wait for the result.
+    pub fn review_start(&mut self, params: &ReviewStartParams) -> Result<ReviewStartResponse> {
+        let response: ReviewStartResponse =
+            self.request(crate::protocol::methods::REVIEW_START, params)?;
+        self.active_turns
+            .insert(response.review_thread_id.clone(), response.turn.id.clone());
+        Ok(response)
+    }
+
+    /// Read messages until the next one that concerns `session`'s review.
+    ///
+    /// Returns items as the review turn completes them, server requests to
+    /// answer with [`SyncClient::respond`], and finally
+    /// [`ReviewEvent::Finished`]. Returns `Ok(None)` at EOF.
+    pub fn next_review_event(
+        &mut self,
+        session: &mut ReviewSession,
+    ) -> Result<Option<ReviewEvent>> {
+        while let Some(message) = self.next_message()? {
+            if let Some(event) = session.observe(message) {
+                return Ok(Some(event));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Run a code review and wait for its result.
+    ///
+    /// Server requests that arrive during the review, such as approvals,
+    /// are declined with a JSON-RPC error; use [`SyncClient::review_start`] and
+    /// [`SyncClient::next_review_event`] to answer them instead.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ServerClosed`] if the app-server exits before the review
+    /// turn completes.
+    pub fn review(&mut self, params: &ReviewStartParams) -> Result<ReviewResult> {
+        let response = self.review_start(params)?;
+        let mut session = ReviewSession::new(&response);
+        loop {
+            match self.next_review_event(&mut session)? {
+                Some(ReviewEvent::Finished(result)) => return Ok(*result),
+                Some(ReviewEvent::Request { id, request }) => {
+                    debug!("[CLIENT] Declining {} during review", request.method());
+                    self.respond_error(id, REQUEST_DECLINED, "not handled during review")?;
+                }
+                Some(ReviewEvent::Item(_)) => {}
+                None => return Err(Error::ServerClosed),
+            }
+        }
+    }
+
+    /// Fuzzy-search file paths under the app-server's working directory.
+    ///
+    /// Returns the matches ranked best first, with the matched characters
+    /// as byte ranges into each [`FileMatch::path`]. To search other roots,
+    /// or to pass a cancellation token, use [`SyncClient::fuzzy_file_search`].
+    pub fn file_search(&mut self, query: &str) -> Result<Vec<FileMatch>> {
+        let params = FuzzyFileSearchParams {
+            cancellation_token: None,
+            query: query.to_string(),
+            roots: vec![self.working_directory.to_string_lossy().into_owned()],
+        };
+        let mut matches: Vec<FileMatch> = self
+            .fuzzy_file_search(&params)?
+            .files
+            .into_iter()
+            .map(FileMatch::from)
+            .collect();
+        matches.sort_by_key(|found| std::cmp::Reverse(found.score));
+        Ok(matches)
+    }
+
+    /// Send a raw `fuzzyFileSearch` request.
+    pub fn fuzzy_file_search(
+        &mut self,
+        params: &FuzzyFileSearchParams,
+    ) -> Result<FuzzyFileSearchResponse> {
+        self.request(crate::protocol::methods::FUZZYFILESEARCH, params)
+    }
+
+    /// Read the effective configuration, and optionally the layers it was
+    /// merged from (see [`ConfigReadParams::with_layers`]).
+    pub fn config_read(&mut self, params: &ConfigReadParams) -> Result<ConfigReadResponse> {
+        self.request(crate::protocol::methods::CONFIG_READ, params)
+    }
+
+    /// Write one value to the user's `config.toml`.
+    pub fn config_value_write(
+        &mut self,
+        params: &ConfigValueWriteParams,
+    ) -> Result<ConfigWriteResponse> {
+        self.request(crate::protocol::methods::CONFIG_VALUE_WRITE, params)
+    }
+
+    /// Apply several edits to the user's `config.toml` in one write.
+    pub fn config_batch_write(
+        &mut self,
+        params: &ConfigBatchWriteParams,
+    ) -> Result<ConfigWriteResponse> {
+        self.request(crate::protocol::methods::CONFIG_BATCHWRITE, params)
+    }
+
+    /// Apply one [`ConfigEdit`] unconditionally, e.g.
+    /// `ConfigEdit::upsert("model", "gpt-5-codex")`.
+    pub fn config_set(&mut self, edit: ConfigEdit) -> Result<ConfigWriteResponse> {
+        self.config_value_write(&edit.into())
+    }
+
+    /// Send a raw `mcpServerStatus/list` request for one page of servers.
+    pub fn mcp_server_status_list(
+        &mut self,
+        params: &ListMcpServerStatusParams,
+    ) -> Result<ListMcpServerStatusResponse> {
+        self.request(crate::protocol::methods::MCPSERVERSTATUS_LIST, params)
+    }
+
+    /// List every configured MCP server, following `nextCursor` through all
+    /// pages.
+    pub fn mcp_servers(
+        &mut self,
+        params: &ListMcpServerStatusParams,
+    ) -> Result<Vec<McpServerStatus>> {
+        let mut params = params.clone();
+        let mut servers = Vec::new();
+        loop {
+            let page = self.mcp_server_status_list(&params)?;
+            servers.extend(page.data);
+            match page.next_cursor {
+                Some(cursor) => params.cursor = Some(cursor),
+                None => return Ok(servers),
+            }
+        }
+    }
+
+    /// Every tool offered by the configured MCP servers.
+    pub fn mcp_tools(&mut self) -> Result<Vec<McpTool>> {
+        let servers = self.mcp_servers(&ListMcpServerStatusParams::tools_only())?;
+        Ok(McpTool::from_servers(&servers))
+    }
+
+    /// Start compacting a thread's history (`thread/compact/start`).
+    ///
+    /// The server reports completion with
+    /// [`Notification::ContextCompacted`].
+    pub fn thread_compact_start(
+        &mut self,
+        params: &ThreadCompactStartParams,
+    ) -> Result<ThreadCompactStartResponse> {
+        self.request(crate::protocol::methods::THREAD_COMPACT_START, params)
+    }
+
+    /// Compact a thread's history and wait until it has been compacted.
+    ///
+    /// Messages that arrive meanwhile are read and dropped; use
+    /// [`SyncClient::thread_compact_start`] to handle them yourself.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::ServerClosed`] if the app-server exits first.
+    pub fn thread_compact(&mut self, thread_id: &str) -> Result<ContextCompactedNotification> {
+        self.thread_compact_start(&ThreadCompactStartParams::new(thread_id))?;
+        while let Some(message) = self.next_message()? {
+            if let Some(compacted) = crate::history::compacted(message, thread_id) {
+                return Ok(compacted);
+            }
+        }
+        Err(Error::ServerClosed)
+    }
+
+    /// Drop the last turns of a thread (`thread/rollback`).
+    ///
+    /// [`ThreadRollbackResponse::thread`] gives the thread as it stands
+    /// afterwards.
+    pub fn thread_rollback(
+        &mut self,
+        params: &ThreadRollbackParams,
+    ) -> Result<ThreadRollbackResponse> {
+        self.request(crate::protocol::methods::THREAD_ROLLBACK, params)
+    }
+
     /// Archive a thread.
     pub fn thread_archive(
         &mut self,
@@ -286,6 +548,7 @@ impl SyncClient {
     pub fn initialize(&mut self, params: &InitializeParams) -> Result<InitializeResponse> {
         let resp: InitializeResponse =
             self.request(crate::protocol::methods::INITIALIZE, params)?;
+        self.capabilities = Capabilities::from_initialize(&resp);
         self.send_notification(crate::protocol::methods::INITIALIZED)?;
         Ok(resp)
     }
@@ -341,6 +604,7 @@ impl SyncClient {
                         Notification::from_envelope(&method, params.clone()).map_err(|e| {
                             Error::Deserialization(ParseError::from_envelope(method, params, e))
                         })?;
+                    self.track_turn(&typed);
                     return Ok(Some(ServerMessage::Notification(typed)));
                 }
                 JsonRpcMessage::Request(req) => {
@@ -367,6 +631,43 @@ impl SyncClient {
         }
     }
 
+    /// What the connected app-server is known to support.
+    ///
+    /// Filled in by [`SyncClient::initialize`] and updated whenever the server
+    /// rejects a request as an unknown method.
+    pub fn capabilities(&self) -> &Capabilities {
+        &self.capabilities
+    }
+
+    /// Whether requests for `method` (one of [`crate::protocol::methods`])
+    /// can be expected to work, so callers can skip features an older
+    /// app-server lacks.
+    ///
+    /// Optimistic: `true` until a request for `method` has come back as an
+    /// unknown method.
+    pub fn supports(&self, method: &str) -> bool {
+        self.capabilities.supports(method)
+    }
+
+    /// Call `tap` with every raw JSON-RPC frame sent or received, replacing
+    /// any previous tap.
+    ///
+    /// Frames are passed as single lines of JSON, inbound ones before they
+    /// are parsed. The tap runs inline on the client's I/O path, so keep it
+    /// cheap.
+    ///
+    /// ```ignore
+    /// client.set_raw_tap(|direction, frame| eprintln!("{direction}: {frame}"));
+    /// ```
+    pub fn set_raw_tap(&mut self, tap: impl FnMut(FrameDirection, &str) + Send + 'static) {
+        self.raw_tap = Some(Box::new(tap));
+    }
+
+    /// Remove the tap set with [`SyncClient::set_raw_tap`].
+    pub fn clear_raw_tap(&mut self) {
+        self.raw_tap = None;
+    }
+
     /// Return an iterator over [`ServerMessage`]s.
     ///
     /// The iterator yields `Result<ServerMessage>` and terminates when the
@@ -376,24 +677,69 @@ impl SyncClient {
         EventIterator { client: self }
     }
 
-    /// Shut down the child process.
+    /// Shut down the app-server gracefully, waiting up to
+    /// [`SHUTDOWN_TIMEOUT`] for it to exit.
     ///
-    /// Kills the process if it's still running. Called automatically on [`Drop`].
+    /// See [`SyncClient::shutdown_timeout`].
     pub fn shutdown(&mut self) -> Result<()> {
+        self.shutdown_timeout(SHUTDOWN_TIMEOUT)
+    }
+
+    /// Shut down the app-server gracefully.
+    ///
+    /// Interrupts any [active turns](SyncClient::active_turns), closes the
+    /// app-server's stdin so it exits on its own, and kills it if it is
+    /// still running once `timeout` has passed. The timeout covers waiting
+    /// for the exit; the interrupts are ordinary requests. The client can't
+    /// send anything afterwards. [`Drop`] kills the process without this
+    /// grace period.
+    pub fn shutdown_timeout(&mut self, timeout: Duration) -> Result<()> {
         debug!("[CLIENT] Shutting down");
-        match self.child.try_wait() {
-            Ok(Some(_)) => Ok(()),
-            Ok(None) => {
-                self.child.kill().map_err(Error::Io)?;
-                self.child.wait().map_err(Error::Io)?;
-                Ok(())
+        if self.child.try_wait().map_err(Error::Io)?.is_some() {
+            return Ok(());
+        }
+        if !self.active_turns.is_empty() {
+            if let Err(e) = self.interrupt_all() {
+                debug!("[CLIENT] Failed to interrupt active turns: {}", e);
             }
-            Err(e) => Err(Error::Io(e)),
         }
+
+        // The app-server exits when its stdin closes.
+        self.writer = None;
+        if codes_spawn::wait_or_kill(&mut self.child, timeout)
+            .map_err(Error::Io)?
+            .is_none()
+        {
+            debug!(
+                "[CLIENT] App-server still running after {:?}, killed it",
+                timeout
+            );
+        }
+        Ok(())
+    }
+
+    /// Kill the process if it's still running.
+    fn kill(&mut self) -> Result<()> {
+        codes_spawn::kill_and_reap(&mut self.child).map_err(Error::Io)
     }
 
     // -- internal --
 
+    fn track_turn(&mut self, notification: &Notification) {
+        match notification {
+            Notification::TurnStarted(started) => {
+                self.active_turns
+                    .insert(started.thread_id.clone(), started.turn.id.clone());
+            }
+            Notification::TurnCompleted(completed)
+                if self.active_turns.get(&completed.thread_id) == Some(&completed.turn.id) =>
+            {
+                self.active_turns.remove(&completed.thread_id);
+            }
+            _ => {}
+        }
+    }
+
     fn send_notification(&mut self, method: &str) -> Result<()> {
         let notif = JsonRpcNotification {
             method: method.to_string(),
@@ -405,9 +751,13 @@ impl SyncClient {
     fn send_raw<T: Serialize>(&mut self, msg: &T) -> Result<()> {
         let json = serde_json::to_string(msg).map_err(Error::Json)?;
         debug!("[CLIENT] Sending: {}", json);
-        self.writer.write_all(json.as_bytes()).map_err(Error::Io)?;
-        self.writer.write_all(b"\n").map_err(Error::Io)?;
-        self.writer.flush().map_err(Error::Io)?;
+        if let Some(tap) = self.raw_tap.as_mut() {
+            tap(FrameDirection::Outbound, &json);
+        }
+        let writer = self.writer.as_mut().ok_or(Error::ServerClosed)?;
+        writer.write_all(json.as_bytes()).map_err(Error::Io)?;
+        writer.write_all(b"\n").map_err(Error::Io)?;
+        writer.flush().map_err(Error::Io)?;
         Ok(())
     }
 
@@ -416,46 +766,34 @@ impl SyncClient {
     }
 
     fn read_message_opt(&mut self) -> Result<Option<JsonRpcMessage>> {
-        loop {
-            let mut line = String::new();
-            match self.reader.read_line(&mut line) {
-                Ok(0) => {
-                    debug!("[CLIENT] Stream closed (EOF)");
-                    return Ok(None);
-                }
-                Ok(_) => {
-                    let trimmed = line.trim();
-                    if trimmed.is_empty() {
-                        continue;
-                    }
-
-                    debug!("[CLIENT] Received: {}", trimmed);
-
-                    match serde_json::from_str::<JsonRpcMessage>(trimmed) {
-                        Ok(msg) => return Ok(Some(msg)),
-                        Err(e) => {
-                            warn!(
-                                "[CLIENT] Failed to deserialize message. \
-                                 Please report this at https://github.com/meawoppl/rust-code-agent-sdks/issues"
-                            );
-                            warn!("[CLIENT] Parse error: {}", e);
-                            warn!("[CLIENT] Raw: {}", trimmed);
-                            return Err(Error::Deserialization(ParseError::from_line(trimmed, e)));
-                        }
-                    }
-                }
-                Err(e) => {
-                    debug!("[CLIENT] Error reading stdout: {}", e);
-                    return Err(Error::Io(e));
-                }
-            }
+        let Some(frame) = self.frames.read_frame(&mut self.reader)? else {
+            debug!("[CLIENT] Stream closed (EOF)");
+            return Ok(None);
+        };
+
+        debug!("[CLIENT] Received: {}", frame);
+        if let Some(tap) = self.raw_tap.as_mut() {
+            tap(FrameDirection::Inbound, frame);
         }
+
+        Flavor::JsonRpc
+            .decode::<JsonRpcMessage>(frame)
+            .map(Some)
+            .map_err(|e| {
+                warn!(
+                    "[CLIENT] Failed to deserialize message. \
+                 Please report this at https://github.com/meawoppl/rust-code-agent-sdks/issues"
+                );
+                warn!("[CLIENT] Parse error: {}", e);
+                warn!("[CLIENT] Raw: {}", frame);
+                Error::Deserialization(ParseError::from_line(frame, e))
+            })
     }
 }
 
 impl Drop for SyncClient {
     fn drop(&mut self) {
-        if let Err(e) = self.shutdown() {
+        if let Err(e) = self.kill() {
             debug!("[CLIENT] Error during shutdown: {}", e);
         }
     }
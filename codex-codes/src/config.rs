@@ -0,0 +1,185 @@
+//! Helpers for reading and editing Codex configuration.
+//!
+//! `config/read` returns the effective [`Config`] together with the layers
+//! it was merged from (system, user, profile, project, session flags…);
+//! `config/value/write` and `config/batchWrite` edit the user's
+//! `config.toml`. The generated types are used as-is; this module adds
+//! constructors for edits, including edits scoped to a named profile, and
+//! lookups into a [`ConfigReadResponse`].
+//!
+//! Writes can be made conditional on the version returned by an earlier
+//! read or write, so a settings UI doesn't clobber edits made elsewhere:
+//!
+//! ```ignore
+//! use codex_codes::{ConfigBatchWriteParams, ConfigEdit, ConfigReadParams};
+//!
+//! let current = client.config_read(&ConfigReadParams::with_layers("/repo")).await?;
+//! client
+//!     .config_batch_write(&ConfigBatchWriteParams {
+//!         edits: vec![
+//!             ConfigEdit::upsert("model", "gpt-5-codex"),
+//!             ConfigEdit::upsert_in_profile("fast", "model_reasoning_effort", "low"),
+//!         ],
+//!         expected_version: current.user_layer(None).map(|layer| layer.version.clone()),
+//!         file_path: None,
+//!         reload_user_config: Some(true),
+//!     })
+//!     .await?;
+//! ```
+
+use crate::protocol::{
+    Config, ConfigEdit, ConfigLayer, ConfigLayerMetadata, ConfigLayerSource, ConfigReadParams,
+    ConfigReadResponse, ConfigValueWriteParams, MergeStrategy,
+};
+use serde::Serialize;
+use serde_json::Value;
+
+impl ConfigEdit {
+    /// Set `key_path` (dotted, e.g. `sandbox_workspace_write.network_access`)
+    /// to `value`, merging tables into what is already there.
+    pub fn upsert(key_path: impl Into<String>, value: impl Serialize) -> Self {
+        Self::new(key_path.into(), value, MergeStrategy::Upsert)
+    }
+
+    /// Set `key_path` to `value`, replacing whatever is there.
+    pub fn replace(key_path: impl Into<String>, value: impl Serialize) -> Self {
+        Self::new(key_path.into(), value, MergeStrategy::Replace)
+    }
+
+    /// Like [`upsert`](Self::upsert), for a key of the named profile
+    /// (`profiles.<profile>.<key_path>`).
+    pub fn upsert_in_profile(
+        profile: &str,
+        key_path: impl AsRef<str>,
+        value: impl Serialize,
+    ) -> Self {
+        Self::upsert(profile_key(profile, key_path.as_ref()), value)
+    }
+
+    /// Remove `key_path`, by replacing it with `null`.
+    pub fn clear(key_path: impl Into<String>) -> Self {
+        Self::replace(key_path, Value::Null)
+    }
+
+    fn new(key_path: String, value: impl Serialize, merge_strategy: MergeStrategy) -> Self {
+        Self {
+            key_path,
+            merge_strategy,
+            // Values are plain config data; a type that can't serialize to
+            // JSON is a caller bug, surfaced by the server as a bad value.
+            value: serde_json::to_value(value).unwrap_or(Value::Null),
+        }
+    }
+}
+
+impl From<ConfigEdit> for ConfigValueWriteParams {
+    fn from(edit: ConfigEdit) -> Self {
+        Self {
+            expected_version: None,
+            file_path: None,
+            key_path: edit.key_path,
+            merge_strategy: edit.merge_strategy,
+            value: edit.value,
+        }
+    }
+}
+
+impl Default for ConfigReadParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigReadParams {
+    /// Read just the effective config, as it applies in the app-server's
+    /// working directory.
+    pub fn new() -> Self {
+        Self {
+            cwd: None,
+            include_layers: None,
+        }
+    }
+
+    /// Read the config as it applies in `cwd`, including project layers,
+    /// and return the individual layers too.
+    pub fn with_layers(cwd: impl Into<String>) -> Self {
+        Self {
+            cwd: Some(cwd.into()),
+            include_layers: Some(true),
+        }
+    }
+}
+
+impl ConfigReadResponse {
+    /// The effective configuration.
+    pub fn effective(&self) -> &Config {
+        &self.config
+    }
+
+    /// The layer that set `key_path`, e.g. `model` or `tools.web_search`.
+    pub fn origin(&self, key_path: &str) -> Option<&ConfigLayerMetadata> {
+        self.origins.get(key_path)
+    }
+
+    /// The user's `config.toml` layer, or the layer of one of its profiles.
+    ///
+    /// Only available when the read asked for layers.
+    pub fn user_layer(&self, profile: Option<&str>) -> Option<&ConfigLayer> {
+        self.layers.as_ref()?.iter().find(|layer| {
+            matches!(&layer.name, ConfigLayerSource::User { profile: p, .. } if p.as_deref() == profile)
+        })
+    }
+
+    /// Names of the profiles defined in the user layer.
+    ///
+    /// Only available when the read asked for layers.
+    pub fn profiles(&self) -> Vec<&str> {
+        self.user_layer(None)
+            .and_then(|layer| layer.config.get("profiles")?.as_object())
+            .map(|profiles| profiles.keys().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn profile_key(profile: &str, key_path: &str) -> String {
+    format!("profiles.{}.{}", profile, key_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_edits_serialize_to_wire_shape() {
+        let edit = ConfigEdit::upsert_in_profile("fast", "model_reasoning_effort", "low");
+        assert_eq!(
+            serde_json::to_value(&edit).unwrap(),
+            json!({"keyPath": "profiles.fast.model_reasoning_effort", "mergeStrategy": "upsert", "value": "low"})
+        );
+        let write = ConfigValueWriteParams::from(ConfigEdit::clear("model"));
+        assert_eq!(write.merge_strategy, MergeStrategy::Replace);
+        assert_eq!(write.value, Value::Null);
+    }
+
+    #[test]
+    fn test_read_response_lookups() {
+        let response: ConfigReadResponse = serde_json::from_value(json!({
+            "config": {"model": "gpt-5-codex"},
+            "origins": {"model": {"name": {"type": "user", "file": "/home/me/.codex/config.toml"}, "version": "v1"}},
+            "layers": [
+                {"name": {"type": "user", "file": "/home/me/.codex/config.toml"}, "version": "v1",
+                 "config": {"model": "gpt-5-codex", "profiles": {"fast": {}, "deep": {}}}},
+                {"name": {"type": "sessionFlags"}, "version": "v0", "config": {}}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(response.effective().model.as_deref(), Some("gpt-5-codex"));
+        assert_eq!(response.origin("model").unwrap().version, "v1");
+        assert_eq!(response.user_layer(None).unwrap().version, "v1");
+        assert!(response.user_layer(Some("fast")).is_none());
+        let mut profiles = response.profiles();
+        profiles.sort();
+        assert_eq!(profiles, ["deep", "fast"]);
+    }
+}
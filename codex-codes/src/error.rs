@@ -159,6 +159,11 @@ pub enum Error {
     #[error("Binary not found: '{name}' is not on PATH. Is it installed?")]
     BinaryNotFound { name: String },
 
+    /// A `CODEX_CODES_*` environment variable read by
+    /// `AppServerBuilder::from_env` holds an unusable value.
+    #[error("Invalid value for {name}: {value:?}")]
+    InvalidEnvVar { name: String, value: String },
+
     /// An unclassified error.
     #[error("Unknown error: {0}")]
     Unknown(String),
@@ -167,6 +172,16 @@ pub enum Error {
 /// A `Result` type alias using [`enum@Error`].
 pub type Result<T> = std::result::Result<T, Error>;
 
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+impl From<codes_codec::FrameError> for Error {
+    fn from(err: codes_codec::FrameError) -> Self {
+        match err {
+            codes_codec::FrameError::Io(e) => Error::Io(e),
+            other => Error::Protocol(other.to_string()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
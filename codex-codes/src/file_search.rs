@@ -0,0 +1,101 @@
+//! Fuzzy file search results, shaped for `@`-mention pickers.
+//!
+//! The app-server's `fuzzyFileSearch` request ranks the files under one or
+//! more roots against a query. [`FileMatch`] is the friendlier form of its
+//! [`FuzzyFileSearchResult`] entries: the matched characters come back as
+//! byte ranges into [`FileMatch::path`], ready for slicing or highlighting.
+//!
+//! ```
+//! use codex_codes::{FileMatch, FuzzyFileSearchResult};
+//!
+//! let raw: FuzzyFileSearchResult = serde_json::from_str(r#"{
+//!     "root": "/repo", "path": "src/main.rs", "fileName": "main.rs",
+//!     "matchType": "file", "score": 42, "indices": [4, 5, 6]
+//! }"#).unwrap();
+//! let found = FileMatch::from(raw);
+//! assert_eq!(found.spans, vec![4..7]);
+//! assert_eq!(&found.path[found.spans[0].clone()], "mai");
+//! ```
+
+use crate::protocol::{FuzzyFileSearchMatchType, FuzzyFileSearchResult};
+use std::ops::Range;
+use std::path::PathBuf;
+
+/// One entry from a fuzzy file search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMatch {
+    /// The search root the match was found under.
+    pub root: String,
+    /// Path of the match relative to `root`.
+    pub path: String,
+    /// Last component of `path`.
+    pub file_name: String,
+    /// Higher is better.
+    pub score: i64,
+    pub is_directory: bool,
+    /// Byte ranges of `path` that matched the query, in order and merged
+    /// where adjacent.
+    pub spans: Vec<Range<usize>>,
+}
+
+impl FileMatch {
+    /// `root` joined with `path`.
+    pub fn full_path(&self) -> PathBuf {
+        PathBuf::from(&self.root).join(&self.path)
+    }
+}
+
+impl From<FuzzyFileSearchResult> for FileMatch {
+    fn from(result: FuzzyFileSearchResult) -> Self {
+        let spans = spans(&result.path, result.indices.as_deref().unwrap_or_default());
+        Self {
+            root: result.root,
+            path: result.path,
+            file_name: result.file_name,
+            score: result.score,
+            is_directory: result.match_type == FuzzyFileSearchMatchType::Directory,
+            spans,
+        }
+    }
+}
+
+/// Turn the server's character indices into merged byte ranges of `path`.
+/// Indices past the end of `path` are ignored.
+fn spans(path: &str, indices: &[i64]) -> Vec<Range<usize>> {
+    let mut indices: Vec<usize> = indices
+        .iter()
+        .filter_map(|&i| usize::try_from(i).ok())
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    let mut wanted = indices.into_iter().peekable();
+    for (char_index, (start, c)) in path.char_indices().enumerate() {
+        if wanted.peek().is_none() {
+            break;
+        }
+        if wanted.next_if_eq(&char_index).is_none() {
+            continue;
+        }
+        let end = start + c.len_utf8();
+        match spans.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => spans.push(start..end),
+        }
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans_are_byte_ranges_over_chars() {
+        // "é" is two bytes, so char 2 ("x") starts at byte 3.
+        assert_eq!(spans("aéx/y", &[0, 1, 2, 4, 9]), vec![0..4, 5..6]);
+        assert_eq!(spans("abc", &[2, 1, 1]), vec![1..3]);
+        assert!(spans("abc", &[]).is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+//! Managing a thread's history: compaction and rollback.
+//!
+//! `thread/compact/start` asks Codex to summarize a thread's history to
+//! free up context. It runs like a turn: the server streams a
+//! `contextCompaction` item and sends `thread/compacted`
+//! ([`Notification::ContextCompacted`]) once the summary has replaced the
+//! history. `thread/rollback` drops the last turns of a thread and returns
+//! the thread as it stands afterwards.
+//!
+//! ```ignore
+//! use codex_codes::ThreadRollbackParams;
+//!
+//! let compacted = client.thread_compact(&thread_id).await?;
+//! println!("compacted in turn {}", compacted.turn_id);
+//!
+//! let thread = client
+//!     .thread_rollback(&ThreadRollbackParams::new(&thread_id, 2))
+//!     .await?
+//!     .thread()?;
+//! ```
+//!
+//! [`Notification::ContextCompacted`]: crate::Notification::ContextCompacted
+
+use crate::protocol::{
+    Thread, ThreadCompactStartParams, ThreadRollbackParams, ThreadRollbackResponse,
+};
+
+impl ThreadCompactStartParams {
+    pub fn new(thread_id: impl Into<String>) -> Self {
+        Self {
+            thread_id: thread_id.into(),
+        }
+    }
+}
+
+impl ThreadRollbackParams {
+    /// Drop the last `num_turns` turns of `thread_id`.
+    pub fn new(thread_id: impl Into<String>, num_turns: u32) -> Self {
+        Self {
+            num_turns: num_turns.into(),
+            thread_id: thread_id.into(),
+        }
+    }
+}
+
+impl ThreadRollbackResponse {
+    /// The thread after the rollback.
+    pub fn thread(&self) -> serde_json::Result<Thread> {
+        serde_json::from_value(self.thread.clone())
+    }
+}
+
+/// Whether `message` reports that `thread_id` has been compacted.
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+pub(crate) fn compacted(
+    message: crate::messages::ServerMessage,
+    thread_id: &str,
+) -> Option<crate::protocol::ContextCompactedNotification> {
+    use crate::messages::{Notification, ServerMessage};
+    match message {
+        ServerMessage::Notification(Notification::ContextCompacted(compacted))
+            if compacted.thread_id == thread_id =>
+        {
+            Some(compacted)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    #[cfg(any(feature = "sync-client", feature = "async-client"))]
+    fn test_compacted_matches_thread() {
+        use crate::messages::{Notification, ServerMessage};
+
+        let message = |thread_id: &str| {
+            ServerMessage::Notification(
+                Notification::from_envelope(
+                    crate::protocol::methods::THREAD_COMPACTED,
+                    Some(json!({"threadId": thread_id, "turnId": "turn_2"})),
+                )
+                .unwrap(),
+            )
+        };
+        assert!(compacted(message("th_other"), "th_1").is_none());
+        assert_eq!(
+            compacted(message("th_1"), "th_1").unwrap().turn_id,
+            "turn_2"
+        );
+    }
+
+    #[test]
+    fn test_rollback_params_wire_shape() {
+        assert_eq!(
+            serde_json::to_value(ThreadRollbackParams::new("th_1", 2)).unwrap(),
+            json!({"threadId": "th_1", "numTurns": 2})
+        );
+    }
+}
@@ -106,6 +106,37 @@ pub enum JsonRpcMessage {
     Notification(JsonRpcNotification),
 }
 
+/// Which way a raw frame travelled; see `set_raw_tap` on the clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameDirection {
+    /// Sent by the client to the app-server.
+    Outbound,
+    /// Received from the app-server.
+    Inbound,
+}
+
+impl FrameDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FrameDirection::Outbound => "outbound",
+            FrameDirection::Inbound => "inbound",
+        }
+    }
+}
+
+impl std::fmt::Display for FrameDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Callback that sees every raw JSON-RPC frame, one line of JSON without
+/// the trailing newline.
+///
+/// Inbound frames are passed before they are parsed, so lines that fail to
+/// deserialize show up too.
+pub type RawTap = Box<dyn FnMut(FrameDirection, &str) + Send>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
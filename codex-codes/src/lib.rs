@@ -41,9 +41,16 @@
 //!   bodies (thread/turn lifecycle, approvals, deltas)
 //! - [`jsonrpc`] — Low-level JSON-RPC message types (request, response, error,
 //!   notification) matching the app-server's wire format
+//! - [`config`] — Edits and lookups for the `config/*` requests
+//! - [`file_search`] — Fuzzy file search matches with highlight spans
+//! - [`history`] — Thread compaction and rollback
+//! - [`mcp`] — MCP servers and the tools they expose
+//! - [`review`] — Following a `review/start` turn to its findings
 //! - [`cli`] — Builder for spawning `codex app-server --listen stdio://`
+//! - `testing` — End-to-end test harness (`integration-tests` feature)
 //! - [`error`] — Error types and result aliases
-//! - [`version`] — Version compatibility checking against the installed CLI
+//! - [`version`] — Version compatibility checking against the installed CLI,
+//!   and probing what a connected app-server supports
 //!
 //! # Protocol Overview
 //!
@@ -106,11 +113,16 @@
 
 pub mod io;
 
+pub mod config;
 pub mod error;
+pub mod file_search;
+pub mod history;
 pub mod jsonrpc;
+pub mod mcp;
 pub mod messages;
 pub mod protocol;
 pub mod protocol_generated;
+pub mod review;
 
 #[cfg(any(feature = "sync-client", feature = "async-client"))]
 pub mod cli;
@@ -127,6 +139,9 @@ pub mod client_sync;
 #[cfg(feature = "async-client")]
 pub mod client_async;
 
+#[cfg(feature = "integration-tests")]
+pub mod testing;
+
 // Exec-level event types (JSONL protocol)
 pub use io::events::{
     ItemCompletedEvent, ItemStartedEvent, ItemUpdatedEvent, ThreadError, ThreadErrorEvent,
@@ -138,20 +153,36 @@ pub use error::{Error, ParseError, Result};
 
 // JSON-RPC types (always available)
 pub use jsonrpc::{
-    JsonRpcError, JsonRpcErrorData, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest,
-    JsonRpcResponse, RequestId,
+    FrameDirection, JsonRpcError, JsonRpcErrorData, JsonRpcMessage, JsonRpcNotification,
+    JsonRpcRequest, JsonRpcResponse, RawTap, RequestId,
 };
 
 // App-server protocol types — generated from the upstream JSON Schema and
 // re-exported through `protocol::*`.
 pub use protocol::*;
 
+// Fuzzy file search matches
+pub use file_search::FileMatch;
+
+// MCP server listings
+pub use mcp::McpTool;
+
+// Code review turns
+pub use review::{
+    ReviewCodeLocation, ReviewEvent, ReviewFinding, ReviewLineRange, ReviewOutput, ReviewResult,
+    ReviewSession,
+};
+
 // Typed message dispatch (notifications + server-to-client requests)
 pub use messages::{Notification, ServerMessage, ServerRequest};
 
 // CLI builder (feature-gated)
 #[cfg(any(feature = "sync-client", feature = "async-client"))]
-pub use cli::AppServerBuilder;
+pub use cli::{AppServerBuilder, CodexCliBuilder};
+
+// Version and capability probing (feature-gated)
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+pub use version::{Capabilities, CodexVersion};
 
 // Sync client
 #[cfg(feature = "sync-client")]
@@ -0,0 +1,150 @@
+//! MCP servers and the tools they provide.
+//!
+//! `mcpServerStatus/list` reports each configured MCP server with its auth
+//! state and the tools, resources and resource templates it exposes; while
+//! servers start, `mcpServer/startupStatus/updated` notifications report
+//! their progress. [`McpTool`] flattens the tool maps of a listing into one
+//! list, which is what a "tools available" panel usually wants.
+//!
+//! ```
+//! use codex_codes::{ListMcpServerStatusResponse, McpTool};
+//!
+//! let listing: ListMcpServerStatusResponse = serde_json::from_str(r#"{"data": [{
+//!     "name": "docs", "authStatus": "unsupported", "resources": [], "resourceTemplates": [],
+//!     "tools": {"search": {"name": "search", "description": "Search the docs", "inputSchema": {}}}
+//! }]}"#).unwrap();
+//! let tools = McpTool::from_servers(&listing.data);
+//! assert_eq!(tools[0].server, "docs");
+//! assert_eq!(tools[0].name, "search");
+//! ```
+
+use crate::protocol::{
+    ListMcpServerStatusParams, McpAuthStatus, McpServerStartupState, McpServerStatus,
+    McpServerStatusDetail, McpServerStatusUpdatedNotification,
+};
+
+/// One tool offered by an MCP server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct McpTool {
+    /// Name of the server providing the tool.
+    pub server: String,
+    pub name: String,
+    /// Human-readable name, when the server gives one.
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// JSON Schema of the tool's arguments.
+    pub input_schema: serde_json::Value,
+}
+
+impl McpTool {
+    /// Every tool of every server in `servers`, ordered by server then tool.
+    pub fn from_servers(servers: &[McpServerStatus]) -> Vec<Self> {
+        servers
+            .iter()
+            .flat_map(McpServerStatus::mcp_tools)
+            .collect()
+    }
+
+    /// `title` if the server gave one, otherwise `name`.
+    pub fn display_name(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.name)
+    }
+}
+
+impl McpServerStatus {
+    /// The server's tools, ordered by name.
+    pub fn mcp_tools(&self) -> impl Iterator<Item = McpTool> + '_ {
+        self.tools.iter().map(|(key, tool)| McpTool {
+            server: self.name.clone(),
+            name: if tool.name.is_empty() {
+                key.clone()
+            } else {
+                tool.name.clone()
+            },
+            title: tool.title.clone(),
+            description: tool.description.clone(),
+            input_schema: tool.input_schema.clone(),
+        })
+    }
+
+    /// Whether the server wants an OAuth login before it can be used.
+    pub fn needs_login(&self) -> bool {
+        self.auth_status == McpAuthStatus::NotLoggedIn
+    }
+}
+
+impl Default for ListMcpServerStatusParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ListMcpServerStatusParams {
+    /// List every server with full detail.
+    pub fn new() -> Self {
+        Self {
+            cursor: None,
+            detail: None,
+            limit: None,
+            thread_id: None,
+        }
+    }
+
+    /// List servers with their tools and auth status only, skipping
+    /// resources.
+    pub fn tools_only() -> Self {
+        Self {
+            detail: Some(McpServerStatusDetail::ToolsAndAuthOnly),
+            ..Self::new()
+        }
+    }
+}
+
+impl McpServerStartupState {
+    /// Whether the server has stopped starting, either way.
+    pub fn is_settled(&self) -> bool {
+        !matches!(self, Self::Starting)
+    }
+}
+
+impl McpServerStatusUpdatedNotification {
+    /// Whether the server started and its tools are available.
+    pub fn is_ready(&self) -> bool {
+        self.status == McpServerStartupState::Ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tools_flatten_in_server_order() {
+        let servers: Vec<McpServerStatus> = serde_json::from_value(json!([
+            {"name": "github", "authStatus": "notLoggedIn", "tools": {
+                "list_issues": {"name": "list_issues", "title": "List issues", "inputSchema": {}},
+                "create_issue": {"name": "create_issue", "inputSchema": {"type": "object"}}
+            }},
+            {"name": "docs", "authStatus": "unsupported", "tools": {
+                "search": {"inputSchema": {}}
+            }}
+        ]))
+        .unwrap();
+        assert!(servers[0].needs_login());
+
+        let tools = McpTool::from_servers(&servers);
+        let names: Vec<_> = tools
+            .iter()
+            .map(|tool| (tool.server.as_str(), tool.display_name()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("github", "create_issue"),
+                ("github", "List issues"),
+                ("docs", "search")
+            ]
+        );
+    }
+}
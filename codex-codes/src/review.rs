@@ -0,0 +1,283 @@
+//! Typed helpers for code review turns.
+//!
+//! `review/start` runs a review of uncommitted changes, a branch, a commit
+//! or custom instructions as a turn on a review thread. The server streams
+//! the turn's items like any other, brackets them with `enteredReviewMode`
+//! / `exitedReviewMode` items, and puts the reviewer's report in the
+//! `exitedReviewMode` item's `review` text.
+//!
+//! [`ReviewSession`] follows one review through the message stream: it
+//! picks out the items belonging to the review turn and assembles a
+//! [`ReviewResult`] when the turn completes. The clients wrap it as
+//! `next_review_event` (streaming) and `review` (start and wait).
+//!
+//! # Example
+//!
+//! ```ignore
+//! use codex_codes::{AsyncClient, ReviewStartParams, ReviewTarget};
+//!
+//! let result = client
+//!     .review(&ReviewStartParams {
+//!         thread_id: thread_id.clone(),
+//!         target: ReviewTarget::UncommittedChanges,
+//!         delivery: None,
+//!     })
+//!     .await?;
+//! for finding in result.findings() {
+//!     println!("{}: {}", finding.title, finding.body);
+//! }
+//! ```
+
+use crate::jsonrpc::RequestId;
+use crate::messages::{Notification, ServerMessage, ServerRequest};
+use crate::protocol::{ReviewStartResponse, ThreadItem, Turn, TurnStatus};
+use serde::{Deserialize, Serialize};
+
+/// The structured report a reviewer produces.
+///
+/// Codex asks the review model for JSON in this shape; [`parse`](Self::parse)
+/// recovers it from the `exitedReviewMode` text when it is present there.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewOutput {
+    #[serde(default)]
+    pub findings: Vec<ReviewFinding>,
+    /// The reviewer's verdict, e.g. `patch is correct`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overall_correctness: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overall_explanation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overall_confidence_score: Option<f64>,
+}
+
+/// One issue raised by a review.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewFinding {
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence_score: Option<f64>,
+    /// 0 (most urgent) to 3.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_location: Option<ReviewCodeLocation>,
+}
+
+/// Where a [`ReviewFinding`] points in the code.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewCodeLocation {
+    pub absolute_file_path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_range: Option<ReviewLineRange>,
+}
+
+/// An inclusive range of line numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewLineRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl ReviewOutput {
+    /// Parse the structured report out of review text.
+    ///
+    /// Accepts the JSON on its own, inside a fenced code block, or embedded
+    /// in surrounding prose. Returns `None` for plain-text reviews.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if let Ok(output) = serde_json::from_str(text) {
+            return Some(output);
+        }
+        let start = text.find('{')?;
+        let end = text.rfind('}')?;
+        serde_json::from_str(text.get(start..=end)?).ok()
+    }
+}
+
+/// The outcome of a review turn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewResult {
+    /// Thread the review ran on; the source thread itself for inline
+    /// delivery.
+    pub review_thread_id: String,
+    /// The completed review turn.
+    pub turn: Turn,
+    /// The reviewer's report from the `exitedReviewMode` item, if the turn
+    /// got that far.
+    pub review: Option<String>,
+    /// The report parsed with [`ReviewOutput::parse`], when it was
+    /// structured.
+    pub output: Option<ReviewOutput>,
+    /// Every item the review turn completed, in order.
+    pub items: Vec<ThreadItem>,
+}
+
+impl ReviewResult {
+    /// The findings of a structured report; empty otherwise.
+    pub fn findings(&self) -> &[ReviewFinding] {
+        self.output.as_ref().map_or(&[], |output| &output.findings)
+    }
+
+    /// Whether the review turn ran to completion.
+    pub fn is_completed(&self) -> bool {
+        self.turn.status == TurnStatus::Completed
+    }
+}
+
+/// What [`ReviewSession::observe`] made of a message.
+#[derive(Debug, Clone)]
+pub enum ReviewEvent {
+    /// The review turn completed an item.
+    Item(ThreadItem),
+    /// The server needs an answer (e.g. an approval) before the review can
+    /// go on.
+    Request {
+        id: RequestId,
+        request: ServerRequest,
+    },
+    /// The review turn ended.
+    Finished(Box<ReviewResult>),
+}
+
+/// Tracks one review started with `review/start`.
+#[derive(Debug, Clone)]
+pub struct ReviewSession {
+    review_thread_id: String,
+    turn_id: String,
+    review: Option<String>,
+    items: Vec<ThreadItem>,
+}
+
+impl ReviewSession {
+    /// Follow the review that `response` started.
+    pub fn new(response: &ReviewStartResponse) -> Self {
+        Self {
+            review_thread_id: response.review_thread_id.clone(),
+            turn_id: response.turn.id.clone(),
+            review: None,
+            items: Vec::new(),
+        }
+    }
+
+    pub fn review_thread_id(&self) -> &str {
+        &self.review_thread_id
+    }
+
+    pub fn turn_id(&self) -> &str {
+        &self.turn_id
+    }
+
+    /// Feed the next message from the server.
+    ///
+    /// Returns `None` for messages that don't concern the review, such as
+    /// deltas and other threads' notifications.
+    pub fn observe(&mut self, message: ServerMessage) -> Option<ReviewEvent> {
+        match message {
+            ServerMessage::Notification(Notification::ItemCompleted(completed))
+                if self.is_review_turn(&completed.thread_id, &completed.turn_id) =>
+            {
+                if let ThreadItem::ExitedReviewMode { review, .. } = &completed.item {
+                    self.review = Some(review.clone());
+                }
+                self.items.push(completed.item.clone());
+                Some(ReviewEvent::Item(completed.item))
+            }
+            ServerMessage::Notification(Notification::TurnCompleted(completed))
+                if self.is_review_turn(&completed.thread_id, &completed.turn.id) =>
+            {
+                let output = self.review.as_deref().and_then(ReviewOutput::parse);
+                Some(ReviewEvent::Finished(Box::new(ReviewResult {
+                    review_thread_id: self.review_thread_id.clone(),
+                    turn: completed.turn,
+                    review: self.review.take(),
+                    output,
+                    items: std::mem::take(&mut self.items),
+                })))
+            }
+            ServerMessage::Request { id, request } => Some(ReviewEvent::Request { id, request }),
+            _ => None,
+        }
+    }
+
+    fn is_review_turn(&self, thread_id: &str, turn_id: &str) -> bool {
+        thread_id == self.review_thread_id && turn_id == self.turn_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn notification(method: &str, params: serde_json::Value) -> ServerMessage {
+        ServerMessage::Notification(Notification::from_envelope(method, Some(params)).unwrap())
+    }
+
+    fn turn(status: &str) -> serde_json::Value {
+        json!({"id": "turn_1", "items": [], "status": status})
+    }
+
+    #[test]
+    fn test_parse_review_output_from_prose() {
+        let text = r#"Here is the review:
+```json
+{"findings":[{"title":"[P1] Unchecked unwrap","body":"Panics on empty input.","confidence_score":0.8,"priority":1,
+  "code_location":{"absolute_file_path":"/repo/src/lib.rs","line_range":{"start":10,"end":12}}}],
+ "overall_correctness":"patch is incorrect","overall_explanation":"See finding.","overall_confidence_score":0.7}
+```"#;
+        let output = ReviewOutput::parse(text).unwrap();
+        assert_eq!(output.findings.len(), 1);
+        let location = output.findings[0].code_location.as_ref().unwrap();
+        assert_eq!(
+            location.line_range,
+            Some(ReviewLineRange { start: 10, end: 12 })
+        );
+        assert_eq!(
+            output.overall_correctness.as_deref(),
+            Some("patch is incorrect")
+        );
+        assert!(ReviewOutput::parse("Looks good to me.").is_none());
+    }
+
+    #[test]
+    fn test_session_collects_review_turn() {
+        let response: ReviewStartResponse = serde_json::from_value(json!({
+            "reviewThreadId": "th_review", "turn": turn("inProgress")
+        }))
+        .unwrap();
+        let mut session = ReviewSession::new(&response);
+
+        let other_thread = notification(
+            "item/completed",
+            json!({"threadId": "th_other", "turnId": "turn_1", "completedAtMs": 0,
+                   "item": {"type": "contextCompaction", "id": "i0"}}),
+        );
+        assert!(session.observe(other_thread).is_none());
+
+        let review = r#"{"findings":[{"title":"Typo","body":"teh"}]}"#;
+        for item in [
+            json!({"type": "enteredReviewMode", "id": "i1", "review": "current changes"}),
+            json!({"type": "exitedReviewMode", "id": "i2", "review": review}),
+        ] {
+            let event = session.observe(notification(
+                "item/completed",
+                json!({"threadId": "th_review", "turnId": "turn_1", "completedAtMs": 0, "item": item}),
+            ));
+            assert!(matches!(event, Some(ReviewEvent::Item(_))));
+        }
+
+        let event = session.observe(notification(
+            "turn/completed",
+            json!({"threadId": "th_review", "turn": turn("completed")}),
+        ));
+        let Some(ReviewEvent::Finished(result)) = event else {
+            panic!("expected the review to finish");
+        };
+        assert!(result.is_completed());
+        assert_eq!(result.items.len(), 2);
+        assert_eq!(result.review.as_deref(), Some(review));
+        assert_eq!(result.findings()[0].title, "Typo");
+    }
+}
@@ -6,8 +6,8 @@
 //! the child process within a fraction of a second.
 //!
 //! This module spawns a tiny background task (tokio for async, std::thread
-//! for sync) whose only job is to read stderr line by line and forward each
-//! line through the `log` crate. Codex's own log level appears as a token
+//! for sync, both from `codes_spawn`) whose only job is to read stderr line
+//! by line and forward each line through the `log` crate. Codex's own log level appears as a token
 //! in the line (e.g. ` INFO `, ` WARN `, ` ERROR `), so we route it to the
 //! matching `log::*` macro after stripping ANSI color codes. Default
 //! filtering through `RUST_LOG` then lets callers tune verbosity.
@@ -73,41 +73,13 @@ pub(crate) fn forward_line(raw: &str) {
 /// Spawn a tokio task that drains `stderr` until EOF.
 #[cfg(feature = "async-client")]
 pub(crate) fn spawn_async(stderr: tokio::process::ChildStderr) -> tokio::task::JoinHandle<()> {
-    use tokio::io::{AsyncBufReadExt, BufReader};
-
-    tokio::spawn(async move {
-        let mut reader = BufReader::new(stderr);
-        let mut line = String::new();
-        loop {
-            line.clear();
-            match reader.read_line(&mut line).await {
-                Ok(0) => break,
-                Ok(_) => forward_line(&line),
-                Err(_) => break,
-            }
-        }
-    })
+    codes_spawn::drain_lines_async(stderr, forward_line)
 }
 
 /// Spawn a std::thread that drains `stderr` until EOF.
 #[cfg(feature = "sync-client")]
 pub(crate) fn spawn_sync(stderr: std::process::ChildStderr) -> std::thread::JoinHandle<()> {
-    use std::io::{BufRead, BufReader};
-
-    std::thread::Builder::new()
-        .name("codex-stderr-drain".to_string())
-        .spawn(move || {
-            let mut reader = BufReader::new(stderr);
-            let mut line = String::new();
-            loop {
-                line.clear();
-                match reader.read_line(&mut line) {
-                    Ok(0) => break,
-                    Ok(_) => forward_line(&line),
-                    Err(_) => break,
-                }
-            }
-        })
+    codes_spawn::drain_lines("codex-stderr-drain", stderr, forward_line)
         .expect("failed to spawn stderr drain thread")
 }
 
@@ -0,0 +1,227 @@
+//! Harness for end-to-end tests against a real `codex app-server`.
+//!
+//! Only built with the `integration-tests` feature. The helpers spawn an
+//! app-server, open a thread, and run a turn to completion while
+//! auto-approving every request, returning the typed messages as a
+//! [`TurnRecord`] to assert on:
+//!
+//! ```ignore
+//! use codex_codes::testing;
+//!
+//! let mut client = testing::start().await?;
+//! let thread_id = testing::new_thread(&mut client).await?;
+//! let turn = testing::run_turn(&mut client, &thread_id, "What is 2 + 2?").await?;
+//! assert!(turn.agent_text().contains('4'));
+//! turn.assert_all_typed();
+//! ```
+//!
+//! Run the suite with
+//! `cargo test -p codex-codes --features integration-tests --test live_client_tests`.
+
+use crate::client_async::AsyncClient;
+use crate::client_sync::SyncClient;
+use crate::error::{Error, Result};
+use crate::messages::{Notification, ServerMessage};
+use crate::protocol::{ThreadItem, ThreadStartParams, Turn, TurnStartParams, UserInput};
+use std::path::Path;
+use std::time::Duration;
+
+/// How long [`run_turn`] waits for each message before calling it a hang.
+pub const MESSAGE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// How many messages a turn may produce before [`run_turn`] gives up on it.
+pub const MAX_MESSAGES: usize = 5000;
+
+/// Start an initialized app-server in the current directory.
+pub async fn start() -> Result<AsyncClient> {
+    AsyncClient::start().await
+}
+
+/// Start an initialized app-server in `dir`.
+///
+/// Codex expects to run inside a git repository; `dir` should be one.
+pub async fn start_in(dir: impl AsRef<Path>) -> Result<AsyncClient> {
+    AsyncClient::start_with(crate::AppServerBuilder::new().working_directory(dir.as_ref())).await
+}
+
+/// Start a thread with default settings and return its id.
+pub async fn new_thread(client: &mut AsyncClient) -> Result<String> {
+    let response = client.thread_start(&default_thread()).await?;
+    Ok(response.thread.id)
+}
+
+/// Sync form of [`new_thread`].
+pub fn new_thread_sync(client: &mut SyncClient) -> Result<String> {
+    let response = client.thread_start(&default_thread())?;
+    Ok(response.thread.id)
+}
+
+/// `turn/start` params for a plain text prompt, everything else default.
+pub fn text_turn(thread_id: &str, prompt: &str) -> TurnStartParams {
+    TurnStartParams {
+        thread_id: thread_id.to_string(),
+        input: vec![UserInput::Text {
+            text: prompt.to_string(),
+            text_elements: None,
+        }],
+        approval_policy: None,
+        approvals_reviewer: None,
+        client_user_message_id: None,
+        cwd: None,
+        effort: None,
+        model: None,
+        output_schema: None,
+        personality: None,
+        sandbox_policy: None,
+        service_tier: None,
+        summary: None,
+    }
+}
+
+/// Send `prompt` on `thread_id` and read until the turn completes,
+/// accepting every approval request on the way.
+///
+/// # Errors
+///
+/// Besides client errors, [`Error::Protocol`] if no message arrives within
+/// [`MESSAGE_TIMEOUT`] or the turn runs past [`MAX_MESSAGES`], and
+/// [`Error::ServerClosed`] if the app-server exits first.
+pub async fn run_turn(
+    client: &mut AsyncClient,
+    thread_id: &str,
+    prompt: &str,
+) -> Result<TurnRecord> {
+    client.turn_start(&text_turn(thread_id, prompt)).await?;
+    let mut record = TurnRecord::default();
+    loop {
+        let message = tokio::time::timeout(MESSAGE_TIMEOUT, client.next_message())
+            .await
+            .map_err(|_| {
+                Error::Protocol(format!(
+                    "no message for {}s after {} messages",
+                    MESSAGE_TIMEOUT.as_secs(),
+                    record.messages.len()
+                ))
+            })??
+            .ok_or(Error::ServerClosed)?;
+        if let ServerMessage::Request { id, .. } = &message {
+            record.approvals += 1;
+            client
+                .respond(id.clone(), &serde_json::json!({"decision": "accept"}))
+                .await?;
+        }
+        if record.push(message)? {
+            return Ok(record);
+        }
+    }
+}
+
+/// Sync form of [`run_turn`]. There is no per-message timeout.
+pub fn run_turn_sync(client: &mut SyncClient, thread_id: &str, prompt: &str) -> Result<TurnRecord> {
+    client.turn_start(&text_turn(thread_id, prompt))?;
+    let mut record = TurnRecord::default();
+    loop {
+        let message = client.next_message()?.ok_or(Error::ServerClosed)?;
+        if let ServerMessage::Request { id, .. } = &message {
+            record.approvals += 1;
+            client.respond(id.clone(), &serde_json::json!({"decision": "accept"}))?;
+        }
+        if record.push(message)? {
+            return Ok(record);
+        }
+    }
+}
+
+/// Everything the server sent during one turn, up to and including
+/// `turn/completed`.
+#[derive(Debug, Clone, Default)]
+pub struct TurnRecord {
+    pub messages: Vec<ServerMessage>,
+    /// Approval requests that were accepted.
+    pub approvals: u32,
+}
+
+impl TurnRecord {
+    /// Add `message`; returns whether it completed the turn.
+    fn push(&mut self, message: ServerMessage) -> Result<bool> {
+        let done = matches!(
+            message,
+            ServerMessage::Notification(Notification::TurnCompleted(_))
+        );
+        self.messages.push(message);
+        if !done && self.messages.len() >= MAX_MESSAGES {
+            return Err(Error::Protocol(format!(
+                "turn produced {} messages without completing",
+                MAX_MESSAGES
+            )));
+        }
+        Ok(done)
+    }
+
+    pub fn notifications(&self) -> impl Iterator<Item = &Notification> {
+        self.messages.iter().filter_map(|message| match message {
+            ServerMessage::Notification(notification) => Some(notification),
+            ServerMessage::Request { .. } => None,
+        })
+    }
+
+    /// The agent's streamed reply, all `item/agentMessage/delta`s joined.
+    pub fn agent_text(&self) -> String {
+        self.notifications()
+            .filter_map(|notification| match notification {
+                Notification::AgentMessageDelta(delta) => Some(delta.delta.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Items completed during the turn, in order.
+    pub fn completed_items(&self) -> Vec<&ThreadItem> {
+        self.notifications()
+            .filter_map(|notification| match notification {
+                Notification::ItemCompleted(completed) => Some(&completed.item),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The turn as reported by `turn/completed`.
+    pub fn turn(&self) -> Option<&Turn> {
+        self.notifications()
+            .find_map(|notification| match notification {
+                Notification::TurnCompleted(completed) => Some(&completed.turn),
+                _ => None,
+            })
+    }
+
+    /// Methods of the messages that had no typed binding.
+    pub fn unknown_methods(&self) -> Vec<&str> {
+        self.messages
+            .iter()
+            .filter_map(|message| match message {
+                ServerMessage::Notification(Notification::Unknown { method, .. }) => {
+                    Some(method.as_str())
+                }
+                ServerMessage::Request { request, .. } if request.is_unknown() => {
+                    Some(request.method())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Panic if any message had no typed binding.
+    #[track_caller]
+    pub fn assert_all_typed(&self) {
+        let unknown = self.unknown_methods();
+        assert!(
+            unknown.is_empty(),
+            "Wire methods with no typed binding: {:?}",
+            unknown
+        );
+    }
+}
+
+fn default_thread() -> ThreadStartParams {
+    serde_json::from_value(serde_json::json!({})).expect("ThreadStartParams has no required fields")
+}
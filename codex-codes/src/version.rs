@@ -1,83 +1,192 @@
 //! Version checking utilities for Codex CLI compatibility.
+//!
+//! Besides the once-per-process warning about untested CLI versions, this
+//! module reads the installed version ([`installed_version`]) and keeps
+//! track of what a connected app-server can do ([`Capabilities`]), so
+//! callers can degrade gracefully on older builds.
 
 use crate::error::Result;
+use crate::protocol::InitializeResponse;
 use log::{debug, warn};
+use std::collections::HashSet;
+use std::fmt;
 use std::process::Command;
 use std::sync::Once;
 
 /// The latest Codex CLI version we've tested against.
 const TESTED_VERSION: &str = "0.143.0";
 
+/// JSON-RPC code for a method the server doesn't implement.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC code the app-server uses when a request doesn't deserialize,
+/// which includes requests for methods it doesn't know.
+const INVALID_REQUEST: i64 = -32600;
+
 /// Ensures version warning is only shown once per session.
 static VERSION_CHECK: Once = Once::new();
 
-/// Check the Codex CLI version and warn if newer than tested.
-///
-/// This will only issue a warning once per program execution.
-pub fn check_codex_version() -> Result<()> {
-    VERSION_CHECK.call_once(|| {
-        if let Err(e) = check_version_impl() {
-            debug!("Failed to check Codex CLI version: {}", e);
-        }
-    });
-    Ok(())
+/// A Codex CLI version, as in `codex-cli 0.143.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CodexVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
 }
 
-fn check_version_impl() -> Result<()> {
-    let output = Command::new("codex")
-        .arg("--version")
-        .output()
-        .map_err(crate::error::Error::Io)?;
-
-    if !output.status.success() {
-        debug!("Failed to check Codex CLI version - command failed");
-        return Ok(());
+impl CodexVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
     }
 
-    let version_str = String::from_utf8_lossy(&output.stdout);
-    let version_line = version_str.lines().next().unwrap_or("");
+    /// Parse a version out of `codex --version` output or a user agent.
+    ///
+    /// Accepts `0.143.0`, `codex-cli 0.143.0`, `codex_cli_rs/0.143.0 (Linux)`
+    /// and pre-release forms such as `0.144.0-alpha.2`, whose suffix is
+    /// ignored. A missing patch number reads as 0.
+    pub fn parse(text: &str) -> Option<Self> {
+        text.split(|c: char| c.is_whitespace() || c == '/')
+            .find_map(Self::parse_number)
+    }
 
-    // Format: "codex-cli X.Y.Z"
-    if let Some(version) = version_line.split_whitespace().last() {
-        if is_version_newer(version, TESTED_VERSION) {
-            warn!(
-                "Codex CLI version {} is newer than tested version {}. \
-                 Please report compatibility at: https://github.com/meawoppl/rust-code-agent-sdks/issues",
-                version, TESTED_VERSION
-            );
-        } else {
-            debug!(
-                "Codex CLI version {} is compatible (tested: {})",
-                version, TESTED_VERSION
-            );
+    fn parse_number(word: &str) -> Option<Self> {
+        let core = word.split(['-', '+']).next()?;
+        let mut parts = core.split('.').map(|part| part.parse::<u32>().ok());
+        let major = parts.next()??;
+        let minor = parts.next()??;
+        let patch = parts.next().unwrap_or(Some(0))?;
+        if parts.next().is_some() {
+            return None;
         }
-    } else {
-        warn!(
-            "Could not parse Codex CLI version from output: '{}'. \
-             Please report compatibility at: https://github.com/meawoppl/rust-code-agent-sdks/issues",
-            version_line
-        );
+        Some(Self::new(major, minor, patch))
     }
 
-    Ok(())
+    /// The newest version this crate has been tested against.
+    pub fn tested() -> Self {
+        Self::parse(TESTED_VERSION).expect("TESTED_VERSION is a valid version")
+    }
 }
 
-/// Compare two version strings (e.g., "0.104.0" vs "0.103.0").
-fn is_version_newer(version: &str, tested: &str) -> bool {
-    let v_parts: Vec<u32> = version.split('.').filter_map(|s| s.parse().ok()).collect();
-    let t_parts: Vec<u32> = tested.split('.').filter_map(|s| s.parse().ok()).collect();
+impl fmt::Display for CodexVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
 
-    use std::cmp::Ordering;
+/// What a connected app-server is known to support.
+///
+/// Built from the `initialize` response and refined as the server rejects
+/// methods it doesn't know: [`supports`](Self::supports) is optimistic and
+/// only turns `false` for a method once a request for it came back as
+/// unknown. The clients keep one up to date; see `AsyncClient::capabilities`.
+#[derive(Debug, Clone, Default)]
+pub struct Capabilities {
+    version: Option<CodexVersion>,
+    user_agent: Option<String>,
+    unsupported: HashSet<String>,
+}
+
+impl Capabilities {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    for i in 0..v_parts.len().min(t_parts.len()) {
-        match v_parts[i].cmp(&t_parts[i]) {
-            Ordering::Greater => return true,
-            Ordering::Less => return false,
-            Ordering::Equal => continue,
+    /// Capabilities of the server that sent `response`.
+    pub fn from_initialize(response: &InitializeResponse) -> Self {
+        Self {
+            version: CodexVersion::parse(&response.user_agent),
+            user_agent: Some(response.user_agent.clone()),
+            unsupported: HashSet::new(),
         }
     }
 
-    v_parts.len() > t_parts.len()
+    /// The server's version, when its user agent gave one.
+    pub fn version(&self) -> Option<CodexVersion> {
+        self.version
+    }
+
+    /// The user agent reported by `initialize`.
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    /// Whether the server is at least `version`. `false` if the version is
+    /// unknown.
+    pub fn at_least(&self, version: CodexVersion) -> bool {
+        self.version.is_some_and(|v| v >= version)
+    }
+
+    /// Whether requests for `method` can be expected to work.
+    pub fn supports(&self, method: &str) -> bool {
+        !self.unsupported.contains(method)
+    }
+
+    /// Record that the server doesn't know `method`.
+    pub fn mark_unsupported(&mut self, method: &str) {
+        self.unsupported.insert(method.to_string());
+    }
+
+    /// Record the outcome of a request for `method` that failed with
+    /// `code` / `message`. Returns whether it showed the method to be
+    /// unsupported.
+    pub fn observe_error(&mut self, method: &str, code: i64, message: &str) -> bool {
+        let unknown = code == METHOD_NOT_FOUND
+            || (code == INVALID_REQUEST && message.contains("unknown variant"));
+        if unknown {
+            debug!("[CLIENT] Server does not support {}", method);
+            self.mark_unsupported(method);
+        }
+        unknown
+    }
+}
+
+/// Run `codex --version` and parse its output.
+pub fn installed_version() -> Result<CodexVersion> {
+    let output = Command::new("codex")
+        .arg("--version")
+        .output()
+        .map_err(crate::error::Error::Io)?;
+    parse_version_output(&output)
+}
+
+/// Async form of [`installed_version`] for tokio-based clients.
+#[cfg(feature = "async-client")]
+pub async fn installed_version_async() -> Result<CodexVersion> {
+    let output = tokio::process::Command::new("codex")
+        .arg("--version")
+        .output()
+        .await
+        .map_err(crate::error::Error::Io)?;
+    parse_version_output(&output)
+}
+
+fn parse_version_output(output: &std::process::Output) -> Result<CodexVersion> {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next().unwrap_or("");
+    if !output.status.success() {
+        return Err(crate::error::Error::ProcessFailed(
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ));
+    }
+    CodexVersion::parse(line).ok_or_else(|| {
+        crate::error::Error::Protocol(format!(
+            "Could not parse Codex CLI version from output: '{}'",
+            line
+        ))
+    })
+}
+
+/// Check the Codex CLI version and warn if newer than tested.
+///
+/// This will only issue a warning once per program execution.
+pub fn check_codex_version() -> Result<()> {
+    VERSION_CHECK.call_once(|| report_version(installed_version()));
+    Ok(())
 }
 
 /// Async version check for tokio-based clients.
@@ -89,62 +198,51 @@ pub async fn check_codex_version_async() -> Result<()> {
 
     ASYNC_VERSION_CHECK
         .get_or_init(|| async {
-            if let Err(e) = check_version_impl_async().await {
-                debug!("Failed to check Codex CLI version: {}", e);
-            }
+            report_version(installed_version_async().await);
         })
         .await;
 
     Ok(())
 }
 
-#[cfg(feature = "async-client")]
-async fn check_version_impl_async() -> Result<()> {
-    use tokio::process::Command;
-
-    let output = Command::new("codex")
-        .arg("--version")
-        .output()
-        .await
-        .map_err(crate::error::Error::Io)?;
-
-    if !output.status.success() {
-        debug!("Failed to check Codex CLI version - command failed");
-        return Ok(());
-    }
-
-    let version_str = String::from_utf8_lossy(&output.stdout);
-    let version_line = version_str.lines().next().unwrap_or("");
-
-    // Format: "codex-cli X.Y.Z"
-    if let Some(version) = version_line.split_whitespace().last() {
-        if is_version_newer(version, TESTED_VERSION) {
+fn report_version(version: Result<CodexVersion>) {
+    let version = match version {
+        Ok(version) => version,
+        Err(crate::error::Error::Protocol(e)) => {
             warn!(
-                "Codex CLI version {} is newer than tested version {}. \
-                 Please report compatibility at: https://github.com/meawoppl/rust-code-agent-sdks/issues",
-                version, TESTED_VERSION
-            );
-        } else {
-            debug!(
-                "Codex CLI version {} is compatible (tested: {})",
-                version, TESTED_VERSION
+                "{}. Please report compatibility at: https://github.com/meawoppl/rust-code-agent-sdks/issues",
+                e
             );
+            return;
         }
-    } else {
+        Err(e) => {
+            debug!("Failed to check Codex CLI version: {}", e);
+            return;
+        }
+    };
+    let tested = CodexVersion::tested();
+    if version > tested {
         warn!(
-            "Could not parse Codex CLI version from output: '{}'. \
+            "Codex CLI version {} is newer than tested version {}. \
              Please report compatibility at: https://github.com/meawoppl/rust-code-agent-sdks/issues",
-            version_line
+            version, tested
+        );
+    } else {
+        debug!(
+            "Codex CLI version {} is compatible (tested: {})",
+            version, tested
         );
     }
-
-    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn is_version_newer(version: &str, tested: &str) -> bool {
+        CodexVersion::parse(version).unwrap() > CodexVersion::parse(tested).unwrap()
+    }
+
     #[test]
     fn test_version_comparison() {
         assert!(is_version_newer("0.105.0", "0.104.0"));
@@ -155,4 +253,44 @@ mod tests {
         assert!(!is_version_newer("0.0.1", "0.104.0"));
         assert!(is_version_newer("0.104.1", "0.104.0"));
     }
+
+    #[test]
+    fn test_parse_version_forms() {
+        let expected = Some(CodexVersion::new(0, 143, 0));
+        assert_eq!(CodexVersion::parse("codex-cli 0.143.0"), expected);
+        assert_eq!(
+            CodexVersion::parse("codex_cli_rs/0.143.0 (Mac OS 15.1.0; arm64) vscode/1.0"),
+            expected
+        );
+        assert_eq!(CodexVersion::parse("0.143"), expected);
+        assert_eq!(
+            CodexVersion::parse("codex-cli 0.144.0-alpha.2"),
+            Some(CodexVersion::new(0, 144, 0))
+        );
+        assert_eq!(CodexVersion::parse("codex-cli"), None);
+        assert_eq!(CodexVersion::tested().to_string(), TESTED_VERSION);
+    }
+
+    #[test]
+    fn test_capabilities_learn_unknown_methods() {
+        let response: InitializeResponse = serde_json::from_value(serde_json::json!({
+            "userAgent": "codex_cli_rs/0.120.3 (Linux 6.8; x86_64)",
+            "platformFamily": "unix", "platformOs": "linux", "codexHome": "/home/me/.codex"
+        }))
+        .unwrap();
+        let mut caps = Capabilities::from_initialize(&response);
+        assert_eq!(caps.version(), Some(CodexVersion::new(0, 120, 3)));
+        assert!(caps.at_least(CodexVersion::new(0, 120, 0)));
+        assert!(!caps.at_least(CodexVersion::tested()));
+
+        assert!(caps.supports("thread/rollback"));
+        assert!(!caps.observe_error("thread/rollback", -32603, "internal error"));
+        assert!(caps.supports("thread/rollback"));
+        assert!(caps.observe_error(
+            "thread/rollback",
+            -32600,
+            "Invalid request: unknown variant `thread/rollback`, expected one of ..."
+        ));
+        assert!(!caps.supports("thread/rollback"));
+    }
 }
@@ -7,6 +7,7 @@
 
 #![cfg(feature = "integration-tests")]
 
+use codex_codes::testing;
 use codex_codes::{
     AppServerBuilder, AsyncClient, ClientInfo, InitializeCapabilities, InitializeParams,
     Notification, ServerMessage, ServerRequest, SyncClient, ThreadStartParams, TurnStartParams,
@@ -44,71 +45,25 @@ async fn test_async_client_start_and_thread_start() {
 
 #[tokio::test]
 async fn test_async_client_basic_turn() {
-    let mut client = AsyncClient::start()
-        .await
-        .expect("Failed to start app-server");
-
-    let thread = client
-        .thread_start(&serde_json::from_value::<ThreadStartParams>(serde_json::json!({})).unwrap())
+    let mut client = testing::start().await.expect("Failed to start app-server");
+    let thread_id = testing::new_thread(&mut client)
         .await
         .expect("Failed to start thread");
 
-    client
-        .turn_start(&TurnStartParams {
-            thread_id: thread.thread.id.clone(),
-            input: vec![UserInput::Text {
-                text: "What is 2 + 2? Reply with just the number.".to_string(),
-                text_elements: None,
-            }],
-            approval_policy: None,
-            approvals_reviewer: None,
-            client_user_message_id: None,
-            cwd: None,
-            effort: None,
-            model: None,
-            output_schema: None,
-            personality: None,
-            sandbox_policy: None,
-            service_tier: None,
-            summary: None,
-        })
-        .await
-        .expect("Failed to start turn");
-
-    let mut found_answer = false;
-    let mut turn_completed = false;
-    let mut message_count = 0;
+    let turn = testing::run_turn(
+        &mut client,
+        &thread_id,
+        "What is 2 + 2? Reply with just the number.",
+    )
+    .await
+    .expect("Turn should have completed");
 
-    while let Some(msg) = client.next_message().await.expect("Failed to read message") {
-        message_count += 1;
-
-        match msg {
-            ServerMessage::Notification(Notification::AgentMessageDelta(d)) => {
-                if d.delta.contains('4') {
-                    found_answer = true;
-                }
-            }
-            ServerMessage::Notification(Notification::TurnCompleted(_)) => {
-                turn_completed = true;
-                break;
-            }
-            ServerMessage::Notification(_) => {}
-            ServerMessage::Request { id, .. } => {
-                // Auto-accept any approval requests
-                client
-                    .respond(id, &serde_json::json!({"decision": "accept"}))
-                    .await
-                    .expect("Failed to respond");
-            }
-        }
-
-        if message_count > 100 {
-            break;
-        }
-    }
-
-    assert!(turn_completed, "Turn should have completed");
-    assert!(found_answer, "Response should contain '4'");
+    assert!(turn.turn().is_some(), "Turn should have completed");
+    assert!(
+        turn.agent_text().contains('4'),
+        "Response should contain '4'"
+    );
+    turn.assert_all_typed();
 
     client.shutdown().await.expect("Failed to shutdown");
 }
@@ -130,6 +85,7 @@ async fn test_async_client_custom_initialize() {
             },
             capabilities: Some(InitializeCapabilities {
                 experimental_api: Some(false),
+                mcp_server_openai_form_elicitation: None,
                 opt_out_notification_methods: None,
                 request_attestation: None,
             }),
@@ -171,166 +127,52 @@ fn test_sync_client_start_and_thread_start() {
 #[test]
 fn test_sync_client_basic_turn() {
     let mut client = SyncClient::start().expect("Failed to start app-server");
+    let thread_id = testing::new_thread_sync(&mut client).expect("Failed to start thread");
 
-    let thread = client
-        .thread_start(&serde_json::from_value::<ThreadStartParams>(serde_json::json!({})).unwrap())
-        .expect("Failed to start thread");
-
-    client
-        .turn_start(&TurnStartParams {
-            thread_id: thread.thread.id.clone(),
-            input: vec![UserInput::Text {
-                text: "What is 2 + 2? Reply with just the number.".to_string(),
-                text_elements: None,
-            }],
-            approval_policy: None,
-            approvals_reviewer: None,
-            client_user_message_id: None,
-            cwd: None,
-            effort: None,
-            model: None,
-            output_schema: None,
-            personality: None,
-            sandbox_policy: None,
-            service_tier: None,
-            summary: None,
-        })
-        .expect("Failed to start turn");
-
-    let mut found_answer = false;
-    let mut turn_completed = false;
-    let mut message_count = 0;
-
-    for result in client.events() {
-        let msg = result.expect("Failed to read message");
-        message_count += 1;
-
-        match msg {
-            ServerMessage::Notification(Notification::AgentMessageDelta(d)) => {
-                if d.delta.contains('4') {
-                    found_answer = true;
-                }
-            }
-            ServerMessage::Notification(Notification::TurnCompleted(_)) => {
-                turn_completed = true;
-                break;
-            }
-            ServerMessage::Notification(_) | ServerMessage::Request { .. } => {}
-        }
-
-        if message_count > 100 {
-            break;
-        }
-    }
+    let turn = testing::run_turn_sync(
+        &mut client,
+        &thread_id,
+        "What is 2 + 2? Reply with just the number.",
+    )
+    .expect("Turn should have completed");
 
-    assert!(turn_completed, "Turn should have completed");
-    assert!(found_answer, "Response should contain '4'");
+    assert!(
+        turn.agent_text().contains('4'),
+        "Response should contain '4'"
+    );
 }
 
 // ── Async client: multi-turn conversation ───────────────────────────
 
 #[tokio::test]
 async fn test_async_client_multi_turn() {
-    let mut client = AsyncClient::start()
-        .await
-        .expect("Failed to start app-server");
-
-    let thread = client
-        .thread_start(&serde_json::from_value::<ThreadStartParams>(serde_json::json!({})).unwrap())
+    let mut client = testing::start().await.expect("Failed to start app-server");
+    let thread_id = testing::new_thread(&mut client)
         .await
         .expect("Failed to start thread");
 
     // First turn: establish context
-    client
-        .turn_start(&TurnStartParams {
-            thread_id: thread.thread.id.clone(),
-            input: vec![UserInput::Text {
-                text: "Remember the number 42. Just say OK.".to_string(),
-                text_elements: None,
-            }],
-            approval_policy: None,
-            approvals_reviewer: None,
-            client_user_message_id: None,
-            cwd: None,
-            effort: None,
-            model: None,
-            output_schema: None,
-            personality: None,
-            sandbox_policy: None,
-            service_tier: None,
-            summary: None,
-        })
-        .await
-        .expect("Failed to start first turn");
-
-    // Drain until turn completes
-    let mut message_count = 0;
-    while let Some(msg) = client.next_message().await.expect("read") {
-        message_count += 1;
-        match msg {
-            ServerMessage::Notification(Notification::TurnCompleted(_)) => break,
-            ServerMessage::Notification(_) => {}
-            ServerMessage::Request { id, .. } => {
-                client
-                    .respond(id, &serde_json::json!({"decision": "accept"}))
-                    .await
-                    .ok();
-            }
-        }
-        if message_count > 100 {
-            break;
-        }
-    }
+    testing::run_turn(
+        &mut client,
+        &thread_id,
+        "Remember the number 42. Just say OK.",
+    )
+    .await
+    .expect("First turn should complete");
 
     // Second turn: check context is maintained
-    client
-        .turn_start(&TurnStartParams {
-            thread_id: thread.thread.id.clone(),
-            input: vec![UserInput::Text {
-                text: "What number did I ask you to remember? Reply with just the number."
-                    .to_string(),
-                text_elements: None,
-            }],
-            approval_policy: None,
-            approvals_reviewer: None,
-            client_user_message_id: None,
-            cwd: None,
-            effort: None,
-            model: None,
-            output_schema: None,
-            personality: None,
-            sandbox_policy: None,
-            service_tier: None,
-            summary: None,
-        })
-        .await
-        .expect("Failed to start second turn");
+    let turn = testing::run_turn(
+        &mut client,
+        &thread_id,
+        "What number did I ask you to remember? Reply with just the number.",
+    )
+    .await
+    .expect("Second turn should complete");
 
-    let mut found_42 = false;
-    let mut message_count = 0;
-    while let Some(msg) = client.next_message().await.expect("read") {
-        message_count += 1;
-        match msg {
-            ServerMessage::Notification(Notification::AgentMessageDelta(d)) => {
-                if d.delta.contains("42") {
-                    found_42 = true;
-                }
-            }
-            ServerMessage::Notification(Notification::TurnCompleted(_)) => break,
-            ServerMessage::Notification(_) => {}
-            ServerMessage::Request { id, .. } => {
-                client
-                    .respond(id, &serde_json::json!({"decision": "accept"}))
-                    .await
-                    .ok();
-            }
-        }
-        if message_count > 100 {
-            break;
-        }
-    }
-
-    assert!(found_42, "Agent should remember 42 from the first turn");
+    assert!(
+        turn.agent_text().contains("42"),
+        "Agent should remember 42 from the first turn"
+    );
 
     client.shutdown().await.expect("Failed to shutdown");
 }
@@ -497,6 +339,9 @@ async fn test_typed_message_audit_strict() {
                     Notification::ExternalAgentConfigImportCompleted(_) => {
                         "ExternalAgentConfigImportCompleted"
                     }
+                    Notification::ExternalAgentConfigImportProgress(_) => {
+                        "ExternalAgentConfigImportProgress"
+                    }
                     Notification::FuzzyFileSearchSessionCompleted(_) => {
                         "FuzzyFileSearchSessionCompleted"
                     }
@@ -514,6 +359,7 @@ async fn test_typed_message_audit_strict() {
                     Notification::TerminalInteraction(_) => "TerminalInteraction",
                     Notification::McpToolCallProgress(_) => "McpToolCallProgress",
                     Notification::ModelRerouted(_) => "ModelRerouted",
+                    Notification::ModelSafetyBufferingUpdated(_) => "ModelSafetyBufferingUpdated",
                     Notification::ModelVerification(_) => "ModelVerification",
                     Notification::ProcessExited(_) => "ProcessExited",
                     Notification::ProcessOutputDelta(_) => "ProcessOutputDelta",
@@ -0,0 +1,28 @@
+//! Example of using the asynchronous Gemini CLI client.
+//!
+//! Sends a single prompt and prints assistant text as it streams.
+
+use gemini_codes::{AsyncClient, GeminiEvent};
+use std::error::Error;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    env_logger::init();
+
+    let mut client = AsyncClient::new().await?;
+    let mut stream = client.query_stream("What is the capital of France?")?;
+
+    while let Some(event) = stream.next().await {
+        match event? {
+            GeminiEvent::Init(init) => println!("[session {} on {}]", init.session_id, init.model),
+            GeminiEvent::Result(result) => println!("\n[{}]", result.status),
+            other => {
+                if let Some(text) = other.assistant_text() {
+                    print!("{}", text);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Example of using the synchronous Gemini CLI client.
+//!
+//! Runs two turns in the same session and prints the assistant text and
+//! tool activity as it streams.
+
+use gemini_codes::{GeminiEvent, SyncClient};
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    env_logger::init();
+
+    let mut client = SyncClient::new()?;
+
+    for prompt in ["What is the capital of France?", "And of Germany?"] {
+        println!("\n> {}\n", prompt);
+        for event in client.query_stream(prompt)? {
+            match event? {
+                GeminiEvent::Message(msg) if msg.role == gemini_codes::MessageRole::Assistant => {
+                    print!("{}", msg.content);
+                }
+                GeminiEvent::ToolUse(tool) => println!("\n[tool: {}]", tool.tool_name),
+                GeminiEvent::Error(err) => eprintln!("[{}] {}", err.severity, err.message),
+                GeminiEvent::Result(result) => {
+                    let tokens = result.stats.map(|s| s.total_tokens).unwrap_or(0);
+                    println!("\n[{} - {} tokens]", result.status, tokens);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    println!("\nSession: {}", client.session_id().unwrap_or("<none>"));
+    Ok(())
+}
@@ -0,0 +1,339 @@
+//! Builder for launching the Gemini CLI in headless mode.
+//!
+//! The Gemini CLI runs one turn per process: [`GeminiCliBuilder`] produces
+//! `gemini --output-format stream-json --prompt <text> [flags]...`, and the
+//! process exits after emitting its `result` event. Multi-turn sessions are
+//! continued by passing the session id from the `init` event to `--resume`,
+//! which the clients do automatically.
+
+use log::debug;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+/// When the CLI may run tools without asking.
+///
+/// Headless runs cannot answer interactive prompts, so tools that would need
+/// approval under the current mode are refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalMode {
+    /// Only read-only tools run without approval.
+    Default,
+    /// File edits are approved automatically.
+    AutoEdit,
+    /// Every tool call is approved automatically.
+    Yolo,
+    /// Read-only planning; no edits or commands.
+    Plan,
+}
+
+impl ApprovalMode {
+    /// Get the CLI string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalMode::Default => "default",
+            ApprovalMode::AutoEdit => "auto_edit",
+            ApprovalMode::Yolo => "yolo",
+            ApprovalMode::Plan => "plan",
+        }
+    }
+}
+
+/// Builder for launching one headless Gemini CLI turn.
+///
+/// Per-turn values (the prompt and the session to resume) are supplied at
+/// spawn time; everything else is fixed for the session.
+#[derive(Debug, Clone)]
+pub struct GeminiCliBuilder {
+    command: PathBuf,
+    working_directory: Option<PathBuf>,
+    model: Option<String>,
+    approval_mode: Option<ApprovalMode>,
+    sandbox: bool,
+    include_directories: Vec<PathBuf>,
+    allowed_tools: Vec<String>,
+    allowed_mcp_server_names: Vec<String>,
+    debug: bool,
+    env: Vec<(String, String)>,
+    extra_args: Vec<String>,
+}
+
+impl Default for GeminiCliBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GeminiCliBuilder {
+    /// Create a new builder with default settings.
+    pub fn new() -> Self {
+        Self {
+            command: PathBuf::from("gemini"),
+            working_directory: None,
+            model: None,
+            approval_mode: None,
+            sandbox: false,
+            include_directories: Vec::new(),
+            allowed_tools: Vec::new(),
+            allowed_mcp_server_names: Vec::new(),
+            debug: false,
+            env: Vec::new(),
+            extra_args: Vec::new(),
+        }
+    }
+
+    /// Set custom path to the gemini binary.
+    pub fn command<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.command = path.into();
+        self
+    }
+
+    /// Set the working directory for the CLI process.
+    pub fn working_directory<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.working_directory = Some(dir.into());
+        self
+    }
+
+    /// Set the model (`--model`).
+    pub fn model<S: Into<String>>(mut self, model: S) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Set the approval mode (`--approval-mode`).
+    pub fn approval_mode(mut self, mode: ApprovalMode) -> Self {
+        self.approval_mode = Some(mode);
+        self
+    }
+
+    /// Run tools inside the CLI's sandbox (`--sandbox`).
+    pub fn sandbox(mut self, enabled: bool) -> Self {
+        self.sandbox = enabled;
+        self
+    }
+
+    /// Add directories to the workspace (`--include-directories`).
+    pub fn include_directories<I, P>(mut self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.include_directories
+            .extend(dirs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Tools that may run without approval (`--allowed-tools`).
+    pub fn allowed_tools<I, S>(mut self, tools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_tools.extend(tools.into_iter().map(Into::into));
+        self
+    }
+
+    /// Restrict MCP servers to these names (`--allowed-mcp-server-names`).
+    pub fn allowed_mcp_server_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed_mcp_server_names
+            .extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// Enable debug output on stderr (`--debug`).
+    pub fn debug(mut self, enabled: bool) -> Self {
+        self.debug = enabled;
+        self
+    }
+
+    /// Set an environment variable for the CLI process, e.g. `GEMINI_API_KEY`.
+    pub fn env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Append raw arguments for flags the SDK doesn't model yet.
+    pub fn extra_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extra_args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Resolve the command path, using `which` for non-absolute paths.
+    ///
+    /// A bare `gemini` that isn't on `PATH` is also looked for where the
+    /// installers put it, since GUI apps and services often run with a
+    /// minimal `PATH`.
+    fn resolve_command(&self) -> crate::error::Result<PathBuf> {
+        let fallbacks = if self.command == Path::new("gemini") {
+            codes_spawn::install_locations("gemini")
+        } else {
+            Vec::new()
+        };
+        codes_spawn::resolve(&self.command, &fallbacks).ok_or_else(|| {
+            crate::error::Error::BinaryNotFound {
+                name: self.command.display().to_string(),
+            }
+        })
+    }
+
+    /// Build the command arguments for one turn.
+    ///
+    /// Layout: `--output-format stream-json [--resume id] [flags]... [extra_args]... --prompt <text>`
+    fn build_args(&self, prompt: &str, resume: Option<&str>) -> Vec<String> {
+        let mut args = vec!["--output-format".to_string(), "stream-json".to_string()];
+        if let Some(session) = resume {
+            args.push("--resume".to_string());
+            args.push(session.to_string());
+        }
+        if let Some(ref model) = self.model {
+            args.push("--model".to_string());
+            args.push(model.clone());
+        }
+        if let Some(mode) = self.approval_mode {
+            args.push("--approval-mode".to_string());
+            args.push(mode.as_str().to_string());
+        }
+        if self.sandbox {
+            args.push("--sandbox".to_string());
+        }
+        if !self.include_directories.is_empty() {
+            args.push("--include-directories".to_string());
+            args.push(
+                self.include_directories
+                    .iter()
+                    .map(|d| d.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+        if !self.allowed_tools.is_empty() {
+            args.push("--allowed-tools".to_string());
+            args.extend(self.allowed_tools.iter().cloned());
+        }
+        if !self.allowed_mcp_server_names.is_empty() {
+            args.push("--allowed-mcp-server-names".to_string());
+            args.extend(self.allowed_mcp_server_names.iter().cloned());
+        }
+        if self.debug {
+            args.push("--debug".to_string());
+        }
+        args.extend(self.extra_args.iter().cloned());
+        // `--prompt` goes last so a variadic flag above can't swallow it.
+        args.push("--prompt".to_string());
+        args.push(prompt.to_string());
+        args
+    }
+
+    fn std_command(
+        &self,
+        prompt: &str,
+        resume: Option<&str>,
+    ) -> crate::error::Result<std::process::Command> {
+        let resolved = self.resolve_command()?;
+        let args = self.build_args(prompt, resume);
+
+        debug!(
+            "[CLI] Spawning gemini: {} {}",
+            resolved.display(),
+            args.join(" ")
+        );
+
+        let mut cmd = std::process::Command::new(&resolved);
+        cmd.args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(ref dir) = self.working_directory {
+            cmd.current_dir(dir);
+        }
+        codes_spawn::apply_env(&mut cmd, self.env.iter().map(|(k, v)| (k, Some(v))));
+        Ok(cmd)
+    }
+
+    /// Spawn one turn asynchronously, optionally resuming `resume`.
+    #[cfg(feature = "async-client")]
+    pub fn spawn(
+        &self,
+        prompt: &str,
+        resume: Option<&str>,
+    ) -> crate::error::Result<tokio::process::Child> {
+        tokio::process::Command::from(self.std_command(prompt, resume)?)
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(crate::error::Error::Io)
+    }
+
+    /// Spawn one turn synchronously, optionally resuming `resume`.
+    pub fn spawn_sync(
+        &self,
+        prompt: &str,
+        resume: Option<&str>,
+    ) -> crate::error::Result<std::process::Child> {
+        self.std_command(prompt, resume)?
+            .spawn()
+            .map_err(crate::error::Error::Io)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_args() {
+        let args = GeminiCliBuilder::new().build_args("hi", None);
+        assert_eq!(
+            args,
+            vec!["--output-format", "stream-json", "--prompt", "hi"]
+        );
+    }
+
+    #[test]
+    fn test_resume_and_options() {
+        let args = GeminiCliBuilder::new()
+            .model("gemini-2.5-pro")
+            .approval_mode(ApprovalMode::AutoEdit)
+            .include_directories(["/a", "/b"])
+            .allowed_tools(["read_file", "glob"])
+            .build_args("next", Some("abc123"));
+        assert_eq!(
+            args,
+            vec![
+                "--output-format",
+                "stream-json",
+                "--resume",
+                "abc123",
+                "--model",
+                "gemini-2.5-pro",
+                "--approval-mode",
+                "auto_edit",
+                "--include-directories",
+                "/a,/b",
+                "--allowed-tools",
+                "read_file",
+                "glob",
+                "--prompt",
+                "next",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extra_args_before_prompt() {
+        let args = GeminiCliBuilder::new()
+            .sandbox(true)
+            .extra_args(["--checkpointing"])
+            .build_args("go", None);
+        assert_eq!(
+            &args[2..],
+            ["--sandbox", "--checkpointing", "--prompt", "go"]
+        );
+    }
+}
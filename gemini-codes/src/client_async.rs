@@ -0,0 +1,201 @@
+//! Asynchronous multi-turn client for the Gemini CLI.
+//!
+//! Each turn spawns `gemini --output-format stream-json --prompt <text>` and
+//! reads events until the `result` event. The session id from the first
+//! turn's `init` event is passed to `--resume` on every later turn, so the
+//! conversation continues across processes.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use gemini_codes::AsyncClient;
+//!
+//! let mut client = AsyncClient::new().await?;
+//! let mut stream = client.query_stream("What is 2 + 2?")?;
+//! while let Some(event) = stream.next().await {
+//!     if let Some(text) = event?.assistant_text() {
+//!         print!("{}", text);
+//!     }
+//! }
+//! ```
+
+use crate::cli::GeminiCliBuilder;
+use crate::error::{Error, Result};
+use crate::io::events::GeminiEvent;
+use crate::stream::{missing_result, next_frame, parse_line, StderrTail};
+use codes_codec::FrameReader;
+use tokio::io::BufReader;
+use tokio::process::{Child, ChildStdout};
+use tokio::task::JoinHandle;
+
+/// Asynchronous multi-turn client for the Gemini CLI.
+#[derive(Debug, Clone)]
+pub struct AsyncClient {
+    builder: GeminiCliBuilder,
+    session_id: Option<String>,
+}
+
+impl AsyncClient {
+    /// Create a client with default settings.
+    pub async fn new() -> Result<Self> {
+        Self::with_builder(GeminiCliBuilder::new()).await
+    }
+
+    /// Create a client with a custom [`GeminiCliBuilder`].
+    ///
+    /// No process is spawned until the first query.
+    pub async fn with_builder(builder: GeminiCliBuilder) -> Result<Self> {
+        crate::version::check_gemini_version_async().await?;
+        Ok(Self {
+            builder,
+            session_id: None,
+        })
+    }
+
+    /// Continue an existing session on the next query.
+    pub async fn resume(builder: GeminiCliBuilder, session_id: impl Into<String>) -> Result<Self> {
+        let mut client = Self::with_builder(builder).await?;
+        client.session_id = Some(session_id.into());
+        Ok(client)
+    }
+
+    /// Session id reported by the last `init` event, if any turn has run.
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// Start a fresh session on the next query.
+    pub fn reset_session(&mut self) {
+        self.session_id = None;
+    }
+
+    /// Run one turn and stream its events, ending after `result`.
+    pub fn query_stream(&mut self, prompt: &str) -> Result<EventStream<'_>> {
+        let mut child = self.builder.spawn(prompt, self.session_id.as_deref())?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| Error::Protocol("Failed to get stdout".to_string()))?;
+        let stderr_tail = StderrTail::default();
+        let stderr = child.stderr.take().map(|pipe| {
+            let tail = stderr_tail.clone();
+            codes_spawn::drain_lines_async(pipe, move |line| tail.push(line))
+        });
+        Ok(EventStream {
+            session_id: &mut self.session_id,
+            child,
+            reader: BufReader::new(stdout),
+            frames: FrameReader::new(),
+            stderr,
+            stderr_tail,
+            finished: false,
+        })
+    }
+
+    /// Run one turn and collect all of its events.
+    pub async fn query(&mut self, prompt: &str) -> Result<Vec<GeminiEvent>> {
+        self.query_stream(prompt)?.collect().await
+    }
+}
+
+/// Async stream of the [`GeminiEvent`]s of one turn.
+///
+/// Yields events until the `result` event. If the process exits first, the
+/// last item is [`Error::ProcessFailed`]. Dropping the stream early kills
+/// the process.
+pub struct EventStream<'a> {
+    session_id: &'a mut Option<String>,
+    child: Child,
+    reader: BufReader<ChildStdout>,
+    frames: FrameReader,
+    stderr: Option<JoinHandle<()>>,
+    stderr_tail: StderrTail,
+    finished: bool,
+}
+
+impl EventStream<'_> {
+    async fn finish(&mut self) -> Option<i32> {
+        self.finished = true;
+        self.child
+            .wait()
+            .await
+            .ok()
+            .and_then(|status| status.code())
+    }
+
+    /// Get the next event.
+    pub async fn next(&mut self) -> Option<Result<GeminiEvent>> {
+        if self.finished {
+            return None;
+        }
+        loop {
+            let frame = match next_frame(self.frames.read_frame_async(&mut self.reader).await) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => {
+                    let code = self.finish().await;
+                    if let Some(handle) = self.stderr.take() {
+                        let _ = handle.await;
+                    }
+                    return Some(Err(missing_result(code, &self.stderr_tail.text())));
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            };
+            let Some(parsed) = parse_line(frame) else {
+                continue;
+            };
+            if let Ok(event) = &parsed {
+                if let Some(id) = event.session_id() {
+                    *self.session_id = Some(id.to_string());
+                }
+                if event.is_result() {
+                    self.finish().await;
+                }
+            }
+            return Some(parsed);
+        }
+    }
+
+    /// Collect all remaining events.
+    pub async fn collect(mut self) -> Result<Vec<GeminiEvent>> {
+        let mut events = Vec::new();
+        while let Some(result) = self.next().await {
+            events.push(result?);
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_cli;
+
+    #[tokio::test]
+    async fn test_turns_resume_the_session() {
+        let cli = fake_cli("async");
+        let mut client = AsyncClient::with_builder(GeminiCliBuilder::new().command(&cli))
+            .await
+            .unwrap();
+
+        let mut stream = client.query_stream("first").unwrap();
+        let mut kinds = Vec::new();
+        while let Some(event) = stream.next().await {
+            kinds.push(event.unwrap().event_type().to_string());
+        }
+        assert_eq!(kinds, ["init", "message", "result"]);
+        assert_eq!(client.session_id(), Some("session-1"));
+
+        let events = client.query("second").await.unwrap();
+        assert_eq!(
+            events[1].assistant_text(),
+            Some("args: --output-format stream-json --resume session-1 --prompt second")
+        );
+
+        let err = client.query("fail").await.unwrap_err();
+        assert!(matches!(err, Error::ProcessFailed(3, ref s) if s == "quota exceeded"));
+        std::fs::remove_file(cli).ok();
+    }
+}
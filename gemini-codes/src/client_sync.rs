@@ -0,0 +1,228 @@
+//! Synchronous multi-turn client for the Gemini CLI.
+//!
+//! Each turn spawns `gemini --output-format stream-json --prompt <text>` and
+//! reads events until the `result` event. The session id from the first
+//! turn's `init` event is passed to `--resume` on every later turn, so the
+//! conversation continues across processes.
+//!
+//! This is the blocking counterpart to [`crate::client_async::AsyncClient`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use gemini_codes::{GeminiEvent, SyncClient};
+//!
+//! let mut client = SyncClient::new()?;
+//! for event in client.query_stream("What is 2 + 2?")? {
+//!     if let Some(text) = event?.assistant_text() {
+//!         print!("{}", text);
+//!     }
+//! }
+//! // Continues the same session.
+//! let follow_up = client.query("And times 3?")?;
+//! ```
+
+use crate::cli::GeminiCliBuilder;
+use crate::error::{Error, Result};
+use crate::io::events::GeminiEvent;
+use crate::stream::{missing_result, next_frame, parse_line, StderrTail};
+use codes_codec::FrameReader;
+use log::debug;
+use std::io::BufReader;
+use std::process::{Child, ChildStdout};
+use std::thread::JoinHandle;
+
+/// Synchronous multi-turn client for the Gemini CLI.
+#[derive(Debug, Clone)]
+pub struct SyncClient {
+    builder: GeminiCliBuilder,
+    session_id: Option<String>,
+}
+
+impl SyncClient {
+    /// Create a client with default settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `gemini` CLI version check fails to run.
+    pub fn new() -> Result<Self> {
+        Self::with_builder(GeminiCliBuilder::new())
+    }
+
+    /// Create a client with a custom [`GeminiCliBuilder`].
+    ///
+    /// No process is spawned until the first query.
+    pub fn with_builder(builder: GeminiCliBuilder) -> Result<Self> {
+        crate::version::check_gemini_version()?;
+        Ok(Self {
+            builder,
+            session_id: None,
+        })
+    }
+
+    /// Continue an existing session on the next query.
+    pub fn resume(builder: GeminiCliBuilder, session_id: impl Into<String>) -> Result<Self> {
+        let mut client = Self::with_builder(builder)?;
+        client.session_id = Some(session_id.into());
+        Ok(client)
+    }
+
+    /// Session id reported by the last `init` event, if any turn has run.
+    pub fn session_id(&self) -> Option<&str> {
+        self.session_id.as_deref()
+    }
+
+    /// Start a fresh session on the next query.
+    pub fn reset_session(&mut self) {
+        self.session_id = None;
+    }
+
+    /// Run one turn and iterate over its events, ending after `result`.
+    pub fn query_stream(&mut self, prompt: &str) -> Result<EventIterator<'_>> {
+        let mut child = self
+            .builder
+            .spawn_sync(prompt, self.session_id.as_deref())?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| Error::Protocol("Failed to get stdout".to_string()))?;
+        let stderr_tail = StderrTail::default();
+        let stderr = child
+            .stderr
+            .take()
+            .map(|pipe| {
+                let tail = stderr_tail.clone();
+                codes_spawn::drain_lines("gemini-stderr-drain", pipe, move |line| tail.push(line))
+            })
+            .transpose()?;
+        Ok(EventIterator {
+            session_id: &mut self.session_id,
+            child,
+            reader: BufReader::new(stdout),
+            frames: FrameReader::new(),
+            stderr,
+            stderr_tail,
+            finished: false,
+        })
+    }
+
+    /// Run one turn and collect all of its events.
+    pub fn query(&mut self, prompt: &str) -> Result<Vec<GeminiEvent>> {
+        self.query_stream(prompt)?.collect()
+    }
+}
+
+/// Iterator over the [`GeminiEvent`]s of one turn.
+///
+/// Yields events until the `result` event. If the process exits first, the
+/// last item is [`Error::ProcessFailed`]. Dropping the iterator early kills
+/// the process.
+pub struct EventIterator<'a> {
+    session_id: &'a mut Option<String>,
+    child: Child,
+    reader: BufReader<ChildStdout>,
+    frames: FrameReader,
+    stderr: Option<JoinHandle<()>>,
+    stderr_tail: StderrTail,
+    finished: bool,
+}
+
+impl EventIterator<'_> {
+    fn finish(&mut self) -> Option<i32> {
+        self.finished = true;
+        self.child.wait().ok().and_then(|status| status.code())
+    }
+
+    fn stderr_text(&mut self) -> String {
+        if let Some(handle) = self.stderr.take() {
+            let _ = handle.join();
+        }
+        self.stderr_tail.text()
+    }
+}
+
+impl Iterator for EventIterator<'_> {
+    type Item = Result<GeminiEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        loop {
+            let frame = match next_frame(self.frames.read_frame(&mut self.reader)) {
+                Ok(Some(frame)) => frame,
+                Ok(None) => {
+                    let code = self.finish();
+                    let stderr = self.stderr_text();
+                    return Some(Err(missing_result(code, &stderr)));
+                }
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+            };
+            let Some(parsed) = parse_line(frame) else {
+                continue;
+            };
+            if let Ok(event) = &parsed {
+                if let Some(id) = event.session_id() {
+                    *self.session_id = Some(id.to_string());
+                }
+                if event.is_result() {
+                    self.finish();
+                }
+            }
+            return Some(parsed);
+        }
+    }
+}
+
+impl Drop for EventIterator<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            debug!("[CLIENT] Turn dropped before result; killing gemini");
+            let _ = codes_spawn::kill_and_reap(&mut self.child);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_cli;
+
+    #[test]
+    fn test_turns_resume_the_session() {
+        let cli = fake_cli("sync");
+        let mut client = SyncClient::with_builder(GeminiCliBuilder::new().command(&cli)).unwrap();
+
+        let events = client.query("first").unwrap();
+        let kinds: Vec<&str> = events.iter().map(GeminiEvent::event_type).collect();
+        assert_eq!(kinds, ["init", "message", "result"]);
+        assert_eq!(
+            events[1].assistant_text(),
+            Some("args: --output-format stream-json --prompt first")
+        );
+        assert_eq!(client.session_id(), Some("session-1"));
+
+        let events = client.query("second").unwrap();
+        assert_eq!(
+            events[1].assistant_text(),
+            Some("args: --output-format stream-json --resume session-1 --prompt second")
+        );
+        std::fs::remove_file(cli).ok();
+    }
+
+    #[test]
+    fn test_exit_without_result_is_an_error() {
+        let cli = fake_cli("sync-fail");
+        let mut client = SyncClient::with_builder(GeminiCliBuilder::new().command(&cli)).unwrap();
+
+        let err = client.query("fail").unwrap_err();
+        match err {
+            Error::ProcessFailed(3, stderr) => assert_eq!(stderr, "quota exceeded"),
+            other => panic!("unexpected {:?}", other),
+        }
+        std::fs::remove_file(cli).ok();
+    }
+}
@@ -0,0 +1,128 @@
+//! Error types for the gemini-codes crate.
+//!
+//! All fallible operations return [`Result<T>`], which uses [`enum@Error`] as the
+//! error type. The variants cover JSON serialization, I/O, process failures,
+//! and turn-level errors reported by the CLI.
+
+use serde_json::Value;
+use thiserror::Error;
+
+/// Error type for parsing failures that preserves the raw line.
+///
+/// Returned inside [`Error::Deserialization`] when a line of stream-json
+/// output fails to deserialize. `raw_json` is populated when the line was
+/// valid JSON that didn't match any known event; `None` when it wasn't JSON
+/// at all.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    /// Line from stdout.
+    pub raw_line: String,
+    /// Parsed JSON value when the line was valid JSON.
+    pub raw_json: Option<Value>,
+    /// The underlying serde error description.
+    pub error_message: String,
+}
+
+impl ParseError {
+    /// Build a [`ParseError`] from an offending line and its serde error.
+    pub fn from_line(line: impl Into<String>, error: serde_json::Error) -> Self {
+        let raw_line = line.into();
+        let raw_json = serde_json::from_str::<Value>(&raw_line).ok();
+        ParseError {
+            raw_line,
+            raw_json,
+            error_message: error.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Failed to parse stream-json event: {} (raw: {})",
+            self.error_message, self.raw_line
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// All possible errors from gemini-codes operations.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// JSON serialization or deserialization failed.
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// An I/O error occurred communicating with the CLI process.
+    ///
+    /// Common causes: process not found, pipe broken, permission denied.
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// A protocol-level error (e.g., missing stdout pipe).
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+
+    /// A line of CLI output could not be deserialized.
+    ///
+    /// If you encounter this, please report it with the `raw_line` — it
+    /// likely indicates a protocol change.
+    #[error("Deserialization error: {0}")]
+    Deserialization(#[from] ParseError),
+
+    /// The CLI exited before emitting a `result` event.
+    ///
+    /// Carries the exit code (-1 if killed by a signal) and the tail of
+    /// stderr.
+    #[error("Process exited with status {0}: {1}")]
+    ProcessFailed(i32, String),
+
+    /// The CLI binary could not be found on PATH.
+    #[error("Binary not found: '{name}' is not on PATH. Is it installed?")]
+    BinaryNotFound { name: String },
+
+    /// An unclassified error.
+    #[error("Unknown error: {0}")]
+    Unknown(String),
+}
+
+/// A `Result` type alias using [`enum@Error`].
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+impl From<codes_codec::FrameError> for Error {
+    fn from(err: codes_codec::FrameError) -> Self {
+        match err {
+            codes_codec::FrameError::Io(e) => Error::Io(e),
+            other => Error::Protocol(other.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_error_keeps_raw_json_when_line_is_json() {
+        let line = r#"{"type":"mystery"}"#;
+        let err = serde_json::from_str::<i32>(line).unwrap_err();
+
+        let pe = ParseError::from_line(line, err);
+        assert_eq!(pe.raw_line, line);
+        assert_eq!(pe.raw_json, Some(json!({"type": "mystery"})));
+        assert!(format!("{}", Error::Deserialization(pe)).contains("mystery"));
+    }
+
+    #[test]
+    fn parse_error_without_json() {
+        let line = "Loaded cached credentials.";
+        let err = serde_json::from_str::<Value>(line).unwrap_err();
+
+        let pe = ParseError::from_line(line, err);
+        assert!(pe.raw_json.is_none());
+    }
+}
@@ -0,0 +1,393 @@
+//! Headless stream-json event types.
+//!
+//! These types represent the events emitted by
+//! `gemini --prompt <text> --output-format stream-json`, where each line is a
+//! JSON object with a `"type"` field. A turn always starts with `init` and
+//! ends with `result`.
+//!
+//! # Example
+//!
+//! ```
+//! use gemini_codes::GeminiEvent;
+//!
+//! let json = r#"{"type":"init","timestamp":"2025-10-10T12:00:00.000Z","session_id":"abc123","model":"gemini-2.5-pro"}"#;
+//! let event: GeminiEvent = serde_json::from_str(json).unwrap();
+//! assert_eq!(event.event_type(), "init");
+//! assert_eq!(event.session_id(), Some("abc123"));
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::fmt;
+
+/// Author of a [`MessageEvent`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MessageRole {
+    User,
+    Assistant,
+    /// A role not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl MessageRole {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::User => "user",
+            Self::Assistant => "assistant",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for MessageRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for MessageRole {
+    fn from(s: &str) -> Self {
+        match s {
+            "user" => Self::User,
+            "assistant" => Self::Assistant,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for MessageRole {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageRole {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// Outcome of a tool call or of the whole turn.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Status {
+    Success,
+    Error,
+    /// A status not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl Status {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Success => "success",
+            Self::Error => "error",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for Status {
+    fn from(s: &str) -> Self {
+        match s {
+            "success" => Self::Success,
+            "error" => Self::Error,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for Status {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Status {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// Severity of a non-fatal [`ErrorEvent`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+    /// A severity not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for Severity {
+    fn from(s: &str) -> Self {
+        match s {
+            "warning" => Self::Warning,
+            "error" => Self::Error,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for Severity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Severity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// Error details attached to a failed tool call or turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorDetail {
+    /// Error class, e.g. `"FatalToolExecutionError"`.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub error_type: Option<String>,
+    pub message: String,
+}
+
+/// Token and timing statistics for a completed turn.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Stats {
+    #[serde(default)]
+    pub total_tokens: u64,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub tool_calls: u64,
+    /// Fields added by newer CLI versions (per-model breakdowns, cache
+    /// counts, ...).
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// First event of every turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InitEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    /// Session id; pass to `--resume` to continue the conversation.
+    pub session_id: String,
+    pub model: String,
+}
+
+/// A user message echo, or a chunk of assistant text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    pub role: MessageRole,
+    pub content: String,
+    /// True when `content` is an incremental chunk to append to the previous
+    /// assistant text rather than a complete message.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub delta: bool,
+}
+
+/// The model asked to run a tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolUseEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    pub tool_name: String,
+    /// Correlates with [`ToolResultEvent::tool_id`].
+    pub tool_id: String,
+    #[serde(default)]
+    pub parameters: Value,
+}
+
+/// A tool call finished.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolResultEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    pub tool_id: String,
+    pub status: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorDetail>,
+}
+
+/// A non-fatal error or warning; the turn continues.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Last event of every turn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    pub status: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorDetail>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<Stats>,
+}
+
+impl ResultEvent {
+    /// True if the turn failed.
+    pub fn is_error(&self) -> bool {
+        self.status != Status::Success
+    }
+}
+
+/// All events emitted by the Gemini CLI in stream-json mode.
+///
+/// Each variant corresponds to a `"type"` value in the JSONL output.
+/// Use [`GeminiEvent::event_type`] to get the type string.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GeminiEvent {
+    Init(InitEvent),
+    Message(MessageEvent),
+    ToolUse(ToolUseEvent),
+    ToolResult(ToolResultEvent),
+    Error(ErrorEvent),
+    Result(ResultEvent),
+}
+
+impl GeminiEvent {
+    /// Returns the event type string (e.g., `"init"`, `"tool_use"`).
+    pub fn event_type(&self) -> &str {
+        match self {
+            GeminiEvent::Init(_) => "init",
+            GeminiEvent::Message(_) => "message",
+            GeminiEvent::ToolUse(_) => "tool_use",
+            GeminiEvent::ToolResult(_) => "tool_result",
+            GeminiEvent::Error(_) => "error",
+            GeminiEvent::Result(_) => "result",
+        }
+    }
+
+    /// Session id, if this is the `init` event.
+    pub fn session_id(&self) -> Option<&str> {
+        match self {
+            GeminiEvent::Init(init) => Some(&init.session_id),
+            _ => None,
+        }
+    }
+
+    /// Assistant text carried by this event, if any.
+    pub fn assistant_text(&self) -> Option<&str> {
+        match self {
+            GeminiEvent::Message(msg) if msg.role == MessageRole::Assistant => Some(&msg.content),
+            _ => None,
+        }
+    }
+
+    /// True for the `result` event that ends a turn.
+    pub fn is_result(&self) -> bool {
+        matches!(self, GeminiEvent::Result(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> GeminiEvent {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_turn_events_parse() {
+        let events = [
+            r#"{"type":"init","timestamp":"2025-10-10T12:00:00.000Z","session_id":"abc123","model":"gemini-2.5-pro"}"#,
+            r#"{"type":"message","role":"user","content":"List files","timestamp":"2025-10-10T12:00:01.000Z"}"#,
+            r#"{"type":"tool_use","tool_name":"run_shell_command","tool_id":"bash-123","parameters":{"command":"ls -la"},"timestamp":"2025-10-10T12:00:02.000Z"}"#,
+            r#"{"type":"tool_result","tool_id":"bash-123","status":"success","output":"a.txt","timestamp":"2025-10-10T12:00:03.000Z"}"#,
+            r#"{"type":"message","role":"assistant","content":"Here are","delta":true,"timestamp":"2025-10-10T12:00:04.000Z"}"#,
+            r#"{"type":"result","status":"success","stats":{"total_tokens":250,"input_tokens":50,"output_tokens":200,"duration_ms":3000,"tool_calls":1},"timestamp":"2025-10-10T12:00:05.000Z"}"#,
+        ]
+        .map(parse);
+
+        let kinds: Vec<&str> = events.iter().map(GeminiEvent::event_type).collect();
+        assert_eq!(
+            kinds,
+            [
+                "init",
+                "message",
+                "tool_use",
+                "tool_result",
+                "message",
+                "result"
+            ]
+        );
+        assert_eq!(events[0].session_id(), Some("abc123"));
+        assert_eq!(events[1].assistant_text(), None);
+        assert_eq!(events[4].assistant_text(), Some("Here are"));
+
+        match &events[5] {
+            GeminiEvent::Result(r) => {
+                assert!(!r.is_error());
+                assert_eq!(r.stats.as_ref().unwrap().tool_calls, 1);
+            }
+            other => panic!("expected result, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_error_result_and_unknown_values() {
+        let event = parse(
+            r#"{"type":"result","status":"error","error":{"type":"FatalTurnLimitedError","message":"too many turns"},"stats":{"total_tokens":1,"models":{"gemini-2.5-pro":{}}}}"#,
+        );
+        let GeminiEvent::Result(result) = event else {
+            panic!("expected result");
+        };
+        assert!(result.is_error());
+        assert_eq!(result.error.unwrap().message, "too many turns");
+        assert!(result.stats.unwrap().extra.contains_key("models"));
+
+        let event = parse(r#"{"type":"error","severity":"notice","message":"retrying"}"#);
+        let GeminiEvent::Error(err) = event else {
+            panic!("expected error event");
+        };
+        assert_eq!(err.severity, Severity::Unknown("notice".into()));
+        assert_eq!(err.severity.to_string(), "notice");
+    }
+
+    #[test]
+    fn test_round_trip_preserves_wire_shape() {
+        let json = r#"{"type":"message","role":"assistant","content":"hi","delta":true}"#;
+        let value: Value = serde_json::from_str(json).unwrap();
+        let reserialized = serde_json::to_value(parse(json)).unwrap();
+        assert_eq!(reserialized, value);
+    }
+}
@@ -0,0 +1,118 @@
+//! A typed Rust interface for the [Gemini CLI](https://github.com/google-gemini/gemini-cli) protocol.
+//!
+//! This crate provides type-safe bindings for the Gemini CLI's headless
+//! `stream-json` output, plus sync and async clients that run multi-turn
+//! sessions by resuming the CLI's session id between turns.
+//!
+//! # Quick Start
+//!
+//! ```bash
+//! cargo add gemini-codes
+//! ```
+//!
+//! See `examples/async_client.rs` and `examples/sync_client.rs` for runnable
+//! versions of the usage patterns below. The high-level shape:
+//!
+//! ```ignore
+//! let mut client = AsyncClient::with_builder(GeminiCliBuilder::new().model("gemini-2.5-pro")).await?;
+//! for event in client.query("What is 2 + 2?").await? {
+//!     if let Some(text) = event.assistant_text() {
+//!         print!("{}", text);
+//!     }
+//! }
+//! ```
+//!
+//! # Architecture
+//!
+//! The crate mirrors `claude-codes` and `codex-codes`:
+//!
+//! - [`client_async`] / [`client_sync`] — High-level clients that spawn one
+//!   CLI process per turn and carry the session id across turns
+//! - [`io`] — Typed [`GeminiEvent`]s for every line of `stream-json` output
+//! - [`cli`] — Builder for the `gemini` command line
+//! - [`error`] — Error types and result aliases
+//! - [`version`] — Version compatibility checking against the installed CLI
+//!
+//! # Protocol Overview
+//!
+//! `gemini --output-format stream-json --prompt <text>` runs a single turn
+//! and writes newline-delimited JSON events to stdout:
+//!
+//! 1. **`init`** — session id and model
+//! 2. **`message`** — the user prompt echo, then assistant text (often as
+//!    `delta` chunks)
+//! 3. **`tool_use`** / **`tool_result`** — tool calls and their outcomes
+//! 4. **`error`** — non-fatal warnings and errors
+//! 5. **`result`** — final status and token/timing stats; the process exits
+//!
+//! Follow-up turns pass the session id to `--resume`. Headless runs cannot
+//! answer approval prompts, so set an [`ApprovalMode`] or `allowed_tools`
+//! for tools that need approval.
+//!
+//! # Feature Flags
+//!
+//! | Feature | Description | WASM-compatible |
+//! |---------|-------------|-----------------|
+//! | `types` | Core event types only | Yes |
+//! | `sync-client` | Synchronous client with blocking I/O | No |
+//! | `async-client` | Asynchronous client using tokio | No |
+//!
+//! All features are enabled by default. For WASM or type-sharing use cases:
+//!
+//! ```toml
+//! [dependencies]
+//! gemini-codes = { version = "0.1", default-features = false, features = ["types"] }
+//! ```
+//!
+//! # Parsing Raw Events
+//!
+//! ```
+//! use gemini_codes::GeminiEvent;
+//!
+//! let json = r#"{"type":"tool_use","tool_name":"read_file","tool_id":"t1","parameters":{"path":"a.rs"}}"#;
+//! let event: GeminiEvent = serde_json::from_str(json).unwrap();
+//! assert_eq!(event.event_type(), "tool_use");
+//! ```
+
+pub mod io;
+
+pub mod error;
+
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+pub mod cli;
+
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+pub mod version;
+
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+mod stream;
+
+#[cfg(all(test, unix, any(feature = "sync-client", feature = "async-client")))]
+mod test_support;
+
+#[cfg(feature = "sync-client")]
+pub mod client_sync;
+
+#[cfg(feature = "async-client")]
+pub mod client_async;
+
+// Stream-json event types
+pub use io::events::{
+    ErrorDetail, ErrorEvent, GeminiEvent, InitEvent, MessageEvent, MessageRole, ResultEvent,
+    Severity, Stats, Status, ToolResultEvent, ToolUseEvent,
+};
+
+// Error types (always available)
+pub use error::{Error, ParseError, Result};
+
+// CLI builder (feature-gated)
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+pub use cli::{ApprovalMode, GeminiCliBuilder};
+
+// Sync client
+#[cfg(feature = "sync-client")]
+pub use client_sync::{EventIterator, SyncClient};
+
+// Async client
+#[cfg(feature = "async-client")]
+pub use client_async::{AsyncClient, EventStream};
@@ -0,0 +1,127 @@
+//! Line handling shared by the sync and async clients.
+
+use crate::error::{Error, ParseError, Result};
+use crate::io::events::GeminiEvent;
+use codes_codec::FrameError;
+use log::debug;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Number of trailing stderr lines kept for [`Error::ProcessFailed`].
+const STDERR_TAIL_LINES: usize = 20;
+
+/// Parse one line of stdout.
+///
+/// Returns `None` for blank lines and for the plain-text notices the CLI
+/// sometimes prints before the event stream starts (e.g. "Loaded cached
+/// credentials.").
+pub(crate) fn parse_line(line: &str) -> Option<Result<GeminiEvent>> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    if !trimmed.starts_with('{') {
+        debug!("[CLIENT] Skipping non-JSON output: {}", trimmed);
+        return None;
+    }
+    Some(
+        serde_json::from_str(trimmed)
+            .map_err(|e| Error::Deserialization(ParseError::from_line(trimmed, e))),
+    )
+}
+
+/// Settle the outcome of reading a frame. A last line cut off without a
+/// newline ends the output if it is one of the CLI's plain-text notices,
+/// and is a truncated event otherwise.
+pub(crate) fn next_frame(
+    read: std::result::Result<Option<&str>, FrameError>,
+) -> Result<Option<&str>> {
+    match read {
+        Err(FrameError::Truncated { partial }) if !partial.starts_with('{') => {
+            debug!("[CLIENT] Skipping non-JSON output: {}", partial);
+            Ok(None)
+        }
+        other => other.map_err(Error::from),
+    }
+}
+
+/// The last lines a turn wrote to stderr, filled by a background drain.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StderrTail(Arc<Mutex<VecDeque<String>>>);
+
+impl StderrTail {
+    /// Keep `line`, dropping the oldest once [`STDERR_TAIL_LINES`] are held.
+    pub(crate) fn push(&self, line: &str) {
+        let mut lines = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if lines.len() == STDERR_TAIL_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line.to_string());
+    }
+
+    /// The kept lines, joined with newlines.
+    pub(crate) fn text(&self) -> String {
+        let lines = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        Vec::from_iter(lines.iter().map(String::as_str)).join("\n")
+    }
+}
+
+/// Error for a turn whose process ended without a `result` event.
+pub(crate) fn missing_result(code: Option<i32>, stderr: &str) -> Error {
+    let lines: Vec<&str> = stderr.lines().collect();
+    let tail = lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n");
+    Error::ProcessFailed(code.unwrap_or(-1), tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line_skips_notices() {
+        assert!(parse_line("").is_none());
+        assert!(parse_line("Loaded cached credentials.").is_none());
+        assert!(parse_line(r#"{"type":"bogus"}"#).unwrap().is_err());
+        let event = parse_line(r#"{"type":"init","session_id":"s","model":"m"}"#)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.session_id(), Some("s"));
+    }
+
+    #[test]
+    fn test_stderr_tail_keeps_last_lines() {
+        let tail = StderrTail::default();
+        for i in 0..30 {
+            tail.push(&format!("line {}", i));
+        }
+        let text = tail.text();
+        assert!(text.starts_with("line 10\n"));
+        assert!(text.ends_with("line 29"));
+    }
+
+    #[test]
+    fn test_unterminated_notice_ends_output() {
+        let mut frames = codes_codec::FrameReader::new();
+        let mut input = std::io::Cursor::new("{\"type\":\"init\"}\nDone.");
+        assert!(next_frame(frames.read_frame(&mut input)).unwrap().is_some());
+        assert!(next_frame(frames.read_frame(&mut input)).unwrap().is_none());
+
+        let mut input = std::io::Cursor::new("{\"type\":\"mess");
+        assert!(matches!(
+            next_frame(frames.read_frame(&mut input)),
+            Err(Error::Protocol(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_result_keeps_stderr_tail() {
+        let stderr: String = (0..30).map(|i| format!("line {}\n", i)).collect();
+        match missing_result(Some(1), &stderr) {
+            Error::ProcessFailed(1, tail) => {
+                assert!(tail.starts_with("line 10"));
+                assert!(tail.ends_with("line 29"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
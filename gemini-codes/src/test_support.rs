@@ -0,0 +1,28 @@
+//! Stand-in CLI for client tests.
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+/// Write a script that mimics one headless turn.
+///
+/// It echoes its arguments back as the assistant text, reports session
+/// `session-1`, and exits with status 3 (and no `result`) when the prompt is
+/// `fail`.
+pub(crate) fn fake_cli(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("gemini-codes-{}-{}.sh", name, std::process::id()));
+    let script = r#"#!/bin/sh
+for prompt; do :; done
+if [ "$prompt" = fail ]; then
+  echo "quota exceeded" >&2
+  exit 3
+fi
+echo 'Loaded cached credentials.'
+echo '{"type":"init","session_id":"session-1","model":"gemini-test"}'
+printf '{"type":"message","role":"assistant","content":"args: %s","delta":true}\n' "$*"
+echo '{"type":"result","status":"success","stats":{"total_tokens":3}}'
+"#;
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
@@ -0,0 +1,57 @@
+//! Example of using the asynchronous OpenCode ACP client.
+//!
+//! Opens a session in the current directory, sends one prompt, prints the
+//! agent's reply and tool activity as it streams, and approves each tool
+//! call once.
+
+use opencode_codes::{
+    AsyncClient, NewSessionParams, PromptParams, ServerMessage, ServerRequest, SessionUpdate,
+};
+use std::error::Error;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    env_logger::init();
+
+    let mut client = AsyncClient::start().await?;
+    let cwd = std::env::current_dir()?.display().to_string();
+    let session = client.new_session(&NewSessionParams::new(cwd)).await?;
+
+    client
+        .prompt(&PromptParams::text(
+            &session.session_id,
+            "List the files in this directory.",
+        ))
+        .await?;
+
+    while let Some(msg) = client.next_message().await? {
+        match msg {
+            ServerMessage::Notification(_) => match msg.session_update().map(|n| &n.update) {
+                Some(SessionUpdate::AgentMessageChunk { content }) => {
+                    print!("{}", content.as_text().unwrap_or_default());
+                }
+                Some(SessionUpdate::ToolCall(call)) => println!("\n[tool: {}]", call.title),
+                _ => {}
+            },
+            ServerMessage::Request {
+                id,
+                request: ServerRequest::RequestPermission(params),
+            } => {
+                println!("\n[approving: {}]", params.tool_call.tool_call_id);
+                client.respond(id, &params.allow()).await?;
+            }
+            ServerMessage::Request { id, request } => {
+                client
+                    .respond_error(id, -32601, &format!("{} not supported", request.method()))
+                    .await?;
+            }
+            ServerMessage::PromptComplete(done) => {
+                println!("\n[{}]", done.stop_reason);
+                break;
+            }
+        }
+    }
+
+    client.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+//! Example of using the synchronous OpenCode ACP client.
+//!
+//! Runs two prompts in the same session, rejecting any tool call, and
+//! prints the agent's reply as it streams.
+
+use opencode_codes::{NewSessionParams, PromptParams, ServerMessage, ServerRequest, SyncClient};
+use std::error::Error;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    env_logger::init();
+
+    let mut client = SyncClient::start()?;
+    let cwd = std::env::current_dir()?.display().to_string();
+    let session = client.new_session(&NewSessionParams::new(cwd))?;
+
+    for prompt in ["What is the capital of France?", "And of Germany?"] {
+        println!("\n> {}\n", prompt);
+        client.prompt(&PromptParams::text(&session.session_id, prompt))?;
+
+        while let Some(msg) = client.next_message()? {
+            if let Some(text) = msg.session_update().and_then(|n| n.update.agent_text()) {
+                print!("{}", text);
+            }
+            match msg {
+                ServerMessage::Request {
+                    id,
+                    request: ServerRequest::RequestPermission(params),
+                } => client.respond(id, &params.reject())?,
+                ServerMessage::PromptComplete(done) => {
+                    println!("\n[{}]", done.stop_reason);
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    println!("\nSession: {}", session.session_id);
+    Ok(())
+}
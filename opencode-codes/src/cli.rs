@@ -0,0 +1,198 @@
+//! Builder for launching the OpenCode ACP agent process.
+//!
+//! The [`AcpBuilder`] configures and spawns `opencode acp`, a long-lived
+//! process that speaks the Agent Client Protocol over newline-delimited stdio.
+//!
+//! `opencode acp` writes its logs to stderr when `--print-logs` is set. An
+//! unread pipe fills at ~64 KB and blocks the child, so the clients drain
+//! stderr in the background (with `codes_spawn`) and forward each line
+//! through the `log` crate at debug level.
+
+use log::debug;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+/// Module path used when forwarding stderr lines through the `log` crate.
+const STDERR_TARGET: &str = "opencode_codes::stderr";
+
+/// Forward a line of the agent's stderr to the `log` crate.
+pub(crate) fn log_stderr_line(line: &str) {
+    if !line.is_empty() {
+        debug!(target: STDERR_TARGET, "{}", line);
+    }
+}
+
+/// Builder for launching an OpenCode ACP agent.
+///
+/// Produces commands of the form: `opencode acp [--print-logs] [--log-level L] [extra]...`
+///
+/// Models, providers and agent permissions come from OpenCode's own config
+/// (`opencode.json`); point at a specific one with
+/// `env("OPENCODE_CONFIG", path)`. Per-session settings such as the working
+/// directory and MCP servers are sent over ACP in `session/new`.
+#[derive(Debug, Clone)]
+pub struct AcpBuilder {
+    command: PathBuf,
+    working_directory: Option<PathBuf>,
+    print_logs: bool,
+    log_level: Option<String>,
+    env: Vec<(String, String)>,
+    extra_args: Vec<String>,
+}
+
+impl Default for AcpBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AcpBuilder {
+    /// Create a new builder with default settings.
+    pub fn new() -> Self {
+        Self {
+            command: PathBuf::from("opencode"),
+            working_directory: None,
+            print_logs: false,
+            log_level: None,
+            env: Vec::new(),
+            extra_args: Vec::new(),
+        }
+    }
+
+    /// Set custom path to the opencode binary.
+    pub fn command<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.command = path.into();
+        self
+    }
+
+    /// Set the working directory for the agent process.
+    pub fn working_directory<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.working_directory = Some(dir.into());
+        self
+    }
+
+    /// Write OpenCode's logs to stderr (`--print-logs`).
+    ///
+    /// Stderr is drained in the background and forwarded to the `log` crate
+    /// at debug level.
+    pub fn print_logs(mut self, enabled: bool) -> Self {
+        self.print_logs = enabled;
+        self
+    }
+
+    /// Set OpenCode's log level (`--log-level`), e.g. `"DEBUG"`.
+    pub fn log_level<S: Into<String>>(mut self, level: S) -> Self {
+        self.log_level = Some(level.into());
+        self
+    }
+
+    /// Set an environment variable for the agent process.
+    pub fn env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Append raw arguments to the `acp` subcommand for flags the SDK
+    /// doesn't model yet.
+    pub fn extra_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.extra_args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Resolve the command path, using `which` for non-absolute paths.
+    ///
+    /// A bare `opencode` that isn't on `PATH` is also looked for where the
+    /// installers put it, since GUI apps and services often run with a
+    /// minimal `PATH`.
+    fn resolve_command(&self) -> crate::error::Result<PathBuf> {
+        let fallbacks = if self.command == Path::new("opencode") {
+            codes_spawn::install_locations("opencode")
+        } else {
+            Vec::new()
+        };
+        codes_spawn::resolve(&self.command, &fallbacks).ok_or_else(|| {
+            crate::error::Error::BinaryNotFound {
+                name: self.command.display().to_string(),
+            }
+        })
+    }
+
+    /// Build the command arguments.
+    ///
+    /// Layout: `acp [--print-logs] [--log-level L] [extra_args]...`
+    fn build_args(&self) -> Vec<String> {
+        let mut args = vec!["acp".to_string()];
+        if self.print_logs {
+            args.push("--print-logs".to_string());
+        }
+        if let Some(ref level) = self.log_level {
+            args.push("--log-level".to_string());
+            args.push(level.clone());
+        }
+        args.extend(self.extra_args.iter().cloned());
+        args
+    }
+
+    fn std_command(&self) -> crate::error::Result<std::process::Command> {
+        let resolved = self.resolve_command()?;
+        let args = self.build_args();
+
+        debug!(
+            "[CLI] Spawning opencode: {} {}",
+            resolved.display(),
+            args.join(" ")
+        );
+
+        let mut cmd = std::process::Command::new(&resolved);
+        cmd.args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(ref dir) = self.working_directory {
+            cmd.current_dir(dir);
+        }
+        codes_spawn::apply_env(&mut cmd, self.env.iter().map(|(k, v)| (k, Some(v))));
+        Ok(cmd)
+    }
+
+    /// Spawn the agent process asynchronously.
+    #[cfg(feature = "async-client")]
+    pub async fn spawn(self) -> crate::error::Result<tokio::process::Child> {
+        tokio::process::Command::from(self.std_command()?)
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(crate::error::Error::Io)
+    }
+
+    /// Spawn the agent process synchronously.
+    pub fn spawn_sync(self) -> crate::error::Result<std::process::Child> {
+        self.std_command()?.spawn().map_err(crate::error::Error::Io)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_args() {
+        assert_eq!(AcpBuilder::new().build_args(), vec!["acp"]);
+    }
+
+    #[test]
+    fn test_logging_and_extra_args() {
+        let args = AcpBuilder::new()
+            .print_logs(true)
+            .log_level("DEBUG")
+            .extra_args(["--port", "0"])
+            .build_args();
+        assert_eq!(
+            args,
+            vec!["acp", "--print-logs", "--log-level", "DEBUG", "--port", "0"]
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! Asynchronous multi-turn client for the OpenCode ACP agent.
+//!
+//! Spawns `opencode acp` and communicates over newline-delimited JSON-RPC.
+//! The connection stays open for multiple sessions and prompts until
+//! explicitly shut down.
+//!
+//! # Lifecycle
+//!
+//! 1. Create a client with [`AsyncClient::start`] (spawns and initializes the agent)
+//! 2. Call [`AsyncClient::new_session`] to create a session
+//! 3. Call [`AsyncClient::prompt`] to send user input
+//! 4. Read [`AsyncClient::next_message`] (or [`AsyncClient::events`]) until
+//!    [`ServerMessage::PromptComplete`]
+//! 5. Answer permission prompts via [`AsyncClient::respond`]
+//! 6. Repeat steps 3-5 for follow-up prompts
+//!
+//! # Example
+//!
+//! ```ignore
+//! use opencode_codes::{NewSessionParams, PromptParams, ServerMessage, ServerRequest, AsyncClient};
+//!
+//! let mut client = AsyncClient::start().await?;
+//! let session = client
+//!     .new_session(&NewSessionParams::new("/path/to/project"))
+//!     .await?;
+//!
+//! client
+//!     .prompt(&PromptParams::text(&session.session_id, "Hello!"))
+//!     .await?;
+//! while let Some(msg) = client.next_message().await? {
+//!     match msg {
+//!         ServerMessage::Request { id, request: ServerRequest::RequestPermission(p) } => {
+//!             client.respond(id, &p.allow()).await?;
+//!         }
+//!         ServerMessage::PromptComplete(done) => {
+//!             println!("stopped: {}", done.stop_reason);
+//!             break;
+//!         }
+//!         other => println!("{:?}", other),
+//!     }
+//! }
+//! ```
+
+use crate::cli::AcpBuilder;
+use crate::dispatch::{decode_result, into_server_message, parse_line};
+use crate::error::{Error, Result};
+use crate::jsonrpc::{
+    JsonRpcError, JsonRpcErrorData, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest,
+    JsonRpcResponse, JsonRpcVersion, RequestId,
+};
+use crate::messages::ServerMessage;
+use crate::protocol::{
+    methods, AuthenticateParams, AuthenticateResponse, CancelNotification, InitializeParams,
+    InitializeResponse, LoadSessionParams, LoadSessionResponse, NewSessionParams,
+    NewSessionResponse, PromptParams, SetSessionModeParams, SetSessionModeResponse,
+};
+use codes_codec::FrameReader;
+use log::{debug, error, warn};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::VecDeque;
+use tokio::io::{AsyncWriteExt, BufReader, BufWriter};
+use tokio::process::Child;
+
+/// Buffer size for reading stdout (10MB).
+const STDOUT_BUFFER_SIZE: usize = 10 * 1024 * 1024;
+
+/// Asynchronous multi-turn client for the OpenCode ACP agent.
+///
+/// Communicates with a long-lived `opencode acp` process via
+/// newline-delimited JSON-RPC over stdio. Manages request/response
+/// correlation and buffers incoming notifications that arrive while
+/// waiting for RPC responses.
+///
+/// The client automatically kills the agent process when dropped.
+pub struct AsyncClient {
+    child: Child,
+    writer: BufWriter<tokio::process::ChildStdin>,
+    reader: BufReader<tokio::process::ChildStdout>,
+    /// Line framing for `reader`, reusing one buffer across messages
+    frames: FrameReader,
+    /// Handle to the background task draining the child's stderr pipe.
+    _stderr_drain: tokio::task::JoinHandle<()>,
+    next_id: i64,
+    buffered: VecDeque<ServerMessage>,
+    /// Id of the in-flight `session/prompt`, whose response ends the turn.
+    pending_prompt: Option<RequestId>,
+}
+
+impl AsyncClient {
+    /// Start an agent with default settings.
+    ///
+    /// Spawns `opencode acp`, performs the `initialize` handshake, and returns
+    /// a connected client ready for `new_session()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `opencode` CLI is not installed, the process
+    /// fails to start, or the initialization handshake fails.
+    pub async fn start() -> Result<Self> {
+        Self::start_with(AcpBuilder::new()).await
+    }
+
+    /// Start an agent with a custom [`AcpBuilder`].
+    ///
+    /// Performs the `initialize` handshake with [`InitializeParams::default`]
+    /// before returning.
+    pub async fn start_with(builder: AcpBuilder) -> Result<Self> {
+        let mut client = Self::spawn(builder).await?;
+        client.initialize(&InitializeParams::default()).await?;
+        Ok(client)
+    }
+
+    /// Spawn an agent without performing the `initialize` handshake.
+    ///
+    /// Use this to advertise client capabilities (e.g. `fs`). You **must**
+    /// call [`AsyncClient::initialize`] before any other requests.
+    pub async fn spawn(builder: AcpBuilder) -> Result<Self> {
+        crate::version::check_opencode_version_async().await?;
+
+        let mut child = builder.spawn().await?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| Error::Protocol("Failed to get stdin".to_string()))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| Error::Protocol("Failed to get stdout".to_string()))?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| Error::Protocol("Failed to get stderr".to_string()))?;
+
+        Ok(Self {
+            child,
+            writer: BufWriter::new(stdin),
+            reader: BufReader::with_capacity(STDOUT_BUFFER_SIZE, stdout),
+            frames: FrameReader::new(),
+            _stderr_drain: codes_spawn::drain_lines_async(stderr, crate::cli::log_stderr_line),
+            next_id: 1,
+            buffered: VecDeque::new(),
+            pending_prompt: None,
+        })
+    }
+
+    /// Send a JSON-RPC request and wait for the matching response.
+    ///
+    /// Any notifications or agent requests that arrive before the response
+    /// are buffered and can be retrieved via [`AsyncClient::next_message`].
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Protocol`] if a prompt is still running; wait for
+    ///   [`ServerMessage::PromptComplete`] first
+    /// - [`Error::JsonRpc`] if the agent returns a JSON-RPC error
+    /// - [`Error::ServerClosed`] if the connection drops before a response arrives
+    /// - [`Error::Json`] if response deserialization fails
+    pub async fn request<P: Serialize, R: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: &P,
+    ) -> Result<R> {
+        if self.pending_prompt.is_some() {
+            return Err(Error::Protocol(format!(
+                "cannot send {} while a prompt is running",
+                method
+            )));
+        }
+        let id = self.send_request(method, params).await?;
+
+        loop {
+            match self.read_message().await? {
+                JsonRpcMessage::Response(resp) if resp.id == id => {
+                    return decode_result(resp.result);
+                }
+                JsonRpcMessage::Error(err) if err.id == id => {
+                    return Err(Error::JsonRpc {
+                        code: err.error.code,
+                        message: err.error.message,
+                    });
+                }
+                other => {
+                    if let Some(msg) = into_server_message(other, &mut self.pending_prompt)? {
+                        self.buffered.push_back(msg);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Perform the `initialize` handshake.
+    ///
+    /// This must be the first request after spawning the process.
+    pub async fn initialize(&mut self, params: &InitializeParams) -> Result<InitializeResponse> {
+        self.request(methods::INITIALIZE, params).await
+    }
+
+    /// Authenticate with one of the methods from [`InitializeResponse::auth_methods`].
+    pub async fn authenticate(&mut self, method_id: &str) -> Result<AuthenticateResponse> {
+        self.request(
+            methods::AUTHENTICATE,
+            &AuthenticateParams {
+                method_id: method_id.to_string(),
+            },
+        )
+        .await
+    }
+
+    /// Create a new session.
+    pub async fn new_session(&mut self, params: &NewSessionParams) -> Result<NewSessionResponse> {
+        self.request(methods::SESSION_NEW, params).await
+    }
+
+    /// Load a previous session.
+    ///
+    /// The agent replays the history as `session/update` notifications, which
+    /// are buffered and returned by [`AsyncClient::next_message`].
+    pub async fn load_session(
+        &mut self,
+        params: &LoadSessionParams,
+    ) -> Result<LoadSessionResponse> {
+        self.request(methods::SESSION_LOAD, params).await
+    }
+
+    /// Switch a session to another mode.
+    pub async fn set_mode(
+        &mut self,
+        params: &SetSessionModeParams,
+    ) -> Result<SetSessionModeResponse> {
+        self.request(methods::SESSION_SET_MODE, params).await
+    }
+
+    /// Send a prompt without waiting for the turn to finish.
+    ///
+    /// Consume [`AsyncClient::events`] until [`ServerMessage::PromptComplete`],
+    /// answering any permission requests along the way. Only one prompt may
+    /// run at a time.
+    pub async fn prompt(&mut self, params: &PromptParams) -> Result<RequestId> {
+        if self.pending_prompt.is_some() {
+            return Err(Error::Protocol("a prompt is already running".to_string()));
+        }
+        let id = self.send_request(methods::SESSION_PROMPT, params).await?;
+        self.pending_prompt = Some(id.clone());
+        Ok(id)
+    }
+
+    /// Ask the agent to stop the running prompt.
+    ///
+    /// The turn still ends with [`ServerMessage::PromptComplete`], with
+    /// [`StopReason::Cancelled`](crate::StopReason::Cancelled).
+    pub async fn cancel(&mut self, session_id: &str) -> Result<()> {
+        let params = CancelNotification {
+            session_id: session_id.to_string(),
+        };
+        self.send_raw(&JsonRpcNotification {
+            jsonrpc: JsonRpcVersion,
+            method: methods::SESSION_CANCEL.to_string(),
+            params: Some(serde_json::to_value(params).map_err(Error::Json)?),
+        })
+        .await
+    }
+
+    /// Respond to an agent-to-client request.
+    ///
+    /// For permission prompts pass a
+    /// [`RequestPermissionResponse`](crate::RequestPermissionResponse), e.g.
+    /// from [`RequestPermissionParams::allow`](crate::RequestPermissionParams::allow).
+    pub async fn respond<R: Serialize>(&mut self, id: RequestId, result: &R) -> Result<()> {
+        let resp = JsonRpcResponse {
+            jsonrpc: JsonRpcVersion,
+            id,
+            result: serde_json::to_value(result).map_err(Error::Json)?,
+        };
+        self.send_raw(&resp).await
+    }
+
+    /// Respond to an agent-to-client request with an error.
+    pub async fn respond_error(&mut self, id: RequestId, code: i64, message: &str) -> Result<()> {
+        let err = JsonRpcError {
+            jsonrpc: JsonRpcVersion,
+            id,
+            error: JsonRpcErrorData {
+                code,
+                message: message.to_string(),
+                data: None,
+            },
+        };
+        self.send_raw(&err).await
+    }
+
+    /// Read the next incoming message.
+    ///
+    /// Returns buffered messages first, then reads from the wire.
+    /// Returns `Ok(None)` when the agent closes the connection (EOF).
+    pub async fn next_message(&mut self) -> Result<Option<ServerMessage>> {
+        if let Some(msg) = self.buffered.pop_front() {
+            return Ok(Some(msg));
+        }
+        loop {
+            let Some(msg) = self.read_message_opt().await? else {
+                return Ok(None);
+            };
+            if let Some(msg) = into_server_message(msg, &mut self.pending_prompt)? {
+                return Ok(Some(msg));
+            }
+        }
+    }
+
+    /// Return an async stream over the current prompt's [`ServerMessage`]s.
+    ///
+    /// The stream ends after [`ServerMessage::PromptComplete`], or at EOF.
+    pub fn events(&mut self) -> EventStream<'_> {
+        EventStream {
+            client: self,
+            done: false,
+        }
+    }
+
+    /// `true` while a prompt is running.
+    pub fn is_prompting(&self) -> bool {
+        self.pending_prompt.is_some()
+    }
+
+    /// Get the process ID.
+    pub fn pid(&self) -> Option<u32> {
+        self.child.id()
+    }
+
+    /// Check if the child process is still running.
+    pub fn is_alive(&mut self) -> bool {
+        self.child.try_wait().ok().flatten().is_none()
+    }
+
+    /// Shut down the agent process.
+    ///
+    /// Consumes the client. If you don't call this explicitly, the
+    /// [`Drop`] implementation will kill the process automatically.
+    pub async fn shutdown(mut self) -> Result<()> {
+        debug!("[CLIENT] Shutting down");
+        self.child.kill().await.map_err(Error::Io)?;
+        Ok(())
+    }
+
+    // -- internal --
+
+    async fn send_request<P: Serialize>(&mut self, method: &str, params: &P) -> Result<RequestId> {
+        let id = RequestId::Integer(self.next_id);
+        self.next_id += 1;
+        let req = JsonRpcRequest {
+            jsonrpc: JsonRpcVersion,
+            id: id.clone(),
+            method: method.to_string(),
+            params: Some(serde_json::to_value(params).map_err(Error::Json)?),
+        };
+        self.send_raw(&req).await?;
+        Ok(id)
+    }
+
+    async fn send_raw<T: Serialize>(&mut self, msg: &T) -> Result<()> {
+        let json = serde_json::to_string(msg).map_err(Error::Json)?;
+        debug!("[CLIENT] Sending: {}", json);
+        self.writer
+            .write_all(json.as_bytes())
+            .await
+            .map_err(Error::Io)?;
+        self.writer.write_all(b"\n").await.map_err(Error::Io)?;
+        self.writer.flush().await.map_err(Error::Io)?;
+        Ok(())
+    }
+
+    async fn read_message(&mut self) -> Result<JsonRpcMessage> {
+        self.read_message_opt().await?.ok_or(Error::ServerClosed)
+    }
+
+    async fn read_message_opt(&mut self) -> Result<Option<JsonRpcMessage>> {
+        loop {
+            let Some(frame) = self.frames.read_frame_async(&mut self.reader).await? else {
+                debug!("[CLIENT] Stream closed (EOF)");
+                if self.pending_prompt.take().is_some() {
+                    warn!("[CLIENT] Agent exited while a prompt was running");
+                }
+                return Ok(None);
+            };
+            if let Some(parsed) = parse_line(frame) {
+                return parsed.map(Some);
+            }
+        }
+    }
+}
+
+impl Drop for AsyncClient {
+    fn drop(&mut self) {
+        if self.is_alive() {
+            if let Err(e) = self.child.start_kill() {
+                error!("Failed to kill opencode process on drop: {}", e);
+            }
+        }
+    }
+}
+
+/// Async stream of one prompt's [`ServerMessage`]s from an [`AsyncClient`].
+pub struct EventStream<'a> {
+    client: &'a mut AsyncClient,
+    done: bool,
+}
+
+impl EventStream<'_> {
+    /// Get the next message; `None` after [`ServerMessage::PromptComplete`] or EOF.
+    pub async fn next(&mut self) -> Option<Result<ServerMessage>> {
+        if self.done {
+            return None;
+        }
+        match self.client.next_message().await {
+            Ok(Some(msg)) => {
+                self.done = matches!(msg, ServerMessage::PromptComplete(_));
+                Some(Ok(msg))
+            }
+            Ok(None) => None,
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+
+    /// Collect the remaining messages of the prompt.
+    pub async fn collect(mut self) -> Result<Vec<ServerMessage>> {
+        let mut msgs = Vec::new();
+        while let Some(result) = self.next().await {
+            msgs.push(result?);
+        }
+        Ok(msgs)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::messages::ServerRequest;
+    use crate::test_support::fake_agent;
+
+    #[tokio::test]
+    async fn test_prompt_with_permission_round_trip() {
+        let agent = fake_agent("async");
+        let mut client = AsyncClient::start_with(AcpBuilder::new().command(&agent))
+            .await
+            .unwrap();
+        let session = client
+            .new_session(&NewSessionParams::new("/tmp"))
+            .await
+            .unwrap();
+
+        client
+            .prompt(&PromptParams::text(&session.session_id, "use a tool"))
+            .await
+            .unwrap();
+        let mut text = String::new();
+        while let Some(msg) = client.next_message().await.unwrap() {
+            if let Some(t) = msg.session_update().and_then(|n| n.update.agent_text()) {
+                text.push_str(t);
+            }
+            match msg {
+                ServerMessage::Request {
+                    id,
+                    request: ServerRequest::RequestPermission(p),
+                } => client.respond(id, &p.reject()).await.unwrap(),
+                ServerMessage::PromptComplete(_) => break,
+                _ => {}
+            }
+        }
+        assert_eq!(text, "hello rejected");
+
+        client
+            .prompt(&PromptParams::text(&session.session_id, "again"))
+            .await
+            .unwrap();
+        let msgs = client.events().collect().await.unwrap();
+        assert_eq!(msgs.len(), 2);
+        client.shutdown().await.unwrap();
+        std::fs::remove_file(agent).ok();
+    }
+}
@@ -0,0 +1,133 @@
+//! Wire-to-typed conversion shared by the sync and async clients.
+
+use crate::error::{Error, ParseError, Result};
+use crate::jsonrpc::{JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, RequestId};
+use crate::messages::{Notification, ServerMessage, ServerRequest};
+use crate::protocol::methods;
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Decode a response `result`, treating `null` as an empty object so
+/// all-optional response structs decode from ACP's bare `null` results.
+pub(crate) fn decode_result<R: DeserializeOwned>(result: Value) -> Result<R> {
+    let result = match result {
+        Value::Null => Value::Object(Default::default()),
+        other => other,
+    };
+    serde_json::from_value(result).map_err(Error::Json)
+}
+
+/// Parse one line of agent stdout as a JSON-RPC message. Blank lines
+/// yield `None`.
+pub(crate) fn parse_line(line: &str) -> Option<Result<JsonRpcMessage>> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+    debug!("[CLIENT] Received: {}", trimmed);
+    Some(
+        codes_codec::Flavor::JsonRpc
+            .decode::<JsonRpcMessage>(trimmed)
+            .map_err(|e| {
+                warn!(
+                    "[CLIENT] Failed to deserialize message. \
+                 Please report this at https://github.com/meawoppl/rust-code-agent-sdks/issues"
+                );
+                warn!("[CLIENT] Parse error: {}", e);
+                warn!("[CLIENT] Raw: {}", trimmed);
+                Error::Deserialization(ParseError::from_line(trimmed, e))
+            }),
+    )
+}
+
+/// Lift an incoming message that isn't the response to a pending
+/// non-prompt request into a [`ServerMessage`].
+///
+/// The response to the in-flight `session/prompt` (whose id is taken out of
+/// `pending_prompt`) becomes [`ServerMessage::PromptComplete`]; an error for
+/// it becomes [`Error::JsonRpc`]. Stray responses are logged and yield
+/// `Ok(None)`.
+pub(crate) fn into_server_message(
+    msg: JsonRpcMessage,
+    pending_prompt: &mut Option<RequestId>,
+) -> Result<Option<ServerMessage>> {
+    match msg {
+        JsonRpcMessage::Notification(JsonRpcNotification { method, params, .. }) => {
+            let typed = Notification::from_envelope(&method, params.clone()).map_err(|e| {
+                Error::Deserialization(ParseError::from_envelope(method, params, e))
+            })?;
+            Ok(Some(ServerMessage::Notification(typed)))
+        }
+        JsonRpcMessage::Request(JsonRpcRequest {
+            id, method, params, ..
+        }) => {
+            let typed = ServerRequest::from_envelope(&method, params.clone()).map_err(|e| {
+                Error::Deserialization(ParseError::from_envelope(method, params, e))
+            })?;
+            Ok(Some(ServerMessage::Request { id, request: typed }))
+        }
+        JsonRpcMessage::Response(resp) if pending_prompt.as_ref() == Some(&resp.id) => {
+            *pending_prompt = None;
+            let result = resp.result;
+            serde_json::from_value(result.clone())
+                .map(|r| Some(ServerMessage::PromptComplete(r)))
+                .map_err(|e| {
+                    Error::Deserialization(ParseError::from_envelope(
+                        methods::SESSION_PROMPT,
+                        Some(result),
+                        e,
+                    ))
+                })
+        }
+        JsonRpcMessage::Error(err) if pending_prompt.as_ref() == Some(&err.id) => {
+            *pending_prompt = None;
+            Err(Error::JsonRpc {
+                code: err.error.code,
+                message: err.error.message,
+            })
+        }
+        JsonRpcMessage::Response(resp) => {
+            warn!(
+                "[CLIENT] Unexpected response (no pending request): id={}",
+                resp.id
+            );
+            Ok(None)
+        }
+        JsonRpcMessage::Error(err) => {
+            warn!(
+                "[CLIENT] Unexpected error (no pending request): id={} code={}",
+                err.id, err.error.code
+            );
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> JsonRpcMessage {
+        parse_line(line).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_prompt_response_completes_turn() {
+        let mut pending = Some(RequestId::Integer(3));
+        let msg = parse(r#"{"jsonrpc":"2.0","id":3,"result":{"stopReason":"cancelled"}}"#);
+        let out = into_server_message(msg, &mut pending).unwrap();
+        assert!(matches!(out, Some(ServerMessage::PromptComplete(_))));
+        assert!(pending.is_none());
+
+        let msg = parse(r#"{"jsonrpc":"2.0","id":3,"result":{"stopReason":"end_turn"}}"#);
+        assert!(into_server_message(msg, &mut pending).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_null_result_decodes_as_empty_struct() {
+        let resp: crate::protocol::SetSessionModeResponse = decode_result(Value::Null).unwrap();
+        let _ = resp;
+        assert!(parse_line("   \n").is_none());
+    }
+}
@@ -0,0 +1,198 @@
+//! JSON-RPC message types for the Agent Client Protocol.
+//!
+//! Unlike the Codex app-server, ACP agents follow JSON-RPC 2.0 to the letter:
+//! every message carries `"jsonrpc": "2.0"`. Outgoing messages always include
+//! it; incoming messages are accepted with or without it.
+//!
+//! # Wire format
+//!
+//! Messages are newline-delimited JSON objects. Each message is one of:
+//! - **Request** — has `id` + `method` (+ optional `params`)
+//! - **Error** — has `id` + `error` (with `code`, `message`, optional `data`)
+//! - **Response** — has `id` + `result` (which may be `null` or omitted)
+//! - **Notification** — has `method` (+ optional `params`), no `id`
+//!
+//! Use [`JsonRpcMessage`] to deserialize any incoming line, then match on the variant.
+//!
+//! # Example
+//!
+//! ```
+//! use opencode_codes::JsonRpcMessage;
+//!
+//! let line = r#"{"jsonrpc":"2.0","id":2,"result":{"sessionId":"ses_abc"}}"#;
+//! let msg: JsonRpcMessage = serde_json::from_str(line).unwrap();
+//! assert!(matches!(msg, JsonRpcMessage::Response(_)));
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// The `"jsonrpc": "2.0"` marker.
+///
+/// Always serializes as `"2.0"`. Deserialization accepts any string so a
+/// slightly off agent doesn't break the connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonRpcVersion;
+
+impl Serialize for JsonRpcVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str("2.0")
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonRpcVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?;
+        Ok(JsonRpcVersion)
+    }
+}
+
+/// A JSON-RPC request/response identifier.
+///
+/// Can be either a string or an integer. The client uses integer IDs; the
+/// agent may use either form.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequestId {
+    String(String),
+    Integer(i64),
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestId::String(s) => write!(f, "{}", s),
+            RequestId::Integer(i) => write!(f, "{}", i),
+        }
+    }
+}
+
+/// A JSON-RPC request (client-to-agent or agent-to-client).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+    #[serde(default)]
+    pub jsonrpc: JsonRpcVersion,
+    pub id: RequestId,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+/// A JSON-RPC notification (no response expected).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcNotification {
+    #[serde(default)]
+    pub jsonrpc: JsonRpcVersion,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+/// A JSON-RPC success response.
+///
+/// ACP methods with nothing to return (e.g. `session/set_mode`) answer with
+/// `"result": null`, and some agents omit `result` entirely; both become
+/// [`Value::Null`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+    #[serde(default)]
+    pub jsonrpc: JsonRpcVersion,
+    pub id: RequestId,
+    #[serde(default)]
+    pub result: Value,
+}
+
+/// The error payload within a JSON-RPC error response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcErrorData {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+/// A JSON-RPC error response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRpcError {
+    #[serde(default)]
+    pub jsonrpc: JsonRpcVersion,
+    pub id: RequestId,
+    pub error: JsonRpcErrorData,
+}
+
+/// Any JSON-RPC message on the wire.
+///
+/// Deserialized via untagged serde — the presence of `id`, `method`, `result`,
+/// or `error` fields determines which variant is matched.
+///
+/// Variant ordering matters for untagged deserialization:
+/// - Request has both `id` and `method`
+/// - Error has `id` and `error`
+/// - Response has `id` (and usually `result`); it must come after Error
+///   because `result` is optional
+/// - Notification has only `method` (no `id`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum JsonRpcMessage {
+    Request(JsonRpcRequest),
+    Error(JsonRpcError),
+    Response(JsonRpcResponse),
+    Notification(JsonRpcNotification),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outgoing_messages_carry_version() {
+        let req = JsonRpcRequest {
+            jsonrpc: JsonRpcVersion,
+            id: RequestId::Integer(1),
+            method: "initialize".to_string(),
+            params: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&req).unwrap(),
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize"}"#
+        );
+    }
+
+    #[test]
+    fn test_message_dispatch() {
+        let cases = [
+            (
+                r#"{"jsonrpc":"2.0","id":0,"method":"session/request_permission","params":{}}"#,
+                "request",
+            ),
+            (r#"{"jsonrpc":"2.0","id":1,"result":null}"#, "response"),
+            (r#"{"jsonrpc":"2.0","id":1}"#, "response"),
+            (
+                r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32603,"message":"boom"}}"#,
+                "error",
+            ),
+            (
+                r#"{"jsonrpc":"2.0","method":"session/update","params":{}}"#,
+                "notification",
+            ),
+            (r#"{"method":"session/update"}"#, "notification"),
+        ];
+        for (line, expected) in cases {
+            let kind = match serde_json::from_str::<JsonRpcMessage>(line).unwrap() {
+                JsonRpcMessage::Request(_) => "request",
+                JsonRpcMessage::Error(_) => "error",
+                JsonRpcMessage::Response(_) => "response",
+                JsonRpcMessage::Notification(_) => "notification",
+            };
+            assert_eq!(kind, expected, "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_request_id_forms() {
+        let id: RequestId = serde_json::from_str(r#""req_1""#).unwrap();
+        assert_eq!(id.to_string(), "req_1");
+        let id: RequestId = serde_json::from_str("42").unwrap();
+        assert_eq!(id, RequestId::Integer(42));
+    }
+}
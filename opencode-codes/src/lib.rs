@@ -0,0 +1,138 @@
+//! A typed Rust interface for the [OpenCode](https://github.com/sst/opencode) agent.
+//!
+//! This crate provides type-safe bindings for `opencode acp`, which speaks the
+//! [Agent Client Protocol](https://agentclientprotocol.com) (ACP) over stdio:
+//! newline-delimited JSON-RPC 2.0 carrying sessions, prompts, streamed
+//! message and tool call updates, and permission prompts.
+//!
+//! # Quick Start
+//!
+//! ```bash
+//! cargo add opencode-codes
+//! ```
+//!
+//! See `examples/async_client.rs` and `examples/sync_client.rs` for runnable
+//! versions of the usage patterns below. The high-level shape:
+//!
+//! ```ignore
+//! let mut client = AsyncClient::start().await?;
+//! let session = client.new_session(&NewSessionParams::new(cwd)).await?;
+//! client.prompt(&PromptParams::text(&session.session_id, "What is 2 + 2?")).await?;
+//! while let Some(msg) = client.next_message().await? {
+//!     // stream updates, answer permission requests, stop at PromptComplete
+//! }
+//! ```
+//!
+//! # Architecture
+//!
+//! The crate follows the layout of `codex-codes`:
+//!
+//! - [`client_async`] / [`client_sync`] — High-level clients that manage the
+//!   agent process, request/response correlation, and message buffering
+//! - [`protocol`] — ACP request params and response types (`initialize`,
+//!   `session/new`, `session/prompt`, ...) and [`ContentBlock`]
+//! - [`updates`] — `session/update` payloads: message chunks, tool calls, plans
+//! - [`messages`] — Typed dispatch of agent notifications and requests
+//! - [`jsonrpc`] — Low-level JSON-RPC 2.0 message types
+//! - [`cli`] — Builder for spawning `opencode acp`
+//! - [`error`] — Error types and result aliases
+//! - [`version`] — Version compatibility checking against the installed CLI
+//!
+//! # Protocol Overview
+//!
+//! 1. **Initialize** — `initialize` negotiates the protocol version and
+//!    capabilities (handled automatically by `start()`)
+//! 2. **Create a session** — `session/new` with an absolute working directory
+//! 3. **Prompt** — `session/prompt` sends user content; the agent streams
+//!    `session/update` notifications while it works
+//! 4. **Permissions** — the agent may send `session/request_permission`,
+//!    which must be answered before it continues
+//! 5. **Turn ends** — the `session/prompt` response arrives with a
+//!    [`StopReason`], surfaced as [`ServerMessage::PromptComplete`]
+//!
+//! `session/cancel` stops a running prompt; it still ends with a
+//! `PromptComplete` whose stop reason is `cancelled`.
+//!
+//! # Feature Flags
+//!
+//! | Feature | Description | WASM-compatible |
+//! |---------|-------------|-----------------|
+//! | `types` | Core message types only | Yes |
+//! | `sync-client` | Synchronous client with blocking I/O | No |
+//! | `async-client` | Asynchronous client using tokio | No |
+//!
+//! All features are enabled by default. For WASM or type-sharing use cases:
+//!
+//! ```toml
+//! [dependencies]
+//! opencode-codes = { version = "0.1", default-features = false, features = ["types"] }
+//! ```
+
+pub mod jsonrpc;
+pub mod messages;
+pub mod protocol;
+pub mod updates;
+
+pub mod error;
+
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+pub mod cli;
+
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+pub mod version;
+
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+mod dispatch;
+
+#[cfg(all(test, unix, any(feature = "sync-client", feature = "async-client")))]
+mod test_support;
+
+#[cfg(feature = "sync-client")]
+pub mod client_sync;
+
+#[cfg(feature = "async-client")]
+pub mod client_async;
+
+// JSON-RPC types
+pub use jsonrpc::{
+    JsonRpcError, JsonRpcErrorData, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest,
+    JsonRpcResponse, JsonRpcVersion, RequestId,
+};
+
+// ACP request / response types
+pub use protocol::{
+    AgentCapabilities, AuthMethod, AuthenticateParams, AuthenticateResponse, CancelNotification,
+    ClientCapabilities, ContentBlock, EnvVariable, FileSystemCapability, Implementation,
+    InitializeParams, InitializeResponse, LoadSessionParams, LoadSessionResponse, McpCapabilities,
+    McpServer, NewSessionParams, NewSessionResponse, PromptCapabilities, PromptParams,
+    PromptResponse, SessionMode, SessionModeState, SetSessionModeParams, SetSessionModeResponse,
+    StopReason, PROTOCOL_VERSION,
+};
+
+// session/update payloads
+pub use updates::{
+    AvailableCommand, PlanEntry, SessionNotification, SessionUpdate, ToolCall, ToolCallContent,
+    ToolCallLocation, ToolCallStatus, ToolCallUpdate, ToolKind,
+};
+
+// Typed dispatch
+pub use messages::{
+    Notification, PermissionOption, PermissionOptionKind, ReadTextFileParams, ReadTextFileResponse,
+    RequestPermissionOutcome, RequestPermissionParams, RequestPermissionResponse, ServerMessage,
+    ServerRequest, WriteTextFileParams, WriteTextFileResponse,
+};
+
+// Error types (always available)
+pub use error::{Error, ParseError, Result};
+
+// CLI builder (feature-gated)
+#[cfg(any(feature = "sync-client", feature = "async-client"))]
+pub use cli::AcpBuilder;
+
+// Sync client
+#[cfg(feature = "sync-client")]
+pub use client_sync::{EventIterator, SyncClient};
+
+// Async client
+#[cfg(feature = "async-client")]
+pub use client_async::{AsyncClient, EventStream};
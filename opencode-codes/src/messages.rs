@@ -0,0 +1,369 @@
+//! Typed dispatch for agent notifications and agent-to-client requests.
+//!
+//! Mirrors `codex_codes::messages`: the loose JSON-RPC envelope is lifted into
+//! closed enums — [`Notification`] for agent notifications and
+//! [`ServerRequest`] for requests the agent sends to the client (permission
+//! prompts and file access). Unknown methods route to an `Unknown` variant
+//! with the raw params preserved; known methods whose params fail to decode
+//! are an error.
+//!
+//! [`ServerMessage`] is what the clients yield while a prompt runs. Because
+//! ACP answers `session/prompt` only once the turn is over, the prompt's
+//! response arrives in the same stream as
+//! [`ServerMessage::PromptComplete`].
+
+use crate::jsonrpc::RequestId;
+use crate::protocol::{methods, PromptResponse};
+use crate::updates::{SessionNotification, ToolCallUpdate};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// An agent-to-client notification.
+#[derive(Debug, Clone)]
+pub enum Notification {
+    /// `session/update`
+    SessionUpdate(SessionNotification),
+    /// A method this crate version does not yet model.
+    Unknown {
+        method: String,
+        params: Option<Value>,
+    },
+}
+
+impl Notification {
+    /// Return the wire `method` string for this notification.
+    pub fn method(&self) -> &str {
+        match self {
+            Self::SessionUpdate(_) => methods::SESSION_UPDATE,
+            Self::Unknown { method, .. } => method,
+        }
+    }
+
+    /// Construct a [`Notification`] from a `method` + `params` envelope.
+    pub fn from_envelope(method: &str, params: Option<Value>) -> Result<Self, serde_json::Error> {
+        match method {
+            methods::SESSION_UPDATE => {
+                serde_json::from_value(params.unwrap_or(Value::Null)).map(Self::SessionUpdate)
+            }
+            _ => Ok(Self::Unknown {
+                method: method.to_string(),
+                params,
+            }),
+        }
+    }
+}
+
+/// An agent-to-client request that expects a response.
+#[derive(Debug, Clone)]
+pub enum ServerRequest {
+    /// `session/request_permission` — answer with a [`RequestPermissionResponse`].
+    RequestPermission(RequestPermissionParams),
+    /// `fs/read_text_file` — answer with a [`ReadTextFileResponse`]. Only sent
+    /// if the client advertised the capability.
+    ReadTextFile(ReadTextFileParams),
+    /// `fs/write_text_file` — answer with a [`WriteTextFileResponse`]. Only
+    /// sent if the client advertised the capability.
+    WriteTextFile(WriteTextFileParams),
+    /// A method this crate version does not yet model (e.g. `terminal/*`).
+    Unknown {
+        method: String,
+        params: Option<Value>,
+    },
+}
+
+impl ServerRequest {
+    /// Return the wire `method` string for this request.
+    pub fn method(&self) -> &str {
+        match self {
+            Self::RequestPermission(_) => methods::SESSION_REQUEST_PERMISSION,
+            Self::ReadTextFile(_) => methods::FS_READ_TEXT_FILE,
+            Self::WriteTextFile(_) => methods::FS_WRITE_TEXT_FILE,
+            Self::Unknown { method, .. } => method,
+        }
+    }
+
+    /// Construct a [`ServerRequest`] from a `method` + `params` envelope.
+    pub fn from_envelope(method: &str, params: Option<Value>) -> Result<Self, serde_json::Error> {
+        let params_value = params.clone().unwrap_or(Value::Null);
+        match method {
+            methods::SESSION_REQUEST_PERMISSION => {
+                serde_json::from_value(params_value).map(Self::RequestPermission)
+            }
+            methods::FS_READ_TEXT_FILE => {
+                serde_json::from_value(params_value).map(Self::ReadTextFile)
+            }
+            methods::FS_WRITE_TEXT_FILE => {
+                serde_json::from_value(params_value).map(Self::WriteTextFile)
+            }
+            _ => Ok(Self::Unknown {
+                method: method.to_string(),
+                params,
+            }),
+        }
+    }
+}
+
+/// Anything the agent sends that the caller needs to see.
+#[derive(Debug, Clone)]
+pub enum ServerMessage {
+    /// A notification, usually a `session/update`.
+    Notification(Notification),
+    /// A request the caller must answer with `respond` or `respond_error`.
+    Request {
+        id: RequestId,
+        request: ServerRequest,
+    },
+    /// The response to the in-flight `session/prompt`; the turn is over.
+    PromptComplete(PromptResponse),
+}
+
+impl ServerMessage {
+    /// The session update carried by this message, if any.
+    pub fn session_update(&self) -> Option<&SessionNotification> {
+        match self {
+            Self::Notification(Notification::SessionUpdate(n)) => Some(n),
+            _ => None,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// session/request_permission
+// ---------------------------------------------------------------------------
+
+/// What choosing a [`PermissionOption`] means.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PermissionOptionKind {
+    AllowOnce,
+    AllowAlways,
+    RejectOnce,
+    RejectAlways,
+    /// A kind not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl PermissionOptionKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::AllowOnce => "allow_once",
+            Self::AllowAlways => "allow_always",
+            Self::RejectOnce => "reject_once",
+            Self::RejectAlways => "reject_always",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for PermissionOptionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for PermissionOptionKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "allow_once" => Self::AllowOnce,
+            "allow_always" => Self::AllowAlways,
+            "reject_once" => Self::RejectOnce,
+            "reject_always" => Self::RejectAlways,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for PermissionOptionKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for PermissionOptionKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// One choice offered in a permission prompt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionOption {
+    pub option_id: String,
+    pub name: String,
+    pub kind: PermissionOptionKind,
+}
+
+/// Parameters of `session/request_permission`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestPermissionParams {
+    pub session_id: String,
+    /// The tool call awaiting permission.
+    pub tool_call: ToolCallUpdate,
+    pub options: Vec<PermissionOption>,
+}
+
+impl RequestPermissionParams {
+    /// The first offered option of the given kind.
+    pub fn option(&self, kind: &PermissionOptionKind) -> Option<&PermissionOption> {
+        self.options.iter().find(|o| &o.kind == kind)
+    }
+
+    /// Allow this call once, falling back to "always" if that's all that's
+    /// offered. Cancels if no allow option exists.
+    pub fn allow(&self) -> RequestPermissionResponse {
+        self.pick(&[
+            PermissionOptionKind::AllowOnce,
+            PermissionOptionKind::AllowAlways,
+        ])
+    }
+
+    /// Reject this call once, falling back to "always". Cancels if no reject
+    /// option exists.
+    pub fn reject(&self) -> RequestPermissionResponse {
+        self.pick(&[
+            PermissionOptionKind::RejectOnce,
+            PermissionOptionKind::RejectAlways,
+        ])
+    }
+
+    fn pick(&self, kinds: &[PermissionOptionKind]) -> RequestPermissionResponse {
+        kinds
+            .iter()
+            .find_map(|kind| self.option(kind))
+            .map(|o| RequestPermissionResponse::selected(o.option_id.clone()))
+            .unwrap_or_else(RequestPermissionResponse::cancelled)
+    }
+}
+
+/// The client's answer to a permission prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "lowercase")]
+pub enum RequestPermissionOutcome {
+    /// The prompt was dismissed, e.g. because the turn is being cancelled.
+    Cancelled,
+    /// The user picked one of the offered options.
+    Selected {
+        #[serde(rename = "optionId")]
+        option_id: String,
+    },
+}
+
+/// Result payload for `session/request_permission`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestPermissionResponse {
+    pub outcome: RequestPermissionOutcome,
+}
+
+impl RequestPermissionResponse {
+    /// Pick the option with this id.
+    pub fn selected(option_id: impl Into<String>) -> Self {
+        Self {
+            outcome: RequestPermissionOutcome::Selected {
+                option_id: option_id.into(),
+            },
+        }
+    }
+
+    /// Dismiss the prompt.
+    pub fn cancelled() -> Self {
+        Self {
+            outcome: RequestPermissionOutcome::Cancelled,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// fs/*
+// ---------------------------------------------------------------------------
+
+/// Parameters of `fs/read_text_file`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadTextFileParams {
+    pub session_id: String,
+    pub path: String,
+    /// 1-based line to start reading from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    /// Maximum number of lines to read.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+}
+
+/// Result payload for `fs/read_text_file`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadTextFileResponse {
+    pub content: String,
+}
+
+/// Parameters of `fs/write_text_file`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteTextFileParams {
+    pub session_id: String,
+    pub path: String,
+    pub content: String,
+}
+
+/// Result payload for `fs/write_text_file`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WriteTextFileResponse {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn permission_params(kinds: &[&str]) -> RequestPermissionParams {
+        let options: Vec<Value> = kinds
+            .iter()
+            .map(|k| json!({"optionId": format!("opt_{}", k), "name": k, "kind": k}))
+            .collect();
+        serde_json::from_value(json!({
+            "sessionId": "ses_1",
+            "toolCall": {"toolCallId": "call_1", "title": "edit a.rs", "kind": "edit"},
+            "options": options
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_request_dispatch() {
+        let req = ServerRequest::from_envelope(
+            methods::SESSION_REQUEST_PERMISSION,
+            Some(serde_json::to_value(permission_params(&["allow_once"])).unwrap()),
+        )
+        .unwrap();
+        assert_eq!(req.method(), "session/request_permission");
+
+        let req = ServerRequest::from_envelope("terminal/create", Some(json!({}))).unwrap();
+        assert!(
+            matches!(req, ServerRequest::Unknown { ref method, .. } if method == "terminal/create")
+        );
+
+        assert!(ServerRequest::from_envelope(methods::FS_READ_TEXT_FILE, Some(json!({}))).is_err());
+    }
+
+    #[test]
+    fn test_permission_helpers() {
+        let params = permission_params(&["allow_always", "reject_once"]);
+        assert_eq!(
+            params.allow(),
+            RequestPermissionResponse::selected("opt_allow_always")
+        );
+        assert_eq!(
+            serde_json::to_value(params.reject()).unwrap(),
+            json!({"outcome": {"outcome": "selected", "optionId": "opt_reject_once"}})
+        );
+
+        let params = permission_params(&["reject_once"]);
+        assert_eq!(params.allow(), RequestPermissionResponse::cancelled());
+        assert_eq!(
+            serde_json::to_value(RequestPermissionResponse::cancelled()).unwrap(),
+            json!({"outcome": {"outcome": "cancelled"}})
+        );
+    }
+}
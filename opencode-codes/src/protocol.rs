@@ -0,0 +1,532 @@
+//! Agent Client Protocol request and response types.
+//!
+//! `opencode acp` speaks the [Agent Client Protocol](https://agentclientprotocol.com)
+//! (ACP): the client drives sessions with `initialize`, `session/new` and
+//! `session/prompt`, while the agent streams progress back as
+//! `session/update` notifications (see [`crate::updates`]) and asks the
+//! client for permission via `session/request_permission` (see
+//! [`crate::messages`]).
+//!
+//! Field names are camelCase on the wire.
+//!
+//! # Example
+//!
+//! ```
+//! use opencode_codes::{ContentBlock, PromptParams};
+//!
+//! let params = PromptParams::text("ses_1", "What is 2 + 2?");
+//! let json = serde_json::to_value(&params).unwrap();
+//! assert_eq!(json["prompt"][0]["type"], "text");
+//! assert!(matches!(&params.prompt[0], ContentBlock::Text { text } if text == "What is 2 + 2?"));
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// The ACP protocol version this crate implements.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// JSON-RPC method names used by the Agent Client Protocol.
+pub mod methods {
+    // Client → agent requests
+    pub const INITIALIZE: &str = "initialize";
+    pub const AUTHENTICATE: &str = "authenticate";
+    pub const SESSION_NEW: &str = "session/new";
+    pub const SESSION_LOAD: &str = "session/load";
+    pub const SESSION_PROMPT: &str = "session/prompt";
+    pub const SESSION_SET_MODE: &str = "session/set_mode";
+
+    // Client → agent notifications
+    pub const SESSION_CANCEL: &str = "session/cancel";
+
+    // Agent → client notifications
+    pub const SESSION_UPDATE: &str = "session/update";
+
+    // Agent → client requests
+    pub const SESSION_REQUEST_PERMISSION: &str = "session/request_permission";
+    pub const FS_READ_TEXT_FILE: &str = "fs/read_text_file";
+    pub const FS_WRITE_TEXT_FILE: &str = "fs/write_text_file";
+}
+
+// ---------------------------------------------------------------------------
+// initialize / authenticate
+// ---------------------------------------------------------------------------
+
+/// Name and version of a client or agent implementation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Implementation {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// File-system operations the client can perform on the agent's behalf.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSystemCapability {
+    #[serde(default)]
+    pub read_text_file: bool,
+    #[serde(default)]
+    pub write_text_file: bool,
+}
+
+/// Capabilities the client advertises in `initialize`.
+///
+/// The default advertises nothing, so the agent uses its own file system and
+/// terminal and never sends `fs/*` requests.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientCapabilities {
+    #[serde(default)]
+    pub fs: FileSystemCapability,
+    #[serde(default)]
+    pub terminal: bool,
+}
+
+/// Parameters for `initialize`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeParams {
+    pub protocol_version: u16,
+    #[serde(default)]
+    pub client_capabilities: ClientCapabilities,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_info: Option<Implementation>,
+}
+
+impl Default for InitializeParams {
+    fn default() -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            client_capabilities: ClientCapabilities::default(),
+            client_info: Some(Implementation {
+                name: "opencode-codes".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                title: None,
+            }),
+        }
+    }
+}
+
+/// Content types the agent accepts in `session/prompt` beyond text.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptCapabilities {
+    #[serde(default)]
+    pub image: bool,
+    #[serde(default)]
+    pub audio: bool,
+    #[serde(default)]
+    pub embedded_context: bool,
+}
+
+/// MCP transports the agent can connect to besides stdio.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpCapabilities {
+    #[serde(default)]
+    pub http: bool,
+    #[serde(default)]
+    pub sse: bool,
+}
+
+/// Capabilities the agent reports in its `initialize` response.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentCapabilities {
+    /// Whether `session/load` is supported.
+    #[serde(default)]
+    pub load_session: bool,
+    #[serde(default)]
+    pub prompt_capabilities: PromptCapabilities,
+    #[serde(default)]
+    pub mcp_capabilities: McpCapabilities,
+}
+
+/// An authentication method offered by the agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthMethod {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Response to `initialize`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeResponse {
+    pub protocol_version: u16,
+    #[serde(default)]
+    pub agent_capabilities: AgentCapabilities,
+    #[serde(default)]
+    pub auth_methods: Vec<AuthMethod>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_info: Option<Implementation>,
+}
+
+/// Parameters for `authenticate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticateParams {
+    /// One of the ids from [`InitializeResponse::auth_methods`].
+    pub method_id: String,
+}
+
+/// Response to `authenticate`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuthenticateResponse {}
+
+// ---------------------------------------------------------------------------
+// Sessions
+// ---------------------------------------------------------------------------
+
+/// An environment variable passed to a stdio MCP server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvVariable {
+    pub name: String,
+    pub value: String,
+}
+
+/// A stdio MCP server the agent should connect to for this session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServer {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: Vec<EnvVariable>,
+}
+
+/// A mode the agent can operate in (e.g. OpenCode's `build` and `plan` agents).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMode {
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// The current mode and the modes available in a session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionModeState {
+    pub current_mode_id: String,
+    #[serde(default)]
+    pub available_modes: Vec<SessionMode>,
+}
+
+/// Parameters for `session/new`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSessionParams {
+    /// Absolute working directory for the session.
+    pub cwd: String,
+    #[serde(default)]
+    pub mcp_servers: Vec<McpServer>,
+}
+
+impl NewSessionParams {
+    /// A session rooted at `cwd` with no extra MCP servers.
+    pub fn new(cwd: impl Into<String>) -> Self {
+        Self {
+            cwd: cwd.into(),
+            mcp_servers: Vec::new(),
+        }
+    }
+}
+
+/// Response to `session/new`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewSessionResponse {
+    pub session_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modes: Option<SessionModeState>,
+}
+
+/// Parameters for `session/load`.
+///
+/// The agent replays the session's history as `session/update`
+/// notifications before responding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadSessionParams {
+    pub session_id: String,
+    pub cwd: String,
+    #[serde(default)]
+    pub mcp_servers: Vec<McpServer>,
+}
+
+/// Response to `session/load`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadSessionResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modes: Option<SessionModeState>,
+}
+
+/// Parameters for `session/set_mode`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetSessionModeParams {
+    pub session_id: String,
+    pub mode_id: String,
+}
+
+/// Response to `session/set_mode`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetSessionModeResponse {}
+
+/// Parameters for the `session/cancel` notification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelNotification {
+    pub session_id: String,
+}
+
+// ---------------------------------------------------------------------------
+// Prompts
+// ---------------------------------------------------------------------------
+
+/// A piece of content in a prompt, a message chunk, or tool output.
+///
+/// Unrecognized `type`s deserialize to [`ContentBlock::Unknown`] with the raw
+/// JSON preserved.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentBlock {
+    /// Plain text.
+    Text { text: String },
+    /// Base64-encoded image data.
+    Image { data: String, mime_type: String },
+    /// Base64-encoded audio data.
+    Audio { data: String, mime_type: String },
+    /// A reference to a resource the agent can fetch itself.
+    ResourceLink { uri: String, name: String },
+    /// An embedded resource; the payload is kept as raw JSON.
+    Resource { resource: Value },
+    /// A content type not yet known to this version of the crate.
+    Unknown(Value),
+}
+
+impl ContentBlock {
+    /// Convenience constructor for a text block.
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { text: text.into() }
+    }
+
+    /// The text of a [`ContentBlock::Text`] block.
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text { text } => Some(text),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum KnownContentBlock {
+    Text {
+        text: String,
+    },
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    Audio {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    ResourceLink {
+        uri: String,
+        name: String,
+    },
+    Resource {
+        resource: Value,
+    },
+}
+
+impl Serialize for ContentBlock {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let known = match self.clone() {
+            Self::Text { text } => KnownContentBlock::Text { text },
+            Self::Image { data, mime_type } => KnownContentBlock::Image { data, mime_type },
+            Self::Audio { data, mime_type } => KnownContentBlock::Audio { data, mime_type },
+            Self::ResourceLink { uri, name } => KnownContentBlock::ResourceLink { uri, name },
+            Self::Resource { resource } => KnownContentBlock::Resource { resource },
+            Self::Unknown(value) => return value.serialize(serializer),
+        };
+        known.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ContentBlock {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let known = matches!(
+            value.get("type").and_then(Value::as_str),
+            Some("text" | "image" | "audio" | "resource_link" | "resource")
+        );
+        if !known {
+            return Ok(Self::Unknown(value));
+        }
+        let block = match serde_json::from_value(value).map_err(serde::de::Error::custom)? {
+            KnownContentBlock::Text { text } => Self::Text { text },
+            KnownContentBlock::Image { data, mime_type } => Self::Image { data, mime_type },
+            KnownContentBlock::Audio { data, mime_type } => Self::Audio { data, mime_type },
+            KnownContentBlock::ResourceLink { uri, name } => Self::ResourceLink { uri, name },
+            KnownContentBlock::Resource { resource } => Self::Resource { resource },
+        };
+        Ok(block)
+    }
+}
+
+/// Parameters for `session/prompt`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptParams {
+    pub session_id: String,
+    pub prompt: Vec<ContentBlock>,
+}
+
+impl PromptParams {
+    /// A prompt consisting of a single text block.
+    pub fn text(session_id: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+            prompt: vec![ContentBlock::text(text)],
+        }
+    }
+}
+
+/// Why the agent stopped working on a prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StopReason {
+    /// The turn finished normally.
+    EndTurn,
+    MaxTokens,
+    MaxTurnRequests,
+    Refusal,
+    /// The client sent `session/cancel`.
+    Cancelled,
+    /// A stop reason not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl StopReason {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::EndTurn => "end_turn",
+            Self::MaxTokens => "max_tokens",
+            Self::MaxTurnRequests => "max_turn_requests",
+            Self::Refusal => "refusal",
+            Self::Cancelled => "cancelled",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for StopReason {
+    fn from(s: &str) -> Self {
+        match s {
+            "end_turn" => Self::EndTurn,
+            "max_tokens" => Self::MaxTokens,
+            "max_turn_requests" => Self::MaxTurnRequests,
+            "refusal" => Self::Refusal,
+            "cancelled" => Self::Cancelled,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for StopReason {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for StopReason {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// Response to `session/prompt`, sent once the turn is over.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptResponse {
+    pub stop_reason: StopReason,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_initialize_params_default() {
+        let json = serde_json::to_value(InitializeParams::default()).unwrap();
+        assert_eq!(json["protocolVersion"], 1);
+        assert_eq!(json["clientCapabilities"]["fs"]["readTextFile"], false);
+        assert_eq!(json["clientInfo"]["name"], "opencode-codes");
+    }
+
+    #[test]
+    fn test_initialize_response_minimal() {
+        let resp: InitializeResponse = serde_json::from_value(json!({
+            "protocolVersion": 1,
+            "agentCapabilities": {"loadSession": true, "promptCapabilities": {"image": true}},
+            "authMethods": [{"id": "opencode-login", "name": "Login"}]
+        }))
+        .unwrap();
+        assert!(resp.agent_capabilities.load_session);
+        assert!(resp.agent_capabilities.prompt_capabilities.image);
+        assert_eq!(resp.auth_methods[0].id, "opencode-login");
+    }
+
+    #[test]
+    fn test_content_block_roundtrip_and_unknown() {
+        let image = ContentBlock::Image {
+            data: "aGk=".into(),
+            mime_type: "image/png".into(),
+        };
+        let json = serde_json::to_value(&image).unwrap();
+        assert_eq!(
+            json,
+            json!({"type": "image", "data": "aGk=", "mimeType": "image/png"})
+        );
+        assert_eq!(serde_json::from_value::<ContentBlock>(json).unwrap(), image);
+
+        let raw = json!({"type": "hologram", "frames": 3});
+        let block: ContentBlock = serde_json::from_value(raw.clone()).unwrap();
+        assert_eq!(block, ContentBlock::Unknown(raw.clone()));
+        assert_eq!(serde_json::to_value(&block).unwrap(), raw);
+    }
+
+    #[test]
+    fn test_stop_reason() {
+        let resp: PromptResponse =
+            serde_json::from_value(json!({"stopReason": "end_turn"})).unwrap();
+        assert_eq!(resp.stop_reason, StopReason::EndTurn);
+        assert_eq!(
+            StopReason::from("paused"),
+            StopReason::Unknown("paused".into())
+        );
+    }
+}
@@ -0,0 +1,45 @@
+//! Stand-in agent for client tests.
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+
+/// Write a script that mimics `opencode acp`.
+///
+/// It answers `initialize` and `session/new` (session `ses_1`). A
+/// `session/prompt` streams the chunk `hello`; if the prompt mentions `tool`
+/// it then asks for permission and streams ` allowed` or ` rejected`
+/// depending on the reply. Every prompt ends with `end_turn`.
+pub(crate) fn fake_agent(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("opencode-codes-{}-{}.sh", name, std::process::id()));
+    let script = r#"#!/bin/sh
+chunk() {
+  printf '{"jsonrpc":"2.0","method":"session/update","params":{"sessionId":"ses_1","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"%s"}}}}\n' "$1"
+}
+echo "INFO starting acp" >&2
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      echo '{"jsonrpc":"2.0","id":'"$id"',"result":{"protocolVersion":1,"agentCapabilities":{"loadSession":true}}}' ;;
+    *'"method":"session/new"'*)
+      echo '{"jsonrpc":"2.0","id":'"$id"',"result":{"sessionId":"ses_1"}}' ;;
+    *'"method":"session/prompt"'*)
+      chunk hello
+      case "$line" in
+        *tool*)
+          echo '{"jsonrpc":"2.0","id":"perm-1","method":"session/request_permission","params":{"sessionId":"ses_1","toolCall":{"toolCallId":"call_1","title":"bash"},"options":[{"optionId":"once","name":"Allow","kind":"allow_once"},{"optionId":"no","name":"Reject","kind":"reject_once"}]}}'
+          IFS= read -r answer
+          case "$answer" in
+            *'"optionId":"once"'*) chunk " allowed" ;;
+            *) chunk " rejected" ;;
+          esac ;;
+      esac
+      echo '{"jsonrpc":"2.0","id":'"$id"',"result":{"stopReason":"end_turn"}}' ;;
+  esac
+done
+"#;
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
@@ -0,0 +1,348 @@
+//! `session/update` notification payloads.
+//!
+//! While a prompt is running, the agent streams its progress as
+//! `session/update` notifications. Each carries a [`SessionUpdate`]
+//! discriminated by the `sessionUpdate` field: message and thought chunks,
+//! tool call lifecycle, plans, and mode changes.
+//!
+//! # Example
+//!
+//! ```
+//! use opencode_codes::{SessionNotification, SessionUpdate};
+//!
+//! let json = r#"{"sessionId":"ses_1","update":{"sessionUpdate":"agent_message_chunk","content":{"type":"text","text":"4"}}}"#;
+//! let notif: SessionNotification = serde_json::from_str(json).unwrap();
+//! assert_eq!(notif.update.agent_text(), Some("4"));
+//! ```
+
+use crate::protocol::ContentBlock;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::fmt;
+
+/// Parameters of a `session/update` notification.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionNotification {
+    pub session_id: String,
+    pub update: SessionUpdate,
+}
+
+/// One update to a session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "sessionUpdate", rename_all = "snake_case")]
+pub enum SessionUpdate {
+    /// A chunk of the user's message (sent when replaying a loaded session).
+    UserMessageChunk { content: ContentBlock },
+    /// A chunk of the agent's reply.
+    AgentMessageChunk { content: ContentBlock },
+    /// A chunk of the agent's reasoning.
+    AgentThoughtChunk { content: ContentBlock },
+    /// A new tool call.
+    ToolCall(ToolCall),
+    /// A change to an existing tool call.
+    ToolCallUpdate(ToolCallUpdate),
+    /// The agent's current plan, replacing any previous one.
+    Plan { entries: Vec<PlanEntry> },
+    /// Slash commands the agent currently accepts.
+    AvailableCommandsUpdate {
+        #[serde(rename = "availableCommands")]
+        available_commands: Vec<AvailableCommand>,
+    },
+    /// The session switched modes.
+    CurrentModeUpdate {
+        #[serde(rename = "currentModeId")]
+        current_mode_id: String,
+    },
+    /// An update kind not yet known to this version of the crate.
+    #[serde(other)]
+    Unknown,
+}
+
+impl SessionUpdate {
+    /// Text of an [`SessionUpdate::AgentMessageChunk`], if this is one.
+    pub fn agent_text(&self) -> Option<&str> {
+        match self {
+            Self::AgentMessageChunk { content } => content.as_text(),
+            _ => None,
+        }
+    }
+}
+
+/// A tool call reported by the agent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    pub tool_call_id: String,
+    /// Human-readable description, e.g. `"Read src/main.rs"`.
+    pub title: String,
+    #[serde(default)]
+    pub kind: ToolKind,
+    #[serde(default)]
+    pub status: ToolCallStatus,
+    #[serde(default)]
+    pub content: Vec<ToolCallContent>,
+    #[serde(default)]
+    pub locations: Vec<ToolCallLocation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_input: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_output: Option<Value>,
+}
+
+/// A partial update to a tool call; only the changed fields are present.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCallUpdate {
+    pub tool_call_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ToolKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ToolCallStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<Vec<ToolCallContent>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locations: Option<Vec<ToolCallLocation>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_input: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_output: Option<Value>,
+}
+
+/// Output attached to a tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolCallContent {
+    /// Regular content such as text output.
+    Content { content: ContentBlock },
+    /// A file modification.
+    Diff {
+        path: String,
+        #[serde(rename = "oldText", default, skip_serializing_if = "Option::is_none")]
+        old_text: Option<String>,
+        #[serde(rename = "newText")]
+        new_text: String,
+    },
+    /// A terminal created by the client.
+    Terminal {
+        #[serde(rename = "terminalId")]
+        terminal_id: String,
+    },
+    /// A content type not yet known to this version of the crate.
+    #[serde(other)]
+    Unknown,
+}
+
+/// A file location a tool call is working on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallLocation {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+}
+
+/// Category of a tool call, used by clients to pick icons and wording.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum ToolKind {
+    Read,
+    Edit,
+    Delete,
+    Move,
+    Search,
+    Execute,
+    Think,
+    Fetch,
+    SwitchMode,
+    #[default]
+    Other,
+    /// A kind not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl ToolKind {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Read => "read",
+            Self::Edit => "edit",
+            Self::Delete => "delete",
+            Self::Move => "move",
+            Self::Search => "search",
+            Self::Execute => "execute",
+            Self::Think => "think",
+            Self::Fetch => "fetch",
+            Self::SwitchMode => "switch_mode",
+            Self::Other => "other",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for ToolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for ToolKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "read" => Self::Read,
+            "edit" => Self::Edit,
+            "delete" => Self::Delete,
+            "move" => Self::Move,
+            "search" => Self::Search,
+            "execute" => Self::Execute,
+            "think" => Self::Think,
+            "fetch" => Self::Fetch,
+            "switch_mode" => Self::SwitchMode,
+            "other" => Self::Other,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for ToolKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ToolKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// Execution status of a tool call or plan entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum ToolCallStatus {
+    #[default]
+    Pending,
+    InProgress,
+    Completed,
+    Failed,
+    /// A status not yet known to this version of the crate.
+    Unknown(String),
+}
+
+impl ToolCallStatus {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Pending => "pending",
+            Self::InProgress => "in_progress",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Unknown(s) => s.as_str(),
+        }
+    }
+}
+
+impl fmt::Display for ToolCallStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for ToolCallStatus {
+    fn from(s: &str) -> Self {
+        match s {
+            "pending" => Self::Pending,
+            "in_progress" => Self::InProgress,
+            "completed" => Self::Completed,
+            "failed" => Self::Failed,
+            other => Self::Unknown(other.to_string()),
+        }
+    }
+}
+
+impl Serialize for ToolCallStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ToolCallStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(Self::from(s.as_str()))
+    }
+}
+
+/// One step of the agent's plan.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanEntry {
+    pub content: String,
+    /// `high`, `medium` or `low`.
+    pub priority: String,
+    /// Uses the same `pending` / `in_progress` / `completed` strings as tool calls.
+    pub status: ToolCallStatus,
+}
+
+/// A slash command the agent accepts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailableCommand {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_call_lifecycle() {
+        let start: SessionUpdate = serde_json::from_value(json!({
+            "sessionUpdate": "tool_call",
+            "toolCallId": "call_1",
+            "title": "bash",
+            "kind": "execute",
+            "status": "pending",
+            "rawInput": {"command": "ls"}
+        }))
+        .unwrap();
+        let SessionUpdate::ToolCall(call) = start else {
+            panic!("expected tool_call");
+        };
+        assert_eq!(call.kind, ToolKind::Execute);
+        assert_eq!(call.raw_input.unwrap()["command"], "ls");
+
+        let update: SessionUpdate = serde_json::from_value(json!({
+            "sessionUpdate": "tool_call_update",
+            "toolCallId": "call_1",
+            "status": "completed",
+            "content": [
+                {"type": "content", "content": {"type": "text", "text": "Cargo.toml"}},
+                {"type": "diff", "path": "/a.rs", "newText": "fn main() {}"}
+            ]
+        }))
+        .unwrap();
+        let SessionUpdate::ToolCallUpdate(update) = update else {
+            panic!("expected tool_call_update");
+        };
+        assert_eq!(update.status, Some(ToolCallStatus::Completed));
+        assert!(matches!(
+            &update.content.unwrap()[1],
+            ToolCallContent::Diff { old_text: None, .. }
+        ));
+    }
+
+    #[test]
+    fn test_plan_and_unknown_updates() {
+        let plan: SessionUpdate = serde_json::from_value(json!({
+            "sessionUpdate": "plan",
+            "entries": [{"content": "Write tests", "priority": "high", "status": "in_progress"}]
+        }))
+        .unwrap();
+        assert!(matches!(plan, SessionUpdate::Plan { ref entries }
+            if entries[0].status == ToolCallStatus::InProgress));
+
+        let unknown: SessionUpdate =
+            serde_json::from_value(json!({"sessionUpdate": "usage_update", "tokens": 10})).unwrap();
+        assert!(matches!(unknown, SessionUpdate::Unknown));
+    }
+}